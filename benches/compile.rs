@@ -0,0 +1,143 @@
+//! Measures how fast ginto lexes, parses and checks large generated
+//! programs.
+//!
+//! Run with `cargo bench`; `cargo bench -- parse` runs the parsing benches
+//! only, and `cargo bench -- lex` the lexing ones.
+
+use std::{fmt::Write, hint::black_box};
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use ginto_diag::{FileId, SourceManager};
+use ginto_driver::Driver;
+use ginto_syntax::{Lexer, Parser};
+
+/// A program of `count` functions, each calling the one before it.
+fn many_functions(count: usize) -> String {
+    let mut source = String::from("fn f0(x: u64) -> u64:\n    x\n");
+    for i in 1..count {
+        writeln!(
+            source,
+            "\nfn f{i}(x: u64) -> u64:\n    let y = x * {i} + 1\n    if y > 100:\n        f{}(y - 100)\n    else:\n        y",
+            i - 1
+        )
+        .unwrap();
+    }
+    writeln!(source, "\nfn main() -> u64:\n    f{}(1)", count - 1).unwrap();
+    source
+}
+
+/// A program whose `main` returns an expression nested `depth` deep,
+/// which constant folding can evaluate without overflowing.
+fn deep_expression(depth: usize) -> String {
+    let mut expr = String::from("1");
+    for i in 1..=depth {
+        expr = match i % 2 {
+            0 => format!("({i} + {expr})"),
+            _ => format!("({expr} / 2)"),
+        };
+    }
+    format!("fn main() -> u64:\n    {expr}\n")
+}
+
+/// A program of `count` lines binding long strings, most of whose bytes
+/// the lexer skips over inside string literals.
+fn many_strings(count: usize) -> String {
+    let mut source = String::from("fn main() -> u64:\n");
+    for i in 0..count {
+        writeln!(
+            source,
+            "    let s{i} = \"line {i} of a long string literal, with a \\\"quote\\\" and \\t tabs\""
+        )
+        .unwrap();
+    }
+    source.push_str("    0\n");
+    source
+}
+
+fn programs() -> Vec<(&'static str, String)> {
+    vec![
+        ("functions/10k", many_functions(10_000)),
+        ("expression/depth-500", deep_expression(500)),
+    ]
+}
+
+fn add(source: &str) -> (SourceManager, FileId) {
+    let mut sm = SourceManager::new();
+    let file = sm.add_file("bench.ginto".to_string(), source.to_string());
+    (sm, file)
+}
+
+fn lex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex");
+    for (name, source) in programs() {
+        let (_, file) = add(&source);
+        let tokens = Lexer::new(file, &source).lex_all().unwrap().len();
+        group.throughput(Throughput::Elements(tokens as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &source, |b, source| {
+            b.iter(|| Lexer::new(file, black_box(source)).lex_all().unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Lexing in bytes of source per second, which shouldn't depend on how long
+/// the tokens are.
+fn lex_bytes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex-bytes");
+    let programs = [
+        ("functions/10k", many_functions(10_000)),
+        ("strings/10k", many_strings(10_000)),
+    ];
+    for (name, source) in programs {
+        let (_, file) = add(&source);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &source, |b, source| {
+            b.iter(|| Lexer::new(file, black_box(source)).lex_all().unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, source) in programs() {
+        let (_, file) = add(&source);
+        let tokens = Lexer::new(file, &source).lex_all().unwrap();
+        group.throughput(Throughput::Elements(tokens.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &tokens, |b, tokens| {
+            b.iter_batched(
+                || tokens.clone(),
+                |tokens| {
+                    let mut parser = Parser::new(file, tokens);
+                    let program = parser.parse_program();
+                    assert!(parser.errors().is_empty());
+                    program
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Everything `ginto check` does short of linting, through the driver:
+/// lexing, parsing, resolving, type checking and the analyses.
+fn check(c: &mut Criterion) {
+    let mut group = c.benchmark_group("check");
+    group.sample_size(10);
+    for (name, source) in programs() {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &source, |b, source| {
+            b.iter(|| {
+                let (mut sm, file) = add(source);
+                let (compilation, diagnostics) = Driver::new().check(&mut sm, file);
+                assert!(diagnostics.is_empty());
+                compilation.expect("the program has no errors")
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lex, lex_bytes, parse, check);
+criterion_main!(benches);
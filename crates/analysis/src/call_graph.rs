@@ -0,0 +1,216 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use ginto_diag::{FileId, Span};
+use ginto_resolve::{DefId, DefKind, ModuleTree, Resolutions};
+use ginto_syntax::{
+    Expr, ExprKind, Func, ItemKind, Program,
+    visit::{self, Visitor},
+};
+
+/// Functions of a program and the functions each of them references.
+#[derive(Debug, Default, Clone)]
+pub struct CallGraph {
+    funcs: BTreeSet<DefId>,
+    callees: BTreeMap<DefId, BTreeSet<DefId>>,
+    callers: BTreeMap<DefId, BTreeSet<DefId>>,
+}
+
+impl CallGraph {
+    pub fn build(program: &Program, resolutions: &Resolutions) -> Self {
+        let mut graph = Self::default();
+        graph.add_program(program, resolutions);
+        graph
+    }
+
+    /// Builds the graph of every module of `tree`, including calls between
+    /// modules.
+    pub fn build_modules(tree: &ModuleTree, resolutions: &Resolutions) -> Self {
+        let mut graph = Self::default();
+        for (_, module) in tree.modules() {
+            graph.add_program(&module.program, resolutions);
+        }
+        graph
+    }
+
+    fn add_program(&mut self, program: &Program, resolutions: &Resolutions) {
+        for item in &program.items {
+            let ItemKind::Func(func) = &item.inner else {
+                continue;
+            };
+            let Some(def) = resolutions.def_at(program.file_id, func.name.span) else {
+                continue;
+            };
+            self.funcs.insert(def);
+            for callee in func_refs(func, program.file_id, resolutions) {
+                self.callees.entry(def).or_default().insert(callee);
+                self.callers.entry(callee).or_default().insert(def);
+            }
+        }
+    }
+
+    pub fn funcs(&self) -> impl Iterator<Item = DefId> + '_ {
+        self.funcs.iter().copied()
+    }
+
+    pub fn callees(&self, func: DefId) -> impl Iterator<Item = DefId> + '_ {
+        self.callees.get(&func).into_iter().flatten().copied()
+    }
+
+    pub fn callers(&self, func: DefId) -> impl Iterator<Item = DefId> + '_ {
+        self.callers.get(&func).into_iter().flatten().copied()
+    }
+
+    /// Returns every function reachable from `roots`, including the roots.
+    pub fn reachable_from(&self, roots: impl IntoIterator<Item = DefId>) -> BTreeSet<DefId> {
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<DefId> = roots.into_iter().collect();
+        while let Some(func) = stack.pop() {
+            if seen.insert(func) {
+                stack.extend(self.callees(func));
+            }
+        }
+        seen
+    }
+
+    /// The strongly connected components of the graph: groups of functions
+    /// that can all reach each other. A component comes after every
+    /// component it calls into, so leaves come first.
+    pub fn sccs(&self) -> Vec<Vec<DefId>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: 0,
+            indices: BTreeMap::new(),
+            lowlinks: BTreeMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            sccs: Vec::new(),
+        };
+        for func in self.funcs() {
+            if !tarjan.indices.contains_key(&func) {
+                tarjan.connect(func);
+            }
+        }
+        tarjan.sccs
+    }
+
+    /// Whether `func` can call itself, directly or through other functions.
+    pub fn is_recursive(&self, func: DefId) -> bool {
+        self.callees(func)
+            .any(|callee| self.reachable_from([callee]).contains(&func))
+    }
+
+    /// Renders the graph in Graphviz DOT format, labelling each function
+    /// with its name. Recursive functions are drawn with a double border.
+    pub fn to_dot(&self, resolutions: &Resolutions) -> String {
+        let mut out = String::from("digraph calls {\n");
+        for func in self.funcs() {
+            let shape = if self.is_recursive(func) {
+                ", peripheries=2"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                out,
+                "    f{} [label=\"{}\"{}];",
+                func.0,
+                resolutions.def(func).name,
+                shape
+            );
+        }
+        for func in self.funcs() {
+            for callee in self.callees(func) {
+                let _ = writeln!(out, "    f{} -> f{};", func.0, callee.0);
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Tarjan's strongly connected components algorithm.
+struct Tarjan<'a> {
+    graph: &'a CallGraph,
+    index: usize,
+    indices: BTreeMap<DefId, usize>,
+    lowlinks: BTreeMap<DefId, usize>,
+    stack: Vec<DefId>,
+    on_stack: BTreeSet<DefId>,
+    sccs: Vec<Vec<DefId>>,
+}
+
+impl Tarjan<'_> {
+    fn connect(&mut self, func: DefId) {
+        self.indices.insert(func, self.index);
+        self.lowlinks.insert(func, self.index);
+        self.index += 1;
+        self.stack.push(func);
+        self.on_stack.insert(func);
+
+        for callee in self.graph.callees(func) {
+            if !self.indices.contains_key(&callee) {
+                self.connect(callee);
+                let low = self.lowlinks[&func].min(self.lowlinks[&callee]);
+                self.lowlinks.insert(func, low);
+            } else if self.on_stack.contains(&callee) {
+                let low = self.lowlinks[&func].min(self.indices[&callee]);
+                self.lowlinks.insert(func, low);
+            }
+        }
+
+        if self.lowlinks[&func] == self.indices[&func] {
+            let mut scc = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(&member);
+                scc.push(member);
+                if member == func {
+                    break;
+                }
+            }
+            scc.sort();
+            self.sccs.push(scc);
+        }
+    }
+}
+
+/// The functions `func` references, by calling them or as values.
+pub(crate) fn func_refs(
+    func: &Func,
+    file_id: FileId,
+    resolutions: &Resolutions,
+) -> BTreeSet<DefId> {
+    let mut collector = RefCollector {
+        file_id,
+        resolutions,
+        refs: BTreeSet::new(),
+    };
+    collector.visit_func(func);
+    collector.refs
+}
+
+struct RefCollector<'a> {
+    file_id: FileId,
+    resolutions: &'a Resolutions,
+    refs: BTreeSet<DefId>,
+}
+
+impl RefCollector<'_> {
+    fn record(&mut self, span: Span) {
+        if let Some(def) = self.resolutions.resolve(self.file_id, span)
+            && self.resolutions.def(def).kind == DefKind::Func
+        {
+            self.refs.insert(def);
+        }
+    }
+}
+
+impl Visitor for RefCollector<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Var(_) | ExprKind::Path(_) = expr.inner {
+            self.record(expr.span);
+        }
+        visit::walk_expr(self, expr);
+    }
+}
@@ -0,0 +1,567 @@
+use core::fmt;
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span, Spanned};
+use ginto_syntax::{BinOp, Expr, ExprKind, ItemKind, LexerError, Program, Type, UnaryOp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstValue {
+    Unit,
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+}
+
+impl ConstValue {
+    pub fn ty(&self) -> Type {
+        match self {
+            ConstValue::Unit => Type::Unit,
+            ConstValue::Bool(_) => Type::Bool,
+            ConstValue::U64(_) => Type::U64,
+            ConstValue::I64(_) => Type::I64,
+        }
+    }
+
+    /// The value of `self as ty`, wrapping like the runtime conversion.
+    pub fn cast(self, ty: &Type) -> Option<ConstValue> {
+        let bits = match self {
+            ConstValue::U64(v) => v,
+            ConstValue::I64(v) => v as u64,
+            ConstValue::Bool(v) => v as u64,
+            ConstValue::Unit => return (*ty == Type::Unit).then_some(self),
+        };
+        match ty {
+            Type::U64 => Some(ConstValue::U64(bits)),
+            Type::I64 => Some(ConstValue::I64(bits as i64)),
+            Type::Bool => match self {
+                ConstValue::Bool(_) => Some(self),
+                _ => None,
+            },
+            // floats are not folded
+            Type::Unit
+            | Type::F64
+            | Type::Str
+            | Type::Param(_)
+            | Type::SelfType
+            | Type::Fn { .. } => None,
+        }
+    }
+
+    fn into_expr_kind(self) -> ExprKind {
+        match self {
+            ConstValue::Unit => ExprKind::Unit,
+            ConstValue::Bool(v) => ExprKind::Bool(v),
+            ConstValue::U64(v) => ExprKind::Int(v),
+            ConstValue::I64(v) if v >= 0 => ExprKind::Int(v as u64),
+            ConstValue::I64(v) => ExprKind::Unary {
+                op: Spanned::new(UnaryOp::Neg, Span::default()),
+                expr: Box::new(Expr::new(ExprKind::Int(v.unsigned_abs()), Span::default())),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstValue::Unit => write!(f, "()"),
+            ConstValue::Bool(v) => write!(f, "{}", v),
+            ConstValue::U64(v) => write!(f, "{}", v),
+            ConstValue::I64(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstEvalError {
+    Overflow {
+        /// The failing operation as written, e.g. `18446744073709551615 + 1`.
+        expr: String,
+        ty: Type,
+        span: Span,
+        operands: Vec<Spanned<ConstValue>>,
+        file_id: FileId,
+    },
+    /// A `/` or `mod` whose divisor is constant zero. The dividend is only
+    /// known when it is itself constant.
+    DivisionByZero {
+        op: BinOp,
+        dividend: Option<Spanned<ConstValue>>,
+        divisor: Span,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A literal-only `let` initializer whose value does not fit the
+    /// annotated type. `value` is `None` when it does not even fit `i128`.
+    OutOfRange {
+        value: Option<i128>,
+        ty: Spanned<Type>,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A `static_assert` whose condition evaluated to `false`.
+    StaticAssertFailed {
+        message: Option<String>,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A `static_assert` whose condition cannot be evaluated at compile
+    /// time.
+    StaticAssertNotConst { span: Span, file_id: FileId },
+}
+
+fn operand_label(file_id: FileId, operand: Spanned<ConstValue>) -> Label {
+    Label::secondary(file_id, operand.span)
+        .with_message(format!("this evaluates to `{}`", operand.inner))
+}
+
+impl DiagnosticConvertible for ConstEvalError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            ConstEvalError::Overflow {
+                expr,
+                ty,
+                span,
+                operands,
+                file_id,
+            } => Diagnostic::error("this arithmetic operation will overflow")
+                .with_label(Label::primary(file_id, span).with_message(format!(
+                    "attempt to compute `{}`, which would overflow `{}`",
+                    expr, ty
+                )))
+                .with_labels(
+                    operands
+                        .into_iter()
+                        .map(|operand| operand_label(file_id, operand))
+                        .collect(),
+                ),
+
+            ConstEvalError::DivisionByZero {
+                op,
+                dividend,
+                divisor,
+                span,
+                file_id,
+            } => {
+                let dividend_str = dividend
+                    .as_ref()
+                    .map_or("a value".to_string(), |d| format!("`{}`", d.inner));
+                let message = match op {
+                    BinOp::Mod => format!(
+                        "attempt to calculate the remainder of {} with a divisor of zero",
+                        dividend_str
+                    ),
+                    _ => format!("attempt to divide {} by zero", dividend_str),
+                };
+                Diagnostic::error("this operation will panic at runtime")
+                    .with_label(Label::primary(file_id, span).with_message(message))
+                    .with_labels(
+                        dividend
+                            .into_iter()
+                            .map(|dividend| operand_label(file_id, dividend))
+                            .collect(),
+                    )
+                    .with_label(
+                        Label::secondary(file_id, divisor).with_message("this evaluates to `0`"),
+                    )
+                    .with_help(
+                        "dividing by zero aborts the program with a runtime error; \
+                         use a non-zero divisor or check the divisor before dividing",
+                    )
+            }
+
+            ConstEvalError::OutOfRange {
+                value,
+                ty,
+                span,
+                file_id,
+            } => {
+                let (min, max) = match ty.inner {
+                    Type::I64 => (i64::MIN as i128, i64::MAX as i128),
+                    _ => (u64::MIN as i128, u64::MAX as i128),
+                };
+                let value = match value {
+                    Some(value) => format!("`{}`", value),
+                    None => "a value".to_string(),
+                };
+                LexerError::NumberTooLarge { span, file_id }
+                    .into_diagnostic()
+                    .with_label(
+                        Label::secondary(file_id, ty.span)
+                            .with_message(format!("expected `{}` because of this", ty.inner)),
+                    )
+                    .with_note(format!(
+                        "the initializer evaluates to {}, but `{}` ranges from {} to {}",
+                        value, ty.inner, min, max
+                    ))
+            }
+
+            ConstEvalError::StaticAssertFailed {
+                message,
+                span,
+                file_id,
+            } => {
+                let message = match message {
+                    Some(message) => format!("static assertion failed: {}", message),
+                    None => "static assertion failed".to_string(),
+                };
+                Diagnostic::error(message).with_label(
+                    Label::primary(file_id, span).with_message("this evaluates to `false`"),
+                )
+            }
+
+            ConstEvalError::StaticAssertNotConst { span, file_id } => {
+                Diagnostic::error("static assertion condition is not a constant")
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message("this cannot be evaluated at compile time"),
+                    )
+                    .with_note("only literals, operators and casts are evaluated at compile time")
+            }
+        }
+    }
+}
+
+/// Evaluates constant subexpressions, replacing them with literals and
+/// reporting operations that are guaranteed to fail at runtime.
+///
+/// Integer literals are `u64` unless an annotation or a negation says
+/// otherwise, so `-1` and `let x: i64 = 1 - 2` are folded as `i64`.
+pub struct ConstEvaluator {
+    file_id: FileId,
+    return_hint: Option<Type>,
+    errors: Vec<ConstEvalError>,
+}
+
+impl ConstEvaluator {
+    pub fn new(file_id: FileId) -> Self {
+        Self {
+            file_id,
+            return_hint: None,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn errors(&self) -> &[ConstEvalError] {
+        &self.errors
+    }
+
+    pub fn fold_program(&mut self, program: &mut Program) {
+        for func in program.funcs_mut() {
+            self.return_hint = func.ty.as_ref().map(|ty| ty.inner.clone());
+            let hint = self.return_hint.clone();
+            self.fold_expr(&mut func.body, hint.as_ref());
+        }
+        self.return_hint = None;
+        for item in &mut program.items {
+            if let ItemKind::StaticAssert(assert) = &mut item.inner {
+                let span = assert.cond.span;
+                let reported = self.errors.len();
+                let error = match self.fold_expr(&mut assert.cond, None) {
+                    Some(ConstValue::Bool(false)) => ConstEvalError::StaticAssertFailed {
+                        message: assert.message.as_ref().map(|message| message.inner.clone()),
+                        span,
+                        file_id: self.file_id,
+                    },
+                    // an operation in the condition already failed
+                    None if self.errors.len() > reported => continue,
+                    None => ConstEvalError::StaticAssertNotConst {
+                        span,
+                        file_id: self.file_id,
+                    },
+                    Some(_) => continue,
+                };
+                self.errors.push(error);
+            }
+        }
+    }
+
+    /// Folds `expr` in place and returns its value if it is constant. `hint`
+    /// is the type the surrounding context expects for integer literals.
+    pub fn fold_expr(&mut self, expr: &mut Expr, hint: Option<&Type>) -> Option<ConstValue> {
+        let span = expr.span;
+        let value = match &mut expr.inner {
+            ExprKind::Int(v) => match hint {
+                Some(Type::I64) => i64::try_from(*v).ok().map(ConstValue::I64),
+                _ => Some(ConstValue::U64(*v)),
+            },
+            ExprKind::Bool(v) => Some(ConstValue::Bool(*v)),
+            ExprKind::Unit => Some(ConstValue::Unit),
+            // floats are not folded and strings are built at runtime
+            ExprKind::Float(_) | ExprKind::Str(_) => None,
+            ExprKind::Unary { op, expr: operand } => {
+                let hint = match op.inner {
+                    UnaryOp::Neg => Some(hint.unwrap_or(&Type::I64)),
+                    UnaryOp::Not => None,
+                };
+                let value = self.fold_expr(operand, hint)?;
+                self.eval_unary(op.inner, Spanned::new(value, operand.span), span)
+            }
+            ExprKind::Cast { expr: operand, ty } => {
+                let value = self.fold_expr(operand, None)?;
+                value.cast(&ty.inner)
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let operand_hint = if is_arithmetic(op.inner) { hint } else { None };
+                let l = self.fold_expr(lhs, operand_hint);
+                let rhs_hint = l.map(|l| l.ty()).or_else(|| operand_hint.cloned());
+                let r = self.fold_expr(rhs, rhs_hint.as_ref());
+                if matches!(op.inner, BinOp::Div | BinOp::Mod)
+                    && matches!(r, Some(ConstValue::U64(0) | ConstValue::I64(0)))
+                {
+                    self.errors.push(ConstEvalError::DivisionByZero {
+                        op: op.inner,
+                        dividend: l.map(|l| Spanned::new(l, lhs.span)),
+                        divisor: rhs.span,
+                        span,
+                        file_id: self.file_id,
+                    });
+                    return None;
+                }
+                let l = Spanned::new(l?, lhs.span);
+                let r = Spanned::new(r?, rhs.span);
+                self.eval_binary(op.inner, l, r, span)
+            }
+            ExprKind::Let { ty, value, .. } => {
+                let value = value.as_deref_mut()?;
+                if let Some(ty) = ty
+                    && matches!(ty.inner, Type::U64 | Type::I64)
+                    && let Some(exact) = eval_exact(value)
+                    && !fits(exact, &ty.inner)
+                {
+                    self.errors.push(ConstEvalError::OutOfRange {
+                        value: exact,
+                        ty: ty.clone(),
+                        span: value.span,
+                        file_id: self.file_id,
+                    });
+                    return None;
+                }
+                let hint = ty.as_ref().map(|ty| ty.inner.clone());
+                self.fold_expr(value, hint.as_ref());
+                None
+            }
+            ExprKind::Assign { value, .. } => {
+                self.fold_expr(value, None);
+                None
+            }
+            ExprKind::Block { exprs, tail } => {
+                for expr in exprs {
+                    self.fold_expr(expr, None);
+                }
+                if let Some(tail) = tail {
+                    self.fold_expr(tail, hint);
+                }
+                None
+            }
+            // closures are values built at runtime
+            ExprKind::Closure { body, .. } => {
+                self.fold_expr(body, None);
+                None
+            }
+            ExprKind::Call { callee, args } => {
+                self.fold_expr(callee, None);
+                for arg in args {
+                    self.fold_expr(arg, None);
+                }
+                None
+            }
+            ExprKind::MethodCall { receiver, args, .. } => {
+                self.fold_expr(receiver, None);
+                for arg in args {
+                    self.fold_expr(arg, None);
+                }
+                None
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.fold_expr(scrutinee, None);
+                for arm in arms {
+                    self.fold_expr(&mut arm.body, hint);
+                }
+                None
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                self.fold_expr(cond, None);
+                self.fold_expr(then_branch, hint);
+                if let Some(else_branch) = else_branch {
+                    self.fold_expr(else_branch, hint);
+                }
+                None
+            }
+            ExprKind::While { cond, body } => {
+                self.fold_expr(cond, None);
+                self.fold_expr(body, None);
+                None
+            }
+            ExprKind::Return(value) => {
+                if let Some(value) = value {
+                    let hint = self.return_hint.clone();
+                    self.fold_expr(value, hint.as_ref());
+                }
+                None
+            }
+            ExprKind::Var(_) | ExprKind::Path(_) | ExprKind::Error => None,
+        };
+
+        if let Some(value) = value
+            && matches!(
+                expr.inner,
+                ExprKind::Unary { .. } | ExprKind::Binary { .. } | ExprKind::Cast { .. }
+            )
+        {
+            expr.inner = value.into_expr_kind();
+            if let ExprKind::Unary { op, expr: operand } = &mut expr.inner {
+                op.span = span;
+                operand.span = span;
+            }
+        }
+        value
+    }
+
+    fn eval_unary(
+        &mut self,
+        op: UnaryOp,
+        operand: Spanned<ConstValue>,
+        span: Span,
+    ) -> Option<ConstValue> {
+        match (op, operand.inner) {
+            (UnaryOp::Not, ConstValue::Bool(v)) => Some(ConstValue::Bool(!v)),
+            (UnaryOp::Neg, ConstValue::I64(v)) => match v.checked_neg() {
+                Some(v) => Some(ConstValue::I64(v)),
+                None => {
+                    self.errors.push(ConstEvalError::Overflow {
+                        expr: format!("-({})", v),
+                        ty: Type::I64,
+                        span,
+                        operands: vec![operand],
+                        file_id: self.file_id,
+                    });
+                    None
+                }
+            },
+            _ => None,
+        }
+    }
+
+    fn eval_binary(
+        &mut self,
+        op: BinOp,
+        lhs: Spanned<ConstValue>,
+        rhs: Spanned<ConstValue>,
+        span: Span,
+    ) -> Option<ConstValue> {
+        use ConstValue::*;
+
+        let result = match (lhs.inner, rhs.inner) {
+            (U64(l), U64(r)) => match op {
+                BinOp::Add => l.checked_add(r).map(U64),
+                BinOp::Sub => l.checked_sub(r).map(U64),
+                BinOp::Mul => l.checked_mul(r).map(U64),
+                BinOp::Div => l.checked_div(r).map(U64),
+                BinOp::Mod => l.checked_rem(r).map(U64),
+                _ => return compare(op, l, r),
+            },
+            (I64(l), I64(r)) => match op {
+                BinOp::Add => l.checked_add(r).map(I64),
+                BinOp::Sub => l.checked_sub(r).map(I64),
+                BinOp::Mul => l.checked_mul(r).map(I64),
+                BinOp::Div => l.checked_div(r).map(I64),
+                BinOp::Mod => l.checked_rem(r).map(I64),
+                _ => return compare(op, l, r),
+            },
+            (Bool(l), Bool(r)) => {
+                return match op {
+                    BinOp::And => Some(Bool(l && r)),
+                    BinOp::Or => Some(Bool(l || r)),
+                    _ => compare(op, l, r),
+                };
+            }
+            (Unit, Unit) => return compare(op, (), ()),
+            _ => return None,
+        };
+
+        if result.is_none() {
+            self.errors.push(ConstEvalError::Overflow {
+                expr: format!("{} {} {}", lhs.inner, op, rhs.inner),
+                ty: lhs.inner.ty(),
+                span,
+                operands: vec![lhs, rhs],
+                file_id: self.file_id,
+            });
+        }
+        result
+    }
+}
+
+/// Evaluates an integer expression made only of literals with unbounded
+/// precision, ignoring the types of intermediate results. Returns `None` if
+/// the expression is not literal-only and `Some(None)` if the value exceeds
+/// `i128`.
+fn eval_exact(expr: &Expr) -> Option<Option<i128>> {
+    match &expr.inner {
+        ExprKind::Int(v) => Some(Some(*v as i128)),
+        ExprKind::Unary {
+            op: Spanned {
+                inner: UnaryOp::Neg,
+                ..
+            },
+            expr,
+        } => Some(eval_exact(expr)?.and_then(i128::checked_neg)),
+        ExprKind::Binary { op, lhs, rhs } => {
+            let l = eval_exact(lhs)?;
+            let r = eval_exact(rhs)?;
+            let (Some(l), Some(r)) = (l, r) else {
+                return Some(None);
+            };
+            Some(match op.inner {
+                BinOp::Add => l.checked_add(r),
+                BinOp::Sub => l.checked_sub(r),
+                BinOp::Mul => l.checked_mul(r),
+                // division by zero is reported by the regular folding
+                BinOp::Div if r != 0 => l.checked_div(r),
+                BinOp::Mod if r != 0 => l.checked_rem(r),
+                _ => return None,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn fits(value: Option<i128>, ty: &Type) -> bool {
+    match (value, ty) {
+        (Some(v), Type::U64) => u64::try_from(v).is_ok(),
+        (Some(v), Type::I64) => i64::try_from(v).is_ok(),
+        _ => false,
+    }
+}
+
+fn is_arithmetic(op: BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod
+    )
+}
+
+fn compare<T: PartialOrd>(op: BinOp, l: T, r: T) -> Option<ConstValue> {
+    let result = match op {
+        BinOp::Equal => l == r,
+        BinOp::NotEq => l != r,
+        BinOp::Less => l < r,
+        BinOp::Le => l <= r,
+        BinOp::Greater => l > r,
+        BinOp::Ge => l >= r,
+        _ => return None,
+    };
+    Some(ConstValue::Bool(result))
+}
+
+/// Folds every constant subexpression in `program` and returns the
+/// operations that are guaranteed to fail.
+pub fn fold_constants(program: &mut Program) -> Vec<ConstEvalError> {
+    let mut evaluator = ConstEvaluator::new(program.file_id);
+    evaluator.fold_program(program);
+    evaluator.errors
+}
@@ -0,0 +1,85 @@
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span, sym};
+use ginto_resolve::Resolutions;
+use ginto_syntax::{ItemKind, Program};
+
+use crate::CallGraph;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeadCodeWarning {
+    UnusedFunction {
+        name: String,
+        span: Span,
+        file_id: FileId,
+        /// Unused functions that reference this one, if any.
+        dead_callers: Vec<String>,
+    },
+}
+
+impl DiagnosticConvertible for DeadCodeWarning {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            DeadCodeWarning::UnusedFunction {
+                name,
+                span,
+                file_id,
+                dead_callers,
+            } => {
+                let diagnostic = Diagnostic::warning(format!("function `{}` is never used", name))
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message("not reachable from `main` or any `pub` function"),
+                    );
+                if dead_callers.is_empty() {
+                    diagnostic
+                } else {
+                    let callers = dead_callers
+                        .iter()
+                        .map(|name| format!("`{}`", name))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    diagnostic.with_note(format!(
+                        "`{}` is only referenced from functions that are themselves unused: {}",
+                        name, callers
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Warns about private functions that cannot be reached from `main`, from
+/// a `pub` function, or from another module.
+pub fn check_dead_code(program: &Program, resolutions: &Resolutions) -> Vec<DeadCodeWarning> {
+    let graph = CallGraph::build(program, resolutions);
+    let roots = program.items.iter().filter_map(|item| {
+        let ItemKind::Func(func) = &item.inner else {
+            return None;
+        };
+        let def = resolutions.def_at(program.file_id, func.name.span)?;
+        let used_elsewhere = resolutions
+            .uses_of(def)
+            .any(|(file_id, _)| file_id != program.file_id);
+        (func.is_pub || func.name.inner == sym::MAIN || used_elsewhere).then_some(def)
+    });
+    let reachable = graph.reachable_from(roots);
+
+    let mut warnings = Vec::new();
+    for func in graph.funcs() {
+        if reachable.contains(&func) {
+            continue;
+        }
+        let def = resolutions.def(func);
+        let dead_callers = graph
+            .callers(func)
+            .filter(|caller| *caller != func)
+            .map(|caller| resolutions.def(caller).name.to_string())
+            .collect();
+        warnings.push(DeadCodeWarning::UnusedFunction {
+            name: def.name.to_string(),
+            span: def.span,
+            file_id: def.file_id,
+            dead_callers,
+        });
+    }
+    warnings
+}
@@ -0,0 +1,237 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span, Symbol};
+use ginto_resolve::{DefId, Resolutions};
+use ginto_syntax::{BinOp, Expr, ExprKind, Program, StrPart};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitError {
+    UseBeforeInit {
+        name: String,
+        span: Span,
+        decl: Span,
+        /// A path on which the variable is left unassigned, when some other
+        /// path does assign it.
+        skipped_by: Option<Span>,
+        file_id: FileId,
+    },
+}
+
+impl DiagnosticConvertible for InitError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            InitError::UseBeforeInit {
+                name,
+                span,
+                decl,
+                skipped_by,
+                file_id,
+            } => {
+                let state = if skipped_by.is_some() {
+                    "is possibly-uninitialized"
+                } else {
+                    "isn't initialized"
+                };
+                let diagnostic = Diagnostic::error(format!("used binding `{}` {}", name, state))
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message(format!("`{}` used here but it {}", name, state)),
+                    )
+                    .with_label(
+                        Label::secondary(file_id, decl)
+                            .with_message("binding declared here but left uninitialized"),
+                    );
+                match skipped_by {
+                    Some(branch) => {
+                        diagnostic.with_label(Label::secondary(file_id, branch).with_message(
+                            format!("`{}` is not initialized when this path is taken", name),
+                        ))
+                    }
+                    None => diagnostic.with_help(format!(
+                        "assign a value to `{}` on every path before reading it",
+                        name
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// Variables definitely assigned at a program point, or `None` if the point
+/// is unreachable (every variable counts as assigned there).
+type State = Option<BTreeSet<DefId>>;
+
+/// Reports reads of `let` bindings declared without an initializer that are
+/// not assigned on every path leading to the read.
+pub fn check_definite_assignment(program: &Program, resolutions: &Resolutions) -> Vec<InitError> {
+    let mut checker = InitChecker {
+        file_id: program.file_id,
+        resolutions,
+        declared: HashMap::new(),
+        skipped: HashMap::new(),
+        reported: HashSet::new(),
+        errors: Vec::new(),
+    };
+    for func in program.funcs() {
+        checker.expr(&func.body, Some(BTreeSet::new()));
+    }
+    checker.errors
+}
+
+struct InitChecker<'a> {
+    file_id: FileId,
+    resolutions: &'a Resolutions,
+    /// Bindings declared without an initializer and where.
+    declared: HashMap<DefId, Span>,
+    skipped: HashMap<DefId, Span>,
+    reported: HashSet<DefId>,
+    errors: Vec<InitError>,
+}
+
+impl InitChecker<'_> {
+    fn expr(&mut self, expr: &Expr, state: State) -> State {
+        match &expr.inner {
+            ExprKind::Int(_)
+            | ExprKind::Float(_)
+            | ExprKind::Bool(_)
+            | ExprKind::Unit
+            | ExprKind::Path(_)
+            | ExprKind::Error => state,
+            ExprKind::Str(parts) => parts.iter().fold(state, |state, part| match part {
+                StrPart::Lit(_) => state,
+                StrPart::Expr(expr) => self.expr(expr, state),
+            }),
+            ExprKind::Var(name) => {
+                self.read(*name, expr.span, &state);
+                state
+            }
+            ExprKind::Unary { expr, .. } | ExprKind::Cast { expr, .. } => self.expr(expr, state),
+            ExprKind::Binary { op, lhs, rhs } => {
+                let state = self.expr(lhs, state);
+                if matches!(op.inner, BinOp::And | BinOp::Or) {
+                    // the right operand may not be evaluated
+                    let rhs_state = self.expr(rhs, state.clone());
+                    self.merge(vec![(state, rhs.span), (rhs_state, rhs.span)])
+                } else {
+                    self.expr(rhs, state)
+                }
+            }
+            ExprKind::Let { name, value, .. } => match value {
+                Some(value) => self.expr(value, state),
+                None => {
+                    if let Some(def) = self.resolutions.def_at(self.file_id, name.span) {
+                        self.declared.insert(def, name.span);
+                    }
+                    state
+                }
+            },
+            ExprKind::Assign { name, value } => {
+                let state = self.expr(value, state);
+                match (state, self.resolutions.resolve(self.file_id, name.span)) {
+                    (Some(mut assigned), Some(def)) => {
+                        assigned.insert(def);
+                        Some(assigned)
+                    }
+                    (state, _) => state,
+                }
+            }
+            ExprKind::Block { exprs, tail } => exprs
+                .iter()
+                .chain(tail.as_deref())
+                .fold(state, |state, expr| self.expr(expr, state)),
+            ExprKind::Closure { body, .. } => {
+                // captures are read when the closure is created, and the
+                // body runs later without affecting the enclosing function
+                self.expr(body, state.clone());
+                state
+            }
+            ExprKind::Call { callee, args } => {
+                let state = self.expr(callee, state);
+                args.iter().fold(state, |state, arg| self.expr(arg, state))
+            }
+            ExprKind::MethodCall { receiver, args, .. } => {
+                let state = self.expr(receiver, state);
+                args.iter().fold(state, |state, arg| self.expr(arg, state))
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                let state = self.expr(scrutinee, state);
+                let branches = arms
+                    .iter()
+                    .map(|arm| (self.expr(&arm.body, state.clone()), arm.body.span))
+                    .collect();
+                self.merge(branches)
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                let state = self.expr(cond, state);
+                let then_state = self.expr(then_branch, state.clone());
+                let else_state = match else_branch {
+                    Some(else_branch) => (self.expr(else_branch, state), else_branch.span),
+                    // without an `else`, a false condition skips the branch
+                    None => (state, cond.span),
+                };
+                self.merge(vec![(then_state, then_branch.span), else_state])
+            }
+            ExprKind::While { cond, body } => {
+                let state = self.expr(cond, state);
+                let body_state = self.expr(body, state.clone());
+                // a false condition skips the body altogether
+                self.merge(vec![(body_state, body.span), (state, cond.span)])
+            }
+            ExprKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expr(value, state);
+                }
+                None
+            }
+        }
+    }
+
+    fn read(&mut self, name: Symbol, span: Span, state: &State) {
+        let Some(def) = self.resolutions.resolve(self.file_id, span) else {
+            return;
+        };
+        let Some(&decl) = self.declared.get(&def) else {
+            return;
+        };
+        let Some(assigned) = state else {
+            return;
+        };
+        if assigned.contains(&def) || !self.reported.insert(def) {
+            return;
+        }
+        self.errors.push(InitError::UseBeforeInit {
+            name: name.to_string(),
+            span,
+            decl,
+            skipped_by: self.skipped.get(&def).copied(),
+            file_id: self.file_id,
+        });
+    }
+
+    /// Joins the states at the end of alternative paths, remembering for each
+    /// variable assigned on only some of them a path that skips it.
+    fn merge(&mut self, branches: Vec<(State, Span)>) -> State {
+        let reachable: Vec<(&BTreeSet<DefId>, Span)> = branches
+            .iter()
+            .filter_map(|(state, span)| state.as_ref().map(|s| (s, *span)))
+            .collect();
+        let (first, _) = reachable.first()?;
+        let joined: BTreeSet<DefId> = first
+            .iter()
+            .filter(|def| reachable.iter().all(|(s, _)| s.contains(def)))
+            .copied()
+            .collect();
+        for (assigned, _) in &reachable {
+            for def in assigned.difference(&joined) {
+                if let Some((_, span)) = reachable.iter().find(|(s, _)| !s.contains(def)) {
+                    self.skipped.insert(*def, *span);
+                }
+            }
+        }
+        Some(joined)
+    }
+}
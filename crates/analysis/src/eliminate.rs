@@ -0,0 +1,173 @@
+use std::fmt;
+
+use ginto_diag::sym;
+use ginto_resolve::{ModuleTree, Resolutions};
+use ginto_syntax::{Expr, ExprKind, ItemKind};
+
+use crate::{CallGraph, call_graph::func_refs, diverges};
+
+/// What [`eliminate_dead_code`] removed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DeadCodeStats {
+    pub funcs: usize,
+    /// Branches of constant conditions and statements after diverging ones.
+    pub blocks: usize,
+}
+
+impl fmt::Display for DeadCodeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        write!(
+            f,
+            "removed {} unreachable function{} and {} unreachable block{}",
+            self.funcs,
+            plural(self.funcs),
+            self.blocks,
+            plural(self.blocks)
+        )
+    }
+}
+
+/// Removes the functions that cannot be reached from `main` or a `pub`
+/// function of the root module, and the code no path through a function
+/// reaches, so backends only emit what the program can run.
+///
+/// Run after [`fold_constants`](crate::fold_constants), which turns
+/// constant conditions into the literals this pass looks for.
+pub fn eliminate_dead_code(tree: &mut ModuleTree, resolutions: &Resolutions) -> DeadCodeStats {
+    let mut stats = DeadCodeStats::default();
+    // first, as calls in removed blocks no longer keep functions alive
+    for program in tree.programs_mut() {
+        for func in program.funcs_mut() {
+            stats.blocks += prune(&mut func.body);
+        }
+    }
+
+    let graph = CallGraph::build_modules(tree, resolutions);
+    let mut roots = Vec::new();
+    for (id, module) in tree.modules() {
+        let file_id = module.program.file_id;
+        for item in &module.program.items {
+            match &item.inner {
+                ItemKind::Func(func)
+                    if id == tree.root() && (func.is_pub || func.name.inner == sym::MAIN) =>
+                {
+                    roots.extend(resolutions.def_at(file_id, func.name.span));
+                }
+                // methods are not in the call graph, so whatever they
+                // reference is kept
+                ItemKind::Impl(decl) => {
+                    for method in &decl.methods {
+                        roots.extend(func_refs(method, file_id, resolutions));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    let reachable = graph.reachable_from(roots);
+    for program in tree.programs_mut() {
+        let file_id = program.file_id;
+        let before = program.items.len();
+        program.items.retain(|item| match &item.inner {
+            ItemKind::Func(func) => resolutions
+                .def_at(file_id, func.name.span)
+                .is_none_or(|def| reachable.contains(&def)),
+            _ => true,
+        });
+        stats.funcs += before - program.items.len();
+    }
+    stats
+}
+
+/// Removes the unreachable code of `expr` and returns how many blocks of
+/// it were removed.
+fn prune(expr: &mut Expr) -> usize {
+    let mut removed = 0;
+    let mut replacement = None;
+    match &mut expr.inner {
+        ExprKind::Int(_)
+        | ExprKind::Float(_)
+        | ExprKind::Bool(_)
+        | ExprKind::Unit
+        | ExprKind::Str(_)
+        | ExprKind::Var(_)
+        | ExprKind::Path(_)
+        | ExprKind::Error
+        | ExprKind::Let { value: None, .. }
+        | ExprKind::Return(None) => {}
+        ExprKind::Unary { expr, .. } | ExprKind::Cast { expr, .. } => removed += prune(expr),
+        ExprKind::Binary { lhs, rhs, .. } => removed += prune(lhs) + prune(rhs),
+        ExprKind::Let {
+            value: Some(value), ..
+        }
+        | ExprKind::Assign { value, .. }
+        | ExprKind::Return(Some(value)) => removed += prune(value),
+        ExprKind::Closure { body, .. } => removed += prune(body),
+        ExprKind::Call { callee, args } => {
+            removed += prune(callee);
+            removed += args.iter_mut().map(prune).sum::<usize>();
+        }
+        ExprKind::MethodCall { receiver, args, .. } => {
+            removed += prune(receiver);
+            removed += args.iter_mut().map(prune).sum::<usize>();
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            removed += prune(scrutinee);
+            removed += arms
+                .iter_mut()
+                .map(|arm| prune(&mut arm.body))
+                .sum::<usize>();
+        }
+        ExprKind::Block { exprs, tail } => {
+            removed += exprs.iter_mut().map(prune).sum::<usize>();
+            if let Some(tail) = tail {
+                removed += prune(tail);
+            }
+            // the diverging statement stays, so the block still diverges
+            if let Some(at) = exprs.iter().position(diverges)
+                && (at + 1 < exprs.len() || tail.is_some())
+            {
+                exprs.truncate(at + 1);
+                *tail = None;
+                removed += 1;
+            }
+        }
+        ExprKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => {
+            removed += prune(cond) + prune(then_branch);
+            if let Some(else_branch) = else_branch {
+                removed += prune(else_branch);
+            }
+            match cond.inner {
+                ExprKind::Bool(true) => {
+                    removed += usize::from(else_branch.is_some());
+                    let unit = Expr::new(ExprKind::Unit, then_branch.span);
+                    replacement = Some(std::mem::replace(&mut **then_branch, unit));
+                }
+                ExprKind::Bool(false) => {
+                    removed += 1;
+                    replacement = Some(match else_branch.take() {
+                        Some(else_branch) => *else_branch,
+                        None => Expr::new(ExprKind::Unit, expr.span),
+                    });
+                }
+                _ => {}
+            }
+        }
+        ExprKind::While { cond, body } => {
+            removed += prune(cond) + prune(body);
+            if let ExprKind::Bool(false) = cond.inner {
+                removed += 1;
+                replacement = Some(Expr::new(ExprKind::Unit, expr.span));
+            }
+        }
+    }
+    if let Some(replacement) = replacement {
+        *expr = replacement;
+    }
+    removed
+}
@@ -0,0 +1,416 @@
+//! Match exhaustiveness and arm reachability, following the usefulness
+//! algorithm from Maranget's "Warnings for pattern matching".
+//!
+//! A pattern is *useful* with respect to a matrix of earlier patterns when
+//! some value matches it but none of the rows. An arm is unreachable when its
+//! pattern is not useful against the arms above it, and a match is
+//! exhaustive when the wildcard is not useful against all of its arms. The
+//! values that make the wildcard useful are the missing patterns reported to
+//! the user.
+
+use core::fmt;
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span};
+use ginto_syntax::{
+    Expr, ExprKind, MatchArm, PatternKind, Program,
+    visit::{self, Visitor},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchError {
+    NonExhaustive {
+        missing: Vec<String>,
+        span: Span,
+        file_id: FileId,
+    },
+    UnreachableArm {
+        span: Span,
+        /// The arm after which no value is left for this one, and whether it
+        /// covers the unreachable arm on its own.
+        covered_by: Span,
+        covered_alone: bool,
+        file_id: FileId,
+    },
+}
+
+impl DiagnosticConvertible for MatchError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            MatchError::NonExhaustive {
+                missing,
+                span,
+                file_id,
+            } => {
+                let patterns = missing
+                    .iter()
+                    .map(|p| format!("`{}`", p))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let noun = if missing.len() == 1 {
+                    "pattern"
+                } else {
+                    "patterns"
+                };
+                Diagnostic::error(format!("non-exhaustive patterns: {} not covered", patterns))
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message(format!("{} {} not covered", noun, patterns)),
+                    )
+                    .with_help(
+                        "ensure that all possible cases are being handled by adding a match arm \
+                         with a wildcard pattern or the missing patterns",
+                    )
+            }
+
+            MatchError::UnreachableArm {
+                span,
+                covered_by,
+                covered_alone,
+                file_id,
+            } => {
+                let covered = if covered_alone {
+                    "every value it could match is already matched here"
+                } else {
+                    "the arms up to here already match every value it could match"
+                };
+                Diagnostic::warning("unreachable pattern")
+                    .with_label(Label::primary(file_id, span).with_message("unreachable pattern"))
+                    .with_label(Label::secondary(file_id, covered_by).with_message(covered))
+            }
+        }
+    }
+}
+
+/// Checks every `match` in `program` for missing and unreachable arms.
+pub fn check_matches(program: &Program) -> Vec<MatchError> {
+    let mut checker = MatchChecker {
+        file_id: program.file_id,
+        errors: Vec::new(),
+    };
+    checker.visit_program(program);
+    checker.errors
+}
+
+struct MatchChecker {
+    file_id: FileId,
+    errors: Vec<MatchError>,
+}
+
+impl MatchChecker {
+    fn check_match(&mut self, scrutinee: &Expr, arms: &[MatchArm]) {
+        // each alternative of an or-pattern is a row of its own
+        let arm_patterns: Vec<Vec<(Pat, Span)>> = arms
+            .iter()
+            .map(|arm| {
+                visit::pattern_alternatives(&arm.pattern)
+                    .iter()
+                    .map(|alt| (Pat::lower(&alt.inner), alt.span))
+                    .collect()
+            })
+            .collect();
+        let patterns: Vec<Pat> = arm_patterns
+            .iter()
+            .flatten()
+            .map(|(pat, _)| pat.clone())
+            .collect();
+        let Some(ty) = infer_ty(&patterns) else {
+            // patterns of different types are a type error, not our concern
+            return;
+        };
+        let tys = [ty];
+
+        let mut rows: Vec<(Pat, Span)> = Vec::new();
+        for (arm, alternatives) in arms.iter().zip(arm_patterns) {
+            // an alternative can also be covered by earlier ones in its arm
+            let earlier: Vec<Vec<(Pat, Span)>> = (0..alternatives.len())
+                .map(|i| rows.iter().chain(&alternatives[..i]).cloned().collect())
+                .collect();
+            let useful: Vec<bool> = alternatives
+                .iter()
+                .zip(&earlier)
+                .map(|((pat, _), earlier)| {
+                    is_useful(&matrix(earlier), std::slice::from_ref(pat), &tys)
+                })
+                .collect();
+            if useful.iter().all(|useful| !useful) {
+                let pats: Vec<Pat> = alternatives.iter().map(|(pat, _)| pat.clone()).collect();
+                self.report_unreachable(&pats, arm.pattern.span, &rows, &tys);
+            } else {
+                for (i, (pat, span)) in alternatives.iter().enumerate() {
+                    if !useful[i] {
+                        self.report_unreachable(
+                            std::slice::from_ref(pat),
+                            *span,
+                            &earlier[i],
+                            &tys,
+                        );
+                    }
+                }
+            }
+            rows.extend(alternatives);
+        }
+
+        let witnesses = compute_witnesses(&matrix(&rows), &[Pat::Wild], &tys);
+        if !witnesses.is_empty() {
+            let mut missing: Vec<String> = witnesses
+                .into_iter()
+                .map(|mut w| w.remove(0).to_string())
+                .collect();
+            missing.dedup();
+            self.errors.push(MatchError::NonExhaustive {
+                missing,
+                span: scrutinee.span,
+                file_id: self.file_id,
+            });
+        }
+    }
+
+    /// Reports the pattern with alternatives `pats` at `span`, none of which
+    /// is useful against `rows`, pointing at the row that covers it.
+    fn report_unreachable(
+        &mut self,
+        pats: &[Pat],
+        span: Span,
+        rows: &[(Pat, Span)],
+        tys: &[PatTy],
+    ) {
+        let matrix = matrix(rows);
+        let covered = |matrix: &[Row]| {
+            pats.iter()
+                .all(|pat| !is_useful(matrix, std::slice::from_ref(pat), tys))
+        };
+        let covering = (0..rows.len())
+            .find(|&j| covered(&matrix[..=j]))
+            .unwrap_or(0);
+        self.errors.push(MatchError::UnreachableArm {
+            span,
+            covered_by: rows.get(covering).map_or(span, |(_, span)| *span),
+            covered_alone: covered(&matrix[covering..=covering]),
+            file_id: self.file_id,
+        });
+    }
+}
+
+fn matrix(rows: &[(Pat, Span)]) -> Vec<Row> {
+    rows.iter().map(|(pat, _)| vec![pat.clone()]).collect()
+}
+
+impl Visitor for MatchChecker {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Match { scrutinee, arms } = &expr.inner {
+            self.check_match(scrutinee, arms);
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+/// The type of a pattern column, as far as exhaustiveness is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatTy {
+    Bool,
+    Unit,
+    Int,
+    Unknown,
+}
+
+impl PatTy {
+    /// All constructors of the type, or `None` if there are too many to
+    /// enumerate.
+    fn constructors(&self) -> Option<Vec<Ctor>> {
+        match self {
+            PatTy::Bool => Some(vec![Ctor::Bool(false), Ctor::Bool(true)]),
+            PatTy::Unit => Some(vec![Ctor::Unit]),
+            PatTy::Int | PatTy::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Ctor {
+    Bool(bool),
+    Unit,
+    Int(i128),
+}
+
+impl Ctor {
+    fn ty(&self) -> PatTy {
+        match self {
+            Ctor::Bool(_) => PatTy::Bool,
+            Ctor::Unit => PatTy::Unit,
+            Ctor::Int(_) => PatTy::Int,
+        }
+    }
+
+    /// Types of the sub-patterns this constructor carries.
+    fn field_tys(&self) -> Vec<PatTy> {
+        Vec::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Pat {
+    Wild,
+    Ctor(Ctor, Vec<Pat>),
+}
+
+impl Pat {
+    fn lower(pattern: &PatternKind) -> Self {
+        match pattern {
+            PatternKind::Wildcard | PatternKind::Bind(_) => Pat::Wild,
+            PatternKind::Int(v) => Pat::Ctor(Ctor::Int(*v), Vec::new()),
+            PatternKind::Bool(v) => Pat::Ctor(Ctor::Bool(*v), Vec::new()),
+            PatternKind::Unit => Pat::Ctor(Ctor::Unit, Vec::new()),
+            PatternKind::Or(_) => unreachable!("or-patterns are split into rows before lowering"),
+        }
+    }
+
+    fn head_ctor(&self) -> Option<&Ctor> {
+        match self {
+            Pat::Wild => None,
+            Pat::Ctor(ctor, _) => Some(ctor),
+        }
+    }
+}
+
+impl fmt::Display for Pat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pat::Wild => write!(f, "_"),
+            Pat::Ctor(Ctor::Bool(v), _) => write!(f, "{}", v),
+            Pat::Ctor(Ctor::Unit, _) => write!(f, "()"),
+            Pat::Ctor(Ctor::Int(v), _) => write!(f, "{}", v),
+        }
+    }
+}
+
+type Row = Vec<Pat>;
+
+fn infer_ty(patterns: &[Pat]) -> Option<PatTy> {
+    let mut ty = PatTy::Unknown;
+    for ctor in patterns.iter().filter_map(Pat::head_ctor) {
+        match ty {
+            PatTy::Unknown => ty = ctor.ty(),
+            ty if ty != ctor.ty() => return None,
+            _ => {}
+        }
+    }
+    Some(ty)
+}
+
+/// The rows of `matrix` that can match `ctor`, with the head replaced by the
+/// constructor's fields.
+fn specialize(matrix: &[Row], ctor: &Ctor) -> Vec<Row> {
+    matrix
+        .iter()
+        .filter_map(|row| specialize_row(row, ctor))
+        .collect()
+}
+
+fn specialize_row(row: &[Pat], ctor: &Ctor) -> Option<Row> {
+    let (head, rest) = row.split_first()?;
+    let mut fields = match head {
+        Pat::Wild => vec![Pat::Wild; ctor.field_tys().len()],
+        Pat::Ctor(c, fields) if c == ctor => fields.clone(),
+        Pat::Ctor(..) => return None,
+    };
+    fields.extend_from_slice(rest);
+    Some(fields)
+}
+
+/// The rows of `matrix` whose head is a wildcard, without the head.
+fn default_matrix(matrix: &[Row]) -> Vec<Row> {
+    matrix
+        .iter()
+        .filter(|row| matches!(row.first(), Some(Pat::Wild)))
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+fn is_useful(matrix: &[Row], row: &[Pat], tys: &[PatTy]) -> bool {
+    !compute_witnesses(matrix, row, tys).is_empty()
+}
+
+/// Returns the values (as rows of patterns) that `row` matches but no row of
+/// `matrix` does. An empty result means `row` is not useful.
+fn compute_witnesses(matrix: &[Row], row: &[Pat], tys: &[PatTy]) -> Vec<Row> {
+    let Some((head, rest)) = row.split_first() else {
+        return if matrix.is_empty() {
+            vec![Vec::new()]
+        } else {
+            Vec::new()
+        };
+    };
+    let (ty, rest_tys) = tys
+        .split_first()
+        .expect("pattern row and types differ in length");
+
+    match head {
+        Pat::Ctor(ctor, fields) => {
+            let arity = fields.len();
+            let mut inner_row = fields.clone();
+            inner_row.extend_from_slice(rest);
+            let mut inner_tys = ctor.field_tys();
+            inner_tys.extend_from_slice(rest_tys);
+            compute_witnesses(&specialize(matrix, ctor), &inner_row, &inner_tys)
+                .into_iter()
+                .map(|w| rebuild(ctor, arity, w))
+                .collect()
+        }
+        Pat::Wild => {
+            let used: Vec<&Ctor> = matrix.iter().filter_map(|r| r[0].head_ctor()).collect();
+            let all = ty.constructors();
+            let missing: Option<Vec<Ctor>> = all
+                .as_ref()
+                .map(|all| all.iter().filter(|c| !used.contains(c)).cloned().collect());
+
+            match (all, missing) {
+                // every constructor appears: a value is missing only if it is
+                // missing for one of the constructors
+                (Some(all), Some(missing)) if missing.is_empty() => all
+                    .iter()
+                    .flat_map(|ctor| {
+                        let mut inner_row = vec![Pat::Wild; ctor.field_tys().len()];
+                        inner_row.extend_from_slice(rest);
+                        let mut inner_tys = ctor.field_tys();
+                        inner_tys.extend_from_slice(rest_tys);
+                        compute_witnesses(&specialize(matrix, ctor), &inner_row, &inner_tys)
+                            .into_iter()
+                            .map(|w| rebuild(ctor, ctor.field_tys().len(), w))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect(),
+                (_, missing) => {
+                    let witnesses = compute_witnesses(&default_matrix(matrix), rest, rest_tys);
+                    let heads: Vec<Pat> = match missing {
+                        Some(missing) if !used.is_empty() => missing
+                            .into_iter()
+                            .map(|ctor| {
+                                let arity = ctor.field_tys().len();
+                                Pat::Ctor(ctor, vec![Pat::Wild; arity])
+                            })
+                            .collect(),
+                        _ => vec![Pat::Wild],
+                    };
+                    witnesses
+                        .into_iter()
+                        .flat_map(|w| {
+                            heads.iter().map(move |head| {
+                                let mut row = vec![head.clone()];
+                                row.extend(w.iter().cloned());
+                                row
+                            })
+                        })
+                        .collect()
+                }
+            }
+        }
+    }
+}
+
+/// Folds the first `arity` patterns of a witness back under `ctor`.
+fn rebuild(ctor: &Ctor, arity: usize, mut witness: Row) -> Row {
+    let rest = witness.split_off(arity);
+    let mut row = vec![Pat::Ctor(ctor.clone(), witness)];
+    row.extend(rest);
+    row
+}
@@ -0,0 +1,17 @@
+mod call_graph;
+mod const_eval;
+mod dead_code;
+mod definite_assignment;
+mod eliminate;
+mod exhaustiveness;
+mod mutability;
+mod unreachable;
+
+pub use call_graph::*;
+pub use const_eval::*;
+pub use dead_code::*;
+pub use definite_assignment::*;
+pub use eliminate::*;
+pub use exhaustiveness::*;
+pub use mutability::*;
+pub use unreachable::*;
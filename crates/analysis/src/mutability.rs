@@ -0,0 +1,417 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span, Spanned, Symbol};
+use ginto_resolve::{BUILTIN_FILE, DefId, DefKind, Resolutions};
+use ginto_syntax::{Expr, ExprKind, Param, Program, visit};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MutabilityError {
+    /// An assignment to an immutable variable that may already hold a value.
+    AssignTwice {
+        name: String,
+        span: Span,
+        first: Span,
+        decl: Span,
+        /// Whether the binding can be declared `mut`, which match patterns
+        /// cannot.
+        can_be_mut: bool,
+        file_id: FileId,
+    },
+    AssignToParam {
+        name: String,
+        span: Span,
+        decl: Span,
+        file_id: FileId,
+    },
+    /// An assignment to something that is not a variable, like a function.
+    AssignToItem {
+        name: String,
+        kind: DefKind,
+        span: Span,
+        def_span: Span,
+        def_file: FileId,
+        file_id: FileId,
+    },
+    /// An assignment inside a closure to a variable it captured, which
+    /// would only change the closure's copy.
+    AssignToCapture {
+        name: String,
+        span: Span,
+        closure: Span,
+        file_id: FileId,
+    },
+}
+
+impl DiagnosticConvertible for MutabilityError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            MutabilityError::AssignTwice {
+                name,
+                span,
+                first,
+                decl,
+                can_be_mut,
+                file_id,
+            } => {
+                let diagnostic = Diagnostic::error(format!(
+                    "cannot assign twice to immutable variable `{}`",
+                    name
+                ))
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message("cannot assign twice to immutable variable"),
+                )
+                .with_label(
+                    Label::secondary(file_id, first)
+                        .with_message(format!("first assignment to `{}`", name)),
+                );
+                let diagnostic = if decl == first {
+                    diagnostic
+                } else {
+                    diagnostic.with_label(
+                        Label::secondary(file_id, decl)
+                            .with_message(format!("`{}` declared as immutable here", name)),
+                    )
+                };
+                if can_be_mut {
+                    diagnostic.with_help(format!(
+                        "consider making this binding mutable: `mut {}`",
+                        name
+                    ))
+                } else {
+                    diagnostic.with_help(format!(
+                        "consider binding the value to a new variable: `let mut {0} = {0}`",
+                        name
+                    ))
+                }
+            }
+
+            MutabilityError::AssignToParam {
+                name,
+                span,
+                decl,
+                file_id,
+            } => Diagnostic::error(format!("cannot assign to immutable argument `{}`", name))
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message("cannot assign to immutable argument"),
+                )
+                .with_label(
+                    Label::secondary(file_id, decl)
+                        .with_message(format!("`{}` declared as immutable here", name)),
+                )
+                .with_help(format!(
+                    "consider making this binding mutable: `mut {}`",
+                    name
+                )),
+
+            MutabilityError::AssignToItem {
+                name,
+                kind,
+                span,
+                def_span,
+                def_file,
+                file_id,
+            } => {
+                let diagnostic =
+                    Diagnostic::error(format!("cannot assign to {} `{}`", kind.descr(), name))
+                        .with_label(
+                            Label::primary(file_id, span).with_message("cannot assign to this"),
+                        )
+                        .with_note("only variables can be assigned to");
+                // builtins have no source to point at
+                if def_file == BUILTIN_FILE {
+                    return diagnostic;
+                }
+                diagnostic.with_label(
+                    Label::secondary(def_file, def_span)
+                        .with_message(format!("{} defined here", kind.descr())),
+                )
+            }
+
+            MutabilityError::AssignToCapture {
+                name,
+                span,
+                closure,
+                file_id,
+            } => Diagnostic::error(format!(
+                "cannot assign to `{}`, as it is captured by a closure",
+                name
+            ))
+            .with_label(Label::primary(file_id, span).with_message("cannot assign"))
+            .with_label(
+                Label::secondary(file_id, closure)
+                    .with_message(format!("`{}` is captured by value here", name)),
+            )
+            .with_note("closures capture copies of variables, which they cannot change"),
+        }
+    }
+}
+
+/// Checks every assignment in `program` against the mutability of its
+/// target. A variable declared with `let x` and no initializer may be
+/// assigned once; further assignments need `let mut x`.
+pub fn check_mutability(program: &Program, resolutions: &Resolutions) -> Vec<MutabilityError> {
+    let mut checker = MutChecker {
+        file_id: program.file_id,
+        resolutions,
+        bindings: HashMap::new(),
+        closures: Vec::new(),
+        errors: Vec::new(),
+    };
+    for func in program.funcs() {
+        for param in &func.params {
+            match &param.inner {
+                Param::SelfParam => checker.declare(param.span, false, BindingKind::Param),
+                Param::Named { name, mutable, .. } => {
+                    checker.declare(name.span, *mutable, BindingKind::Param)
+                }
+            }
+        }
+        checker.expr(&func.body, Some(BTreeMap::new()));
+    }
+    checker.errors
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BindingKind {
+    Param,
+    /// A `let` with an initializer.
+    Initialized,
+    Pattern,
+    /// A `let` without an initializer.
+    Deferred,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Binding {
+    mutable: bool,
+    decl: Span,
+    kind: BindingKind,
+}
+
+/// The immutable deferred variables that may have been assigned, with their
+/// first assignment. `None` when the current point is unreachable.
+type State = Option<BTreeMap<DefId, Span>>;
+
+struct MutChecker<'a> {
+    file_id: FileId,
+    resolutions: &'a Resolutions,
+    bindings: HashMap<DefId, Binding>,
+    /// The closures around the current point, innermost last, with their
+    /// spans.
+    closures: Vec<(DefId, Span)>,
+    errors: Vec<MutabilityError>,
+}
+
+impl MutChecker<'_> {
+    fn declare(&mut self, decl: Span, mutable: bool, kind: BindingKind) {
+        if let Some(def) = self.resolutions.def_at(self.file_id, decl) {
+            self.bindings.insert(
+                def,
+                Binding {
+                    mutable,
+                    decl,
+                    kind,
+                },
+            );
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, state: State) -> State {
+        match &expr.inner {
+            ExprKind::Int(_)
+            | ExprKind::Float(_)
+            | ExprKind::Bool(_)
+            | ExprKind::Unit
+            | ExprKind::Str(_)
+            | ExprKind::Var(_)
+            | ExprKind::Path(_)
+            | ExprKind::Error => state,
+            ExprKind::Unary { expr, .. } | ExprKind::Cast { expr, .. } => self.expr(expr, state),
+            ExprKind::Binary { lhs, rhs, .. } => {
+                let state = self.expr(lhs, state);
+                self.expr(rhs, state)
+            }
+            ExprKind::Let {
+                name,
+                mutable,
+                value,
+                ..
+            } => {
+                let state = match value {
+                    Some(value) => self.expr(value, state),
+                    None => state,
+                };
+                let kind = if value.is_some() {
+                    BindingKind::Initialized
+                } else {
+                    BindingKind::Deferred
+                };
+                self.declare(name.span, *mutable, kind);
+                state
+            }
+            ExprKind::Assign { name, value } => {
+                let state = self.expr(value, state);
+                self.assign(name, expr.span, state)
+            }
+            ExprKind::Block { exprs, tail } => exprs
+                .iter()
+                .chain(tail.as_deref())
+                .fold(state, |state, expr| self.expr(expr, state)),
+            ExprKind::Closure { params, body } => {
+                for param in params {
+                    if let Param::Named { name, mutable, .. } = &param.inner {
+                        self.declare(name.span, *mutable, BindingKind::Param);
+                    }
+                }
+                if let Some(def) = self.resolutions.def_at(self.file_id, expr.span) {
+                    self.closures.push((def, expr.span));
+                    self.expr(body, Some(BTreeMap::new()));
+                    self.closures.pop();
+                }
+                state
+            }
+            ExprKind::Call { callee, args } => {
+                let state = self.expr(callee, state);
+                args.iter().fold(state, |state, arg| self.expr(arg, state))
+            }
+            ExprKind::MethodCall { receiver, args, .. } => {
+                let state = self.expr(receiver, state);
+                args.iter().fold(state, |state, arg| self.expr(arg, state))
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                let state = self.expr(scrutinee, state);
+                let branches = arms
+                    .iter()
+                    .map(|arm| {
+                        for name in visit::pattern_bindings(&arm.pattern) {
+                            self.declare(name.span, false, BindingKind::Pattern);
+                        }
+                        self.expr(&arm.body, state.clone())
+                    })
+                    .collect();
+                merge(branches)
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                let state = self.expr(cond, state);
+                let then_state = self.expr(then_branch, state.clone());
+                let else_state = match else_branch {
+                    Some(else_branch) => self.expr(else_branch, state),
+                    None => state,
+                };
+                merge(vec![then_state, else_state])
+            }
+            ExprKind::While { cond, body } => {
+                let state = self.expr(cond, state);
+                let once = self.expr(body, state.clone());
+                // the next iteration sees what the previous one assigned,
+                // and reports the same errors again
+                let reported = self.errors.len();
+                let again = self.expr(cond, merge(vec![state.clone(), once]));
+                let again = self.expr(body, again);
+                for error in self.errors.split_off(reported) {
+                    if !self.errors.contains(&error) {
+                        self.errors.push(error);
+                    }
+                }
+                merge(vec![state, again])
+            }
+            ExprKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expr(value, state);
+                }
+                None
+            }
+        }
+    }
+
+    fn assign(&mut self, name: &Spanned<Symbol>, span: Span, state: State) -> State {
+        let Some(def) = self.resolutions.resolve(self.file_id, name.span) else {
+            return state;
+        };
+        if let Some(&(_, closure)) = self
+            .closures
+            .iter()
+            .rev()
+            .find(|(closure, _)| self.resolutions.captures(*closure).contains(&def))
+        {
+            self.errors.push(MutabilityError::AssignToCapture {
+                name: name.inner.to_string(),
+                span,
+                closure,
+                file_id: self.file_id,
+            });
+            return state;
+        }
+        let target = self.resolutions.def(def);
+        let Some(&binding) = self.bindings.get(&def) else {
+            if matches!(
+                target.kind,
+                DefKind::Func | DefKind::Mod | DefKind::Trait | DefKind::Builtin
+            ) {
+                self.errors.push(MutabilityError::AssignToItem {
+                    name: name.inner.to_string(),
+                    kind: target.kind,
+                    span: name.span,
+                    def_span: target.span,
+                    def_file: target.file_id,
+                    file_id: self.file_id,
+                });
+            }
+            return state;
+        };
+        if binding.mutable {
+            return state;
+        }
+        let error = match binding.kind {
+            BindingKind::Param => MutabilityError::AssignToParam {
+                name: name.inner.to_string(),
+                span,
+                decl: binding.decl,
+                file_id: self.file_id,
+            },
+            BindingKind::Initialized | BindingKind::Pattern => MutabilityError::AssignTwice {
+                name: name.inner.to_string(),
+                span,
+                first: binding.decl,
+                decl: binding.decl,
+                can_be_mut: binding.kind == BindingKind::Initialized,
+                file_id: self.file_id,
+            },
+            BindingKind::Deferred => {
+                let mut assigned = state?;
+                let Some(&first) = assigned.get(&def) else {
+                    assigned.insert(def, span);
+                    return Some(assigned);
+                };
+                self.errors.push(MutabilityError::AssignTwice {
+                    name: name.inner.to_string(),
+                    span,
+                    first,
+                    decl: binding.decl,
+                    can_be_mut: true,
+                    file_id: self.file_id,
+                });
+                return Some(assigned);
+            }
+        };
+        self.errors.push(error);
+        state
+    }
+}
+
+/// Joins alternative paths: a variable may be assigned after them if it may
+/// be assigned on any reachable one.
+fn merge(branches: Vec<State>) -> State {
+    branches.into_iter().flatten().reduce(|mut joined, state| {
+        for (def, span) in state {
+            joined.entry(def).or_insert(span);
+        }
+        joined
+    })
+}
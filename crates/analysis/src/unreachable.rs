@@ -0,0 +1,106 @@
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span};
+use ginto_syntax::{
+    Expr, ExprKind, Program,
+    visit::{self, Visitor},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnreachableWarning {
+    UnreachableCode {
+        /// Everything from the first dead statement to the end of the block.
+        span: Span,
+        diverging: Span,
+        file_id: FileId,
+    },
+}
+
+impl DiagnosticConvertible for UnreachableWarning {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            UnreachableWarning::UnreachableCode {
+                span,
+                diverging,
+                file_id,
+            } => Diagnostic::warning("unreachable code")
+                .with_label(Label::primary(file_id, span).with_message("unreachable code"))
+                .with_label(
+                    Label::secondary(file_id, diverging)
+                        .with_message("any code following this expression is unreachable"),
+                ),
+        }
+    }
+}
+
+/// Whether evaluating `expr` never completes normally, e.g. because every
+/// path through it hits a `return`.
+pub fn diverges(expr: &Expr) -> bool {
+    match &expr.inner {
+        ExprKind::Return(_) => true,
+        ExprKind::Int(_)
+        | ExprKind::Float(_)
+        | ExprKind::Bool(_)
+        | ExprKind::Unit
+        | ExprKind::Str(_)
+        | ExprKind::Var(_)
+        | ExprKind::Path(_)
+        | ExprKind::Error
+        | ExprKind::Closure { .. } => false,
+        ExprKind::Unary { expr, .. } | ExprKind::Cast { expr, .. } => diverges(expr),
+        ExprKind::Binary { lhs, rhs, .. } => diverges(lhs) || diverges(rhs),
+        ExprKind::Let { value, .. } => value.as_deref().is_some_and(diverges),
+        ExprKind::Assign { value, .. } => diverges(value),
+        ExprKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => {
+            diverges(cond)
+                || (diverges(then_branch) && else_branch.as_deref().is_some_and(diverges))
+        }
+        // the body may not run at all
+        ExprKind::While { cond, .. } => diverges(cond),
+        ExprKind::Block { exprs, tail } => {
+            exprs.iter().any(diverges) || tail.as_deref().is_some_and(diverges)
+        }
+        ExprKind::Call { callee, args } => diverges(callee) || args.iter().any(diverges),
+        ExprKind::MethodCall { receiver, args, .. } => {
+            diverges(receiver) || args.iter().any(diverges)
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            diverges(scrutinee) || (!arms.is_empty() && arms.iter().all(|arm| diverges(&arm.body)))
+        }
+    }
+}
+
+/// Warns about statements that follow a diverging statement in a block.
+pub fn check_unreachable(program: &Program) -> Vec<UnreachableWarning> {
+    let mut checker = UnreachableChecker {
+        file_id: program.file_id,
+        warnings: Vec::new(),
+    };
+    checker.visit_program(program);
+    checker.warnings
+}
+
+struct UnreachableChecker {
+    file_id: FileId,
+    warnings: Vec<UnreachableWarning>,
+}
+
+impl Visitor for UnreachableChecker {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Block { exprs, tail } = &expr.inner {
+            let stmts: Vec<&Expr> = exprs.iter().chain(tail.as_deref()).collect();
+            if let Some(i) = stmts.iter().position(|stmt| diverges(stmt))
+                && let (Some(first), Some(last)) = (stmts.get(i + 1), stmts.last())
+            {
+                self.warnings.push(UnreachableWarning::UnreachableCode {
+                    span: first.span.merge(last.span),
+                    diverging: stmts[i].span,
+                    file_id: self.file_id,
+                });
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use common::{check, check_files};
+use ginto_analysis::CallGraph;
+use ginto_driver::Compilation;
+use ginto_resolve::DefId;
+
+struct Graph {
+    graph: CallGraph,
+    program: Compilation,
+}
+
+impl Graph {
+    fn of(source: &str) -> Self {
+        let program = check(source);
+        let root = program.tree.module(program.tree.root());
+        let graph = CallGraph::build(&root.program, &program.resolutions);
+        Self { graph, program }
+    }
+
+    fn name(&self, func: DefId) -> String {
+        self.program.resolutions.def(func).name.to_string()
+    }
+
+    fn names(&self, funcs: impl IntoIterator<Item = DefId>) -> Vec<String> {
+        let mut names: Vec<_> = funcs.into_iter().map(|func| self.name(func)).collect();
+        names.sort();
+        names
+    }
+
+    fn func(&self, name: &str) -> DefId {
+        self.graph
+            .funcs()
+            .find(|&func| self.name(func) == name)
+            .unwrap_or_else(|| panic!("no function `{name}`"))
+    }
+
+    fn callees(&self, name: &str) -> Vec<String> {
+        self.names(self.graph.callees(self.func(name)))
+    }
+
+    fn callers(&self, name: &str) -> Vec<String> {
+        self.names(self.graph.callers(self.func(name)))
+    }
+}
+
+const PROGRAM: &str = "\
+fn main() -> u64:
+    even(10) as u64 + helper()
+
+fn helper() -> u64:
+    leaf() + leaf()
+
+fn leaf() -> u64:
+    1
+
+fn even(n: u64) -> bool:
+    if n = 0: true else: odd(n - 1)
+
+fn odd(n: u64) -> bool:
+    if n = 0: false else: even(n - 1)
+
+fn fact(n: u64) -> u64:
+    if n = 0: 1 else: n * fact(n - 1)
+
+fn unused() -> u64:
+    leaf()
+";
+
+#[test]
+fn every_function_is_a_node() {
+    let graph = Graph::of(PROGRAM);
+    assert_eq!(
+        graph.names(graph.graph.funcs()),
+        ["even", "fact", "helper", "leaf", "main", "odd", "unused"]
+    );
+}
+
+#[test]
+fn edges_go_from_callers_to_callees_once() {
+    let graph = Graph::of(PROGRAM);
+    assert_eq!(graph.callees("main"), ["even", "helper"]);
+    assert_eq!(graph.callees("helper"), ["leaf"]);
+    assert!(graph.callees("leaf").is_empty());
+    assert_eq!(graph.callers("leaf"), ["helper", "unused"]);
+    assert!(graph.callers("main").is_empty());
+}
+
+#[test]
+fn functions_used_as_values_are_referenced() {
+    let graph = Graph::of(
+        "fn apply(f: fn(u64) -> u64) -> u64:\n    f(1)\n\nfn inc(n: u64) -> u64:\n    n + 1\n\nfn main() -> u64:\n    apply(inc)\n",
+    );
+    assert_eq!(graph.callees("main"), ["apply", "inc"]);
+    // calling a parameter is not an edge
+    assert!(graph.callees("apply").is_empty());
+}
+
+#[test]
+fn reachable_functions_include_the_roots() {
+    let graph = Graph::of(PROGRAM);
+    let main = graph.func("main");
+    assert_eq!(
+        graph.names(graph.graph.reachable_from([main])),
+        ["even", "helper", "leaf", "main", "odd"]
+    );
+    let leaf = graph.func("leaf");
+    assert_eq!(graph.names(graph.graph.reachable_from([leaf])), ["leaf"]);
+}
+
+#[test]
+fn recursion_directly_or_through_others() {
+    let graph = Graph::of(PROGRAM);
+    let recursive: Vec<_> = graph
+        .graph
+        .funcs()
+        .filter(|&func| graph.graph.is_recursive(func))
+        .collect();
+    assert_eq!(graph.names(recursive), ["even", "fact", "odd"]);
+}
+
+#[test]
+fn components_come_after_those_they_call() {
+    let graph = Graph::of(PROGRAM);
+    let sccs: Vec<Vec<String>> = graph
+        .graph
+        .sccs()
+        .into_iter()
+        .map(|scc| graph.names(scc))
+        .collect();
+    assert_eq!(sccs.len(), 6);
+    assert!(sccs.contains(&vec!["even".to_string(), "odd".to_string()]));
+    let position = |name: &str| {
+        sccs.iter()
+            .position(|scc| scc.iter().any(|func| func == name))
+            .unwrap()
+    };
+    assert!(position("leaf") < position("helper"));
+    assert!(position("helper") < position("main"));
+    assert!(position("even") < position("main"));
+    assert!(position("leaf") < position("unused"));
+}
+
+#[test]
+fn graphs_span_every_module() {
+    let program = check_files(
+        "mod math\nuse math::double\n\nfn main() -> u64:\n    double(2)\n",
+        &[(
+            "math.ginto",
+            "pub fn double(n: u64) -> u64:\n    add(n, n)\n\nfn add(a: u64, b: u64) -> u64:\n    a + b\n",
+        )],
+    );
+    let graph = CallGraph::build_modules(&program.tree, &program.resolutions);
+    let graph = Graph { graph, program };
+    assert_eq!(graph.callees("main"), ["double"]);
+    assert_eq!(graph.callees("double"), ["add"]);
+    let main = graph.func("main");
+    assert_eq!(graph.graph.reachable_from([main]).len(), 3);
+}
+
+#[test]
+fn graphs_render_to_dot() {
+    let graph = Graph::of(
+        "fn main() -> u64:\n    f(3)\n\nfn f(n: u64) -> u64:\n    if n = 0: 0 else: f(n - 1)\n",
+    );
+    let (main, f) = (graph.func("main").0, graph.func("f").0);
+    assert_eq!(
+        graph.graph.to_dot(&graph.program.resolutions),
+        format!(
+            "digraph calls {{\n    f{main} [label=\"main\"];\n    f{f} [label=\"f\", peripheries=2];\n    f{main} -> f{f};\n    f{f} -> f{f};\n}}\n"
+        )
+    );
+}
@@ -0,0 +1,42 @@
+//! What the tests of the analyses share: parsing programs and checking
+//! their names and types through the driver, as `ginto` does, before the
+//! analysis under test runs.
+
+// each test uses some of the helpers
+#![allow(dead_code)]
+
+use ginto_analysis::{ConstEvalError, fold_constants};
+use ginto_diag::FileId;
+use ginto_driver::Compilation;
+use ginto_syntax::{Lexer, Parser, Program, pretty_print};
+use ginto_test_support::check_with;
+
+/// The program `source`, which must parse.
+pub fn parse(source: &str) -> Program {
+    let tokens = Lexer::new(FileId(0), source).lex_all().unwrap();
+    let mut parser = Parser::new(FileId(0), tokens);
+    let program = parser.parse_program();
+    assert!(parser.errors().is_empty(), "{:#?}", parser.errors());
+    program
+}
+
+/// `source` with its constants folded, printed back, and the operations
+/// that are guaranteed to fail.
+pub fn fold(source: &str) -> (String, Vec<ConstEvalError>) {
+    let mut program = parse(source);
+    let errors = fold_constants(&mut program);
+    (pretty_print(&program), errors)
+}
+
+/// The program whose root file holds `source`, with its names resolved and
+/// its types checked but none of the analyses run. Panics with the
+/// diagnostics of a program with errors.
+pub fn check(source: &str) -> Compilation {
+    check_files(source, &[])
+}
+
+/// The program whose root file holds `root`, with the modules of `files`,
+/// named after their path, checked as `check` does.
+pub fn check_files(root: &str, files: &[(&str, &str)]) -> Compilation {
+    check_with("main.ginto", root, files, |driver| driver.lints_only()).0
+}
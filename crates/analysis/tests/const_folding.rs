@@ -0,0 +1,167 @@
+mod common;
+
+use common::fold;
+use ginto_analysis::{ConstEvalError, ConstEvaluator, ConstValue};
+use ginto_diag::{DiagnosticConvertible, FileId, Severity};
+use ginto_syntax::{ExprKind, ItemKind, Type};
+use ginto_test_support::labels;
+
+/// The body of `f`, returning `ty`, with its constants folded.
+fn fold_body(ty: &str, body: &str) -> String {
+    let (folded, errors) = fold(&format!("fn f() -> {ty}:\n    {body}\n"));
+    assert!(errors.is_empty(), "{errors:#?}");
+    folded
+        .strip_prefix(&format!("fn f() -> {ty}:\n    "))
+        .unwrap()
+        .trim_end()
+        .to_string()
+}
+
+#[test]
+fn folds_arithmetic_on_literals() {
+    assert_eq!(fold_body("u64", "2 * 3 + 1"), "7");
+    assert_eq!(fold_body("u64", "(10 - 4) / 2 mod 2"), "1");
+    assert_eq!(fold_body("i64", "1 - 2"), "-1");
+    assert_eq!(fold_body("u64", "-(4) as u64 + 3"), "18446744073709551615");
+}
+
+#[test]
+fn folds_comparisons_and_logic() {
+    assert_eq!(fold_body("bool", "1 < 2 && not false"), "true");
+    assert_eq!(fold_body("bool", "3 = 4 || () = ()"), "true");
+    assert_eq!(fold_body("bool", "-1 <> -1"), "false");
+}
+
+#[test]
+fn folds_casts_like_the_runtime() {
+    assert_eq!(fold_body("i64", "18446744073709551615 as i64"), "-1");
+    assert_eq!(fold_body("u64", "true as u64 + 1"), "2");
+}
+
+#[test]
+fn annotations_and_return_types_pick_the_type_of_literals() {
+    let (folded, errors) = fold("fn f() -> i64:\n    let a: i64 = 2 - 5\n    return 0 - 1\n");
+    assert!(errors.is_empty(), "{errors:#?}");
+    assert_eq!(
+        folded,
+        "fn f() -> i64:\n    let a: i64 = -3\n    return -1\n"
+    );
+}
+
+#[test]
+fn leaves_what_depends_on_variables() {
+    assert_eq!(fold_body("u64", "x + 2 * 3"), "x + 6");
+    assert_eq!(fold_body("u64", "g(1 + 1)"), "g(2)");
+}
+
+#[test]
+fn folds_inside_every_expression() {
+    let (folded, errors) = fold(
+        "fn f(x: u64) -> u64:
+    while x < 2 + 2:
+        x = x + 1 * 1
+    if 1 = 1: 2 + 2 else: 3
+",
+    );
+    assert!(errors.is_empty(), "{errors:#?}");
+    assert_eq!(
+        folded,
+        "fn f(x: u64) -> u64:
+    while x < 4:
+        x = x + 1
+    if true: 4 else: 3
+"
+    );
+}
+
+#[test]
+fn returns_the_value_of_constant_expressions() {
+    let mut program = common::parse("fn f() -> u64:\n    6 * 7\n");
+    let ItemKind::Func(func) = &mut program.items[0].inner else {
+        panic!("expected a function");
+    };
+    let mut evaluator = ConstEvaluator::new(FileId(0));
+    let value = evaluator.fold_expr(&mut func.body, Some(&Type::U64));
+    assert_eq!(value, None, "blocks are not constants themselves");
+    let ExprKind::Block {
+        tail: Some(tail), ..
+    } = &mut func.body.inner
+    else {
+        panic!("expected a block with a tail");
+    };
+    assert!(matches!(tail.inner, ExprKind::Int(42)));
+    assert_eq!(
+        evaluator.fold_expr(tail, Some(&Type::I64)),
+        Some(ConstValue::I64(42))
+    );
+    assert!(evaluator.errors().is_empty());
+}
+
+#[test]
+fn overflowing_operations_are_denied() {
+    let source = "fn f() -> u64:\n    18446744073709551615 + 1\n";
+    let (_, errors) = fold(source);
+    let [error @ ConstEvalError::Overflow { .. }] = errors.as_slice() else {
+        panic!("expected an overflow: {errors:#?}");
+    };
+    let diagnostic = error.clone().into_diagnostic();
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(
+        diagnostic.message,
+        "this arithmetic operation will overflow"
+    );
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            (
+                "18446744073709551615 + 1",
+                "attempt to compute `18446744073709551615 + 1`, which would overflow `u64`"
+                    .to_string()
+            ),
+            (
+                "18446744073709551615",
+                "this evaluates to `18446744073709551615`".to_string()
+            ),
+            ("1", "this evaluates to `1`".to_string()),
+        ]
+    );
+    // the level, and the note saying where it comes from, are the lint's
+    assert!(diagnostic.notes.is_empty());
+}
+
+#[test]
+fn overflow_is_found_in_every_type_and_operation() {
+    let overflows = |ty: &str, body: &str| {
+        let (_, errors) = fold(&format!("fn f() -> {ty}:\n    {body}\n"));
+        errors
+            .into_iter()
+            .map(|error| match error {
+                ConstEvalError::Overflow { expr, ty, .. } => format!("{expr}: {ty}"),
+                error => panic!("expected an overflow: {error:?}"),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(overflows("u64", "0 - 1"), ["0 - 1: u64"]);
+    assert_eq!(
+        overflows("u64", "4294967296 * 4294967296"),
+        ["4294967296 * 4294967296: u64"]
+    );
+    assert_eq!(
+        overflows("i64", "9223372036854775807 + 1"),
+        ["9223372036854775807 + 1: i64"]
+    );
+    assert_eq!(
+        overflows("i64", "-9223372036854775807 - 2"),
+        ["-9223372036854775807 - 2: i64"]
+    );
+    assert_eq!(
+        overflows("i64", "(-9223372036854775807 - 1) / -1"),
+        ["-9223372036854775808 / -1: i64"]
+    );
+}
+
+#[test]
+fn operands_that_overflow_are_reported_once() {
+    let (_, errors) = fold("fn f() -> u64:\n    (0 - 1) * 2 + (0 - 2)\n");
+    assert_eq!(errors.len(), 2, "{errors:#?}");
+}
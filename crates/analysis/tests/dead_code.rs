@@ -0,0 +1,145 @@
+mod common;
+
+use common::{check, check_files};
+use ginto_analysis::{DeadCodeWarning, check_dead_code};
+use ginto_diag::{DiagnosticConvertible, Severity};
+use ginto_test_support::text;
+
+/// The unused functions of the root module of `source`, with the unused
+/// functions referencing each of them.
+fn unused(source: &str) -> Vec<(String, Vec<String>)> {
+    let program = check(source);
+    let root = &program.tree.module(program.tree.root()).program;
+    check_dead_code(root, &program.resolutions)
+        .into_iter()
+        .map(|warning| {
+            let DeadCodeWarning::UnusedFunction {
+                name,
+                span,
+                dead_callers,
+                ..
+            } = warning;
+            assert_eq!(text(source, span), name);
+            (name, dead_callers)
+        })
+        .collect()
+}
+
+#[test]
+fn functions_called_from_main_are_used() {
+    let source = "fn main() -> u64:
+    helper(1)
+
+fn helper(n: u64) -> u64:
+    deeper(n)
+
+fn deeper(n: u64) -> u64:
+    n
+";
+    assert!(unused(source).is_empty());
+}
+
+#[test]
+fn functions_nothing_calls_are_unused() {
+    let source = "fn main():
+    ()
+
+fn helper() -> u64:
+    1
+";
+    assert_eq!(unused(source), [("helper".to_string(), vec![])]);
+}
+
+#[test]
+fn functions_only_dead_code_calls_are_unused_and_say_so() {
+    let source = "fn main():
+    ()
+
+fn outer() -> u64:
+    inner()
+
+fn inner() -> u64:
+    1
+";
+    assert_eq!(
+        unused(source),
+        [
+            ("outer".to_string(), vec![]),
+            ("inner".to_string(), vec!["outer".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn recursion_does_not_keep_a_function_alive() {
+    let source = "fn main():
+    ()
+
+fn countdown(n: u64) -> u64:
+    if n = 0: 0 else: countdown(n - 1)
+";
+    assert_eq!(unused(source), [("countdown".to_string(), vec![])]);
+}
+
+#[test]
+fn public_functions_are_roots() {
+    let source = "pub fn api() -> u64:
+    helper()
+
+fn helper() -> u64:
+    1
+";
+    assert!(unused(source).is_empty());
+}
+
+#[test]
+fn functions_used_as_values_are_used() {
+    let source = "fn main() -> u64:
+    let f = helper
+    f()
+
+fn helper() -> u64:
+    1
+";
+    assert!(unused(source).is_empty());
+}
+
+#[test]
+fn functions_used_by_other_modules_are_used() {
+    let program = check_files(
+        "mod math\n\nfn main() -> u64:\n    math::double(2)\n",
+        &[(
+            "math.ginto",
+            "pub fn double(n: u64) -> u64:\n    twice(n)\n\nfn twice(n: u64) -> u64:\n    n * 2\n\nfn spare() -> u64:\n    0\n",
+        )],
+    );
+    let names: Vec<_> = program
+        .tree
+        .modules()
+        .flat_map(|(_, module)| check_dead_code(&module.program, &program.resolutions))
+        .map(|DeadCodeWarning::UnusedFunction { name, .. }| name)
+        .collect();
+    assert_eq!(names, ["spare"]);
+}
+
+#[test]
+fn unused_functions_are_warnings_on_their_name() {
+    let source = "fn main():\n    ()\n\nfn outer():\n    inner()\n\nfn inner():\n    ()\n";
+    let program = check(source);
+    let root = &program.tree.module(program.tree.root()).program;
+    let diagnostics: Vec<_> = check_dead_code(root, &program.resolutions)
+        .into_iter()
+        .map(DiagnosticConvertible::into_diagnostic)
+        .collect();
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(diagnostics[0].message, "function `outer` is never used");
+    assert_eq!(
+        diagnostics[0].labels[0].message.as_deref(),
+        Some("not reachable from `main` or any `pub` function")
+    );
+    assert!(diagnostics[0].notes.is_empty());
+    assert_eq!(
+        diagnostics[1].notes,
+        ["`inner` is only referenced from functions that are themselves unused: `outer`"]
+    );
+}
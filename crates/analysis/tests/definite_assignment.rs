@@ -0,0 +1,200 @@
+mod common;
+
+use common::check;
+use ginto_analysis::{InitError, check_definite_assignment};
+use ginto_diag::{DiagnosticConvertible, Severity};
+use ginto_test_support::{labels, text};
+
+/// The reads of uninitialized bindings in `source`, with the path that
+/// skips the assignment if some other path assigns them. Blocks span the
+/// indentation after them, which is left out.
+fn uninitialized(source: &str) -> Vec<(String, Option<&str>)> {
+    let program = check(source);
+    let root = &program.tree.module(program.tree.root()).program;
+    check_definite_assignment(root, &program.resolutions)
+        .into_iter()
+        .map(
+            |InitError::UseBeforeInit {
+                 name,
+                 span,
+                 skipped_by,
+                 ..
+             }| {
+                assert_eq!(text(source, span), name);
+                (name, skipped_by.map(|span| text(source, span).trim_end()))
+            },
+        )
+        .collect()
+}
+
+#[test]
+fn reads_after_an_assignment_are_accepted() {
+    let source = "fn f() -> u64:
+    let x: u64
+    x = 1
+    x
+";
+    assert!(uninitialized(source).is_empty());
+}
+
+#[test]
+fn reads_before_any_assignment_are_errors() {
+    let source = "fn f() -> u64:
+    let x: u64
+    let y = x
+    x = 1
+    y
+";
+    assert_eq!(uninitialized(source), [("x".to_string(), None)]);
+}
+
+#[test]
+fn both_branches_of_an_if_must_assign() {
+    let source = "fn f(c: bool) -> u64:
+    let x: u64
+    if c:
+        x = 1
+    else:
+        x = 2
+    x
+";
+    assert!(uninitialized(source).is_empty());
+
+    let source = "fn f(c: bool) -> u64:
+    let x: u64
+    if c:
+        x = 1
+    else:
+        ()
+    x
+";
+    assert_eq!(uninitialized(source), [("x".to_string(), Some("()"))]);
+}
+
+#[test]
+fn an_if_without_else_may_skip_the_assignment() {
+    let source = "fn f(c: bool) -> u64:
+    let x: u64
+    if c:
+        x = 1
+    x
+";
+    assert_eq!(uninitialized(source), [("x".to_string(), Some("c"))]);
+}
+
+#[test]
+fn every_arm_of_a_match_must_assign() {
+    let source = "fn f(n: u64) -> u64:
+    let x: u64
+    match n:
+        0 -> x = 1
+        _ -> ()
+    x
+";
+    assert_eq!(uninitialized(source), [("x".to_string(), Some("()"))]);
+}
+
+#[test]
+fn loops_may_not_run() {
+    let source = "fn f(c: bool) -> u64:
+    let x: u64
+    while c:
+        x = 1
+    x
+";
+    let errors = uninitialized(source);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, "x");
+}
+
+#[test]
+fn closures_read_what_they_capture_when_created() {
+    let source = "fn f() -> u64:
+    let x: u64
+    let g = || x
+    x = 1
+    g()
+";
+    assert_eq!(uninitialized(source), [("x".to_string(), None)]);
+}
+
+#[test]
+fn paths_that_return_do_not_count() {
+    let source = "fn f(c: bool) -> u64:
+    let x: u64
+    if c:
+        x = 1
+    else:
+        return 0
+    x
+";
+    assert!(uninitialized(source).is_empty());
+}
+
+#[test]
+fn each_binding_is_reported_once() {
+    let source = "fn f() -> u64:
+    let x: u64
+    let a = x
+    let b = x
+    a + b
+";
+    assert_eq!(uninitialized(source), [("x".to_string(), None)]);
+}
+
+#[test]
+fn bindings_with_an_initializer_are_always_initialized() {
+    let source = "fn f() -> u64:
+    let x = 1
+    x
+";
+    assert!(uninitialized(source).is_empty());
+}
+
+#[test]
+fn reads_of_uninitialized_bindings_point_at_the_declaration() {
+    let source = "fn f() -> u64:\n    let x: u64\n    x\n";
+    let program = check(source);
+    let root = &program.tree.module(program.tree.root()).program;
+    let diagnostic = check_definite_assignment(root, &program.resolutions)
+        .remove(0)
+        .into_diagnostic();
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.message, "used binding `x` isn't initialized");
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            ("x", "`x` used here but it isn't initialized".to_string()),
+            (
+                "x",
+                "binding declared here but left uninitialized".to_string()
+            ),
+        ]
+    );
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("assign a value to `x` on every path before reading it")
+    );
+}
+
+#[test]
+fn possibly_uninitialized_reads_point_at_the_path_skipping_the_assignment() {
+    let source = "fn f(c: bool) -> u64:\n    let x: u64\n    if c:\n        x = 1\n    x\n";
+    let program = check(source);
+    let root = &program.tree.module(program.tree.root()).program;
+    let diagnostic = check_definite_assignment(root, &program.resolutions)
+        .remove(0)
+        .into_diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "used binding `x` is possibly-uninitialized"
+    );
+    assert_eq!(
+        labels(source, &diagnostic)[2],
+        (
+            "c",
+            "`x` is not initialized when this path is taken".to_string()
+        )
+    );
+    assert_eq!(diagnostic.help, None);
+}
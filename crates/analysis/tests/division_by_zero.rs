@@ -0,0 +1,98 @@
+mod common;
+
+use common::fold;
+use ginto_analysis::ConstEvalError;
+use ginto_diag::{DiagnosticConvertible, Severity};
+use ginto_syntax::BinOp;
+use ginto_test_support::{labels, text};
+
+/// The divisions by zero of the body of `f`, with the text of their
+/// divisor.
+fn divisions_by_zero(body: &str) -> Vec<(BinOp, String)> {
+    let source = format!("fn f(x: u64) -> u64:\n    {body}\n");
+    let (_, errors) = fold(&source);
+    errors
+        .into_iter()
+        .map(|error| match error {
+            ConstEvalError::DivisionByZero { op, divisor, .. } => {
+                (op, text(&source, divisor).to_string())
+            }
+            error => panic!("expected a division by zero: {error:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn literal_zero_divisors_are_errors() {
+    assert_eq!(divisions_by_zero("x / 0"), [(BinOp::Div, "0".to_string())]);
+    assert_eq!(
+        divisions_by_zero("x mod 0"),
+        [(BinOp::Mod, "0".to_string())]
+    );
+}
+
+#[test]
+fn divisors_folding_to_zero_are_errors() {
+    assert_eq!(
+        divisions_by_zero("x / (2 - 2)"),
+        [(BinOp::Div, "(2 - 2)".to_string())]
+    );
+    assert_eq!(
+        divisions_by_zero("x mod (3 * 0)"),
+        [(BinOp::Mod, "(3 * 0)".to_string())]
+    );
+    assert_eq!(
+        divisions_by_zero("let y: i64 = 1 / (0 - 0)\n    x"),
+        [(BinOp::Div, "(0 - 0)".to_string())]
+    );
+}
+
+#[test]
+fn other_divisors_are_accepted() {
+    assert!(divisions_by_zero("x / 1").is_empty());
+    assert!(divisions_by_zero("x mod x").is_empty());
+    assert!(divisions_by_zero("0 / x").is_empty());
+}
+
+#[test]
+fn the_constant_dividend_is_named() {
+    let source = "fn f() -> u64:\n    7 / 0\n";
+    let (_, errors) = fold(source);
+    let diagnostic = errors[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.message, "this operation will panic at runtime");
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            ("7 / 0", "attempt to divide `7` by zero".to_string()),
+            ("7", "this evaluates to `7`".to_string()),
+            ("0", "this evaluates to `0`".to_string()),
+        ]
+    );
+    // the level, and the note saying where it comes from, are the lint's
+    assert!(diagnostic.notes.is_empty());
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some(
+            "dividing by zero aborts the program with a runtime error; \
+             use a non-zero divisor or check the divisor before dividing"
+        )
+    );
+}
+
+#[test]
+fn a_variable_dividend_is_a_value() {
+    let source = "fn f(x: u64) -> u64:\n    x mod 0\n";
+    let (_, errors) = fold(source);
+    let diagnostic = errors[0].clone().into_diagnostic();
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            (
+                "x mod 0",
+                "attempt to calculate the remainder of a value with a divisor of zero".to_string()
+            ),
+            ("0", "this evaluates to `0`".to_string()),
+        ]
+    );
+}
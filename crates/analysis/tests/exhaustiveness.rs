@@ -0,0 +1,266 @@
+mod common;
+
+use common::parse;
+use ginto_analysis::{MatchError, check_matches};
+use ginto_diag::{DiagnosticConvertible, FileId, Severity, Span};
+use ginto_test_support::text;
+
+/// The errors of the matches of `source`, with their spans as the text at
+/// them.
+fn check(source: &str) -> Vec<String> {
+    check_matches(&parse(source))
+        .into_iter()
+        .map(|err| match err {
+            MatchError::NonExhaustive { missing, span, .. } => {
+                format!("`{}` misses {}", text(source, span), missing.join(", "))
+            }
+            MatchError::UnreachableArm {
+                span,
+                covered_by,
+                covered_alone,
+                ..
+            } => format!(
+                "`{}` is covered by `{}`{}",
+                text(source, span),
+                text(source, covered_by),
+                if covered_alone {
+                    ""
+                } else {
+                    " and the arms above"
+                }
+            ),
+        })
+        .collect()
+}
+
+/// A function matching its parameter `x`, of type `ty`, with `arms`.
+fn func(ty: &str, arms: &[&str]) -> String {
+    let mut source = format!("fn f(x: {ty}) -> u64:\n    match x:\n");
+    for arm in arms {
+        source.push_str(&format!("        {arm}\n"));
+    }
+    source
+}
+
+#[test]
+fn both_booleans_are_exhaustive() {
+    assert!(check(&func("bool", &["true -> 1", "false -> 2"])).is_empty());
+    assert!(check(&func("bool", &["false -> 1", "true -> 2"])).is_empty());
+}
+
+#[test]
+fn a_missing_boolean_is_reported() {
+    assert_eq!(check(&func("bool", &["true -> 1"])), ["`x` misses false"]);
+    assert_eq!(check(&func("bool", &["false -> 1"])), ["`x` misses true"]);
+}
+
+#[test]
+fn unit_has_a_single_value() {
+    assert!(check(&func("()", &["() -> 1"])).is_empty());
+    assert_eq!(
+        check(&func("()", &["() -> 1", "_ -> 2"])),
+        ["`_` is covered by `()`"]
+    );
+}
+
+#[test]
+fn integers_need_a_catch_all() {
+    assert_eq!(check(&func("u64", &["0 -> 1", "1 -> 2"])), ["`x` misses _"]);
+    assert!(check(&func("u64", &["0 -> 1", "1 -> 2", "_ -> 3"])).is_empty());
+    assert!(check(&func("i64", &["-1 -> 1", "n -> n as u64"])).is_empty());
+    assert_eq!(check(&func("i64", &["-1 -> 1"])), ["`x` misses _"]);
+}
+
+#[test]
+fn repeated_integers_are_unreachable() {
+    assert_eq!(
+        check(&func("u64", &["0 -> 1", "1 -> 2", "0 -> 3", "_ -> 4"])),
+        ["`0` is covered by `0`"]
+    );
+    assert_eq!(
+        check(&func(
+            "i64",
+            &[
+                "-9223372036854775808 -> 1",
+                "-9223372036854775808 -> 2",
+                "_ -> 3"
+            ]
+        )),
+        ["`-9223372036854775808` is covered by `-9223372036854775808`"]
+    );
+    // `-1` and `1` are different values
+    assert!(check(&func("i64", &["-1 -> 1", "1 -> 2", "_ -> 3"])).is_empty());
+}
+
+#[test]
+fn arms_after_a_wildcard_are_unreachable() {
+    assert_eq!(
+        check(&func("bool", &["_ -> 1", "true -> 2", "false -> 3"])),
+        ["`true` is covered by `_`", "`false` is covered by `_`"]
+    );
+    assert_eq!(
+        check(&func("u64", &["_ -> 1", "_ -> 2"])),
+        ["`_` is covered by `_`"]
+    );
+}
+
+#[test]
+fn bindings_match_anything() {
+    assert!(check(&func("u64", &["n -> n"])).is_empty());
+    assert_eq!(
+        check(&func("u64", &["n -> n", "0 -> 1"])),
+        ["`0` is covered by `n`"]
+    );
+}
+
+#[test]
+fn a_catch_all_after_every_value_is_unreachable() {
+    assert_eq!(
+        check(&func("bool", &["true -> 1", "false -> 2", "_ -> 3"])),
+        ["`_` is covered by `false` and the arms above"]
+    );
+}
+
+#[test]
+fn or_patterns_cover_each_alternative() {
+    assert!(check(&func("bool", &["true | false -> 1"])).is_empty());
+    assert!(check(&func("u64", &["0 | 1 -> 1", "_ -> 2"])).is_empty());
+    assert_eq!(
+        check(&func("u64", &["0 | 1 -> 1", "2 | 3 -> 2"])),
+        ["`x` misses _"]
+    );
+}
+
+#[test]
+fn or_pattern_alternatives_can_be_unreachable() {
+    // covered by an alternative of the same arm
+    assert_eq!(
+        check(&func("bool", &["true | true -> 1", "false -> 2"])),
+        ["`true` is covered by `true`"]
+    );
+    // covered by an earlier arm, while the other alternative is not
+    assert_eq!(
+        check(&func("u64", &["0 -> 1", "0 | 1 -> 2", "_ -> 3"])),
+        ["`0` is covered by `0`"]
+    );
+}
+
+#[test]
+fn or_patterns_covered_by_earlier_arms_are_unreachable_as_a_whole() {
+    assert_eq!(
+        check(&func(
+            "bool",
+            &["true -> 1", "false -> 2", "true | false -> 3"]
+        )),
+        ["`true | false` is covered by `false` and the arms above"]
+    );
+    assert_eq!(
+        check(&func("u64", &["_ -> 1", "0 | 1 -> 2"])),
+        ["`0 | 1` is covered by `_`"]
+    );
+}
+
+#[test]
+fn nested_matches_are_checked_on_their_own() {
+    let source = "fn f(a: bool, b: bool) -> u64:
+    match a:
+        true ->
+            match b:
+                true -> 1
+        false -> 2
+";
+    assert_eq!(check(source), ["`b` misses false"]);
+
+    let source = "fn f(a: bool, b: u64) -> u64:
+    match a:
+        true -> match b:
+            _ -> 1
+            7 -> 2
+";
+    assert_eq!(check(source), ["`a` misses false", "`7` is covered by `_`"]);
+}
+
+#[test]
+fn every_match_of_a_program_is_checked() {
+    let source = "fn f(x: bool) -> u64:
+    match x:
+        true -> 1
+
+fn g(y: u64) -> u64:
+    let z = match y:
+        0 -> 1
+    z
+";
+    assert_eq!(check(source), ["`x` misses false", "`y` misses _"]);
+}
+
+#[test]
+fn patterns_of_different_types_are_left_to_the_type_checker() {
+    assert!(check(&func("bool", &["true -> 1", "0 -> 2"])).is_empty());
+}
+
+#[test]
+fn missing_patterns_are_errors_naming_them() {
+    let diagnostic = MatchError::NonExhaustive {
+        missing: vec!["false".to_string()],
+        span: Span::from_range(3..4),
+        file_id: FileId(0),
+    }
+    .into_diagnostic();
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(
+        diagnostic.message,
+        "non-exhaustive patterns: `false` not covered"
+    );
+    assert_eq!(
+        diagnostic.labels[0].message.as_deref(),
+        Some("pattern `false` not covered")
+    );
+
+    let diagnostic = MatchError::NonExhaustive {
+        missing: vec!["0".to_string(), "_".to_string()],
+        span: Span::from_range(3..4),
+        file_id: FileId(0),
+    }
+    .into_diagnostic();
+    assert_eq!(
+        diagnostic.labels[0].message.as_deref(),
+        Some("patterns `0`, `_` not covered")
+    );
+}
+
+#[test]
+fn unreachable_arms_are_warnings_pointing_at_what_covers_them() {
+    let unreachable = |covered_alone| {
+        MatchError::UnreachableArm {
+            span: Span::from_range(10..14),
+            covered_by: Span::from_range(3..4),
+            covered_alone,
+            file_id: FileId(0),
+        }
+        .into_diagnostic()
+    };
+    let diagnostic = unreachable(true);
+    assert_eq!(diagnostic.severity, Severity::Warning);
+    assert_eq!(diagnostic.message, "unreachable pattern");
+    let labels: Vec<_> = diagnostic
+        .labels
+        .iter()
+        .map(|label| (label.span, label.is_primary, label.message.as_deref()))
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            (Span::from_range(10..14), true, Some("unreachable pattern")),
+            (
+                Span::from_range(3..4),
+                false,
+                Some("every value it could match is already matched here")
+            ),
+        ]
+    );
+    assert_eq!(
+        unreachable(false).labels[1].message.as_deref(),
+        Some("the arms up to here already match every value it could match")
+    );
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use common::check;
+use ginto_analysis::{MutabilityError, check_mutability};
+use ginto_diag::{Diagnostic, DiagnosticConvertible};
+use ginto_test_support::labels;
+
+fn errors(source: &str) -> Vec<MutabilityError> {
+    let program = check(source);
+    let root = program.tree.module(program.tree.root());
+    check_mutability(&root.program, &program.resolutions)
+}
+
+fn diagnostic(source: &str) -> Diagnostic {
+    let errors = errors(source);
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    errors[0].clone().into_diagnostic()
+}
+
+#[test]
+fn mutable_variables_can_be_assigned() {
+    let source = "fn main(mut n: u64) -> u64:\n    let mut x = 1\n    x = x + n\n    n = 2\n    x = n\n    x\n";
+    assert!(errors(source).is_empty());
+}
+
+#[test]
+fn immutable_variables_cannot_be_assigned() {
+    let source = "fn main() -> u64:\n    let x = 1\n    x = 2\n    x\n";
+    let diagnostic = diagnostic(source);
+    assert_eq!(
+        diagnostic.message,
+        "cannot assign twice to immutable variable `x`"
+    );
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            (
+                "x = 2",
+                "cannot assign twice to immutable variable".to_string()
+            ),
+            ("x", "first assignment to `x`".to_string()),
+        ]
+    );
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("consider making this binding mutable: `mut x`")
+    );
+}
+
+#[test]
+fn variables_declared_without_a_value_are_assigned_once() {
+    let source = "fn main(c: bool) -> u64:\n    let x: u64\n    if c:\n        x = 1\n    else:\n        x = 2\n    x\n";
+    assert!(errors(source).is_empty());
+
+    let source = "fn main() -> u64:\n    let x: u64\n    x = 1\n    x = 2\n    x\n";
+    let diagnostic = diagnostic(source);
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            (
+                "x = 2",
+                "cannot assign twice to immutable variable".to_string()
+            ),
+            ("x = 1", "first assignment to `x`".to_string()),
+            ("x", "`x` declared as immutable here".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn assignments_in_loops_happen_again() {
+    let source = "fn main() -> u64:\n    let x: u64\n    while true:\n        x = 1\n    x\n";
+    let errors = errors(source);
+    // reported once, not once per iteration checked
+    assert!(matches!(
+        &errors[..],
+        [MutabilityError::AssignTwice { span, first, .. }] if span == first
+    ));
+}
+
+#[test]
+fn parameters_are_immutable() {
+    let source = "fn f(n: u64) -> u64:\n    n = n + 1\n    n\n";
+    let diagnostic = diagnostic(source);
+    assert_eq!(
+        diagnostic.message,
+        "cannot assign to immutable argument `n`"
+    );
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            (
+                "n = n + 1",
+                "cannot assign to immutable argument".to_string()
+            ),
+            ("n", "`n` declared as immutable here".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn match_bindings_are_immutable() {
+    let source =
+        "fn f(n: u64) -> u64:\n    match n:\n        m ->\n            m = 1\n            m\n";
+    let errors = errors(source);
+    assert!(matches!(
+        &errors[..],
+        [MutabilityError::AssignTwice {
+            can_be_mut: false,
+            ..
+        }]
+    ));
+    assert_eq!(
+        errors[0].clone().into_diagnostic().help.as_deref(),
+        Some("consider binding the value to a new variable: `let mut m = m`")
+    );
+}
+
+#[test]
+fn functions_cannot_be_assigned() {
+    let source = "fn g() -> u64:\n    1\n\nfn main() -> u64:\n    g = 2\n    g()\n";
+    let diagnostic = diagnostic(source);
+    assert_eq!(diagnostic.message, "cannot assign to function `g`");
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            ("g", "cannot assign to this".to_string()),
+            ("g", "function defined here".to_string()),
+        ]
+    );
+    assert_eq!(diagnostic.notes, ["only variables can be assigned to"]);
+}
+
+#[test]
+fn closures_cannot_assign_their_captures() {
+    let source = "fn main() -> u64:\n    let mut x = 1\n    let f = || x = 2\n    f()\n    x\n";
+    let diagnostic = diagnostic(source);
+    assert_eq!(
+        diagnostic.message,
+        "cannot assign to `x`, as it is captured by a closure"
+    );
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            ("x = 2", "cannot assign".to_string()),
+            ("|| x = 2", "`x` is captured by value here".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn closures_can_assign_their_own_variables() {
+    let source =
+        "fn main() -> u64:\n    let f = |mut n: u64|\n        n = n + 1\n        n\n    f(1)\n";
+    assert!(errors(source).is_empty());
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::fold;
+use ginto_analysis::ConstEvalError;
+use ginto_diag::{Diagnostic, DiagnosticConvertible};
+use ginto_syntax::{LexerError, Type};
+use ginto_test_support::labels;
+
+/// The values and types of the initializers of `body` that don't fit their
+/// annotation.
+fn out_of_range(body: &str) -> Vec<(Option<i128>, Type)> {
+    let (_, errors) = fold(&format!("fn f():\n    {body}\n"));
+    errors
+        .into_iter()
+        .map(|error| match error {
+            ConstEvalError::OutOfRange { value, ty, .. } => (value, ty.inner),
+            error => panic!("expected an initializer out of range: {error:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn initializers_that_fit_are_accepted() {
+    assert!(out_of_range("let x: i64 = 9223372036854775807").is_empty());
+    assert!(out_of_range("let x: i64 = -9223372036854775808").is_empty());
+    assert!(out_of_range("let x: u64 = 18446744073709551615").is_empty());
+    assert!(out_of_range("let x: u64 = 0").is_empty());
+}
+
+#[test]
+fn literals_out_of_range_are_errors() {
+    assert_eq!(
+        out_of_range("let x: i64 = 9999999999999999999"),
+        [(Some(9999999999999999999), Type::I64)]
+    );
+    assert_eq!(
+        out_of_range("let x: i64 = -9223372036854775809"),
+        [(Some(-9223372036854775809), Type::I64)]
+    );
+    assert_eq!(out_of_range("let x: u64 = -1"), [(Some(-1), Type::U64)]);
+}
+
+#[test]
+fn literal_only_arithmetic_is_evaluated_exactly() {
+    // each step fits `i128`, however large its intermediate results
+    assert_eq!(
+        out_of_range("let x: u64 = 18446744073709551615 * 2 - 1"),
+        [(Some(36893488147419103229), Type::U64)]
+    );
+    assert_eq!(out_of_range("let x: u64 = 1 - 2"), [(Some(-1), Type::U64)]);
+    // the result fits, but computing it overflows at runtime
+    let (_, errors) = fold("fn f():\n    let x: u64 = 18446744073709551615 * 2 / 2\n");
+    assert!(
+        matches!(errors.as_slice(), [ConstEvalError::Overflow { .. }]),
+        "{errors:#?}"
+    );
+}
+
+#[test]
+fn values_beyond_i128_are_reported_without_a_value() {
+    assert_eq!(
+        out_of_range(
+            "let x: u64 = 18446744073709551615 * 18446744073709551615 * 18446744073709551615"
+        ),
+        [(None, Type::U64)]
+    );
+}
+
+#[test]
+fn initializers_with_variables_or_without_annotations_are_left_alone() {
+    assert!(out_of_range("let y = 1\n    let x: u64 = y - 2").is_empty());
+    assert!(out_of_range("let x = 9999999999999999999").is_empty());
+    assert!(out_of_range("let x: bool = true").is_empty());
+}
+
+#[test]
+fn reuses_the_error_of_literals_too_large() {
+    let source = "fn f():\n    let x: i64 = 9999999999999999999\n";
+    let (_, errors) = fold(source);
+    let diagnostic = errors[0].clone().into_diagnostic();
+    let ConstEvalError::OutOfRange { span, file_id, .. } = &errors[0] else {
+        panic!("expected an initializer out of range");
+    };
+    let too_large: Diagnostic = LexerError::NumberTooLarge {
+        span: *span,
+        file_id: *file_id,
+    }
+    .into_diagnostic();
+    assert_eq!(diagnostic.message, too_large.message);
+    assert_eq!(diagnostic.code, too_large.code);
+    assert_eq!(diagnostic.help, too_large.help);
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            (
+                "9999999999999999999",
+                "this value does not fit in the target type".to_string()
+            ),
+            ("i64", "expected `i64` because of this".to_string()),
+        ]
+    );
+    assert_eq!(
+        diagnostic.notes,
+        [
+            "the initializer evaluates to `9999999999999999999`, but `i64` ranges from \
+          -9223372036854775808 to 9223372036854775807"
+        ]
+    );
+}
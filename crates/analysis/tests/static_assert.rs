@@ -0,0 +1,118 @@
+mod common;
+
+use common::fold;
+use ginto_analysis::ConstEvalError;
+use ginto_diag::{DiagnosticConvertible, SourceManager};
+use ginto_driver::{Driver, Stage};
+use ginto_test_support::labels;
+
+fn errors(source: &str) -> Vec<ConstEvalError> {
+    fold(source).1
+}
+
+#[test]
+fn assertions_that_hold_are_silent() {
+    let sources = [
+        "static_assert(true)\n",
+        "static_assert(1 + 1 = 2, \"math works\")\n",
+        "static_assert(18446744073709551615 > 0)\n",
+        "static_assert(-1 as u64 = 18446744073709551615)\n",
+        "static_assert(not (3 mod 2 = 0) && 10 / 3 = 3)\n",
+    ];
+    for source in sources {
+        assert!(errors(source).is_empty(), "{source}");
+    }
+}
+
+#[test]
+fn failing_assertions_report_their_message() {
+    let source = "static_assert(2 * 2 = 5, \"arithmetic is broken\")\n";
+    let errors = errors(source);
+    let [ConstEvalError::StaticAssertFailed { message, .. }] = &errors[..] else {
+        panic!("expected a failed assertion: {errors:#?}");
+    };
+    assert_eq!(message.as_deref(), Some("arithmetic is broken"));
+    let diagnostic = errors[0].clone().into_diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "static assertion failed: arithmetic is broken"
+    );
+    assert_eq!(
+        labels(source, &diagnostic),
+        [("2 * 2 = 5", "this evaluates to `false`".to_string())]
+    );
+}
+
+#[test]
+fn failing_assertions_without_a_message() {
+    let errors = errors("static_assert(false)\n");
+    assert_eq!(
+        errors[0].clone().into_diagnostic().message,
+        "static assertion failed"
+    );
+}
+
+#[test]
+fn every_failing_assertion_is_reported() {
+    let errors = errors("static_assert(1 = 2)\nstatic_assert(true)\nstatic_assert(3 < 2)\n");
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn conditions_must_be_constant() {
+    let source = "fn f() -> bool:\n    true\n\nstatic_assert(f())\n";
+    let errors = errors(source);
+    assert!(matches!(
+        &errors[..],
+        [ConstEvalError::StaticAssertNotConst { .. }]
+    ));
+    let diagnostic = errors[0].clone().into_diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "static assertion condition is not a constant"
+    );
+    assert_eq!(
+        labels(source, &diagnostic),
+        [(
+            "f()",
+            "this cannot be evaluated at compile time".to_string()
+        )]
+    );
+}
+
+#[test]
+fn conditions_that_fail_to_evaluate_report_only_why() {
+    let errors = errors("static_assert(1 / 0 = 0)\n");
+    assert!(matches!(
+        &errors[..],
+        [ConstEvalError::DivisionByZero { .. }]
+    ));
+}
+
+/// The stage checking `source` stops at, and the messages of its errors.
+fn checked(source: &str) -> (Option<Stage>, Vec<String>) {
+    let mut sm = SourceManager::new();
+    let root = sm.add_file("main.ginto".to_string(), source.to_string());
+    let checked = Driver::new().load(&mut sm, root);
+    let messages = checked
+        .diagnostics
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect();
+    (checked.failed, messages)
+}
+
+#[test]
+fn failing_assertions_fail_the_program() {
+    let (stage, messages) =
+        checked("static_assert(1 > 2, \"too small\")\n\nfn main() -> u64:\n    0\n");
+    assert_eq!(stage, Some(Stage::ConstantFolding));
+    assert_eq!(messages, ["static assertion failed: too small"]);
+}
+
+#[test]
+fn conditions_must_be_booleans() {
+    let (stage, messages) = checked("static_assert(1 + 1)\n\nfn main() -> u64:\n    0\n");
+    assert_eq!(stage, Some(Stage::Typecheck));
+    assert_eq!(messages, ["static assertion condition must be a `bool`"]);
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use common::parse;
+use ginto_analysis::{UnreachableWarning, check_unreachable, diverges};
+use ginto_diag::{DiagnosticConvertible, Severity};
+use ginto_syntax::ItemKind;
+use ginto_test_support::{labels, text};
+
+/// The unreachable code of `source` and the statement it follows.
+fn unreachable(source: &str) -> Vec<(&str, &str)> {
+    check_unreachable(&parse(source))
+        .into_iter()
+        .map(
+            |UnreachableWarning::UnreachableCode {
+                 span, diverging, ..
+             }| (text(source, span), text(source, diverging)),
+        )
+        .collect()
+}
+
+#[test]
+fn code_after_return_is_unreachable() {
+    let source = "fn f() -> u64:
+    return 1
+    let x = 2
+    x
+";
+    assert_eq!(unreachable(source), [("let x = 2\n    x", "return 1")]);
+}
+
+#[test]
+fn a_return_at_the_end_leaves_nothing_unreachable() {
+    let source = "fn f() -> u64:
+    let x = 2
+    return x
+";
+    assert!(unreachable(source).is_empty());
+}
+
+#[test]
+fn ifs_diverge_when_both_branches_do() {
+    let source = "fn f(c: bool) -> u64:
+    if c: return 1 else: return 2
+    3
+";
+    assert_eq!(
+        unreachable(source),
+        [("3", "if c: return 1 else: return 2")]
+    );
+
+    let source = "fn f(c: bool) -> u64:
+    if c: return 1
+    3
+";
+    assert!(unreachable(source).is_empty());
+}
+
+#[test]
+fn matches_diverge_when_every_arm_does() {
+    let source = "fn f(c: bool) -> u64:
+    match c:
+        true -> return 1
+        false -> return 2
+    3
+";
+    assert_eq!(unreachable(source).len(), 1);
+    assert_eq!(unreachable(source)[0].0, "3");
+
+    let source = "fn f(c: bool) -> u64:
+    match c:
+        true -> return 1
+        false -> 2
+    3
+";
+    assert!(unreachable(source).is_empty());
+}
+
+#[test]
+fn loops_may_not_run_their_body() {
+    let source = "fn f(c: bool) -> u64:
+    while c:
+        return 1
+    2
+";
+    assert!(unreachable(source).is_empty());
+}
+
+#[test]
+fn diverging_operands_make_the_statement_diverge() {
+    let source = "fn f() -> u64:
+    let x = 1 + return 2
+    x
+";
+    assert_eq!(unreachable(source), [("x", "let x = 1 + return 2")]);
+}
+
+#[test]
+fn nested_blocks_are_checked_on_their_own() {
+    let source = "fn f(c: bool) -> u64:
+    if c:
+        return 1
+        2
+    else:
+        3
+";
+    assert_eq!(unreachable(source), [("2", "return 1")]);
+}
+
+#[test]
+fn only_the_first_diverging_statement_is_reported() {
+    let source = "fn f() -> u64:
+    return 1
+    return 2
+    3
+";
+    assert_eq!(unreachable(source), [("return 2\n    3", "return 1")]);
+}
+
+#[test]
+fn diverges_looks_through_blocks() {
+    let program = parse("fn f() -> u64:\n    let x = 1\n    return x\n\nfn g() -> u64:\n    1\n");
+    let bodies: Vec<_> = program
+        .items
+        .iter()
+        .map(|item| match &item.inner {
+            ItemKind::Func(func) => diverges(&func.body),
+            _ => panic!("expected a function"),
+        })
+        .collect();
+    assert_eq!(bodies, [true, false]);
+}
+
+#[test]
+fn unreachable_code_is_a_warning_pointing_at_the_diverging_statement() {
+    let source = "fn f() -> u64:\n    return 1\n    2\n";
+    let diagnostic = check_unreachable(&parse(source))
+        .remove(0)
+        .into_diagnostic();
+    assert_eq!(diagnostic.severity, Severity::Warning);
+    assert_eq!(diagnostic.message, "unreachable code");
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            ("2", "unreachable code".to_string()),
+            (
+                "return 1",
+                "any code following this expression is unreachable".to_string()
+            ),
+        ]
+    );
+}
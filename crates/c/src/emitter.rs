@@ -0,0 +1,687 @@
+use std::{collections::HashMap, fmt::Write};
+
+use ginto_codegen::{
+    IntLiteral, Lowering, Repr, Signature, Unsupported, compare_units, func_instances, param_name,
+    param_name_span,
+};
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span, Symbol};
+use ginto_resolve::{DefId, DefKind, ModuleTree, Resolutions};
+use ginto_syntax::{
+    BinOp, Expr, ExprKind, Func, MatchArm, Pattern, PatternKind, Type, UnaryOp, visit,
+};
+use ginto_typeck::{Instance, Ty, TypeckResults};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CError {
+    /// A construct the C backend cannot compile yet.
+    Unsupported {
+        what: &'static str,
+        span: Span,
+        file_id: FileId,
+    },
+}
+
+impl From<Unsupported> for CError {
+    fn from(err: Unsupported) -> Self {
+        CError::Unsupported {
+            what: err.what,
+            span: err.span,
+            file_id: err.file_id,
+        }
+    }
+}
+
+impl DiagnosticConvertible for CError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            CError::Unsupported {
+                what,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("{} cannot be compiled to C", what)).with_label(
+                Label::primary(file_id, span).with_message("not supported by the C backend"),
+            ),
+        }
+    }
+}
+
+/// The code every translation unit starts with: the arithmetic that stops
+/// the program where the interpreter reports an error. Its names start with
+/// `rt_`, and those of the program's functions with `ginto_`.
+const PRELUDE: &str = r#"#include <inttypes.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+static inline void rt_panic(const char *message) {
+    fprintf(stderr, "error: %s\n", message);
+    exit(1);
+}
+
+static inline uint64_t rt_add_u64(uint64_t a, uint64_t b) {
+    if (a > UINT64_MAX - b) rt_panic("arithmetic overflow");
+    return a + b;
+}
+
+static inline uint64_t rt_sub_u64(uint64_t a, uint64_t b) {
+    if (a < b) rt_panic("arithmetic overflow");
+    return a - b;
+}
+
+static inline uint64_t rt_mul_u64(uint64_t a, uint64_t b) {
+    if (a != 0 && b > UINT64_MAX / a) rt_panic("arithmetic overflow");
+    return a * b;
+}
+
+static inline uint64_t rt_div_u64(uint64_t a, uint64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    return a / b;
+}
+
+static inline uint64_t rt_rem_u64(uint64_t a, uint64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    return a % b;
+}
+
+static inline uint64_t rt_neg_u64(uint64_t a) {
+    if (a != 0) rt_panic("arithmetic overflow");
+    return 0;
+}
+
+static inline int64_t rt_add_i64(int64_t a, int64_t b) {
+    if ((b > 0 && a > INT64_MAX - b) || (b < 0 && a < INT64_MIN - b)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a + b;
+}
+
+static inline int64_t rt_sub_i64(int64_t a, int64_t b) {
+    if ((b < 0 && a > INT64_MAX + b) || (b > 0 && a < INT64_MIN + b)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a - b;
+}
+
+static inline int64_t rt_mul_i64(int64_t a, int64_t b) {
+    if (a > 0 ? (b > 0 ? a > INT64_MAX / b : b < INT64_MIN / a)
+              : (b > 0 ? a < INT64_MIN / b : a != 0 && b < INT64_MAX / a)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a * b;
+}
+
+static inline int64_t rt_div_i64(int64_t a, int64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) rt_panic("arithmetic overflow");
+    return a / b;
+}
+
+static inline int64_t rt_rem_i64(int64_t a, int64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) rt_panic("arithmetic overflow");
+    return a % b;
+}
+
+static inline int64_t rt_neg_i64(int64_t a) {
+    if (a == INT64_MIN) rt_panic("arithmetic overflow");
+    return -a;
+}
+"#;
+
+/// Compiles a type-checked program to a C99 translation unit.
+///
+/// Every function becomes a C function named after its path, like
+/// `ginto_math__add` for `crate::math::add`. If the root file has a `main`
+/// function, a C `main` runs it and prints its value, like `ginto` does.
+/// The errors the interpreter reports, like an arithmetic overflow, stop
+/// the program with a message.
+///
+/// A generic function becomes a C function for each of its `instances`,
+/// like `ginto_id_of_u64` for `id::<u64>`.
+///
+/// `u64` and `i64` are represented as `uint64_t` and `int64_t` and `bool`
+/// as `bool`; `()` has no representation. Function values are not
+/// supported yet.
+pub fn emit_c(
+    tree: &ModuleTree,
+    resolutions: &Resolutions,
+    types: &TypeckResults,
+    instances: &[Instance],
+) -> Result<String, Vec<CError>> {
+    let mut emitter = Emitter {
+        lower: Lowering::new(resolutions, types),
+        funcs: HashMap::new(),
+        locals: HashMap::new(),
+        body: String::new(),
+        indent: 1,
+        next_name: 0,
+    };
+
+    // name every function first, so calls can refer to later ones
+    let mut compiled = Vec::new();
+    let mut main = None;
+    for (func, instance) in func_instances(tree, resolutions, types, instances) {
+        emitter.lower.enter(func.file_id, &instance);
+        let Some(sig) = emitter.lower.signature(func.func, func.def) else {
+            continue;
+        };
+        let mut name = match func.module == tree.root() {
+            true => "ginto_".to_string(),
+            false => format!(
+                "ginto_{}__",
+                tree.path(func.module)["crate::".len()..].replace("::", "__")
+            ),
+        };
+        if let Some(decl) = func.impl_decl {
+            write!(
+                name,
+                "{}_as_{}__",
+                decl.self_ty.inner, decl.trait_name.inner
+            )
+            .unwrap();
+        }
+        write!(name, "{}", func.func.name.inner).unwrap();
+        for (i, arg) in instance.args.iter().enumerate() {
+            let sep = if i == 0 { "_of_" } else { "_" };
+            let arg = arg.to_string().replace(|c: char| !c.is_alphanumeric(), "_");
+            write!(name, "{}{}", sep, arg).unwrap();
+        }
+        if func.is_main(tree) {
+            main = Some(instance.clone());
+        }
+        emitter.funcs.insert(instance.clone(), (name, sig));
+        compiled.push((func, instance));
+    }
+
+    let mut out = String::from(PRELUDE);
+    out.push('\n');
+    for (func, instance) in &compiled {
+        emitter.lower.enter(func.file_id, instance);
+        let prototype = emitter.prototype(func.func, instance);
+        writeln!(out, "static {};", prototype).unwrap();
+    }
+    for (func, instance) in compiled {
+        emitter.lower.enter(func.file_id, &instance);
+        out.push('\n');
+        out.push_str(&emitter.compile_func(func.func, &instance));
+    }
+    if let Some(main) = main {
+        out.push('\n');
+        out.push_str(&main_wrapper(
+            &emitter.funcs[&main].0,
+            types.def_ty(main.def),
+        ));
+    }
+    if !emitter.lower.errors.is_empty() {
+        return Err(emitter.lower.errors.into_iter().map(CError::from).collect());
+    }
+    Ok(out)
+}
+
+/// A C `main` calling the program's `main`, named `name` and of type `ty`,
+/// and printing its value.
+fn main_wrapper(name: &str, ty: Option<&Ty>) -> String {
+    let ret = match ty {
+        Some(Ty::Fn(sig)) => Some(&*sig.ret),
+        _ => None,
+    };
+    let print = match ret {
+        Some(Ty::U64) => format!("printf(\"%\" PRIu64 \"\\n\", {}());", name),
+        Some(Ty::I64) => format!("printf(\"%\" PRId64 \"\\n\", {}());", name),
+        Some(Ty::Bool) => format!("puts({}() ? \"true\" : \"false\");", name),
+        _ => format!("{}();\n    puts(\"()\");", name),
+    };
+    format!("int main(void) {{\n    {}\n    return 0;\n}}\n", print)
+}
+
+/// The C type of the values of `repr`.
+fn c_ty(repr: Repr) -> &'static str {
+    match repr {
+        Repr::U64 => "uint64_t",
+        Repr::I64 => "int64_t",
+        Repr::Bool => "bool",
+    }
+}
+
+struct Emitter<'a> {
+    lower: Lowering<'a>,
+    /// The C name and signature of each function instance.
+    funcs: HashMap<Instance, (String, Signature)>,
+    /// The C name of each variable of the function being compiled that has
+    /// a representation.
+    locals: HashMap<DefId, String>,
+    body: String,
+    indent: usize,
+    /// Numbers the variables and temporaries of the function being
+    /// compiled, which keeps their names apart.
+    next_name: usize,
+}
+
+impl Emitter<'_> {
+    fn expr_c_ty(&mut self, expr: &Expr) -> Option<&'static str> {
+        self.lower.expr_repr(expr).map(c_ty)
+    }
+
+    fn def_c_ty(&mut self, def: DefId, span: Span) -> Option<&'static str> {
+        self.lower.def_repr(def, span).map(c_ty)
+    }
+
+    /// The declaration of `func`, with its parameters named as in its
+    /// body.
+    fn prototype(&mut self, func: &Func, instance: &Instance) -> String {
+        let (name, sig) = self.funcs[instance].clone();
+        let params: Vec<_> = func
+            .params
+            .iter()
+            .zip(sig.params)
+            .filter_map(|(param, repr)| Some((param, repr?)))
+            .enumerate()
+            .map(|(i, (param, repr))| format!("{} {}_{}", c_ty(repr), param_name(param), i + 1))
+            .collect();
+        let params = match params.is_empty() {
+            true => "void".to_string(),
+            false => params.join(", "),
+        };
+        let ret = sig.ret.map_or("void", c_ty);
+        format!("{} {}({})", ret, name, params)
+    }
+
+    fn compile_func(&mut self, func: &Func, instance: &Instance) -> String {
+        self.locals.clear();
+        self.body.clear();
+        self.indent = 1;
+        self.next_name = 0;
+
+        let prototype = self.prototype(func, instance);
+        for param in &func.params {
+            let span = param_name_span(param);
+            if let Some(def) = self.lower.def_at(span)
+                && self.def_c_ty(def, param.span).is_some()
+            {
+                let name = self.fresh(&param_name(param));
+                self.locals.insert(def, name);
+            }
+        }
+        let value = self.compile_expr(&func.body);
+        if let (Some(_), Some(value)) = (self.funcs[instance].1.ret, value) {
+            self.line(format!("return {};", value));
+        }
+        format!("static {} {{\n{}}}\n", prototype, self.body)
+    }
+
+    fn line(&mut self, line: String) {
+        writeln!(self.body, "{}{}", "    ".repeat(self.indent), line).unwrap();
+    }
+
+    /// A new name for a variable called `name`, or a temporary. Ending in a
+    /// number, it is not a C keyword.
+    fn fresh(&mut self, name: &str) -> String {
+        self.next_name += 1;
+        format!("{}_{}", name, self.next_name)
+    }
+
+    /// Declares a temporary holding `value`, so that it is evaluated where
+    /// it is in the code, and returns its name.
+    fn temp(&mut self, ty: &str, value: String) -> String {
+        let name = self.fresh("");
+        self.line(format!("{} {} = {};", ty, name, value));
+        name
+    }
+
+    /// The variable declared at `span`, if its type has a representation.
+    fn declare_local(&mut self, name: Symbol, span: Span) -> Option<String> {
+        let def = self.lower.def_at(span)?;
+        if let Some(local) = self.locals.get(&def) {
+            return Some(local.clone());
+        }
+        let ty = self.def_c_ty(def, span)?;
+        let local = name.with(|name| self.fresh(name));
+        self.line(format!("{} {};", ty, local));
+        self.locals.insert(def, local.clone());
+        Some(local)
+    }
+
+    /// Compiles `expr` to statements and returns its value as a C
+    /// expression, if it has a representation.
+    fn compile_expr(&mut self, expr: &Expr) -> Option<String> {
+        let value = self.compile_expr_kind(expr);
+        // a value is expected of an expression that never finishes, like a
+        // `return` in an arm; any will do
+        if self.lower.expr_ty(expr) == Some(Ty::Never) {
+            return None;
+        }
+        value
+    }
+
+    /// Compiles `expr`, whose value is used, to a C expression.
+    fn operand(&mut self, expr: &Expr) -> String {
+        self.compile_expr(expr).unwrap_or_else(|| "0".to_string())
+    }
+
+    fn compile_expr_kind(&mut self, expr: &Expr) -> Option<String> {
+        let span = expr.span;
+        match &expr.inner {
+            ExprKind::Int(v) => self.compile_int(IntLiteral::new(
+                *v as i128,
+                self.lower.expr_ty(expr).as_ref(),
+            )),
+            ExprKind::Bool(v) => Some(v.to_string()),
+            ExprKind::Unit => None,
+            ExprKind::Error => unreachable!("program with syntax errors was compiled"),
+            ExprKind::Float(_) => {
+                self.lower.unsupported("floats", span);
+                None
+            }
+            ExprKind::Str(_) => {
+                self.lower.unsupported("strings", span);
+                None
+            }
+            ExprKind::Closure { .. } => {
+                self.lower.unsupported("closures", span);
+                None
+            }
+            ExprKind::Unary { op, expr: operand } => match op.inner {
+                UnaryOp::Neg => {
+                    if let Some(lit) = self.lower.int_literal(expr) {
+                        return self.compile_int(lit);
+                    }
+                    let value = self.operand(operand);
+                    let (helper, ty) = match self.lower.expr_ty(operand) {
+                        Some(Ty::I64) => ("rt_neg_i64", "int64_t"),
+                        _ => ("rt_neg_u64", "uint64_t"),
+                    };
+                    Some(self.temp(ty, format!("{}({})", helper, value)))
+                }
+                UnaryOp::Not => {
+                    let value = self.operand(operand);
+                    Some(self.temp("bool", format!("!{}", value)))
+                }
+            },
+            ExprKind::Binary { op, lhs, rhs } => self.compile_binary(op.inner, lhs, rhs),
+            ExprKind::Cast { expr: operand, ty } => {
+                let value = self.compile_expr(operand)?;
+                match ty.inner {
+                    Type::U64 => Some(format!("(uint64_t){}", value)),
+                    Type::I64 => Some(format!("(int64_t){}", value)),
+                    _ => Some(value),
+                }
+            }
+            ExprKind::Let { name, value, .. } => {
+                let local = self.declare_local(name.inner, name.span);
+                if let Some(value) = value {
+                    let value = self.compile_expr(value);
+                    if let (Some(local), Some(value)) = (local, value) {
+                        self.line(format!("{} = {};", local, value));
+                    }
+                }
+                None
+            }
+            ExprKind::Assign { name, value } => {
+                let value = self.compile_expr(value);
+                let local = self
+                    .lower
+                    .resolve(name.span)
+                    .and_then(|def| self.locals.get(&def).cloned());
+                if let (Some(local), Some(value)) = (local, value) {
+                    self.line(format!("{} = {};", local, value));
+                }
+                None
+            }
+            ExprKind::Block { exprs, tail } => {
+                for expr in exprs {
+                    self.compile_expr(expr);
+                }
+                tail.as_ref().and_then(|tail| self.compile_expr(tail))
+            }
+            ExprKind::Call { callee, args } => {
+                let instance = match &callee.inner {
+                    ExprKind::Var(_) | ExprKind::Path(_) => self
+                        .lower
+                        .resolve(callee.span)
+                        .map(|def| self.lower.instance(def, callee.span)),
+                    _ => None,
+                };
+                match instance {
+                    Some(instance) if self.funcs.contains_key(&instance) => {
+                        let args: Vec<_> = args.iter().collect();
+                        self.compile_call(&instance, &args)
+                    }
+                    instance => {
+                        self.lower.unsupported_call(instance.as_ref(), span);
+                        None
+                    }
+                }
+            }
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => {
+                let instance = self
+                    .lower
+                    .types
+                    .method_target(self.lower.file_id, method.span)
+                    .map(|def| self.lower.instance(def, method.span))
+                    .filter(|instance| self.funcs.contains_key(instance));
+                let Some(instance) = instance else {
+                    self.lower.unsupported("calls to this method", span);
+                    return None;
+                };
+                let args: Vec<_> = [&**receiver].into_iter().chain(args).collect();
+                self.compile_call(&instance, &args)
+            }
+            ExprKind::Match { scrutinee, arms } => self.compile_match(expr, scrutinee, arms),
+            ExprKind::Return(value) => {
+                let value = value.as_ref().and_then(|value| self.compile_expr(value));
+                match value {
+                    Some(value) => self.line(format!("return {};", value)),
+                    None => self.line("return;".to_string()),
+                }
+                None
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                let result = self.result(expr);
+                let cond = self.operand(cond);
+                self.line(format!("if ({}) {{", cond));
+                self.compile_branch(then_branch, result.as_deref());
+                if let Some(else_branch) = else_branch {
+                    self.line("} else {".to_string());
+                    self.compile_branch(else_branch, result.as_deref());
+                }
+                self.line("}".to_string());
+                result
+            }
+            ExprKind::While { cond, body } => {
+                // the condition may need statements of its own, so it is
+                // computed at the top of the loop
+                self.line("for (;;) {".to_string());
+                self.indent += 1;
+                let cond = self.operand(cond);
+                self.line(format!("if (!{}) break;", cond));
+                self.compile_expr(body);
+                self.indent -= 1;
+                self.line("}".to_string());
+                None
+            }
+            ExprKind::Var(_) | ExprKind::Path(_) => {
+                let def = self.lower.resolve(span)?;
+                match self.locals.get(&def) {
+                    Some(local) => Some(local.clone()),
+                    None if self.lower.resolutions.def(def).kind == DefKind::Func => {
+                        self.lower.unsupported("function values", span);
+                        None
+                    }
+                    // a variable of type `()`
+                    None => None,
+                }
+            }
+        }
+    }
+
+    /// A variable for the value of the branching expression `expr`, if it
+    /// has a representation.
+    fn result(&mut self, expr: &Expr) -> Option<String> {
+        let ty = self.expr_c_ty(expr)?;
+        let result = self.fresh("");
+        self.line(format!("{} {};", ty, result));
+        Some(result)
+    }
+
+    /// Compiles `branch` in a nested block, storing its value in `result`.
+    fn compile_branch(&mut self, branch: &Expr, result: Option<&str>) {
+        self.indent += 1;
+        let value = self.compile_expr(branch);
+        if let (Some(result), Some(value)) = (result, value) {
+            self.line(format!("{} = {};", result, value));
+        }
+        self.indent -= 1;
+    }
+
+    fn compile_call(&mut self, instance: &Instance, args: &[&Expr]) -> Option<String> {
+        let (name, sig) = self.funcs[instance].clone();
+        let mut operands = Vec::new();
+        for (arg, ty) in args.iter().zip(sig.params) {
+            let value = self.compile_expr(arg);
+            if ty.is_some() {
+                operands.push(value.unwrap_or_else(|| "0".to_string()));
+            }
+        }
+        let call = format!("{}({})", name, operands.join(", "));
+        match sig.ret {
+            Some(ret) => Some(self.temp(c_ty(ret), call)),
+            None => {
+                self.line(format!("{};", call));
+                None
+            }
+        }
+    }
+
+    /// A literal out of range for its type stops the program, like it fails
+    /// in the interpreter.
+    fn compile_int(&mut self, lit: IntLiteral) -> Option<String> {
+        c_int(lit).or_else(|| {
+            self.line("rt_panic(\"arithmetic overflow\");".to_string());
+            Some("0".to_string())
+        })
+    }
+
+    fn compile_binary(&mut self, op: BinOp, lhs: &Expr, rhs: &Expr) -> Option<String> {
+        if let BinOp::And | BinOp::Or = op {
+            let lhs = self.operand(lhs);
+            let result = self.temp("bool", lhs);
+            let cond = match op {
+                BinOp::And => result.clone(),
+                _ => format!("!{}", result),
+            };
+            self.line(format!("if ({}) {{", cond));
+            self.compile_branch(rhs, Some(&result));
+            self.line("}".to_string());
+            return Some(result);
+        }
+
+        let ty = self.lower.expr_ty(lhs);
+        let l = self.operand(lhs);
+        let r = self.operand(rhs);
+        let (suffix, c_ty) = match ty {
+            Some(Ty::I64) => ("i64", "int64_t"),
+            _ => ("u64", "uint64_t"),
+        };
+        let helper = match op {
+            BinOp::Add => "add",
+            BinOp::Sub => "sub",
+            BinOp::Mul => "mul",
+            BinOp::Div => "div",
+            BinOp::Mod => "rem",
+            _ => {
+                let value = match ty {
+                    Some(Ty::Unit) => compare_units(op).to_string(),
+                    _ => format!("{} {} {}", l, comparison(op), r),
+                };
+                return Some(self.temp("bool", value));
+            }
+        };
+        Some(self.temp(c_ty, format!("rt_{}_{}({}, {})", helper, suffix, l, r)))
+    }
+
+    /// Compiles a `match` to a chain of `if`s testing the scrutinee.
+    fn compile_match(
+        &mut self,
+        expr: &Expr,
+        scrutinee: &Expr,
+        arms: &[MatchArm],
+    ) -> Option<String> {
+        let scrutinee_ty = self.lower.expr_ty(scrutinee);
+        let value = self.compile_expr(scrutinee);
+        let result = self.result(expr);
+        for (i, arm) in arms.iter().enumerate() {
+            let test = compile_test(&arm.pattern, value.as_deref(), scrutinee_ty.as_ref());
+            match (i, test.as_str()) {
+                (0, _) => self.line(format!("if ({}) {{", test)),
+                (_, "true") => self.line("} else {".to_string()),
+                _ => self.line(format!("}} else if ({}) {{", test)),
+            }
+            self.indent += 1;
+            for name in visit::pattern_bindings(&arm.pattern) {
+                if let (Some(binding), Some(value)) =
+                    (self.declare_local(name.inner, name.span), &value)
+                {
+                    self.line(format!("{} = {};", binding, value));
+                }
+            }
+            self.indent -= 1;
+            self.compile_branch(&arm.body, result.as_deref());
+            // the arms after one matching anything are unreachable
+            if i > 0 && test == "true" {
+                break;
+            }
+        }
+        self.line("}".to_string());
+        result
+    }
+}
+
+/// A C condition telling whether the scrutinee `value` matches `pattern`.
+fn compile_test(pattern: &Pattern, value: Option<&str>, ty: Option<&Ty>) -> String {
+    match (&pattern.inner, value) {
+        (PatternKind::Int(n), Some(value)) => match c_int(IntLiteral::new(*n, ty)) {
+            Some(n) => format!("{} == {}", value, n),
+            None => "false".to_string(),
+        },
+        (PatternKind::Bool(true), Some(value)) => value.to_string(),
+        (PatternKind::Bool(false), Some(value)) => format!("!{}", value),
+        (PatternKind::Or(alternatives), _) => alternatives
+            .iter()
+            .map(|alternative| format!("({})", compile_test(alternative, value, ty)))
+            .collect::<Vec<_>>()
+            .join(" || "),
+        _ => "true".to_string(),
+    }
+}
+
+/// The C constant of `lit`, or `None` if it is out of range for its type.
+fn c_int(lit: IntLiteral) -> Option<String> {
+    let bits = lit.bits()?;
+    Some(match lit.signed {
+        true if bits == i64::MIN => "INT64_MIN".to_string(),
+        true => format!("INT64_C({})", bits),
+        false => format!("UINT64_C({})", bits as u64),
+    })
+}
+
+/// The C operator of a comparison.
+fn comparison(op: BinOp) -> &'static str {
+    match op {
+        BinOp::Equal => "==",
+        BinOp::NotEq => "!=",
+        BinOp::Less => "<",
+        BinOp::Le => "<=",
+        BinOp::Greater => ">",
+        BinOp::Ge => ">=",
+        _ => unreachable!("`{}` is not a comparison", op),
+    }
+}
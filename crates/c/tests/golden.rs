@@ -0,0 +1,25 @@
+//! Compiles every program of `tests/programs` to C and compares it,
+//! or the errors, to the `.c` file next to it. Run with `BLESS=1` to
+//! rewrite the `.c` files.
+
+use ginto_c::emit_c;
+use ginto_diag::DiagnosticConvertible;
+use ginto_test_support::golden;
+
+#[test]
+fn c_golden() {
+    golden(env!("CARGO_MANIFEST_DIR"), "c", |program| {
+        match emit_c(
+            &program.tree,
+            &program.resolutions,
+            &program.types,
+            &program.instances,
+        ) {
+            Ok(out) => out,
+            Err(errors) => errors
+                .into_iter()
+                .map(|err| format!("// error: {}\n", err.into_diagnostic().message))
+                .collect(),
+        }
+    });
+}
@@ -0,0 +1,147 @@
+#include <inttypes.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+static inline void rt_panic(const char *message) {
+    fprintf(stderr, "error: %s\n", message);
+    exit(1);
+}
+
+static inline uint64_t rt_add_u64(uint64_t a, uint64_t b) {
+    if (a > UINT64_MAX - b) rt_panic("arithmetic overflow");
+    return a + b;
+}
+
+static inline uint64_t rt_sub_u64(uint64_t a, uint64_t b) {
+    if (a < b) rt_panic("arithmetic overflow");
+    return a - b;
+}
+
+static inline uint64_t rt_mul_u64(uint64_t a, uint64_t b) {
+    if (a != 0 && b > UINT64_MAX / a) rt_panic("arithmetic overflow");
+    return a * b;
+}
+
+static inline uint64_t rt_div_u64(uint64_t a, uint64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    return a / b;
+}
+
+static inline uint64_t rt_rem_u64(uint64_t a, uint64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    return a % b;
+}
+
+static inline uint64_t rt_neg_u64(uint64_t a) {
+    if (a != 0) rt_panic("arithmetic overflow");
+    return 0;
+}
+
+static inline int64_t rt_add_i64(int64_t a, int64_t b) {
+    if ((b > 0 && a > INT64_MAX - b) || (b < 0 && a < INT64_MIN - b)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a + b;
+}
+
+static inline int64_t rt_sub_i64(int64_t a, int64_t b) {
+    if ((b < 0 && a > INT64_MAX + b) || (b > 0 && a < INT64_MIN + b)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a - b;
+}
+
+static inline int64_t rt_mul_i64(int64_t a, int64_t b) {
+    if (a > 0 ? (b > 0 ? a > INT64_MAX / b : b < INT64_MIN / a)
+              : (b > 0 ? a < INT64_MIN / b : a != 0 && b < INT64_MAX / a)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a * b;
+}
+
+static inline int64_t rt_div_i64(int64_t a, int64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) rt_panic("arithmetic overflow");
+    return a / b;
+}
+
+static inline int64_t rt_rem_i64(int64_t a, int64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) rt_panic("arithmetic overflow");
+    return a % b;
+}
+
+static inline int64_t rt_neg_i64(int64_t a) {
+    if (a == INT64_MIN) rt_panic("arithmetic overflow");
+    return -a;
+}
+
+static uint64_t ginto_classify(int64_t n_1);
+static bool ginto_both(bool a_1, bool b_2);
+static uint64_t ginto_count(uint64_t n_1);
+static uint64_t ginto_main(void);
+
+static uint64_t ginto_classify(int64_t n_1) {
+    uint64_t _2;
+    if (n_1 == INT64_C(0)) {
+        _2 = UINT64_C(100);
+    } else if ((n_1 == INT64_C(1)) || (n_1 == INT64_C(2)) || (n_1 == INT64_C(3))) {
+        _2 = UINT64_C(200);
+    } else {
+        int64_t x_3;
+        x_3 = n_1;
+        bool _4 = x_3 > INT64_C(0);
+        uint64_t _5;
+        if (_4) {
+            _5 = UINT64_C(400);
+        } else if (!_4) {
+            _5 = UINT64_C(500);
+        }
+        _2 = _5;
+    }
+    return _2;
+}
+
+static bool ginto_both(bool a_1, bool b_2) {
+    bool _3 = a_1;
+    if (_3) {
+        bool _4 = !b_2;
+        _3 = _4;
+    }
+    bool _5 = _3;
+    if (!_5) {
+        bool _6 = a_1 == b_2;
+        _5 = _6;
+    }
+    return _5;
+}
+
+static uint64_t ginto_count(uint64_t n_1) {
+    uint64_t total_2;
+    total_2 = UINT64_C(0);
+    uint64_t i_3;
+    i_3 = n_1;
+    bool _4 = i_3 > UINT64_C(10);
+    if (_4) {
+        return UINT64_C(0);
+    }
+    uint64_t _5 = rt_add_u64(total_2, i_3);
+    total_2 = _5;
+    return total_2;
+}
+
+static uint64_t ginto_main(void) {
+    uint64_t _1 = ginto_classify(INT64_C(2));
+    uint64_t _2 = ginto_count(UINT64_C(3));
+    uint64_t _3 = rt_add_u64(_1, _2);
+    bool _4 = ginto_both(true, false);
+    uint64_t _5 = rt_add_u64(_3, (uint64_t)_4);
+    return _5;
+}
+
+int main(void) {
+    printf("%" PRIu64 "\n", ginto_main());
+    return 0;
+}
@@ -0,0 +1,130 @@
+#include <inttypes.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+static inline void rt_panic(const char *message) {
+    fprintf(stderr, "error: %s\n", message);
+    exit(1);
+}
+
+static inline uint64_t rt_add_u64(uint64_t a, uint64_t b) {
+    if (a > UINT64_MAX - b) rt_panic("arithmetic overflow");
+    return a + b;
+}
+
+static inline uint64_t rt_sub_u64(uint64_t a, uint64_t b) {
+    if (a < b) rt_panic("arithmetic overflow");
+    return a - b;
+}
+
+static inline uint64_t rt_mul_u64(uint64_t a, uint64_t b) {
+    if (a != 0 && b > UINT64_MAX / a) rt_panic("arithmetic overflow");
+    return a * b;
+}
+
+static inline uint64_t rt_div_u64(uint64_t a, uint64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    return a / b;
+}
+
+static inline uint64_t rt_rem_u64(uint64_t a, uint64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    return a % b;
+}
+
+static inline uint64_t rt_neg_u64(uint64_t a) {
+    if (a != 0) rt_panic("arithmetic overflow");
+    return 0;
+}
+
+static inline int64_t rt_add_i64(int64_t a, int64_t b) {
+    if ((b > 0 && a > INT64_MAX - b) || (b < 0 && a < INT64_MIN - b)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a + b;
+}
+
+static inline int64_t rt_sub_i64(int64_t a, int64_t b) {
+    if ((b < 0 && a > INT64_MAX + b) || (b > 0 && a < INT64_MIN + b)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a - b;
+}
+
+static inline int64_t rt_mul_i64(int64_t a, int64_t b) {
+    if (a > 0 ? (b > 0 ? a > INT64_MAX / b : b < INT64_MIN / a)
+              : (b > 0 ? a < INT64_MIN / b : a != 0 && b < INT64_MAX / a)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a * b;
+}
+
+static inline int64_t rt_div_i64(int64_t a, int64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) rt_panic("arithmetic overflow");
+    return a / b;
+}
+
+static inline int64_t rt_rem_i64(int64_t a, int64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) rt_panic("arithmetic overflow");
+    return a % b;
+}
+
+static inline int64_t rt_neg_i64(int64_t a) {
+    if (a == INT64_MIN) rt_panic("arithmetic overflow");
+    return -a;
+}
+
+static bool ginto_id_of_bool(bool x_1);
+static uint64_t ginto_id_of_u64(uint64_t x_1);
+static bool ginto_pick_of_bool(bool first_1, bool a_2, bool b_3);
+static uint64_t ginto_pick_of_u64(bool first_1, uint64_t a_2, uint64_t b_3);
+static uint64_t ginto_main(void);
+
+static bool ginto_id_of_bool(bool x_1) {
+    return x_1;
+}
+
+static uint64_t ginto_id_of_u64(uint64_t x_1) {
+    return x_1;
+}
+
+static bool ginto_pick_of_bool(bool first_1, bool a_2, bool b_3) {
+    bool _4;
+    if (first_1) {
+        bool _5 = ginto_id_of_bool(a_2);
+        _4 = _5;
+    } else {
+        _4 = b_3;
+    }
+    return _4;
+}
+
+static uint64_t ginto_pick_of_u64(bool first_1, uint64_t a_2, uint64_t b_3) {
+    uint64_t _4;
+    if (first_1) {
+        uint64_t _5 = ginto_id_of_u64(a_2);
+        _4 = _5;
+    } else {
+        _4 = b_3;
+    }
+    return _4;
+}
+
+static uint64_t ginto_main(void) {
+    bool flag_1;
+    bool _2 = ginto_id_of_bool(true);
+    bool _3 = ginto_pick_of_bool(false, false, _2);
+    flag_1 = _3;
+    uint64_t _4 = ginto_id_of_u64(UINT64_C(4));
+    uint64_t _5 = ginto_pick_of_u64(flag_1, UINT64_C(3), _4);
+    return _5;
+}
+
+int main(void) {
+    printf("%" PRIu64 "\n", ginto_main());
+    return 0;
+}
@@ -0,0 +1,138 @@
+#include <inttypes.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+static inline void rt_panic(const char *message) {
+    fprintf(stderr, "error: %s\n", message);
+    exit(1);
+}
+
+static inline uint64_t rt_add_u64(uint64_t a, uint64_t b) {
+    if (a > UINT64_MAX - b) rt_panic("arithmetic overflow");
+    return a + b;
+}
+
+static inline uint64_t rt_sub_u64(uint64_t a, uint64_t b) {
+    if (a < b) rt_panic("arithmetic overflow");
+    return a - b;
+}
+
+static inline uint64_t rt_mul_u64(uint64_t a, uint64_t b) {
+    if (a != 0 && b > UINT64_MAX / a) rt_panic("arithmetic overflow");
+    return a * b;
+}
+
+static inline uint64_t rt_div_u64(uint64_t a, uint64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    return a / b;
+}
+
+static inline uint64_t rt_rem_u64(uint64_t a, uint64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    return a % b;
+}
+
+static inline uint64_t rt_neg_u64(uint64_t a) {
+    if (a != 0) rt_panic("arithmetic overflow");
+    return 0;
+}
+
+static inline int64_t rt_add_i64(int64_t a, int64_t b) {
+    if ((b > 0 && a > INT64_MAX - b) || (b < 0 && a < INT64_MIN - b)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a + b;
+}
+
+static inline int64_t rt_sub_i64(int64_t a, int64_t b) {
+    if ((b < 0 && a > INT64_MAX + b) || (b > 0 && a < INT64_MIN + b)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a - b;
+}
+
+static inline int64_t rt_mul_i64(int64_t a, int64_t b) {
+    if (a > 0 ? (b > 0 ? a > INT64_MAX / b : b < INT64_MIN / a)
+              : (b > 0 ? a < INT64_MIN / b : a != 0 && b < INT64_MAX / a)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a * b;
+}
+
+static inline int64_t rt_div_i64(int64_t a, int64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) rt_panic("arithmetic overflow");
+    return a / b;
+}
+
+static inline int64_t rt_rem_i64(int64_t a, int64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) rt_panic("arithmetic overflow");
+    return a % b;
+}
+
+static inline int64_t rt_neg_i64(int64_t a) {
+    if (a == INT64_MIN) rt_panic("arithmetic overflow");
+    return -a;
+}
+
+static uint64_t ginto_sum_to(uint64_t n_1);
+static uint64_t ginto_collatz(uint64_t n_1);
+static uint64_t ginto_main(void);
+
+static uint64_t ginto_sum_to(uint64_t n_1) {
+    uint64_t total_2;
+    total_2 = UINT64_C(0);
+    uint64_t i_3;
+    i_3 = UINT64_C(0);
+    for (;;) {
+        bool _4 = i_3 < n_1;
+        if (!_4) break;
+        uint64_t _5 = rt_add_u64(i_3, UINT64_C(1));
+        i_3 = _5;
+        uint64_t _6 = rt_add_u64(total_2, i_3);
+        total_2 = _6;
+    }
+    return total_2;
+}
+
+static uint64_t ginto_collatz(uint64_t n_1) {
+    uint64_t n_2;
+    n_2 = n_1;
+    uint64_t steps_3;
+    steps_3 = UINT64_C(0);
+    for (;;) {
+        bool _4 = n_2 != UINT64_C(1);
+        if (!_4) break;
+        uint64_t _5 = rt_rem_u64(n_2, UINT64_C(2));
+        bool _6 = _5 == UINT64_C(0);
+        if (_6) {
+            uint64_t _7 = rt_div_u64(n_2, UINT64_C(2));
+            n_2 = _7;
+        } else {
+            uint64_t _8 = rt_mul_u64(UINT64_C(3), n_2);
+            uint64_t _9 = rt_add_u64(_8, UINT64_C(1));
+            n_2 = _9;
+        }
+        uint64_t _10 = rt_add_u64(steps_3, UINT64_C(1));
+        steps_3 = _10;
+    }
+    return steps_3;
+}
+
+static uint64_t ginto_main(void) {
+    for (;;) {
+        if (!false) break;
+    }
+    uint64_t _1 = ginto_sum_to(UINT64_C(10));
+    uint64_t _2 = ginto_collatz(UINT64_C(27));
+    uint64_t _3 = rt_add_u64(_1, _2);
+    return _3;
+}
+
+int main(void) {
+    printf("%" PRIu64 "\n", ginto_main());
+    return 0;
+}
@@ -0,0 +1,102 @@
+#include <inttypes.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+static inline void rt_panic(const char *message) {
+    fprintf(stderr, "error: %s\n", message);
+    exit(1);
+}
+
+static inline uint64_t rt_add_u64(uint64_t a, uint64_t b) {
+    if (a > UINT64_MAX - b) rt_panic("arithmetic overflow");
+    return a + b;
+}
+
+static inline uint64_t rt_sub_u64(uint64_t a, uint64_t b) {
+    if (a < b) rt_panic("arithmetic overflow");
+    return a - b;
+}
+
+static inline uint64_t rt_mul_u64(uint64_t a, uint64_t b) {
+    if (a != 0 && b > UINT64_MAX / a) rt_panic("arithmetic overflow");
+    return a * b;
+}
+
+static inline uint64_t rt_div_u64(uint64_t a, uint64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    return a / b;
+}
+
+static inline uint64_t rt_rem_u64(uint64_t a, uint64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    return a % b;
+}
+
+static inline uint64_t rt_neg_u64(uint64_t a) {
+    if (a != 0) rt_panic("arithmetic overflow");
+    return 0;
+}
+
+static inline int64_t rt_add_i64(int64_t a, int64_t b) {
+    if ((b > 0 && a > INT64_MAX - b) || (b < 0 && a < INT64_MIN - b)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a + b;
+}
+
+static inline int64_t rt_sub_i64(int64_t a, int64_t b) {
+    if ((b < 0 && a > INT64_MAX + b) || (b > 0 && a < INT64_MIN + b)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a - b;
+}
+
+static inline int64_t rt_mul_i64(int64_t a, int64_t b) {
+    if (a > 0 ? (b > 0 ? a > INT64_MAX / b : b < INT64_MIN / a)
+              : (b > 0 ? a < INT64_MIN / b : a != 0 && b < INT64_MAX / a)) {
+        rt_panic("arithmetic overflow");
+    }
+    return a * b;
+}
+
+static inline int64_t rt_div_i64(int64_t a, int64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) rt_panic("arithmetic overflow");
+    return a / b;
+}
+
+static inline int64_t rt_rem_i64(int64_t a, int64_t b) {
+    if (b == 0) rt_panic("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) rt_panic("arithmetic overflow");
+    return a % b;
+}
+
+static inline int64_t rt_neg_i64(int64_t a) {
+    if (a == INT64_MIN) rt_panic("arithmetic overflow");
+    return -a;
+}
+
+static uint64_t ginto_u64_as_Shape__area(uint64_t self_1);
+static void ginto_nothing(void);
+static uint64_t ginto_main(void);
+
+static uint64_t ginto_u64_as_Shape__area(uint64_t self_1) {
+    uint64_t _2 = rt_mul_u64(self_1, self_1);
+    return _2;
+}
+
+static void ginto_nothing(void) {
+}
+
+static uint64_t ginto_main(void) {
+    ginto_nothing();
+    uint64_t _1 = ginto_u64_as_Shape__area(UINT64_C(3));
+    return _1;
+}
+
+int main(void) {
+    printf("%" PRIu64 "\n", ginto_main());
+    return 0;
+}
@@ -0,0 +1,95 @@
+use ginto_diag::{FileId, Span, Spanned, sym};
+use ginto_resolve::{DefId, ModuleId, ModuleTree, Resolutions};
+use ginto_syntax::{Func, ImplDecl, ItemKind, Param};
+use ginto_typeck::{Instance, Ty, TypeckResults};
+
+/// A function a backend compiles: a free function or a method of an impl.
+#[derive(Clone, Copy)]
+pub struct FuncDef<'a> {
+    pub func: &'a Func,
+    pub def: DefId,
+    pub module: ModuleId,
+    pub file_id: FileId,
+    /// The impl the function is a method of, `None` for free functions.
+    pub impl_decl: Option<&'a ImplDecl>,
+}
+
+impl FuncDef<'_> {
+    /// Whether this is the `main` function of the root module.
+    pub fn is_main(&self, tree: &ModuleTree) -> bool {
+        self.module == tree.root() && self.impl_decl.is_none() && self.func.name.inner == sym::MAIN
+    }
+}
+
+/// The functions of every module of `tree`, in the order they are
+/// declared.
+pub fn funcs<'a>(tree: &'a ModuleTree, resolutions: &Resolutions) -> Vec<FuncDef<'a>> {
+    let mut funcs = Vec::new();
+    for (module, loaded) in tree.modules() {
+        let file_id = loaded.program.file_id;
+        for item in &loaded.program.items {
+            let (methods, impl_decl) = match &item.inner {
+                ItemKind::Func(func) => (std::slice::from_ref(func), None),
+                ItemKind::Impl(decl) => (decl.methods.as_slice(), Some(decl)),
+                _ => continue,
+            };
+            for func in methods {
+                if let Some(def) = resolutions.def_at(file_id, func.name.span) {
+                    funcs.push(FuncDef {
+                        func,
+                        def,
+                        module,
+                        file_id,
+                        impl_decl,
+                    });
+                }
+            }
+        }
+    }
+    funcs
+}
+
+/// The function instances a backend compiles, in the order their functions
+/// are declared: the one instance of each function without type parameters
+/// and, of each generic function, those in `instances`.
+pub fn func_instances<'a>(
+    tree: &'a ModuleTree,
+    resolutions: &Resolutions,
+    types: &TypeckResults,
+    instances: &[Instance],
+) -> Vec<(FuncDef<'a>, Instance)> {
+    let mut compiled = Vec::new();
+    for func in funcs(tree, resolutions) {
+        match types.def_ty(func.def) {
+            Some(Ty::Fn(sig)) if !sig.generics.is_empty() => compiled.extend(
+                instances
+                    .iter()
+                    .filter(|instance| instance.def == func.def)
+                    .map(|instance| (func, instance.clone())),
+            ),
+            _ => compiled.push((
+                func,
+                Instance {
+                    def: func.def,
+                    args: Vec::new(),
+                },
+            )),
+        }
+    }
+    compiled
+}
+
+/// The span of the name `param` declares, where its definition is.
+pub fn param_name_span(param: &Spanned<Param>) -> Span {
+    match &param.inner {
+        Param::SelfParam => param.span,
+        Param::Named { name, .. } => name.span,
+    }
+}
+
+pub fn param_name(param: &Spanned<Param>) -> String {
+    match &param.inner {
+        Param::SelfParam => "self".to_string(),
+        Param::Named { name, .. } => name.inner.to_string(),
+    }
+}
@@ -0,0 +1,7 @@
+mod funcs;
+mod literal;
+mod lower;
+
+pub use funcs::*;
+pub use literal::*;
+pub use lower::*;
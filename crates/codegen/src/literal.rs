@@ -0,0 +1,57 @@
+use ginto_diag::FileId;
+use ginto_syntax::{BinOp, Expr, ExprKind, UnaryOp};
+use ginto_typeck::{Ty, TypeckResults};
+
+/// An integer literal, with the type the type checker inferred for it:
+/// `i64`, or `u64` otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntLiteral {
+    pub value: i128,
+    pub signed: bool,
+}
+
+impl IntLiteral {
+    pub fn new(value: i128, ty: Option<&Ty>) -> Self {
+        Self {
+            value,
+            signed: ty == Some(&Ty::I64),
+        }
+    }
+
+    /// The integer literal `expr` is, if it is one. A negated literal is
+    /// one too, as `-9223372036854775808` only fits `i64` once negated.
+    pub fn of(expr: &Expr, types: &TypeckResults, file_id: FileId) -> Option<Self> {
+        let value = match &expr.inner {
+            ExprKind::Int(v) => *v as i128,
+            ExprKind::Unary { op, expr: operand } if op.inner == UnaryOp::Neg => {
+                match operand.inner {
+                    ExprKind::Int(v) => -(v as i128),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        Some(Self::new(value, types.expr_ty(file_id, expr.span)))
+    }
+
+    /// The 64 bits of the literal, or `None` if it is out of range for its
+    /// type.
+    pub fn bits(self) -> Option<i64> {
+        match self.signed {
+            true => i64::try_from(self.value).ok(),
+            false => u64::try_from(self.value).ok().map(|v| v as i64),
+        }
+    }
+
+    pub fn ty_name(self) -> &'static str {
+        match self.signed {
+            true => "i64",
+            false => "u64",
+        }
+    }
+}
+
+/// The result of the comparison `() op ()`: `()` is equal to itself.
+pub fn compare_units(op: BinOp) -> bool {
+    matches!(op, BinOp::Equal | BinOp::Le | BinOp::Ge)
+}
@@ -0,0 +1,190 @@
+use ginto_diag::{FileId, Span};
+use ginto_resolve::{DefId, DefKind, Resolutions};
+use ginto_syntax::{Expr, Func};
+use ginto_typeck::{Instance, Ty, TyParam, TypeckResults};
+
+use crate::{IntLiteral, param_name_span};
+
+/// How the backends represent the values of a type. `()` and `!` have no
+/// representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repr {
+    U64,
+    I64,
+    Bool,
+}
+
+/// The representation of a function's parameters and result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub params: Vec<Option<Repr>>,
+    pub ret: Option<Repr>,
+}
+
+/// A construct a backend cannot compile yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unsupported {
+    pub what: &'static str,
+    pub span: Span,
+    pub file_id: FileId,
+}
+
+/// What the backends share to lower the types of a program: the results of
+/// checking it, the function instance being compiled and the constructs
+/// found so far that cannot be compiled.
+pub struct Lowering<'a> {
+    pub resolutions: &'a Resolutions,
+    pub types: &'a TypeckResults,
+    pub file_id: FileId,
+    /// The type parameters of the function being compiled, which stand for
+    /// the types in `args`.
+    generics: Vec<TyParam>,
+    args: Vec<Ty>,
+    pub errors: Vec<Unsupported>,
+}
+
+impl<'a> Lowering<'a> {
+    pub fn new(resolutions: &'a Resolutions, types: &'a TypeckResults) -> Self {
+        Self {
+            resolutions,
+            types,
+            file_id: FileId(0),
+            generics: Vec::new(),
+            args: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Starts lowering `instance`, of a function declared in `file_id`.
+    pub fn enter(&mut self, file_id: FileId, instance: &Instance) {
+        self.file_id = file_id;
+        self.generics = match self.types.def_ty(instance.def) {
+            Some(Ty::Fn(sig)) => sig.generics.clone(),
+            _ => Vec::new(),
+        };
+        self.args = instance.args.clone();
+    }
+
+    /// `ty`, with the type arguments of the instance being compiled in
+    /// place of its type parameters.
+    pub fn subst(&self, ty: &Ty) -> Ty {
+        ty.subst(&self.generics, &self.args)
+    }
+
+    /// The instance of `def` referred to at `span`, with the type arguments
+    /// it is used with there.
+    pub fn instance(&self, def: DefId, span: Span) -> Instance {
+        let args = self
+            .types
+            .instantiation(self.file_id, span)
+            .unwrap_or_default()
+            .iter()
+            .map(|ty| self.subst(ty))
+            .collect();
+        Instance { def, args }
+    }
+
+    pub fn unsupported(&mut self, what: &'static str, span: Span) {
+        self.errors.push(Unsupported {
+            what,
+            span,
+            file_id: self.file_id,
+        });
+    }
+
+    /// Reports a call the backend has no function for: to a builtin, to an
+    /// instance of a generic function the driver did not collect, or, if
+    /// `callee` is not a function, through a function value.
+    pub fn unsupported_call(&mut self, callee: Option<&Instance>, span: Span) {
+        let what = match callee {
+            Some(callee) if self.resolutions.def(callee.def).kind == DefKind::Builtin => {
+                "calls to builtin functions"
+            }
+            Some(callee) if !callee.args.is_empty() => {
+                "calls to generic functions `main` does not reach"
+            }
+            _ => "calls through function values",
+        };
+        self.unsupported(what, span);
+    }
+
+    /// The representation of values of `ty`, `None` for types without one.
+    pub fn repr(&mut self, ty: Option<&Ty>, span: Span) -> Option<Repr> {
+        match ty? {
+            Ty::U64 => Some(Repr::U64),
+            Ty::I64 => Some(Repr::I64),
+            Ty::Bool => Some(Repr::Bool),
+            Ty::Unit | Ty::Never | Ty::Error => None,
+            Ty::F64 => {
+                self.unsupported("floats", span);
+                None
+            }
+            Ty::Str => {
+                self.unsupported("strings", span);
+                None
+            }
+            Ty::Fn(_) => {
+                self.unsupported("function values", span);
+                None
+            }
+            Ty::Param(_) => {
+                self.unsupported("values of generic type", span);
+                None
+            }
+            Ty::Unknown => {
+                self.unsupported("variables without a known type", span);
+                None
+            }
+        }
+    }
+
+    pub fn expr_ty(&self, expr: &Expr) -> Option<Ty> {
+        let ty = self.types.expr_ty(self.file_id, expr.span)?;
+        Some(self.subst(ty))
+    }
+
+    pub fn expr_repr(&mut self, expr: &Expr) -> Option<Repr> {
+        let ty = self.expr_ty(expr);
+        self.repr(ty.as_ref(), expr.span)
+    }
+
+    /// The integer literal `expr` is, if it is one.
+    pub fn int_literal(&self, expr: &Expr) -> Option<IntLiteral> {
+        IntLiteral::of(expr, self.types, self.file_id)
+    }
+
+    pub fn def_repr(&mut self, def: DefId, span: Span) -> Option<Repr> {
+        let ty = self.types.def_ty(def).map(|ty| self.subst(ty));
+        self.repr(ty.as_ref(), span)
+    }
+
+    /// The definition of the variable declared at `span` in the file being
+    /// compiled.
+    pub fn def_at(&self, span: Span) -> Option<DefId> {
+        self.resolutions.def_at(self.file_id, span)
+    }
+
+    /// The definition `span` in the file being compiled refers to.
+    pub fn resolve(&self, span: Span) -> Option<DefId> {
+        self.resolutions.resolve(self.file_id, span)
+    }
+
+    /// The representation of `func`, defined as `def`, in the instance
+    /// being compiled.
+    pub fn signature(&mut self, func: &Func, def: DefId) -> Option<Signature> {
+        let Some(Ty::Fn(sig)) = self.types.def_ty(def) else {
+            return None;
+        };
+        let params = func
+            .params
+            .iter()
+            .map(|param| {
+                let def = self.def_at(param_name_span(param))?;
+                self.def_repr(def, param.span)
+            })
+            .collect();
+        let ret = func.ty.as_ref().map_or(func.name.span, |ty| ty.span);
+        let ret = self.repr(Some(&self.subst(&sig.ret)), ret);
+        Some(Signature { params, ret })
+    }
+}
@@ -0,0 +1,37 @@
+use ginto_codegen::{IntLiteral, funcs};
+use ginto_syntax::ExprKind;
+use ginto_test_support::check;
+use ginto_typeck::Ty;
+
+#[test]
+fn folds_negation_into_literals_of_the_inferred_type() {
+    let program = check("fn main() -> i64:\n    -9223372036854775808\n").0;
+    let funcs = funcs(&program.tree, &program.resolutions);
+    let ExprKind::Block {
+        tail: Some(tail), ..
+    } = &funcs[0].func.body.inner
+    else {
+        panic!("body is not a block with a tail");
+    };
+    let lit = IntLiteral::of(tail, &program.types, funcs[0].file_id).unwrap();
+    assert_eq!(
+        lit,
+        IntLiteral {
+            value: i64::MIN as i128,
+            signed: true,
+        }
+    );
+    assert_eq!(lit.bits(), Some(i64::MIN));
+    assert_eq!(lit.ty_name(), "i64");
+}
+
+#[test]
+fn literals_out_of_range_have_no_bits() {
+    assert_eq!(IntLiteral::new(-1, Some(&Ty::U64)).bits(), None);
+    assert_eq!(IntLiteral::new(-1, Some(&Ty::I64)).bits(), Some(-1));
+    assert_eq!(IntLiteral::new(u64::MAX as i128, None).bits(), Some(-1));
+    assert_eq!(
+        IntLiteral::new(u64::MAX as i128, Some(&Ty::I64)).bits(),
+        None
+    );
+}
@@ -0,0 +1,89 @@
+use ginto_codegen::{Lowering, Repr, Signature, Unsupported, func_instances, funcs};
+use ginto_test_support::check;
+
+const SOURCE: &str = "trait Shape:
+    fn area(self) -> u64
+
+impl Shape for u64:
+    fn area(self) -> u64:
+        self * self
+
+fn id<T>(x: T) -> T:
+    x
+
+fn scale(x: f64, by: i64, flip: bool) -> bool:
+    flip
+
+fn main() -> u64:
+    id(2)
+";
+
+#[test]
+fn walks_free_functions_and_methods_in_order() {
+    let program = check(SOURCE).0;
+    let funcs = funcs(&program.tree, &program.resolutions);
+    let names: Vec<_> = funcs
+        .iter()
+        .map(|func| func.func.name.inner.to_string())
+        .collect();
+    assert_eq!(names, ["area", "id", "scale", "main"]);
+    assert!(funcs[0].impl_decl.is_some());
+    assert!(funcs[1..].iter().all(|func| func.impl_decl.is_none()));
+    let mains: Vec<_> = funcs
+        .iter()
+        .map(|func| func.is_main(&program.tree))
+        .collect();
+    assert_eq!(mains, [false, false, false, true]);
+}
+
+#[test]
+fn lowers_the_signatures_of_instances() {
+    let program = check(SOURCE).0;
+    let instances = func_instances(
+        &program.tree,
+        &program.resolutions,
+        &program.types,
+        &program.instances,
+    );
+    let names: Vec<_> = instances
+        .iter()
+        .map(|(_, instance)| instance.display(&program.resolutions))
+        .collect();
+    assert_eq!(names, ["area", "id::<u64>", "scale", "main"]);
+
+    let mut lower = Lowering::new(&program.resolutions, &program.types);
+    let sigs: Vec<_> = instances
+        .iter()
+        .map(|(func, instance)| {
+            lower.enter(func.file_id, instance);
+            lower.signature(func.func, func.def)
+        })
+        .collect();
+    assert_eq!(
+        sigs,
+        [
+            Some(Signature {
+                params: vec![Some(Repr::U64)],
+                ret: Some(Repr::U64),
+            }),
+            Some(Signature {
+                params: vec![Some(Repr::U64)],
+                ret: Some(Repr::U64),
+            }),
+            Some(Signature {
+                params: vec![None, Some(Repr::I64), Some(Repr::Bool)],
+                ret: Some(Repr::Bool),
+            }),
+            Some(Signature {
+                params: vec![],
+                ret: Some(Repr::U64),
+            }),
+        ]
+    );
+    let errors: Vec<_> = lower
+        .errors
+        .iter()
+        .map(|Unsupported { what, span, .. }| (*what, &SOURCE[span.start.0..span.end.0]))
+        .collect();
+    assert_eq!(errors, [("floats", "x: f64")]);
+}
@@ -0,0 +1,349 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use ginto_diag::{BytePos, Diagnostic, DiagnosticConvertible, FileId, Label, SourceManager, Span};
+use ginto_fmt::FormatOptions;
+use ginto_lint::{Level, LintLevels, LintStore};
+use toml_edit::{Document, Item, TableLike};
+
+/// The name of the file configuring a project, in its root directory.
+pub const CONFIG_FILE: &str = "ginto.toml";
+
+/// The widest indentation `indent-width` may ask for.
+pub const MAX_INDENT_WIDTH: usize = 16;
+
+/// The settings of a project, read from its `ginto.toml`:
+///
+/// ```toml
+/// [project]
+/// entry = "src/main.ginto"
+/// source-dirs = ["src"]
+/// out-dir = "build"
+///
+/// [lints]
+/// shadowing = "warn"
+/// warnings = "deny"
+///
+/// [fmt]
+/// indent-width = 2
+/// ```
+///
+/// Every key is optional. Paths are relative to the directory of the file.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// The directory of the `ginto.toml`.
+    pub root: PathBuf,
+    /// The root file of the program, by default `main.ginto`.
+    pub entry: PathBuf,
+    /// The directories whose files `ginto fmt` formats, by default the
+    /// project's.
+    pub source_dirs: Vec<PathBuf>,
+    /// The directory `--emit` and `build` write their output to, by default
+    /// that of each program's file.
+    pub out_dir: Option<PathBuf>,
+    /// The level of each lint named in `[lints]`, which the command line
+    /// may override.
+    pub lint_levels: LintLevels,
+    pub fmt: FormatOptions,
+}
+
+impl Config {
+    /// The settings of a project in `root` without a `ginto.toml`.
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            entry: root.join("main.ginto"),
+            source_dirs: vec![root.clone()],
+            out_dir: None,
+            lint_levels: LintLevels::new(),
+            fmt: FormatOptions::default(),
+            root,
+        }
+    }
+
+    /// The `.ginto` files in the source directories and the directories
+    /// within them, sorted.
+    pub fn source_files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for dir in &self.source_dirs {
+            collect_sources(dir, &mut files);
+        }
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+fn collect_sources(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sources(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "ginto") {
+            files.push(path);
+        }
+    }
+}
+
+/// The `ginto.toml` in `dir` or the nearest directory above it that has
+/// one.
+pub fn find_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    Syntax {
+        message: String,
+        span: Span,
+        file_id: FileId,
+    },
+    UnknownKey {
+        key: String,
+        /// The table the key is in, `None` for the top level.
+        table: Option<&'static str>,
+        span: Span,
+        file_id: FileId,
+    },
+    InvalidValue {
+        key: String,
+        expected: String,
+        span: Span,
+        file_id: FileId,
+    },
+    UnknownLint {
+        name: String,
+        span: Span,
+        file_id: FileId,
+    },
+}
+
+impl DiagnosticConvertible for ConfigError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            ConfigError::Syntax {
+                message,
+                span,
+                file_id,
+            } => Diagnostic::error("invalid TOML")
+                .with_label(Label::primary(file_id, span).with_message(message)),
+
+            ConfigError::UnknownKey {
+                key,
+                table,
+                span,
+                file_id,
+            } => {
+                let (place, keys) = match table {
+                    Some(table) => (format!("`[{table}]`"), keys_of(table)),
+                    None => ("`ginto.toml`".to_string(), TABLES),
+                };
+                let keys: Vec<_> = keys.iter().map(|key| format!("`{key}`")).collect();
+                Diagnostic::error(format!("unknown key `{key}` in {place}"))
+                    .with_label(Label::primary(file_id, span).with_message("unknown key"))
+                    .with_note(format!("the keys of {place} are {}", keys.join(", ")))
+            }
+
+            ConfigError::InvalidValue {
+                key,
+                expected,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("invalid value for `{key}`")).with_label(
+                Label::primary(file_id, span).with_message(format!("expected {expected}")),
+            ),
+
+            ConfigError::UnknownLint {
+                name,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("unknown lint: `{name}`"))
+                .with_label(Label::primary(file_id, span).with_message("no lint has this name"))
+                .with_help("`ginto lint --list` lists the lints"),
+        }
+    }
+}
+
+const TABLES: &[&str] = &["project", "lints", "fmt"];
+
+fn keys_of(table: &str) -> &'static [&'static str] {
+    match table {
+        "project" => &["entry", "source-dirs", "out-dir"],
+        "fmt" => &["indent-width"],
+        _ => &[],
+    }
+}
+
+const LEVELS: [Level; 3] = [Level::Allow, Level::Warn, Level::Deny];
+
+/// Reads the `ginto.toml` `file` of `sm`, whose lints are those of `lints`.
+/// A key with a bad value keeps its default, so that the rest of the file
+/// still applies.
+pub fn parse_config(
+    sm: &SourceManager,
+    file_id: FileId,
+    lints: &LintStore,
+) -> (Config, Vec<ConfigError>) {
+    let file = sm.get_file(file_id).expect("the config is added");
+    let root = Path::new(&file.name)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let mut parser = ConfigParser {
+        config: Config::new(root),
+        file_id,
+        errors: Vec::new(),
+    };
+    match Document::parse(file.source.as_str()) {
+        Ok(document) => parser.document(document.as_table(), lints),
+        Err(err) => parser.errors.push(ConfigError::Syntax {
+            message: err.message().trim().to_string(),
+            span: parser.span(err.span()),
+            file_id,
+        }),
+    }
+    (parser.config, parser.errors)
+}
+
+struct ConfigParser {
+    config: Config,
+    file_id: FileId,
+    errors: Vec<ConfigError>,
+}
+
+impl ConfigParser {
+    fn document(&mut self, document: &dyn TableLike, lints: &LintStore) {
+        for (key, item) in document.iter() {
+            if !TABLES.contains(&key) {
+                self.unknown_key(document, key, None);
+                continue;
+            }
+            let Some(table) = item.as_table_like() else {
+                self.invalid_value(key, item, "a table");
+                continue;
+            };
+            match key {
+                "project" => self.project(table),
+                "lints" => self.lints(table, lints),
+                _ => self.fmt(table),
+            }
+        }
+    }
+
+    fn project(&mut self, table: &dyn TableLike) {
+        for (key, item) in table.iter() {
+            match key {
+                "entry" => {
+                    if let Some(path) = self.path(key, item) {
+                        self.config.entry = path;
+                    }
+                }
+                "source-dirs" => {
+                    let dirs = item.as_array().and_then(|dirs| {
+                        dirs.iter()
+                            .map(|dir| dir.as_str().map(|dir| self.config.root.join(dir)))
+                            .collect::<Option<Vec<_>>>()
+                    });
+                    match dirs {
+                        Some(dirs) => self.config.source_dirs = dirs,
+                        None => self.invalid_value(key, item, "an array of paths"),
+                    }
+                }
+                "out-dir" => {
+                    if let Some(path) = self.path(key, item) {
+                        self.config.out_dir = Some(path);
+                    }
+                }
+                _ => self.unknown_key(table, key, Some("project")),
+            }
+        }
+    }
+
+    fn lints(&mut self, table: &dyn TableLike, lints: &LintStore) {
+        for (name, item) in table.iter() {
+            let level = item
+                .as_str()
+                .and_then(|level| LEVELS.into_iter().find(|l| l.to_string() == level));
+            let Some(level) = level else {
+                self.invalid_value(name, item, "`allow`, `warn` or `deny`");
+                continue;
+            };
+            if let Err(name) = self.config.lint_levels.set_in_config(lints, name, level) {
+                let span = self.key_span(table, &name);
+                self.errors.push(ConfigError::UnknownLint {
+                    name,
+                    span,
+                    file_id: self.file_id,
+                });
+            }
+        }
+    }
+
+    fn fmt(&mut self, table: &dyn TableLike) {
+        for (key, item) in table.iter() {
+            match key {
+                "indent-width" => {
+                    let width = item
+                        .as_integer()
+                        .and_then(|width| usize::try_from(width).ok())
+                        .filter(|width| (1..=MAX_INDENT_WIDTH).contains(width));
+                    match width {
+                        Some(width) => self.config.fmt.indent_width = width,
+                        None => self.invalid_value(
+                            key,
+                            item,
+                            &format!("a number from 1 to {MAX_INDENT_WIDTH}"),
+                        ),
+                    }
+                }
+                _ => self.unknown_key(table, key, Some("fmt")),
+            }
+        }
+    }
+
+    /// The path `item` holds, relative to the project.
+    fn path(&mut self, key: &str, item: &Item) -> Option<PathBuf> {
+        match item.as_str() {
+            Some(path) => Some(self.config.root.join(path)),
+            None => {
+                self.invalid_value(key, item, "a path");
+                None
+            }
+        }
+    }
+
+    fn unknown_key(&mut self, table: &dyn TableLike, key: &str, name: Option<&'static str>) {
+        let span = self.key_span(table, key);
+        self.errors.push(ConfigError::UnknownKey {
+            key: key.to_string(),
+            table: name,
+            span,
+            file_id: self.file_id,
+        });
+    }
+
+    fn invalid_value(&mut self, key: &str, item: &Item, expected: &str) {
+        let span = self.span(item.span());
+        self.errors.push(ConfigError::InvalidValue {
+            key: key.to_string(),
+            expected: expected.to_string(),
+            span,
+            file_id: self.file_id,
+        });
+    }
+
+    fn key_span(&self, table: &dyn TableLike, key: &str) -> Span {
+        self.span(table.key(key).and_then(|key| key.span()))
+    }
+
+    fn span(&self, range: Option<Range<usize>>) -> Span {
+        let range = range.unwrap_or(0..0);
+        Span::new(BytePos(range.start), BytePos(range.end))
+    }
+}
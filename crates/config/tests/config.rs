@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use ginto_config::{Config, ConfigError, parse_config};
+use ginto_diag::{DiagnosticConvertible, SourceManager};
+use ginto_lint::{Level, LintStore};
+
+fn parse(source: &str) -> (Config, Vec<ConfigError>, SourceManager) {
+    let mut sm = SourceManager::new();
+    let file = sm.add_file("project/ginto.toml".to_string(), source.to_string());
+    let (config, errors) = parse_config(&sm, file, &LintStore::with_builtin_lints());
+    (config, errors, sm)
+}
+
+/// The text each error's primary label covers.
+fn labelled(errors: &[ConfigError], sm: &SourceManager) -> Vec<String> {
+    errors
+        .iter()
+        .map(|err| {
+            let label = err.clone().into_diagnostic().labels.remove(0);
+            let source = &sm.get_file(label.file_id).unwrap().source;
+            source[label.span.start.0..label.span.end.0].to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn reads_every_setting() {
+    let (config, errors, _) = parse(
+        "[project]\nentry = \"src/app.ginto\"\nsource-dirs = [\"src\", \"lib\"]\nout-dir = \"build\"\n\n\
+         [lints]\nshadowing = \"deny\"\nwarnings = \"allow\"\n\n[fmt]\nindent-width = 2\n",
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    let root = Path::new("project");
+    assert_eq!(config.entry, root.join("src/app.ginto"));
+    assert_eq!(config.source_dirs, [root.join("src"), root.join("lib")]);
+    assert_eq!(config.out_dir, Some(root.join("build")));
+    assert_eq!(config.fmt.indent_width, 2);
+    let lints = LintStore::with_builtin_lints();
+    let shadowing = lints.find("shadowing").unwrap();
+    assert_eq!(config.lint_levels.level(shadowing).0, Level::Deny);
+}
+
+#[test]
+fn defaults_to_the_project_directory() {
+    let (config, errors, _) = parse("");
+    assert!(errors.is_empty());
+    assert_eq!(config.entry, Path::new("project/main.ginto"));
+    assert_eq!(config.source_dirs, [Path::new("project")]);
+    assert_eq!(config.out_dir, None);
+    assert_eq!(config.fmt.indent_width, 4);
+}
+
+#[test]
+fn reports_unknown_keys_and_bad_values() {
+    let (config, errors, sm) = parse(
+        "name = \"app\"\n[project]\nentry = 1\nsource-dirs = \"src\"\nmain = \"x\"\n\n\
+         [lints]\nshadowing = \"forbid\"\nno_such_lint = \"deny\"\n\n[fmt]\nindent-width = 0\n",
+    );
+    assert!(matches!(
+        &errors[0],
+        ConfigError::UnknownKey { table: None, .. }
+    ));
+    assert!(matches!(&errors[1], ConfigError::InvalidValue { key, .. } if key == "entry"));
+    assert!(matches!(
+        &errors[3],
+        ConfigError::UnknownKey {
+            table: Some("project"),
+            ..
+        }
+    ));
+    assert!(matches!(&errors[5], ConfigError::UnknownLint { .. }));
+    assert_eq!(
+        labelled(&errors, &sm),
+        [
+            "name",
+            "1",
+            "\"src\"",
+            "main",
+            "\"forbid\"",
+            "no_such_lint",
+            "0"
+        ]
+    );
+    // the keys with bad values keep their defaults
+    assert_eq!(config.entry, Path::new("project/main.ginto"));
+    assert_eq!(config.fmt.indent_width, 4);
+}
+
+#[test]
+fn lists_the_keys_a_table_has() {
+    let (_, errors, _) = parse("[fmt]\nwidth = 2\n");
+    let diagnostic = errors[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.message, "unknown key `width` in `[fmt]`");
+    assert_eq!(diagnostic.notes, ["the keys of `[fmt]` are `indent-width`"]);
+}
+
+#[test]
+fn reports_invalid_toml_where_it_is() {
+    let (_, errors, sm) = parse("[project]\nentry = \n");
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(&errors[0], ConfigError::Syntax { .. }));
+    let diagnostic = errors[0].clone().into_diagnostic();
+    let span = diagnostic.labels[0].span;
+    let source = &sm.get_file(diagnostic.labels[0].file_id).unwrap().source;
+    assert_eq!(&source[..span.start.0], "[project]\nentry = ");
+}
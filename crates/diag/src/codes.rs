@@ -0,0 +1,42 @@
+/// A code given to diagnostics of one kind, with the explanation of the
+/// problem `ginto --explain` prints, written in markdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub explanation: &'static str,
+}
+
+impl ErrorCode {
+    /// The first sentence of the explanation, summing up the problem, on a
+    /// single line.
+    pub fn summary(&self) -> String {
+        let paragraph = self.explanation.split("\n\n").next().unwrap_or_default();
+        let paragraph = paragraph.trim().replace('\n', " ");
+        match paragraph.find(". ") {
+            Some(end) => paragraph[..=end].to_string(),
+            None => paragraph,
+        }
+    }
+}
+
+macro_rules! error_codes {
+    ($($code:literal),* $(,)?) => {
+        &[$(ErrorCode {
+            code: $code,
+            explanation: include_str!(concat!("../codes/", $code, ".md")),
+        }),*]
+    };
+}
+
+/// Every code, errors then warnings, in order.
+pub static ERROR_CODES: &[ErrorCode] = error_codes![
+    "E0001", "E0002", "E0003", "E0004", "E0005", "E0006", "W0001", "W0002", "W0003", "W0004",
+    "W0005", "W0006", "W0007", "W0008", "W0009", "W0010",
+];
+
+/// The code written `code`, in any case.
+pub fn find_error_code(code: &str) -> Option<&'static ErrorCode> {
+    ERROR_CODES
+        .iter()
+        .find(|error_code| error_code.code.eq_ignore_ascii_case(code))
+}
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use crate::{Diagnostic, DiagnosticRenderer, Severity, SourceManager};
+
+/// Collects the diagnostics of every phase of checking a program, and
+/// renders them in the order of their files and spans, each once.
+#[derive(Debug, Default)]
+pub struct Emitter {
+    pending: Vec<Diagnostic>,
+    /// The diagnostics in `pending`, to add each once.
+    seen: HashSet<Diagnostic>,
+    errors: usize,
+    warnings: usize,
+}
+
+impl Emitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `diagnostic`, unless an identical one is waiting to be
+    /// rendered.
+    pub fn emit(&mut self, diagnostic: Diagnostic) {
+        if !self.seen.insert(diagnostic.clone()) {
+            return;
+        }
+        match diagnostic.severity {
+            Severity::Error => self.errors += 1,
+            Severity::Warning => self.warnings += 1,
+            Severity::Note | Severity::Help => {}
+        }
+        self.pending.push(diagnostic);
+    }
+
+    pub fn extend(&mut self, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+        for diagnostic in diagnostics {
+            self.emit(diagnostic);
+        }
+    }
+
+    /// Renders the diagnostics added since the last flush with `renderer`,
+    /// sorted by the file and span of their primary label, each on the lines
+    /// after the last. Diagnostics without a primary label come first.
+    pub fn flush(&mut self, sm: &SourceManager, renderer: &dyn DiagnosticRenderer) -> String {
+        self.seen.clear();
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.sort_by_key(|diagnostic| {
+            diagnostic
+                .labels
+                .iter()
+                .find(|label| label.is_primary)
+                .map(|label| (label.file_id, label.span))
+        });
+        pending
+            .into_iter()
+            .map(|diagnostic| format!("{}\n", renderer.render(sm, diagnostic)))
+            .collect()
+    }
+
+    /// How many errors have been added, not counting duplicates.
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    /// How many warnings have been added, not counting duplicates.
+    pub fn warnings(&self) -> usize {
+        self.warnings
+    }
+
+    /// The line ending a report, like `1 error, 2 warnings`.
+    pub fn summary(&self) -> String {
+        format!(
+            "{}, {}",
+            plural(self.errors, "error"),
+            plural(self.warnings, "warning")
+        )
+    }
+
+    /// The error ending the report of a compilation that has errors, like
+    /// `aborting due to 2 previous errors; 1 warning emitted`.
+    pub fn aborting(&self) -> Option<Diagnostic> {
+        if self.errors == 0 {
+            return None;
+        }
+        let mut message = format!(
+            "aborting due to {}",
+            match self.errors {
+                1 => "1 previous error".to_string(),
+                n => format!("{n} previous errors"),
+            }
+        );
+        if self.warnings > 0 {
+            message.push_str(&format!("; {} emitted", plural(self.warnings, "warning")));
+        }
+        Some(Diagnostic::error(message))
+    }
+
+    /// Forgets the diagnostics and counts, for checking programs again.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        format!("{n} {word}")
+    } else {
+        format!("{n} {word}s")
+    }
+}
@@ -1,12 +1,22 @@
+mod codes;
+mod emitter;
+mod patch;
 mod renderer;
 mod source;
 mod span;
+mod suggest;
+mod symbol;
 
+pub use codes::*;
+pub use emitter::*;
+pub use patch::*;
 pub use renderer::*;
 pub use source::*;
 pub use span::*;
+pub use suggest::*;
+pub use symbol::*;
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub enum Severity {
     Error,
     Warning,
@@ -25,7 +35,7 @@ impl Severity {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub struct Label {
     pub span: Span,
     pub file_id: FileId,
@@ -58,7 +68,7 @@ impl Label {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub struct Diagnostic {
     pub message: String,
     pub severity: Severity,
@@ -66,6 +76,7 @@ pub struct Diagnostic {
     pub labels: Vec<Label>,
     pub notes: Vec<String>,
     pub help: Option<String>,
+    pub suggestions: Vec<Suggestion>,
 }
 
 impl Diagnostic {
@@ -77,9 +88,18 @@ impl Diagnostic {
             labels: Vec::new(),
             notes: Vec::new(),
             help: None,
+            suggestions: Vec::new(),
         }
     }
 
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error).with_message(message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning).with_message(message)
+    }
+
     pub fn with_message(mut self, message: impl Into<String>) -> Self {
         self.message = message.into();
         self
@@ -114,6 +134,11 @@ impl Diagnostic {
         self.help = Some(help.into());
         self
     }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
 }
 
 pub trait DiagnosticConvertible {
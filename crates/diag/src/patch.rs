@@ -0,0 +1,155 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{FileId, Span};
+
+/// How sure a suggestion is to be what the code meant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Applicability {
+    /// The edits keep the program's meaning and can be applied without
+    /// asking, as `--fix` does.
+    MachineApplicable,
+    /// The edits are likely right but need a look.
+    MaybeIncorrect,
+}
+
+impl Applicability {
+    /// The name of the applicability in JSON diagnostics, as rustc spells it.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "MachineApplicable",
+            Applicability::MaybeIncorrect => "MaybeIncorrect",
+        }
+    }
+}
+
+/// Replaces the text of `span` in `file_id` with `text`. An empty span
+/// inserts, an empty text deletes.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Edit {
+    pub file_id: FileId,
+    pub span: Span,
+    pub text: String,
+}
+
+impl Edit {
+    pub fn new(file_id: FileId, span: Span, text: impl Into<String>) -> Self {
+        Self {
+            file_id,
+            span,
+            text: text.into(),
+        }
+    }
+
+    pub fn insert(file_id: FileId, at: Span, text: impl Into<String>) -> Self {
+        Self::new(file_id, Span::new(at.start, at.start), text)
+    }
+
+    pub fn delete(file_id: FileId, span: Span) -> Self {
+        Self::new(file_id, span, "")
+    }
+}
+
+/// A change to the source that resolves a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
+pub struct Suggestion {
+    pub message: String,
+    pub edits: Vec<Edit>,
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    pub fn new(message: impl Into<String>, edits: Vec<Edit>, applicability: Applicability) -> Self {
+        Self {
+            message: message.into(),
+            edits,
+            applicability,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// The span of an edit is out of the source or not on a character
+    /// boundary.
+    OutOfBounds(Span),
+    /// Two edits change the same text.
+    Overlapping(Span, Span),
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::OutOfBounds(span) => {
+                write!(
+                    f,
+                    "the edit at {}..{} is outside the source",
+                    span.start.0, span.end.0
+                )
+            }
+            PatchError::Overlapping(first, second) => write!(
+                f,
+                "the edits at {}..{} and {}..{} overlap",
+                first.start.0, first.end.0, second.start.0, second.end.0
+            ),
+        }
+    }
+}
+
+/// `source` with `edits` applied. Edits are given in spans of the original
+/// source, in any order; inserts at the same position keep their order.
+pub fn apply_edits(source: &str, edits: &[Edit]) -> Result<String, PatchError> {
+    let mut edits: Vec<_> = edits.iter().collect();
+    edits.sort_by_key(|edit| (edit.span.start, edit.span.end));
+    let mut out = String::with_capacity(source.len());
+    let mut pos = 0;
+    let mut last: Option<Span> = None;
+    for edit in edits {
+        let Span { start, end } = edit.span;
+        if end.0 > source.len()
+            || !source.is_char_boundary(start.0)
+            || !source.is_char_boundary(end.0)
+        {
+            return Err(PatchError::OutOfBounds(edit.span));
+        }
+        if let Some(last) = last
+            && start.0 < pos
+        {
+            return Err(PatchError::Overlapping(last, edit.span));
+        }
+        out.push_str(&source[pos..start.0]);
+        out.push_str(&edit.text);
+        pos = end.0;
+        last = Some(edit.span);
+    }
+    out.push_str(&source[pos..]);
+    Ok(out)
+}
+
+/// The edits of the machine-applicable suggestions of `suggestions`, by
+/// file. A suggestion whose edits overlap those of an earlier one is left
+/// out, so that every set of edits applies cleanly.
+pub fn machine_applicable_edits<'a>(
+    suggestions: impl IntoIterator<Item = &'a Suggestion>,
+) -> BTreeMap<FileId, Vec<Edit>> {
+    let mut files: BTreeMap<FileId, Vec<Edit>> = BTreeMap::new();
+    for suggestion in suggestions {
+        if suggestion.applicability != Applicability::MachineApplicable {
+            continue;
+        }
+        let overlaps = suggestion.edits.iter().any(|edit| {
+            files.get(&edit.file_id).is_some_and(|taken| {
+                taken.iter().any(|other| {
+                    other.span == edit.span
+                        || other.span.start < edit.span.end && edit.span.start < other.span.end
+                })
+            })
+        });
+        if overlaps {
+            continue;
+        }
+        for edit in &suggestion.edits {
+            files.entry(edit.file_id).or_default().push(edit.clone());
+        }
+    }
+    files
+}
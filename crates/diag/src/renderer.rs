@@ -1,52 +1,523 @@
-use crate::{Diagnostic, LineColumn, SourceManager};
+use core::fmt::Write;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    Applicability, BytePos, Diagnostic, Edit, FileId, LineColumn, Severity, SourceManager, Span,
+    Suggestion, find_error_code,
+};
 
 pub trait DiagnosticRenderer {
     fn render(&self, source_manager: &SourceManager, diagnostic: Diagnostic) -> String;
 }
 
+/// How the renderers for people style diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RendererOptions {
+    /// Whether to color the output with ANSI escapes.
+    pub color: bool,
+}
+
+impl RendererOptions {
+    /// The renderer for people these options ask for.
+    pub fn renderer(self) -> &'static dyn DiagnosticRenderer {
+        if self.color {
+            &AnsiDiagnosticRenderer
+        } else {
+            &PlainDiagnosticRenderer
+        }
+    }
+}
+
+/// When to color output, as `--color` sets it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color output written to a terminal, unless the environment says
+    /// otherwise.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color output written to a stream that `is_terminal` says
+    /// is a terminal or not. For `Auto`, a non-empty `NO_COLOR` turns color
+    /// off, and otherwise a `CLICOLOR_FORCE` other than `0` turns it on.
+    pub fn use_color(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+                if var("NO_COLOR").is_some() {
+                    false
+                } else if var("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+                    true
+                } else {
+                    is_terminal
+                }
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("`{s}` isn't `auto`, `always` or `never`")),
+        }
+    }
+}
+
 pub struct PlainDiagnosticRenderer;
 
 impl DiagnosticRenderer for PlainDiagnosticRenderer {
     fn render(&self, source_manager: &SourceManager, diagnostic: Diagnostic) -> String {
-        let mut out = String::new();
-        out.push_str(diagnostic.severity.as_str());
-        if let Some(code) = &diagnostic.code {
-            out.push_str(&format!("[{}]", code));
-        }
-        out.push_str(&format!(" : {}\n", diagnostic.message));
-        for label in &diagnostic.labels {
-            let file = source_manager
-                .get_file(label.file_id)
-                .expect("file not found in SourceManager");
-            let span = label.span;
-            let start = span.start.0;
-            let end = span.end.0;
-            let LineColumn { line, col } = file.line_col(span.start);
-            let line_start = file.line_starts[line - 1].0;
+        render_human(source_manager, diagnostic, &Palette::PLAIN)
+    }
+}
+
+/// Renders diagnostics like `PlainDiagnosticRenderer`, colored with ANSI
+/// escapes for terminals.
+pub struct AnsiDiagnosticRenderer;
+
+impl DiagnosticRenderer for AnsiDiagnosticRenderer {
+    fn render(&self, source_manager: &SourceManager, diagnostic: Diagnostic) -> String {
+        render_human(source_manager, diagnostic, &Palette::ANSI)
+    }
+}
+
+/// The escapes starting each part of a diagnostic, and `reset` ending it.
+struct Palette {
+    error: &'static str,
+    warning: &'static str,
+    note: &'static str,
+    message: &'static str,
+    gutter: &'static str,
+    reset: &'static str,
+}
+
+impl Palette {
+    const PLAIN: Palette = Palette {
+        error: "",
+        warning: "",
+        note: "",
+        message: "",
+        gutter: "",
+        reset: "",
+    };
+
+    const ANSI: Palette = Palette {
+        error: "\x1b[1;31m",
+        warning: "\x1b[1;33m",
+        note: "\x1b[1;36m",
+        message: "\x1b[1m",
+        gutter: "\x1b[1;34m",
+        reset: "\x1b[0m",
+    };
+
+    fn severity(&self, severity: &Severity) -> &'static str {
+        match severity {
+            Severity::Error => self.error,
+            Severity::Warning => self.warning,
+            Severity::Note | Severity::Help => self.note,
+        }
+    }
+}
+
+fn render_human(
+    source_manager: &SourceManager,
+    diagnostic: Diagnostic,
+    palette: &Palette,
+) -> String {
+    let Palette { gutter, reset, .. } = palette;
+    let severity = palette.severity(&diagnostic.severity);
+    let mut out = String::new();
+    out.push_str(severity);
+    out.push_str(diagnostic.severity.as_str());
+    if let Some(code) = &diagnostic.code {
+        out.push_str(&format!("[{}]", code));
+    }
+    out.push_str(&format!(
+        "{reset}{} : {}{reset}\n",
+        palette.message, diagnostic.message
+    ));
+    for label in &diagnostic.labels {
+        let file = source_manager
+            .get_file(label.file_id)
+            .expect("file not found in SourceManager");
+        let span = label.span;
+        // columns count characters, as editors do
+        let LineColumn { line, col } = file.line_col_chars(span.start);
+        // trailing whitespace, like the newline and indentation a newline
+        // token spans, doesn't take the span to the lines after it
+        let covered = file.source[span.start.0..span.end.0].trim_end().len();
+        let last_line = if covered > 0 {
+            file.line_col(BytePos(span.start.0 + covered - 1)).line
+        } else {
+            line
+        };
+        out.push_str(&format!(
+            " {gutter}-->{reset} {}:{}:{}\n",
+            file.name, line, col
+        ));
+        // secondary labels are drawn in the color of the gutter
+        let carets = if label.is_primary { severity } else { gutter };
+        for n in line..=last_line {
+            let line_start = file.line_starts[n - 1].0;
             let line_end = file
                 .line_starts
-                .get(line)
+                .get(n)
                 .map(|p| p.0)
                 .unwrap_or(file.source.len());
-            let line_src = &file.source[line_start..line_end];
-            out.push_str(&format!(" --> {}:{}:{}\n", file.name, line, col));
-            out.push_str(&format!("{:4} | {}\n", line, line_src.trim_end()));
-            let caret_len = (end - start).max(1);
-            let caret_pad = col - 1;
-            out.push_str("     | ");
-            out.push_str(&" ".repeat(caret_pad));
-            out.push_str(&"^".repeat(caret_len));
-            if let Some(msg) = &label.message {
+            let line_full = &file.source[line_start..line_end];
+            let line_src = line_full.trim_end();
+            out.push_str(&format!(
+                "{gutter}{:4} |{reset} {}\n",
+                n,
+                expand_tabs(line_src)
+            ));
+            // the lines after the first are underlined from their indentation
+            let from = if n == line {
+                span.start.0 - line_start
+            } else {
+                line_src.len() - line_src.trim_start().len()
+            };
+            let to = if n == last_line {
+                (span.end.0 - line_start).min(line_src.len())
+            } else {
+                line_src.len()
+            };
+            // the carets go under the characters as a terminal shows them
+            let pad = display_width(&line_full[..from]);
+            let width = display_width(&line_full[from..to.max(from)]);
+            out.push_str(&format!("{gutter}     |{reset} "));
+            out.push_str(&" ".repeat(pad));
+            out.push_str(carets);
+            out.push_str(&"^".repeat(width.max(1)));
+            if n == last_line
+                && let Some(msg) = &label.message
+            {
                 out.push_str(&format!(" {}", msg));
             }
+            out.push_str(reset);
             out.push('\n');
         }
-        for note in diagnostic.notes {
-            out.push_str(&format!("note: {}\n", note));
+    }
+    for note in diagnostic.notes {
+        out.push_str(&format!("{}note{reset}: {}\n", palette.note, note));
+    }
+    if let Some(help) = diagnostic.help {
+        out.push_str(&format!("{}help{reset}: {}\n", palette.note, help));
+    }
+    for suggestion in &diagnostic.suggestions {
+        render_suggestion(&mut out, source_manager, suggestion, palette);
+    }
+    out
+}
+
+/// Renders `suggestion` as a `help` with the lines it changes, as they
+/// read once its edits are applied and with the new text underlined.
+fn render_suggestion(
+    out: &mut String,
+    source_manager: &SourceManager,
+    suggestion: &Suggestion,
+    palette: &Palette,
+) {
+    let Palette { gutter, reset, .. } = palette;
+    out.push_str(&format!(
+        "{}help{reset}: {}\n",
+        palette.note, suggestion.message
+    ));
+    let mut edits: Vec<_> = suggestion.edits.iter().collect();
+    edits.sort();
+    // an edit overlapping one before it can't be shown applied, as
+    // `apply_edits` can't apply it
+    let mut last: Option<&Edit> = None;
+    edits.retain(|&edit| {
+        let overlaps = last
+            .is_some_and(|last| last.file_id == edit.file_id && edit.span.start < last.span.end);
+        if !overlaps {
+            last = Some(edit);
+        }
+        !overlaps
+    });
+    let mut edits = edits.into_iter().peekable();
+    while let Some(first) = edits.next() {
+        let file = source_manager
+            .get_file(first.file_id)
+            .expect("file not found in SourceManager");
+        let line = file.line_col(first.span.start).line;
+        // the edits starting on the same line are shown together
+        let mut group = vec![first];
+        while let Some(edit) = edits.next_if(|edit| {
+            edit.file_id == first.file_id && file.line_col(edit.span.start).line == line
+        }) {
+            group.push(edit);
+        }
+        let start = file.line_starts[line - 1].0;
+        let last_line = file.line_col(group[group.len() - 1].span.end).line;
+        let end = file
+            .line_starts
+            .get(last_line)
+            .map_or(file.source.len(), |p| p.0);
+
+        let mut patched = String::new();
+        let mut inserted = Vec::new();
+        let mut pos = start;
+        for edit in group {
+            patched.push_str(&file.source[pos..edit.span.start.0]);
+            inserted.push(patched.len()..patched.len() + edit.text.len());
+            patched.push_str(&edit.text);
+            pos = edit.span.end.0;
+        }
+        patched.push_str(&file.source[pos..end]);
+
+        let mut offset = 0;
+        for (i, text) in patched.trim_end_matches('\n').split('\n').enumerate() {
+            out.push_str(&format!(
+                "{gutter}{:4} |{reset} {}\n",
+                line + i,
+                expand_tabs(text.trim_end())
+            ));
+            let markers: String = text
+                .char_indices()
+                .map(|(j, c)| {
+                    let at = offset + j;
+                    let marker =
+                        if !c.is_whitespace() && inserted.iter().any(|range| range.contains(&at)) {
+                            "~"
+                        } else {
+                            " "
+                        };
+                    marker.repeat(char_width(c))
+                })
+                .collect();
+            let markers = markers.trim_end();
+            if !markers.is_empty() {
+                out.push_str(&format!(
+                    "{gutter}     |{reset} {}{markers}{reset}\n",
+                    palette.note
+                ));
+            }
+            offset += text.len() + 1;
+        }
+    }
+}
+
+/// How many columns a tab takes in rendered source lines.
+const TAB_WIDTH: usize = 4;
+
+/// `line` as it is rendered, with its tabs expanded to spaces so they take
+/// the same width in every terminal.
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+/// How many columns a terminal shows `c` in: two for wide characters like
+/// CJK ideographs and most emoji, none for combining marks.
+fn char_width(c: char) -> usize {
+    if c == '\t' {
+        TAB_WIDTH
+    } else {
+        c.width().unwrap_or(0)
+    }
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// Renders a diagnostic as a JSON object on a single line, in the shape
+/// rustc's `--error-format=json` gives its own, for tools that read the
+/// compiler's output. Lines and columns are 1-based, columns counted in
+/// characters; notes, help and suggestions are its children.
+pub struct JsonDiagnosticRenderer;
+
+impl DiagnosticRenderer for JsonDiagnosticRenderer {
+    fn render(&self, source_manager: &SourceManager, diagnostic: Diagnostic) -> String {
+        let mut out = String::new();
+        out.push_str("{\"$message_type\":\"diagnostic\",\"message\":");
+        json_str(&mut out, &diagnostic.message);
+        out.push_str(",\"code\":");
+        match &diagnostic.code {
+            Some(code) => {
+                out.push_str("{\"code\":");
+                json_str(&mut out, code);
+                out.push_str(",\"explanation\":");
+                json_opt_str(
+                    &mut out,
+                    find_error_code(code).map(|error_code| error_code.explanation),
+                );
+                out.push('}');
+            }
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"level\":");
+        json_str(&mut out, diagnostic.severity.as_str());
+        out.push_str(",\"spans\":[");
+        for (i, label) in diagnostic.labels.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            json_span(
+                &mut out,
+                source_manager,
+                JsonSpan {
+                    file_id: label.file_id,
+                    span: label.span,
+                    is_primary: label.is_primary,
+                    label: label.message.as_deref(),
+                    replacement: None,
+                },
+            );
+        }
+        out.push_str("],\"children\":[");
+        let mut children = Vec::new();
+        for note in &diagnostic.notes {
+            children.push(json_child(source_manager, "note", note, &[]));
         }
-        if let Some(help) = diagnostic.help {
-            out.push_str(&format!("help: {}\n", help));
+        if let Some(help) = &diagnostic.help {
+            children.push(json_child(source_manager, "help", help, &[]));
         }
+        for suggestion in &diagnostic.suggestions {
+            let spans: Vec<_> = suggestion
+                .edits
+                .iter()
+                .map(|edit| JsonSpan {
+                    file_id: edit.file_id,
+                    span: edit.span,
+                    is_primary: true,
+                    label: None,
+                    replacement: Some((&edit.text, suggestion.applicability)),
+                })
+                .collect();
+            children.push(json_child(
+                source_manager,
+                "help",
+                &suggestion.message,
+                &spans,
+            ));
+        }
+        out.push_str(&children.join(","));
+        out.push_str("],\"rendered\":");
+        json_str(
+            &mut out,
+            &PlainDiagnosticRenderer.render(source_manager, diagnostic),
+        );
+        out.push('}');
         out
     }
 }
+
+/// A span of a diagnostic or of one of its children, as JSON shows it.
+#[derive(Clone, Copy)]
+struct JsonSpan<'a> {
+    file_id: FileId,
+    span: Span,
+    is_primary: bool,
+    label: Option<&'a str>,
+    /// The text a suggestion replaces the span with.
+    replacement: Option<(&'a str, Applicability)>,
+}
+
+fn json_child(sm: &SourceManager, level: &str, message: &str, spans: &[JsonSpan]) -> String {
+    let mut out = String::from("{\"message\":");
+    json_str(&mut out, message);
+    out.push_str(",\"code\":null,\"level\":");
+    json_str(&mut out, level);
+    out.push_str(",\"spans\":[");
+    for (i, span) in spans.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        json_span(&mut out, sm, *span);
+    }
+    out.push_str("],\"children\":[],\"rendered\":null}");
+    out
+}
+
+fn json_span(out: &mut String, sm: &SourceManager, span: JsonSpan) {
+    let file = sm
+        .get_file(span.file_id)
+        .expect("file not found in SourceManager");
+    let start = file.line_col_chars(span.span.start);
+    let end = file.line_col_chars(span.span.end);
+    out.push_str("{\"file_name\":");
+    json_str(out, &file.name);
+    write!(
+        out,
+        ",\"byte_start\":{},\"byte_end\":{},\"line_start\":{},\"line_end\":{},\
+         \"column_start\":{},\"column_end\":{},\"is_primary\":{},\"text\":[",
+        span.span.start.0,
+        span.span.end.0,
+        start.line,
+        end.line,
+        start.col,
+        end.col,
+        span.is_primary
+    )
+    .expect("writing to a string can't fail");
+    // each line the span covers, with the columns of the part it covers
+    for line in start.line..=end.line {
+        if line > start.line {
+            out.push(',');
+        }
+        let text = file.line_text(line);
+        out.push_str("{\"text\":");
+        json_str(out, text);
+        let highlight_start = if line == start.line { start.col } else { 1 };
+        let highlight_end = if line == end.line {
+            end.col
+        } else {
+            text.chars().count() + 1
+        };
+        write!(
+            out,
+            ",\"highlight_start\":{highlight_start},\"highlight_end\":{highlight_end}}}"
+        )
+        .expect("writing to a string can't fail");
+    }
+    out.push_str("],\"label\":");
+    json_opt_str(out, span.label);
+    out.push_str(",\"suggested_replacement\":");
+    json_opt_str(out, span.replacement.map(|(text, _)| text));
+    out.push_str(",\"suggestion_applicability\":");
+    json_opt_str(
+        out,
+        span.replacement
+            .map(|(_, applicability)| applicability.as_str()),
+    );
+    out.push_str(",\"expansion\":null}");
+}
+
+fn json_opt_str(out: &mut String, s: Option<&str>) {
+    match s {
+        Some(s) => json_str(out, s),
+        None => out.push_str("null"),
+    }
+}
+
+fn json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            ch if ch.is_control() => {
+                write!(out, "\\u{:04x}", ch as u32).expect("writing to a string can't fail")
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
@@ -1,6 +1,12 @@
-use crate::{BytePos, Span};
+use std::{
+    collections::BTreeMap,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq)]
+use crate::{BytePos, Diagnostic, DiagnosticConvertible, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct FileId(pub usize);
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq)]
@@ -9,19 +15,49 @@ pub struct LineColumn {
     pub col: usize,
 }
 
+/// What the column of a position counts from the start of its line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnUnit {
+    Bytes,
+    Chars,
+    /// UTF-16 code units, as editors speaking LSP count.
+    Utf16,
+}
+
+impl ColumnUnit {
+    fn width(self, len_utf8: usize) -> usize {
+        match self {
+            ColumnUnit::Bytes => len_utf8,
+            ColumnUnit::Chars => 1,
+            ColumnUnit::Utf16 if len_utf8 == 4 => 2,
+            ColumnUnit::Utf16 => 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq)]
 pub struct SourceFile {
     pub name: String,
     pub source: String,
     pub line_starts: Vec<BytePos>,
+    /// The position and length in bytes of each character longer than a
+    /// byte, in order, so columns in other units don't need the lines
+    /// scanned again.
+    multibyte_chars: Vec<(BytePos, usize)>,
 }
 
 impl SourceFile {
     pub fn new(name: String, source: String, line_starts: Vec<BytePos>) -> Self {
+        let multibyte_chars = source
+            .char_indices()
+            .filter(|(_, ch)| !ch.is_ascii())
+            .map(|(pos, ch)| (BytePos(pos), ch.len_utf8()))
+            .collect();
         Self {
             name,
             source,
             line_starts,
+            multibyte_chars,
         }
     }
 
@@ -41,11 +77,81 @@ impl SourceFile {
             col: col + 1,
         }
     }
+
+    /// The text of the 1-based `line`, without its line ending.
+    pub fn line_text(&self, line: usize) -> &str {
+        let Some(start) = self.line_starts.get(line.saturating_sub(1)) else {
+            return "";
+        };
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.source.len(), |next| next.0);
+        let text = &self.source[start.0..end];
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        text.strip_suffix('\r').unwrap_or(text)
+    }
+
+    /// The line and column of `pos`, with columns in UTF-16 code units.
+    pub fn line_col_utf16(&self, pos: BytePos) -> LineColumn {
+        self.line_col_in(pos, ColumnUnit::Utf16)
+    }
+
+    /// The line and column of `pos`, with columns in characters.
+    pub fn line_col_chars(&self, pos: BytePos) -> LineColumn {
+        self.line_col_in(pos, ColumnUnit::Chars)
+    }
+
+    pub fn line_col_in(&self, pos: BytePos, unit: ColumnUnit) -> LineColumn {
+        let LineColumn { line, col } = self.line_col(pos);
+        let line_start = self.line_starts[line - 1];
+        let col = self
+            .multibyte_chars_in(line_start, pos)
+            .iter()
+            .fold(col, |col, &(_, len)| col - len + unit.width(len));
+        LineColumn { line, col }
+    }
+
+    /// The position at `position`, whose column counts `unit`s. A column
+    /// past the end of its line is the end of the line, and a line past the
+    /// end of the file is the end of the file.
+    pub fn position_to_byte_pos(&self, position: LineColumn, unit: ColumnUnit) -> BytePos {
+        let Some(&line_start) = self.line_starts.get(position.line.saturating_sub(1)) else {
+            return BytePos(self.source.len());
+        };
+        let line_end = line_start.0 + self.line_text(position.line).len();
+        let target = position.col.saturating_sub(1);
+        let (mut pos, mut units) = (line_start.0, 0);
+        for &(start, len) in self.multibyte_chars_in(line_start, BytePos(line_end)) {
+            // the bytes before the character are a unit each
+            if units + start.0 - pos > target {
+                break;
+            }
+            units += start.0 - pos;
+            if units + unit.width(len) > target {
+                return start;
+            }
+            units += unit.width(len);
+            pos = start.0 + len;
+        }
+        BytePos((pos + target - units).min(line_end))
+    }
+
+    /// The characters longer than a byte that start in `start..end`.
+    fn multibyte_chars_in(&self, start: BytePos, end: BytePos) -> &[(BytePos, usize)] {
+        let first = self
+            .multibyte_chars
+            .partition_point(|&(pos, _)| pos < start);
+        let last = self.multibyte_chars.partition_point(|&(pos, _)| pos < end);
+        &self.multibyte_chars[first..last]
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, PartialOrd, Eq)]
 pub struct SourceManager {
     files: Vec<SourceFile>,
+    /// The files read from disk, by their canonical path.
+    paths: BTreeMap<PathBuf, FileId>,
 }
 
 impl SourceManager {
@@ -61,9 +167,122 @@ impl SourceManager {
         file_id
     }
 
+    /// Replaces the source of the file named `name`, keeping its id, or adds
+    /// it if there is none.
+    pub fn set_file(&mut self, name: String, source: String) -> FileId {
+        match self.file_id(&name) {
+            Some(file_id) => {
+                let line_starts = compute_line_starts(&source);
+                self.files[file_id.0] = SourceFile::new(name, source, line_starts);
+                file_id
+            }
+            None => self.add_file(name, source),
+        }
+    }
+
+    /// Reads the file at `path` and adds it, named by its path. A file
+    /// already read from the same path, however it was spelled, isn't read
+    /// again.
+    pub fn add_file_from_path(&mut self, path: impl AsRef<Path>) -> Result<FileId, FileReadError> {
+        let path = path.as_ref();
+        let canonical = canonicalize(path)?;
+        if let Some(&file_id) = self.paths.get(&canonical) {
+            return Ok(file_id);
+        }
+        let source = read_file(path)?;
+        let file_id = self.add_file(path.display().to_string(), source);
+        self.paths.insert(canonical, file_id);
+        Ok(file_id)
+    }
+
+    /// Reads the file at `path` again, replacing the source of the file
+    /// read from the same path or named by it, or adding it.
+    pub fn set_file_from_path(&mut self, path: impl AsRef<Path>) -> Result<FileId, FileReadError> {
+        let path = path.as_ref();
+        let canonical = canonicalize(path)?;
+        let source = read_file(path)?;
+        let name = match self.paths.get(&canonical) {
+            Some(&file_id) => self.files[file_id.0].name.clone(),
+            None => path.display().to_string(),
+        };
+        let file_id = self.set_file(name, source);
+        self.paths.insert(canonical, file_id);
+        Ok(file_id)
+    }
+
+    /// The id of the file read from `path`, however it was spelled.
+    pub fn file_id_by_path(&self, path: impl AsRef<Path>) -> Option<FileId> {
+        let canonical = path.as_ref().canonicalize().ok()?;
+        self.paths.get(&canonical).copied()
+    }
+
+    /// Reads standard input to its end and adds it as the file `<stdin>`.
+    pub fn add_stdin(&mut self) -> Result<FileId, FileReadError> {
+        let mut source = String::new();
+        match io::stdin().read_to_string(&mut source) {
+            Ok(_) => Ok(self.add_file(STDIN_NAME.to_string(), source)),
+            Err(err) => Err(FileReadError {
+                path: STDIN_NAME.to_string(),
+                kind: err.kind(),
+                message: err.to_string(),
+            }),
+        }
+    }
+
     pub fn get_file(&self, id: FileId) -> Option<&SourceFile> {
         self.files.get(id.0)
     }
+
+    /// The id of the file named `name`.
+    pub fn file_id(&self, name: &str) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|file| file.name == name)
+            .map(FileId)
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| (FileId(i), file))
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, FileReadError> {
+    std::fs::read_to_string(path).map_err(|err| FileReadError::new(path, err))
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, FileReadError> {
+    path.canonicalize()
+        .map_err(|err| FileReadError::new(path, err))
+}
+
+/// The name of the file `SourceManager::add_stdin` adds.
+pub const STDIN_NAME: &str = "<stdin>";
+
+/// A file `SourceManager::add_file_from_path` could not read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReadError {
+    pub path: String,
+    pub kind: io::ErrorKind,
+    pub message: String,
+}
+
+impl FileReadError {
+    fn new(path: &Path, err: io::Error) -> Self {
+        Self {
+            path: path.display().to_string(),
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl DiagnosticConvertible for FileReadError {
+    fn into_diagnostic(self) -> Diagnostic {
+        Diagnostic::error(format!("couldn't read `{}`: {}", self.path, self.message))
+    }
 }
 
 fn compute_line_starts(source: &str) -> Vec<BytePos> {
@@ -0,0 +1,53 @@
+use crate::{Applicability, Edit, FileId, Span, Suggestion};
+
+/// How many insertions, deletions and substitutions of a character, and
+/// swaps of two adjacent ones, turn `a` into `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // rows[i][j] is the distance between the first i of `a` and j of `b`
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = rows[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution.min(rows[i - 1][j] + 1).min(rows[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// The candidate `name` is most likely a misspelling of: the closest other
+/// one, within an edit per three characters of `name` and keeping at least
+/// one of them. Ties go to the first in alphabetical order, so the answer
+/// doesn't depend on the order of `candidates`.
+pub fn find_similar<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let len = name.chars().count();
+    let limit = (len / 3).max(1).min(len.saturating_sub(1));
+    candidates
+        .into_iter()
+        .filter(|&candidate| candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Suggests writing `name` at `span` instead.
+pub fn did_you_mean(file_id: FileId, span: Span, name: &str) -> Suggestion {
+    Suggestion::new(
+        format!("did you mean `{name}`?"),
+        vec![Edit::new(file_id, span, name)],
+        Applicability::MaybeIncorrect,
+    )
+}
@@ -0,0 +1,196 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+thread_local! {
+    /// The interner of the session the thread is in.
+    static CURRENT: RefCell<Option<Arc<Interner>>> = const { RefCell::new(None) };
+}
+
+/// A name interned in the current session, which compares, hashes and
+/// copies like the integer it is. Symbols are only meaningful in the
+/// session they were interned in, see [`Interner::enter`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn intern(name: &str) -> Self {
+        Interner::with_current(|interner| interner.intern(name))
+    }
+
+    /// Calls `f` with the name, for rendering it or comparing it to text.
+    pub fn with<R>(self, f: impl FnOnce(&str) -> R) -> R {
+        Interner::with_current(|interner| f(interner.lookup(self)))
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Self::intern(name)
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.with(|name| name == other)
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.with(|name| name == *other)
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with(|name| fmt::Debug::fmt(name, f))
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with(|name| f.write_str(name))
+    }
+}
+
+/// Names the compiler looks for, interned first in every session so that
+/// they are known without looking them up.
+pub mod sym {
+    use super::Symbol;
+
+    pub const CRATE: Symbol = Symbol(0);
+    pub const SELF: Symbol = Symbol(1);
+    pub const MAIN: Symbol = Symbol(2);
+    pub const CLOSURE: Symbol = Symbol(3);
+    pub const SELF_TYPE: Symbol = Symbol(4);
+
+    pub(super) const NAMES: [&str; 5] = ["crate", "self", "main", "{closure}", "Self"];
+}
+
+/// The first chunk holds this many names, and each next one twice as many
+/// as the one before.
+const FIRST_CHUNK: usize = 32;
+const CHUNKS: usize = (u32::BITS - FIRST_CHUNK.ilog2() + 1) as usize;
+
+/// A chunk of names, allocated when the first of them is interned.
+type Chunk = OnceLock<Box<[OnceLock<Arc<str>>]>>;
+
+/// The names interned during a session, freed with it. Names are stored in
+/// chunks that never move once allocated, so they are read without a lock
+/// while other threads of the session intern more.
+#[derive(Debug)]
+pub struct Interner {
+    symbols: Mutex<HashMap<Arc<str>, Symbol>>,
+    chunks: [Chunk; CHUNKS],
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        let interner = Self {
+            symbols: Mutex::default(),
+            chunks: std::array::from_fn(|_| OnceLock::new()),
+        };
+        for name in sym::NAMES {
+            interner.intern(name);
+        }
+        interner
+    }
+
+    /// The interner of the session this thread is in, or of a session of
+    /// its own started for it if it hasn't entered one.
+    pub fn current() -> Arc<Self> {
+        Self::with_current(|_| ());
+        CURRENT.with(|current| current.borrow().clone().expect("a session was started"))
+    }
+
+    /// Makes `self` the interner of the session this thread is in until the
+    /// returned guard is dropped. Threads working on the same program enter
+    /// the same interner, so they agree on what its symbols are.
+    pub fn enter(self: Arc<Self>) -> Entered {
+        let previous = CURRENT.with(|current| current.replace(Some(self)));
+        Entered {
+            previous,
+            thread: PhantomData,
+        }
+    }
+
+    fn with_current<R>(f: impl FnOnce(&Self) -> R) -> R {
+        CURRENT.with(|current| {
+            if current.borrow().is_none() {
+                *current.borrow_mut() = Some(Arc::new(Self::new()));
+            }
+            f(current.borrow().as_ref().expect("a session was started"))
+        })
+    }
+
+    pub fn intern(&self, name: &str) -> Symbol {
+        let mut symbols = self.symbols.lock().unwrap();
+        if let Some(&symbol) = symbols.get(name) {
+            return symbol;
+        }
+        let index = u32::try_from(symbols.len()).expect("too many names to intern");
+        let (chunk, slot) = locate(index);
+        let name: Arc<str> = name.into();
+        self.chunks[chunk]
+            .get_or_init(|| (0..FIRST_CHUNK << chunk).map(|_| OnceLock::new()).collect())[slot]
+            .set(name.clone())
+            .expect("names are interned once");
+        symbols.insert(name, Symbol(index));
+        Symbol(index)
+    }
+
+    /// The name of `symbol`, which must have been interned by this table.
+    pub fn lookup(&self, symbol: Symbol) -> &str {
+        let (chunk, slot) = locate(symbol.0);
+        self.chunks[chunk]
+            .get()
+            .and_then(|chunk| chunk[slot].get())
+            .expect("symbol was interned in another session")
+    }
+
+    /// How many names were interned, counting those of [`sym`].
+    pub fn len(&self) -> usize {
+        self.symbols.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The chunk and the slot in it of the name at `index`.
+fn locate(index: u32) -> (usize, usize) {
+    let position = index as usize + FIRST_CHUNK;
+    let bits = position.ilog2();
+    (
+        (bits - FIRST_CHUNK.ilog2()) as usize,
+        position - (1 << bits),
+    )
+}
+
+/// A thread's stay in the session of an interner, see [`Interner::enter`].
+/// Dropping it returns the thread to the session it was in before.
+#[must_use = "the session is left when this is dropped"]
+pub struct Entered {
+    previous: Option<Arc<Interner>>,
+    /// Left on the thread that entered.
+    thread: PhantomData<*const ()>,
+}
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
@@ -0,0 +1,66 @@
+use std::{fs, path::Path};
+
+use ginto_diag::{ERROR_CODES, find_error_code};
+
+/// The string literals like `"E0001"` in the Rust files under `dir`.
+fn codes_in(dir: &Path, codes: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            codes_in(&path, codes);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            let source = fs::read_to_string(&path).unwrap();
+            for (start, _) in source.match_indices('"') {
+                let Some(literal) = source.get(start + 1..start + 7) else {
+                    continue;
+                };
+                let bytes = literal.as_bytes();
+                if matches!(bytes[0], b'E' | b'W')
+                    && bytes[1..5].iter().all(u8::is_ascii_digit)
+                    && bytes[5] == b'"'
+                {
+                    codes.push(literal[..5].to_string());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn explains_every_code_in_use() {
+    let crates = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let mut codes = Vec::new();
+    for krate in fs::read_dir(crates).unwrap() {
+        let src = krate.unwrap().path().join("src");
+        if src.is_dir() {
+            codes_in(&src, &mut codes);
+        }
+    }
+    assert!(codes.contains(&"E0001".to_string()));
+    assert!(codes.contains(&"W0001".to_string()));
+    for code in codes {
+        assert!(
+            find_error_code(&code).is_some(),
+            "`{code}` has no explanation"
+        );
+    }
+}
+
+#[test]
+fn lists_codes_in_order_with_examples() {
+    assert!(ERROR_CODES.is_sorted_by_key(|code| code.code));
+    for code in ERROR_CODES {
+        assert!(
+            code.explanation.contains("```ginto\n"),
+            "`{}` has no example",
+            code.code
+        );
+        assert!(code.summary().ends_with('.'), "{}", code.summary());
+    }
+    let code = find_error_code("e0004").unwrap();
+    assert_eq!(code.code, "E0004");
+    assert_eq!(
+        code.summary(),
+        "A line is indented less than the line before it, but not as much as any block it could close."
+    );
+}
@@ -0,0 +1,62 @@
+use ginto_diag::{
+    BytePos, Diagnostic, Emitter, FileId, Label, PlainDiagnosticRenderer, Severity, SourceManager,
+    Span,
+};
+
+fn at(file: FileId, start: usize, message: &str) -> Diagnostic {
+    Diagnostic::error(message).with_label(Label::primary(
+        file,
+        Span::new(BytePos(start), BytePos(start + 1)),
+    ))
+}
+
+#[test]
+fn flushes_sorted_and_deduplicated() {
+    let mut sm = SourceManager::new();
+    let file = sm.add_file("main.ginto".to_string(), "let a = b + c\n".to_string());
+    let c = at(file, 12, "c");
+    let b = at(file, 8, "b");
+    let warning = Diagnostic::warning("no label");
+
+    let mut emitter = Emitter::new();
+    emitter.extend([c.clone(), b, c, warning]);
+    assert_eq!((emitter.errors(), emitter.warnings()), (2, 1));
+    assert_eq!(emitter.summary(), "2 errors, 1 warning");
+
+    let rendered = emitter.flush(&sm, &PlainDiagnosticRenderer);
+    let messages: Vec<_> = rendered
+        .lines()
+        .filter_map(|line| line.split_once(" : "))
+        .map(|(_, message)| message)
+        .collect();
+    assert_eq!(messages, ["no label", "b", "c"]);
+    assert_eq!(emitter.flush(&sm, &PlainDiagnosticRenderer), "");
+
+    emitter.clear();
+    assert_eq!(emitter.summary(), "0 errors, 0 warnings");
+}
+
+#[test]
+fn aborts_compilations_with_errors() {
+    let mut sm = SourceManager::new();
+    let file = sm.add_file("main.ginto".to_string(), "let a = b + c\n".to_string());
+    let mut emitter = Emitter::new();
+    emitter.emit(Diagnostic::warning("unused"));
+    assert_eq!(emitter.aborting(), None);
+
+    emitter.extend([at(file, 8, "b"), at(file, 8, "b")]);
+    let aborting = emitter.aborting().unwrap();
+    assert_eq!(aborting.severity, Severity::Error);
+    assert_eq!(
+        aborting.message,
+        "aborting due to 1 previous error; 1 warning emitted"
+    );
+
+    // the counts go on across flushes, until the emitter is cleared
+    emitter.flush(&sm, &PlainDiagnosticRenderer);
+    emitter.emit(at(file, 12, "c"));
+    assert_eq!(
+        emitter.aborting().unwrap().message,
+        "aborting due to 2 previous errors; 1 warning emitted"
+    );
+}
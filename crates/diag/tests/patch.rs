@@ -0,0 +1,63 @@
+use ginto_diag::{
+    Applicability, BytePos, Edit, FileId, PatchError, Span, Suggestion, apply_edits,
+    machine_applicable_edits,
+};
+
+fn span(start: usize, end: usize) -> Span {
+    Span::new(BytePos(start), BytePos(end))
+}
+
+#[test]
+fn applies_edits_given_in_any_order() {
+    let file = FileId(0);
+    let edits = [
+        Edit::delete(file, span(5, 13)),
+        Edit::insert(file, span(4, 5), "_"),
+        Edit::new(file, span(0, 3), "var"),
+    ];
+    assert_eq!(apply_edits("let x && true", &edits).unwrap(), "var _x");
+}
+
+#[test]
+fn rejects_overlapping_and_out_of_bounds_edits() {
+    let file = FileId(0);
+    let edits = [
+        Edit::delete(file, span(0, 4)),
+        Edit::insert(file, span(2, 2), "_"),
+    ];
+    assert_eq!(
+        apply_edits("abcdef", &edits),
+        Err(PatchError::Overlapping(span(0, 4), span(2, 2)))
+    );
+    let edits = [Edit::delete(file, span(4, 9))];
+    assert_eq!(
+        apply_edits("abcdef", &edits),
+        Err(PatchError::OutOfBounds(span(4, 9)))
+    );
+}
+
+#[test]
+fn collects_only_machine_applicable_suggestions_that_do_not_overlap() {
+    let suggestion = |start, end, applicability| {
+        Suggestion::new(
+            "fix it",
+            vec![Edit::delete(FileId(1), span(start, end))],
+            applicability,
+        )
+    };
+    let suggestions = [
+        suggestion(0, 2, Applicability::MachineApplicable),
+        suggestion(1, 3, Applicability::MachineApplicable),
+        suggestion(4, 6, Applicability::MaybeIncorrect),
+        suggestion(6, 8, Applicability::MachineApplicable),
+    ];
+    let edits = machine_applicable_edits(&suggestions);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[&FileId(1)],
+        vec![
+            Edit::delete(FileId(1), span(0, 2)),
+            Edit::delete(FileId(1), span(6, 8))
+        ]
+    );
+}
@@ -0,0 +1,254 @@
+use ginto_diag::{
+    AnsiDiagnosticRenderer, Applicability, BytePos, Diagnostic, DiagnosticRenderer, Edit,
+    JsonDiagnosticRenderer, Label, PlainDiagnosticRenderer, RendererOptions, Severity,
+    SourceManager, Span, Suggestion,
+};
+
+fn diagnostic(sm: &mut SourceManager) -> Diagnostic {
+    let file = sm.add_file(
+        "main.ginto".to_string(),
+        "fn main() -> u64:\n    missing\n".to_string(),
+    );
+    Diagnostic::new(Severity::Error)
+        .with_code("E0425")
+        .with_message("cannot find `missing` in this scope")
+        .with_label(
+            Label::primary(file, Span::new(BytePos(22), BytePos(29)))
+                .with_message("not found in this scope"),
+        )
+        .with_label(Label::secondary(file, Span::new(BytePos(3), BytePos(7))))
+        .with_note("a note")
+        .with_help("some help")
+        .with_suggestion(Suggestion::new(
+            "call it instead",
+            vec![Edit::new(
+                file,
+                Span::new(BytePos(22), BytePos(29)),
+                "main()",
+            )],
+            Applicability::MaybeIncorrect,
+        ))
+}
+
+/// `text` without its ANSI escapes.
+fn strip_escapes(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            chars.by_ref().find(|ch| *ch == 'm');
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+#[test]
+fn colors_only_what_plain_output_has() {
+    let mut sm = SourceManager::new();
+    let diagnostic = diagnostic(&mut sm);
+    let plain = PlainDiagnosticRenderer.render(&sm, diagnostic.clone());
+    let ansi = AnsiDiagnosticRenderer.render(&sm, diagnostic);
+    assert!(ansi.starts_with("\x1b[1;31merror[E0425]\x1b[0m"));
+    assert!(!plain.contains('\x1b'));
+    assert_eq!(strip_escapes(&ansi), plain);
+}
+
+#[test]
+fn options_pick_the_renderer() {
+    let mut sm = SourceManager::new();
+    let diagnostic = diagnostic(&mut sm);
+    let colored = RendererOptions { color: true }
+        .renderer()
+        .render(&sm, diagnostic.clone());
+    let plain = RendererOptions::default()
+        .renderer()
+        .render(&sm, diagnostic);
+    assert!(colored.contains('\x1b'));
+    assert!(!plain.contains('\x1b'));
+}
+
+#[test]
+fn shows_suggestions_applied() {
+    let mut sm = SourceManager::new();
+    let diagnostic = diagnostic(&mut sm);
+    let plain = PlainDiagnosticRenderer.render(&sm, diagnostic);
+    assert!(plain.ends_with(
+        "help: some help\n\
+         help: call it instead\n   \
+         2 |     main()\n     \
+         |     ~~~~~~\n"
+    ));
+}
+
+#[test]
+fn leaves_out_edits_overlapping_earlier_ones() {
+    let mut sm = SourceManager::new();
+    let source = "fn main() -> u64:\n    crate::helperFn(2)\n";
+    let file = sm.add_file("main.ginto".to_string(), source.to_string());
+    let span = |text: &str| {
+        let start = source.find(text).unwrap();
+        Span::from_range(start..start + text.len())
+    };
+    let diagnostic = Diagnostic::warning("function `helperFn` should have a snake case name")
+        .with_suggestion(Suggestion::new(
+            "convert the identifier to snake case",
+            vec![
+                Edit::new(file, span("helperFn"), "helper_fn"),
+                Edit::new(file, span("crate::helperFn"), "crate::helper_fn"),
+                Edit::new(file, span("(2)"), "(3)"),
+            ],
+            Applicability::MaybeIncorrect,
+        ));
+    for rendered in [
+        PlainDiagnosticRenderer.render(&sm, diagnostic.clone()),
+        JsonDiagnosticRenderer.render(&sm, diagnostic.clone()),
+    ] {
+        assert!(rendered.contains("crate::helper_fn(3)"), "{rendered}");
+    }
+}
+
+#[test]
+fn underlines_each_line_of_a_multiline_span() {
+    let mut sm = SourceManager::new();
+    let file = sm.add_file(
+        "main.ginto".to_string(),
+        "fn f(a: bool) -> u64:\n    if a:\n        1\n".to_string(),
+    );
+    // from `if` through the newline after `1`
+    let diagnostic = Diagnostic::error("`if` may be missing an `else` clause").with_label(
+        Label::primary(file, Span::new(BytePos(26), BytePos(42)))
+            .with_message("expected `u64`, found `()`"),
+    );
+    assert_eq!(
+        PlainDiagnosticRenderer.render(&sm, diagnostic),
+        "error : `if` may be missing an `else` clause\n \
+         --> main.ginto:2:5\n   \
+         2 |     if a:\n     \
+         |     ^^^^^\n   \
+         3 |         1\n     \
+         |         ^ expected `u64`, found `()`\n"
+    );
+}
+
+#[test]
+fn lines_up_carets_under_tabs_and_wide_characters() {
+    let mut sm = SourceManager::new();
+    let source = "\tlet s = \"日本😀\" + 名前\n";
+    let file = sm.add_file("main.ginto".to_string(), source.to_string());
+    let at = |text: &str| {
+        let start = source.find(text).unwrap();
+        Span::from_range(start..start + text.len())
+    };
+    let diagnostic = Diagnostic::error("cannot add")
+        .with_label(Label::primary(file, at("名前")).with_message("not a string"))
+        .with_label(Label::secondary(file, at("\"日本😀\"")));
+    let rendered = PlainDiagnosticRenderer.render(&sm, diagnostic);
+    let lines: Vec<_> = rendered.lines().collect();
+    assert_eq!(lines[2], "   1 |     let s = \"日本😀\" + 名前");
+    assert_eq!(lines[3], "     |                        ^^^^ not a string");
+    assert_eq!(lines[6], "     |             ^^^^^^^^");
+}
+
+#[test]
+fn renders_json_as_rustc_does() {
+    let mut sm = SourceManager::new();
+    let diagnostic = diagnostic(&mut sm);
+    let rendered = PlainDiagnosticRenderer.render(&sm, diagnostic.clone());
+    let span = |start: usize, end: usize, line: usize, text: &str, cols: (usize, usize)| {
+        format!(
+            "\"file_name\":\"main.ginto\",\"byte_start\":{start},\"byte_end\":{end},\
+             \"line_start\":{line},\"line_end\":{line},\"column_start\":{},\"column_end\":{},",
+            cols.0, cols.1
+        ) + &format!(
+            "\"is_primary\":{},\"text\":[{{\"text\":\"{text}\",\"highlight_start\":{},\
+             \"highlight_end\":{}}}]",
+            start != 3,
+            cols.0,
+            cols.1
+        )
+    };
+    let expected = format!(
+        "{{\"$message_type\":\"diagnostic\",\"message\":\"cannot find `missing` in this scope\",\
+         \"code\":{{\"code\":\"E0425\",\"explanation\":null}},\"level\":\"error\",\
+         \"spans\":[{{{},\"label\":\"not found in this scope\",\"suggested_replacement\":null,\
+         \"suggestion_applicability\":null,\"expansion\":null}},\
+         {{{},\"label\":null,\"suggested_replacement\":null,\"suggestion_applicability\":null,\
+         \"expansion\":null}}],\
+         \"children\":[{{\"message\":\"a note\",\"code\":null,\"level\":\"note\",\"spans\":[],\
+         \"children\":[],\"rendered\":null}},\
+         {{\"message\":\"some help\",\"code\":null,\"level\":\"help\",\"spans\":[],\
+         \"children\":[],\"rendered\":null}},\
+         {{\"message\":\"call it instead\",\"code\":null,\"level\":\"help\",\
+         \"spans\":[{{{},\"label\":null,\"suggested_replacement\":\"main()\",\
+         \"suggestion_applicability\":\"MaybeIncorrect\",\"expansion\":null}}],\
+         \"children\":[],\"rendered\":null}}],\
+         \"rendered\":\"{}\"}}",
+        span(22, 29, 2, "    missing", (5, 12)),
+        span(3, 7, 1, "fn main() -> u64:", (4, 8)),
+        span(22, 29, 2, "    missing", (5, 12)),
+        rendered.replace('\n', "\\n"),
+    );
+    assert_eq!(JsonDiagnosticRenderer.render(&sm, diagnostic), expected);
+}
+
+#[test]
+fn counts_json_columns_in_characters() {
+    let mut sm = SourceManager::new();
+    let file = sm.add_file(
+        "main.ginto".to_string(),
+        "let s = \"日本\" + x\n".to_string(),
+    );
+    let diagnostic = Diagnostic::new(Severity::Error)
+        .with_code("E0001")
+        .with_message("mismatched types")
+        .with_label(Label::primary(file, Span::new(BytePos(19), BytePos(20))));
+    let json = JsonDiagnosticRenderer.render(&sm, diagnostic);
+    assert!(
+        json.contains(
+            "\"byte_start\":19,\"byte_end\":20,\"line_start\":1,\"line_end\":1,\
+                       \"column_start\":16,\"column_end\":17,"
+        ),
+        "{json}"
+    );
+    // known codes carry their explanation
+    assert!(
+        json.contains("\"code\":{\"code\":\"E0001\",\"explanation\":\"A character"),
+        "{json}"
+    );
+}
+
+#[test]
+fn points_at_character_columns() {
+    let mut sm = SourceManager::new();
+    let file = sm.add_file(
+        "main.ginto".to_string(),
+        "let s = \"日本\" + x\r\n".to_string(),
+    );
+    let diagnostic = Diagnostic::error("mismatched types")
+        .with_label(Label::primary(file, Span::new(BytePos(19), BytePos(20))));
+    assert_eq!(
+        PlainDiagnosticRenderer.render(&sm, diagnostic),
+        "error : mismatched types\n \
+         --> main.ginto:1:16\n   \
+         1 | let s = \"日本\" + x\n     \
+         |                  ^\n"
+    );
+}
+
+#[test]
+fn renders_errors_and_warnings_built_from_their_message() {
+    let sm = SourceManager::new();
+    assert_eq!(
+        PlainDiagnosticRenderer.render(&sm, Diagnostic::error("cannot read `main.ginto`")),
+        PlainDiagnosticRenderer.render(
+            &sm,
+            Diagnostic::new(Severity::Error).with_message("cannot read `main.ginto`")
+        )
+    );
+    assert_eq!(
+        Diagnostic::warning("unused variable").severity,
+        Severity::Warning
+    );
+}
@@ -0,0 +1,123 @@
+use std::io;
+
+use ginto_diag::{BytePos, ColumnUnit, DiagnosticConvertible, LineColumn, SourceManager};
+
+#[test]
+fn adds_files_read_from_disk() {
+    let path = std::env::temp_dir().join(format!("ginto-source-{}.ginto", std::process::id()));
+    std::fs::write(&path, "fn main():\n    ()\n").unwrap();
+    let mut sm = SourceManager::new();
+    let file_id = sm.add_file_from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let file = sm.get_file(file_id).unwrap();
+    assert_eq!(file.name, path.display().to_string());
+    assert_eq!(file.source, "fn main():\n    ()\n");
+}
+
+#[test]
+fn reads_each_file_once() {
+    let dir = std::env::temp_dir().join(format!("ginto-paths-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("main.ginto");
+    let respelled = dir.join(".").join("main.ginto");
+    std::fs::write(&path, "a\n").unwrap();
+    let mut sm = SourceManager::new();
+    let file_id = sm.add_file_from_path(&path).unwrap();
+    assert_eq!(sm.add_file_from_path(&respelled).unwrap(), file_id);
+    assert_eq!(sm.file_id_by_path(&respelled), Some(file_id));
+
+    std::fs::write(&path, "b\n").unwrap();
+    let updated = sm.set_file_from_path(&respelled).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(updated, file_id);
+    assert_eq!(sm.files().count(), 1);
+    let file = sm.get_file(file_id).unwrap();
+    assert_eq!(file.name, path.display().to_string());
+    assert_eq!(file.source, "b\n");
+}
+
+#[test]
+fn reports_files_it_cannot_read() {
+    let mut sm = SourceManager::new();
+    let err = sm.add_file_from_path("does/not/exist.ginto").unwrap_err();
+    assert_eq!(err.kind, io::ErrorKind::NotFound);
+    assert!(
+        err.into_diagnostic()
+            .message
+            .starts_with("couldn't read `does/not/exist.ginto`: ")
+    );
+}
+
+#[test]
+fn updates_files_in_place() {
+    let mut sm = SourceManager::new();
+    let other = sm.add_file("other.ginto".to_string(), String::new());
+    let file_id = sm.set_file("main.ginto".to_string(), "a\n".to_string());
+    assert_eq!(sm.file_id("main.ginto"), Some(file_id));
+
+    let updated = sm.set_file("main.ginto".to_string(), "a\nb\nc\n".to_string());
+    assert_eq!(updated, file_id);
+    assert_ne!(updated, other);
+    assert_eq!(sm.files().count(), 2);
+    let file = sm.get_file(file_id).unwrap();
+    assert_eq!(file.source, "a\nb\nc\n");
+    assert_eq!(file.line_col(BytePos(4)).line, 3);
+}
+
+#[test]
+fn converts_columns_between_units() {
+    let mut sm = SourceManager::new();
+    let file_id = sm.add_file("main.ginto".to_string(), "a\n\"é😀\" x\n".to_string());
+    let file = sm.get_file(file_id).unwrap();
+    // `x` is after 1 + 2 + 4 + 1 + 1 bytes, 5 characters and 6 UTF-16 units
+    let x = BytePos(11);
+    let at = |line, col| LineColumn { line, col };
+    assert_eq!(file.line_col(x), at(2, 10));
+    assert_eq!(file.line_col_chars(x), at(2, 6));
+    assert_eq!(file.line_col_utf16(x), at(2, 7));
+    for unit in [ColumnUnit::Bytes, ColumnUnit::Chars, ColumnUnit::Utf16] {
+        assert_eq!(
+            file.position_to_byte_pos(file.line_col_in(x, unit), unit),
+            x
+        );
+    }
+    // inside the emoji, past the end of the line and past the end of the file
+    assert_eq!(
+        file.position_to_byte_pos(at(2, 4), ColumnUnit::Utf16),
+        BytePos(5)
+    );
+    assert_eq!(
+        file.position_to_byte_pos(at(2, 99), ColumnUnit::Chars),
+        BytePos(12)
+    );
+    assert_eq!(
+        file.position_to_byte_pos(at(9, 1), ColumnUnit::Bytes),
+        BytePos(13)
+    );
+}
+
+#[test]
+fn ends_lines_before_crlf() {
+    let mut sm = SourceManager::new();
+    let file_id = sm.add_file("main.ginto".to_string(), "é = 1\r\nx\r\n".to_string());
+    let file = sm.get_file(file_id).unwrap();
+    let at = |line, col| LineColumn { line, col };
+    assert_eq!(file.line_text(1), "é = 1");
+    // past the end of the line is before its `\r\n`
+    for unit in [ColumnUnit::Bytes, ColumnUnit::Chars, ColumnUnit::Utf16] {
+        assert_eq!(file.position_to_byte_pos(at(1, 99), unit), BytePos(6));
+    }
+    assert_eq!(
+        file.position_to_byte_pos(at(1, 5), ColumnUnit::Chars),
+        BytePos(5)
+    );
+    assert_eq!(
+        file.position_to_byte_pos(at(2, 2), ColumnUnit::Utf16),
+        BytePos(9)
+    );
+    assert_eq!(
+        file.position_to_byte_pos(at(2, 3), ColumnUnit::Utf16),
+        BytePos(9)
+    );
+}
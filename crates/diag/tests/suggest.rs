@@ -0,0 +1,21 @@
+use ginto_diag::{edit_distance, find_similar};
+
+#[test]
+fn counts_swaps_as_one_edit() {
+    assert_eq!(edit_distance("let", "let"), 0);
+    assert_eq!(edit_distance("lte", "let"), 1);
+    assert_eq!(edit_distance("retrun", "return"), 1);
+    assert_eq!(edit_distance("", "fn"), 2);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+}
+
+#[test]
+fn finds_the_closest_name_within_reach() {
+    let names = ["total", "totals", "limit"];
+    assert_eq!(find_similar("totl", names), Some("total"));
+    assert_eq!(find_similar("limt", names), Some("limit"));
+    assert_eq!(find_similar("qqqq", names), None);
+    assert_eq!(find_similar("total", names), Some("totals"));
+    assert_eq!(find_similar("ab", ["ac", "aa"]), Some("aa"));
+    assert_eq!(find_similar("y", ["x", "yy"]), None);
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use ginto_diag::{Interner, Symbol, sym};
+
+#[test]
+fn interns_each_name_once() {
+    let a = Symbol::intern("main");
+    assert_eq!(a, Symbol::intern(&String::from("main")));
+    assert_ne!(a, Symbol::intern("mainly"));
+    assert_eq!(a.with(str::len), 4);
+    assert_eq!(a, "main");
+    assert_eq!(format!("{a} {a:?}"), "main \"main\"");
+}
+
+#[test]
+fn orders_by_when_names_were_interned() {
+    let _session = Arc::new(Interner::new()).enter();
+    let zebra = Symbol::intern("zebra");
+    let aardvark = Symbol::intern("aardvark");
+    let mut symbols = [aardvark, zebra];
+    symbols.sort();
+    assert_eq!(symbols, [zebra, aardvark]);
+}
+
+#[test]
+fn looks_up_in_a_table_of_its_own() {
+    let interner = Interner::new();
+    let x = interner.intern("x");
+    let y = interner.intern("y");
+    assert_eq!(interner.intern("x"), x);
+    assert_eq!((interner.lookup(x), interner.lookup(y)), ("x", "y"));
+    assert_eq!(interner.lookup(sym::SELF), "self");
+    assert_eq!(interner.intern("main"), sym::MAIN);
+}
+
+#[test]
+fn keeps_names_of_many_chunks() {
+    let interner = Interner::new();
+    let symbols: Vec<_> = (0..5000).map(|i| interner.intern(&i.to_string())).collect();
+    for (i, symbol) in symbols.into_iter().enumerate() {
+        assert_eq!(interner.lookup(symbol), i.to_string());
+    }
+}
+
+#[test]
+fn interns_in_the_session_entered() {
+    let outer = Interner::current();
+    let main = Symbol::intern("main");
+    let session = Arc::new(Interner::new());
+    {
+        let _session = session.clone().enter();
+        Symbol::intern("other");
+        assert_eq!(Symbol::intern("main").to_string(), "main");
+        assert!(Arc::ptr_eq(&Interner::current(), &session));
+    }
+    assert_eq!(main.to_string(), "main");
+    assert!(Arc::ptr_eq(&Interner::current(), &outer));
+    assert_eq!(session.len(), Interner::new().len() + 1);
+}
+
+#[test]
+fn shares_sessions_between_threads() {
+    let main = Symbol::intern("main");
+    let interner = Interner::current();
+    let other = std::thread::spawn(move || {
+        let _session = interner.enter();
+        assert_eq!(main.to_string(), "main");
+        Symbol::intern("other")
+    })
+    .join()
+    .unwrap();
+    assert_eq!(other, "other");
+}
@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+
+use ginto_diag::{FileId, SourceManager, Span};
+use ginto_resolve::{DefId, DefKind, ModuleId, ModuleTree, Resolutions};
+use ginto_syntax::{
+    Expr, ExprKind, Func, ItemKind, doc_comment, func_signature, method_signature,
+    visit::{self, Visitor},
+};
+
+/// The documentation of a program: its modules, what each declares, and
+/// which functions refer to which.
+///
+/// The documentation of an item is its signature, the comment above it and
+/// its place in the program.
+#[derive(Debug, Clone)]
+pub struct Docs {
+    /// The modules, each before the modules it declares.
+    pub modules: Vec<ModuleDoc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleDoc {
+    /// The module's path, like `crate::math`.
+    pub path: String,
+    pub submodules: Vec<Link>,
+    pub items: Vec<ItemDoc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemDocKind {
+    Function,
+    Trait,
+    Impl,
+    Method,
+    Import,
+}
+
+impl ItemDocKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemDocKind::Function => "function",
+            ItemDocKind::Trait => "trait",
+            ItemDocKind::Impl => "impl",
+            ItemDocKind::Method => "method",
+            ItemDocKind::Import => "import",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemDoc {
+    pub kind: ItemDocKind,
+    /// The item's name, or `Trait for Type` for an `impl`.
+    pub name: String,
+    pub is_pub: bool,
+    /// The item as declared, without its body.
+    pub signature: String,
+    /// The comments on the lines right above the item, see `doc_comment`.
+    pub doc: Option<String>,
+    /// The id of the item on its module's page.
+    pub anchor: String,
+    /// The trait an `impl` implements, or the item a `use` imports.
+    pub target: Option<Link>,
+    /// The functions the item's body refers to, in the order they first
+    /// appear.
+    pub refers_to: Vec<Link>,
+    /// The functions and methods whose bodies refer to this function.
+    pub referred_to_by: Vec<Link>,
+    /// The methods of a trait or an `impl`.
+    pub methods: Vec<ItemDoc>,
+}
+
+/// Where a module or an item is documented.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The path of the module or item, like `crate::math::add`.
+    pub path: String,
+    /// The path of the module whose page documents it.
+    pub module: String,
+    /// The id of the item on that page, `None` for a module.
+    pub anchor: Option<String>,
+}
+
+/// Documents the modules of `tree`, whose files are in `sm` and whose
+/// names `resolutions` resolved.
+pub fn document(sm: &SourceManager, tree: &ModuleTree, resolutions: &Resolutions) -> Docs {
+    let links = links(tree, resolutions);
+    let mut modules: Vec<_> = tree
+        .modules()
+        .map(|(id, _)| ModuleDocumenter::new(sm, tree, id, resolutions, &links).module())
+        .collect();
+
+    let mut referrers: HashMap<String, Vec<Link>> = HashMap::new();
+    for module in &modules {
+        for item in module.items.iter().flat_map(with_methods) {
+            let referrer = module.link(item);
+            for link in &item.refers_to {
+                referrers
+                    .entry(link.path.clone())
+                    .or_default()
+                    .push(referrer.clone());
+            }
+        }
+    }
+    for module in &mut modules {
+        let path = module.path.clone();
+        for item in &mut module.items {
+            if item.kind == ItemDocKind::Function
+                && let Some(referrers) = referrers.remove(&format!("{path}::{}", item.name))
+            {
+                item.referred_to_by = referrers;
+            }
+        }
+    }
+    Docs { modules }
+}
+
+fn with_methods(item: &ItemDoc) -> impl Iterator<Item = &ItemDoc> {
+    std::iter::once(item).chain(&item.methods)
+}
+
+impl ModuleDoc {
+    /// A link to `item`, one of the items of this module or their methods.
+    fn link(&self, item: &ItemDoc) -> Link {
+        Link {
+            path: format!("{}::{}", self.path, item.name),
+            module: self.path.clone(),
+            anchor: Some(item.anchor.clone()),
+        }
+    }
+}
+
+/// A link to every module, free function and trait of `tree`, by the
+/// definition of its name.
+fn links(tree: &ModuleTree, resolutions: &Resolutions) -> HashMap<DefId, Link> {
+    let mut links = HashMap::new();
+    for (id, module) in tree.modules() {
+        let path = tree.path(id);
+        let file_id = module.program.file_id;
+        for item in &module.program.items {
+            let (name, link) = match &item.inner {
+                ItemKind::Func(func) => (
+                    &func.name,
+                    item_link(&path, &func.name.inner.to_string(), "fn"),
+                ),
+                ItemKind::Trait(decl) => (
+                    &decl.name,
+                    item_link(&path, &decl.name.inner.to_string(), "trait"),
+                ),
+                ItemKind::Mod(decl) => {
+                    let child = format!("{path}::{}", decl.name.inner);
+                    let link = Link {
+                        path: child.clone(),
+                        module: child,
+                        anchor: None,
+                    };
+                    (&decl.name, link)
+                }
+                _ => continue,
+            };
+            if let Some(def) = resolutions.def_at(file_id, name.span) {
+                links.insert(def, link);
+            }
+        }
+    }
+    links
+}
+
+fn item_link(module: &str, name: &str, kind: &str) -> Link {
+    Link {
+        path: format!("{module}::{name}"),
+        module: module.to_string(),
+        anchor: Some(anchor(&[kind, name])),
+    }
+}
+
+/// An anchor of `parts` joined by `.`, with the characters of each other
+/// than letters, digits and `_` replaced by `-`.
+fn anchor(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .map(|part| {
+            part.chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || c == '_' {
+                        c
+                    } else {
+                        '-'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+struct ModuleDocumenter<'a> {
+    tree: &'a ModuleTree,
+    id: ModuleId,
+    file_id: FileId,
+    /// The source of the module's file.
+    source: &'a str,
+    resolutions: &'a Resolutions,
+    links: &'a HashMap<DefId, Link>,
+}
+
+impl<'a> ModuleDocumenter<'a> {
+    fn new(
+        sm: &'a SourceManager,
+        tree: &'a ModuleTree,
+        id: ModuleId,
+        resolutions: &'a Resolutions,
+        links: &'a HashMap<DefId, Link>,
+    ) -> Self {
+        let file_id = tree.module(id).program.file_id;
+        Self {
+            tree,
+            id,
+            file_id,
+            source: sm.get_file(file_id).map_or("", |file| &file.source),
+            resolutions,
+            links,
+        }
+    }
+
+    fn module(&self) -> ModuleDoc {
+        let module = self.tree.module(self.id);
+        let mut submodules: Vec<_> = module
+            .children
+            .values()
+            .map(|&child| {
+                let path = self.tree.path(child);
+                Link {
+                    path: path.clone(),
+                    module: path,
+                    anchor: None,
+                }
+            })
+            .collect();
+        // symbols order by when they were interned
+        submodules.sort_by(|a, b| a.path.cmp(&b.path));
+        let items = module
+            .program
+            .items
+            .iter()
+            .filter_map(|item| match &item.inner {
+                ItemKind::Func(func) => Some(self.func(func, ItemDocKind::Function, &["fn"])),
+                ItemKind::Trait(decl) => {
+                    let name = &decl.name.inner.to_string();
+                    let methods = decl
+                        .methods
+                        .iter()
+                        .map(|method| ItemDoc {
+                            kind: ItemDocKind::Method,
+                            name: format!("{name}::{}", method.inner.name.inner),
+                            is_pub: decl.is_pub,
+                            signature: method_signature(&method.inner),
+                            doc: self.doc(method.inner.name.span),
+                            anchor: anchor(&["trait", name, &method.inner.name.inner.to_string()]),
+                            target: None,
+                            refers_to: Vec::new(),
+                            referred_to_by: Vec::new(),
+                            methods: Vec::new(),
+                        })
+                        .collect();
+                    Some(ItemDoc {
+                        kind: ItemDocKind::Trait,
+                        name: name.clone(),
+                        is_pub: decl.is_pub,
+                        signature: format!("{}trait {name}", visibility(decl.is_pub)),
+                        doc: self.doc(decl.name.span),
+                        anchor: anchor(&["trait", name]),
+                        target: None,
+                        refers_to: Vec::new(),
+                        referred_to_by: Vec::new(),
+                        methods,
+                    })
+                }
+                ItemKind::Impl(decl) => {
+                    let trait_name = &decl.trait_name.inner.to_string();
+                    let self_ty = decl.self_ty.inner.to_string();
+                    let name = format!("{trait_name} for {self_ty}");
+                    let methods = decl
+                        .methods
+                        .iter()
+                        .map(|method| {
+                            let mut doc = self.func(
+                                method,
+                                ItemDocKind::Method,
+                                &["impl", trait_name, &self_ty],
+                            );
+                            doc.name = format!("<{self_ty} as {trait_name}>::{}", doc.name);
+                            doc
+                        })
+                        .collect();
+                    Some(ItemDoc {
+                        kind: ItemDocKind::Impl,
+                        signature: format!("impl {name}"),
+                        doc: self.doc(decl.trait_name.span),
+                        name,
+                        is_pub: false,
+                        anchor: anchor(&["impl", trait_name, &self_ty]),
+                        target: self.link_at(decl.trait_name.span),
+                        refers_to: Vec::new(),
+                        referred_to_by: Vec::new(),
+                        methods,
+                    })
+                }
+                ItemKind::Use(decl) => {
+                    let name = &decl.name().inner.to_string();
+                    let path: Vec<_> = decl.path.iter().map(|seg| seg.inner.to_string()).collect();
+                    Some(ItemDoc {
+                        kind: ItemDocKind::Import,
+                        name: name.clone(),
+                        is_pub: decl.is_pub,
+                        signature: format!("{}use {}", visibility(decl.is_pub), path.join("::")),
+                        doc: self.doc(decl.name().span),
+                        anchor: anchor(&["use", name]),
+                        target: self.link_at(decl.name().span),
+                        refers_to: Vec::new(),
+                        referred_to_by: Vec::new(),
+                        methods: Vec::new(),
+                    })
+                }
+                ItemKind::Mod(_) | ItemKind::StaticAssert(_) => None,
+            })
+            .collect();
+        ModuleDoc {
+            path: self.tree.path(self.id),
+            submodules,
+            items,
+        }
+    }
+
+    /// Documents `func`, whose anchor is its name after `anchor_prefix`.
+    fn func(&self, func: &Func, kind: ItemDocKind, anchor_prefix: &[&str]) -> ItemDoc {
+        let mut refs = RefFinder {
+            documenter: self,
+            found: Vec::new(),
+        };
+        visit::walk_func(&mut refs, func);
+        let name = func.name.inner.to_string();
+        let mut parts = anchor_prefix.to_vec();
+        parts.push(&name);
+        ItemDoc {
+            kind,
+            name: name.clone(),
+            is_pub: func.is_pub,
+            signature: func_signature(func),
+            doc: self.doc(func.name.span),
+            anchor: anchor(&parts),
+            target: None,
+            refers_to: refs.found,
+            referred_to_by: Vec::new(),
+            methods: Vec::new(),
+        }
+    }
+
+    /// The doc comment of the item whose first line holds `span`.
+    fn doc(&self, span: Span) -> Option<String> {
+        doc_comment(self.source, span.start)
+    }
+
+    /// The link to what the name at `span` of the module's file refers to.
+    fn link_at(&self, span: Span) -> Option<Link> {
+        let def = self.resolutions.resolve(self.file_id, span)?;
+        self.links.get(&def).cloned()
+    }
+}
+
+fn visibility(is_pub: bool) -> &'static str {
+    if is_pub { "pub " } else { "" }
+}
+
+/// Collects the functions an expression refers to by name.
+struct RefFinder<'a, 'b> {
+    documenter: &'b ModuleDocumenter<'a>,
+    found: Vec<Link>,
+}
+
+impl Visitor for RefFinder<'_, '_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Var(_) | ExprKind::Path(_) = &expr.inner
+            && let Some(def) = self
+                .documenter
+                .resolutions
+                .resolve(self.documenter.file_id, expr.span)
+            && self.documenter.resolutions.def(def).kind == DefKind::Func
+            && let Some(link) = self.documenter.links.get(&def)
+            && !self.found.contains(link)
+        {
+            self.found.push(link.clone());
+        }
+        visit::walk_expr(self, expr);
+    }
+}
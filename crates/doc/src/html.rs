@@ -0,0 +1,150 @@
+use std::fmt::Write;
+
+use crate::{Docs, ItemDoc, ItemDocKind, Link, ModuleDoc};
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; }
+pre { background: #f4f4f4; padding: 0.5em; overflow-x: auto; }
+section { margin-bottom: 1.5em; }
+section section { margin-left: 2em; }
+nav, .refs { font-size: 0.9em; }
+";
+
+/// The file name of the page documenting the module at `path`:
+/// `index.html` for the root and, say, `math.vec.html` for `crate::math::vec`.
+pub fn page_name(path: &str) -> String {
+    match path.strip_prefix("crate::") {
+        Some(rest) => format!("{}.html", rest.replace("::", ".")),
+        None => "index.html".to_string(),
+    }
+}
+
+/// The pages of `docs`, a page per module, by file name. Pages link to
+/// each other by name, so they belong in the same directory.
+pub fn render_html(docs: &Docs) -> Vec<(String, String)> {
+    docs.modules
+        .iter()
+        .map(|module| (page_name(&module.path), page(module)))
+        .collect()
+}
+
+fn page(module: &ModuleDoc) -> String {
+    let mut out = String::new();
+    let title = escape(&module.path);
+    writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>\n{STYLE}</style>\n</head>\n<body>"
+    )
+    .expect("writing to a string can't fail");
+    out.push_str(&breadcrumbs(&module.path));
+    writeln!(out, "<h1>Module <code>{title}</code></h1>").expect("writing to a string can't fail");
+
+    if !module.submodules.is_empty() {
+        out.push_str("<h2>Modules</h2>\n<ul>\n");
+        for link in &module.submodules {
+            writeln!(out, "<li>{}</li>", link_html(link)).expect("writing to a string can't fail");
+        }
+        out.push_str("</ul>\n");
+    }
+    let sections = [
+        (ItemDocKind::Import, "Imports"),
+        (ItemDocKind::Function, "Functions"),
+        (ItemDocKind::Trait, "Traits"),
+        (ItemDocKind::Impl, "Implementations"),
+    ];
+    for (kind, heading) in sections {
+        let items: Vec<_> = module
+            .items
+            .iter()
+            .filter(|item| item.kind == kind)
+            .collect();
+        if items.is_empty() {
+            continue;
+        }
+        writeln!(out, "<h2>{heading}</h2>").expect("writing to a string can't fail");
+        for item in items {
+            self::item(&mut out, item);
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Links to the module at `path` and each module above it.
+fn breadcrumbs(path: &str) -> String {
+    let segments: Vec<_> = path.split("::").collect();
+    let links: Vec<_> = (1..=segments.len())
+        .map(|len| {
+            let module = segments[..len].join("::");
+            format!(
+                "<a href=\"{}\">{}</a>",
+                page_name(&module),
+                escape(segments[len - 1])
+            )
+        })
+        .collect();
+    format!("<nav>{}</nav>\n", links.join("::"))
+}
+
+fn item(out: &mut String, item: &ItemDoc) {
+    writeln!(
+        out,
+        "<section id=\"{}\">\n<pre><code>{}</code></pre>",
+        item.anchor,
+        escape(&item.signature)
+    )
+    .expect("writing to a string can't fail");
+    if let Some(doc) = &item.doc {
+        // blank lines separate paragraphs
+        for paragraph in doc.split("\n\n").filter(|p| !p.trim().is_empty()) {
+            writeln!(out, "<p>{}</p>", escape(paragraph)).expect("writing to a string can't fail");
+        }
+    }
+    if let Some(target) = &item.target {
+        let what = match item.kind {
+            ItemDocKind::Impl => "Implements",
+            _ => "Imports",
+        };
+        writeln!(out, "<p class=\"refs\">{what} {}</p>", link_html(target))
+            .expect("writing to a string can't fail");
+    }
+    refs(out, "Refers to", &item.refers_to);
+    refs(out, "Referred to by", &item.referred_to_by);
+    for method in &item.methods {
+        self::item(out, method);
+    }
+    out.push_str("</section>\n");
+}
+
+fn refs(out: &mut String, what: &str, links: &[Link]) {
+    if links.is_empty() {
+        return;
+    }
+    let links: Vec<_> = links.iter().map(link_html).collect();
+    writeln!(out, "<p class=\"refs\">{what} {}</p>", links.join(", "))
+        .expect("writing to a string can't fail");
+}
+
+fn link_html(link: &Link) -> String {
+    let page = page_name(&link.module);
+    let href = match &link.anchor {
+        Some(anchor) => format!("{page}#{anchor}"),
+        None => page,
+    };
+    format!("<a href=\"{href}\"><code>{}</code></a>", escape(&link.path))
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
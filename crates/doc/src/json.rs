@@ -0,0 +1,102 @@
+use std::fmt::Write;
+
+use crate::{Docs, ItemDoc, Link};
+
+/// `docs` as a single line of JSON, for renderers of their own:
+/// `{"modules":[{"path":…,"submodules":[…],"items":[…]}]}`, with links
+/// as `{"path":…,"module":…,"anchor":…}`.
+pub fn render_json(docs: &Docs) -> String {
+    let mut out = String::from("{\"modules\":[");
+    for (i, module) in docs.modules.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"path\":");
+        json_str(&mut out, &module.path);
+        out.push_str(",\"submodules\":");
+        links(&mut out, &module.submodules);
+        out.push_str(",\"items\":");
+        items(&mut out, &module.items);
+        out.push('}');
+    }
+    out.push_str("]}\n");
+    out
+}
+
+fn items(out: &mut String, items: &[ItemDoc]) {
+    out.push('[');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"kind\":");
+        json_str(out, item.kind.as_str());
+        out.push_str(",\"name\":");
+        json_str(out, &item.name);
+        write!(out, ",\"pub\":{},\"signature\":", item.is_pub)
+            .expect("writing to a string can't fail");
+        json_str(out, &item.signature);
+        out.push_str(",\"doc\":");
+        match &item.doc {
+            Some(doc) => json_str(out, doc),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"anchor\":");
+        json_str(out, &item.anchor);
+        out.push_str(",\"target\":");
+        match &item.target {
+            Some(target) => link(out, target),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"refers_to\":");
+        links(out, &item.refers_to);
+        out.push_str(",\"referred_to_by\":");
+        links(out, &item.referred_to_by);
+        out.push_str(",\"methods\":");
+        self::items(out, &item.methods);
+        out.push('}');
+    }
+    out.push(']');
+}
+
+fn links(out: &mut String, links: &[Link]) {
+    out.push('[');
+    for (i, l) in links.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        link(out, l);
+    }
+    out.push(']');
+}
+
+fn link(out: &mut String, link: &Link) {
+    out.push_str("{\"path\":");
+    json_str(out, &link.path);
+    out.push_str(",\"module\":");
+    json_str(out, &link.module);
+    out.push_str(",\"anchor\":");
+    match &link.anchor {
+        Some(anchor) => json_str(out, anchor),
+        None => out.push_str("null"),
+    }
+    out.push('}');
+}
+
+fn json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            ch if ch.is_control() => {
+                write!(out, "\\u{:04x}", ch as u32).expect("writing to a string can't fail")
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,7 @@
+mod docs;
+mod html;
+mod json;
+
+pub use docs::*;
+pub use html::*;
+pub use json::*;
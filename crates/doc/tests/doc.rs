@@ -0,0 +1,137 @@
+use ginto_diag::SourceManager;
+use ginto_doc::{Docs, ItemDocKind, document, page_name, render_html, render_json};
+use ginto_driver::Driver;
+
+/// Documents the program of `files`, the first its root, written to a
+/// directory of their own.
+fn docs(name: &str, files: &[(&str, &str)]) -> Docs {
+    let dir = std::env::temp_dir().join(format!("ginto-doc-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, source) in files {
+        std::fs::write(dir.join(file), source).unwrap();
+    }
+    let mut sm = SourceManager::new();
+    let root = sm.add_file_from_path(dir.join(files[0].0)).unwrap();
+    let (program, diagnostics) = Driver::new().check(&mut sm, root);
+    let program = program.unwrap_or_else(|| panic!("{diagnostics:#?}"));
+    std::fs::remove_dir_all(dir).unwrap();
+    document(&sm, &program.tree, &program.resolutions)
+}
+
+const MAIN: &str = "mod math
+use math::add
+
+trait Show:
+    fn show(self) -> u64
+
+impl Show for u64:
+    fn show(self) -> u64:
+        add(self, 1)
+
+fn main() -> u64:
+    add(1, twice(2))
+
+fn twice<T>(x: T) -> T:
+    x
+";
+
+const MATH: &str = "pub fn add(a: u64, b: u64) -> u64:\n    a + b\n";
+
+#[test]
+fn documents_modules_signatures_and_references() {
+    let docs = docs("refs", &[("main.ginto", MAIN), ("math.ginto", MATH)]);
+    let paths: Vec<_> = docs.modules.iter().map(|m| m.path.as_str()).collect();
+    assert_eq!(paths, ["crate", "crate::math"]);
+    let root = &docs.modules[0];
+    assert_eq!(root.submodules[0].path, "crate::math");
+
+    let signatures: Vec<_> = root
+        .items
+        .iter()
+        .map(|item| (item.kind, item.signature.as_str()))
+        .collect();
+    assert_eq!(
+        signatures,
+        [
+            (ItemDocKind::Import, "use math::add"),
+            (ItemDocKind::Trait, "trait Show"),
+            (ItemDocKind::Impl, "impl Show for u64"),
+            (ItemDocKind::Function, "fn main() -> u64"),
+            (ItemDocKind::Function, "fn twice<T>(x: T) -> T"),
+        ]
+    );
+    assert_eq!(
+        root.items[0].target.as_ref().unwrap().path,
+        "crate::math::add"
+    );
+    assert_eq!(root.items[2].target.as_ref().unwrap().path, "crate::Show");
+
+    let main = &root.items[3];
+    let refers_to: Vec<_> = main.refers_to.iter().map(|l| l.path.as_str()).collect();
+    assert_eq!(refers_to, ["crate::math::add", "crate::twice"]);
+
+    let add = &docs.modules[1].items[0];
+    assert_eq!(add.signature, "pub fn add(a: u64, b: u64) -> u64");
+    let referred_to_by: Vec<_> = add.referred_to_by.iter().map(|l| l.path.as_str()).collect();
+    assert_eq!(
+        referred_to_by,
+        ["crate::<u64 as Show>::show", "crate::main"]
+    );
+}
+
+#[test]
+fn renders_linked_pages_and_json() {
+    let docs = docs("render", &[("main.ginto", MAIN), ("math.ginto", MATH)]);
+    assert_eq!(page_name("crate"), "index.html");
+    assert_eq!(page_name("crate::math::vec"), "math.vec.html");
+
+    let pages = render_html(&docs);
+    let names: Vec<_> = pages.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["index.html", "math.html"]);
+    let index = &pages[0].1;
+    assert!(index.contains("<section id=\"fn.twice\">"), "{index}");
+    assert!(
+        index.contains("<a href=\"math.html#fn.add\"><code>crate::math::add</code></a>"),
+        "{index}"
+    );
+    assert!(index.contains("fn twice&lt;T&gt;(x: T) -&gt; T"), "{index}");
+
+    let json = render_json(&docs);
+    assert!(
+        json.starts_with("{\"modules\":[{\"path\":\"crate\","),
+        "{json}"
+    );
+    assert!(
+        json.contains(
+            "{\"path\":\"crate::math::add\",\"module\":\"crate::math\",\"anchor\":\"fn.add\"}"
+        ),
+        "{json}"
+    );
+}
+
+#[test]
+fn items_are_documented_by_the_comments_above_them() {
+    let main = "# Shows values.\ntrait Show:\n    # Shows `self`.\n    fn show(self) -> u64\n\n\
+                # not attached\n\n# The entry.\n#\n# Returns <1>.\nfn main() -> u64:\n    1\n";
+    let docs = docs("comments", &[("main.ginto", main)]);
+    let items = &docs.modules[0].items;
+    assert_eq!(items[0].doc.as_deref(), Some("Shows values."));
+    assert_eq!(items[0].methods[0].doc.as_deref(), Some("Shows `self`."));
+    assert_eq!(items[1].doc.as_deref(), Some("The entry.\n\nReturns <1>."));
+
+    let index = &render_html(&docs)[0].1;
+    assert!(
+        index.contains(
+            "<pre><code>fn main() -&gt; u64</code></pre>\n<p>The entry.</p>\n<p>Returns &lt;1&gt;.</p>"
+        ),
+        "{index}"
+    );
+    assert!(!index.contains("not attached"), "{index}");
+    let json = render_json(&docs);
+    assert!(
+        json.contains(
+            "\"signature\":\"fn main() -> u64\",\"doc\":\"The entry.\\n\\nReturns <1>.\""
+        ),
+        "{json}"
+    );
+}
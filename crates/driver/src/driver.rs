@@ -0,0 +1,378 @@
+use std::{fmt, io, path::Path};
+
+use ginto_analysis::{
+    MatchError, check_definite_assignment, check_matches, check_mutability, fold_constants,
+};
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Severity, SourceManager};
+use ginto_eval::Builtins;
+use ginto_lint::{LintContext, LintLevels, LintStore, const_eval_lint};
+use ginto_resolve::{ModuleTree, Resolutions, Resolver, load_modules, load_modules_with};
+use ginto_syntax::ItemKind;
+use ginto_typeck::{Instance, TypeChecker, TypeckResults, collect_instances};
+use tracing::{debug, info_span};
+
+/// A program that passed every check, ready to run or compile.
+pub struct Compilation {
+    pub tree: ModuleTree,
+    pub resolutions: Resolutions,
+    pub types: TypeckResults,
+    pub builtins: Builtins,
+    /// The function instances the entry point uses, itself first; empty if
+    /// the program has no entry point.
+    pub instances: Vec<Instance>,
+}
+
+/// The stages of checking a program, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    /// Reading, lexing and parsing the files of the program.
+    Load,
+    Resolve,
+    Typecheck,
+    /// Collecting the instances of generic functions `main` uses.
+    Instances,
+    DefiniteAssignment,
+    Mutability,
+    ConstantFolding,
+    Exhaustiveness,
+    /// Lints set to `deny`.
+    Lint,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Stage::Load => "load",
+            Stage::Resolve => "resolve",
+            Stage::Typecheck => "typecheck",
+            Stage::Instances => "instances",
+            Stage::DefiniteAssignment => "definite assignment",
+            Stage::Mutability => "mutability",
+            Stage::ConstantFolding => "constant folding",
+            Stage::Exhaustiveness => "exhaustiveness",
+            Stage::Lint => "lint",
+        })
+    }
+}
+
+/// What checking a program produced, up to the stage that found errors:
+/// tools like editors use what the earlier stages found in programs with
+/// errors.
+pub struct Checked {
+    pub tree: ModuleTree,
+    /// `None` if loading stopped checking before names were resolved.
+    pub resolutions: Option<Resolutions>,
+    /// `None` if checking stopped before types were checked.
+    pub types: Option<TypeckResults>,
+    pub builtins: Builtins,
+    /// The function instances the entry point uses, once collected.
+    pub instances: Vec<Instance>,
+    /// The stage that found errors, if one did.
+    pub failed: Option<Stage>,
+    /// The diagnostics of every file, in the order the stages reported
+    /// them, including the warnings of lints.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Checked {
+    /// The program, if it has no errors, and the diagnostics.
+    pub fn into_compilation(self) -> (Option<Compilation>, Vec<Diagnostic>) {
+        let compilation = match (self.failed, self.resolutions, self.types) {
+            (None, Some(resolutions), Some(types)) => Some(Compilation {
+                tree: self.tree,
+                resolutions,
+                types,
+                builtins: self.builtins,
+                instances: self.instances,
+            }),
+            _ => None,
+        };
+        (compilation, self.diagnostics)
+    }
+}
+
+type Reader<'a> = dyn FnMut(&SourceManager, &Path) -> io::Result<String> + 'a;
+
+/// Checks programs spread over several files: the root file given to
+/// `check` and the files of the modules it declares, and of those they
+/// declare. Every tool checking programs goes through it, so they agree on
+/// what a program with errors is.
+pub struct Driver<'a> {
+    builtins: Builtins,
+    lints: Option<(&'a mut LintStore, &'a LintLevels)>,
+    read: Option<&'a mut Reader<'a>>,
+    entry: &'a str,
+    past_load_errors: bool,
+    lints_only: bool,
+}
+
+impl Default for Driver<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Driver<'a> {
+    /// A driver checking programs against the prelude, without lints,
+    /// reading modules from disk and starting at `main`.
+    pub fn new() -> Self {
+        Self {
+            builtins: Builtins::with_prelude(),
+            lints: None,
+            read: None,
+            entry: "main",
+            past_load_errors: false,
+            lints_only: false,
+        }
+    }
+
+    pub fn with_builtins(mut self, builtins: Builtins) -> Self {
+        self.builtins = builtins;
+        self
+    }
+
+    /// Runs the lints of `lints` on every file of a program that has no
+    /// errors, or none but matches missing values, at the levels of
+    /// `levels`.
+    pub fn with_lints(mut self, lints: &'a mut LintStore, levels: &'a LintLevels) -> Self {
+        self.lints = Some((lints, levels));
+        self
+    }
+
+    /// Gets the source of each module file from `read` rather than from
+    /// disk, as an editor does for files with unsaved changes.
+    pub fn with_reader(mut self, read: &'a mut Reader<'a>) -> Self {
+        self.read = Some(read);
+        self
+    }
+
+    /// Collects the instances of generic functions from the function of
+    /// the root module named `entry` instead of `main`.
+    pub fn with_entry(mut self, entry: &'a str) -> Self {
+        self.entry = entry;
+        self
+    }
+
+    /// Goes on to resolve and check the modules that loaded when others
+    /// couldn't be read or parsed, as editors do to keep showing what they
+    /// can.
+    pub fn past_load_errors(mut self) -> Self {
+        self.past_load_errors = true;
+        self
+    }
+
+    /// Runs the lints right after checking types, skipping the analyses a
+    /// program needs to be run or compiled, as `ginto lint` does.
+    pub fn lints_only(mut self) -> Self {
+        self.lints_only = true;
+        self
+    }
+
+    /// Loads the program whose root file is `root` into `sm` and checks
+    /// it, stopping at the first stage that finds errors in any of its
+    /// files. Returns the program if it has no errors, with the
+    /// diagnostics of every file in the order the stages reported them.
+    pub fn check(
+        self,
+        sm: &mut SourceManager,
+        root: FileId,
+    ) -> (Option<Compilation>, Vec<Diagnostic>) {
+        self.load(sm, root).into_compilation()
+    }
+
+    /// Loads the program whose root file is `root` into `sm` and checks it
+    /// as `check` does, keeping what every stage that ran produced.
+    pub fn load(mut self, sm: &mut SourceManager, root: FileId) -> Checked {
+        let name = sm
+            .get_file(root)
+            .map(|file| file.name.clone())
+            .unwrap_or_default();
+        let _span = info_span!("check", root = %name).entered();
+        let (tree, load_errors) = info_span!("load").in_scope(|| match self.read.take() {
+            Some(read) => load_modules_with(sm, root, |sm, path| read(sm, path)),
+            None => load_modules(sm, root),
+        });
+        let load_diagnostics = diagnostics(load_errors);
+        if !load_diagnostics.is_empty() && !self.past_load_errors {
+            return stopped(
+                Checked {
+                    tree,
+                    resolutions: None,
+                    types: None,
+                    builtins: self.builtins,
+                    instances: Vec::new(),
+                    failed: None,
+                    diagnostics: Vec::new(),
+                },
+                Stage::Load,
+                load_diagnostics,
+            );
+        }
+        let mut checked = self.check_tree(tree);
+        if !load_diagnostics.is_empty() {
+            checked.failed = Some(Stage::Load);
+            checked.diagnostics.splice(0..0, load_diagnostics);
+        }
+        checked
+    }
+
+    /// Checks the modules of `tree`, which are already loaded, stopping at
+    /// the first stage that finds errors.
+    pub fn check_tree(self, tree: ModuleTree) -> Checked {
+        let Self {
+            builtins,
+            lints,
+            entry,
+            lints_only,
+            ..
+        } = self;
+        let mut resolver = Resolver::new();
+        builtins.declare_names(&mut resolver);
+        let resolutions = info_span!("resolve").in_scope(|| resolver.resolve_modules(&tree));
+        let resolve_errors = resolver.errors().to_vec();
+        let mut checked = Checked {
+            tree,
+            resolutions: None,
+            types: None,
+            builtins,
+            instances: Vec::new(),
+            failed: None,
+            diagnostics: Vec::new(),
+        };
+        if !resolve_errors.is_empty() {
+            checked.resolutions = Some(resolutions);
+            return stopped(checked, Stage::Resolve, diagnostics(resolve_errors));
+        }
+        let mut checker = TypeChecker::new(&resolutions);
+        checked.builtins.declare_types(&mut checker, &resolutions);
+        let types = info_span!("typecheck").in_scope(|| checker.check_modules(&checked.tree));
+        let type_errors = checker.errors().to_vec();
+        drop(checker);
+        checked.resolutions = Some(resolutions);
+        checked.types = Some(types);
+        if !type_errors.is_empty() {
+            return stopped(checked, Stage::Typecheck, diagnostics(type_errors));
+        }
+        let Checked {
+            tree,
+            resolutions: Some(resolutions),
+            types: Some(types),
+            instances,
+            ..
+        } = &mut checked
+        else {
+            unreachable!("names and types were checked");
+        };
+
+        let mut const_warnings = Vec::new();
+        let mut match_errors = Vec::new();
+        if !lints_only {
+            let root = tree.module(tree.root());
+            let entry = root
+                .program
+                .items
+                .iter()
+                .find_map(|item| match &item.inner {
+                    ItemKind::Func(func) if func.name.inner == entry => {
+                        resolutions.def_at(root.program.file_id, func.name.span)
+                    }
+                    _ => None,
+                });
+            if let Some(entry) = entry {
+                let (entry_instances, mono_errors) = info_span!("instances")
+                    .in_scope(|| collect_instances(tree, resolutions, types, entry));
+                if !mono_errors.is_empty() {
+                    return stopped(checked, Stage::Instances, diagnostics(mono_errors));
+                }
+                *instances = entry_instances;
+            }
+            let analysis = info_span!("analysis").entered();
+            let init_errors: Vec<_> = tree
+                .modules()
+                .flat_map(|(_, module)| check_definite_assignment(&module.program, resolutions))
+                .collect();
+            if !init_errors.is_empty() {
+                return stopped(checked, Stage::DefiniteAssignment, diagnostics(init_errors));
+            }
+            let mut_errors: Vec<_> = tree
+                .modules()
+                .flat_map(|(_, module)| check_mutability(&module.program, resolutions))
+                .collect();
+            if !mut_errors.is_empty() {
+                return stopped(checked, Stage::Mutability, diagnostics(mut_errors));
+            }
+            // operations certain to fail are reported by lints, which deny
+            // them unless their level is lowered
+            let default_levels = LintLevels::new();
+            let levels = lints
+                .as_ref()
+                .map_or(&default_levels, |(_, levels)| *levels);
+            let mut cx = LintContext::new(resolutions, types, levels);
+            let mut const_errors = Vec::new();
+            for err in tree.programs_mut().flat_map(fold_constants) {
+                match const_eval_lint(&err) {
+                    Some(lint) => cx.emit(lint, err.into_diagnostic()),
+                    None => const_errors.push(err.into_diagnostic()),
+                }
+            }
+            const_errors.extend(cx.into_diagnostics());
+            if const_errors
+                .iter()
+                .any(|diagnostic| diagnostic.severity == Severity::Error)
+            {
+                return stopped(checked, Stage::ConstantFolding, const_errors);
+            }
+            const_warnings = const_errors;
+            // unreachable arms are reported by the `unreachable_patterns` lint,
+            // which still runs when matches miss values, so both are reported
+            match_errors = diagnostics(
+                tree.modules()
+                    .flat_map(|(_, module)| check_matches(&module.program))
+                    .filter(|err| matches!(err, MatchError::NonExhaustive { .. }))
+                    .collect(),
+            );
+            drop(analysis);
+        }
+
+        let mut lint_diagnostics = const_warnings;
+        if let Some((lints, levels)) = lints {
+            let _span = info_span!("lint").entered();
+            for (_, module) in tree.modules() {
+                lint_diagnostics.extend(lints.check_program(
+                    &module.program,
+                    resolutions,
+                    types,
+                    levels,
+                ));
+            }
+        }
+        if !match_errors.is_empty() {
+            match_errors.extend(lint_diagnostics);
+            return stopped(checked, Stage::Exhaustiveness, match_errors);
+        }
+        // lints set to `deny` fail the program
+        if lint_diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+        {
+            return stopped(checked, Stage::Lint, lint_diagnostics);
+        }
+        checked.diagnostics = lint_diagnostics;
+        checked
+    }
+}
+
+/// `checked`, which `stage` failed with `diagnostics`.
+fn stopped(mut checked: Checked, stage: Stage, diagnostics: Vec<Diagnostic>) -> Checked {
+    debug!(stage = %stage, errors = diagnostics.len(), "stopped checking");
+    checked.failed = Some(stage);
+    checked.diagnostics = diagnostics;
+    checked
+}
+
+fn diagnostics<E: DiagnosticConvertible>(errors: Vec<E>) -> Vec<Diagnostic> {
+    errors
+        .into_iter()
+        .map(DiagnosticConvertible::into_diagnostic)
+        .collect()
+}
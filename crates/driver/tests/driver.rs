@@ -0,0 +1,243 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ginto_diag::{Severity, SourceManager};
+use ginto_driver::{Driver, Stage};
+use ginto_eval::{Interpreter, Value};
+use ginto_lint::{Level, LintLevels, LintStore};
+
+/// Writes `files`, named relative to a new directory, returning it.
+fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ginto-driver-{name}-{}", std::process::id()));
+    for (path, source) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn checks_the_modules_a_program_declares() {
+    let dir = project(
+        "modules",
+        &[
+            (
+                "main.ginto",
+                "mod math\nuse math::double\n\nfn main() -> u64:\n    double(21)\n",
+            ),
+            (
+                "math.ginto",
+                "mod util\nuse util::add\n\npub fn double(n: u64) -> u64:\n    add(n, n)\n",
+            ),
+            (
+                "math/util.ginto",
+                "pub fn add(a: u64, b: u64) -> u64:\n    a + b\n",
+            ),
+        ],
+    );
+    let mut sm = SourceManager::new();
+    let root = sm.set_file_from_path(dir.join("main.ginto")).unwrap();
+    let (compilation, diagnostics) = Driver::new().check(&mut sm, root);
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+    let compilation = compilation.unwrap();
+    assert_eq!(compilation.tree.modules().count(), 3);
+    assert_eq!(sm.files().count(), 3);
+}
+
+#[test]
+fn reports_the_errors_of_every_file() {
+    let dir = project(
+        "errors",
+        &[
+            ("main.ginto", "mod other\n\nfn main() -> u64:\n    true\n"),
+            ("other.ginto", "pub fn f() -> bool:\n    1\n"),
+        ],
+    );
+    let mut sm = SourceManager::new();
+    let root = sm.set_file_from_path(dir.join("main.ginto")).unwrap();
+    let (compilation, diagnostics) = Driver::new().check(&mut sm, root);
+    assert!(compilation.is_none());
+    let mut files: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.labels[0].file_id)
+        .collect();
+    files.dedup();
+    assert_eq!(files.len(), 2, "{diagnostics:?}");
+}
+
+#[test]
+fn fails_programs_on_denied_lints() {
+    let dir = project(
+        "lints",
+        &[(
+            "main.ginto",
+            "fn main() -> u64:\n    let unused = 1\n    2\n",
+        )],
+    );
+    let mut sm = SourceManager::new();
+    let root = sm.set_file_from_path(dir.join("main.ginto")).unwrap();
+    let mut lints = LintStore::with_builtin_lints();
+
+    let levels = LintLevels::new();
+    let (compilation, diagnostics) = Driver::new()
+        .with_lints(&mut lints, &levels)
+        .check(&mut sm, root);
+    assert!(compilation.is_some());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+
+    let mut levels = LintLevels::new();
+    levels.set(&lints, "unused_variables", Level::Deny).unwrap();
+    let (compilation, diagnostics) = Driver::new()
+        .with_lints(&mut lints, &levels)
+        .check(&mut sm, root);
+    assert!(compilation.is_none());
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+}
+
+#[test]
+fn lints_programs_with_matches_missing_values() {
+    let source = "pub fn f(n: u64) -> u64:\n    match n:\n        0 -> 1\n\nfn main() -> u64:\n    match 2:\n        _ -> 1\n        0 -> 0\n";
+    let mut sm = SourceManager::new();
+    let root = sm.add_file("main.ginto".to_string(), source.to_string());
+    let mut lints = LintStore::with_builtin_lints();
+    let levels = LintLevels::new();
+    let checked = Driver::new()
+        .with_lints(&mut lints, &levels)
+        .load(&mut sm, root);
+    assert_eq!(checked.failed, Some(Stage::Exhaustiveness));
+    let severities: Vec<_> = checked
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.severity.clone(), diagnostic.code.as_deref()))
+        .collect();
+    assert_eq!(
+        severities,
+        [(Severity::Error, None), (Severity::Warning, Some("W0003"))]
+    );
+}
+
+#[test]
+fn reports_the_stage_that_failed() {
+    let stages = [
+        ("fn main() -> u64:\n    missing\n", Stage::Resolve),
+        ("fn main() -> u64:\n    true\n", Stage::Typecheck),
+        (
+            "fn main() -> u64:\n    let x: u64\n    x\n",
+            Stage::DefiniteAssignment,
+        ),
+        (
+            "fn main() -> u64:\n    let x = 1\n    x = 2\n    x\n",
+            Stage::Mutability,
+        ),
+        ("fn main() -> u64:\n    1 / 0\n", Stage::ConstantFolding),
+    ];
+    for (source, stage) in stages {
+        let mut sm = SourceManager::new();
+        let root = sm.add_file("main.ginto".to_string(), source.to_string());
+        let (tree, _) = ginto_resolve::load_modules(&mut sm, root);
+        let checked = Driver::new().check_tree(tree);
+        assert_eq!(checked.failed, Some(stage), "{source}");
+        assert!(!checked.diagnostics.is_empty());
+    }
+}
+
+#[test]
+fn reads_modules_through_the_reader() {
+    let dir = std::env::temp_dir().join("ginto-driver-reader-not-on-disk");
+    let mut sm = SourceManager::new();
+    let root = sm.add_file(
+        dir.join("main.ginto").display().to_string(),
+        "mod other\nuse other::f\n\nfn main() -> u64:\n    f()\n".to_string(),
+    );
+    let mut read = |_: &SourceManager, path: &Path| {
+        assert!(path.ends_with("other.ginto"), "{}", path.display());
+        Ok("pub fn f() -> u64:\n    1\n".to_string())
+    };
+    let checked = Driver::new().with_reader(&mut read).load(&mut sm, root);
+    assert_eq!(checked.failed, None, "{:?}", checked.diagnostics);
+    assert_eq!(checked.tree.modules().count(), 2);
+}
+
+#[test]
+fn checks_past_modules_that_fail_to_load() {
+    let dir = project(
+        "past-load",
+        &[("main.ginto", "mod missing\n\nfn main() -> u64:\n    true\n")],
+    );
+    let mut sm = SourceManager::new();
+    let root = sm.set_file_from_path(dir.join("main.ginto")).unwrap();
+    let checked = Driver::new().load(&mut sm, root);
+    assert_eq!(checked.failed, Some(Stage::Load));
+    assert_eq!(checked.diagnostics.len(), 1);
+
+    let checked = Driver::new().past_load_errors().load(&mut sm, root);
+    assert_eq!(checked.failed, Some(Stage::Load));
+    assert!(checked.types.is_some());
+    // the missing module and the mistyped body of `main`
+    assert_eq!(checked.diagnostics.len(), 2, "{:?}", checked.diagnostics);
+}
+
+#[test]
+fn calls_functions_through_module_paths() {
+    let dir = project(
+        "paths",
+        &[
+            (
+                "main.ginto",
+                "mod math\n\nfn main() -> u64:\n    let f = math::util::add\n    math::double(f(1, 2)) + crate::seven()\n\npub fn seven() -> u64:\n    7\n",
+            ),
+            (
+                "math.ginto",
+                "pub mod util\n\npub fn double(n: u64) -> u64:\n    util::add(n, n)\n",
+            ),
+            (
+                "math/util.ginto",
+                "pub fn add(a: u64, b: u64) -> u64:\n    a + b\n",
+            ),
+        ],
+    );
+    let mut sm = SourceManager::new();
+    let root = sm.set_file_from_path(dir.join("main.ginto")).unwrap();
+    let (compilation, diagnostics) = Driver::new().check(&mut sm, root);
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+    let compilation = compilation.unwrap();
+    let mut interpreter = Interpreter::new(&compilation.resolutions, &compilation.types)
+        .with_builtins(&compilation.builtins);
+    let value = interpreter.run_modules(&compilation.tree).unwrap();
+    assert_eq!(value, Value::U64(13));
+}
+
+#[test]
+fn reports_paths_that_name_nothing() {
+    let dir = project(
+        "bad-paths",
+        &[
+            (
+                "main.ginto",
+                "mod math\n\nfn main() -> u64:\n    math::triple(1) + nope::f() + math::secret() + math\n",
+            ),
+            ("math.ginto", "fn secret() -> u64:\n    1\n"),
+        ],
+    );
+    let mut sm = SourceManager::new();
+    let root = sm.set_file_from_path(dir.join("main.ginto")).unwrap();
+    let (compilation, diagnostics) = Driver::new().check(&mut sm, root);
+    assert!(compilation.is_none());
+    let messages: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "cannot find `triple` in `crate::math`",
+            "cannot find `nope` in this scope",
+            "function `secret` is private",
+            "expected value, found module `math`",
+        ]
+    );
+}
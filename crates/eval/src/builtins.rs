@@ -0,0 +1,108 @@
+use ginto_resolve::{DefKind, Resolutions, Resolver};
+use ginto_syntax::Type;
+use ginto_typeck::TypeChecker;
+
+use crate::Value;
+
+/// The native code of a builtin function. An error stops the program with
+/// its message.
+pub type BuiltinFn = dyn Fn(&[Value]) -> Result<Value, String> + Send + Sync;
+
+/// A function provided by the host, with the signature programs see.
+pub struct Builtin {
+    pub name: String,
+    pub generics: Vec<String>,
+    pub params: Vec<Type>,
+    pub ret: Type,
+    func: Box<BuiltinFn>,
+}
+
+impl Builtin {
+    pub fn call(&self, args: &[Value]) -> Result<Value, String> {
+        (self.func)(args)
+    }
+}
+
+/// The functions a host exposes to programs.
+///
+/// Builtins are declared to the resolver and the type checker, which check
+/// calls to them like calls to any function, and the interpreter and the
+/// VM run them when called. Items of the same name shadow them.
+#[derive(Default)]
+pub struct Builtins {
+    builtins: Vec<Builtin>,
+}
+
+/// The builtins of an interpreter or VM the host gave none.
+pub static NO_BUILTINS: Builtins = Builtins::new();
+
+impl Builtins {
+    pub const fn new() -> Self {
+        Self {
+            builtins: Vec::new(),
+        }
+    }
+
+    /// Registers `func` as the builtin `name`, replacing a builtin of the
+    /// same name.
+    pub fn register(
+        &mut self,
+        name: &str,
+        params: &[Type],
+        ret: Type,
+        func: impl Fn(&[Value]) -> Result<Value, String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.register_generic(name, &[], params, ret, func)
+    }
+
+    /// Registers a builtin with type parameters `generics`, which `params`
+    /// and `ret` refer to as `Type::Param`. Their symbols tie the builtins
+    /// to the session they are registered in.
+    pub fn register_generic(
+        &mut self,
+        name: &str,
+        generics: &[&str],
+        params: &[Type],
+        ret: Type,
+        func: impl Fn(&[Value]) -> Result<Value, String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.builtins.retain(|builtin| builtin.name != name);
+        self.builtins.push(Builtin {
+            name: name.to_string(),
+            generics: generics.iter().map(|generic| generic.to_string()).collect(),
+            params: params.to_vec(),
+            ret,
+            func: Box::new(func),
+        });
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Builtin> {
+        self.builtins.iter().find(|builtin| builtin.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Builtin> {
+        self.builtins.iter()
+    }
+
+    /// Declares every builtin to `resolver`, before it resolves.
+    pub fn declare_names(&self, resolver: &mut Resolver) {
+        for builtin in &self.builtins {
+            let generics: Vec<&str> = builtin.generics.iter().map(String::as_str).collect();
+            resolver.declare_builtin(&builtin.name, &generics);
+        }
+    }
+
+    /// Declares the signature of every builtin in `resolutions` to
+    /// `checker`, before it checks.
+    pub fn declare_types(&self, checker: &mut TypeChecker, resolutions: &Resolutions) {
+        for (def, info) in resolutions.defs() {
+            if info.kind != DefKind::Builtin {
+                continue;
+            }
+            if let Some(builtin) = info.name.with(|name| self.get(name)) {
+                checker.declare_builtin(def, &builtin.params, &builtin.ret);
+            }
+        }
+    }
+}
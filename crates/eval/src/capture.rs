@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{RuntimeError, Value};
+
+/// The lines a program printed, collected instead of written to stdout.
+/// Clones share the same lines.
+#[derive(Debug, Clone, Default)]
+pub struct CapturedOutput {
+    lines: Arc<Mutex<String>>,
+}
+
+impl CapturedOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_line(&self, line: &str) {
+        let mut lines = self.lines.lock().unwrap_or_else(|err| err.into_inner());
+        lines.push_str(line);
+        lines.push('\n');
+    }
+
+    /// Returns everything printed so far, leaving nothing.
+    pub fn take(&self) -> String {
+        let mut lines = self.lines.lock().unwrap_or_else(|err| err.into_inner());
+        std::mem::take(&mut *lines)
+    }
+}
+
+/// What a program did when run with captured output: what it printed, and
+/// the value it returned or the error that stopped it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult {
+    pub output: String,
+    pub result: Result<Value, RuntimeError>,
+}
+
+impl RunResult {
+    /// The result of a run whose builtins printed to `output`.
+    pub fn capture(output: &CapturedOutput, result: Result<Value, RuntimeError>) -> Self {
+        Self {
+            output: output.take(),
+            result,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use ginto_diag::{FileId, Span};
+
+use crate::Value;
+
+/// Hooks a debugger gets from a running program, before each statement.
+pub trait DebugHandler {
+    /// Whether to stop before the statement at `span` of `file_id`. Asked
+    /// for every statement the program runs while it is not stepping.
+    fn is_breakpoint(&mut self, file_id: FileId, span: Span) -> bool;
+
+    /// Called when the program stopped before a statement, at a breakpoint
+    /// or after a step. Returns how the program goes on.
+    fn on_stop(&mut self, stop: &DebugStop) -> DebugAction;
+}
+
+/// How a stopped program goes on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugAction {
+    /// Runs until the next breakpoint.
+    Continue,
+    /// Stops before the next statement, in a called function too.
+    StepIn,
+    /// Stops before the next statement of the current function, or of its
+    /// caller once it returns.
+    StepOver,
+    /// Stops before the next statement of the caller.
+    StepOut,
+}
+
+/// A program stopped before a statement.
+#[derive(Debug, Clone)]
+pub struct DebugStop {
+    file_id: FileId,
+    span: Span,
+    depth: usize,
+    locals: Vec<(String, Value)>,
+}
+
+impl DebugStop {
+    pub fn file_id(&self) -> FileId {
+        self.file_id
+    }
+
+    /// The span of the statement about to run.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// How many calls deep the program is, 1 in the function it started in.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The locals of the running function assigned so far, in the order
+    /// they were declared.
+    pub fn locals(&self) -> &[(String, Value)] {
+        &self.locals
+    }
+
+    /// The value of the local `name`, the latest declared one if it is
+    /// shadowed.
+    pub fn local(&self, name: &str) -> Option<&Value> {
+        self.locals
+            .iter()
+            .rev()
+            .find_map(|(local, value)| (local == name).then_some(value))
+    }
+}
+
+/// Decides where a program stops for a [`DebugHandler`], for evaluators to
+/// call before each statement.
+pub struct Debugger<'a> {
+    handler: &'a mut dyn DebugHandler,
+    action: DebugAction,
+    /// The depth of the last stop.
+    depth: usize,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(handler: &'a mut dyn DebugHandler) -> Self {
+        Self {
+            handler,
+            action: DebugAction::Continue,
+            depth: 0,
+        }
+    }
+
+    /// Stops before the statement at `span` if a step or a breakpoint ends
+    /// there. `locals` are only taken when stopping.
+    pub fn before_statement(
+        &mut self,
+        file_id: FileId,
+        span: Span,
+        depth: usize,
+        locals: impl FnOnce() -> Vec<(String, Value)>,
+    ) {
+        let stepped = match self.action {
+            DebugAction::Continue => false,
+            DebugAction::StepIn => true,
+            DebugAction::StepOver => depth <= self.depth,
+            DebugAction::StepOut => depth < self.depth,
+        };
+        if !stepped && !self.handler.is_breakpoint(file_id, span) {
+            return;
+        }
+        let stop = DebugStop {
+            file_id,
+            span,
+            depth,
+            locals: locals(),
+        };
+        self.action = self.handler.on_stop(&stop);
+        self.depth = depth;
+    }
+}
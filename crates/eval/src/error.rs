@@ -0,0 +1,246 @@
+use core::fmt;
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span};
+
+use crate::{CALL_DEPTH_LIMIT, Limit, Value, ValueError};
+
+/// How many frames of the call stack a rendered runtime error shows, counting
+/// runs of the same recursive call as one.
+pub const STACK_FRAMES_SHOWN: usize = 8;
+
+/// An error that stopped a running program, with the calls that led to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    /// The calls active when the error occurred, innermost first. The call
+    /// of the entry function is not included.
+    pub stack: Vec<StackFrame>,
+}
+
+/// A call on the stack of a running program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    /// The name of the called function.
+    pub name: String,
+    /// The span of the call expression.
+    pub span: Span,
+    pub file_id: FileId,
+}
+
+impl From<RuntimeErrorKind> for RuntimeError {
+    fn from(kind: RuntimeErrorKind) -> Self {
+        RuntimeError {
+            kind,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl DiagnosticConvertible for RuntimeError {
+    fn into_diagnostic(self) -> Diagnostic {
+        // recursion pushes the same frame over and over, so runs of equal
+        // frames are shown once
+        let mut runs: Vec<(&StackFrame, usize)> = Vec::new();
+        for frame in &self.stack {
+            match runs.last_mut() {
+                Some((last, count)) if *last == frame => *count += 1,
+                _ => runs.push((frame, 1)),
+            }
+        }
+        let shown = runs.len().min(STACK_FRAMES_SHOWN);
+        let labels = runs[..shown]
+            .iter()
+            .map(|&(frame, count)| {
+                let message = match count {
+                    1 => format!("in call to `{}`", frame.name),
+                    _ => format!("in call to `{}`, repeated {} times", frame.name, count),
+                };
+                Label::secondary(frame.file_id, frame.span).with_message(message)
+            })
+            .collect();
+        let hidden: usize = runs[shown..].iter().map(|&(_, count)| count).sum();
+        let diagnostic = self.kind.into_diagnostic().with_labels(labels);
+        match hidden {
+            0 => diagnostic,
+            1 => diagnostic.with_note("1 more call is not shown"),
+            _ => diagnostic.with_note(format!("{} more calls are not shown", hidden)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeErrorKind {
+    Overflow {
+        /// The failing operation with its operands, e.g. `18446744073709551615 + 1`.
+        expr: String,
+        /// The name of the overflowing type.
+        ty: &'static str,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A `/` or `mod` by zero.
+    DivisionByZero {
+        expr: String,
+        span: Span,
+        file_id: FileId,
+    },
+    /// Operands the operator is not defined for, which the type checker
+    /// lets through in a few places.
+    InvalidOperands {
+        op: String,
+        /// The type names of the operands.
+        types: Vec<&'static str>,
+        span: Span,
+        file_id: FileId,
+    },
+    StackOverflow {
+        name: String,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A limit the program was run with, which it exceeded at `span`.
+    LimitExceeded {
+        limit: Limit,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A call through `Interpreter::call` with the wrong number of
+    /// arguments, which calls in checked programs cannot make.
+    ArityMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    /// A program without a `main` function to start from.
+    NoMain { file_id: FileId },
+    /// A builtin function that failed, with the message it gave.
+    BuiltinFailed {
+        name: String,
+        message: String,
+        span: Span,
+        file_id: FileId,
+    },
+}
+
+impl RuntimeErrorKind {
+    /// The error for the operation `expr`, applying `op` to `operands`,
+    /// failing with `error`.
+    pub fn from_value_error(
+        error: ValueError,
+        expr: String,
+        op: impl fmt::Display,
+        operands: &[Value],
+        span: Span,
+        file_id: FileId,
+    ) -> Self {
+        match error {
+            ValueError::Overflow => RuntimeErrorKind::Overflow {
+                expr,
+                ty: operands[0].type_name(),
+                span,
+                file_id,
+            },
+            ValueError::DivisionByZero => RuntimeErrorKind::DivisionByZero {
+                expr,
+                span,
+                file_id,
+            },
+            ValueError::InvalidOperands => RuntimeErrorKind::InvalidOperands {
+                op: op.to_string(),
+                types: operands.iter().map(Value::type_name).collect(),
+                span,
+                file_id,
+            },
+        }
+    }
+}
+
+impl DiagnosticConvertible for RuntimeErrorKind {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            RuntimeErrorKind::Overflow {
+                expr,
+                ty,
+                span,
+                file_id,
+            } => Diagnostic::error("arithmetic overflow").with_label(
+                Label::primary(file_id, span).with_message(format!(
+                    "attempt to compute `{}`, which would overflow `{}`",
+                    expr, ty
+                )),
+            ),
+
+            RuntimeErrorKind::DivisionByZero {
+                expr,
+                span,
+                file_id,
+            } => Diagnostic::error("attempt to divide by zero").with_label(
+                Label::primary(file_id, span)
+                    .with_message(format!("attempt to compute `{}`", expr)),
+            ),
+
+            RuntimeErrorKind::InvalidOperands {
+                op,
+                types,
+                span,
+                file_id,
+            } => Diagnostic::error(format!(
+                "cannot apply `{}` to {}",
+                op.trim(),
+                types
+                    .iter()
+                    .map(|ty| format!("`{}`", ty))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            ))
+            .with_label(Label::primary(file_id, span).with_message("invalid operands")),
+
+            RuntimeErrorKind::StackOverflow {
+                name,
+                span,
+                file_id,
+            } => Diagnostic::error("stack overflow")
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("this call to `{}` is too deep", name)),
+                )
+                .with_note(format!(
+                    "calls can only be nested {} deep",
+                    CALL_DEPTH_LIMIT
+                )),
+
+            RuntimeErrorKind::LimitExceeded {
+                limit,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("program exceeded its limit of {}", limit))
+                .with_label(Label::primary(file_id, span).with_message("stopped here")),
+
+            RuntimeErrorKind::ArityMismatch {
+                name,
+                expected,
+                found,
+            } => Diagnostic::error(format!(
+                "`{}` takes {} argument{} but {} {} supplied",
+                name,
+                expected,
+                if expected == 1 { "" } else { "s" },
+                found,
+                if found == 1 { "was" } else { "were" }
+            )),
+
+            RuntimeErrorKind::NoMain { file_id } => Diagnostic::error("`main` function not found")
+                .with_label(Label::primary(file_id, Span::default()))
+                .with_help("add a `fn main()` to run the program"),
+
+            RuntimeErrorKind::BuiltinFailed {
+                name,
+                message,
+                span,
+                file_id,
+            } => Diagnostic::error(message).with_label(
+                Label::primary(file_id, span).with_message(format!("in this call to `{}`", name)),
+            ),
+        }
+    }
+}
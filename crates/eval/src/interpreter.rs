@@ -0,0 +1,787 @@
+use std::collections::HashMap;
+
+use ginto_codegen::IntLiteral;
+use ginto_diag::{FileId, Span, Symbol, sym};
+use ginto_resolve::{DefId, DefKind, ModuleTree, Resolutions};
+use ginto_syntax::{
+    BinOp, Expr, ExprKind, Func, ItemKind, MatchArm, Param, Pattern, PatternKind, Program, StrPart,
+    UnaryOp,
+};
+use ginto_typeck::TypeckResults;
+
+use crate::{
+    Builtin, Builtins, DebugHandler, Debugger, Limit, Limits, Meter, NO_BUILTINS, Outcome,
+    RuntimeError, RuntimeErrorKind, StackFrame, Tracer, Tracing, Value,
+};
+
+/// How many calls deep a program may recurse before it is stopped with a
+/// stack overflow.
+pub const CALL_DEPTH_LIMIT: usize = 512;
+
+/// The stack size a thread running the interpreter needs for
+/// `CALL_DEPTH_LIMIT` nested calls to fit even in unoptimized builds.
+pub const INTERPRETER_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+/// Why evaluation stopped before producing a value.
+enum Unwind {
+    Return(Value),
+    /// A call in tail position, which the running function's frame makes
+    /// in its own place once the function is done with it.
+    TailCall {
+        callee: Value,
+        args: Vec<Value>,
+        span: Span,
+    },
+    Error(RuntimeError),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Unwind::Error(error)
+    }
+}
+
+impl From<RuntimeErrorKind> for Unwind {
+    fn from(kind: RuntimeErrorKind) -> Self {
+        Unwind::Error(kind.into())
+    }
+}
+
+/// Runs type-checked programs by walking their syntax trees.
+///
+/// Variables are looked up by the definitions the resolver assigned them,
+/// and integer literals take the type the type checker inferred for them.
+pub struct Interpreter<'a> {
+    resolutions: &'a Resolutions,
+    types: &'a TypeckResults,
+    funcs: HashMap<DefId, (&'a Func, FileId)>,
+    /// The closure expressions evaluated so far, by their definitions.
+    closures: HashMap<DefId, (&'a Expr, FileId)>,
+    builtins: &'a Builtins,
+    /// The file of the function being run.
+    file_id: FileId,
+    /// The name of the function being run, for tracing.
+    func_name: Option<Symbol>,
+    locals: HashMap<DefId, Value>,
+    depth: usize,
+    meter: Meter,
+    debugger: Option<Debugger<'a>>,
+    tracing: Option<Tracing<'a>>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(resolutions: &'a Resolutions, types: &'a TypeckResults) -> Self {
+        Self {
+            resolutions,
+            types,
+            funcs: HashMap::new(),
+            closures: HashMap::new(),
+            builtins: &NO_BUILTINS,
+            file_id: FileId(0),
+            func_name: None,
+            locals: HashMap::new(),
+            depth: 0,
+            meter: Meter::default(),
+            debugger: None,
+            tracing: None,
+        }
+    }
+
+    /// Runs calls to builtin functions with `builtins`, which the program
+    /// was resolved and checked with.
+    pub fn with_builtins(mut self, builtins: &'a Builtins) -> Self {
+        self.builtins = builtins;
+        self
+    }
+
+    /// Stops programs exceeding `limits`.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.meter = Meter::new(limits);
+        self
+    }
+
+    /// Lets `handler` stop the program before each statement.
+    pub fn with_debugger(mut self, handler: &'a mut dyn DebugHandler) -> Self {
+        self.debugger = Some(Debugger::new(handler));
+        self
+    }
+
+    /// Reports every expression the program evaluates to `tracer`.
+    pub fn with_tracer(mut self, tracer: &'a mut dyn Tracer) -> Self {
+        self.tracing = Some(Tracing::new(tracer));
+        self
+    }
+
+    /// Makes the functions and methods of `program` callable.
+    pub fn load(&mut self, program: &'a Program) {
+        for func in program.funcs() {
+            if let Some(def) = self.resolutions.def_at(program.file_id, func.name.span) {
+                self.funcs.insert(def, (func, program.file_id));
+            }
+        }
+    }
+
+    /// Runs the `main` function of `program`, which may only call functions
+    /// of `program` and of programs loaded before.
+    pub fn run(&mut self, program: &'a Program) -> Result<Value, RuntimeError> {
+        self.load(program);
+        let main = program
+            .items
+            .iter()
+            .find_map(|item| match &item.inner {
+                ItemKind::Func(func) if func.name.inner == sym::MAIN => {
+                    self.resolutions.def_at(program.file_id, func.name.span)
+                }
+                _ => None,
+            })
+            .ok_or(RuntimeErrorKind::NoMain {
+                file_id: program.file_id,
+            })?;
+        self.call(main, Vec::new())
+    }
+
+    /// Runs the `main` function of the root module of `tree`.
+    pub fn run_modules(&mut self, tree: &'a ModuleTree) -> Result<Value, RuntimeError> {
+        for (_, module) in tree.modules() {
+            self.load(&module.program);
+        }
+        self.run(&tree.module(tree.root()).program)
+    }
+
+    /// Calls the loaded function `def` with `args`. The call itself is not
+    /// part of the stack of an error it returns.
+    pub fn call(&mut self, def: DefId, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let (func, file_id) = self.funcs[&def];
+        if args.len() != func.params.len() {
+            return Err(RuntimeErrorKind::ArityMismatch {
+                name: func.name.inner.to_string(),
+                expected: func.params.len(),
+                found: args.len(),
+            }
+            .into());
+        }
+        self.file_id = file_id;
+        self.meter.start();
+        self.invoke(def, args)
+    }
+
+    /// Evaluates `expr` of the file `file_id` outside of any function, with
+    /// the locals set so far. A `return` ends evaluation with its value.
+    pub fn eval_expr(&mut self, file_id: FileId, expr: &'a Expr) -> Result<Value, RuntimeError> {
+        self.file_id = file_id;
+        self.meter.start();
+        match self.eval(expr) {
+            Ok(value) | Err(Unwind::Return(value)) => Ok(value),
+            // there is no frame to reuse
+            Err(Unwind::TailCall { callee, args, span }) => self.call_value_at(callee, args, span),
+            Err(Unwind::Error(error)) => Err(error),
+        }
+    }
+
+    /// The value of the local variable `def` outside of any function.
+    pub fn local(&self, def: DefId) -> Option<Value> {
+        self.locals.get(&def).cloned()
+    }
+
+    pub fn set_local(&mut self, def: DefId, value: Value) {
+        self.locals.insert(def, value);
+    }
+
+    /// Calls `def` from the call expression at `span`, adding the call to the
+    /// stack of an error it returns.
+    fn call_at(&mut self, def: DefId, args: Vec<Value>, span: Span) -> Result<Value, RuntimeError> {
+        // builtins run natively, without a frame of their own
+        if let Some(builtin) = self.builtin(def) {
+            return builtin.call(&args).map_err(|message| {
+                RuntimeErrorKind::BuiltinFailed {
+                    name: builtin.name.clone(),
+                    message,
+                    span,
+                    file_id: self.file_id,
+                }
+                .into()
+            });
+        }
+        let (func, _) = self.funcs[&def];
+        self.in_frame(func.name.inner, span, |this| this.invoke(def, args))
+    }
+
+    /// Calls the function or closure `callee` from the call expression at
+    /// `span`.
+    fn call_value_at(
+        &mut self,
+        callee: Value,
+        args: Vec<Value>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        match callee {
+            Value::Fn(def) => self.call_at(def, args, span),
+            Value::Closure { func, env } => self.call_closure_at(func, &env, args, span),
+            _ => unreachable!("callee is not a function"),
+        }
+    }
+
+    /// Calls the closure `func` from the call expression at `span`.
+    fn call_closure_at(
+        &mut self,
+        func: DefId,
+        env: &[Value],
+        args: Vec<Value>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        self.in_frame(sym::CLOSURE, span, |this| {
+            this.invoke_closure(func, env, args)
+        })
+    }
+
+    /// Runs `call`, a call to `name` at `span`, unless the stack is full,
+    /// and adds it to the stack of an error it returns.
+    fn in_frame(
+        &mut self,
+        name: Symbol,
+        span: Span,
+        call: impl FnOnce(&mut Self) -> Result<Value, RuntimeError>,
+    ) -> Result<Value, RuntimeError> {
+        if self.depth >= CALL_DEPTH_LIMIT {
+            return Err(RuntimeErrorKind::StackOverflow {
+                name: name.to_string(),
+                span,
+                file_id: self.file_id,
+            }
+            .into());
+        }
+        self.meter
+            .call(self.depth)
+            .map_err(|limit| self.limit_exceeded(limit, span))?;
+        call(self).map_err(|mut error| {
+            error.stack.push(StackFrame {
+                name: name.to_string(),
+                span,
+                file_id: self.file_id,
+            });
+            error
+        })
+    }
+
+    fn invoke(&mut self, def: DefId, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let (body, file_id, locals) = self.fn_frame(def, args);
+        let name = self.funcs[&def].0.name.inner;
+        self.run_body(body, file_id, locals, name)
+    }
+
+    fn invoke_closure(
+        &mut self,
+        func: DefId,
+        env: &[Value],
+        args: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let (body, file_id, locals) = self.closure_frame(func, env, args);
+        self.run_body(body, file_id, locals, sym::CLOSURE)
+    }
+
+    /// The body of the loaded function `def`, its file, and its parameters
+    /// bound to `args`.
+    fn fn_frame(&self, def: DefId, args: Vec<Value>) -> (&'a Expr, FileId, HashMap<DefId, Value>) {
+        let (func, file_id) = *self
+            .funcs
+            .get(&def)
+            .expect("called function was not loaded");
+
+        let mut locals = HashMap::new();
+        for (param, arg) in func.params.iter().zip(args) {
+            let name_span = match &param.inner {
+                Param::SelfParam => param.span,
+                Param::Named { name, .. } => name.span,
+            };
+            if let Some(def) = self.resolutions.def_at(file_id, name_span) {
+                locals.insert(def, arg);
+            }
+        }
+        (&func.body, file_id, locals)
+    }
+
+    /// The body of the closure `func`, its file, and its captures bound to
+    /// the values of `env`, which live on after the scope that defined them
+    /// ends, and its parameters to `args`.
+    fn closure_frame(
+        &self,
+        func: DefId,
+        env: &[Value],
+        args: Vec<Value>,
+    ) -> (&'a Expr, FileId, HashMap<DefId, Value>) {
+        let (expr, file_id) = self.closures[&func];
+        let ExprKind::Closure { params, body } = &expr.inner else {
+            unreachable!("closure definition is not a closure");
+        };
+        let mut locals: HashMap<DefId, Value> = self
+            .resolutions
+            .captures(func)
+            .iter()
+            .copied()
+            .zip(env.iter().cloned())
+            .collect();
+        for (param, arg) in params.iter().zip(args) {
+            if let Param::Named { name, .. } = &param.inner
+                && let Some(def) = self.resolutions.def_at(file_id, name.span)
+            {
+                locals.insert(def, arg);
+            }
+        }
+        (body, file_id, locals)
+    }
+
+    /// Evaluates the body of a function or closure of `file_id` with only
+    /// `locals` in scope. Tail calls run in the same frame, one after the
+    /// other, so tail recursion never overflows.
+    fn run_body(
+        &mut self,
+        body: &'a Expr,
+        file_id: FileId,
+        locals: HashMap<DefId, Value>,
+        name: Symbol,
+    ) -> Result<Value, RuntimeError> {
+        let caller_locals = std::mem::replace(&mut self.locals, locals);
+        let caller_file = std::mem::replace(&mut self.file_id, file_id);
+        let caller_name = self.func_name.replace(name);
+        self.depth += 1;
+        let mut result = self.eval_body(body);
+        while let Err(Unwind::TailCall { callee, args, .. }) = result {
+            let (body, file_id, locals) = match callee {
+                Value::Fn(def) => {
+                    self.func_name = Some(self.funcs[&def].0.name.inner);
+                    self.fn_frame(def, args)
+                }
+                Value::Closure { func, env } => {
+                    self.func_name = Some(sym::CLOSURE);
+                    self.closure_frame(func, &env, args)
+                }
+                _ => unreachable!("callee is not a function"),
+            };
+            self.locals = locals;
+            self.file_id = file_id;
+            result = self.eval_body(body);
+        }
+        self.depth -= 1;
+        self.locals = caller_locals;
+        self.file_id = caller_file;
+        self.func_name = caller_name;
+
+        match result {
+            Ok(value) | Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(error)) => Err(error),
+            Err(Unwind::TailCall { .. }) => unreachable!("tail call was not made"),
+        }
+    }
+
+    /// Evaluates the body of the running function, whose value is not an
+    /// input of the call for tracing.
+    fn eval_body(&mut self, body: &'a Expr) -> Result<Value, Unwind> {
+        if let Some(tracing) = &mut self.tracing {
+            tracing.enter();
+        }
+        let result = self.eval_tail(body);
+        if let Some(tracing) = &mut self.tracing {
+            tracing.skip();
+        }
+        result
+    }
+
+    /// Evaluates `expr`, whose value the running function returns. A call
+    /// to a function or closure in tail position is not made here but
+    /// unwinds to `run_body` as `Unwind::TailCall`.
+    fn eval_tail(&mut self, expr: &'a Expr) -> Result<Value, Unwind> {
+        if self.tracing.is_none() {
+            return self.eval_tail_untraced(expr);
+        }
+        self.traced(expr, Self::eval_tail_untraced)
+    }
+
+    fn eval_tail_untraced(&mut self, expr: &'a Expr) -> Result<Value, Unwind> {
+        match &expr.inner {
+            ExprKind::Block {
+                exprs,
+                tail: Some(tail),
+            } => {
+                for expr in exprs {
+                    self.before_statement(expr);
+                    self.eval(expr)?;
+                }
+                self.before_statement(tail);
+                self.eval_tail(tail)
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch: Some(else_branch),
+            } => match self.eval(cond)? {
+                Value::Bool(true) => self.eval_tail(then_branch),
+                _ => self.eval_tail(else_branch),
+            },
+            ExprKind::Match { scrutinee, arms } => {
+                let value = self.eval(scrutinee)?;
+                let body = self.select_arm(arms, &value);
+                self.eval_tail(body)
+            }
+            ExprKind::Call { callee, args } => {
+                let callee = self.eval(callee)?;
+                let args = self.eval_args(args)?;
+                // builtins have no frame to reuse
+                if let Value::Fn(def) = callee
+                    && self.builtin(def).is_some()
+                {
+                    return Ok(self.call_at(def, args, expr.span)?);
+                }
+                Err(Unwind::TailCall {
+                    callee,
+                    args,
+                    span: expr.span,
+                })
+            }
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => {
+                let def = self
+                    .types
+                    .method_target(self.file_id, method.span)
+                    .expect("method call was not resolved");
+                let mut values = vec![self.eval(receiver)?];
+                values.extend(self.eval_args(args)?);
+                Err(Unwind::TailCall {
+                    callee: Value::Fn(def),
+                    args: values,
+                    span: expr.span,
+                })
+            }
+            _ => self.eval_untraced(expr),
+        }
+    }
+
+    fn eval(&mut self, expr: &'a Expr) -> Result<Value, Unwind> {
+        if self.tracing.is_none() {
+            return self.eval_untraced(expr);
+        }
+        self.traced(expr, Self::eval_untraced)
+    }
+
+    /// Evaluates `expr` with `eval`, reporting it to the tracer.
+    fn traced(
+        &mut self,
+        expr: &'a Expr,
+        eval: impl FnOnce(&mut Self, &'a Expr) -> Result<Value, Unwind>,
+    ) -> Result<Value, Unwind> {
+        if let Some(tracing) = &mut self.tracing {
+            tracing.enter();
+        }
+        let result = eval(self, expr);
+        let outcome = match &result {
+            Ok(value) => Outcome::Value(value),
+            Err(Unwind::Return(value)) => Outcome::Returned(value),
+            Err(Unwind::TailCall { .. }) => Outcome::TailCall,
+            Err(Unwind::Error(error)) => Outcome::Failed(error),
+        };
+        if let Some(tracing) = &mut self.tracing {
+            let file_id = self.file_id;
+            match self.func_name {
+                Some(name) => name.with(|name| tracing.leave(file_id, expr.span, name, outcome)),
+                None => tracing.leave(file_id, expr.span, "", outcome),
+            }
+        }
+        result
+    }
+
+    fn eval_untraced(&mut self, expr: &'a Expr) -> Result<Value, Unwind> {
+        self.step(expr.span)?;
+        match &expr.inner {
+            ExprKind::Int(v) => {
+                let ty = self.types.expr_ty(self.file_id, expr.span);
+                Ok(self.int(IntLiteral::new(*v as i128, ty), expr.span)?)
+            }
+            ExprKind::Float(v) => Ok(Value::F64(*v)),
+            ExprKind::Bool(v) => Ok(Value::Bool(*v)),
+            ExprKind::Unit => Ok(Value::Unit),
+            ExprKind::Error => unreachable!("program with syntax errors was run"),
+            ExprKind::Str(parts) => {
+                // literals without interpolation are constants in the VM
+                if !matches!(parts.as_slice(), [] | [StrPart::Lit(_)]) {
+                    self.allocate(expr.span)?;
+                }
+                let mut value = String::new();
+                for part in parts {
+                    match part {
+                        StrPart::Lit(text) => value.push_str(text),
+                        StrPart::Expr(expr) => value.push_str(&self.eval(expr)?.to_string()),
+                    }
+                }
+                Ok(Value::Str(value.into()))
+            }
+            ExprKind::Unary { op, expr: operand } => {
+                match IntLiteral::of(expr, self.types, self.file_id) {
+                    Some(lit) => Ok(self.int(lit, expr.span)?),
+                    None => self.eval_unary(op.inner, operand, expr.span),
+                }
+            }
+            ExprKind::Binary { op, lhs, rhs } => self.eval_binary(op.inner, lhs, rhs, expr.span),
+            ExprKind::Cast { expr: operand, ty } => Ok(self.eval(operand)?.cast(&ty.inner)),
+            ExprKind::Let { name, value, .. } => {
+                if let Some(value) = value {
+                    let value = self.eval(value)?;
+                    if let Some(def) = self.resolutions.def_at(self.file_id, name.span) {
+                        self.locals.insert(def, value);
+                    }
+                }
+                Ok(Value::Unit)
+            }
+            ExprKind::Assign { name, value } => {
+                let value = self.eval(value)?;
+                if let Some(def) = self.resolutions.resolve(self.file_id, name.span) {
+                    self.locals.insert(def, value);
+                }
+                Ok(Value::Unit)
+            }
+            ExprKind::Block { exprs, tail } => {
+                for expr in exprs {
+                    self.before_statement(expr);
+                    self.eval(expr)?;
+                }
+                match tail {
+                    Some(tail) => {
+                        self.before_statement(tail);
+                        self.eval(tail)
+                    }
+                    None => Ok(Value::Unit),
+                }
+            }
+            ExprKind::Call { callee, args } => {
+                let callee = self.eval(callee)?;
+                let args = self.eval_args(args)?;
+                Ok(self.call_value_at(callee, args, expr.span)?)
+            }
+            ExprKind::Closure { .. } => {
+                self.allocate(expr.span)?;
+                Ok(self.eval_closure(expr))
+            }
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => {
+                let def = self
+                    .types
+                    .method_target(self.file_id, method.span)
+                    .expect("method call was not resolved");
+                let receiver = self.eval(receiver)?;
+                self.eval_call(def, Some(receiver), args, expr.span)
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                let value = self.eval(scrutinee)?;
+                let body = self.select_arm(arms, &value);
+                self.eval(body)
+            }
+            ExprKind::Return(value) => {
+                let value = match value {
+                    Some(value) => self.eval_tail(value)?,
+                    None => Value::Unit,
+                };
+                Err(Unwind::Return(value))
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => match (self.eval(cond)?, else_branch) {
+                (Value::Bool(true), _) => self.eval(then_branch),
+                (_, Some(else_branch)) => self.eval(else_branch),
+                (_, None) => Ok(Value::Unit),
+            },
+            ExprKind::While { cond, body } => {
+                while let Value::Bool(true) = self.eval(cond)? {
+                    self.eval(body)?;
+                }
+                Ok(Value::Unit)
+            }
+            ExprKind::Var(name) => Ok(self.var(*name, expr.span)),
+            ExprKind::Path(path) => Ok(self.var(path[path.len() - 1].inner, expr.span)),
+        }
+    }
+
+    // the larger expressions are evaluated out of line to keep the stack
+    // frame of `eval`, which all recursion goes through, small
+
+    fn eval_unary(&mut self, op: UnaryOp, operand: &'a Expr, span: Span) -> Result<Value, Unwind> {
+        let value = self.eval(operand)?;
+        value.unary(op).map_err(|error| {
+            let expr = format!("{}({})", op, value);
+            let operands = [value.clone()];
+            RuntimeErrorKind::from_value_error(error, expr, op, &operands, span, self.file_id)
+                .into()
+        })
+    }
+
+    fn eval_binary(
+        &mut self,
+        op: BinOp,
+        lhs: &'a Expr,
+        rhs: &'a Expr,
+        span: Span,
+    ) -> Result<Value, Unwind> {
+        let lhs = self.eval(lhs)?;
+        match (op, &lhs) {
+            (BinOp::And, Value::Bool(false)) => return Ok(Value::Bool(false)),
+            (BinOp::Or, Value::Bool(true)) => return Ok(Value::Bool(true)),
+            _ => {}
+        }
+        let rhs = self.eval(rhs)?;
+        lhs.binary(op, &rhs).map_err(|error| {
+            let expr = format!("{} {} {}", lhs, op, rhs);
+            let operands = [lhs.clone(), rhs.clone()];
+            RuntimeErrorKind::from_value_error(error, expr, op, &operands, span, self.file_id)
+                .into()
+        })
+    }
+
+    /// The body of the first arm matching `value`, with the arm's names
+    /// bound.
+    fn select_arm(&mut self, arms: &'a [MatchArm], value: &Value) -> &'a Expr {
+        let arm = arms
+            .iter()
+            .find(|arm| self.bind(&arm.pattern, value))
+            .expect("match is not exhaustive");
+        &arm.body
+    }
+
+    fn eval_call(
+        &mut self,
+        def: DefId,
+        receiver: Option<Value>,
+        args: &'a [Expr],
+        span: Span,
+    ) -> Result<Value, Unwind> {
+        let mut values: Vec<Value> = receiver.into_iter().collect();
+        values.extend(self.eval_args(args)?);
+        Ok(self.call_at(def, values, span)?)
+    }
+
+    fn eval_args(&mut self, args: &'a [Expr]) -> Result<Vec<Value>, Unwind> {
+        args.iter().map(|arg| self.eval(arg)).collect()
+    }
+
+    /// Counts a step of evaluating the expression at `span`.
+    fn step(&mut self, span: Span) -> Result<(), RuntimeError> {
+        self.meter
+            .step()
+            .map_err(|limit| self.limit_exceeded(limit, span))
+    }
+
+    /// Counts a string or closure created by the expression at `span`.
+    fn allocate(&mut self, span: Span) -> Result<(), RuntimeError> {
+        self.meter
+            .allocate()
+            .map_err(|limit| self.limit_exceeded(limit, span))
+    }
+
+    fn limit_exceeded(&self, limit: Limit, span: Span) -> RuntimeError {
+        RuntimeErrorKind::LimitExceeded {
+            limit,
+            span,
+            file_id: self.file_id,
+        }
+        .into()
+    }
+
+    /// Lets the debugger, if there is one, stop before `stmt`.
+    fn before_statement(&mut self, stmt: &Expr) {
+        let Some(debugger) = &mut self.debugger else {
+            return;
+        };
+        let (resolutions, locals) = (self.resolutions, &self.locals);
+        debugger.before_statement(self.file_id, stmt.span, self.depth, || {
+            let mut locals: Vec<_> = locals.iter().collect();
+            locals.sort_by_key(|(def, _)| **def);
+            locals
+                .into_iter()
+                .map(|(def, value)| (resolutions.def(*def).name.to_string(), value.clone()))
+                .collect()
+        });
+    }
+
+    /// A closure value, holding the current values of its captures.
+    fn eval_closure(&mut self, expr: &'a Expr) -> Value {
+        let func = self
+            .resolutions
+            .def_at(self.file_id, expr.span)
+            .expect("closure was not resolved");
+        self.closures.insert(func, (expr, self.file_id));
+        let env = self
+            .resolutions
+            .captures(func)
+            .iter()
+            .map(|def| {
+                self.locals
+                    .get(def)
+                    .cloned()
+                    .expect("captured variable was not assigned")
+            })
+            .collect();
+        Value::Closure { func, env }
+    }
+
+    fn var(&self, name: Symbol, span: Span) -> Value {
+        let def = self
+            .resolutions
+            .resolve(self.file_id, span)
+            .expect("variable was not resolved");
+        match self.locals.get(&def) {
+            Some(value) => value.clone(),
+            None if self.funcs.contains_key(&def) || self.builtin(def).is_some() => Value::Fn(def),
+            None => unreachable!("`{}` read before it was assigned", name),
+        }
+    }
+
+    /// The registered builtin `def` refers to, if it is a builtin.
+    fn builtin(&self, def: DefId) -> Option<&'a Builtin> {
+        let info = self.resolutions.def(def);
+        if info.kind != DefKind::Builtin {
+            return None;
+        }
+        let builtin = info.name.with(|name| self.builtins.get(name));
+        Some(builtin.unwrap_or_else(|| panic!("builtin `{}` was not registered", info.name)))
+    }
+
+    /// The value of the integer literal `lit`, at `span`.
+    fn int(&self, lit: IntLiteral, span: Span) -> Result<Value, RuntimeError> {
+        match lit.bits() {
+            Some(bits) if lit.signed => Ok(Value::I64(bits)),
+            Some(bits) => Ok(Value::U64(bits as u64)),
+            None => Err(RuntimeErrorKind::Overflow {
+                expr: lit.value.to_string(),
+                ty: lit.ty_name(),
+                span,
+                file_id: self.file_id,
+            }
+            .into()),
+        }
+    }
+
+    /// Whether `value` matches `pattern`, binding its names if it does.
+    fn bind(&mut self, pattern: &Pattern, value: &Value) -> bool {
+        match (&pattern.inner, value) {
+            (PatternKind::Wildcard, _) => true,
+            (PatternKind::Bind(_), _) => {
+                if let Some(def) = self.resolutions.def_at(self.file_id, pattern.span) {
+                    self.locals.insert(def, value.clone());
+                }
+                true
+            }
+            (PatternKind::Int(n), Value::U64(v)) => *n == *v as i128,
+            (PatternKind::Int(n), Value::I64(v)) => *n == *v as i128,
+            (PatternKind::Bool(b), Value::Bool(v)) => b == v,
+            (PatternKind::Unit, Value::Unit) => true,
+            (PatternKind::Or(alternatives), _) => {
+                alternatives.iter().any(|alt| self.bind(alt, value))
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+mod builtins;
+mod capture;
+mod debug;
+mod error;
+mod interpreter;
+mod limits;
+mod prelude;
+mod trace;
+mod value;
+
+pub use builtins::*;
+pub use capture::*;
+pub use debug::*;
+pub use error::*;
+pub use interpreter::*;
+pub use limits::*;
+pub use trace::*;
+pub use value::*;
@@ -0,0 +1,105 @@
+use core::fmt;
+use std::time::{Duration, Instant};
+
+use crate::CALL_DEPTH_LIMIT;
+
+/// How many steps run between two checks of the clock for `Limits::timeout`.
+const TIMEOUT_CHECK_INTERVAL: u64 = 1024;
+
+/// Limits on what a program may use, for running untrusted code. A program
+/// exceeding one stops with `RuntimeErrorKind::LimitExceeded`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// How many steps a program may run: expressions evaluated by the
+    /// interpreter, instructions run by the VM.
+    pub max_steps: Option<u64>,
+    /// How many calls deep a program may go. Deeper than
+    /// `CALL_DEPTH_LIMIT` is a stack overflow either way.
+    pub max_call_depth: Option<usize>,
+    /// How many strings a program may build by interpolation, and how many
+    /// closures it may create.
+    pub max_heap_values: Option<u64>,
+    /// How long a program may run.
+    pub timeout: Option<Duration>,
+}
+
+/// A limit a program exceeded, with its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Steps(u64),
+    CallDepth(usize),
+    HeapValues(u64),
+    Timeout(Duration),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Steps(n) => write!(f, "{} steps", n),
+            Limit::CallDepth(n) => write!(f, "{} nested calls", n),
+            Limit::HeapValues(n) => write!(f, "{} heap values", n),
+            Limit::Timeout(duration) => write!(f, "{:?} of running time", duration),
+        }
+    }
+}
+
+/// Measures what a running program uses against its `Limits`, for
+/// evaluators to consult as they run it.
+#[derive(Debug, Clone, Default)]
+pub struct Meter {
+    limits: Limits,
+    steps: u64,
+    heap_values: u64,
+    deadline: Option<Instant>,
+}
+
+impl Meter {
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// Starts measuring a new run from nothing used.
+    pub fn start(&mut self) {
+        self.steps = 0;
+        self.heap_values = 0;
+        self.deadline = self.limits.timeout.map(|timeout| Instant::now() + timeout);
+    }
+
+    /// Counts a step, failing once there were too many or time is up.
+    pub fn step(&mut self) -> Result<(), Limit> {
+        self.steps += 1;
+        if let Some(max) = self.limits.max_steps
+            && self.steps > max
+        {
+            return Err(Limit::Steps(max));
+        }
+        if self.steps.is_multiple_of(TIMEOUT_CHECK_INTERVAL)
+            && let (Some(deadline), Some(timeout)) = (self.deadline, self.limits.timeout)
+            && Instant::now() >= deadline
+        {
+            return Err(Limit::Timeout(timeout));
+        }
+        Ok(())
+    }
+
+    /// Counts a string or closure created, failing once there were too
+    /// many.
+    pub fn allocate(&mut self) -> Result<(), Limit> {
+        self.heap_values += 1;
+        match self.limits.max_heap_values {
+            Some(max) if self.heap_values > max => Err(Limit::HeapValues(max)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks a call made `depth` calls deep, before the stack overflows.
+    pub fn call(&self, depth: usize) -> Result<(), Limit> {
+        match self.limits.max_call_depth {
+            Some(max) if depth >= max && max < CALL_DEPTH_LIMIT => Err(Limit::CallDepth(max)),
+            _ => Ok(()),
+        }
+    }
+}
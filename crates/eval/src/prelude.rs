@@ -0,0 +1,79 @@
+use std::cmp::Ordering;
+
+use ginto_diag::Symbol;
+use ginto_syntax::Type;
+
+use crate::{Builtins, CapturedOutput, Value};
+
+impl Builtins {
+    /// The builtins every program can use: `print`, `assert`, `min`, `max`,
+    /// `abs` and `len`.
+    pub fn with_prelude() -> Self {
+        Self::prelude(|line| println!("{}", line))
+    }
+
+    /// The prelude, with `print` writing to `output` instead of stdout.
+    pub fn with_captured_prelude(output: &CapturedOutput) -> Self {
+        let output = output.clone();
+        Self::prelude(move |line| output.push_line(line))
+    }
+
+    /// The prelude, with `print` writing its line to `print`.
+    fn prelude(print: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        let t = || Type::Param(Symbol::intern("T"));
+        let mut builtins = Self::new();
+        builtins
+            .register_generic("print", &["T"], &[t()], Type::Unit, move |args| {
+                print(&args[0].to_string());
+                Ok(Value::Unit)
+            })
+            .register("assert", &[Type::Bool], Type::Unit, |args| match args[0] {
+                Value::Bool(true) => Ok(Value::Unit),
+                _ => Err("assertion failed".to_string()),
+            })
+            .register_generic("min", &["T"], &[t(), t()], t(), |args| {
+                match compare(&args[0], &args[1])? {
+                    Ordering::Greater => Ok(args[1].clone()),
+                    _ => Ok(args[0].clone()),
+                }
+            })
+            .register_generic("max", &["T"], &[t(), t()], t(), |args| {
+                match compare(&args[0], &args[1])? {
+                    Ordering::Greater => Ok(args[0].clone()),
+                    _ => Ok(args[1].clone()),
+                }
+            })
+            .register("abs", &[Type::I64], Type::I64, |args| match args[0] {
+                Value::I64(v) => v
+                    .checked_abs()
+                    .map(Value::I64)
+                    .ok_or_else(|| format!("`abs({})` would overflow `i64`", v)),
+                _ => unreachable!("`abs` takes an `i64`"),
+            })
+            .register("len", &[Type::Str], Type::U64, |args| match &args[0] {
+                // the length in characters, as strings are indexed
+                Value::Str(v) => Ok(Value::U64(v.chars().count() as u64)),
+                _ => unreachable!("`len` takes a `str`"),
+            });
+        builtins
+    }
+}
+
+/// Orders two values of the same type. Functions have no order.
+fn compare(lhs: &Value, rhs: &Value) -> Result<Ordering, String> {
+    match (lhs, rhs) {
+        (Value::Unit, Value::Unit) => Ok(Ordering::Equal),
+        (Value::Bool(a), Value::Bool(b)) => Ok(a.cmp(b)),
+        (Value::U64(a), Value::U64(b)) => Ok(a.cmp(b)),
+        (Value::I64(a), Value::I64(b)) => Ok(a.cmp(b)),
+        (Value::F64(a), Value::F64(b)) => a
+            .partial_cmp(b)
+            .ok_or_else(|| "cannot compare `NaN`".to_string()),
+        (Value::Str(a), Value::Str(b)) => Ok(a.cmp(b)),
+        _ => Err(format!(
+            "cannot compare `{}` with `{}`",
+            lhs.type_name(),
+            rhs.type_name()
+        )),
+    }
+}
@@ -0,0 +1,110 @@
+use ginto_diag::{FileId, Span};
+
+use crate::{RuntimeError, Value};
+
+/// Receives every expression the interpreter evaluates, once it is done
+/// with it.
+pub trait Tracer {
+    fn on_eval(&mut self, event: &TraceEvent);
+}
+
+/// What evaluating an expression came to.
+#[derive(Debug, Clone, Copy)]
+pub enum Outcome<'e> {
+    Value(&'e Value),
+    /// A `return` left the function with the value.
+    Returned(&'e Value),
+    /// The expression is a call in tail position, made in place of the
+    /// running function once it is done.
+    TailCall,
+    Failed(&'e RuntimeError),
+}
+
+/// An expression the interpreter evaluated.
+#[derive(Debug, Clone, Copy)]
+pub struct TraceEvent<'e> {
+    file_id: FileId,
+    span: Span,
+    func: &'e str,
+    depth: usize,
+    inputs: &'e [Value],
+    outcome: Outcome<'e>,
+}
+
+impl<'e> TraceEvent<'e> {
+    pub fn file_id(&self) -> FileId {
+        self.file_id
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The name of the function the expression is in, `{closure}` in a
+    /// closure and empty outside of any function.
+    pub fn func(&self) -> &'e str {
+        self.func
+    }
+
+    /// How many expressions the expression is nested in, counting those of
+    /// the calls it is in.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The values of the expression's subexpressions, in the order they
+    /// were evaluated.
+    pub fn inputs(&self) -> &'e [Value] {
+        self.inputs
+    }
+
+    pub fn outcome(&self) -> Outcome<'e> {
+        self.outcome
+    }
+}
+
+/// Collects the inputs of the expressions being evaluated for a
+/// [`Tracer`], for evaluators to call around each expression.
+pub struct Tracing<'a> {
+    tracer: &'a mut dyn Tracer,
+    /// The values of the subexpressions evaluated so far of each expression
+    /// being evaluated, innermost last.
+    inputs: Vec<Vec<Value>>,
+}
+
+impl<'a> Tracing<'a> {
+    pub fn new(tracer: &'a mut dyn Tracer) -> Self {
+        Self {
+            tracer,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Starts an expression, or a function body whose value is not an input
+    /// of the call evaluating it.
+    pub fn enter(&mut self) {
+        self.inputs.push(Vec::new());
+    }
+
+    /// Ends the expression started last, reporting it to the tracer and
+    /// making its value an input of the expression it is in.
+    pub fn leave(&mut self, file_id: FileId, span: Span, func: &str, outcome: Outcome) {
+        let inputs = self.inputs.pop().unwrap_or_default();
+        self.tracer.on_eval(&TraceEvent {
+            file_id,
+            span,
+            func,
+            depth: self.inputs.len(),
+            inputs: &inputs,
+            outcome,
+        });
+        if let (Outcome::Value(value), Some(outer)) = (outcome, self.inputs.last_mut()) {
+            outer.push(value.clone());
+        }
+    }
+
+    /// Ends the function body started last, without reporting it.
+    pub fn skip(&mut self) {
+        self.inputs.pop();
+    }
+}
@@ -0,0 +1,360 @@
+use core::fmt;
+use std::sync::Arc;
+
+use ginto_resolve::DefId;
+use ginto_syntax::{BinOp, Type, UnaryOp};
+
+/// A value computed at runtime.
+///
+/// Arithmetic on integers is checked: overflow and division by zero are
+/// errors rather than wrapping or panicking. Arithmetic on floats follows
+/// IEEE 754, so dividing by zero gives an infinity.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    /// An immutable string, shared by the values copied from it.
+    Str(Arc<str>),
+    /// A function named as a value, called through a variable.
+    Fn(DefId),
+    /// The closure `func` with the values of its captures, in the order
+    /// the resolver lists them.
+    Closure {
+        func: DefId,
+        env: Env,
+    },
+}
+
+/// The captured values of a closure, shared by the copies of the closure.
+///
+/// Values are immutable and a closure captures values made before it, so
+/// environments cannot form cycles and counting references frees them all.
+/// They can nest deeply though, as closures capturing closures, so they are
+/// freed one at a time rather than recursively.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Env(Arc<[Value]>);
+
+impl Env {
+    /// Whether the environment holds closures with captures of their own,
+    /// which would be freed recursively.
+    fn nests(&self) -> bool {
+        self.0
+            .iter()
+            .any(|value| matches!(value, Value::Closure { env, .. } if !env.is_empty()))
+    }
+}
+
+impl std::ops::Deref for Env {
+    type Target = [Value];
+
+    fn deref(&self) -> &[Value] {
+        &self.0
+    }
+}
+
+impl From<Vec<Value>> for Env {
+    fn from(values: Vec<Value>) -> Self {
+        Env(values.into())
+    }
+}
+
+impl FromIterator<Value> for Env {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        Env(iter.into_iter().collect())
+    }
+}
+
+impl Drop for Env {
+    fn drop(&mut self) {
+        if !self.nests() {
+            return;
+        }
+        // the nested environments this one alone holds are taken out before
+        // it is freed, leaving empty ones to free without recursing
+        let mut pending = vec![std::mem::take(&mut self.0)];
+        while let Some(mut env) = pending.pop() {
+            let Some(values) = Arc::get_mut(&mut env) else {
+                continue;
+            };
+            for value in values {
+                if let Value::Closure { env, .. } = value
+                    && !env.is_empty()
+                {
+                    pending.push(std::mem::take(&mut env.0));
+                }
+            }
+        }
+    }
+}
+
+/// Why an operation on values failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueError {
+    Overflow,
+    DivisionByZero,
+    /// The operator is not defined for the operands' types.
+    InvalidOperands,
+}
+
+impl Value {
+    /// The name of the value's type as written in source.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "()",
+            Value::Bool(_) => "bool",
+            Value::U64(_) => "u64",
+            Value::I64(_) => "i64",
+            Value::F64(_) => "f64",
+            Value::Str(_) => "str",
+            Value::Fn(_) | Value::Closure { .. } => "fn",
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::U64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::I64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::F64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Whether the values are the same, unlike `==` telling `0.0` from
+    /// `-0.0` and taking a `NaN` to be itself.
+    pub fn is_identical(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::F64(l), Value::F64(r)) => l.to_bits() == r.to_bits(),
+            _ => self == other,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// An integer of type `ty`, which is `u64` unless it is `i64`, or
+    /// `None` if `v` does not fit.
+    pub fn int(v: i128, ty: &Type) -> Option<Value> {
+        match ty {
+            Type::I64 => i64::try_from(v).ok().map(Value::I64),
+            _ => u64::try_from(v).ok().map(Value::U64),
+        }
+    }
+
+    pub fn unary(&self, op: UnaryOp) -> Result<Value, ValueError> {
+        match (op, self) {
+            (UnaryOp::Not, Value::Bool(v)) => Ok(Value::Bool(!v)),
+            (UnaryOp::Neg, Value::I64(v)) => {
+                v.checked_neg().map(Value::I64).ok_or(ValueError::Overflow)
+            }
+            (UnaryOp::Neg, Value::F64(v)) => Ok(Value::F64(-v)),
+            (UnaryOp::Neg, Value::U64(0)) => Ok(Value::U64(0)),
+            (UnaryOp::Neg, Value::U64(_)) => Err(ValueError::Overflow),
+            _ => Err(ValueError::InvalidOperands),
+        }
+    }
+
+    /// Applies the binary operator `op`. `&&` and `||` evaluate both
+    /// operands here; short-circuiting is up to the caller.
+    pub fn binary(&self, op: BinOp, rhs: &Value) -> Result<Value, ValueError> {
+        match op {
+            BinOp::Add => self.checked_add(rhs),
+            BinOp::Sub => self.checked_sub(rhs),
+            BinOp::Mul => self.checked_mul(rhs),
+            BinOp::Div => self.checked_div(rhs),
+            BinOp::Mod => self.checked_rem(rhs),
+            BinOp::Equal => Ok(Value::Bool(self == rhs)),
+            BinOp::NotEq => Ok(Value::Bool(self != rhs)),
+            BinOp::And | BinOp::Or => match (self, rhs) {
+                (Value::Bool(l), Value::Bool(r)) if op == BinOp::And => Ok(Value::Bool(*l && *r)),
+                (Value::Bool(l), Value::Bool(r)) => Ok(Value::Bool(*l || *r)),
+                _ => Err(ValueError::InvalidOperands),
+            },
+            BinOp::Less | BinOp::Le | BinOp::Greater | BinOp::Ge => {
+                // every comparison with `NaN` is false, so floats have no
+                // ordering to share with the other types
+                if let (Value::F64(l), Value::F64(r)) = (self, rhs) {
+                    return Ok(Value::Bool(match op {
+                        BinOp::Less => l < r,
+                        BinOp::Le => l <= r,
+                        BinOp::Greater => l > r,
+                        _ => l >= r,
+                    }));
+                }
+                let ordering = match (self, rhs) {
+                    (Value::U64(l), Value::U64(r)) => l.cmp(r),
+                    (Value::I64(l), Value::I64(r)) => l.cmp(r),
+                    (Value::Bool(l), Value::Bool(r)) => l.cmp(r),
+                    (Value::Str(l), Value::Str(r)) => l.cmp(r),
+                    _ => return Err(ValueError::InvalidOperands),
+                };
+                Ok(Value::Bool(match op {
+                    BinOp::Less => ordering.is_lt(),
+                    BinOp::Le => ordering.is_le(),
+                    BinOp::Greater => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                }))
+            }
+        }
+    }
+
+    /// Adds numbers or joins strings.
+    pub fn checked_add(&self, rhs: &Value) -> Result<Value, ValueError> {
+        if let (Value::Str(l), Value::Str(r)) = (self, rhs) {
+            return Ok(Value::Str(format!("{}{}", l, r).into()));
+        }
+        self.arith(rhs, u64::checked_add, i64::checked_add, |l, r| l + r)
+    }
+
+    pub fn checked_sub(&self, rhs: &Value) -> Result<Value, ValueError> {
+        self.arith(rhs, u64::checked_sub, i64::checked_sub, |l, r| l - r)
+    }
+
+    pub fn checked_mul(&self, rhs: &Value) -> Result<Value, ValueError> {
+        self.arith(rhs, u64::checked_mul, i64::checked_mul, |l, r| l * r)
+    }
+
+    pub fn checked_div(&self, rhs: &Value) -> Result<Value, ValueError> {
+        if rhs.is_zero() {
+            return Err(ValueError::DivisionByZero);
+        }
+        self.arith(rhs, u64::checked_div, i64::checked_div, |l, r| l / r)
+    }
+
+    pub fn checked_rem(&self, rhs: &Value) -> Result<Value, ValueError> {
+        if rhs.is_zero() {
+            return Err(ValueError::DivisionByZero);
+        }
+        self.arith(rhs, u64::checked_rem, i64::checked_rem, |l, r| l % r)
+    }
+
+    fn is_zero(&self) -> bool {
+        matches!(self, Value::U64(0) | Value::I64(0))
+    }
+
+    fn arith(
+        &self,
+        rhs: &Value,
+        unsigned: fn(u64, u64) -> Option<u64>,
+        signed: fn(i64, i64) -> Option<i64>,
+        float: fn(f64, f64) -> f64,
+    ) -> Result<Value, ValueError> {
+        let result = match (self, rhs) {
+            (&Value::U64(l), &Value::U64(r)) => unsigned(l, r).map(Value::U64),
+            (&Value::I64(l), &Value::I64(r)) => signed(l, r).map(Value::I64),
+            (&Value::F64(l), &Value::F64(r)) => Some(Value::F64(float(l, r))),
+            _ => return Err(ValueError::InvalidOperands),
+        };
+        result.ok_or(ValueError::Overflow)
+    }
+
+    /// Converts the value to `ty` by reinterpreting its bits, the same way
+    /// constant evaluation does. Floats convert to and from integers by
+    /// value, rounding toward zero and saturating. Values that cannot be
+    /// converted are returned unchanged.
+    pub fn cast(&self, ty: &Type) -> Value {
+        match (self, ty) {
+            (&Value::F64(v), Type::U64) => return Value::U64(v as u64),
+            (&Value::F64(v), Type::I64) => return Value::I64(v as i64),
+            (&Value::U64(v), Type::F64) => return Value::F64(v as f64),
+            (&Value::I64(v), Type::F64) => return Value::F64(v as f64),
+            _ => {}
+        }
+        let bits = match *self {
+            Value::U64(v) => v,
+            Value::I64(v) => v as u64,
+            Value::Bool(v) => v as u64,
+            Value::Unit | Value::F64(_) | Value::Str(_) | Value::Fn(_) | Value::Closure { .. } => {
+                return self.clone();
+            }
+        };
+        match ty {
+            Type::U64 => Value::U64(bits),
+            Type::I64 => Value::I64(bits as i64),
+            _ => self.clone(),
+        }
+    }
+}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Unit
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(v)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(v: u64) -> Self {
+        Value::U64(v)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(v: i64) -> Self {
+        Value::I64(v)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Self {
+        Value::F64(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::Str(v.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::Str(v.into())
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::U64(v) => write!(f, "{}", v),
+            Value::I64(v) => write!(f, "{}", v),
+            // `{:?}` keeps the `.0` of whole numbers, telling them from integers
+            Value::F64(v) => write!(f, "{:?}", v),
+            Value::Str(v) => write!(f, "{}", v),
+            Value::Fn(_) => write!(f, "<fn>"),
+            Value::Closure { .. } => write!(f, "<closure>"),
+        }
+    }
+}
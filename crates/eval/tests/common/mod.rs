@@ -0,0 +1,36 @@
+//! What the tests of the interpreter share: running programs checked as
+//! `ginto` does on a stack the interpreter fits in.
+
+// each test uses some of the helpers
+#![allow(dead_code)]
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible, SourceManager};
+use ginto_eval::{Interpreter, RuntimeError, Value};
+use ginto_test_support::{check_files, on_interpreter_stack};
+
+/// The result of running the program whose root file `main.ginto` holds
+/// `source`.
+pub fn run(source: &str) -> Result<Value, RuntimeError> {
+    run_files(source, &[]).0
+}
+
+/// The result of running the program whose root file `main.ginto` holds
+/// `root`, with the modules of `files`, named after their path, and the
+/// files it was loaded from.
+pub fn run_files(
+    root: &str,
+    files: &[(&str, &str)],
+) -> (Result<Value, RuntimeError>, SourceManager) {
+    let (compilation, sm) = check_files(root, files);
+    let result = on_interpreter_stack(|| {
+        Interpreter::new(&compilation.resolutions, &compilation.types)
+            .with_builtins(&compilation.builtins)
+            .run_modules(&compilation.tree)
+    });
+    (result, sm)
+}
+
+/// The diagnostic of the runtime error running `source` stops with.
+pub fn error(source: &str) -> Diagnostic {
+    run(source).unwrap_err().into_diagnostic()
+}
@@ -0,0 +1,201 @@
+//! Runtime errors point at the failing expression and at the calls that
+//! led to it.
+
+mod common;
+
+use common::error;
+use ginto_diag::{DiagnosticConvertible, Severity};
+use ginto_eval::{CALL_DEPTH_LIMIT, Interpreter, RuntimeErrorKind, STACK_FRAMES_SHOWN, Value};
+use ginto_test_support::{check_files, labels};
+
+#[test]
+fn shows_the_calls_leading_to_a_division_by_zero() {
+    let source = "fn div(a: u64, b: u64) -> u64:
+    a / b
+
+fn plus_one(n: u64, d: u64) -> u64:
+    div(n, d) + 1
+
+fn main() -> u64:
+    plus_one(1, 0) + 1
+";
+    let diagnostic = error(source);
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.message, "attempt to divide by zero");
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            ("a / b", "attempt to compute `1 / 0`".to_string()),
+            ("div(n, d)", "in call to `div`".to_string()),
+            ("plus_one(1, 0)", "in call to `plus_one`".to_string()),
+        ]
+    );
+    assert!(diagnostic.notes.is_empty(), "{:?}", diagnostic.notes);
+}
+
+#[test]
+fn reports_overflow_with_the_overflowing_type() {
+    let source = "fn sub(a: i64, b: i64) -> i64:
+    a - b
+
+fn main() -> i64:
+    sub(-9223372036854775808, 1) * 2
+";
+    let diagnostic = error(source);
+    assert_eq!(diagnostic.message, "arithmetic overflow");
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            (
+                "a - b",
+                "attempt to compute `-9223372036854775808 - 1`, which would overflow `i64`"
+                    .to_string()
+            ),
+            (
+                "sub(-9223372036854775808, 1)",
+                "in call to `sub`".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn shows_recursive_calls_once() {
+    let source = "fn depth(n: u64) -> u64:
+    if n = 0:
+        0
+    else:
+        depth(n - 1) + 1
+
+fn main() -> u64:
+    depth(1000000)
+";
+    let diagnostic = error(source);
+    assert_eq!(diagnostic.message, "stack overflow");
+    assert_eq!(
+        labels(source, &diagnostic),
+        [
+            (
+                "depth(n - 1)",
+                "this call to `depth` is too deep".to_string()
+            ),
+            (
+                "depth(n - 1)",
+                format!(
+                    "in call to `depth`, repeated {} times",
+                    CALL_DEPTH_LIMIT - 1
+                )
+            ),
+        ]
+    );
+    assert_eq!(
+        diagnostic.notes,
+        [format!(
+            "calls can only be nested {} deep",
+            CALL_DEPTH_LIMIT
+        )]
+    );
+}
+
+#[test]
+fn leaves_out_calls_past_the_innermost() {
+    // the calls alternate, so none of them are shown once for a run
+    let source = "fn ping(n: u64) -> u64:
+    pong(n) + 1
+
+fn pong(n: u64) -> u64:
+    10 / n + ping(n - 1)
+
+fn main() -> u64:
+    ping(5) * 2
+";
+    let diagnostic = error(source);
+    let calls: Vec<_> = labels(source, &diagnostic)[1..]
+        .iter()
+        .map(|(call, _)| *call)
+        .collect();
+    assert_eq!(calls.len(), STACK_FRAMES_SHOWN);
+    assert_eq!(calls[..3], ["pong(n)", "ping(n - 1)", "pong(n)"]);
+    // 6 calls of `pong`, 5 of `ping` from `pong` and 1 from `main`
+    assert_eq!(
+        diagnostic.notes,
+        [format!(
+            "{} more calls are not shown",
+            12 - STACK_FRAMES_SHOWN
+        )]
+    );
+}
+
+#[test]
+fn shows_calls_in_other_modules() {
+    let (result, sm) = common::run_files(
+        "mod math\n\nfn main() -> u64:\n    math::div(1, 0) + 1\n",
+        &[(
+            "math.ginto",
+            "pub fn div(a: u64, b: u64) -> u64:\n    a / b\n",
+        )],
+    );
+    let diagnostic = result.unwrap_err().into_diagnostic();
+    let files: Vec<_> = diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            let file = sm.get_file(label.file_id).unwrap();
+            (
+                file.name.as_str(),
+                &file.source[label.span.start.0..label.span.end.0],
+            )
+        })
+        .collect();
+    assert_eq!(
+        files,
+        [("math.ginto", "a / b"), ("main.ginto", "math::div(1, 0)")]
+    );
+}
+
+#[test]
+fn reports_calls_with_the_wrong_number_of_arguments() {
+    let (compilation, _) = check_files(
+        "fn add(a: u64, b: u64) -> u64:\n    a + b\n\nfn main() -> u64:\n    add(1, 2)\n",
+        &[],
+    );
+    let (add, _) = compilation
+        .resolutions
+        .defs()
+        .find(|(_, def)| def.name == "add")
+        .unwrap();
+    let mut interpreter = Interpreter::new(&compilation.resolutions, &compilation.types);
+    interpreter.load(&compilation.tree.module(compilation.tree.root()).program);
+    let error = interpreter.call(add, vec![Value::U64(1)]).unwrap_err();
+    assert!(error.stack.is_empty());
+    assert_eq!(
+        error.kind,
+        RuntimeErrorKind::ArityMismatch {
+            name: "add".to_string(),
+            expected: 2,
+            found: 1,
+        }
+    );
+    assert_eq!(
+        error.into_diagnostic().message,
+        "`add` takes 2 arguments but 1 was supplied"
+    );
+    assert_eq!(
+        interpreter.call(add, vec![Value::U64(1), Value::U64(2)]),
+        Ok(Value::U64(3))
+    );
+}
+
+#[test]
+fn reports_programs_without_main() {
+    let (compilation, _) = check_files("fn start() -> u64:\n    1\n", &[]);
+    let error = Interpreter::new(&compilation.resolutions, &compilation.types)
+        .run_modules(&compilation.tree)
+        .unwrap_err();
+    let diagnostic = error.into_diagnostic();
+    assert_eq!(diagnostic.message, "`main` function not found");
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("add a `fn main()` to run the program")
+    );
+}
@@ -0,0 +1,185 @@
+//! Checked programs run to the value their `main` returns.
+
+mod common;
+
+use common::run;
+use ginto_eval::{RuntimeErrorKind, Value};
+
+#[test]
+fn computes_arithmetic() {
+    let source = "fn main() -> u64:
+    let a = 7
+    (a + 3) * 4 - 10 / 3 + 17 mod 5
+";
+    assert_eq!(run(source), Ok(Value::U64(39)));
+    let source = "fn main() -> i64:
+    let a: i64 = 3
+    -a * 5
+";
+    assert_eq!(run(source), Ok(Value::I64(-15)));
+    let source = "fn main() -> f64:
+    7 as f64 / 2.0
+";
+    assert_eq!(run(source), Ok(Value::F64(3.5)));
+}
+
+#[test]
+fn checks_for_overflow() {
+    let source = "fn inc(n: u64) -> u64:
+    n + 1
+
+fn main() -> u64:
+    inc(18446744073709551615)
+";
+    let error = run(source).unwrap_err();
+    assert!(
+        matches!(
+            &error.kind,
+            RuntimeErrorKind::Overflow { expr, ty: "u64", .. } if expr == "18446744073709551615 + 1"
+        ),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn short_circuits_logical_operators() {
+    // `boom` divides by zero if it is called
+    let source = "fn boom(n: u64) -> bool:
+    n / n = 1
+
+fn main() -> bool:
+    let a = false && boom(0)
+    let b = true || boom(0)
+    not a && b
+";
+    assert_eq!(run(source), Ok(Value::Bool(true)));
+}
+
+#[test]
+fn binds_and_assigns_variables() {
+    let source = "fn main() -> u64:
+    let mut total = 1
+    let step: u64
+    step = 2
+    total = total + step
+    total = total * 10
+    total
+";
+    assert_eq!(run(source), Ok(Value::U64(30)));
+}
+
+#[test]
+fn calls_functions() {
+    let source = "fn fib(n: u64) -> u64:
+    if n < 2:
+        n
+    else:
+        fib(n - 1) + fib(n - 2)
+
+fn twice(f: fn(u64) -> u64, x: u64) -> u64:
+    f(f(x))
+
+fn main() -> u64:
+    twice(fib, 6)
+";
+    assert_eq!(run(source), Ok(Value::U64(21)));
+}
+
+#[test]
+fn runs_loops_and_early_returns() {
+    let source = "fn first_square_above(limit: u64) -> u64:
+    let mut i = 0
+    while true:
+        if i * i > limit:
+            return i
+        i = i + 1
+    0
+
+fn main() -> u64:
+    let mut sum = 0
+    let mut i = 0
+    while i <> 5:
+        sum = sum + i
+        i = i + 1
+    sum * 100 + first_square_above(50)
+";
+    assert_eq!(run(source), Ok(Value::U64(1008)));
+}
+
+#[test]
+fn matches_patterns() {
+    let source = "fn classify(n: i64) -> u64:
+    match n:
+        0 -> 1
+        1 | 2 -> 2
+        x -> match x > 0:
+            true -> 3
+            false -> 4
+
+fn main() -> u64:
+    classify(0) * 1000 + classify(2) * 100 + classify(9) * 10 + classify(-9)
+";
+    assert_eq!(run(source), Ok(Value::U64(1234)));
+}
+
+#[test]
+fn captures_values_in_closures() {
+    let source = "fn adder(n: u64) -> fn(u64) -> u64:
+    |x| x + n
+
+fn main() -> u64:
+    let mut k = 1
+    let add = adder(10)
+    let scale = |x: u64| x * k
+    k = 100
+    add(scale(5))
+";
+    // closures capture the value a variable has when they are made
+    assert_eq!(run(source), Ok(Value::U64(15)));
+}
+
+#[test]
+fn calls_methods_and_generic_functions() {
+    let source = "trait Shape:
+    fn area(self) -> u64
+
+impl Shape for u64:
+    fn area(self) -> u64:
+        self * self
+
+fn id<T>(x: T) -> T:
+    x
+
+fn main() -> str:
+    let n = id(3).area()
+    let s = id(\"area \")
+    s + \"{n}\"
+";
+    assert_eq!(run(source), Ok(Value::from("area 9")));
+}
+
+#[test]
+fn runs_functions_of_other_modules() {
+    let (result, _) = common::run_files(
+        "mod math\n\nuse math::square\n\nfn main() -> u64:\n    square(3) + math::util::half(10)\n",
+        &[
+            (
+                "math.ginto",
+                "pub mod util\n\npub fn square(n: u64) -> u64:\n    n * n\n",
+            ),
+            (
+                "math/util.ginto",
+                "pub fn half(n: u64) -> u64:\n    n / 2\n",
+            ),
+        ],
+    );
+    assert_eq!(result, Ok(Value::U64(14)));
+}
+
+#[test]
+fn returns_unit_from_main_without_a_return_type() {
+    let source = "fn main():
+    let x = 1
+";
+    assert_eq!(run(source), Ok(Value::Unit));
+}
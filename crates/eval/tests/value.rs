@@ -0,0 +1,202 @@
+//! Values compute with checked integer arithmetic, convert between types
+//! and print as they are written in source.
+
+use ginto_eval::{Env, Value, ValueError};
+use ginto_resolve::DefId;
+use ginto_syntax::{BinOp, Type, UnaryOp};
+
+#[test]
+fn checks_integer_arithmetic() {
+    let max = Value::U64(u64::MAX);
+    assert_eq!(max.checked_add(&Value::U64(1)), Err(ValueError::Overflow));
+    assert_eq!(
+        Value::U64(0).checked_sub(&Value::U64(1)),
+        Err(ValueError::Overflow)
+    );
+    assert_eq!(
+        Value::I64(i64::MIN).checked_mul(&Value::I64(-1)),
+        Err(ValueError::Overflow)
+    );
+    assert_eq!(
+        Value::I64(i64::MIN).checked_div(&Value::I64(-1)),
+        Err(ValueError::Overflow)
+    );
+    assert_eq!(
+        Value::U64(7).checked_add(&Value::U64(5)),
+        Ok(Value::U64(12))
+    );
+    assert_eq!(
+        Value::I64(-7).checked_rem(&Value::I64(3)),
+        Ok(Value::I64(-1))
+    );
+}
+
+#[test]
+fn refuses_to_divide_integers_by_zero() {
+    assert_eq!(
+        Value::U64(1).checked_div(&Value::U64(0)),
+        Err(ValueError::DivisionByZero)
+    );
+    assert_eq!(
+        Value::I64(1).checked_rem(&Value::I64(0)),
+        Err(ValueError::DivisionByZero)
+    );
+    // floats divide by zero to an infinity
+    assert_eq!(
+        Value::F64(1.0).checked_div(&Value::F64(0.0)),
+        Ok(Value::F64(f64::INFINITY))
+    );
+}
+
+#[test]
+fn rejects_operands_of_different_types() {
+    assert_eq!(
+        Value::U64(1).checked_add(&Value::I64(1)),
+        Err(ValueError::InvalidOperands)
+    );
+    assert_eq!(
+        Value::Bool(true).binary(BinOp::Less, &Value::U64(1)),
+        Err(ValueError::InvalidOperands)
+    );
+    assert_eq!(
+        Value::Str("a".into()).unary(UnaryOp::Neg),
+        Err(ValueError::InvalidOperands)
+    );
+}
+
+#[test]
+fn applies_operators() {
+    assert_eq!(
+        Value::from("ab").binary(BinOp::Add, &Value::from("cd")),
+        Ok(Value::from("abcd"))
+    );
+    assert_eq!(
+        Value::from("ab").binary(BinOp::Less, &Value::from("b")),
+        Ok(Value::Bool(true))
+    );
+    assert_eq!(
+        Value::Bool(true).binary(BinOp::And, &Value::Bool(false)),
+        Ok(Value::Bool(false))
+    );
+    assert_eq!(
+        Value::F64(f64::NAN).binary(BinOp::Ge, &Value::F64(f64::NAN)),
+        Ok(Value::Bool(false))
+    );
+    assert_eq!(Value::U64(0).unary(UnaryOp::Neg), Ok(Value::U64(0)));
+    assert_eq!(Value::U64(1).unary(UnaryOp::Neg), Err(ValueError::Overflow));
+    assert_eq!(
+        Value::I64(i64::MIN).unary(UnaryOp::Neg),
+        Err(ValueError::Overflow)
+    );
+    assert_eq!(
+        Value::Bool(true).unary(UnaryOp::Not),
+        Ok(Value::Bool(false))
+    );
+}
+
+#[test]
+fn names_the_types_of_values() {
+    let values = [
+        Value::Unit,
+        Value::Bool(true),
+        Value::U64(1),
+        Value::I64(-1),
+        Value::F64(0.5),
+        Value::from("s"),
+        Value::Fn(DefId(0)),
+        Value::Closure {
+            func: DefId(0),
+            env: Env::from(vec![Value::U64(1)]),
+        },
+    ];
+    let names: Vec<_> = values.iter().map(Value::type_name).collect();
+    assert_eq!(
+        names,
+        ["()", "bool", "u64", "i64", "f64", "str", "fn", "fn"]
+    );
+}
+
+#[test]
+fn prints_values_as_source_writes_them() {
+    let printed: Vec<_> = [
+        Value::Unit,
+        Value::Bool(false),
+        Value::U64(42),
+        Value::I64(-42),
+        Value::F64(3.0),
+        Value::F64(0.25),
+        Value::from("héllo"),
+        Value::Fn(DefId(0)),
+        Value::Closure {
+            func: DefId(0),
+            env: Env::from(Vec::new()),
+        },
+    ]
+    .iter()
+    .map(Value::to_string)
+    .collect();
+    assert_eq!(
+        printed,
+        [
+            "()",
+            "false",
+            "42",
+            "-42",
+            "3.0",
+            "0.25",
+            "héllo",
+            "<fn>",
+            "<closure>"
+        ]
+    );
+}
+
+#[test]
+fn converts_to_and_from_rust_values() {
+    assert_eq!(Value::from(()), Value::Unit);
+    assert_eq!(Value::from(7u64).as_u64(), Some(7));
+    assert_eq!(Value::from(-7i64).as_i64(), Some(-7));
+    assert_eq!(Value::from(1.5).as_f64(), Some(1.5));
+    assert_eq!(Value::from(true).as_bool(), Some(true));
+    assert_eq!(Value::from("s".to_string()).as_str(), Some("s"));
+    // values only convert to the type they have
+    assert_eq!(Value::U64(7).as_i64(), None);
+    assert_eq!(Value::Bool(true).as_u64(), None);
+    assert_eq!(Value::U64(1).as_str(), None);
+}
+
+#[test]
+fn makes_integers_that_fit_their_type() {
+    assert_eq!(Value::int(-1, &Type::I64), Some(Value::I64(-1)));
+    assert_eq!(Value::int(-1, &Type::U64), None);
+    assert_eq!(
+        Value::int(u64::MAX as i128, &Type::U64),
+        Some(Value::U64(u64::MAX))
+    );
+    assert_eq!(Value::int(u64::MAX as i128, &Type::I64), None);
+}
+
+#[test]
+fn casts_between_types() {
+    // integers keep their bits
+    assert_eq!(Value::I64(-1).cast(&Type::U64), Value::U64(u64::MAX));
+    assert_eq!(Value::U64(u64::MAX).cast(&Type::I64), Value::I64(-1));
+    assert_eq!(Value::Bool(true).cast(&Type::U64), Value::U64(1));
+    // floats convert by value, rounding toward zero and saturating
+    assert_eq!(Value::F64(-2.7).cast(&Type::I64), Value::I64(-2));
+    assert_eq!(Value::F64(-2.7).cast(&Type::U64), Value::U64(0));
+    assert_eq!(Value::F64(1e30).cast(&Type::U64), Value::U64(u64::MAX));
+    assert_eq!(Value::I64(-3).cast(&Type::F64), Value::F64(-3.0));
+    // values that cannot be converted are unchanged
+    assert_eq!(Value::from("1").cast(&Type::U64), Value::from("1"));
+}
+
+#[test]
+fn tells_identical_floats_apart_from_equal_ones() {
+    assert_eq!(Value::F64(0.0), Value::F64(-0.0));
+    assert!(!Value::F64(0.0).is_identical(&Value::F64(-0.0)));
+    assert_ne!(Value::F64(f64::NAN), Value::F64(f64::NAN));
+    assert!(Value::F64(f64::NAN).is_identical(&Value::F64(f64::NAN)));
+    assert!(Value::from("a").is_identical(&Value::from("a")));
+    assert!(!Value::U64(1).is_identical(&Value::I64(1)));
+}
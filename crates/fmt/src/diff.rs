@@ -0,0 +1,202 @@
+use core::{fmt::Write, ops::Range};
+
+/// Lines of unchanged context around each change.
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// Writes the changes from `old` to `new`, the contents of the file
+/// `name` before and after formatting, as a unified diff. Returns an empty
+/// string if they are equal.
+pub fn unified_diff(name: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<_> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<_> = new.split_inclusive('\n').collect();
+    let edits = diff_lines(&old_lines, &new_lines);
+    if edits.iter().all(|edit| *edit == Edit::Keep) {
+        return String::new();
+    }
+
+    let mut out = format!("--- {name}\n+++ {name}\n");
+    // the line of each text before each edit
+    let mut positions = Vec::with_capacity(edits.len() + 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for edit in &edits {
+        positions.push((old_pos, new_pos));
+        match edit {
+            Edit::Keep => (old_pos, new_pos) = (old_pos + 1, new_pos + 1),
+            Edit::Delete => old_pos += 1,
+            Edit::Insert => new_pos += 1,
+        }
+    }
+    positions.push((old_pos, new_pos));
+
+    for (start, end) in hunks(&edits) {
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        writeln!(
+            out,
+            "@@ -{} +{} @@",
+            range(old_start, old_end - old_start),
+            range(new_start, new_end - new_start)
+        )
+        .expect("writing to a string can't fail");
+        for (edit, &(old_pos, new_pos)) in edits[start..end].iter().zip(&positions[start..]) {
+            let (sign, line) = match edit {
+                Edit::Keep => (' ', old_lines[old_pos]),
+                Edit::Delete => ('-', old_lines[old_pos]),
+                Edit::Insert => ('+', new_lines[new_pos]),
+            };
+            out.push(sign);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+/// A replacement of the bytes `range` of a text by `new_text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_text: String,
+}
+
+/// The edits turning `old` into `new`, one for each run of changed lines,
+/// in order. Their ranges are in `old` and don't overlap.
+pub fn text_edits(old: &str, new: &str) -> Vec<TextEdit> {
+    let old_lines: Vec<_> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<_> = new.split_inclusive('\n').collect();
+    let mut edits: Vec<TextEdit> = Vec::new();
+    // the byte offset in `old` and the line of `new` before each edit
+    let (mut offset, mut new_pos) = (0, 0);
+    let mut old_pos = 0;
+    let mut in_change = false;
+    for edit in diff_lines(&old_lines, &new_lines) {
+        if edit == Edit::Keep {
+            in_change = false;
+            offset += old_lines[old_pos].len();
+            (old_pos, new_pos) = (old_pos + 1, new_pos + 1);
+            continue;
+        }
+        if !in_change {
+            edits.push(TextEdit {
+                range: offset..offset,
+                new_text: String::new(),
+            });
+            in_change = true;
+        }
+        let last = edits.last_mut().expect("a change was started");
+        match edit {
+            Edit::Delete => {
+                offset += old_lines[old_pos].len();
+                old_pos += 1;
+                last.range.end = offset;
+            }
+            Edit::Insert => {
+                last.new_text.push_str(new_lines[new_pos]);
+                new_pos += 1;
+            }
+            Edit::Keep => unreachable!(),
+        }
+    }
+    edits
+}
+
+/// The range of `len` lines from the 0-based line `start`, as a hunk header
+/// writes it. An empty range is written as the line before it.
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{len}", start + 1),
+    }
+}
+
+/// Splits `edits` into the ranges shown as hunks: each change with the
+/// context around it, with changes whose context overlaps in one hunk.
+fn hunks(edits: &[Edit]) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in edits
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| **edit != Edit::Keep)
+    {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + 1 + CONTEXT).min(edits.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    hunks
+}
+
+/// The shortest edit script from `old` to `new`, found with Myers'
+/// algorithm.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    let offset = max as usize + 1;
+    // the furthest line of `old` reached on each diagonal, for each number
+    // of edits
+    let mut v = vec![0isize; 2 * offset + 1];
+    let mut trace = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset as isize) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // walks back from the end through the diagonals the search took
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let i = (k + offset as isize) as usize;
+        let prev_k = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + offset as isize) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(if x == prev_x {
+                Edit::Insert
+            } else {
+                Edit::Delete
+            });
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    edits.reverse();
+    edits
+}
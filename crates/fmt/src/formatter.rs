@@ -0,0 +1,54 @@
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId};
+use ginto_syntax::{Cst, DEFAULT_INDENT_WIDTH, pretty_print_with_indent};
+
+/// How the formatter lays out code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// How many spaces each block is indented by.
+    pub indent_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: DEFAULT_INDENT_WIDTH,
+        }
+    }
+}
+
+/// Formats `source`, the contents of the file `file_id`: blocks are
+/// indented by four spaces, operators spaced, redundant parentheses
+/// dropped, items separated by a blank line and runs of blank lines in
+/// blocks collapsed to one.
+///
+/// Besides the syntax tree, the formatter keeps the blank lines and the
+/// comments of `source`, as `pretty_print_with_source` places them.
+/// Formatting the output again leaves it unchanged.
+pub fn format_source(file_id: FileId, source: &str) -> Result<String, Vec<Diagnostic>> {
+    format_source_with(file_id, source, &FormatOptions::default())
+}
+
+/// Formats `source` as `format_source` does, laid out with `options`.
+pub fn format_source_with(
+    file_id: FileId,
+    source: &str,
+    options: &FormatOptions,
+) -> Result<String, Vec<Diagnostic>> {
+    let cst = Cst::new(file_id, source).map_err(into_diagnostics)?;
+    let (program, errors) = cst.to_ast();
+    if !errors.is_empty() {
+        return Err(into_diagnostics(errors));
+    }
+    Ok(pretty_print_with_indent(
+        &program,
+        &cst,
+        options.indent_width,
+    ))
+}
+
+fn into_diagnostics<E: DiagnosticConvertible>(errors: Vec<E>) -> Vec<Diagnostic> {
+    errors
+        .into_iter()
+        .map(DiagnosticConvertible::into_diagnostic)
+        .collect()
+}
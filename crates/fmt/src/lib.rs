@@ -0,0 +1,5 @@
+mod diff;
+mod formatter;
+
+pub use diff::*;
+pub use formatter::*;
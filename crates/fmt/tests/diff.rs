@@ -0,0 +1,56 @@
+use ginto_fmt::{TextEdit, text_edits, unified_diff};
+
+#[test]
+fn diffs_equal_texts_as_nothing() {
+    assert_eq!(unified_diff("a.ginto", "a\nb\n", "a\nb\n"), "");
+}
+
+#[test]
+fn shows_changes_with_their_context() {
+    let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+    let new = "1\n2\n3\n4\nfive\n6\n7\n8\n9\n10\n11\n12\n13\n";
+    assert_eq!(
+        unified_diff("a.ginto", old, new),
+        "--- a.ginto\n+++ a.ginto\n\
+         @@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n\
+         @@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n"
+    );
+}
+
+#[test]
+fn marks_lines_without_a_newline() {
+    assert_eq!(
+        unified_diff("a.ginto", "a\nb", "a\nb\n"),
+        "--- a.ginto\n+++ a.ginto\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n"
+    );
+    assert_eq!(
+        unified_diff("a.ginto", "", "a\n"),
+        "--- a.ginto\n+++ a.ginto\n@@ -0,0 +1 @@\n+a\n"
+    );
+}
+
+#[test]
+fn edits_changed_lines() {
+    let old = "a\nb\nc\nd\n";
+    let new = "a\nB\nc\nd\ne\n";
+    let edits = text_edits(old, new);
+    assert_eq!(
+        edits,
+        [
+            TextEdit {
+                range: 2..4,
+                new_text: "B\n".to_string(),
+            },
+            TextEdit {
+                range: 8..8,
+                new_text: "e\n".to_string(),
+            },
+        ]
+    );
+    let mut applied = old.to_string();
+    for edit in edits.iter().rev() {
+        applied.replace_range(edit.range.clone(), &edit.new_text);
+    }
+    assert_eq!(applied, new);
+    assert!(text_edits(new, new).is_empty());
+}
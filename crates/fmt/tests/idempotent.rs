@@ -0,0 +1,199 @@
+//! Formats randomly laid out programs, which must come out formatted the
+//! same way again and parse to the same program.
+
+use ginto_diag::FileId;
+use ginto_fmt::format_source;
+use ginto_syntax::{Lexer, Parser, pretty_print};
+use proptest::prelude::*;
+
+/// Spacing around an operator, which the formatter normalizes.
+fn space() -> impl Strategy<Value = &'static str> {
+    prop_oneof![Just(""), Just(" "), Just("  ")]
+}
+
+/// A comment, or none.
+fn comment() -> impl Strategy<Value = Option<&'static str>> {
+    prop::option::of(prop_oneof![Just("# a note"), Just("## docs  ")])
+}
+
+fn expr() -> impl Strategy<Value = String> {
+    let leaf = prop_oneof![
+        (0u64..1000).prop_map(|v| v.to_string()),
+        prop_oneof![Just("a"), Just("b"), Just("c")].prop_map(str::to_string),
+        any::<bool>().prop_map(|v| v.to_string()),
+        Just("()".to_string()),
+        Just("\"a {a} {{}}\\n\"".to_string()),
+    ];
+    leaf.prop_recursive(4, 24, 3, |inner| {
+        let op = prop_oneof![
+            Just("+"),
+            Just("-"),
+            Just("*"),
+            Just("/"),
+            Just("mod"),
+            Just("="),
+            Just("<>"),
+            Just("<"),
+            Just(">="),
+            Just("&&"),
+            Just("||"),
+        ];
+        prop_oneof![
+            // operands are parenthesized so any nesting parses
+            (inner.clone(), space(), op, space(), inner.clone()).prop_map(
+                |(lhs, before, op, after, rhs)| {
+                    let (before, after) = if op == "mod" {
+                        (" ", " ")
+                    } else {
+                        (before, after)
+                    };
+                    format!("({lhs}){before}{op}{after}({rhs})")
+                }
+            ),
+            inner.clone().prop_map(|e| format!("-({e})")),
+            inner.clone().prop_map(|e| format!("not ({e})")),
+            inner.clone().prop_map(|e| format!("({e}) as i64")),
+            prop::collection::vec(inner.clone(), 0..3)
+                .prop_map(|args| format!("f({})", args.join(" , "))),
+            (inner, space()).prop_map(|(e, space)| format!("g(|x|{space}({e}))")),
+        ]
+    })
+}
+
+/// A block of statements at `depth` levels of indentation of `unit`
+/// spaces each, with random blank lines and comments between them and
+/// after them.
+fn block(depth: usize, unit: usize) -> BoxedStrategy<String> {
+    let indent = " ".repeat(depth * unit);
+    let simple = prop_oneof![
+        expr(),
+        (any::<bool>(), expr()).prop_map(|(mutable, e)| {
+            format!("let {}x = {e}", if mutable { "mut " } else { "" })
+        }),
+        expr().prop_map(|e| format!("x = {e}")),
+        expr().prop_map(|e| format!("return {e}")),
+    ];
+    let stmt = if depth < 3 {
+        let nested = block(depth + 1, unit);
+        let indent = indent.clone();
+        prop_oneof![
+            4 => simple,
+            1 => (expr(), nested.clone(), prop::option::of(nested.clone())).prop_map(
+                move |(cond, then, otherwise)| match otherwise {
+                    Some(otherwise) => format!("if {cond}:\n{then}\n{indent}else:\n{otherwise}"),
+                    None => format!("if {cond}:\n{then}"),
+                }
+            ),
+            1 => (expr(), expr()).prop_map({
+                let indent = " ".repeat((depth + 1) * unit);
+                move |(scrutinee, e)| {
+                    format!("match {scrutinee}:\n{indent}0 | 1 -> a\n\n\n{indent}_ -> {e}")
+                }
+            }),
+        ]
+        .boxed()
+    } else {
+        simple.boxed()
+    };
+    prop::collection::vec((stmt, 0usize..3, space(), comment(), comment()), 1..4)
+        .prop_map(move |stmts| {
+            let lines: Vec<_> = stmts
+                .into_iter()
+                .map(|(stmt, blanks, trailing, before, after)| {
+                    let before = before.map_or(String::new(), |c| format!("{indent}{c}\n"));
+                    let after = after.map_or(String::new(), |c| format!(" {c}"));
+                    format!(
+                        "{}{before}{indent}{stmt}{trailing}{after}",
+                        "\n".repeat(blanks)
+                    )
+                })
+                .collect();
+            lines.join("\n")
+        })
+        .boxed()
+}
+
+fn program() -> impl Strategy<Value = String> {
+    (1usize..5).prop_flat_map(|unit| {
+        prop::collection::vec((block(1, unit), 0usize..3), 1..3).prop_map(|funcs| {
+            let funcs: Vec<_> = funcs
+                .into_iter()
+                .enumerate()
+                .map(|(i, (body, blanks))| {
+                    format!("{}fn f{i}(a: u64, b: u64):\n{body}", "\n".repeat(blanks))
+                })
+                .collect();
+            format!("mod m\n\n\nuse m::c\n{}\n", funcs.join("\n"))
+        })
+    })
+}
+
+/// The text of the comments of `source`, in order.
+fn comments(source: &str) -> Vec<String> {
+    let mut lexer = Lexer::new(FileId(0), source);
+    lexer.lex_all().unwrap();
+    lexer
+        .comments()
+        .iter()
+        .map(|span| source[span.start.0..span.end.0].to_string())
+        .collect()
+}
+
+fn parse_and_print(source: &str) -> String {
+    let tokens = Lexer::new(FileId(0), source).lex_all().unwrap();
+    let mut parser = Parser::new(FileId(0), tokens);
+    let program = parser.parse_program();
+    assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+    pretty_print(&program)
+}
+
+proptest! {
+    #[test]
+    fn formatting_is_idempotent(source in program()) {
+        let formatted = format_source(FileId(0), &source)
+            .unwrap_or_else(|errors| panic!("{errors:?} formatting:\n{source}"));
+        let again = format_source(FileId(0), &formatted)
+            .unwrap_or_else(|errors| panic!("{errors:?} formatting again:\n{formatted}"));
+        prop_assert_eq!(&again, &formatted);
+        prop_assert_eq!(parse_and_print(&formatted), parse_and_print(&source));
+        prop_assert_eq!(comments(&formatted), comments(&source));
+    }
+}
+
+#[test]
+fn keeps_single_blank_lines_in_blocks() {
+    let source = "fn main()  ->u64:\n  let x=1\n\n\n\n  let y =x*  2\n  y\n\n\n\nfn f():\n  ()\n";
+    let formatted = format_source(FileId(0), source).unwrap();
+    assert_eq!(
+        formatted,
+        "fn main() -> u64:\n    let x = 1\n\n    let y = x * 2\n    y\n\nfn f():\n    ()\n"
+    );
+}
+
+#[test]
+fn keeps_comparisons_from_reading_as_assignments() {
+    let source = "fn f(a: u64):\n    (a)=((0)=(0))\n    (a = 0) && true\n    a = 1\n";
+    let formatted = format_source(FileId(0), source).unwrap();
+    assert_eq!(
+        formatted,
+        "fn f(a: u64):\n    (a = (0 = 0))\n    (a = 0 && true)\n    a = 1\n"
+    );
+    assert_eq!(parse_and_print(&formatted), parse_and_print(source));
+}
+
+#[test]
+fn keeps_number_literals_as_written() {
+    let source = "fn f(a: i64) -> u64:\n    match a:\n        -0x10 -> 0b1010\n        _ -> 1_000 + 0o17\n\nfn g() -> f64:\n    2.50e3\n";
+    let formatted = format_source(FileId(0), source).unwrap();
+    assert_eq!(formatted, source);
+}
+
+#[test]
+fn keeps_comments() {
+    let source = "# header\n\nfn f(a: u64) -> u64:  # trailing\n  # before\n  let b=a+1\n\n  # ends the block\n\n# top\nfn g():\n  ()\n";
+    let formatted = format_source(FileId(0), source).unwrap();
+    assert_eq!(
+        formatted,
+        "# header\n\nfn f(a: u64) -> u64:  # trailing\n    # before\n    let b = a + 1\n\n    # ends the block\n\n# top\nfn g():\n    ()\n"
+    );
+}
@@ -0,0 +1,133 @@
+use ginto_analysis::{
+    ConstEvalError, MatchError, check_dead_code, check_matches, check_unreachable,
+};
+use ginto_diag::DiagnosticConvertible;
+use ginto_syntax::Program;
+
+use crate::{Level, Lint, LintContext, LintPass};
+
+pub static DEAD_CODE: Lint = Lint {
+    name: "dead_code",
+    code: "W0001",
+    default_level: Level::Warn,
+    description: "detects private functions that are never called",
+};
+
+pub static UNREACHABLE_CODE: Lint = Lint {
+    name: "unreachable_code",
+    code: "W0002",
+    default_level: Level::Warn,
+    description: "detects statements that follow a `return` or another diverging expression",
+};
+
+pub static UNREACHABLE_PATTERNS: Lint = Lint {
+    name: "unreachable_patterns",
+    code: "W0003",
+    default_level: Level::Warn,
+    description: "detects match arms that no value can reach",
+};
+
+pub static UNUSED_VARIABLES: Lint = Lint {
+    name: "unused_variables",
+    code: "W0004",
+    default_level: Level::Warn,
+    description: "detects parameters and local variables that are never used",
+};
+
+pub static SHADOWING: Lint = Lint {
+    name: "shadowing",
+    code: "W0005",
+    default_level: Level::Allow,
+    description: "detects bindings that hide an earlier binding of the same name",
+};
+
+pub static UNUSED_COMPARISONS: Lint = Lint {
+    name: "unused_comparisons",
+    code: "W0006",
+    default_level: Level::Warn,
+    description: "detects comparisons made useless by the range of the compared type",
+};
+
+pub static REDUNDANT_BOOL_OPERANDS: Lint = Lint {
+    name: "redundant_bool_operands",
+    code: "W0007",
+    default_level: Level::Warn,
+    description: "detects `&&` and `||` with a literal `true` or `false` operand",
+};
+
+pub static NON_SNAKE_CASE: Lint = Lint {
+    name: "non_snake_case",
+    code: "W0008",
+    default_level: Level::Warn,
+    description: "detects functions, modules and variables whose names are not snake_case",
+};
+
+pub static ARITHMETIC_OVERFLOW: Lint = Lint {
+    name: "arithmetic_overflow",
+    code: "W0009",
+    default_level: Level::Deny,
+    description: "detects arithmetic on constants that is certain to overflow",
+};
+
+pub static UNCONDITIONAL_PANIC: Lint = Lint {
+    name: "unconditional_panic",
+    code: "W0010",
+    default_level: Level::Deny,
+    description: "detects divisions and remainders by a constant zero",
+};
+
+/// The lint that reports `err`, found by constant folding, unless it is an
+/// error no level can change.
+pub fn const_eval_lint(err: &ConstEvalError) -> Option<&'static Lint> {
+    match err {
+        ConstEvalError::Overflow { .. } => Some(&ARITHMETIC_OVERFLOW),
+        ConstEvalError::DivisionByZero { .. } => Some(&UNCONDITIONAL_PANIC),
+        _ => None,
+    }
+}
+
+pub struct DeadCodePass;
+
+impl LintPass for DeadCodePass {
+    fn lints(&self) -> Vec<&'static Lint> {
+        vec![&DEAD_CODE]
+    }
+
+    fn check_program(&mut self, cx: &mut LintContext<'_>, program: &Program) {
+        for warning in check_dead_code(program, cx.resolutions) {
+            cx.emit(&DEAD_CODE, warning.into_diagnostic());
+        }
+    }
+}
+
+pub struct UnreachableCodePass;
+
+impl LintPass for UnreachableCodePass {
+    fn lints(&self) -> Vec<&'static Lint> {
+        vec![&UNREACHABLE_CODE]
+    }
+
+    fn check_program(&mut self, cx: &mut LintContext<'_>, program: &Program) {
+        for warning in check_unreachable(program) {
+            cx.emit(&UNREACHABLE_CODE, warning.into_diagnostic());
+        }
+    }
+}
+
+/// Reports the unreachable arms found by the exhaustiveness check. Missing
+/// arms are errors and are not reported here.
+pub struct UnreachablePatternsPass;
+
+impl LintPass for UnreachablePatternsPass {
+    fn lints(&self) -> Vec<&'static Lint> {
+        vec![&UNREACHABLE_PATTERNS]
+    }
+
+    fn check_program(&mut self, cx: &mut LintContext<'_>, program: &Program) {
+        for err in check_matches(program) {
+            if let MatchError::UnreachableArm { .. } = err {
+                cx.emit(&UNREACHABLE_PATTERNS, err.into_diagnostic());
+            }
+        }
+    }
+}
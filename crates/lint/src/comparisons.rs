@@ -0,0 +1,193 @@
+use ginto_diag::{Applicability, Diagnostic, Edit, FileId, Label, Span, Spanned, Suggestion};
+use ginto_syntax::{
+    BinOp, Expr, ExprKind, Program, UnaryOp,
+    visit::{self, Visitor},
+};
+use ginto_typeck::{Ty, TypeckResults};
+
+use crate::{Lint, LintContext, LintPass, REDUNDANT_BOOL_OPERANDS, UNUSED_COMPARISONS};
+
+/// Reports comparisons whose result is decided by the range of the compared
+/// type, like `x >= 0` for a `u64`, and `&&`/`||` with a literal operand.
+///
+/// Runs on the folded program, so constant subexpressions have already been
+/// replaced by literals.
+pub struct ConstantComparisonsPass;
+
+impl LintPass for ConstantComparisonsPass {
+    fn lints(&self) -> Vec<&'static Lint> {
+        vec![&UNUSED_COMPARISONS, &REDUNDANT_BOOL_OPERANDS]
+    }
+
+    fn check_program(&mut self, cx: &mut LintContext<'_>, program: &Program) {
+        let mut finder = ComparisonFinder {
+            file_id: program.file_id,
+            types: cx.types,
+            found: Vec::new(),
+        };
+        finder.visit_program(program);
+        for (lint, diagnostic) in finder.found {
+            cx.emit(lint, diagnostic);
+        }
+    }
+}
+
+struct ComparisonFinder<'a> {
+    file_id: FileId,
+    types: &'a TypeckResults,
+    found: Vec<(&'static Lint, Diagnostic)>,
+}
+
+impl ComparisonFinder<'_> {
+    fn check_comparison(&mut self, op: BinOp, lhs: &Expr, rhs: &Expr, span: Span) {
+        // normalize to `operand op literal`
+        let (operand, op, value) = match (int_literal(lhs), int_literal(rhs)) {
+            (None, Some(value)) => (lhs, op, value),
+            (Some(value), None) => (rhs, flip(op), value),
+            _ => return,
+        };
+        let ty = match self.types.expr_ty(self.file_id, operand.span) {
+            Some(ty @ (Ty::U64 | Ty::I64)) => ty,
+            _ => return,
+        };
+        let (min, max) = match ty {
+            Ty::I64 => (i64::MIN as i128, i64::MAX as i128),
+            _ => (u64::MIN as i128, u64::MAX as i128),
+        };
+        let result = match op {
+            BinOp::Ge if value <= min => true,
+            BinOp::Ge if value > max => false,
+            BinOp::Greater if value < min => true,
+            BinOp::Greater if value >= max => false,
+            BinOp::Le if value >= max => true,
+            BinOp::Le if value < min => false,
+            BinOp::Less if value > max => true,
+            BinOp::Less if value <= min => false,
+            _ => return,
+        };
+        let diagnostic = Diagnostic::warning("comparison is useless due to type limits")
+            .with_label(
+                Label::primary(self.file_id, span)
+                    .with_message(format!("this is always `{}`", result)),
+            )
+            .with_label(
+                Label::secondary(self.file_id, operand.span)
+                    .with_message(format!("this is of type `{}`", ty)),
+            )
+            .with_note(format!(
+                "`{}` ranges from {} to {}, so every value of it compares the same way",
+                ty, min, max
+            ))
+            // the operand may have effects the comparison kept
+            .with_suggestion(Suggestion::new(
+                format!("replace the comparison with `{}`", result),
+                vec![Edit::new(self.file_id, span, result.to_string())],
+                Applicability::MaybeIncorrect,
+            ));
+        self.found.push((&UNUSED_COMPARISONS, diagnostic));
+    }
+
+    fn check_logical(&mut self, op: BinOp, lhs: &Expr, rhs: &Expr, span: Span) {
+        let (literal, value) = match (&lhs.inner, &rhs.inner) {
+            (ExprKind::Bool(value), _) => (lhs, *value),
+            (_, ExprKind::Bool(value)) => (rhs, *value),
+            _ => return,
+        };
+        // `true && x` and `false || x` are just `x`
+        let absorbing = matches!((op, value), (BinOp::And, false) | (BinOp::Or, true));
+        let (message, note) = if absorbing {
+            (
+                format!("this `{}` expression always evaluates to `{}`", op, value),
+                format!("`{} {} x` is `{}` whatever `x` is", value, op, value),
+            )
+        } else {
+            (
+                format!("the `{}` operand of `{}` has no effect", value, op),
+                format!("`{} {} x` is equal to `x`", value, op),
+            )
+        };
+        let diagnostic = Diagnostic::warning(message)
+            .with_label(Label::primary(self.file_id, span).with_message("redundant check"))
+            .with_label(
+                Label::secondary(self.file_id, literal.span)
+                    .with_message(format!("this is always `{}`", value)),
+            )
+            .with_note(note);
+        let suggestion = if absorbing {
+            // the other operand is skipped only when the literal comes first
+            let applicability = if literal.span == lhs.span {
+                Applicability::MachineApplicable
+            } else {
+                Applicability::MaybeIncorrect
+            };
+            Suggestion::new(
+                format!("replace the whole expression with `{}`", value),
+                vec![Edit::new(self.file_id, span, value.to_string())],
+                applicability,
+            )
+        } else {
+            // the operand left takes the place of the expression, and binds
+            // at least as tightly as the operator did
+            let removed = if literal.span == lhs.span {
+                Span::new(lhs.span.start, rhs.span.start)
+            } else {
+                Span::new(lhs.span.end, rhs.span.end)
+            };
+            Suggestion::new(
+                format!("remove the `{}` operand", value),
+                vec![Edit::delete(self.file_id, removed)],
+                Applicability::MachineApplicable,
+            )
+        };
+        self.found.push((
+            &REDUNDANT_BOOL_OPERANDS,
+            diagnostic.with_suggestion(suggestion),
+        ));
+    }
+}
+
+impl Visitor for ComparisonFinder<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Binary {
+            op: Spanned { inner: op, .. },
+            lhs,
+            rhs,
+        } = &expr.inner
+        {
+            match op {
+                BinOp::Less | BinOp::Le | BinOp::Greater | BinOp::Ge => {
+                    self.check_comparison(*op, lhs, rhs, expr.span)
+                }
+                BinOp::And | BinOp::Or => self.check_logical(*op, lhs, rhs, expr.span),
+                _ => {}
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+/// The value of an integer literal, possibly negated.
+fn int_literal(expr: &Expr) -> Option<i128> {
+    match &expr.inner {
+        ExprKind::Int(v) => Some(*v as i128),
+        ExprKind::Unary {
+            op: Spanned {
+                inner: UnaryOp::Neg,
+                ..
+            },
+            expr,
+        } => int_literal(expr).map(|v| -v),
+        _ => None,
+    }
+}
+
+/// The operator that gives the same result with the operands swapped.
+fn flip(op: BinOp) -> BinOp {
+    match op {
+        BinOp::Less => BinOp::Greater,
+        BinOp::Le => BinOp::Ge,
+        BinOp::Greater => BinOp::Less,
+        BinOp::Ge => BinOp::Le,
+        op => op,
+    }
+}
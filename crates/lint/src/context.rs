@@ -0,0 +1,80 @@
+use ginto_diag::{Diagnostic, Severity};
+use ginto_resolve::Resolutions;
+use ginto_syntax::Program;
+use ginto_typeck::TypeckResults;
+
+use crate::{Level, LevelSource, Lint, LintLevels};
+
+/// A check that reports one or more lints.
+pub trait LintPass {
+    /// The lints this pass may emit.
+    fn lints(&self) -> Vec<&'static Lint>;
+
+    fn check_program(&mut self, cx: &mut LintContext<'_>, program: &Program);
+}
+
+/// What a lint pass can see of the program, and where its findings go.
+pub struct LintContext<'a> {
+    pub resolutions: &'a Resolutions,
+    pub types: &'a TypeckResults,
+    levels: &'a LintLevels,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> LintContext<'a> {
+    pub fn new(
+        resolutions: &'a Resolutions,
+        types: &'a TypeckResults,
+        levels: &'a LintLevels,
+    ) -> Self {
+        Self {
+            resolutions,
+            types,
+            levels,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Reports `diagnostic` for `lint` at the lint's configured level,
+    /// dropping it if the lint is allowed.
+    pub fn emit(&mut self, lint: &'static Lint, mut diagnostic: Diagnostic) {
+        let (level, source) = self.levels.level(lint);
+        diagnostic.severity = match level {
+            Level::Allow => return,
+            Level::Warn => Severity::Warning,
+            Level::Deny => Severity::Error,
+        };
+        let name = lint.name;
+        let note = match source {
+            LevelSource::Default if level == Level::Deny => {
+                format!("`{}` is denied by default", name)
+            }
+            LevelSource::Default => format!("`{}` is on by default", name),
+            LevelSource::CommandLine(level) => {
+                format!(
+                    "requested on the command line with `{} {}`",
+                    level.flag(),
+                    name
+                )
+            }
+            LevelSource::Warnings(level) => format!(
+                "`{} {}` implied by `{} warnings`",
+                level.flag(),
+                name,
+                level.flag()
+            ),
+            LevelSource::Config(level) => {
+                format!("set to `{level}` in `[lints]` of `ginto.toml`")
+            }
+            LevelSource::ConfigWarnings(level) => {
+                format!("set to `{level}` by `warnings = \"{level}\"` in `[lints]` of `ginto.toml`")
+            }
+        };
+        self.diagnostics
+            .push(diagnostic.with_code(lint.code).with_note(note));
+    }
+
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible};
+
+use crate::{Level, Lint, LintStore};
+
+/// The name that sets the level of every lint that warns by default.
+pub const WARNINGS: &str = "warnings";
+
+/// Why a lint has the level it has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelSource {
+    Default,
+    /// Set by name on the command line.
+    CommandLine(Level),
+    /// Set through the `warnings` group on the command line.
+    Warnings(Level),
+    /// Set by name in the project's `ginto.toml`.
+    Config(Level),
+    /// Set through the `warnings` group in the project's `ginto.toml`.
+    ConfigWarnings(Level),
+}
+
+impl LevelSource {
+    /// The level set, unless it is the lint's default.
+    pub fn level(self) -> Option<Level> {
+        match self {
+            LevelSource::Default => None,
+            LevelSource::CommandLine(level)
+            | LevelSource::Warnings(level)
+            | LevelSource::Config(level)
+            | LevelSource::ConfigWarnings(level) => Some(level),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintLevelError {
+    UnknownLint { name: String, level: Level },
+}
+
+impl DiagnosticConvertible for LintLevelError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            LintLevelError::UnknownLint { name, level } => {
+                Diagnostic::warning(format!("unknown lint: `{}`", name)).with_note(format!(
+                    "requested on the command line with `{} {}`",
+                    level.flag(),
+                    name
+                ))
+            }
+        }
+    }
+}
+
+/// The configured level of each lint. A level set for a lint by name takes
+/// precedence over one set through `warnings`.
+#[derive(Debug, Default, Clone)]
+pub struct LintLevels {
+    levels: HashMap<&'static str, LevelSource>,
+    warnings: Option<LevelSource>,
+}
+
+impl LintLevels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the level of the lint called `name`, which may be spelled with
+    /// dashes instead of underscores.
+    pub fn set(
+        &mut self,
+        store: &LintStore,
+        name: &str,
+        level: Level,
+    ) -> Result<(), LintLevelError> {
+        let source = match name {
+            WARNINGS => LevelSource::Warnings(level),
+            _ => LevelSource::CommandLine(level),
+        };
+        self.set_from(store, name, source)
+            .map_err(|name| LintLevelError::UnknownLint { name, level })
+    }
+
+    /// Sets the level of the lint called `name` as `set` does, as the
+    /// project's `ginto.toml` asks. Returns the name if there is no such
+    /// lint.
+    pub fn set_in_config(
+        &mut self,
+        store: &LintStore,
+        name: &str,
+        level: Level,
+    ) -> Result<(), String> {
+        let source = match name {
+            WARNINGS => LevelSource::ConfigWarnings(level),
+            _ => LevelSource::Config(level),
+        };
+        self.set_from(store, name, source)
+    }
+
+    fn set_from(
+        &mut self,
+        store: &LintStore,
+        name: &str,
+        source: LevelSource,
+    ) -> Result<(), String> {
+        if name == WARNINGS {
+            self.warnings = Some(source);
+            return Ok(());
+        }
+        match store.find(name) {
+            Some(lint) => {
+                self.levels.insert(lint.name, source);
+                Ok(())
+            }
+            None => Err(name.to_string()),
+        }
+    }
+
+    /// Parses `-A name`, `-W name` and `-D name` flags, also accepting the
+    /// name attached to the flag as in `-Dwarnings`. Other arguments are
+    /// returned untouched.
+    pub fn parse_args(
+        &mut self,
+        store: &LintStore,
+        args: impl IntoIterator<Item = String>,
+    ) -> (Vec<String>, Vec<LintLevelError>) {
+        let mut rest = Vec::new();
+        let mut errors = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(level) = arg.get(..2).and_then(Level::from_flag) else {
+                rest.push(arg);
+                continue;
+            };
+            let name = match &arg[2..] {
+                "" => args.next(),
+                attached => Some(attached.to_string()),
+            };
+            if let Some(name) = name
+                && let Err(err) = self.set(store, &name, level)
+            {
+                errors.push(err);
+            }
+        }
+        (rest, errors)
+    }
+
+    pub fn level(&self, lint: &Lint) -> (Level, LevelSource) {
+        if let Some(&source) = self.levels.get(lint.name) {
+            return (source.level().unwrap_or(lint.default_level), source);
+        }
+        match self.warnings {
+            Some(source) if lint.default_level == Level::Warn => {
+                (source.level().unwrap_or(lint.default_level), source)
+            }
+            _ => (lint.default_level, LevelSource::Default),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+mod builtin;
+mod comparisons;
+mod context;
+mod levels;
+mod lint;
+mod naming;
+mod shadowing;
+mod store;
+mod unused;
+
+pub use builtin::*;
+pub use comparisons::*;
+pub use context::*;
+pub use levels::*;
+pub use lint::*;
+pub use naming::*;
+pub use shadowing::*;
+pub use store::*;
+pub use unused::*;
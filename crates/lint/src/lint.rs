@@ -0,0 +1,57 @@
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Level {
+    /// The command line flag that sets a lint to this level.
+    pub fn flag(&self) -> &'static str {
+        match self {
+            Level::Allow => "-A",
+            Level::Warn => "-W",
+            Level::Deny => "-D",
+        }
+    }
+
+    pub fn from_flag(flag: &str) -> Option<Self> {
+        match flag {
+            "-A" => Some(Level::Allow),
+            "-W" => Some(Level::Warn),
+            "-D" => Some(Level::Deny),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Level::Allow => "allow",
+            Level::Warn => "warn",
+            Level::Deny => "deny",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A named check whose level can be configured. Lints are declared as
+/// statics and compared by address.
+#[derive(Debug)]
+pub struct Lint {
+    pub name: &'static str,
+    pub code: &'static str,
+    pub default_level: Level,
+    pub description: &'static str,
+}
+
+impl PartialEq for Lint {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for Lint {}
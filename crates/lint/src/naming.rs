@@ -0,0 +1,115 @@
+use std::cmp::Reverse;
+
+use ginto_diag::{Applicability, Diagnostic, Edit, Label, Suggestion};
+use ginto_resolve::DefKind;
+use ginto_syntax::Program;
+
+use crate::{Lint, LintContext, LintPass, NON_SNAKE_CASE};
+
+/// Warns about functions, modules, parameters and locals whose names are not
+/// snake_case, suggesting to rename them to the snake_case spelling where
+/// they are declared and used.
+pub struct NamingPass;
+
+impl LintPass for NamingPass {
+    fn lints(&self) -> Vec<&'static Lint> {
+        vec![&NON_SNAKE_CASE]
+    }
+
+    fn check_program(&mut self, cx: &mut LintContext<'_>, program: &Program) {
+        let misnamed: Vec<_> = cx
+            .resolutions
+            .defs()
+            .filter(|(_, def)| {
+                // type parameters and traits are conventionally upper
+                // case, like `T` and `Show`, and closures have no name
+                def.file_id == program.file_id
+                    && !matches!(
+                        def.kind,
+                        DefKind::TypeParam | DefKind::Trait | DefKind::Closure
+                    )
+                    && !def.name.with(is_snake_case)
+            })
+            .map(|(id, def)| (id, def.clone()))
+            .collect();
+        for (id, def) in misnamed {
+            let name = def.name.with(to_snake_case);
+            let mut edits: Vec<Edit> = cx
+                .resolutions
+                .def_spans_of(id)
+                .chain(cx.resolutions.uses_of(id))
+                .map(|(file_id, span)| Edit::new(file_id, span, name.as_str()))
+                .collect();
+            // a span within one taken already would be edited twice, so
+            // the edits couldn't be applied
+            edits.sort_by_key(|edit| (edit.file_id, edit.span.start, Reverse(edit.span.end)));
+            edits.dedup_by(|edit, kept| {
+                edit.file_id == kept.file_id && edit.span.end <= kept.span.end
+            });
+            // a module is named after its file, a method after the trait's,
+            // and the new name may already be taken
+            let applicability = if matches!(def.kind, DefKind::Mod | DefKind::Method)
+                || cx
+                    .resolutions
+                    .defs()
+                    .any(|(_, other)| other.name == name.as_str())
+            {
+                Applicability::MaybeIncorrect
+            } else {
+                Applicability::MachineApplicable
+            };
+            let diagnostic = Diagnostic::warning(format!(
+                "{} `{}` should have a snake case name",
+                def.kind.descr(),
+                def.name
+            ))
+            .with_label(
+                Label::primary(def.file_id, def.span)
+                    .with_message(format!("convert the identifier to snake case: `{}`", name)),
+            )
+            .with_suggestion(Suggestion::new(
+                format!("rename it to `{}`", name),
+                edits,
+                applicability,
+            ));
+            cx.emit(&NON_SNAKE_CASE, diagnostic);
+        }
+    }
+}
+
+/// Whether `name` is lowercase words separated by single underscores.
+/// Leading underscores, used for intentionally unused names, are allowed.
+pub fn is_snake_case(name: &str) -> bool {
+    let name = name.trim_start_matches('_');
+    !name.chars().any(char::is_uppercase) && !name.contains("__") && !name.ends_with('_')
+}
+
+/// Converts `name` to snake_case, splitting words at case changes, so both
+/// `fooBar` and `FooBar` become `foo_bar` and `HTTPServer` becomes
+/// `http_server`.
+pub fn to_snake_case(name: &str) -> String {
+    let leading = name.len() - name.trim_start_matches('_').len();
+    let chars: Vec<char> = name[leading..].chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !word.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if !prev.is_uppercase() || next_is_lower {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    format!("{}{}", "_".repeat(leading), words.join("_"))
+}
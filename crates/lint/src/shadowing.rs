@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use ginto_diag::{Diagnostic, FileId, Label, Span, Symbol};
+use ginto_syntax::{
+    Expr, ExprKind, Func, Param, Program,
+    visit::{self, Visitor},
+};
+
+use crate::{Lint, LintContext, LintPass, SHADOWING};
+
+/// Reports `let` and pattern bindings that hide a parameter or an earlier
+/// binding of the same name that is still in scope.
+pub struct ShadowingPass;
+
+impl LintPass for ShadowingPass {
+    fn lints(&self) -> Vec<&'static Lint> {
+        vec![&SHADOWING]
+    }
+
+    fn check_program(&mut self, cx: &mut LintContext<'_>, program: &Program) {
+        let mut finder = ShadowFinder {
+            scopes: Vec::new(),
+            found: Vec::new(),
+        };
+        finder.visit_program(program);
+        for (name, span, previous) in finder.found {
+            cx.emit(
+                &SHADOWING,
+                shadowing_diagnostic(program.file_id, name, span, previous),
+            );
+        }
+    }
+}
+
+fn shadowing_diagnostic(file_id: FileId, name: Symbol, span: Span, previous: Span) -> Diagnostic {
+    Diagnostic::warning(format!("`{}` shadows an earlier binding", name))
+        .with_label(
+            Label::primary(file_id, span).with_message(format!("`{}` is redeclared here", name)),
+        )
+        .with_label(
+            Label::secondary(file_id, previous)
+                .with_message("the shadowed binding is declared here"),
+        )
+}
+
+struct ShadowFinder {
+    scopes: Vec<HashMap<Symbol, Span>>,
+    /// Each shadowing binding, with its name and the span of the binding it
+    /// hides.
+    found: Vec<(Symbol, Span, Span)>,
+}
+
+impl ShadowFinder {
+    fn bind(&mut self, name: Symbol, span: Span) {
+        let previous = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name).copied());
+        if let Some(previous) = previous {
+            self.found.push((name, span, previous));
+        }
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, span);
+        }
+    }
+
+    fn with_scope(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+}
+
+impl Visitor for ShadowFinder {
+    fn visit_func(&mut self, func: &Func) {
+        self.with_scope(|this| {
+            for param in &func.params {
+                let Param::Named { name, .. } = &param.inner else {
+                    continue;
+                };
+                this.scopes
+                    .last_mut()
+                    .expect("scope stack is empty")
+                    .insert(name.inner, name.span);
+            }
+            visit::walk_func(this, func);
+        });
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.inner {
+            ExprKind::Let { name, value, .. } => {
+                if let Some(value) = value {
+                    self.visit_expr(value);
+                }
+                self.bind(name.inner, name.span);
+            }
+            ExprKind::Block { .. } => self.with_scope(|this| visit::walk_expr(this, expr)),
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                self.visit_expr(cond);
+                self.with_scope(|this| this.visit_expr(then_branch));
+                if let Some(else_branch) = else_branch {
+                    self.with_scope(|this| this.visit_expr(else_branch));
+                }
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.visit_expr(scrutinee);
+                for arm in arms {
+                    self.with_scope(|this| {
+                        // later alternatives of an or-pattern bind the same names again
+                        let first = &visit::pattern_alternatives(&arm.pattern)[0];
+                        for name in visit::pattern_bindings(first) {
+                            this.bind(name.inner, name.span);
+                        }
+                        this.visit_expr(&arm.body);
+                    });
+                }
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
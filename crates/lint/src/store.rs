@@ -0,0 +1,81 @@
+use ginto_diag::Diagnostic;
+use ginto_resolve::Resolutions;
+use ginto_syntax::Program;
+use ginto_typeck::TypeckResults;
+
+use crate::{
+    ARITHMETIC_OVERFLOW, ConstantComparisonsPass, DeadCodePass, Lint, LintContext, LintLevels,
+    LintPass, NamingPass, ShadowingPass, UNCONDITIONAL_PANIC, UnreachableCodePass,
+    UnreachablePatternsPass, UnusedVariablesPass,
+};
+
+/// The registered lints and the passes that check them.
+#[derive(Default)]
+pub struct LintStore {
+    lints: Vec<&'static Lint>,
+    passes: Vec<Box<dyn LintPass>>,
+}
+
+impl LintStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A store with every lint that ships with the compiler.
+    pub fn with_builtin_lints() -> Self {
+        let mut store = Self::new();
+        store.register_pass(Box::new(DeadCodePass));
+        store.register_pass(Box::new(UnreachableCodePass));
+        store.register_pass(Box::new(UnreachablePatternsPass));
+        store.register_pass(Box::new(UnusedVariablesPass));
+        store.register_pass(Box::new(ShadowingPass));
+        store.register_pass(Box::new(ConstantComparisonsPass));
+        store.register_pass(Box::new(NamingPass));
+        store.register_lint(&ARITHMETIC_OVERFLOW);
+        store.register_lint(&UNCONDITIONAL_PANIC);
+        store
+    }
+
+    pub fn register_pass(&mut self, pass: Box<dyn LintPass>) {
+        for lint in pass.lints() {
+            self.register_lint(lint);
+        }
+        self.passes.push(pass);
+    }
+
+    /// Registers a lint that a stage of the driver reports rather than a
+    /// pass, like those of constant folding, so its level can be set.
+    pub fn register_lint(&mut self, lint: &'static Lint) {
+        assert!(
+            self.find(lint.name).is_none(),
+            "lint `{}` is registered twice",
+            lint.name
+        );
+        self.lints.push(lint);
+    }
+
+    pub fn lints(&self) -> &[&'static Lint] {
+        &self.lints
+    }
+
+    /// Looks up a lint by name, accepting dashes in place of underscores.
+    pub fn find(&self, name: &str) -> Option<&'static Lint> {
+        let name = name.replace('-', "_");
+        self.lints.iter().copied().find(|lint| lint.name == name)
+    }
+
+    /// Runs every pass over `program`.
+    pub fn check_program(
+        &mut self,
+        program: &Program,
+        resolutions: &Resolutions,
+        types: &TypeckResults,
+        levels: &LintLevels,
+    ) -> Vec<Diagnostic> {
+        let mut cx = LintContext::new(resolutions, types, levels);
+        for pass in &mut self.passes {
+            pass.check_program(&mut cx, program);
+        }
+        cx.into_diagnostics()
+    }
+}
@@ -0,0 +1,60 @@
+use ginto_diag::{Applicability, Diagnostic, Edit, Label, Suggestion, sym};
+use ginto_resolve::DefKind;
+use ginto_syntax::{ItemKind, Param, Program};
+
+use crate::{Lint, LintContext, LintPass, UNUSED_VARIABLES};
+
+/// Warns about parameters and locals that are never read or assigned. Names
+/// starting with `_` are exempt.
+pub struct UnusedVariablesPass;
+
+impl LintPass for UnusedVariablesPass {
+    fn lints(&self) -> Vec<&'static Lint> {
+        vec![&UNUSED_VARIABLES]
+    }
+
+    fn check_program(&mut self, cx: &mut LintContext<'_>, program: &Program) {
+        // the parameters of trait methods have no body to be used in
+        let declared_only: Vec<_> = program
+            .items
+            .iter()
+            .filter_map(|item| match &item.inner {
+                ItemKind::Trait(decl) => Some(decl),
+                _ => None,
+            })
+            .flat_map(|decl| &decl.methods)
+            .flat_map(|method| &method.inner.params)
+            .filter_map(|param| match &param.inner {
+                Param::SelfParam => None,
+                Param::Named { name, .. } => Some(name.span),
+            })
+            .collect();
+        let unused: Vec<_> = cx
+            .resolutions
+            .defs()
+            .filter(|(id, def)| {
+                def.file_id == program.file_id
+                    && matches!(def.kind, DefKind::Param | DefKind::Local)
+                    && !def.name.with(|name| name.starts_with('_'))
+                    // a method's receiver is part of the trait's signature
+                    && def.name != sym::SELF
+                    && !declared_only.contains(&def.span)
+                    && cx.resolutions.uses_of(*id).next().is_none()
+            })
+            .map(|(_, def)| def.clone())
+            .collect();
+        for def in unused {
+            let diagnostic = Diagnostic::warning(format!("unused variable: `{}`", def.name))
+                .with_label(Label::primary(def.file_id, def.span).with_message(format!(
+                    "if this is intentional, prefix it with an underscore: `_{}`",
+                    def.name
+                )))
+                .with_suggestion(Suggestion::new(
+                    format!("rename it to `_{}`", def.name),
+                    vec![Edit::insert(def.file_id, def.span, "_")],
+                    Applicability::MachineApplicable,
+                ));
+            cx.emit(&UNUSED_VARIABLES, diagnostic);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! What the tests of lints share: linting programs through the driver, as
+//! `ginto lint` does.
+
+// each test uses some of the helpers
+#![allow(dead_code)]
+
+use ginto_diag::{Diagnostic, SourceManager, apply_edits};
+use ginto_driver::Driver;
+use ginto_lint::{Level, LintLevels, LintStore};
+use ginto_test_support::reader;
+
+/// The diagnostics of linting `source` with every builtin lint at its
+/// default level.
+pub fn lint(source: &str) -> Vec<Diagnostic> {
+    lint_with(source, &[])
+}
+
+/// The diagnostics of linting `source` with the builtin lints, each lint
+/// named in `levels` set to its level as the command line does.
+pub fn lint_with(source: &str, levels: &[(&str, Level)]) -> Vec<Diagnostic> {
+    lint_files_with(source, &[], levels).0
+}
+
+/// The diagnostics of linting the program whose root file `main.ginto`
+/// holds `root`, with the modules of `files`, named after their path, and
+/// the files it was loaded from.
+pub fn lint_files(root: &str, files: &[(&str, &str)]) -> (Vec<Diagnostic>, SourceManager) {
+    lint_files_with(root, files, &[])
+}
+
+fn lint_files_with(
+    root: &str,
+    files: &[(&str, &str)],
+    levels: &[(&str, Level)],
+) -> (Vec<Diagnostic>, SourceManager) {
+    let mut store = LintStore::with_builtin_lints();
+    let mut lint_levels = LintLevels::new();
+    for &(name, level) in levels {
+        lint_levels.set(&store, name, level).unwrap();
+    }
+    let mut read = reader(files);
+    let mut sm = SourceManager::new();
+    let root = sm.add_file("main.ginto".to_string(), root.to_string());
+    let (_, diagnostics) = Driver::new()
+        .with_lints(&mut store, &lint_levels)
+        .with_reader(&mut read)
+        .lints_only()
+        .check(&mut sm, root);
+    (diagnostics, sm)
+}
+
+/// The diagnostics of `source` with the lint `code`.
+pub fn lint_code(source: &str, code: &str) -> Vec<Diagnostic> {
+    lint(source)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.code.as_deref() == Some(code))
+        .collect()
+}
+
+/// `source` with the edits of the only suggestion of `diagnostic` applied.
+pub fn fixed(source: &str, diagnostic: &Diagnostic) -> String {
+    assert_eq!(diagnostic.suggestions.len(), 1, "{diagnostic:#?}");
+    apply_edits(source, &diagnostic.suggestions[0].edits).unwrap()
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::{fixed, lint_code};
+use ginto_diag::{Applicability, Diagnostic};
+use ginto_lint::{REDUNDANT_BOOL_OPERANDS, UNUSED_COMPARISONS};
+use ginto_test_support::labels;
+
+fn comparisons(body: &str) -> (String, Vec<Diagnostic>) {
+    let source = format!("fn f(x: u64, y: i64) -> bool:\n    {body}\n");
+    let diagnostics = lint_code(&source, UNUSED_COMPARISONS.code);
+    (source, diagnostics)
+}
+
+fn bool_operands(body: &str) -> (String, Vec<Diagnostic>) {
+    let source = format!("fn f(c: bool) -> bool:\n    {body}\n");
+    let diagnostics = lint_code(&source, REDUNDANT_BOOL_OPERANDS.code);
+    (source, diagnostics)
+}
+
+#[test]
+fn unsigned_numbers_are_never_negative() {
+    let (source, diagnostics) = comparisons("x >= 0");
+    let [diagnostic] = &diagnostics[..] else {
+        panic!("expected one warning: {diagnostics:#?}");
+    };
+    assert_eq!(
+        diagnostic.message,
+        "comparison is useless due to type limits"
+    );
+    assert_eq!(
+        labels(&source, diagnostic),
+        [
+            ("x >= 0", "this is always `true`".to_string()),
+            ("x", "this is of type `u64`".to_string()),
+        ]
+    );
+    assert_eq!(
+        diagnostic.notes[0],
+        "`u64` ranges from 0 to 18446744073709551615, so every value of it compares the same way"
+    );
+    assert_eq!(
+        diagnostic.suggestions[0].applicability,
+        Applicability::MaybeIncorrect
+    );
+    assert!(fixed(&source, diagnostic).ends_with("    true\n"));
+}
+
+#[test]
+fn comparisons_decided_either_way() {
+    let result = |body| {
+        let (source, diagnostics) = comparisons(body);
+        let [diagnostic] = &diagnostics[..] else {
+            panic!("expected one warning for `{body}`: {diagnostics:#?}");
+        };
+        labels(&source, diagnostic)[0].1.clone()
+    };
+    assert_eq!(result("x < 0"), "this is always `false`");
+    assert_eq!(result("x <= 18446744073709551615"), "this is always `true`");
+    assert_eq!(result("x > 18446744073709551615"), "this is always `false`");
+    assert_eq!(result("y >= -9223372036854775808"), "this is always `true`");
+    assert_eq!(result("y < -9223372036854775808"), "this is always `false`");
+}
+
+#[test]
+fn literals_on_the_left_are_compared_the_same_way() {
+    let (source, diagnostics) = comparisons("0 <= x");
+    assert_eq!(
+        labels(&source, &diagnostics[0]),
+        [
+            ("0 <= x", "this is always `true`".to_string()),
+            ("x", "this is of type `u64`".to_string()),
+        ]
+    );
+    let (_, diagnostics) = comparisons("0 > x");
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn comparisons_within_the_range_are_fine() {
+    for body in [
+        "x > 0",
+        "x <= 0",
+        "y >= 0",
+        "y < 0",
+        "x = 0",
+        "x <> 0",
+        "x < y as u64",
+    ] {
+        assert!(comparisons(body).1.is_empty(), "{body}");
+    }
+}
+
+#[test]
+fn literal_operands_that_do_not_change_the_result() {
+    let (source, diagnostics) = bool_operands("true && c");
+    let [diagnostic] = &diagnostics[..] else {
+        panic!("expected one warning: {diagnostics:#?}");
+    };
+    assert_eq!(
+        diagnostic.message,
+        "the `true` operand of `&&` has no effect"
+    );
+    assert_eq!(
+        labels(&source, diagnostic),
+        [
+            ("true && c", "redundant check".to_string()),
+            ("true", "this is always `true`".to_string()),
+        ]
+    );
+    assert_eq!(diagnostic.notes[0], "`true && x` is equal to `x`");
+    assert_eq!(
+        diagnostic.suggestions[0].applicability,
+        Applicability::MachineApplicable
+    );
+    assert!(fixed(&source, diagnostic).ends_with("    c\n"));
+
+    let (source, diagnostics) = bool_operands("c || false");
+    assert_eq!(
+        diagnostics[0].message,
+        "the `false` operand of `||` has no effect"
+    );
+    assert!(fixed(&source, &diagnostics[0]).ends_with("    c\n"));
+}
+
+#[test]
+fn literal_operands_that_decide_the_result() {
+    let (source, diagnostics) = bool_operands("false && c");
+    let diagnostic = &diagnostics[0];
+    assert_eq!(
+        diagnostic.message,
+        "this `&&` expression always evaluates to `false`"
+    );
+    assert_eq!(
+        diagnostic.notes[0],
+        "`false && x` is `false` whatever `x` is"
+    );
+    // `c` is never evaluated, so nothing is lost
+    assert_eq!(
+        diagnostic.suggestions[0].applicability,
+        Applicability::MachineApplicable
+    );
+    assert!(fixed(&source, diagnostic).ends_with("    false\n"));
+
+    // `c` is evaluated first, and the fix would drop it
+    let (source, diagnostics) = bool_operands("c || true");
+    assert_eq!(
+        diagnostics[0].suggestions[0].applicability,
+        Applicability::MaybeIncorrect
+    );
+    assert!(fixed(&source, &diagnostics[0]).ends_with("    true\n"));
+}
+
+#[test]
+fn fixes_keep_the_rest_of_the_expression() {
+    let (source, diagnostics) = bool_operands("true && c = false");
+    assert!(fixed(&source, &diagnostics[0]).ends_with("    c = false\n"));
+    let (source, diagnostics) = bool_operands("not (c && true)");
+    assert!(fixed(&source, &diagnostics[0]).ends_with("    not (c)\n"));
+}
+
+#[test]
+fn logical_operators_without_literals_are_fine() {
+    assert!(bool_operands("c && not c").1.is_empty());
+}
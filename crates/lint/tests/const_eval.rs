@@ -0,0 +1,89 @@
+//! The lints of operations on constants that are certain to fail, which
+//! constant folding reports.
+
+use ginto_diag::{Diagnostic, Severity, SourceManager};
+use ginto_driver::Driver;
+use ginto_lint::{ARITHMETIC_OVERFLOW, Level, LintLevels, LintStore, UNCONDITIONAL_PANIC};
+
+const OVERFLOW: &str = "fn main() -> u64:\n    18446744073709551615 + 1\n";
+const DIVISION: &str = "fn main() -> u64:\n    let n = 10\n    n / 0\n";
+
+/// Whether `source` checks, and its diagnostics, with each lint named in
+/// `levels` set to its level as the command line does.
+fn check_with(source: &str, levels: &[(&str, Level)]) -> (bool, Vec<Diagnostic>) {
+    let mut store = LintStore::with_builtin_lints();
+    let mut lint_levels = LintLevels::new();
+    for &(name, level) in levels {
+        lint_levels.set(&store, name, level).unwrap();
+    }
+    let mut sm = SourceManager::new();
+    let root = sm.add_file("main.ginto".to_string(), source.to_string());
+    let (compilation, diagnostics) = Driver::new()
+        .with_lints(&mut store, &lint_levels)
+        .check(&mut sm, root);
+    (compilation.is_some(), diagnostics)
+}
+
+#[test]
+fn overflow_is_denied_by_default() {
+    let (checks, diagnostics) = check_with(OVERFLOW, &[]);
+    assert!(!checks);
+    let [diagnostic] = &diagnostics[..] else {
+        panic!("expected an overflow: {diagnostics:#?}");
+    };
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.code.as_deref(), Some(ARITHMETIC_OVERFLOW.code));
+    assert_eq!(
+        diagnostic.notes,
+        ["`arithmetic_overflow` is denied by default"]
+    );
+}
+
+#[test]
+fn overflow_can_be_lowered() {
+    let (checks, diagnostics) = check_with(OVERFLOW, &[("arithmetic_overflow", Level::Warn)]);
+    assert!(checks);
+    let [diagnostic] = &diagnostics[..] else {
+        panic!("expected an overflow: {diagnostics:#?}");
+    };
+    assert_eq!(diagnostic.severity, Severity::Warning);
+    assert_eq!(
+        diagnostic.notes,
+        ["requested on the command line with `-W arithmetic_overflow`"]
+    );
+
+    let (checks, diagnostics) = check_with(OVERFLOW, &[("arithmetic-overflow", Level::Allow)]);
+    assert!(checks);
+    assert!(diagnostics.is_empty(), "{diagnostics:#?}");
+}
+
+#[test]
+fn division_by_zero_is_denied_by_default() {
+    let (checks, diagnostics) = check_with(DIVISION, &[]);
+    assert!(!checks);
+    let [diagnostic] = &diagnostics[..] else {
+        panic!("expected a division by zero: {diagnostics:#?}");
+    };
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.code.as_deref(), Some(UNCONDITIONAL_PANIC.code));
+    assert_eq!(
+        diagnostic.notes,
+        ["`unconditional_panic` is denied by default"]
+    );
+}
+
+#[test]
+fn division_by_zero_can_be_lowered() {
+    let (checks, diagnostics) = check_with(DIVISION, &[("warnings", Level::Allow)]);
+    // `warnings` only sets the lints that warn by default
+    assert!(!checks);
+    assert_eq!(diagnostics.len(), 1);
+
+    let (checks, diagnostics) = check_with(DIVISION, &[("unconditional_panic", Level::Warn)]);
+    assert!(checks);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+
+    let (checks, diagnostics) = check_with(DIVISION, &[("unconditional_panic", Level::Allow)]);
+    assert!(checks);
+    assert!(diagnostics.is_empty(), "{diagnostics:#?}");
+}
@@ -0,0 +1,244 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::{lint, lint_with};
+use ginto_diag::{Diagnostic, DiagnosticConvertible, Label, Severity, SourceManager, Span};
+use ginto_driver::Driver;
+use ginto_lint::{
+    Level, LevelSource, Lint, LintContext, LintLevels, LintPass, LintStore, SHADOWING,
+    UNUSED_VARIABLES,
+};
+use ginto_syntax::{ItemKind, Program};
+
+/// A program with an unused variable and a shadowed one.
+const SOURCE: &str =
+    "fn main() -> u64:\n    let unused = 1\n    let x = 1\n    let x = x + 1\n    x\n";
+
+fn unused_variables(diagnostics: &[Diagnostic]) -> Vec<&Diagnostic> {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.code.as_deref() == Some(UNUSED_VARIABLES.code))
+        .collect()
+}
+
+#[test]
+fn builtin_lints_have_distinct_names_and_codes() {
+    let store = LintStore::with_builtin_lints();
+    let names: HashSet<_> = store.lints().iter().map(|lint| lint.name).collect();
+    let codes: HashSet<_> = store.lints().iter().map(|lint| lint.code).collect();
+    assert_eq!(names.len(), store.lints().len());
+    assert_eq!(codes.len(), store.lints().len());
+    assert!(
+        store
+            .lints()
+            .iter()
+            .all(|lint| !lint.description.is_empty())
+    );
+}
+
+#[test]
+fn lints_are_found_by_name_with_dashes_or_underscores() {
+    let store = LintStore::with_builtin_lints();
+    assert_eq!(store.find("unused_variables"), Some(&UNUSED_VARIABLES));
+    assert_eq!(store.find("unused-variables"), Some(&UNUSED_VARIABLES));
+    assert_eq!(store.find("unused"), None);
+}
+
+static UNIT_FUNCTIONS: Lint = Lint {
+    name: "unit_functions",
+    code: "W9999",
+    default_level: Level::Warn,
+    description: "detects functions without a return type",
+};
+
+/// A lint defined outside the crate, to check that passes plug in.
+struct UnitFunctionsPass;
+
+impl LintPass for UnitFunctionsPass {
+    fn lints(&self) -> Vec<&'static Lint> {
+        vec![&UNIT_FUNCTIONS]
+    }
+
+    fn check_program(&mut self, cx: &mut LintContext<'_>, program: &Program) {
+        for item in &program.items {
+            if let ItemKind::Func(func) = &item.inner
+                && func.ty.is_none()
+            {
+                let diagnostic = Diagnostic::warning("function without a return type")
+                    .with_label(Label::primary(program.file_id, func.name.span));
+                cx.emit(&UNIT_FUNCTIONS, diagnostic);
+            }
+        }
+    }
+}
+
+#[test]
+fn passes_registered_by_others_run_with_the_builtin_ones() {
+    let mut store = LintStore::with_builtin_lints();
+    store.register_pass(Box::new(UnitFunctionsPass));
+    assert_eq!(store.find("unit-functions"), Some(&UNIT_FUNCTIONS));
+
+    let mut levels = LintLevels::new();
+    levels.set(&store, "unit_functions", Level::Deny).unwrap();
+    let mut sm = SourceManager::new();
+    let root = sm.add_file("main.ginto".to_string(), "fn f():\n    ()\n".to_string());
+    let (compilation, diagnostics) = Driver::new()
+        .with_lints(&mut store, &levels)
+        .check(&mut sm, root);
+    // a denied lint fails the program
+    assert!(compilation.is_none());
+    let unit: Vec<_> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.code.as_deref() == Some("W9999"))
+        .collect();
+    assert_eq!(unit.len(), 1);
+    assert_eq!(unit[0].severity, Severity::Error);
+}
+
+#[test]
+#[should_panic(expected = "lint `unused_variables` is registered twice")]
+fn lints_are_registered_once() {
+    let mut store = LintStore::with_builtin_lints();
+    store.register_pass(Box::new(ginto_lint::UnusedVariablesPass));
+}
+
+#[test]
+fn lints_report_at_their_default_level() {
+    let diagnostics = lint(SOURCE);
+    let unused = unused_variables(&diagnostics);
+    assert_eq!(unused.len(), 1);
+    assert_eq!(unused[0].severity, Severity::Warning);
+    assert_eq!(unused[0].code.as_deref(), Some("W0004"));
+    assert_eq!(
+        unused[0].notes.last().unwrap(),
+        "`unused_variables` is on by default"
+    );
+    // `shadowing` is allowed by default
+    assert!(
+        diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.code.as_deref() != Some(SHADOWING.code))
+    );
+}
+
+#[test]
+fn allowed_lints_report_nothing() {
+    let diagnostics = lint_with(SOURCE, &[("unused_variables", Level::Allow)]);
+    assert!(unused_variables(&diagnostics).is_empty());
+}
+
+#[test]
+fn denied_lints_are_errors() {
+    let diagnostics = lint_with(SOURCE, &[("unused_variables", Level::Deny)]);
+    let unused = unused_variables(&diagnostics);
+    assert_eq!(unused[0].severity, Severity::Error);
+    assert_eq!(
+        unused[0].notes.last().unwrap(),
+        "requested on the command line with `-D unused_variables`"
+    );
+}
+
+#[test]
+fn lints_allowed_by_default_can_be_enabled() {
+    let diagnostics = lint_with(SOURCE, &[("shadowing", Level::Warn)]);
+    let shadowing: Vec<_> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.code.as_deref() == Some(SHADOWING.code))
+        .collect();
+    assert_eq!(shadowing.len(), 1);
+    assert_eq!(shadowing[0].message, "`x` shadows an earlier binding");
+}
+
+#[test]
+fn warnings_sets_every_lint_that_warns_by_default() {
+    let diagnostics = lint_with(SOURCE, &[("warnings", Level::Deny)]);
+    let unused = unused_variables(&diagnostics);
+    assert_eq!(unused[0].severity, Severity::Error);
+    assert_eq!(
+        unused[0].notes.last().unwrap(),
+        "`-D unused_variables` implied by `-D warnings`"
+    );
+    // but not those allowed by default
+    let store = LintStore::with_builtin_lints();
+    let mut levels = LintLevels::new();
+    levels.set(&store, "warnings", Level::Deny).unwrap();
+    assert_eq!(
+        levels.level(&SHADOWING),
+        (Level::Allow, LevelSource::Default)
+    );
+}
+
+#[test]
+fn levels_set_by_name_win_over_warnings() {
+    let diagnostics = lint_with(
+        SOURCE,
+        &[("unused_variables", Level::Warn), ("warnings", Level::Deny)],
+    );
+    assert_eq!(
+        unused_variables(&diagnostics)[0].severity,
+        Severity::Warning
+    );
+    let diagnostics = lint_with(
+        SOURCE,
+        &[
+            ("warnings", Level::Allow),
+            ("unused-variables", Level::Warn),
+        ],
+    );
+    assert_eq!(unused_variables(&diagnostics).len(), 1);
+}
+
+#[test]
+fn levels_from_the_config_say_so() {
+    let store = LintStore::with_builtin_lints();
+    let mut levels = LintLevels::new();
+    levels
+        .set_in_config(&store, "unused_variables", Level::Deny)
+        .unwrap();
+    assert_eq!(
+        levels.level(&UNUSED_VARIABLES),
+        (Level::Deny, LevelSource::Config(Level::Deny))
+    );
+    assert_eq!(
+        levels.set_in_config(&store, "unused", Level::Deny),
+        Err("unused".to_string())
+    );
+}
+
+#[test]
+fn level_flags_are_parsed_from_the_command_line() {
+    let store = LintStore::with_builtin_lints();
+    let mut levels = LintLevels::new();
+    let args = [
+        "-A",
+        "unused-variables",
+        "main.ginto",
+        "-Wshadowing",
+        "-D",
+        "nope",
+    ];
+    let (rest, errors) = levels.parse_args(&store, args.map(String::from));
+    assert_eq!(rest, ["main.ginto"]);
+    assert_eq!(levels.level(&UNUSED_VARIABLES).0, Level::Allow);
+    assert_eq!(
+        levels.level(&SHADOWING),
+        (Level::Warn, LevelSource::CommandLine(Level::Warn))
+    );
+
+    let diagnostic = errors[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.severity, Severity::Warning);
+    assert_eq!(diagnostic.message, "unknown lint: `nope`");
+    assert_eq!(
+        diagnostic.notes,
+        ["requested on the command line with `-D nope`"]
+    );
+}
+
+#[test]
+fn lint_diagnostics_point_at_the_source() {
+    let diagnostics = lint(SOURCE);
+    let span = unused_variables(&diagnostics)[0].labels[0].span;
+    assert_eq!(span, Span::from_range(26..32));
+    assert_eq!(&SOURCE[26..32], "unused");
+}
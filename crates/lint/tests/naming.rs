@@ -0,0 +1,168 @@
+mod common;
+
+use common::{fixed, lint_code, lint_files, lint_with};
+use ginto_diag::{Applicability, Diagnostic, apply_edits};
+use ginto_lint::{Level, NON_SNAKE_CASE, is_snake_case, to_snake_case};
+use ginto_test_support::labels;
+
+fn misnamed(source: &str) -> Vec<Diagnostic> {
+    lint_code(source, NON_SNAKE_CASE.code)
+}
+
+#[test]
+fn snake_case_names() {
+    for name in [
+        "main",
+        "add_two",
+        "x",
+        "_unused",
+        "__private",
+        "v2",
+        "to_u64",
+    ] {
+        assert!(is_snake_case(name), "{name}");
+    }
+    for name in ["addTwo", "AddTwo", "X", "add__two", "add_two_", "_Unused"] {
+        assert!(!is_snake_case(name), "{name}");
+    }
+}
+
+#[test]
+fn names_convert_to_snake_case_at_case_changes() {
+    assert_eq!(to_snake_case("addTwo"), "add_two");
+    assert_eq!(to_snake_case("AddTwo"), "add_two");
+    assert_eq!(to_snake_case("HTTPServer"), "http_server");
+    assert_eq!(to_snake_case("parseHTTP"), "parse_http");
+    assert_eq!(to_snake_case("add__two_"), "add_two");
+    assert_eq!(to_snake_case("_Unused"), "_unused");
+    assert_eq!(to_snake_case("X"), "x");
+}
+
+#[test]
+fn functions_are_renamed_where_declared_and_called() {
+    let source =
+        "fn addTwo(n: u64) -> u64:\n    n + 2\n\nfn main() -> u64:\n    addTwo(addTwo(1))\n";
+    let diagnostics = misnamed(source);
+    let [diagnostic] = &diagnostics[..] else {
+        panic!("expected one warning: {diagnostics:#?}");
+    };
+    assert_eq!(
+        diagnostic.message,
+        "function `addTwo` should have a snake case name"
+    );
+    assert_eq!(
+        labels(source, diagnostic),
+        [(
+            "addTwo",
+            "convert the identifier to snake case: `add_two`".to_string()
+        )]
+    );
+    let suggestion = &diagnostic.suggestions[0];
+    assert_eq!(suggestion.message, "rename it to `add_two`");
+    assert_eq!(suggestion.applicability, Applicability::MachineApplicable);
+    assert_eq!(
+        fixed(source, diagnostic),
+        "fn add_two(n: u64) -> u64:\n    n + 2\n\nfn main() -> u64:\n    add_two(add_two(1))\n"
+    );
+}
+
+#[test]
+fn variables_are_renamed_where_declared_and_used() {
+    let source = "fn f(firstValue: u64) -> u64:\n    let Total = firstValue + 1\n    Total * 2\n";
+    let diagnostics = misnamed(source);
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "parameter `firstValue` should have a snake case name",
+            "local variable `Total` should have a snake case name",
+        ]
+    );
+    assert_eq!(
+        fixed(source, &diagnostics[0]),
+        "fn f(first_value: u64) -> u64:\n    let Total = first_value + 1\n    Total * 2\n"
+    );
+    assert_eq!(
+        fixed(source, &diagnostics[1]),
+        "fn f(firstValue: u64) -> u64:\n    let total = firstValue + 1\n    total * 2\n"
+    );
+}
+
+#[test]
+fn renames_that_would_clash_need_a_look() {
+    let source = "fn addTwo() -> u64:\n    2\n\nfn add_two() -> u64:\n    addTwo()\n";
+    let diagnostics = misnamed(source);
+    assert_eq!(
+        diagnostics[0].suggestions[0].applicability,
+        Applicability::MaybeIncorrect
+    );
+}
+
+#[test]
+fn methods_are_named_by_their_trait() {
+    let source = "trait Show:\n    fn showIt(self) -> u64\n\nimpl Show for u64:\n    fn showIt(self) -> u64:\n        self\n";
+    let diagnostics = misnamed(source);
+    assert!(!diagnostics.is_empty());
+    assert!(diagnostics.iter().all(|diagnostic| {
+        diagnostic.message == "method `showIt` should have a snake case name"
+            && diagnostic.suggestions[0].applicability == Applicability::MaybeIncorrect
+    }));
+}
+
+#[test]
+fn traits_and_type_parameters_are_upper_case() {
+    let source = "trait Show:\n    fn show(self) -> u64\n\nfn id<T>(x: T) -> T:\n    x\n";
+    assert!(misnamed(source).is_empty());
+}
+
+#[test]
+fn the_lint_can_be_allowed() {
+    let source = "fn addTwo() -> u64:\n    2\n";
+    let diagnostics = lint_with(source, &[("non_snake_case", Level::Allow)]);
+    assert!(
+        diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.code.as_deref() != Some(NON_SNAKE_CASE.code))
+    );
+}
+
+#[test]
+fn functions_called_through_paths_are_renamed_once() {
+    let source =
+        "fn fooBar() -> u64:\n    1\n\nfn main() -> u64:\n    crate::fooBar() + fooBar()\n";
+    let diagnostics = misnamed(source);
+    let [diagnostic] = &diagnostics[..] else {
+        panic!("expected one warning: {diagnostics:#?}");
+    };
+    let suggestion = &diagnostic.suggestions[0];
+    assert_eq!(suggestion.applicability, Applicability::MachineApplicable);
+    assert_eq!(suggestion.edits.len(), 3, "{suggestion:#?}");
+    assert_eq!(
+        fixed(source, diagnostic),
+        source.replace("fooBar", "foo_bar")
+    );
+}
+
+#[test]
+fn functions_of_other_modules_are_renamed_through_paths() {
+    let root = "mod m\n\nfn main() -> u64:\n    m::fooBar() + crate::m::fooBar()\n";
+    let module = "pub fn fooBar() -> u64:\n    1\n";
+    let (diagnostics, sm) = lint_files(root, &[("m.ginto", module)]);
+    let [diagnostic] = &diagnostics[..] else {
+        panic!("expected one warning: {diagnostics:#?}");
+    };
+    let suggestion = &diagnostic.suggestions[0];
+    for (name, source) in [("main.ginto", root), ("m.ginto", module)] {
+        let file = sm.file_id(name).unwrap();
+        let edits: Vec<_> = suggestion
+            .edits
+            .iter()
+            .filter(|edit| edit.file_id == file)
+            .cloned()
+            .collect();
+        assert_eq!(
+            apply_edits(source, &edits).unwrap(),
+            source.replace("fooBar", "foo_bar")
+        );
+    }
+}
@@ -0,0 +1,718 @@
+use std::{collections::HashMap, fmt::Write};
+
+use ginto_codegen::{
+    IntLiteral, Lowering, Repr, Signature, Unsupported, compare_units, func_instances, param_name,
+    param_name_span,
+};
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span, Symbol};
+use ginto_resolve::{DefId, DefKind, ModuleTree, Resolutions};
+use ginto_syntax::{
+    BinOp, Expr, ExprKind, Func, MatchArm, Pattern, PatternKind, Type, UnaryOp, visit,
+};
+use ginto_typeck::{Instance, Ty, TypeckResults};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LlvmError {
+    /// A construct the LLVM backend cannot compile yet.
+    Unsupported {
+        what: &'static str,
+        span: Span,
+        file_id: FileId,
+    },
+}
+
+impl From<Unsupported> for LlvmError {
+    fn from(err: Unsupported) -> Self {
+        LlvmError::Unsupported {
+            what: err.what,
+            span: err.span,
+            file_id: err.file_id,
+        }
+    }
+}
+
+impl DiagnosticConvertible for LlvmError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            LlvmError::Unsupported {
+                what,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("{} cannot be compiled to LLVM IR", what)).with_label(
+                Label::primary(file_id, span).with_message("not supported by the LLVM backend"),
+            ),
+        }
+    }
+}
+
+/// The declarations every module starts with.
+const PRELUDE: &str = "\
+declare { i64, i1 } @llvm.uadd.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.usub.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.umul.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.sadd.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.ssub.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.smul.with.overflow.i64(i64, i64)
+declare void @llvm.trap()
+declare i32 @printf(ptr, ...)
+declare i32 @puts(ptr)
+
+@.fmt.u64 = private unnamed_addr constant [6 x i8] c\"%llu\\0A\\00\"
+@.fmt.i64 = private unnamed_addr constant [6 x i8] c\"%lld\\0A\\00\"
+@.str.true = private unnamed_addr constant [5 x i8] c\"true\\00\"
+@.str.false = private unnamed_addr constant [6 x i8] c\"false\\00\"
+@.str.unit = private unnamed_addr constant [3 x i8] c\"()\\00\"
+";
+
+/// Compiles a type-checked program to textual LLVM IR, ready for `llc` or
+/// `clang`.
+///
+/// Functions are named by their path, like `@"crate::math::add"`, and a
+/// generic function is compiled once for each of its `instances`, like
+/// `@"crate::id::<u64>"`. If the
+/// root file has a `main` function, a C `main` runs it and prints its
+/// value, like `ginto` does. The errors the interpreter reports, like an
+/// arithmetic overflow, call `llvm.trap`.
+///
+/// `u64` and `i64` are represented as `i64` and `bool` as `i1`; `()` has no
+/// representation. Function values are not supported yet.
+pub fn emit_llvm_ir(
+    tree: &ModuleTree,
+    resolutions: &Resolutions,
+    types: &TypeckResults,
+    instances: &[Instance],
+) -> Result<String, Vec<LlvmError>> {
+    let mut emitter = Emitter {
+        lower: Lowering::new(resolutions, types),
+        funcs: HashMap::new(),
+        locals: HashMap::new(),
+        allocas: String::new(),
+        body: String::new(),
+        next_value: 0,
+        next_label: 0,
+        terminated: false,
+    };
+
+    // calls need the name and signature of their callee, which may come
+    // later
+    let mut compiled = Vec::new();
+    let mut main = None;
+    for (func, instance) in func_instances(tree, resolutions, types, instances) {
+        emitter.lower.enter(func.file_id, &instance);
+        let Some(sig) = emitter.lower.signature(func.func, func.def) else {
+            continue;
+        };
+        let path = tree.path(func.module);
+        let name = match func.impl_decl {
+            Some(decl) => format!(
+                "{}::<{} as {}>::{}",
+                path, decl.self_ty.inner, decl.trait_name.inner, func.func.name.inner
+            ),
+            None => format!("{}::{}", path, instance.display(resolutions)),
+        };
+        if func.is_main(tree) {
+            main = Some(instance.clone());
+        }
+        emitter.funcs.insert(instance.clone(), (name, sig));
+        compiled.push((func, instance));
+    }
+
+    let mut out = String::from(PRELUDE);
+    for (func, instance) in compiled {
+        emitter.lower.enter(func.file_id, &instance);
+        out.push('\n');
+        out.push_str(&emitter.compile_func(func.func, &instance));
+    }
+    if let Some(main) = main {
+        let (name, sig) = &emitter.funcs[&main];
+        out.push('\n');
+        out.push_str(&main_wrapper(name, sig, types.def_ty(main.def)));
+    }
+    if !emitter.lower.errors.is_empty() {
+        return Err(emitter
+            .lower
+            .errors
+            .into_iter()
+            .map(LlvmError::from)
+            .collect());
+    }
+    Ok(out)
+}
+
+/// A C `main` calling the program's `main`, of type `ty`, and printing its
+/// value.
+fn main_wrapper(name: &str, sig: &Signature, ty: Option<&Ty>) -> String {
+    let mut out = String::from("define i32 @main() {\nentry:\n");
+    match sig.ret {
+        Some(ret) => writeln!(out, "  %value = call {} @\"{}\"()", ir_ty(ret), name).unwrap(),
+        None => writeln!(out, "  call void @\"{}\"()", name).unwrap(),
+    }
+    let ret = match ty {
+        Some(Ty::Fn(sig)) => Some(&*sig.ret),
+        _ => None,
+    };
+    out.push_str(match ret {
+        Some(Ty::U64) => "  call i32 (ptr, ...) @printf(ptr @.fmt.u64, i64 %value)\n",
+        Some(Ty::I64) => "  call i32 (ptr, ...) @printf(ptr @.fmt.i64, i64 %value)\n",
+        Some(Ty::Bool) => {
+            "  %text = select i1 %value, ptr @.str.true, ptr @.str.false\n  call i32 @puts(ptr %text)\n"
+        }
+        _ => "  call i32 @puts(ptr @.str.unit)\n",
+    });
+    out.push_str("  ret i32 0\n}\n");
+    out
+}
+
+/// The IR type of the values of `repr`.
+fn ir_ty(repr: Repr) -> &'static str {
+    match repr {
+        Repr::U64 | Repr::I64 => "i64",
+        Repr::Bool => "i1",
+    }
+}
+
+struct Emitter<'a> {
+    lower: Lowering<'a>,
+    /// The name and signature of each function instance.
+    funcs: HashMap<Instance, (String, Signature)>,
+    /// The stack slot and type of each variable of the function being
+    /// compiled that has a representation.
+    locals: HashMap<DefId, (String, &'static str)>,
+    /// The `alloca`s of the function being compiled, which all go in its
+    /// entry block.
+    allocas: String,
+    body: String,
+    next_value: usize,
+    next_label: usize,
+    /// Whether the current block already ends with a terminator.
+    terminated: bool,
+}
+
+impl Emitter<'_> {
+    fn expr_ir_ty(&mut self, expr: &Expr) -> Option<&'static str> {
+        self.lower.expr_repr(expr).map(ir_ty)
+    }
+
+    fn def_ir_ty(&mut self, def: DefId, span: Span) -> Option<&'static str> {
+        self.lower.def_repr(def, span).map(ir_ty)
+    }
+
+    fn compile_func(&mut self, func: &Func, instance: &Instance) -> String {
+        self.locals.clear();
+        self.allocas.clear();
+        self.body.clear();
+        self.next_value = 0;
+        self.next_label = 0;
+        self.terminated = false;
+
+        let (name, sig) = self.funcs[instance].clone();
+        let ret = sig.ret.map(ir_ty);
+        let mut header = format!("define {} @\"{}\"(", ret.unwrap_or("void"), name);
+        let mut params = Vec::new();
+        for (param, repr) in func.params.iter().zip(sig.params) {
+            let span = param_name_span(param);
+            if let (Some(repr), Some(def)) = (repr, self.lower.def_at(span)) {
+                params.push((def, ir_ty(repr), param_name(param)));
+            }
+        }
+        for (i, (def, ty, name)) in params.into_iter().enumerate() {
+            if i > 0 {
+                header.push_str(", ");
+            }
+            write!(header, "{} %arg.{}", ty, name).unwrap();
+            let slot = self.alloca(def, ty, &name);
+            writeln!(self.allocas, "  store {} %arg.{}, ptr {}", ty, name, slot).unwrap();
+        }
+        header.push_str(") {\nentry:\n");
+
+        let value = self.compile_expr(&func.body);
+        if !self.terminated {
+            match ret {
+                Some(ty) => {
+                    let value = value.unwrap_or_else(|| "poison".to_string());
+                    self.inst(format!("ret {} {}", ty, value));
+                }
+                None => self.inst("ret void".to_string()),
+            }
+        }
+        format!("{}{}{}}}\n", header, self.allocas, self.body)
+    }
+
+    /// A stack slot for `def`, a variable called `name`.
+    fn alloca(&mut self, def: DefId, ty: &'static str, name: &str) -> String {
+        let slot = format!("%{}.addr{}", name, self.locals.len());
+        writeln!(self.allocas, "  {} = alloca {}", slot, ty).unwrap();
+        self.locals.insert(def, (slot.clone(), ty));
+        slot
+    }
+
+    /// A stack slot holding the result of a branching expression.
+    fn result_slot(&mut self, ty: &'static str) -> String {
+        let slot = format!("%result{}", self.next_label);
+        writeln!(self.allocas, "  {} = alloca {}", slot, ty).unwrap();
+        slot
+    }
+
+    /// The stack slot and type of the variable declared at `span`, if its
+    /// type has a representation.
+    fn declare_local(&mut self, name: Symbol, span: Span) -> Option<(String, &'static str)> {
+        let def = self.lower.def_at(span)?;
+        if let Some(local) = self.locals.get(&def) {
+            return Some(local.clone());
+        }
+        let ty = self.def_ir_ty(def, span)?;
+        let slot = name.with(|name| self.alloca(def, ty, name));
+        Some((slot, ty))
+    }
+
+    fn label(&mut self, prefix: &str) -> String {
+        self.next_label += 1;
+        format!("{}{}", prefix, self.next_label)
+    }
+
+    fn start_block(&mut self, label: &str) {
+        writeln!(self.body, "{}:", label).unwrap();
+        self.terminated = false;
+    }
+
+    /// Appends an instruction, in a new block if the current one has ended,
+    /// as code after a `return` does.
+    fn inst(&mut self, inst: String) {
+        if self.terminated {
+            let label = self.label("dead");
+            self.start_block(&label);
+        }
+        self.terminated = ["ret ", "br ", "unreachable"]
+            .iter()
+            .any(|terminator| inst.starts_with(terminator));
+        writeln!(self.body, "  {}", inst).unwrap();
+    }
+
+    /// Appends an instruction producing a value, and returns the value.
+    fn value(&mut self, inst: String) -> String {
+        self.next_value += 1;
+        let value = format!("%v{}", self.next_value);
+        self.inst(format!("{} = {}", value, inst));
+        value
+    }
+
+    fn branch(&mut self, target: &str) {
+        if !self.terminated {
+            self.inst(format!("br label %{}", target));
+        }
+    }
+
+    fn trap(&mut self) {
+        self.inst("call void @llvm.trap()".to_string());
+        self.inst("unreachable".to_string());
+    }
+
+    /// Traps if the `i1` `cond` is true.
+    fn trap_if(&mut self, cond: &str) {
+        let trap = self.label("trap");
+        let ok = self.label("ok");
+        self.inst(format!("br i1 {}, label %{}, label %{}", cond, trap, ok));
+        self.start_block(&trap);
+        self.trap();
+        self.start_block(&ok);
+    }
+
+    /// Compiles `expr` and returns its value, if it has a representation.
+    fn compile_expr(&mut self, expr: &Expr) -> Option<String> {
+        let value = self.compile_expr_kind(expr);
+        if self.lower.expr_ty(expr) == Some(Ty::Never) {
+            if !self.terminated {
+                self.inst("unreachable".to_string());
+            }
+            return None;
+        }
+        value
+    }
+
+    /// Compiles `expr`, whose value is used, to an operand. An expression
+    /// that never finishes has no value, and stands for any.
+    fn operand(&mut self, expr: &Expr) -> String {
+        self.compile_expr(expr)
+            .unwrap_or_else(|| "poison".to_string())
+    }
+
+    fn compile_expr_kind(&mut self, expr: &Expr) -> Option<String> {
+        let span = expr.span;
+        match &expr.inner {
+            ExprKind::Int(v) => self.compile_int(IntLiteral::new(
+                *v as i128,
+                self.lower.expr_ty(expr).as_ref(),
+            )),
+            ExprKind::Bool(v) => Some(v.to_string()),
+            ExprKind::Unit => None,
+            ExprKind::Error => unreachable!("program with syntax errors was compiled"),
+            ExprKind::Float(_) => {
+                self.lower.unsupported("floats", span);
+                None
+            }
+            ExprKind::Str(_) => {
+                self.lower.unsupported("strings", span);
+                None
+            }
+            ExprKind::Closure { .. } => {
+                self.lower.unsupported("closures", span);
+                None
+            }
+            ExprKind::Unary { op, expr: operand } => match op.inner {
+                UnaryOp::Neg => {
+                    if let Some(lit) = self.lower.int_literal(expr) {
+                        return self.compile_int(lit);
+                    }
+                    let value = self.operand(operand);
+                    match self.lower.expr_ty(operand) {
+                        Some(Ty::I64) => Some(self.checked("ssub", "0", &value)),
+                        _ => {
+                            let nonzero = self.value(format!("icmp ne i64 {}, 0", value));
+                            self.trap_if(&nonzero);
+                            Some("0".to_string())
+                        }
+                    }
+                }
+                UnaryOp::Not => {
+                    let value = self.operand(operand);
+                    Some(self.value(format!("xor i1 {}, true", value)))
+                }
+            },
+            ExprKind::Binary { op, lhs, rhs } => self.compile_binary(op.inner, lhs, rhs),
+            ExprKind::Cast { expr: operand, ty } => {
+                let value = self.compile_expr(operand);
+                let from = self.lower.expr_ty(operand);
+                match (from, &ty.inner, value) {
+                    (Some(Ty::Bool), Type::U64 | Type::I64, Some(value)) => {
+                        Some(self.value(format!("zext i1 {} to i64", value)))
+                    }
+                    (_, _, value) => value,
+                }
+            }
+            ExprKind::Let { name, value, .. } => {
+                let local = self.declare_local(name.inner, name.span);
+                if let Some(value) = value {
+                    let value = self.compile_expr(value);
+                    if let (Some((slot, ty)), Some(value)) = (local, value) {
+                        self.inst(format!("store {} {}, ptr {}", ty, value, slot));
+                    }
+                }
+                None
+            }
+            ExprKind::Assign { name, value } => {
+                let value = self.compile_expr(value);
+                let local = self
+                    .lower
+                    .resolve(name.span)
+                    .and_then(|def| self.locals.get(&def).cloned());
+                if let (Some((slot, ty)), Some(value)) = (local, value) {
+                    self.inst(format!("store {} {}, ptr {}", ty, value, slot));
+                }
+                None
+            }
+            ExprKind::Block { exprs, tail } => {
+                for expr in exprs {
+                    self.compile_expr(expr);
+                }
+                tail.as_ref().and_then(|tail| self.compile_expr(tail))
+            }
+            ExprKind::Call { callee, args } => {
+                let instance = match &callee.inner {
+                    ExprKind::Var(_) | ExprKind::Path(_) => self
+                        .lower
+                        .resolve(callee.span)
+                        .map(|def| self.lower.instance(def, callee.span)),
+                    _ => None,
+                };
+                match instance {
+                    Some(instance) if self.funcs.contains_key(&instance) => {
+                        let args: Vec<_> = args.iter().collect();
+                        self.compile_call(&instance, &args)
+                    }
+                    instance => {
+                        self.lower.unsupported_call(instance.as_ref(), span);
+                        None
+                    }
+                }
+            }
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => {
+                let instance = self
+                    .lower
+                    .types
+                    .method_target(self.lower.file_id, method.span)
+                    .map(|def| self.lower.instance(def, method.span))
+                    .filter(|instance| self.funcs.contains_key(instance));
+                let Some(instance) = instance else {
+                    self.lower.unsupported("calls to this method", span);
+                    return None;
+                };
+                let args: Vec<_> = [&**receiver].into_iter().chain(args).collect();
+                self.compile_call(&instance, &args)
+            }
+            ExprKind::Match { scrutinee, arms } => self.compile_match(expr, scrutinee, arms),
+            ExprKind::Return(value) => {
+                let value = value.as_ref().and_then(|value| {
+                    let ty = self.expr_ir_ty(value)?;
+                    Some((ty, self.operand(value)))
+                });
+                match value {
+                    Some((ty, value)) => self.inst(format!("ret {} {}", ty, value)),
+                    None => self.inst("ret void".to_string()),
+                }
+                None
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                let cond = self.operand(cond);
+                let ty = self.expr_ir_ty(expr);
+                let slot = ty.map(|ty| (self.result_slot(ty), ty));
+                let then_label = self.label("then");
+                let else_label = self.label("else");
+                let end = self.label("end");
+                self.inst(format!(
+                    "br i1 {}, label %{}, label %{}",
+                    cond, then_label, else_label
+                ));
+                for (label, branch) in [
+                    (then_label, Some(then_branch)),
+                    (else_label, else_branch.as_ref()),
+                ] {
+                    self.start_block(&label);
+                    let value = branch.and_then(|branch| self.compile_expr(branch));
+                    if let (Some((slot, ty)), Some(value)) = (&slot, value) {
+                        self.inst(format!("store {} {}, ptr {}", ty, value, slot));
+                    }
+                    self.branch(&end);
+                }
+                self.start_block(&end);
+                slot.map(|(slot, ty)| self.value(format!("load {}, ptr {}", ty, slot)))
+            }
+            ExprKind::While { cond, body } => {
+                let head = self.label("while");
+                let body_label = self.label("body");
+                let end = self.label("end");
+                self.branch(&head);
+                self.start_block(&head);
+                let cond = self.operand(cond);
+                self.inst(format!(
+                    "br i1 {}, label %{}, label %{}",
+                    cond, body_label, end
+                ));
+                self.start_block(&body_label);
+                self.compile_expr(body);
+                self.branch(&head);
+                self.start_block(&end);
+                None
+            }
+            ExprKind::Var(_) | ExprKind::Path(_) => {
+                let def = self.lower.resolve(span)?;
+                match self.locals.get(&def).cloned() {
+                    Some((slot, ty)) => Some(self.value(format!("load {}, ptr {}", ty, slot))),
+                    None if self.lower.resolutions.def(def).kind == DefKind::Func => {
+                        self.lower.unsupported("function values", span);
+                        None
+                    }
+                    None => None,
+                }
+            }
+        }
+    }
+
+    fn compile_call(&mut self, instance: &Instance, args: &[&Expr]) -> Option<String> {
+        let (name, sig) = &self.funcs[instance];
+        let (name, params, ret) = (name.clone(), sig.params.clone(), sig.ret.map(ir_ty));
+        let mut operands = Vec::new();
+        for (arg, repr) in args.iter().zip(params) {
+            let value = self.compile_expr(arg);
+            if let Some(repr) = repr {
+                let value = value.unwrap_or_else(|| "poison".to_string());
+                operands.push(format!("{} {}", ir_ty(repr), value));
+            }
+        }
+        let call = format!(
+            "call {} @\"{}\"({})",
+            ret.unwrap_or("void"),
+            name,
+            operands.join(", ")
+        );
+        match ret {
+            Some(_) => Some(self.value(call)),
+            None => {
+                self.inst(call);
+                None
+            }
+        }
+    }
+
+    /// A literal out of range for its type traps, like it fails in the
+    /// interpreter.
+    fn compile_int(&mut self, lit: IntLiteral) -> Option<String> {
+        let Some(bits) = lit.bits() else {
+            self.trap();
+            return None;
+        };
+        Some(bits.to_string())
+    }
+
+    /// `lhs op rhs` with the `llvm.op.with.overflow` intrinsic, trapping on
+    /// overflow.
+    fn checked(&mut self, op: &str, lhs: &str, rhs: &str) -> String {
+        let pair = self.value(format!(
+            "call {{ i64, i1 }} @llvm.{}.with.overflow.i64(i64 {}, i64 {})",
+            op, lhs, rhs
+        ));
+        let overflow = self.value(format!("extractvalue {{ i64, i1 }} {}, 1", pair));
+        self.trap_if(&overflow);
+        self.value(format!("extractvalue {{ i64, i1 }} {}, 0", pair))
+    }
+
+    /// `lhs op rhs` for a division or remainder, trapping where it is
+    /// undefined.
+    fn checked_div(&mut self, op: &str, signed: bool, lhs: &str, rhs: &str) -> String {
+        let zero = self.value(format!("icmp eq i64 {}, 0", rhs));
+        self.trap_if(&zero);
+        if signed {
+            let min = self.value(format!("icmp eq i64 {}, {}", lhs, i64::MIN));
+            let minus_one = self.value(format!("icmp eq i64 {}, -1", rhs));
+            let overflow = self.value(format!("and i1 {}, {}", min, minus_one));
+            self.trap_if(&overflow);
+        }
+        self.value(format!("{} i64 {}, {}", op, lhs, rhs))
+    }
+
+    fn compile_binary(&mut self, op: BinOp, lhs: &Expr, rhs: &Expr) -> Option<String> {
+        if let BinOp::And | BinOp::Or = op {
+            let slot = self.result_slot("i1");
+            let rhs_label = self.label("rhs");
+            let end = self.label("end");
+            let value = self.operand(lhs);
+            self.inst(format!("store i1 {}, ptr {}", value, slot));
+            let (on_true, on_false) = match op {
+                BinOp::And => (&rhs_label, &end),
+                _ => (&end, &rhs_label),
+            };
+            self.inst(format!(
+                "br i1 {}, label %{}, label %{}",
+                value, on_true, on_false
+            ));
+            self.start_block(&rhs_label);
+            let value = self.operand(rhs);
+            if !self.terminated {
+                self.inst(format!("store i1 {}, ptr {}", value, slot));
+            }
+            self.branch(&end);
+            self.start_block(&end);
+            return Some(self.value(format!("load i1, ptr {}", slot)));
+        }
+
+        let ty = self.lower.expr_ty(lhs);
+        let l = self.operand(lhs);
+        let r = self.operand(rhs);
+        let signed = ty == Some(Ty::I64);
+        let value = match (ty, op) {
+            (Some(Ty::Unit), _) => compare_units(op).to_string(),
+            (Some(Ty::Bool), _) => {
+                let cond = comparison(op, false);
+                self.value(format!("icmp {} i1 {}, {}", cond, l, r))
+            }
+            (_, BinOp::Add) => self.checked(if signed { "sadd" } else { "uadd" }, &l, &r),
+            (_, BinOp::Sub) => self.checked(if signed { "ssub" } else { "usub" }, &l, &r),
+            (_, BinOp::Mul) => self.checked(if signed { "smul" } else { "umul" }, &l, &r),
+            (_, BinOp::Div) => {
+                self.checked_div(if signed { "sdiv" } else { "udiv" }, signed, &l, &r)
+            }
+            (_, BinOp::Mod) => {
+                self.checked_div(if signed { "srem" } else { "urem" }, signed, &l, &r)
+            }
+            _ => {
+                let cond = comparison(op, signed);
+                self.value(format!("icmp {} i64 {}, {}", cond, l, r))
+            }
+        };
+        Some(value)
+    }
+
+    /// Compiles a `match` to a chain of branches testing the scrutinee.
+    fn compile_match(
+        &mut self,
+        expr: &Expr,
+        scrutinee: &Expr,
+        arms: &[MatchArm],
+    ) -> Option<String> {
+        let scrutinee_ty = self.lower.expr_ty(scrutinee);
+        let value = self.compile_expr(scrutinee);
+        let ty = self.expr_ir_ty(expr);
+        let slot = ty.map(|ty| (self.result_slot(ty), ty));
+        let end = self.label("end");
+        for arm in arms {
+            let test = self.compile_test(&arm.pattern, value.as_deref(), scrutinee_ty.as_ref());
+            let body = self.label("arm");
+            let next = self.label("next");
+            self.inst(format!("br i1 {}, label %{}, label %{}", test, body, next));
+            self.start_block(&body);
+            for name in visit::pattern_bindings(&arm.pattern) {
+                if let (Some((binding, ty)), Some(value)) =
+                    (self.declare_local(name.inner, name.span), &value)
+                {
+                    self.inst(format!("store {} {}, ptr {}", ty, value, binding));
+                }
+            }
+            let result = self.compile_expr(&arm.body);
+            if let (Some((slot, ty)), Some(result)) = (&slot, result) {
+                self.inst(format!("store {} {}, ptr {}", ty, result, slot));
+            }
+            self.branch(&end);
+            self.start_block(&next);
+        }
+        // matches are exhaustive
+        self.inst("unreachable".to_string());
+        self.start_block(&end);
+        slot.map(|(slot, ty)| self.value(format!("load {}, ptr {}", ty, slot)))
+    }
+
+    /// An `i1` telling whether the scrutinee `value` matches `pattern`.
+    fn compile_test(&mut self, pattern: &Pattern, value: Option<&str>, ty: Option<&Ty>) -> String {
+        match (&pattern.inner, value) {
+            (PatternKind::Int(n), Some(value)) => match IntLiteral::new(*n, ty).bits() {
+                Some(bits) => self.value(format!("icmp eq i64 {}, {}", value, bits)),
+                None => "false".to_string(),
+            },
+            (PatternKind::Bool(b), Some(value)) => {
+                self.value(format!("icmp eq i1 {}, {}", value, b))
+            }
+            (PatternKind::Or(alternatives), _) => {
+                let mut test = "false".to_string();
+                for alternative in alternatives {
+                    let alternative = self.compile_test(alternative, value, ty);
+                    test = self.value(format!("or i1 {}, {}", test, alternative));
+                }
+                test
+            }
+            _ => "true".to_string(),
+        }
+    }
+}
+
+/// The `icmp` condition of a comparison.
+fn comparison(op: BinOp, signed: bool) -> &'static str {
+    match (op, signed) {
+        (BinOp::Equal, _) => "eq",
+        (BinOp::NotEq, _) => "ne",
+        (BinOp::Less, true) => "slt",
+        (BinOp::Le, true) => "sle",
+        (BinOp::Greater, true) => "sgt",
+        (BinOp::Ge, true) => "sge",
+        (BinOp::Less, false) => "ult",
+        (BinOp::Le, false) => "ule",
+        (BinOp::Greater, false) => "ugt",
+        (BinOp::Ge, false) => "uge",
+        _ => unreachable!("`{}` is not a comparison", op),
+    }
+}
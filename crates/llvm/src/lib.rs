@@ -0,0 +1,3 @@
+mod emitter;
+
+pub use emitter::*;
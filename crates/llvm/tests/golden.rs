@@ -0,0 +1,27 @@
+//! Compiles every program of `tests/programs` to LLVM IR and compares it,
+//! or the errors, to the `.ll` file next to it. Run with `BLESS=1` to
+//! rewrite the `.ll` files.
+
+use ginto_diag::DiagnosticConvertible;
+use ginto_llvm::emit_llvm_ir;
+use ginto_test_support::golden;
+
+#[test]
+fn llvm_ir_golden() {
+    golden(
+        env!("CARGO_MANIFEST_DIR"),
+        "ll",
+        |program| match emit_llvm_ir(
+            &program.tree,
+            &program.resolutions,
+            &program.types,
+            &program.instances,
+        ) {
+            Ok(out) => out,
+            Err(errors) => errors
+                .into_iter()
+                .map(|err| format!("; error: {}\n", err.into_diagnostic().message))
+                .collect(),
+        },
+    );
+}
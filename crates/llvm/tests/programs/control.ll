@@ -0,0 +1,164 @@
+declare { i64, i1 } @llvm.uadd.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.usub.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.umul.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.sadd.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.ssub.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.smul.with.overflow.i64(i64, i64)
+declare void @llvm.trap()
+declare i32 @printf(ptr, ...)
+declare i32 @puts(ptr)
+
+@.fmt.u64 = private unnamed_addr constant [6 x i8] c"%llu\0A\00"
+@.fmt.i64 = private unnamed_addr constant [6 x i8] c"%lld\0A\00"
+@.str.true = private unnamed_addr constant [5 x i8] c"true\00"
+@.str.false = private unnamed_addr constant [6 x i8] c"false\00"
+@.str.unit = private unnamed_addr constant [3 x i8] c"()\00"
+
+define i64 @"crate::classify"(i64 %arg.n) {
+entry:
+  %n.addr0 = alloca i64
+  store i64 %arg.n, ptr %n.addr0
+  %result0 = alloca i64
+  %x.addr1 = alloca i64
+  %result7 = alloca i64
+  %v1 = load i64, ptr %n.addr0
+  %v2 = icmp eq i64 %v1, 0
+  br i1 %v2, label %arm2, label %next3
+arm2:
+  store i64 100, ptr %result0
+  br label %end1
+next3:
+  %v3 = icmp eq i64 %v1, 1
+  %v4 = or i1 false, %v3
+  %v5 = icmp eq i64 %v1, 2
+  %v6 = or i1 %v4, %v5
+  %v7 = icmp eq i64 %v1, 3
+  %v8 = or i1 %v6, %v7
+  br i1 %v8, label %arm4, label %next5
+arm4:
+  store i64 200, ptr %result0
+  br label %end1
+next5:
+  br i1 true, label %arm6, label %next7
+arm6:
+  store i64 %v1, ptr %x.addr1
+  %v9 = load i64, ptr %x.addr1
+  %v10 = icmp sgt i64 %v9, 0
+  %v11 = icmp eq i1 %v10, true
+  br i1 %v11, label %arm9, label %next10
+arm9:
+  store i64 400, ptr %result7
+  br label %end8
+next10:
+  %v12 = icmp eq i1 %v10, false
+  br i1 %v12, label %arm11, label %next12
+arm11:
+  store i64 500, ptr %result7
+  br label %end8
+next12:
+  unreachable
+end8:
+  %v13 = load i64, ptr %result7
+  store i64 %v13, ptr %result0
+  br label %end1
+next7:
+  unreachable
+end1:
+  %v14 = load i64, ptr %result0
+  ret i64 %v14
+}
+
+define i1 @"crate::both"(i1 %arg.a, i1 %arg.b) {
+entry:
+  %a.addr0 = alloca i1
+  store i1 %arg.a, ptr %a.addr0
+  %b.addr1 = alloca i1
+  store i1 %arg.b, ptr %b.addr1
+  %result0 = alloca i1
+  %result2 = alloca i1
+  %v1 = load i1, ptr %a.addr0
+  store i1 %v1, ptr %result2
+  br i1 %v1, label %rhs3, label %end4
+rhs3:
+  %v2 = load i1, ptr %b.addr1
+  %v3 = xor i1 %v2, true
+  store i1 %v3, ptr %result2
+  br label %end4
+end4:
+  %v4 = load i1, ptr %result2
+  store i1 %v4, ptr %result0
+  br i1 %v4, label %end2, label %rhs1
+rhs1:
+  %v5 = load i1, ptr %a.addr0
+  %v6 = load i1, ptr %b.addr1
+  %v7 = icmp eq i1 %v5, %v6
+  store i1 %v7, ptr %result0
+  br label %end2
+end2:
+  %v8 = load i1, ptr %result0
+  ret i1 %v8
+}
+
+define i64 @"crate::count"(i64 %arg.n) {
+entry:
+  %n.addr0 = alloca i64
+  store i64 %arg.n, ptr %n.addr0
+  %total.addr1 = alloca i64
+  %i.addr2 = alloca i64
+  store i64 0, ptr %total.addr1
+  %v1 = load i64, ptr %n.addr0
+  store i64 %v1, ptr %i.addr2
+  %v2 = load i64, ptr %i.addr2
+  %v3 = icmp ugt i64 %v2, 10
+  br i1 %v3, label %then1, label %else2
+then1:
+  ret i64 0
+else2:
+  br label %end3
+end3:
+  %v4 = load i64, ptr %total.addr1
+  %v5 = load i64, ptr %i.addr2
+  %v6 = call { i64, i1 } @llvm.uadd.with.overflow.i64(i64 %v4, i64 %v5)
+  %v7 = extractvalue { i64, i1 } %v6, 1
+  br i1 %v7, label %trap4, label %ok5
+trap4:
+  call void @llvm.trap()
+  unreachable
+ok5:
+  %v8 = extractvalue { i64, i1 } %v6, 0
+  store i64 %v8, ptr %total.addr1
+  %v9 = load i64, ptr %total.addr1
+  ret i64 %v9
+}
+
+define i64 @"crate::main"() {
+entry:
+  %v1 = call i64 @"crate::classify"(i64 2)
+  %v2 = call i64 @"crate::count"(i64 3)
+  %v3 = call { i64, i1 } @llvm.uadd.with.overflow.i64(i64 %v1, i64 %v2)
+  %v4 = extractvalue { i64, i1 } %v3, 1
+  br i1 %v4, label %trap1, label %ok2
+trap1:
+  call void @llvm.trap()
+  unreachable
+ok2:
+  %v5 = extractvalue { i64, i1 } %v3, 0
+  %v6 = call i1 @"crate::both"(i1 true, i1 false)
+  %v7 = zext i1 %v6 to i64
+  %v8 = call { i64, i1 } @llvm.uadd.with.overflow.i64(i64 %v5, i64 %v7)
+  %v9 = extractvalue { i64, i1 } %v8, 1
+  br i1 %v9, label %trap3, label %ok4
+trap3:
+  call void @llvm.trap()
+  unreachable
+ok4:
+  %v10 = extractvalue { i64, i1 } %v8, 0
+  ret i64 %v10
+}
+
+define i32 @main() {
+entry:
+  %value = call i64 @"crate::main"()
+  call i32 (ptr, ...) @printf(ptr @.fmt.u64, i64 %value)
+  ret i32 0
+}
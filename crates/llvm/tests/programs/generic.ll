@@ -0,0 +1,100 @@
+declare { i64, i1 } @llvm.uadd.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.usub.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.umul.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.sadd.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.ssub.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.smul.with.overflow.i64(i64, i64)
+declare void @llvm.trap()
+declare i32 @printf(ptr, ...)
+declare i32 @puts(ptr)
+
+@.fmt.u64 = private unnamed_addr constant [6 x i8] c"%llu\0A\00"
+@.fmt.i64 = private unnamed_addr constant [6 x i8] c"%lld\0A\00"
+@.str.true = private unnamed_addr constant [5 x i8] c"true\00"
+@.str.false = private unnamed_addr constant [6 x i8] c"false\00"
+@.str.unit = private unnamed_addr constant [3 x i8] c"()\00"
+
+define i1 @"crate::id::<bool>"(i1 %arg.x) {
+entry:
+  %x.addr0 = alloca i1
+  store i1 %arg.x, ptr %x.addr0
+  %v1 = load i1, ptr %x.addr0
+  ret i1 %v1
+}
+
+define i64 @"crate::id::<u64>"(i64 %arg.x) {
+entry:
+  %x.addr0 = alloca i64
+  store i64 %arg.x, ptr %x.addr0
+  %v1 = load i64, ptr %x.addr0
+  ret i64 %v1
+}
+
+define i1 @"crate::pick::<bool>"(i1 %arg.first, i1 %arg.a, i1 %arg.b) {
+entry:
+  %first.addr0 = alloca i1
+  store i1 %arg.first, ptr %first.addr0
+  %a.addr1 = alloca i1
+  store i1 %arg.a, ptr %a.addr1
+  %b.addr2 = alloca i1
+  store i1 %arg.b, ptr %b.addr2
+  %result0 = alloca i1
+  %v1 = load i1, ptr %first.addr0
+  br i1 %v1, label %then1, label %else2
+then1:
+  %v2 = load i1, ptr %a.addr1
+  %v3 = call i1 @"crate::id::<bool>"(i1 %v2)
+  store i1 %v3, ptr %result0
+  br label %end3
+else2:
+  %v4 = load i1, ptr %b.addr2
+  store i1 %v4, ptr %result0
+  br label %end3
+end3:
+  %v5 = load i1, ptr %result0
+  ret i1 %v5
+}
+
+define i64 @"crate::pick::<u64>"(i1 %arg.first, i64 %arg.a, i64 %arg.b) {
+entry:
+  %first.addr0 = alloca i1
+  store i1 %arg.first, ptr %first.addr0
+  %a.addr1 = alloca i64
+  store i64 %arg.a, ptr %a.addr1
+  %b.addr2 = alloca i64
+  store i64 %arg.b, ptr %b.addr2
+  %result0 = alloca i64
+  %v1 = load i1, ptr %first.addr0
+  br i1 %v1, label %then1, label %else2
+then1:
+  %v2 = load i64, ptr %a.addr1
+  %v3 = call i64 @"crate::id::<u64>"(i64 %v2)
+  store i64 %v3, ptr %result0
+  br label %end3
+else2:
+  %v4 = load i64, ptr %b.addr2
+  store i64 %v4, ptr %result0
+  br label %end3
+end3:
+  %v5 = load i64, ptr %result0
+  ret i64 %v5
+}
+
+define i64 @"crate::main"() {
+entry:
+  %flag.addr0 = alloca i1
+  %v1 = call i1 @"crate::id::<bool>"(i1 true)
+  %v2 = call i1 @"crate::pick::<bool>"(i1 false, i1 false, i1 %v1)
+  store i1 %v2, ptr %flag.addr0
+  %v3 = load i1, ptr %flag.addr0
+  %v4 = call i64 @"crate::id::<u64>"(i64 4)
+  %v5 = call i64 @"crate::pick::<u64>"(i1 %v3, i64 3, i64 %v4)
+  ret i64 %v5
+}
+
+define i32 @main() {
+entry:
+  %value = call i64 @"crate::main"()
+  call i32 (ptr, ...) @printf(ptr @.fmt.u64, i64 %value)
+  ret i32 0
+}
@@ -0,0 +1,158 @@
+declare { i64, i1 } @llvm.uadd.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.usub.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.umul.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.sadd.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.ssub.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.smul.with.overflow.i64(i64, i64)
+declare void @llvm.trap()
+declare i32 @printf(ptr, ...)
+declare i32 @puts(ptr)
+
+@.fmt.u64 = private unnamed_addr constant [6 x i8] c"%llu\0A\00"
+@.fmt.i64 = private unnamed_addr constant [6 x i8] c"%lld\0A\00"
+@.str.true = private unnamed_addr constant [5 x i8] c"true\00"
+@.str.false = private unnamed_addr constant [6 x i8] c"false\00"
+@.str.unit = private unnamed_addr constant [3 x i8] c"()\00"
+
+define i64 @"crate::sum_to"(i64 %arg.n) {
+entry:
+  %n.addr0 = alloca i64
+  store i64 %arg.n, ptr %n.addr0
+  %total.addr1 = alloca i64
+  %i.addr2 = alloca i64
+  store i64 0, ptr %total.addr1
+  store i64 0, ptr %i.addr2
+  br label %while1
+while1:
+  %v1 = load i64, ptr %i.addr2
+  %v2 = load i64, ptr %n.addr0
+  %v3 = icmp ult i64 %v1, %v2
+  br i1 %v3, label %body2, label %end3
+body2:
+  %v4 = load i64, ptr %i.addr2
+  %v5 = call { i64, i1 } @llvm.uadd.with.overflow.i64(i64 %v4, i64 1)
+  %v6 = extractvalue { i64, i1 } %v5, 1
+  br i1 %v6, label %trap4, label %ok5
+trap4:
+  call void @llvm.trap()
+  unreachable
+ok5:
+  %v7 = extractvalue { i64, i1 } %v5, 0
+  store i64 %v7, ptr %i.addr2
+  %v8 = load i64, ptr %total.addr1
+  %v9 = load i64, ptr %i.addr2
+  %v10 = call { i64, i1 } @llvm.uadd.with.overflow.i64(i64 %v8, i64 %v9)
+  %v11 = extractvalue { i64, i1 } %v10, 1
+  br i1 %v11, label %trap6, label %ok7
+trap6:
+  call void @llvm.trap()
+  unreachable
+ok7:
+  %v12 = extractvalue { i64, i1 } %v10, 0
+  store i64 %v12, ptr %total.addr1
+  br label %while1
+end3:
+  %v13 = load i64, ptr %total.addr1
+  ret i64 %v13
+}
+
+define i64 @"crate::collatz"(i64 %arg.n) {
+entry:
+  %n.addr0 = alloca i64
+  store i64 %arg.n, ptr %n.addr0
+  %n.addr1 = alloca i64
+  %steps.addr2 = alloca i64
+  %v1 = load i64, ptr %n.addr0
+  store i64 %v1, ptr %n.addr1
+  store i64 0, ptr %steps.addr2
+  br label %while1
+while1:
+  %v2 = load i64, ptr %n.addr1
+  %v3 = icmp ne i64 %v2, 1
+  br i1 %v3, label %body2, label %end3
+body2:
+  %v4 = load i64, ptr %n.addr1
+  %v5 = icmp eq i64 2, 0
+  br i1 %v5, label %trap4, label %ok5
+trap4:
+  call void @llvm.trap()
+  unreachable
+ok5:
+  %v6 = urem i64 %v4, 2
+  %v7 = icmp eq i64 %v6, 0
+  br i1 %v7, label %then6, label %else7
+then6:
+  %v8 = load i64, ptr %n.addr1
+  %v9 = icmp eq i64 2, 0
+  br i1 %v9, label %trap9, label %ok10
+trap9:
+  call void @llvm.trap()
+  unreachable
+ok10:
+  %v10 = udiv i64 %v8, 2
+  store i64 %v10, ptr %n.addr1
+  br label %end8
+else7:
+  %v11 = load i64, ptr %n.addr1
+  %v12 = call { i64, i1 } @llvm.umul.with.overflow.i64(i64 3, i64 %v11)
+  %v13 = extractvalue { i64, i1 } %v12, 1
+  br i1 %v13, label %trap11, label %ok12
+trap11:
+  call void @llvm.trap()
+  unreachable
+ok12:
+  %v14 = extractvalue { i64, i1 } %v12, 0
+  %v15 = call { i64, i1 } @llvm.uadd.with.overflow.i64(i64 %v14, i64 1)
+  %v16 = extractvalue { i64, i1 } %v15, 1
+  br i1 %v16, label %trap13, label %ok14
+trap13:
+  call void @llvm.trap()
+  unreachable
+ok14:
+  %v17 = extractvalue { i64, i1 } %v15, 0
+  store i64 %v17, ptr %n.addr1
+  br label %end8
+end8:
+  %v18 = load i64, ptr %steps.addr2
+  %v19 = call { i64, i1 } @llvm.uadd.with.overflow.i64(i64 %v18, i64 1)
+  %v20 = extractvalue { i64, i1 } %v19, 1
+  br i1 %v20, label %trap15, label %ok16
+trap15:
+  call void @llvm.trap()
+  unreachable
+ok16:
+  %v21 = extractvalue { i64, i1 } %v19, 0
+  store i64 %v21, ptr %steps.addr2
+  br label %while1
+end3:
+  %v22 = load i64, ptr %steps.addr2
+  ret i64 %v22
+}
+
+define i64 @"crate::main"() {
+entry:
+  br label %while1
+while1:
+  br i1 false, label %body2, label %end3
+body2:
+  br label %while1
+end3:
+  %v1 = call i64 @"crate::sum_to"(i64 10)
+  %v2 = call i64 @"crate::collatz"(i64 27)
+  %v3 = call { i64, i1 } @llvm.uadd.with.overflow.i64(i64 %v1, i64 %v2)
+  %v4 = extractvalue { i64, i1 } %v3, 1
+  br i1 %v4, label %trap4, label %ok5
+trap4:
+  call void @llvm.trap()
+  unreachable
+ok5:
+  %v5 = extractvalue { i64, i1 } %v3, 0
+  ret i64 %v5
+}
+
+define i32 @main() {
+entry:
+  %value = call i64 @"crate::main"()
+  call i32 (ptr, ...) @printf(ptr @.fmt.u64, i64 %value)
+  ret i32 0
+}
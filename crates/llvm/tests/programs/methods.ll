@@ -0,0 +1,51 @@
+declare { i64, i1 } @llvm.uadd.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.usub.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.umul.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.sadd.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.ssub.with.overflow.i64(i64, i64)
+declare { i64, i1 } @llvm.smul.with.overflow.i64(i64, i64)
+declare void @llvm.trap()
+declare i32 @printf(ptr, ...)
+declare i32 @puts(ptr)
+
+@.fmt.u64 = private unnamed_addr constant [6 x i8] c"%llu\0A\00"
+@.fmt.i64 = private unnamed_addr constant [6 x i8] c"%lld\0A\00"
+@.str.true = private unnamed_addr constant [5 x i8] c"true\00"
+@.str.false = private unnamed_addr constant [6 x i8] c"false\00"
+@.str.unit = private unnamed_addr constant [3 x i8] c"()\00"
+
+define i64 @"crate::<u64 as Shape>::area"(i64 %arg.self) {
+entry:
+  %self.addr0 = alloca i64
+  store i64 %arg.self, ptr %self.addr0
+  %v1 = load i64, ptr %self.addr0
+  %v2 = load i64, ptr %self.addr0
+  %v3 = call { i64, i1 } @llvm.umul.with.overflow.i64(i64 %v1, i64 %v2)
+  %v4 = extractvalue { i64, i1 } %v3, 1
+  br i1 %v4, label %trap1, label %ok2
+trap1:
+  call void @llvm.trap()
+  unreachable
+ok2:
+  %v5 = extractvalue { i64, i1 } %v3, 0
+  ret i64 %v5
+}
+
+define void @"crate::nothing"() {
+entry:
+  ret void
+}
+
+define i64 @"crate::main"() {
+entry:
+  call void @"crate::nothing"()
+  %v1 = call i64 @"crate::<u64 as Shape>::area"(i64 3)
+  ret i64 %v1
+}
+
+define i32 @main() {
+entry:
+  %value = call i64 @"crate::main"()
+  call i32 (ptr, ...) @printf(ptr @.fmt.u64, i64 %value)
+  ret i32 0
+}
@@ -0,0 +1,273 @@
+use std::collections::HashSet;
+
+use ginto_diag::{Spanned, Symbol};
+use ginto_resolve::{DefId, DefKind};
+use ginto_syntax::{
+    Expr, ExprKind, Func, Item, ItemKind, Lexer, Param, Parser, ParserError, TokenKind, Type,
+    visit::{self, Visitor, pattern_bindings},
+};
+use lsp_types::{CompletionItem, CompletionItemKind, Position, Uri};
+
+use crate::{Document, Documents, to_offset};
+
+/// The name the word at the cursor is replaced with to find out what could
+/// be written there.
+const PLACEHOLDER: &str = "__ginto_completion__";
+
+/// The keywords that start an expression.
+const EXPR_KEYWORDS: &[TokenKind] = &[
+    TokenKind::If,
+    TokenKind::Match,
+    TokenKind::While,
+    TokenKind::Return,
+    TokenKind::Not,
+];
+
+/// The keywords that name a type, besides type parameters.
+const TYPE_KEYWORDS: &[TokenKind] = &[
+    TokenKind::U64,
+    TokenKind::I64,
+    TokenKind::F64,
+    TokenKind::Bool,
+    TokenKind::Str,
+];
+
+/// What could be written at `position` in the document at `uri`: the
+/// keywords the parser expects there and, where an expression goes, the
+/// variables and functions in scope.
+pub fn completion(
+    documents: &Documents,
+    uri: &Uri,
+    position: Position,
+) -> Option<Vec<CompletionItem>> {
+    let document = documents.get(uri)?;
+    let _session = document.enter();
+    let file = documents.source_manager().get_file(document.file)?;
+    let pos = to_offset(file, position).0;
+    // the whole word around the cursor, which the client filters by
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    let start = file.source[..pos]
+        .rfind(|ch| !is_word(ch))
+        .map_or(0, |i| i + 1);
+    let end = file.source[pos..]
+        .find(|ch| !is_word(ch))
+        .map_or(file.source.len(), |i| pos + i);
+    let source = format!(
+        "{}{PLACEHOLDER}{}",
+        &file.source[..start],
+        &file.source[end..]
+    );
+    let tokens = Lexer::new(document.file, &source).lex_all().ok()?;
+    let mut parser = Parser::new(document.file, tokens);
+    let program = parser.parse_program();
+
+    // where the parser gives up on the placeholder, it says what it expected
+    let expected = parser.errors().iter().find_map(|err| match err {
+        ParserError::UnexpectedToken {
+            expected,
+            found: TokenKind::Ident(name),
+            ..
+        } if name == PLACEHOLDER => Some(expected),
+        _ => None,
+    });
+    if let Some(expected) = expected {
+        return Some(keywords(expected));
+    }
+
+    let mut finder = ContextFinder::default();
+    finder.visit_program(&program);
+    match finder.context? {
+        Context::Type => Some(keywords(TYPE_KEYWORDS)),
+        Context::Expr { statement, scope } => {
+            let mut items = Vec::new();
+            if statement {
+                items.extend(keywords(&[TokenKind::Let]));
+            }
+            items.extend(keywords(EXPR_KEYWORDS));
+            items.extend(["true", "false"].map(|word| keyword_item(word.to_string())));
+            items.extend(names_in_scope(document, &scope));
+            Some(items)
+        }
+    }
+}
+
+fn keywords(kinds: &[TokenKind]) -> Vec<CompletionItem> {
+    kinds
+        .iter()
+        .filter_map(TokenKind::keyword)
+        .map(|keyword| keyword_item(keyword.to_string()))
+        .collect()
+}
+
+fn keyword_item(label: String) -> CompletionItem {
+    CompletionItem {
+        label,
+        kind: Some(CompletionItemKind::KEYWORD),
+        ..CompletionItem::default()
+    }
+}
+
+/// The variables declared before the cursor in `scope`, innermost first,
+/// then the items of the document's module and the builtins they don't
+/// shadow.
+fn names_in_scope(document: &Document, scope: &[Spanned<Symbol>]) -> Vec<CompletionItem> {
+    let resolutions = &document.resolutions;
+    let program = &document.tree.module(document.tree.root()).program;
+    let locals = scope
+        .iter()
+        .rev()
+        .filter_map(|name| resolutions.def_at(document.file, name.span));
+    let items = program.items.iter().filter_map(|item| match &item.inner {
+        ItemKind::Func(func) => resolutions.def_at(document.file, func.name.span),
+        ItemKind::Mod(decl) => resolutions.def_at(document.file, decl.name.span),
+        ItemKind::Trait(decl) => resolutions.def_at(document.file, decl.name.span),
+        ItemKind::Use(decl) => resolutions.resolve(document.file, decl.name().span),
+        ItemKind::Impl(_) | ItemKind::StaticAssert(_) => None,
+    });
+    let builtins = resolutions
+        .defs()
+        .filter(|(_, def)| def.kind == DefKind::Builtin)
+        .map(|(id, _)| id);
+
+    let mut seen = HashSet::new();
+    locals
+        .chain(items)
+        .chain(builtins)
+        .filter(|&id| seen.insert(resolutions.def(id).name))
+        .map(|id| name_item(document, id))
+        .collect()
+}
+
+fn name_item(document: &Document, id: DefId) -> CompletionItem {
+    let def = document.resolutions.def(id);
+    let kind = match def.kind {
+        DefKind::Func | DefKind::Builtin | DefKind::Method => CompletionItemKind::FUNCTION,
+        DefKind::Mod => CompletionItemKind::MODULE,
+        DefKind::Trait => CompletionItemKind::INTERFACE,
+        DefKind::TypeParam => CompletionItemKind::TYPE_PARAMETER,
+        DefKind::Param | DefKind::Local | DefKind::Closure => CompletionItemKind::VARIABLE,
+    };
+    let detail = document
+        .types
+        .as_ref()
+        .and_then(|types| types.def_ty(id))
+        .map(ToString::to_string);
+    CompletionItem {
+        label: def.name.to_string(),
+        kind: Some(kind),
+        detail,
+        ..CompletionItem::default()
+    }
+}
+
+enum Context {
+    /// A type is written at the cursor.
+    Type,
+    /// An expression is, which may be a statement of a block, with the
+    /// variables declared before it in the order they are.
+    Expr {
+        statement: bool,
+        scope: Vec<Spanned<Symbol>>,
+    },
+}
+
+/// Finds the placeholder in a program, keeping track of the variables in
+/// scope as it goes.
+#[derive(Default)]
+struct ContextFinder {
+    scope: Vec<Spanned<Symbol>>,
+    /// Whether the expression visited next is a statement of a block.
+    statement: bool,
+    context: Option<Context>,
+}
+
+impl ContextFinder {
+    fn check_type(&mut self, ty: &Spanned<Type>) {
+        if contains_placeholder(ty) && self.context.is_none() {
+            self.context = Some(Context::Type);
+        }
+    }
+
+    fn check_params(&mut self, params: &[Spanned<Param>]) {
+        for param in params {
+            if let Param::Named { name, ty, .. } = &param.inner {
+                if let Some(ty) = ty {
+                    self.check_type(ty);
+                }
+                self.scope.push(name.clone());
+            }
+        }
+    }
+}
+
+fn contains_placeholder(ty: &Spanned<Type>) -> bool {
+    match &ty.inner {
+        Type::Param(name) => name == PLACEHOLDER,
+        Type::Fn { params, ret } => {
+            params.iter().any(contains_placeholder) || contains_placeholder(ret)
+        }
+        _ => false,
+    }
+}
+
+impl Visitor for ContextFinder {
+    fn visit_item(&mut self, item: &Item) {
+        if let ItemKind::Impl(decl) = &item.inner {
+            self.check_type(&decl.self_ty);
+        }
+        visit::walk_item(self, item);
+    }
+
+    fn visit_func(&mut self, func: &Func) {
+        let len = self.scope.len();
+        self.check_params(&func.params);
+        if let Some(ty) = &func.ty {
+            self.check_type(ty);
+        }
+        visit::walk_func(self, func);
+        self.scope.truncate(len);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        let statement = std::mem::take(&mut self.statement);
+        let len = self.scope.len();
+        match &expr.inner {
+            ExprKind::Var(name) if name == PLACEHOLDER => {
+                if self.context.is_none() {
+                    self.context = Some(Context::Expr {
+                        statement,
+                        scope: self.scope.clone(),
+                    });
+                }
+            }
+            ExprKind::Block { exprs, tail } => {
+                for expr in exprs.iter().chain(tail.as_deref()) {
+                    self.statement = true;
+                    self.visit_expr(expr);
+                    // a variable is in scope after its declaration
+                    if let ExprKind::Let { name, .. } = &expr.inner {
+                        self.scope.push(name.clone());
+                    }
+                }
+            }
+            ExprKind::Let { ty: Some(ty), .. } | ExprKind::Cast { ty, .. } => {
+                self.check_type(ty);
+                visit::walk_expr(self, expr);
+            }
+            ExprKind::Closure { params, body } => {
+                self.check_params(params);
+                self.visit_expr(body);
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.visit_expr(scrutinee);
+                for arm in arms {
+                    self.scope.extend(pattern_bindings(&arm.pattern));
+                    self.visit_expr(&arm.body);
+                    self.scope.truncate(len);
+                }
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+        self.scope.truncate(len);
+    }
+}
@@ -0,0 +1,133 @@
+use std::{fmt::Write, str::FromStr};
+
+use ginto_diag::{
+    BytePos, ColumnUnit, Diagnostic, FileId, LineColumn, Severity, SourceFile, SourceManager, Span,
+};
+use lsp_types::{
+    DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Position, Range,
+    Uri,
+};
+
+/// The name of the document at `uri` in the source manager: its path for
+/// a `file` URI, so that the modules it declares load from its directory,
+/// and the URI itself otherwise.
+pub fn uri_to_name(uri: &Uri) -> String {
+    let Some(path) = uri.as_str().strip_prefix("file://") else {
+        return uri.as_str().to_string();
+    };
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = match tail {
+            [high, low, ..] if byte == b'%' => std::str::from_utf8(&[*high, *low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// The URI of the file named `name` in the source manager, the inverse of
+/// `uri_to_name`.
+pub fn name_to_uri(name: &str) -> Option<Uri> {
+    if !name.starts_with('/') {
+        return Uri::from_str(name).ok();
+    }
+    let mut uri = String::from("file://");
+    for &byte in name.as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            write!(uri, "%{byte:02X}").expect("writing to a string can't fail");
+        }
+    }
+    Uri::from_str(&uri).ok()
+}
+
+/// The position of `pos` in `file`, counting columns in UTF-16 code units
+/// as clients do.
+pub fn to_position(file: &SourceFile, pos: BytePos) -> Position {
+    let LineColumn { line, col } = file.line_col_utf16(pos);
+    Position::new(line as u32 - 1, col as u32 - 1)
+}
+
+pub fn to_range(file: &SourceFile, span: Span) -> Range {
+    Range::new(to_position(file, span.start), to_position(file, span.end))
+}
+
+/// The offset in `file` of `position`, clamped to the end of its line.
+pub fn to_offset(file: &SourceFile, position: Position) -> BytePos {
+    let position = LineColumn {
+        line: position.line as usize + 1,
+        col: position.character as usize + 1,
+    };
+    file.position_to_byte_pos(position, ColumnUnit::Utf16)
+}
+
+/// Converts `diagnostic`, reported for the document `file`, to what the
+/// client shows: its primary label gives the range and the other labels
+/// the related information. The primary label's message, notes and help
+/// follow the message.
+pub fn to_lsp_diagnostic(
+    sm: &SourceManager,
+    file: FileId,
+    diagnostic: &Diagnostic,
+) -> lsp_types::Diagnostic {
+    let primary = diagnostic
+        .labels
+        .iter()
+        .find(|label| label.is_primary && label.file_id == file);
+    let range = primary
+        .and_then(|label| Some(to_range(sm.get_file(file)?, label.span)))
+        .unwrap_or_default();
+    let mut message = diagnostic.message.clone();
+    if let Some(label_message) = primary.and_then(|label| label.message.as_ref()) {
+        message.push_str(&format!("\n{label_message}"));
+    }
+    for note in &diagnostic.notes {
+        message.push_str(&format!("\nnote: {note}"));
+    }
+    if let Some(help) = &diagnostic.help {
+        message.push_str(&format!("\nhelp: {help}"));
+    }
+    let related: Vec<_> = diagnostic
+        .labels
+        .iter()
+        .filter(|label| !(label.is_primary && label.file_id == file))
+        .filter_map(|label| {
+            let label_file = sm.get_file(label.file_id)?;
+            Some(DiagnosticRelatedInformation {
+                location: Location::new(
+                    name_to_uri(&label_file.name)?,
+                    to_range(label_file, label.span),
+                ),
+                message: label.message.clone().unwrap_or_default(),
+            })
+        })
+        .collect();
+    lsp_types::Diagnostic {
+        range,
+        severity: Some(match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Note => DiagnosticSeverity::INFORMATION,
+            Severity::Help => DiagnosticSeverity::HINT,
+        }),
+        code: diagnostic.code.clone().map(NumberOrString::String),
+        source: Some("ginto".to_string()),
+        message,
+        related_information: (!related.is_empty()).then_some(related),
+        ..lsp_types::Diagnostic::default()
+    }
+}
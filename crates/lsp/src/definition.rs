@@ -0,0 +1,24 @@
+use ginto_resolve::BUILTIN_FILE;
+use lsp_types::{Location, Position, Uri};
+
+use crate::{Documents, name_to_uri, to_offset, to_range};
+
+/// Where the name at `position` in the document at `uri` is defined, which
+/// may be in the file of another module. Builtins have no definition to go
+/// to.
+pub fn definition(documents: &Documents, uri: &Uri, position: Position) -> Option<Location> {
+    let document = documents.get(uri)?;
+    let _session = document.enter();
+    let sm = documents.source_manager();
+    let pos = to_offset(sm.get_file(document.file)?, position);
+    let (_, def) = document.resolutions.name_at(document.file, pos)?;
+    let def = document.resolutions.def(def);
+    if def.file_id == BUILTIN_FILE {
+        return None;
+    }
+    let file = sm.get_file(def.file_id)?;
+    Some(Location::new(
+        name_to_uri(&file.name)?,
+        to_range(file, def.span),
+    ))
+}
@@ -0,0 +1,125 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use ginto_diag::{Diagnostic, Entered, FileId, Interner, SourceManager};
+use ginto_driver::Driver;
+use ginto_lint::{LintLevels, LintStore};
+use ginto_resolve::{ModuleTree, Resolutions};
+use ginto_typeck::TypeckResults;
+use lsp_types::Uri;
+
+use crate::uri_to_name;
+
+/// An open document and what the compiler found in its latest version.
+#[derive(Debug)]
+pub struct Document {
+    pub file: FileId,
+    pub version: i32,
+    /// The modules of the program the document is the root file of, parsed
+    /// with the parser recovering from errors.
+    pub tree: ModuleTree,
+    pub resolutions: Resolutions,
+    /// The types of the program, or `None` if it has names that don't
+    /// resolve.
+    pub types: Option<TypeckResults>,
+    /// What was found in the document itself, rather than in the files of
+    /// its modules.
+    pub diagnostics: Vec<Diagnostic>,
+    /// The names of the program, freed with this version of the document.
+    pub interner: Arc<Interner>,
+}
+
+impl Document {
+    /// Enters the session the document was analyzed in, which its names
+    /// are looked up in until the returned guard is dropped.
+    pub fn enter(&self) -> Entered {
+        self.interner.clone().enter()
+    }
+}
+
+/// The documents the client has open, each a file of the source manager
+/// named by its path, or its URI if it has none.
+#[derive(Debug, Default)]
+pub struct Documents {
+    sm: SourceManager,
+    documents: HashMap<String, Document>,
+}
+
+impl Documents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the text of the document at `uri`, opening it if it isn't, and
+    /// analyzes it again.
+    pub fn update(&mut self, uri: &Uri, version: i32, text: String) -> &Document {
+        let name = uri_to_name(uri);
+        let file = self.sm.set_file(name.clone(), text);
+        let document = self.analyze(file, version);
+        self.documents.insert(name.clone(), document);
+        &self.documents[&name]
+    }
+
+    /// Forgets the document at `uri`. Its file stays in the source manager,
+    /// where other documents' diagnostics may point.
+    pub fn close(&mut self, uri: &Uri) -> Option<Document> {
+        self.documents.remove(&uri_to_name(uri))
+    }
+
+    pub fn get(&self, uri: &Uri) -> Option<&Document> {
+        self.documents.get(&uri_to_name(uri))
+    }
+
+    pub fn source_manager(&self) -> &SourceManager {
+        &self.sm
+    }
+
+    /// Checks the program whose root file is `file` through the driver, as
+    /// `ginto check` does, going on past modules that fail to load. The
+    /// modules it declares are read from disk unless they are open.
+    fn analyze(&mut self, file: FileId, version: i32) -> Document {
+        let documents = &self.documents;
+        let mut read = |sm: &SourceManager, path: &Path| {
+            let name = path.display().to_string();
+            match sm.file_id(&name) {
+                Some(open) if documents.contains_key(&name) => Ok(sm
+                    .get_file(open)
+                    .expect("open files are added")
+                    .source
+                    .clone()),
+                _ => std::fs::read_to_string(path),
+            }
+        };
+        // each version gets a session of its own, so the names only it
+        // uses go away with it
+        let interner = Arc::new(Interner::new());
+        let _session = interner.clone().enter();
+        let mut lints = LintStore::with_builtin_lints();
+        let levels = LintLevels::new();
+        let checked = Driver::new()
+            .with_lints(&mut lints, &levels)
+            .with_reader(&mut read)
+            .past_load_errors()
+            .load(&mut self.sm, file);
+
+        let mut diagnostics = checked.diagnostics;
+        // what is found in other files is shown when they are open
+        diagnostics.retain(|diagnostic| {
+            diagnostic
+                .labels
+                .iter()
+                .find(|label| label.is_primary)
+                .is_none_or(|label| label.file_id == file)
+        });
+        Document {
+            file,
+            version,
+            tree: checked.tree,
+            resolutions: checked
+                .resolutions
+                .expect("names are resolved past load errors"),
+            types: checked.types,
+            diagnostics,
+            interner,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use ginto_diag::BytePos;
+use ginto_fmt::{format_source, text_edits};
+use lsp_types::{Range, TextEdit, Uri};
+
+use crate::{Documents, to_position};
+
+/// The edits formatting the document at `uri`, or only those touching
+/// `range` of it. Returns `None` if it has syntax errors.
+pub fn formatting(documents: &Documents, uri: &Uri, range: Option<Range>) -> Option<Vec<TextEdit>> {
+    let document = documents.get(uri)?;
+    let _session = document.enter();
+    let file = documents.source_manager().get_file(document.file)?;
+    let formatted = format_source(document.file, &file.source).ok()?;
+    let edits = text_edits(&file.source, &formatted)
+        .into_iter()
+        .map(|edit| {
+            let start = to_position(file, BytePos(edit.range.start));
+            let end = to_position(file, BytePos(edit.range.end));
+            TextEdit::new(Range::new(start, end), edit.new_text)
+        })
+        .filter(|edit| {
+            range.is_none_or(|range| edit.range.start <= range.end && range.start <= edit.range.end)
+        })
+        .collect();
+    Some(edits)
+}
@@ -0,0 +1,57 @@
+use ginto_resolve::DefKind;
+use ginto_syntax::doc_comment;
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position, Uri};
+
+use crate::{Documents, to_offset, to_range};
+
+/// What to show for the name or expression at `position` in the document
+/// at `uri`: the signature of a function, the type of a variable with what
+/// kind of variable it is, or the type of an expression. A function,
+/// method, trait or module shows its doc comment too.
+pub fn hover(documents: &Documents, uri: &Uri, position: Position) -> Option<Hover> {
+    let document = documents.get(uri)?;
+    let _session = document.enter();
+    let file = documents.source_manager().get_file(document.file)?;
+    let pos = to_offset(file, position);
+    let (span, value) = match document.resolutions.name_at(document.file, pos) {
+        Some((span, def_id)) => {
+            let def = document.resolutions.def(def_id);
+            let ty = document
+                .types
+                .as_ref()
+                .and_then(|types| types.def_ty(def_id));
+            let code = match (def.kind, ty) {
+                (DefKind::Func | DefKind::Method | DefKind::Builtin, Some(ty)) => {
+                    // the type is written `fn(..) -> ..`
+                    format!("fn {}{}", def.name, &ty.to_string()["fn".len()..])
+                }
+                (DefKind::Mod, _) => format!("mod {}", def.name),
+                (DefKind::Trait, _) => format!("trait {}", def.name),
+                (_, Some(ty)) => format!("{}: {ty}", def.name),
+                (_, None) => def.name.to_string(),
+            };
+            let mut value = format!("```ginto\n{code}\n```\n\n{}", def.kind.descr());
+            let source = documents.source_manager().get_file(def.file_id);
+            if matches!(
+                def.kind,
+                DefKind::Func | DefKind::Method | DefKind::Trait | DefKind::Mod
+            ) && let Some(doc) =
+                source.and_then(|file| doc_comment(&file.source, def.span.start))
+            {
+                value.push_str(&format!("\n\n---\n\n{doc}"));
+            }
+            (span, value)
+        }
+        None => {
+            let (span, ty) = document.types.as_ref()?.expr_at(document.file, pos)?;
+            (span, format!("```ginto\n{ty}\n```"))
+        }
+    };
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(to_range(file, span)),
+    })
+}
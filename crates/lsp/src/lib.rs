@@ -0,0 +1,21 @@
+mod completion;
+mod convert;
+mod definition;
+mod documents;
+mod formatting;
+mod hover;
+mod rename;
+mod semantic_tokens;
+mod server;
+mod symbols;
+
+pub use completion::*;
+pub use convert::*;
+pub use definition::*;
+pub use documents::*;
+pub use formatting::*;
+pub use hover::*;
+pub use rename::*;
+pub use semantic_tokens::*;
+pub use server::*;
+pub use symbols::*;
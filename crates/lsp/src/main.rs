@@ -0,0 +1,18 @@
+use std::process::ExitCode;
+
+use ginto_lsp::Server;
+use lsp_server::Connection;
+
+fn main() -> ExitCode {
+    // the client talks to the server over its standard input and output
+    let (connection, io_threads) = Connection::stdio();
+    let result = Server::initialize(&connection).and_then(Server::run);
+    drop(connection);
+    match result.and_then(|()| Ok(io_threads.join()?)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use ginto_diag::{BytePos, FileId, Span, sym};
+use ginto_resolve::{BUILTIN_FILE, Def, DefId, DefKind};
+use ginto_syntax::{Lexer, Token, TokenKind};
+use lsp_types::{Position, Range, TextEdit, Uri, WorkspaceEdit};
+
+use crate::{Document, Documents, name_to_uri, to_offset, to_range};
+
+/// Why a name can't be renamed, shown to the user as is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenameError {
+    NotRenamable {
+        name: String,
+        kind: DefKind,
+    },
+    InvalidName(String),
+    /// Another definition of the new name is in scope where the renamed
+    /// one is declared or used.
+    Conflict {
+        name: String,
+        kind: DefKind,
+    },
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::NotRenamable { name, kind } => {
+                write!(f, "the {} `{name}` can't be renamed", kind.descr())
+            }
+            RenameError::InvalidName(name) => write!(f, "`{name}` is not a valid name"),
+            RenameError::Conflict { name, kind } => {
+                write!(f, "renaming would clash with the {} `{name}`", kind.descr())
+            }
+        }
+    }
+}
+
+impl Error for RenameError {}
+
+/// The range of the name at `position` in the document at `uri`, if there
+/// is one and it can be renamed.
+pub fn prepare_rename(
+    documents: &Documents,
+    uri: &Uri,
+    position: Position,
+) -> Result<Option<Range>, RenameError> {
+    let Some(document) = documents.get(uri) else {
+        return Ok(None);
+    };
+    let _session = document.enter();
+    let Some(file) = documents.source_manager().get_file(document.file) else {
+        return Ok(None);
+    };
+    let name = renamable(document, to_offset(file, position))?;
+    Ok(name.map(|(span, _)| to_range(file, span)))
+}
+
+/// Renames the name at `position` in the document at `uri`, where it is
+/// declared and everywhere it is used, to `new_name`.
+// the edit is keyed by `Uri`, whose hash doesn't change
+#[allow(clippy::mutable_key_type)]
+pub fn rename(
+    documents: &Documents,
+    uri: &Uri,
+    position: Position,
+    new_name: &str,
+) -> Result<Option<WorkspaceEdit>, RenameError> {
+    let Some(document) = documents.get(uri) else {
+        return Ok(None);
+    };
+    let _session = document.enter();
+    let sm = documents.source_manager();
+    let Some(file) = sm.get_file(document.file) else {
+        return Ok(None);
+    };
+    let Some((_, id)) = renamable(document, to_offset(file, position))? else {
+        return Ok(None);
+    };
+    if !is_name(document.file, new_name) {
+        return Err(RenameError::InvalidName(new_name.to_string()));
+    }
+    let resolutions = &document.resolutions;
+    let sites = |id| {
+        resolutions
+            .def_spans_of(id)
+            .chain(resolutions.uses_of(id))
+            .collect::<Vec<_>>()
+    };
+    let renamed = sites(id);
+    let scopes = Scopes::new(document);
+    let is_item = |def: &Def| {
+        !matches!(
+            def.kind,
+            DefKind::Param | DefKind::Local | DefKind::TypeParam
+        )
+    };
+    let def = resolutions.def(id);
+    for (other, other_def) in resolutions.defs() {
+        if other == id
+            || other_def.name != new_name
+            || matches!(other_def.kind, DefKind::Method | DefKind::Closure)
+        {
+            continue;
+        }
+        let both_items = is_item(def) && is_item(other_def);
+        let clashes = sites(other).iter().any(|&other_site| {
+            renamed
+                .iter()
+                .any(|&site| scopes.overlap(site, other_site, both_items))
+        });
+        if clashes {
+            return Err(RenameError::Conflict {
+                name: other_def.name.to_string(),
+                kind: other_def.kind,
+            });
+        }
+    }
+
+    let mut changes: HashMap<Uri, Vec<TextEdit>> = HashMap::new();
+    for (file_id, span) in renamed {
+        let Some(file) = sm.get_file(file_id) else {
+            continue;
+        };
+        let Some(uri) = name_to_uri(&file.name) else {
+            continue;
+        };
+        let edit = TextEdit::new(to_range(file, span), new_name.to_string());
+        changes.entry(uri).or_default().push(edit);
+    }
+    for edits in changes.values_mut() {
+        edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+    }
+    Ok(Some(WorkspaceEdit::new(changes)))
+}
+
+/// The name at `pos`, with what it refers to, unless it is one the user
+/// can't rename: a builtin, a module, whose name is that of its file, a
+/// method, which calls find through the receiver's type, or `self`.
+fn renamable(document: &Document, pos: BytePos) -> Result<Option<(Span, DefId)>, RenameError> {
+    let Some((span, id)) = document.resolutions.name_at(document.file, pos) else {
+        return Ok(None);
+    };
+    let def = document.resolutions.def(id);
+    match def.kind {
+        DefKind::Func | DefKind::Param | DefKind::Local | DefKind::Trait | DefKind::TypeParam
+            if def.name != sym::SELF && def.file_id != BUILTIN_FILE =>
+        {
+            Ok(Some((span, id)))
+        }
+        kind => Err(RenameError::NotRenamable {
+            name: def.name.to_string(),
+            kind,
+        }),
+    }
+}
+
+fn is_name(file_id: FileId, name: &str) -> bool {
+    let Ok(tokens) = Lexer::new(file_id, name).lex_all() else {
+        return false;
+    };
+    let Some((first, rest)) = tokens.split_first() else {
+        return false;
+    };
+    let is_layout = |token: &Token| matches!(token.inner, TokenKind::Newline | TokenKind::Eof);
+    match &first.inner {
+        TokenKind::Ident(ident) => {
+            ![sym::SELF, sym::SELF_TYPE, sym::CRATE].contains(ident) && rest.iter().all(is_layout)
+        }
+        _ => false,
+    }
+}
+
+/// The spans of the items of every file of a document's program.
+struct Scopes {
+    items: HashMap<FileId, Vec<Span>>,
+}
+
+impl Scopes {
+    fn new(document: &Document) -> Self {
+        let items = document
+            .tree
+            .modules()
+            .map(|(_, module)| {
+                let program = &module.program;
+                let spans = program.items.iter().map(|item| item.span).collect();
+                (program.file_id, spans)
+            })
+            .collect();
+        Self { items }
+    }
+
+    fn item_at(&self, (file_id, span): (FileId, Span)) -> Option<Span> {
+        self.items
+            .get(&file_id)?
+            .iter()
+            .find(|item| item.start <= span.start && span.start < item.end)
+            .copied()
+    }
+
+    /// Whether names at `a` and `b` see each other: items do within a
+    /// file, and other names within an item.
+    fn overlap(&self, a: (FileId, Span), b: (FileId, Span), both_items: bool) -> bool {
+        a.0 == b.0
+            && (both_items
+                || self
+                    .item_at(a)
+                    .is_some_and(|item| Some(item) == self.item_at(b)))
+    }
+}
@@ -0,0 +1,179 @@
+use ginto_diag::sym;
+use ginto_resolve::DefKind;
+use ginto_syntax::{Lexer, TokenKind};
+use lsp_types::{
+    Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensEdit,
+    SemanticTokensLegend, Uri,
+};
+
+use crate::{Documents, to_position};
+
+/// The kinds of tokens, indexed by the `token_type` of each token.
+pub const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::STRING,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::INTERFACE,
+    SemanticTokenType::TYPE_PARAMETER,
+];
+
+/// The modifiers of tokens, each a bit of `token_modifiers_bitset`.
+pub const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[SemanticTokenModifier::DECLARATION];
+
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// Classifies the tokens of the document at `uri`, or of those within
+/// `range` of it. Names are classified by what they resolve to, so that
+/// parameters, variables and functions look different.
+pub fn semantic_tokens(
+    documents: &Documents,
+    uri: &Uri,
+    range: Option<Range>,
+) -> Option<Vec<SemanticToken>> {
+    let document = documents.get(uri)?;
+    let _session = document.enter();
+    let file = documents.source_manager().get_file(document.file)?;
+    // the text is classified as it was analyzed, which fails to lex as a
+    // whole while a string is left open
+    let tokens = Lexer::new(document.file, &file.source).lex_all().ok()?;
+    let resolutions = &document.resolutions;
+
+    let mut result = Vec::new();
+    let mut prev = (0, 0);
+    for token in tokens {
+        let (token_type, declaration) = match &token.inner {
+            TokenKind::Ident(name) => {
+                let def = resolutions.def_at(document.file, token.span);
+                match def.or_else(|| resolutions.resolve(document.file, token.span)) {
+                    Some(id) => (name_type(resolutions.def(id).kind), def.is_some()),
+                    None => match *name {
+                        sym::SELF => (SemanticTokenType::KEYWORD, false),
+                        sym::SELF_TYPE => (SemanticTokenType::TYPE, false),
+                        sym::CRATE => (SemanticTokenType::NAMESPACE, false),
+                        _ => continue,
+                    },
+                }
+            }
+            TokenKind::IntLiteral(_) | TokenKind::FloatLiteral(_) => {
+                (SemanticTokenType::NUMBER, false)
+            }
+            TokenKind::StrLiteral(_) => (SemanticTokenType::STRING, false),
+            TokenKind::BoolLiteral(_)
+            | TokenKind::Let
+            | TokenKind::Mut
+            | TokenKind::Mod
+            | TokenKind::Use
+            | TokenKind::Not
+            | TokenKind::Fn
+            | TokenKind::Pub
+            | TokenKind::Match
+            | TokenKind::Return
+            | TokenKind::If
+            | TokenKind::Else
+            | TokenKind::As
+            | TokenKind::StaticAssert
+            | TokenKind::Trait
+            | TokenKind::Impl
+            | TokenKind::For
+            | TokenKind::While => (SemanticTokenType::KEYWORD, false),
+            TokenKind::U64 | TokenKind::I64 | TokenKind::F64 | TokenKind::Bool | TokenKind::Str => {
+                (SemanticTokenType::TYPE, false)
+            }
+            TokenKind::Plus
+            | TokenKind::Minus
+            | TokenKind::Star
+            | TokenKind::Slash
+            | TokenKind::Equal
+            | TokenKind::NotEqual
+            | TokenKind::Less
+            | TokenKind::LessEqual
+            | TokenKind::Greater
+            | TokenKind::GreaterEqual
+            | TokenKind::And
+            | TokenKind::Or
+            | TokenKind::Pipe
+            | TokenKind::Arrow => (SemanticTokenType::OPERATOR, false),
+            _ => continue,
+        };
+        let start = to_position(file, token.span.start);
+        let end = to_position(file, token.span.end);
+        if let Some(range) = range
+            && (end <= range.start || range.end <= start)
+        {
+            continue;
+        }
+        // tokens don't span lines
+        let delta_line = start.line - prev.0;
+        let delta_start = if delta_line == 0 {
+            start.character - prev.1
+        } else {
+            start.character
+        };
+        result.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: end.character - start.character,
+            token_type: TOKEN_TYPES
+                .iter()
+                .position(|ty| *ty == token_type)
+                .expect("every token type is in the legend") as u32,
+            token_modifiers_bitset: declaration as u32,
+        });
+        prev = (start.line, start.character);
+    }
+    Some(result)
+}
+
+fn name_type(kind: DefKind) -> SemanticTokenType {
+    match kind {
+        DefKind::Param => SemanticTokenType::PARAMETER,
+        DefKind::Local | DefKind::Closure => SemanticTokenType::VARIABLE,
+        DefKind::Func | DefKind::Builtin => SemanticTokenType::FUNCTION,
+        DefKind::Method => SemanticTokenType::METHOD,
+        DefKind::Mod => SemanticTokenType::NAMESPACE,
+        DefKind::Trait => SemanticTokenType::INTERFACE,
+        DefKind::TypeParam => SemanticTokenType::TYPE_PARAMETER,
+    }
+}
+
+/// The edit turning the tokens `old` into `new`, replacing what differs
+/// between their common start and end. Edits count the integers tokens are
+/// sent as, five per token.
+pub fn semantic_tokens_delta(
+    old: &[SemanticToken],
+    new: &[SemanticToken],
+) -> Vec<SemanticTokensEdit> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let deleted = old.len() - prefix - suffix;
+    let inserted = &new[prefix..new.len() - suffix];
+    if deleted == 0 && inserted.is_empty() {
+        return Vec::new();
+    }
+    vec![SemanticTokensEdit {
+        start: 5 * prefix as u32,
+        delete_count: 5 * deleted as u32,
+        data: (!inserted.is_empty()).then(|| inserted.to_vec()),
+    }]
+}
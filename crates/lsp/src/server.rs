@@ -0,0 +1,505 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    time::{Duration, Instant},
+};
+
+use lsp_server::{
+    Connection, ErrorCode, ExtractError, Message, Notification, Request, RequestId, Response,
+};
+use lsp_types::{
+    CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
+    DocumentFormattingParams, DocumentRangeFormattingParams, DocumentSymbolParams,
+    DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse, HoverParams,
+    HoverProviderCapability, LogMessageParams, MessageType, OneOf, PrepareRenameResponse,
+    PublishDiagnosticsParams, RenameOptions, RenameParams, SaveOptions, SemanticToken,
+    SemanticTokens, SemanticTokensDelta, SemanticTokensDeltaParams, SemanticTokensFullDeltaResult,
+    SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensParams,
+    SemanticTokensRangeParams, SemanticTokensRangeResult, SemanticTokensResult,
+    SemanticTokensServerCapabilities, ServerCapabilities, TextDocumentPositionParams,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
+    TextDocumentSyncSaveOptions, Uri,
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        LogMessage, Notification as _, PublishDiagnostics,
+    },
+    request::{
+        Completion, DocumentSymbolRequest, Formatting, GotoDefinition, HoverRequest,
+        PrepareRenameRequest, RangeFormatting, Rename, Request as _,
+        SemanticTokensFullDeltaRequest, SemanticTokensFullRequest, SemanticTokensRangeRequest,
+    },
+};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    Documents, completion, definition, document_symbols, formatting, hover, legend, prepare_rename,
+    rename, semantic_tokens, semantic_tokens_delta, to_lsp_diagnostic,
+};
+
+pub type ServerResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+/// How long edits must pause before a changed document is analyzed again.
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// A language server talking to its client over a connection.
+pub struct Server<'a> {
+    connection: &'a Connection,
+    documents: Documents,
+    /// Changes not analyzed yet, by URI, until `deadline`.
+    pending: HashMap<String, PendingChange>,
+    deadline: Option<Instant>,
+    /// The semantic tokens last sent for each document with their result
+    /// id, which later requests ask for the changes since.
+    sent_tokens: HashMap<String, (String, Vec<SemanticToken>)>,
+    next_result_id: u64,
+}
+
+struct PendingChange {
+    uri: Uri,
+    version: i32,
+    text: String,
+}
+
+impl<'a> Server<'a> {
+    /// Answers the client's `initialize` request on `connection`.
+    pub fn initialize(connection: &'a Connection) -> ServerResult<Self> {
+        connection.initialize(serde_json::to_value(capabilities())?)?;
+        Ok(Self {
+            connection,
+            documents: Documents::new(),
+            pending: HashMap::new(),
+            deadline: None,
+            sent_tokens: HashMap::new(),
+            next_result_id: 0,
+        })
+    }
+
+    /// Handles messages until the client shuts the server down.
+    pub fn run(mut self) -> ServerResult<()> {
+        loop {
+            let message = match self.deadline {
+                Some(deadline) => match self.connection.receiver.recv_deadline(deadline) {
+                    Ok(message) => message,
+                    Err(err) if err.is_timeout() => {
+                        self.flush()?;
+                        continue;
+                    }
+                    Err(_) => return Ok(()),
+                },
+                None => match self.connection.receiver.recv() {
+                    Ok(message) => message,
+                    Err(_) => return Ok(()),
+                },
+            };
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+    }
+
+    fn handle_request(&mut self, request: Request) -> ServerResult<()> {
+        let response = match request.method.as_str() {
+            GotoDefinition::METHOD => {
+                let Some((id, params)) =
+                    self.request_params::<GotoDefinitionParams>(request, GotoDefinition::METHOD)?
+                else {
+                    return Ok(());
+                };
+                let params = params.text_document_position_params;
+                self.analyze_pending(&params.text_document.uri)?;
+                let location =
+                    definition(&self.documents, &params.text_document.uri, params.position);
+                Response::new_ok(id, location.map(GotoDefinitionResponse::Scalar))
+            }
+            HoverRequest::METHOD => {
+                let Some((id, params)) =
+                    self.request_params::<HoverParams>(request, HoverRequest::METHOD)?
+                else {
+                    return Ok(());
+                };
+                let params = params.text_document_position_params;
+                self.analyze_pending(&params.text_document.uri)?;
+                Response::new_ok(
+                    id,
+                    hover(&self.documents, &params.text_document.uri, params.position),
+                )
+            }
+            Completion::METHOD => {
+                let Some((id, params)) =
+                    self.request_params::<CompletionParams>(request, Completion::METHOD)?
+                else {
+                    return Ok(());
+                };
+                let params = params.text_document_position;
+                self.analyze_pending(&params.text_document.uri)?;
+                let items = completion(&self.documents, &params.text_document.uri, params.position);
+                Response::new_ok(id, items.map(CompletionResponse::Array))
+            }
+            DocumentSymbolRequest::METHOD => {
+                let Some((id, params)) = self.request_params::<DocumentSymbolParams>(
+                    request,
+                    DocumentSymbolRequest::METHOD,
+                )?
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.analyze_pending(&uri)?;
+                let symbols = document_symbols(&self.documents, &uri);
+                Response::new_ok(id, symbols.map(DocumentSymbolResponse::Nested))
+            }
+            PrepareRenameRequest::METHOD => {
+                let Some((id, params)) = self.request_params::<TextDocumentPositionParams>(
+                    request,
+                    PrepareRenameRequest::METHOD,
+                )?
+                else {
+                    return Ok(());
+                };
+                self.analyze_pending(&params.text_document.uri)?;
+                match prepare_rename(&self.documents, &params.text_document.uri, params.position) {
+                    Ok(range) => Response::new_ok(id, range.map(PrepareRenameResponse::Range)),
+                    Err(err) => {
+                        Response::new_err(id, ErrorCode::RequestFailed as i32, err.to_string())
+                    }
+                }
+            }
+            Rename::METHOD => {
+                let Some((id, params)) =
+                    self.request_params::<RenameParams>(request, Rename::METHOD)?
+                else {
+                    return Ok(());
+                };
+                let position = params.text_document_position;
+                self.analyze_pending(&position.text_document.uri)?;
+                let uri = &position.text_document.uri;
+                match rename(&self.documents, uri, position.position, &params.new_name) {
+                    Ok(edit) => Response::new_ok(id, edit),
+                    Err(err) => {
+                        Response::new_err(id, ErrorCode::RequestFailed as i32, err.to_string())
+                    }
+                }
+            }
+            SemanticTokensFullRequest::METHOD => {
+                let Some((id, params)) = self.request_params::<SemanticTokensParams>(
+                    request,
+                    SemanticTokensFullRequest::METHOD,
+                )?
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.analyze_pending(&uri)?;
+                let tokens = semantic_tokens(&self.documents, &uri, None)
+                    .map(|data| SemanticTokensResult::Tokens(self.send_tokens(&uri, data)));
+                Response::new_ok(id, tokens)
+            }
+            SemanticTokensFullDeltaRequest::METHOD => {
+                let Some((id, params)) = self.request_params::<SemanticTokensDeltaParams>(
+                    request,
+                    SemanticTokensFullDeltaRequest::METHOD,
+                )?
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.analyze_pending(&uri)?;
+                let tokens = semantic_tokens(&self.documents, &uri, None).map(|data| {
+                    match self.sent_tokens.get(uri.as_str()) {
+                        Some((result_id, old)) if *result_id == params.previous_result_id => {
+                            let edits = semantic_tokens_delta(old, &data);
+                            let result_id = self.send_tokens(&uri, data).result_id;
+                            SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+                                result_id,
+                                edits,
+                            })
+                        }
+                        _ => SemanticTokensFullDeltaResult::Tokens(self.send_tokens(&uri, data)),
+                    }
+                });
+                Response::new_ok(id, tokens)
+            }
+            SemanticTokensRangeRequest::METHOD => {
+                let Some((id, params)) = self.request_params::<SemanticTokensRangeParams>(
+                    request,
+                    SemanticTokensRangeRequest::METHOD,
+                )?
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.analyze_pending(&uri)?;
+                let tokens =
+                    semantic_tokens(&self.documents, &uri, Some(params.range)).map(|data| {
+                        SemanticTokensRangeResult::Tokens(SemanticTokens {
+                            result_id: None,
+                            data,
+                        })
+                    });
+                Response::new_ok(id, tokens)
+            }
+            Formatting::METHOD => {
+                let Some((id, params)) =
+                    self.request_params::<DocumentFormattingParams>(request, Formatting::METHOD)?
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.analyze_pending(&uri)?;
+                Response::new_ok(id, formatting(&self.documents, &uri, None))
+            }
+            RangeFormatting::METHOD => {
+                let Some((id, params)) = self.request_params::<DocumentRangeFormattingParams>(
+                    request,
+                    RangeFormatting::METHOD,
+                )?
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.analyze_pending(&uri)?;
+                Response::new_ok(id, formatting(&self.documents, &uri, Some(params.range)))
+            }
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unhandled request `{}`", request.method),
+            ),
+        };
+        self.connection.sender.send(response.into())?;
+        Ok(())
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> ServerResult<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = self.notification_params::<DidOpenTextDocumentParams>(
+                    notification,
+                    DidOpenTextDocument::METHOD,
+                )?
+                else {
+                    return Ok(());
+                };
+                let document = params.text_document;
+                self.pending.remove(document.uri.as_str());
+                self.documents
+                    .update(&document.uri, document.version, document.text);
+                self.publish(&document.uri)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) = self.notification_params::<DidChangeTextDocumentParams>(
+                    notification,
+                    DidChangeTextDocument::METHOD,
+                )?
+                else {
+                    return Ok(());
+                };
+                // the whole text is sent on every change
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    let document = params.text_document;
+                    self.pending.insert(
+                        document.uri.as_str().to_string(),
+                        PendingChange {
+                            uri: document.uri,
+                            version: document.version,
+                            text: change.text,
+                        },
+                    );
+                    self.deadline = Some(Instant::now() + DEBOUNCE);
+                }
+            }
+            DidSaveTextDocument::METHOD => {
+                let Some(params) = self.notification_params::<DidSaveTextDocumentParams>(
+                    notification,
+                    DidSaveTextDocument::METHOD,
+                )?
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                if !self.analyze_pending(&uri)? {
+                    self.publish(&uri)?;
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = self.notification_params::<DidCloseTextDocumentParams>(
+                    notification,
+                    DidCloseTextDocument::METHOD,
+                )?
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.pending.remove(uri.as_str());
+                self.sent_tokens.remove(uri.as_str());
+                // the client keeps what was published until it is replaced
+                if self.documents.close(&uri).is_some() {
+                    self.send_diagnostics(uri, Vec::new(), None)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The id and parameters of `request`, or `None` once it is answered
+    /// with an error because its parameters are malformed.
+    fn request_params<P: DeserializeOwned>(
+        &self,
+        request: Request,
+        method: &str,
+    ) -> ServerResult<Option<(RequestId, P)>> {
+        let id = request.id.clone();
+        match request.extract(method) {
+            Ok(extracted) => Ok(Some(extracted)),
+            Err(err) => {
+                let message = invalid_params(err);
+                let response = Response::new_err(id, ErrorCode::InvalidParams as i32, message);
+                self.connection.sender.send(response.into())?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// The parameters of `notification`, or `None` if they are malformed.
+    /// Nothing answers a notification, so the error goes to the client's
+    /// log.
+    fn notification_params<P: DeserializeOwned>(
+        &self,
+        notification: Notification,
+        method: &str,
+    ) -> ServerResult<Option<P>> {
+        match notification.extract(method) {
+            Ok(params) => Ok(Some(params)),
+            Err(err) => {
+                let params = LogMessageParams {
+                    typ: MessageType::ERROR,
+                    message: format!("`{}`: {}", method, invalid_params(err)),
+                };
+                let notification = Notification::new(LogMessage::METHOD.to_string(), params);
+                self.connection.sender.send(notification.into())?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Analyzes the documents changed since the last time and publishes
+    /// their diagnostics.
+    fn flush(&mut self) -> ServerResult<()> {
+        self.deadline = None;
+        for (_, change) in std::mem::take(&mut self.pending) {
+            self.documents
+                .update(&change.uri, change.version, change.text);
+            self.publish(&change.uri)?;
+        }
+        Ok(())
+    }
+
+    /// Analyzes the document at `uri` now if it has changed, so a request
+    /// sees its latest text. Returns whether it had.
+    fn analyze_pending(&mut self, uri: &Uri) -> ServerResult<bool> {
+        let Some(change) = self.pending.remove(uri.as_str()) else {
+            return Ok(false);
+        };
+        self.documents.update(uri, change.version, change.text);
+        self.publish(uri)?;
+        Ok(true)
+    }
+
+    /// Remembers `data` as the semantic tokens sent for the document at
+    /// `uri` under a new result id.
+    fn send_tokens(&mut self, uri: &Uri, data: Vec<SemanticToken>) -> SemanticTokens {
+        self.next_result_id += 1;
+        let result_id = self.next_result_id.to_string();
+        self.sent_tokens
+            .insert(uri.as_str().to_string(), (result_id.clone(), data.clone()));
+        SemanticTokens {
+            result_id: Some(result_id),
+            data,
+        }
+    }
+
+    /// Publishes the diagnostics of the open document at `uri`.
+    fn publish(&self, uri: &Uri) -> ServerResult<()> {
+        let Some(document) = self.documents.get(uri) else {
+            return Ok(());
+        };
+        let sm = self.documents.source_manager();
+        let diagnostics = document
+            .diagnostics
+            .iter()
+            .map(|diagnostic| to_lsp_diagnostic(sm, document.file, diagnostic))
+            .collect();
+        self.send_diagnostics(uri.clone(), diagnostics, Some(document.version))
+    }
+
+    fn send_diagnostics(
+        &self,
+        uri: Uri,
+        diagnostics: Vec<lsp_types::Diagnostic>,
+        version: Option<i32>,
+    ) -> ServerResult<()> {
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version,
+        };
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+}
+
+/// Why the parameters of a message could not be read.
+fn invalid_params<T>(err: ExtractError<T>) -> String
+where
+    ExtractError<T>: Error,
+{
+    match err {
+        ExtractError::JsonError { error, .. } => format!("invalid parameters: {}", error),
+        err => err.to_string(),
+    }
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::FULL),
+                save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                    include_text: Some(false),
+                })),
+                ..TextDocumentSyncOptions::default()
+            },
+        )),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: Default::default(),
+        })),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                legend: legend(),
+                range: Some(true),
+                full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                ..SemanticTokensOptions::default()
+            },
+        )),
+        ..ServerCapabilities::default()
+    }
+}
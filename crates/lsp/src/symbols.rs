@@ -0,0 +1,97 @@
+use ginto_diag::{SourceFile, Span, Spanned};
+use ginto_syntax::{ExprKind, Func, ItemKind};
+use lsp_types::{DocumentSymbol, SymbolKind, Uri};
+
+use crate::{Document, Documents, to_range};
+
+/// The outline of the document at `uri`: its modules, traits, `impl`
+/// blocks and functions, with the methods of traits and `impl` blocks and
+/// the variables a function declares at the top of its body nested in them.
+pub fn document_symbols(documents: &Documents, uri: &Uri) -> Option<Vec<DocumentSymbol>> {
+    let document = documents.get(uri)?;
+    let _session = document.enter();
+    let file = documents.source_manager().get_file(document.file)?;
+    let outline = Outline { document, file };
+    let program = &document.tree.module(document.tree.root()).program;
+    let symbols = program
+        .items
+        .iter()
+        .filter_map(|item| match &item.inner {
+            ItemKind::Func(func) => Some(outline.func(func, item.span, SymbolKind::FUNCTION)),
+            ItemKind::Mod(decl) => Some(outline.symbol(&decl.name, item.span, SymbolKind::MODULE)),
+            ItemKind::Trait(decl) => {
+                let mut symbol = outline.symbol(&decl.name, item.span, SymbolKind::INTERFACE);
+                let methods = decl.methods.iter().map(|method| {
+                    outline.symbol(&method.inner.name, method.span, SymbolKind::METHOD)
+                });
+                symbol.children = Some(methods.collect());
+                Some(symbol)
+            }
+            ItemKind::Impl(decl) => {
+                let name = format!("impl {} for {}", decl.trait_name.inner, decl.self_ty.inner);
+                let name = Spanned::new(name, decl.trait_name.span.merge(decl.self_ty.span));
+                let mut symbol = outline.symbol(&name, item.span, SymbolKind::OBJECT);
+                symbol.detail = None;
+                let methods = decl.methods.iter().map(|method| {
+                    let span = method.name.span.merge(method.body.span);
+                    outline.func(method, span, SymbolKind::METHOD)
+                });
+                symbol.children = Some(methods.collect());
+                Some(symbol)
+            }
+            ItemKind::Use(_) | ItemKind::StaticAssert(_) => None,
+        })
+        .collect();
+    Some(symbols)
+}
+
+struct Outline<'a> {
+    document: &'a Document,
+    file: &'a SourceFile,
+}
+
+impl Outline<'_> {
+    fn func(&self, func: &Func, span: Span, kind: SymbolKind) -> DocumentSymbol {
+        let mut symbol = self.symbol(&func.name, span, kind);
+        let stmts = match &func.body.inner {
+            ExprKind::Block { exprs, .. } => exprs.as_slice(),
+            _ => std::slice::from_ref(&func.body),
+        };
+        let locals = stmts.iter().filter_map(|stmt| match &stmt.inner {
+            ExprKind::Let { name, .. } => Some(self.symbol(name, stmt.span, SymbolKind::VARIABLE)),
+            _ => None,
+        });
+        symbol.children = Some(locals.collect());
+        symbol
+    }
+
+    /// The symbol for the item or variable declared at `span` and named
+    /// `name`, its type being the detail if it has one.
+    fn symbol(
+        &self,
+        name: &Spanned<impl ToString>,
+        span: Span,
+        kind: SymbolKind,
+    ) -> DocumentSymbol {
+        let detail = self
+            .document
+            .resolutions
+            .def_at(self.document.file, name.span)
+            .zip(self.document.types.as_ref())
+            .and_then(|(def, types)| types.def_ty(def))
+            .map(ToString::to_string);
+        // the selection must lie within the range
+        let span = span.merge(name.span);
+        #[allow(deprecated)]
+        DocumentSymbol {
+            name: name.inner.to_string(),
+            detail,
+            kind,
+            tags: None,
+            deprecated: None,
+            range: to_range(self.file, span),
+            selection_range: to_range(self.file, name.span),
+            children: None,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::str::FromStr;
+
+use ginto_lsp::{Documents, completion};
+use lsp_types::{CompletionItemKind, Position, Uri};
+
+fn labels(source: &str, line: u32, character: u32) -> Vec<String> {
+    let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+    let mut documents = Documents::new();
+    documents.update(&uri, 1, source.to_string());
+    completion(&documents, &uri, Position::new(line, character))
+        .unwrap()
+        .into_iter()
+        .map(|item| item.label)
+        .collect()
+}
+
+#[test]
+fn completes_keywords_the_parser_expects() {
+    assert_eq!(
+        labels("fn main():\n    ()\n\nst\n", 3, 2),
+        ["fn", "mod", "use", "trait", "impl", "static_assert"]
+    );
+    assert_eq!(
+        labels("fn f(x: u) -> u64:\n    1\n", 0, 9),
+        ["u64", "i64", "f64", "bool", "str"]
+    );
+}
+
+#[test]
+fn completes_names_in_scope_at_the_cursor() {
+    let source = "fn add(a: u64, b: u64) -> u64:\n    a + b\n\n\
+                  fn main() -> u64:\n    let x = 1\n    if true:\n        let y = 2\n        y\n    \
+                  let z = x\n    z\n";
+    let value = labels(source, 8, 13);
+    assert_eq!(
+        value[..7],
+        ["if", "match", "while", "return", "not", "true", "false"]
+    );
+    let names = &value[7..];
+    assert_eq!(names[..3], ["x", "add", "main"]);
+    assert!(names.contains(&"print".to_string()));
+    assert!(!names.contains(&"y".to_string()));
+    assert!(!names.contains(&"z".to_string()));
+
+    let statement = labels(source, 9, 5);
+    assert_eq!(statement[0], "let");
+    assert_eq!(statement[8..10], ["z", "x"]);
+
+    let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+    let mut documents = Documents::new();
+    documents.update(
+        &uri,
+        1,
+        "fn main(count: u64) -> u64:\n    1 + count\n".to_string(),
+    );
+    let items = completion(&documents, &uri, Position::new(1, 9)).unwrap();
+    let count = items.iter().find(|item| item.label == "count").unwrap();
+    assert_eq!(count.kind, Some(CompletionItemKind::VARIABLE));
+    assert_eq!(count.detail.as_deref(), Some("u64"));
+    assert!(items.iter().all(|item| item.label != "let"));
+}
@@ -0,0 +1,41 @@
+use ginto_diag::{BytePos, Diagnostic, FileId, Label, SourceManager, Span};
+use ginto_lsp::{to_lsp_diagnostic, to_offset, to_position};
+use lsp_types::{DiagnosticSeverity, NumberOrString, Position, Range};
+
+#[test]
+fn counts_columns_in_utf16_code_units() {
+    let mut sm = SourceManager::new();
+    let file = sm.add_file("file:///a.ginto".to_string(), "\"é😀\" x\ny\n".to_string());
+    let file = sm.get_file(file).unwrap();
+    // `x` is after 1 + 2 + 4 + 1 + 1 bytes, and 1 + 1 + 2 + 1 + 1 units
+    assert_eq!(to_position(file, BytePos(9)), Position::new(0, 6));
+    assert_eq!(to_position(file, BytePos(11)), Position::new(1, 0));
+    assert_eq!(to_offset(file, Position::new(0, 6)), BytePos(9));
+    assert_eq!(to_offset(file, Position::new(0, 99)), BytePos(10));
+    assert_eq!(to_offset(file, Position::new(9, 0)), BytePos(13));
+}
+
+#[test]
+fn converts_labels_notes_and_help() {
+    let mut sm = SourceManager::new();
+    let file = sm.add_file("file:///a.ginto".to_string(), "let x = y\n".to_string());
+    let diagnostic = Diagnostic::warning("unused variable")
+        .with_code("W0004")
+        .with_label(Label::primary(file, Span::from_range(4..5)).with_message("never used"))
+        .with_label(Label::secondary(file, Span::from_range(8..9)).with_message("here"))
+        .with_note("on by default");
+    let converted = to_lsp_diagnostic(&sm, FileId(0), &diagnostic);
+    assert_eq!(
+        converted.range,
+        Range::new(Position::new(0, 4), Position::new(0, 5))
+    );
+    assert_eq!(converted.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(converted.code, Some(NumberOrString::String("W0004".into())));
+    assert_eq!(
+        converted.message,
+        "unused variable\nnever used\nnote: on by default"
+    );
+    let related = converted.related_information.unwrap();
+    assert_eq!(related[0].location.range.start, Position::new(0, 8));
+    assert_eq!(related[0].message, "here");
+}
@@ -0,0 +1,71 @@
+use std::{fs, str::FromStr};
+
+use ginto_lsp::{Documents, definition, name_to_uri, uri_to_name};
+use lsp_types::{Position, Range, Uri};
+
+#[test]
+fn maps_file_uris_to_paths() {
+    let uri = Uri::from_str("file:///src/my%20dir/main.ginto").unwrap();
+    assert_eq!(uri_to_name(&uri), "/src/my dir/main.ginto");
+    assert_eq!(name_to_uri("/src/my dir/main.ginto"), Some(uri));
+    let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+    assert_eq!(uri_to_name(&uri), "untitled:Untitled-1");
+}
+
+#[test]
+fn goes_to_definitions_across_files() {
+    let dir = std::env::temp_dir().join(format!("ginto-lsp-definition-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("util.ginto"),
+        "pub fn helper(n: u64) -> u64:\n    n\n",
+    )
+    .unwrap();
+    let main = dir.join("main.ginto");
+    let uri = name_to_uri(main.to_str().unwrap()).unwrap();
+    let source = "mod util\nuse util::helper\n\nfn main() -> u64:\n    let x = helper(1)\n    x\n";
+
+    let mut documents = Documents::new();
+    let document = documents.update(&uri, 1, source.to_string());
+    assert!(
+        document.diagnostics.is_empty(),
+        "{:?}",
+        document.diagnostics
+    );
+
+    // a variable, from the end of its name
+    let location = definition(&documents, &uri, Position::new(5, 5)).unwrap();
+    assert_eq!(location.uri, uri);
+    assert_eq!(
+        location.range,
+        Range::new(Position::new(4, 8), Position::new(4, 9))
+    );
+
+    // a function in the file of another module
+    let location = definition(&documents, &uri, Position::new(4, 14)).unwrap();
+    assert_eq!(
+        location.uri,
+        name_to_uri(dir.join("util.ginto").to_str().unwrap()).unwrap()
+    );
+    assert_eq!(
+        location.range,
+        Range::new(Position::new(0, 7), Position::new(0, 13))
+    );
+
+    // a parameter, from its use in the other module's file once it is open
+    let util = name_to_uri(dir.join("util.ginto").to_str().unwrap()).unwrap();
+    documents.update(
+        &util,
+        1,
+        "pub fn helper(n: u64) -> u64:\n    n\n".to_string(),
+    );
+    let location = definition(&documents, &util, Position::new(1, 4)).unwrap();
+    assert_eq!(
+        location.range,
+        Range::new(Position::new(0, 14), Position::new(0, 15))
+    );
+
+    // keywords and builtins have none
+    assert!(definition(&documents, &uri, Position::new(3, 1)).is_none());
+    fs::remove_dir_all(dir).unwrap();
+}
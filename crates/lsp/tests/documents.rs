@@ -0,0 +1,36 @@
+use std::{str::FromStr, sync::Arc};
+
+use ginto_diag::Interner;
+use ginto_lsp::{Documents, hover};
+use lsp_types::{HoverContents, Position, Uri};
+
+#[test]
+fn frees_the_names_of_old_versions() {
+    let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+    let mut documents = Documents::new();
+    documents.update(
+        &uri,
+        1,
+        "fn main() -> u64:\n    let first = 1\n    first\n".to_string(),
+    );
+    let first = Arc::downgrade(&documents.get(&uri).unwrap().interner);
+
+    documents.update(
+        &uri,
+        2,
+        "fn main() -> u64:\n    let second = 2\n    second\n".to_string(),
+    );
+    assert!(first.upgrade().is_none());
+    let document = documents.get(&uri).unwrap();
+    assert!(!Arc::ptr_eq(&document.interner, &Interner::current()));
+
+    // features look names up in the document's session
+    let hover = hover(&documents, &uri, Position::new(2, 5)).unwrap();
+    let HoverContents::Markup(contents) = hover.contents else {
+        panic!("expected markdown");
+    };
+    assert_eq!(
+        contents.value,
+        "```ginto\nsecond: u64\n```\n\nlocal variable"
+    );
+}
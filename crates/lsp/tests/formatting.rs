@@ -0,0 +1,35 @@
+use std::str::FromStr;
+
+use ginto_lsp::{Documents, formatting};
+use lsp_types::{Position, Range, TextEdit, Uri};
+
+#[test]
+fn formats_documents_and_ranges() {
+    let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+    let source = "fn f() -> u64:\n    1+2\n\nfn g() -> u64:\n    (3)*4\n";
+    let mut documents = Documents::new();
+    documents.update(&uri, 1, source.to_string());
+
+    let edits = formatting(&documents, &uri, None).unwrap();
+    assert_eq!(
+        edits,
+        [
+            TextEdit::new(
+                Range::new(Position::new(1, 0), Position::new(2, 0)),
+                "    1 + 2\n".to_string()
+            ),
+            TextEdit::new(
+                Range::new(Position::new(4, 0), Position::new(5, 0)),
+                "    3 * 4\n".to_string()
+            ),
+        ]
+    );
+
+    let range = Range::new(Position::new(4, 4), Position::new(4, 9));
+    let edits = formatting(&documents, &uri, Some(range)).unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "    3 * 4\n");
+
+    documents.update(&uri, 2, "fn f() -> u64:\n    1 +\n".to_string());
+    assert!(formatting(&documents, &uri, None).is_none());
+}
@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+use ginto_lsp::{Documents, hover};
+use lsp_types::{HoverContents, Position, Range, Uri};
+
+fn hover_at(documents: &Documents, uri: &Uri, line: u32, character: u32) -> (String, Range) {
+    let hover = hover(documents, uri, Position::new(line, character)).unwrap();
+    let HoverContents::Markup(contents) = hover.contents else {
+        panic!("expected markdown");
+    };
+    (contents.value, hover.range.unwrap())
+}
+
+#[test]
+fn shows_types_of_names_and_expressions() {
+    let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+    let source = "fn add(a: u64, b: u64) -> u64:\n    let sum = a + b\n    sum\n\n\
+                  fn main() -> u64:\n    add(1, 2) * 2\n";
+    let mut documents = Documents::new();
+    documents.update(&uri, 1, source.to_string());
+
+    let (value, range) = hover_at(&documents, &uri, 2, 5);
+    assert_eq!(value, "```ginto\nsum: u64\n```\n\nlocal variable");
+    assert_eq!(range, Range::new(Position::new(2, 4), Position::new(2, 7)));
+
+    let (value, _) = hover_at(&documents, &uri, 1, 14);
+    assert_eq!(value, "```ginto\na: u64\n```\n\nparameter");
+
+    let (value, _) = hover_at(&documents, &uri, 5, 5);
+    assert_eq!(value, "```ginto\nfn add(u64, u64) -> u64\n```\n\nfunction");
+
+    let (value, range) = hover_at(&documents, &uri, 5, 16);
+    assert_eq!(value, "```ginto\nu64\n```");
+    assert_eq!(
+        range,
+        Range::new(Position::new(5, 16), Position::new(5, 17))
+    );
+
+    assert!(hover(&documents, &uri, Position::new(4, 1)).is_none());
+}
+
+#[test]
+fn shows_the_comments_above_items() {
+    let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+    let source = "# Adds two numbers.\n#\n#   Wraps on overflow.\nfn add(a: u64, b: u64) -> u64:\n    a + b\n\n\
+                  # not attached\n\nfn main() -> u64:\n    # the answer\n    let x = add(1, 2)\n    x\n";
+    let mut documents = Documents::new();
+    documents.update(&uri, 1, source.to_string());
+
+    let (value, _) = hover_at(&documents, &uri, 10, 13);
+    assert_eq!(
+        value,
+        "```ginto\nfn add(u64, u64) -> u64\n```\n\nfunction\n\n---\n\n\
+         Adds two numbers.\n\n  Wraps on overflow."
+    );
+
+    let (value, _) = hover_at(&documents, &uri, 8, 4);
+    assert_eq!(value, "```ginto\nfn main() -> u64\n```\n\nfunction");
+
+    let (value, _) = hover_at(&documents, &uri, 11, 4);
+    assert_eq!(value, "```ginto\nx: u64\n```\n\nlocal variable");
+}
@@ -0,0 +1,117 @@
+use std::str::FromStr;
+
+use ginto_lsp::{Documents, RenameError, prepare_rename, rename};
+use lsp_types::{Position, Range, Uri};
+
+const SOURCE: &str = "fn add(a: u64, b: u64) -> u64:\n    let sum = a + b\n    sum\n\n\
+                      fn main() -> u64:\n    let total = add(1, 2)\n    print(total)\n    total\n";
+
+fn open() -> (Documents, Uri) {
+    open_source(SOURCE)
+}
+
+fn open_source(source: &str) -> (Documents, Uri) {
+    let uri = Uri::from_str("file:///src/main.ginto").unwrap();
+    let mut documents = Documents::new();
+    documents.update(&uri, 1, source.to_string());
+    (documents, uri)
+}
+
+fn edited(documents: &Documents, uri: &Uri, position: Position, new_name: &str) -> String {
+    edited_source(SOURCE, documents, uri, position, new_name)
+}
+
+/// `source`, open in `documents` at `uri`, with the edits of renaming the
+/// name at `position` to `new_name` applied.
+fn edited_source(
+    source: &str,
+    documents: &Documents,
+    uri: &Uri,
+    position: Position,
+    new_name: &str,
+) -> String {
+    let edit = rename(documents, uri, position, new_name).unwrap().unwrap();
+    let [(edited_uri, mut edits)] = Vec::from_iter(edit.changes.unwrap()).try_into().unwrap();
+    assert_eq!(&edited_uri, uri);
+    let mut lines: Vec<String> = source.lines().map(str::to_string).collect();
+    edits.sort_by_key(|edit| edit.range.start);
+    for pair in edits.windows(2) {
+        assert!(pair[0].range.end <= pair[1].range.start, "{edits:#?}");
+    }
+    for edit in edits.iter().rev() {
+        let line = &mut lines[edit.range.start.line as usize];
+        let range = edit.range.start.character as usize..edit.range.end.character as usize;
+        line.replace_range(range, &edit.new_text);
+    }
+    lines.join("\n") + "\n"
+}
+
+#[test]
+fn renames_a_name_where_it_is_declared_and_used() {
+    let (documents, uri) = open();
+    assert_eq!(
+        prepare_rename(&documents, &uri, Position::new(5, 17)).unwrap(),
+        Some(Range::new(Position::new(5, 16), Position::new(5, 19)))
+    );
+    assert_eq!(
+        edited(&documents, &uri, Position::new(5, 17), "plus"),
+        SOURCE.replace("add", "plus")
+    );
+    assert_eq!(
+        edited(&documents, &uri, Position::new(7, 4), "result"),
+        SOURCE.replace("total", "result")
+    );
+    assert_eq!(
+        prepare_rename(&documents, &uri, Position::new(3, 0)),
+        Ok(None)
+    );
+}
+
+#[test]
+fn refuses_names_it_cant_rename_to() {
+    let (documents, uri) = open();
+    assert!(matches!(
+        prepare_rename(&documents, &uri, Position::new(6, 5)),
+        Err(RenameError::NotRenamable { .. })
+    ));
+    assert_eq!(
+        rename(&documents, &uri, Position::new(5, 17), "1x"),
+        Err(RenameError::InvalidName("1x".to_string()))
+    );
+    assert_eq!(
+        rename(&documents, &uri, Position::new(5, 17), "match"),
+        Err(RenameError::InvalidName("match".to_string()))
+    );
+
+    let err = rename(&documents, &uri, Position::new(5, 17), "main").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "renaming would clash with the function `main`"
+    );
+    let err = rename(&documents, &uri, Position::new(1, 8), "b").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "renaming would clash with the parameter `b`"
+    );
+    let err = rename(&documents, &uri, Position::new(5, 8), "add").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "renaming would clash with the function `add`"
+    );
+    // a local of another function doesn't clash
+    assert!(rename(&documents, &uri, Position::new(5, 8), "sum").is_ok());
+}
+
+#[test]
+fn renames_the_last_segment_of_paths() {
+    let source = "fn add(a: u64) -> u64:\n    a\n\nfn main() -> u64:\n    crate::add(1)\n";
+    let (documents, uri) = open_source(source);
+    assert_eq!(
+        edited_source(source, &documents, &uri, Position::new(4, 12), "plus"),
+        source.replace("add", "plus")
+    );
+    assert_eq!(
+        edited_source(source, &documents, &uri, Position::new(0, 4), "plus"),
+        source.replace("add", "plus")
+    );
+}
@@ -0,0 +1,78 @@
+use std::str::FromStr;
+
+use ginto_lsp::{Documents, TOKEN_TYPES, semantic_tokens, semantic_tokens_delta};
+use lsp_types::{Position, Range, SemanticToken, SemanticTokenType, Uri};
+
+/// The tokens with their absolute positions, their type and whether they
+/// are declarations.
+fn decode(tokens: &[SemanticToken]) -> Vec<(u32, u32, u32, SemanticTokenType, bool)> {
+    let (mut line, mut start) = (0, 0);
+    tokens
+        .iter()
+        .map(|token| {
+            if token.delta_line > 0 {
+                start = 0;
+            }
+            line += token.delta_line;
+            start += token.delta_start;
+            let ty = TOKEN_TYPES[token.token_type as usize].clone();
+            (
+                line,
+                start,
+                token.length,
+                ty,
+                token.token_modifiers_bitset == 1,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn classifies_names_by_what_they_refer_to() {
+    let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+    let source = "fn double(n: u64) -> u64:\n    let m = n * 2\n    m\n";
+    let mut documents = Documents::new();
+    documents.update(&uri, 1, source.to_string());
+    let tokens = semantic_tokens(&documents, &uri, None).unwrap();
+    assert_eq!(
+        decode(&tokens),
+        [
+            (0, 0, 2, SemanticTokenType::KEYWORD, false),
+            (0, 3, 6, SemanticTokenType::FUNCTION, true),
+            (0, 10, 1, SemanticTokenType::PARAMETER, true),
+            (0, 13, 3, SemanticTokenType::TYPE, false),
+            (0, 18, 2, SemanticTokenType::OPERATOR, false),
+            (0, 21, 3, SemanticTokenType::TYPE, false),
+            (1, 4, 3, SemanticTokenType::KEYWORD, false),
+            (1, 8, 1, SemanticTokenType::VARIABLE, true),
+            (1, 10, 1, SemanticTokenType::OPERATOR, false),
+            (1, 12, 1, SemanticTokenType::PARAMETER, false),
+            (1, 14, 1, SemanticTokenType::OPERATOR, false),
+            (1, 16, 1, SemanticTokenType::NUMBER, false),
+            (2, 4, 1, SemanticTokenType::VARIABLE, false),
+        ]
+    );
+
+    let range = Range::new(Position::new(1, 10), Position::new(2, 0));
+    let tokens = semantic_tokens(&documents, &uri, Some(range)).unwrap();
+    assert_eq!(decode(&tokens).len(), 4);
+    assert_eq!(decode(&tokens)[0].0, 1);
+}
+
+#[test]
+fn sends_what_changed_as_a_delta() {
+    let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+    let mut documents = Documents::new();
+    documents.update(&uri, 1, "fn f(a: u64) -> u64:\n    a\n".to_string());
+    let old = semantic_tokens(&documents, &uri, None).unwrap();
+    documents.update(&uri, 2, "fn f(a: u64) -> u64:\n    a + 1\n".to_string());
+    let new = semantic_tokens(&documents, &uri, None).unwrap();
+
+    let [edit] = &semantic_tokens_delta(&old, &new)[..] else {
+        panic!("expected one edit");
+    };
+    assert_eq!(edit.start, 5 * old.len() as u32);
+    assert_eq!(edit.delete_count, 0);
+    assert_eq!(edit.data.as_deref(), Some(&new[old.len()..]));
+    assert!(semantic_tokens_delta(&new, &new).is_empty());
+}
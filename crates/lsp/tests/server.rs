@@ -0,0 +1,259 @@
+use std::str::FromStr;
+
+use ginto_diag::Severity;
+use ginto_lsp::{Documents, Server};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId};
+use lsp_types::{
+    DiagnosticSeverity, InitializeParams, LogMessageParams, MessageType, Position,
+    PublishDiagnosticsParams, Uri,
+};
+use serde_json::json;
+
+fn uri() -> Uri {
+    Uri::from_str("file:///src/main.ginto").unwrap()
+}
+
+#[test]
+fn analyzes_documents_as_they_change() {
+    let mut documents = Documents::new();
+    let document = documents.update(&uri(), 1, "fn main() -> u64:\n    1 +\n".to_string());
+    assert_eq!(document.version, 1);
+    assert_eq!(
+        document
+            .tree
+            .module(document.tree.root())
+            .program
+            .items
+            .len(),
+        1
+    );
+    assert_eq!(document.diagnostics[0].message, "expected expression");
+
+    let file = document.file;
+    let document = documents.update(&uri(), 2, "fn main() -> u64:\n    1\n".to_string());
+    assert_eq!(document.file, file);
+    assert!(document.diagnostics.is_empty());
+
+    let document = documents.update(&uri(), 3, "fn main():\n    \"\n".to_string());
+    assert!(document.types.is_some());
+    assert!(!document.diagnostics.is_empty());
+
+    assert!(documents.close(&uri()).is_some());
+    assert!(documents.get(&uri()).is_none());
+}
+
+#[test]
+fn analyzes_documents_as_ginto_check_does() {
+    let mut documents = Documents::new();
+    let document = documents.update(
+        &uri(),
+        1,
+        "fn main() -> u64:\n    let unused = 1\n    2\n".to_string(),
+    );
+    let [lint] = &document.diagnostics[..] else {
+        panic!("expected a lint, got {:?}", document.diagnostics);
+    };
+    assert_eq!(lint.severity, Severity::Warning);
+    assert_eq!(lint.code.as_deref(), Some("W0004"));
+
+    let document = documents.update(
+        &uri(),
+        2,
+        "fn main() -> u64:\n    match true:\n        true -> 1\n".to_string(),
+    );
+    let [error] = &document.diagnostics[..] else {
+        panic!("expected an error, got {:?}", document.diagnostics);
+    };
+    assert_eq!(error.severity, Severity::Error);
+    assert_eq!(
+        error.message,
+        "non-exhaustive patterns: `false` not covered"
+    );
+}
+
+#[test]
+fn serves_a_client_until_shut_down() {
+    let (server, client) = Connection::memory();
+    let thread = std::thread::spawn(move || Server::initialize(&server).and_then(Server::run));
+
+    let request = |id: i32, method: &str, params| {
+        client
+            .sender
+            .send(Request::new(RequestId::from(id), method.to_string(), params).into())
+            .unwrap();
+        match client.receiver.recv().unwrap() {
+            Message::Response(response) => response,
+            message => panic!("expected a response, got {message:?}"),
+        }
+    };
+    let published = || match client.receiver.recv().unwrap() {
+        Message::Notification(notification) => {
+            assert_eq!(notification.method, "textDocument/publishDiagnostics");
+            serde_json::from_value::<PublishDiagnosticsParams>(notification.params).unwrap()
+        }
+        message => panic!("expected diagnostics, got {message:?}"),
+    };
+    let notify = |method: &str, params| {
+        client
+            .sender
+            .send(Notification::new(method.to_string(), params).into())
+            .unwrap();
+    };
+
+    let response = request(1, "initialize", json!(InitializeParams::default()));
+    let result = response.result.unwrap();
+    assert_eq!(result["capabilities"]["textDocumentSync"]["change"], 1);
+    assert!(result["capabilities"]["completionProvider"].is_object());
+    assert_eq!(result["capabilities"]["documentSymbolProvider"], true);
+    assert_eq!(result["capabilities"]["documentFormattingProvider"], true);
+    assert_eq!(
+        result["capabilities"]["renameProvider"]["prepareProvider"],
+        true
+    );
+    notify("initialized", json!({}));
+    notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": uri(),
+                "languageId": "ginto",
+                "version": 1,
+                "text": "fn main():\n    \"é\" +\n",
+            }
+        }),
+    );
+    let params = published();
+    assert_eq!(params.version, Some(1));
+    let [diagnostic] = &params.diagnostics[..] else {
+        panic!("expected one diagnostic, got {:?}", params.diagnostics);
+    };
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    // the end of the line, 10 bytes in as `é` takes two but one code unit
+    assert_eq!(diagnostic.range.start, Position::new(1, 9));
+
+    // rapid edits are analyzed once, at their last version
+    for (version, text) in [(2, "fn main():\n    1 +\n"), (3, "fn main():\n    ()\n")] {
+        notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": uri(), "version": version },
+                "contentChanges": [{ "text": text }],
+            }),
+        );
+    }
+    let params = published();
+    assert_eq!(params.version, Some(3));
+    assert!(params.diagnostics.is_empty());
+
+    let document = json!({ "textDocument": { "uri": uri() } });
+    let tokens = request(4, "textDocument/semanticTokens/full", document.clone());
+    let tokens = tokens.result.unwrap();
+    assert_eq!(tokens["data"].as_array().unwrap().len(), 5 * 2);
+    let mut params = document;
+    params["previousResultId"] = tokens["resultId"].clone();
+    let delta = request(5, "textDocument/semanticTokens/full/delta", params);
+    assert_eq!(delta.result.unwrap()["edits"], json!([]));
+
+    notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": uri(), "version": 4 },
+            "contentChanges": [{ "text": "fn main():\n    let unused = 1\n" }],
+        }),
+    );
+    let params = published();
+    assert_eq!(params.version, Some(4));
+    let [lint] = &params.diagnostics[..] else {
+        panic!("expected a lint, got {:?}", params.diagnostics);
+    };
+    assert_eq!(lint.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(lint.range.start, Position::new(1, 8));
+
+    notify(
+        "textDocument/didClose",
+        json!({ "textDocument": { "uri": uri() } }),
+    );
+    let params = published();
+    assert_eq!(params.version, None);
+    assert!(params.diagnostics.is_empty());
+    let response = request(2, "ginto/unknown", json!(null));
+    assert_eq!(
+        response.error.unwrap().code,
+        ErrorCode::MethodNotFound as i32
+    );
+
+    assert!(request(3, "shutdown", json!(null)).error.is_none());
+    notify("exit", json!(null));
+    thread.join().unwrap().unwrap();
+}
+
+#[test]
+fn rejects_malformed_messages_and_keeps_serving() {
+    let (server, client) = Connection::memory();
+    let thread = std::thread::spawn(move || Server::initialize(&server).and_then(Server::run));
+    let send = |message: Message| client.sender.send(message).unwrap();
+
+    send(
+        Request::new(
+            RequestId::from(1),
+            "initialize".to_string(),
+            InitializeParams::default(),
+        )
+        .into(),
+    );
+    assert!(matches!(
+        client.receiver.recv().unwrap(),
+        Message::Response(_)
+    ));
+    send(Notification::new("initialized".to_string(), json!({})).into());
+
+    // a request with malformed parameters gets an error
+    send(
+        Request::new(
+            RequestId::from(2),
+            "textDocument/hover".to_string(),
+            json!({ "position": 3 }),
+        )
+        .into(),
+    );
+    match client.receiver.recv().unwrap() {
+        Message::Response(response) => {
+            assert_eq!(response.id, RequestId::from(2));
+            assert_eq!(
+                response.error.unwrap().code,
+                ErrorCode::InvalidParams as i32
+            );
+        }
+        message => panic!("expected a response, got {message:?}"),
+    }
+
+    // a notification with malformed parameters is logged and dropped
+    send(
+        Notification::new(
+            "textDocument/didOpen".to_string(),
+            json!({ "textDocument": 1 }),
+        )
+        .into(),
+    );
+    match client.receiver.recv().unwrap() {
+        Message::Notification(notification) => {
+            assert_eq!(notification.method, "window/logMessage");
+            let params: LogMessageParams = serde_json::from_value(notification.params).unwrap();
+            assert_eq!(params.typ, MessageType::ERROR);
+            assert!(
+                params
+                    .message
+                    .starts_with("`textDocument/didOpen`: invalid parameters")
+            );
+        }
+        message => panic!("expected a log message, got {message:?}"),
+    }
+
+    send(Request::new(RequestId::from(3), "shutdown".to_string(), json!(null)).into());
+    match client.receiver.recv().unwrap() {
+        Message::Response(response) => assert!(response.error.is_none()),
+        message => panic!("expected a response, got {message:?}"),
+    }
+    send(Notification::new("exit".to_string(), json!(null)).into());
+    thread.join().unwrap().unwrap();
+}
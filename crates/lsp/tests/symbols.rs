@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+use ginto_lsp::{Documents, document_symbols};
+use lsp_types::{DocumentSymbol, Position, Range, SymbolKind, Uri};
+
+fn outline(symbols: &[DocumentSymbol]) -> Vec<(String, SymbolKind, Vec<String>)> {
+    symbols
+        .iter()
+        .map(|symbol| {
+            let children = symbol.children.iter().flatten();
+            let children = children.map(|child| child.name.clone()).collect();
+            (symbol.name.clone(), symbol.kind, children)
+        })
+        .collect()
+}
+
+#[test]
+fn outlines_items_and_their_variables() {
+    let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+    let source = "trait Show:\n    fn show(self) -> str\n\n\
+                  impl Show for u64:\n    fn show(self) -> str:\n        \"{self}\"\n\n\
+                  fn main() -> u64:\n    let x = 1\n    if true:\n        let y = 2\n    x\n";
+    let mut documents = Documents::new();
+    documents.update(&uri, 1, source.to_string());
+    let symbols = document_symbols(&documents, &uri).unwrap();
+    assert_eq!(
+        outline(&symbols),
+        [
+            (
+                "Show".to_string(),
+                SymbolKind::INTERFACE,
+                vec!["show".to_string()]
+            ),
+            (
+                "impl Show for u64".to_string(),
+                SymbolKind::OBJECT,
+                vec!["show".to_string()]
+            ),
+            (
+                "main".to_string(),
+                SymbolKind::FUNCTION,
+                vec!["x".to_string()]
+            ),
+        ]
+    );
+
+    let main = &symbols[2];
+    assert_eq!(main.detail.as_deref(), Some("fn() -> u64"));
+    assert_eq!(
+        main.selection_range,
+        Range::new(Position::new(7, 3), Position::new(7, 7))
+    );
+    assert_eq!(main.range.start, Position::new(7, 0));
+    assert_eq!(main.range.end, Position::new(12, 0));
+    let x = &main.children.as_ref().unwrap()[0];
+    assert_eq!(x.kind, SymbolKind::VARIABLE);
+    assert_eq!(x.detail.as_deref(), Some("u64"));
+    assert_eq!(
+        x.range,
+        Range::new(Position::new(8, 4), Position::new(8, 13))
+    );
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use ginto_diag::{BytePos, FileId, Span, Symbol};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct DefId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefKind {
+    Func,
+    Param,
+    Local,
+    Mod,
+    TypeParam,
+    Trait,
+    /// A method of a trait or of an `impl` block. Methods are not in scope
+    /// by name; calls find them through the receiver's type.
+    Method,
+    /// A function provided by the host, in scope in every module unless an
+    /// item of the same name shadows it.
+    Builtin,
+    /// A closure, defined at the span of its `|params| body` expression.
+    Closure,
+}
+
+impl DefKind {
+    pub fn descr(&self) -> &'static str {
+        match self {
+            DefKind::Func => "function",
+            DefKind::Param => "parameter",
+            DefKind::Local => "local variable",
+            DefKind::Mod => "module",
+            DefKind::TypeParam => "type parameter",
+            DefKind::Trait => "trait",
+            DefKind::Method => "method",
+            DefKind::Builtin => "builtin function",
+            DefKind::Closure => "closure",
+        }
+    }
+}
+
+/// The file of builtin definitions and their type parameters, which have
+/// no source.
+pub const BUILTIN_FILE: FileId = FileId(usize::MAX);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Def {
+    pub name: Symbol,
+    pub kind: DefKind,
+    pub span: Span,
+    pub file_id: FileId,
+}
+
+/// The result of name resolution: every definition in the program and the
+/// definition each name use refers to, keyed by the span of the use.
+#[derive(Debug, Default, Clone)]
+pub struct Resolutions {
+    defs: Vec<Def>,
+    def_spans: HashMap<(FileId, Span), DefId>,
+    uses: HashMap<(FileId, Span), DefId>,
+    /// What the last segment of each path expression refers to, by the span
+    /// of the whole path. Its segments are in `uses`.
+    paths: HashMap<(FileId, Span), DefId>,
+    /// The type parameters of each builtin function.
+    builtin_generics: HashMap<DefId, Vec<DefId>>,
+    /// The variables of enclosing functions each closure uses.
+    captures: HashMap<DefId, Vec<DefId>>,
+}
+
+impl Resolutions {
+    pub(crate) fn add_def(&mut self, def: Def) -> DefId {
+        let id = DefId(self.defs.len());
+        self.def_spans.insert((def.file_id, def.span), id);
+        self.defs.push(def);
+        id
+    }
+
+    /// Adds a definition without a source, like a builtin function.
+    pub(crate) fn add_builtin_def(&mut self, def: Def) -> DefId {
+        let id = DefId(self.defs.len());
+        self.defs.push(def);
+        id
+    }
+
+    pub(crate) fn set_builtin_generics(&mut self, def: DefId, generics: Vec<DefId>) {
+        self.builtin_generics.insert(def, generics);
+    }
+
+    pub(crate) fn add_capture(&mut self, closure: DefId, def: DefId) {
+        let captures = self.captures.entry(closure).or_default();
+        if !captures.contains(&def) {
+            captures.push(def);
+        }
+    }
+
+    /// Records another place where the existing definition `id` is declared,
+    /// like a later alternative of an or-pattern binding the same name.
+    pub(crate) fn add_def_span(&mut self, file_id: FileId, span: Span, id: DefId) {
+        self.def_spans.insert((file_id, span), id);
+    }
+
+    pub(crate) fn add_use(&mut self, file_id: FileId, span: Span, def: DefId) {
+        self.uses.insert((file_id, span), def);
+    }
+
+    pub(crate) fn add_path(&mut self, file_id: FileId, span: Span, def: DefId) {
+        self.paths.insert((file_id, span), def);
+    }
+
+    pub fn def(&self, id: DefId) -> &Def {
+        &self.defs[id.0]
+    }
+
+    pub fn defs(&self) -> impl Iterator<Item = (DefId, &Def)> {
+        self.defs.iter().enumerate().map(|(i, def)| (DefId(i), def))
+    }
+
+    /// Returns the definition whose name is declared at `span`.
+    pub fn def_at(&self, file_id: FileId, span: Span) -> Option<DefId> {
+        self.def_spans.get(&(file_id, span)).copied()
+    }
+
+    /// Returns the definition the name used at `span` refers to, or that
+    /// the path expression at `span`, like `math::add`, ends with.
+    pub fn resolve(&self, file_id: FileId, span: Span) -> Option<DefId> {
+        let key = (file_id, span);
+        self.uses
+            .get(&key)
+            .or_else(|| self.paths.get(&key))
+            .copied()
+    }
+
+    /// Returns the innermost name used or declared in `file_id` whose span
+    /// contains `pos`, counting the position right after it, with the
+    /// definition it refers to or declares. Closures, which have no name,
+    /// are skipped.
+    pub fn name_at(&self, file_id: FileId, pos: BytePos) -> Option<(Span, DefId)> {
+        self.uses
+            .iter()
+            .chain(&self.def_spans)
+            .filter(|((file, span), _)| *file == file_id && span.start <= pos && pos <= span.end)
+            .filter(|(_, def)| self.def(**def).kind != DefKind::Closure)
+            .map(|((_, span), def)| (*span, *def))
+            .min_by_key(|(span, _)| (span.len(), span.start))
+    }
+
+    /// The type parameters of the builtin function `def`, in order.
+    pub fn builtin_generics(&self, def: DefId) -> &[DefId] {
+        self.builtin_generics.get(&def).map_or(&[], Vec::as_slice)
+    }
+
+    /// The variables of enclosing functions the closure `def` uses, in
+    /// the order of their first use. Closures capture them by value.
+    pub fn captures(&self, def: DefId) -> &[DefId] {
+        self.captures.get(&def).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every place where `id` is declared: its own span, and those of the
+    /// later alternatives of an or-pattern binding it.
+    pub fn def_spans_of(&self, id: DefId) -> impl Iterator<Item = (FileId, Span)> + '_ {
+        self.def_spans
+            .iter()
+            .filter(move |(_, def)| **def == id)
+            .map(|(site, _)| *site)
+    }
+
+    /// Every name that refers to `id`. A path refers to it by its last
+    /// segment, so that no two of the spans overlap.
+    pub fn uses_of(&self, id: DefId) -> impl Iterator<Item = (FileId, Span)> + '_ {
+        self.uses
+            .iter()
+            .filter(move |(_, def)| **def == id)
+            .map(|(site, _)| *site)
+    }
+}
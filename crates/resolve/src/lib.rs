@@ -0,0 +1,7 @@
+mod def;
+mod module_tree;
+mod resolver;
+
+pub use def::*;
+pub use module_tree::*;
+pub use resolver::*;
@@ -0,0 +1,411 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::{Path, PathBuf},
+};
+
+use ginto_diag::{
+    Diagnostic, DiagnosticConvertible, FileId, Interner, Label, SourceManager, Span, Symbol,
+};
+use ginto_syntax::{ItemKind, Lexer, LexerError, Parser, ParserError, Program};
+use rayon::prelude::*;
+use tracing::info_span;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct ModuleId(pub usize);
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub name: Symbol,
+    pub program: Program,
+    pub parent: Option<ModuleId>,
+    pub children: BTreeMap<Symbol, ModuleId>,
+    /// The name in the `mod` declaration that loaded this module, in the
+    /// parent's file.
+    pub decl: Option<Span>,
+}
+
+/// Every module of a program, rooted at the file it was compiled from.
+#[derive(Debug, Clone)]
+pub struct ModuleTree {
+    modules: Vec<Module>,
+}
+
+impl ModuleTree {
+    /// A tree of just `program`, whose `mod` declarations load nothing.
+    pub fn from_program(program: Program) -> Self {
+        ModuleTree {
+            modules: vec![Module {
+                name: Symbol::intern("crate"),
+                program,
+                parent: None,
+                children: BTreeMap::new(),
+                decl: None,
+            }],
+        }
+    }
+
+    pub fn root(&self) -> ModuleId {
+        ModuleId(0)
+    }
+
+    pub fn module(&self, id: ModuleId) -> &Module {
+        &self.modules[id.0]
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = (ModuleId, &Module)> {
+        self.modules
+            .iter()
+            .enumerate()
+            .map(|(i, module)| (ModuleId(i), module))
+    }
+
+    pub fn programs_mut(&mut self) -> impl Iterator<Item = &mut Program> {
+        self.modules.iter_mut().map(|module| &mut module.program)
+    }
+
+    /// Whether `ancestor` is `id` or one of its enclosing modules.
+    pub fn is_ancestor(&self, ancestor: ModuleId, id: ModuleId) -> bool {
+        let mut current = Some(id);
+        while let Some(module) = current {
+            if module == ancestor {
+                return true;
+            }
+            current = self.module(module).parent;
+        }
+        false
+    }
+
+    /// The path of `id` from the root, like `crate::math::vec`.
+    pub fn path(&self, id: ModuleId) -> String {
+        match self.module(id).parent {
+            Some(parent) => format!("{}::{}", self.path(parent), self.module(id).name),
+            None => "crate".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    FileNotFound {
+        name: String,
+        path: String,
+        span: Span,
+        file_id: FileId,
+    },
+    Unreadable {
+        path: String,
+        message: String,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A `mod` declaration that loads a file already being loaded by one of
+    /// its ancestors, which is possible through symbolic links. `chain` holds
+    /// every `mod` declaration from that ancestor down to this one.
+    RecursiveModule {
+        path: String,
+        chain: Vec<(String, Span, FileId)>,
+    },
+    Lexer(LexerError),
+    Parser(ParserError),
+}
+
+impl DiagnosticConvertible for LoadError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            LoadError::FileNotFound {
+                name,
+                path,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("file not found for module `{}`", name))
+                .with_label(Label::primary(file_id, span).with_message("declared here"))
+                .with_help(format!(
+                    "to create the module `{}`, create file \"{}\"",
+                    name, path
+                )),
+
+            LoadError::Unreadable {
+                path,
+                message,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("couldn't read `{}`: {}", path, message))
+                .with_label(Label::primary(file_id, span).with_message("module declared here")),
+
+            LoadError::RecursiveModule { path, chain } => {
+                let labels = chain
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (name, span, file_id))| {
+                        let label = if i == 0 {
+                            Label::primary(file_id, span)
+                        } else {
+                            Label::secondary(file_id, span)
+                        };
+                        label.with_message(format!("declares `mod {}` here", name))
+                    })
+                    .collect();
+                Diagnostic::error(format!("module file `{}` includes itself", path))
+                    .with_labels(labels)
+                    .with_note(
+                        "each file can only be loaded once along a chain of `mod` declarations",
+                    )
+            }
+
+            LoadError::Lexer(err) => err.into_diagnostic(),
+            LoadError::Parser(err) => err.into_diagnostic(),
+        }
+    }
+}
+
+/// Parses the file `root` and every file reachable from it through `mod`
+/// declarations, adding the loaded files to `sm` or updating those it
+/// already has.
+///
+/// `mod name` in `dir/file.ginto` loads `dir/name.ginto`, except in the root
+/// file, whose children live in `dir` itself; in general a module's children
+/// live in the directory named after it.
+pub fn load_modules(sm: &mut SourceManager, root: FileId) -> (ModuleTree, Vec<LoadError>) {
+    load_modules_with(sm, root, |_, path| std::fs::read_to_string(path))
+}
+
+/// Loads the modules of `root` as `load_modules` does, getting the source
+/// of each file it loads from `read` rather than from disk, as an editor
+/// does for files with unsaved changes.
+///
+/// The files a module declares are read in order but lexed and parsed in
+/// parallel, after which their modules are loaded one by one, so the tree
+/// and the errors come out in the same order on any number of threads.
+pub fn load_modules_with(
+    sm: &mut SourceManager,
+    root: FileId,
+    mut read: impl FnMut(&SourceManager, &Path) -> io::Result<String>,
+) -> (ModuleTree, Vec<LoadError>) {
+    let mut loader = ModuleLoader {
+        sm,
+        read: &mut read,
+        modules: Vec::new(),
+        paths: Vec::new(),
+        errors: Vec::new(),
+    };
+    let root_path = loader
+        .sm
+        .get_file(root)
+        .map(|file| PathBuf::from(&file.name))
+        .unwrap_or_default();
+    let root_dir = root_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let canonical = root_path.canonicalize().ok();
+    let parsed = parse(loader.sm, root);
+    loader.load(
+        parsed,
+        Symbol::intern("crate"),
+        None,
+        None,
+        canonical,
+        root_dir,
+    );
+    let tree = ModuleTree {
+        modules: loader.modules,
+    };
+    (tree, loader.errors)
+}
+
+struct ModuleLoader<'a> {
+    sm: &'a mut SourceManager,
+    read: &'a mut dyn FnMut(&SourceManager, &Path) -> io::Result<String>,
+    modules: Vec<Module>,
+    /// The canonical path of each module's file, when it has one on disk.
+    paths: Vec<Option<PathBuf>>,
+    errors: Vec<LoadError>,
+}
+
+/// A `mod` declaration whose file was read, waiting for its module to be
+/// loaded.
+struct Child {
+    name: Symbol,
+    span: Span,
+    file_id: FileId,
+    path: Option<PathBuf>,
+}
+
+impl ModuleLoader<'_> {
+    fn load(
+        &mut self,
+        (program, errors): (Program, Vec<LoadError>),
+        name: Symbol,
+        parent: Option<ModuleId>,
+        decl: Option<Span>,
+        path: Option<PathBuf>,
+        dir: PathBuf,
+    ) -> ModuleId {
+        self.errors.extend(errors);
+        let file_id = program.file_id;
+        let id = ModuleId(self.modules.len());
+        let decls: Vec<(Symbol, Span)> = program
+            .items
+            .iter()
+            .filter_map(|item| match &item.inner {
+                ItemKind::Mod(decl) => Some((decl.name.inner, decl.name.span)),
+                _ => None,
+            })
+            .collect();
+        self.modules.push(Module {
+            name,
+            program,
+            parent,
+            children: BTreeMap::new(),
+            decl,
+        });
+        self.paths.push(path);
+
+        // the errors of reading a file go before those of loading it, and
+        // of the files after it
+        let mut children = Vec::new();
+        let mut read = BTreeSet::new();
+        for (name, span) in decls {
+            // a second declaration of the same module is reported by the
+            // resolver as a duplicate definition
+            if read.contains(&name) {
+                continue;
+            }
+            let path = dir.join(format!("{}.ginto", name));
+            let canonical = path.canonicalize().ok();
+            if let Some(canonical) = &canonical
+                && let Some(chain) = self.recursion(id, canonical, name, span, file_id)
+            {
+                children.push(Err(LoadError::RecursiveModule {
+                    path: path.display().to_string(),
+                    chain,
+                }));
+                continue;
+            }
+            match self.read(name, &path, span, file_id) {
+                Ok(child_file) => {
+                    read.insert(name);
+                    children.push(Ok(Child {
+                        name,
+                        span,
+                        file_id: child_file,
+                        path: canonical,
+                    }));
+                }
+                Err(err) => children.push(Err(err)),
+            }
+        }
+
+        let interner = Interner::current();
+        let sm = &*self.sm;
+        let parsed: Vec<_> = children
+            .par_iter()
+            .map(|child| {
+                let child = child.as_ref().ok()?;
+                let _session = interner.clone().enter();
+                Some(parse(sm, child.file_id))
+            })
+            .collect();
+        for (child, parsed) in children.into_iter().zip(parsed) {
+            let (child, parsed) = match (child, parsed) {
+                (Ok(child), Some(parsed)) => (child, parsed),
+                (Err(err), _) => {
+                    self.errors.push(err);
+                    continue;
+                }
+                (Ok(_), None) => unreachable!("read files are parsed"),
+            };
+            let child_dir = dir.join(child.name.to_string());
+            let module = self.load(
+                parsed,
+                child.name,
+                Some(id),
+                Some(child.span),
+                child.path,
+                child_dir,
+            );
+            self.modules[id.0].children.insert(child.name, module);
+        }
+        id
+    }
+
+    /// If `path` is the file of `module` or of one of its ancestors, returns
+    /// the `mod` declarations leading from that ancestor to `path`, ending
+    /// with the declaration of `name` in `module`.
+    fn recursion(
+        &self,
+        module: ModuleId,
+        path: &Path,
+        name: Symbol,
+        span: Span,
+        file_id: FileId,
+    ) -> Option<Vec<(String, Span, FileId)>> {
+        let mut chain = vec![(name.to_string(), span, file_id)];
+        let mut current = module;
+        loop {
+            if self.paths[current.0].as_deref() == Some(path) {
+                chain.reverse();
+                return Some(chain);
+            }
+            let module = &self.modules[current.0];
+            let (parent, decl) = (module.parent?, module.decl?);
+            let parent_file = self.modules[parent.0].program.file_id;
+            chain.push((module.name.to_string(), decl, parent_file));
+            current = parent;
+        }
+    }
+
+    fn read(
+        &mut self,
+        name: Symbol,
+        path: &Path,
+        span: Span,
+        file_id: FileId,
+    ) -> Result<FileId, LoadError> {
+        let display = path.display().to_string();
+        match (self.read)(self.sm, path) {
+            // a file loaded before is updated in place, so reloading a
+            // program doesn't grow `sm`
+            Ok(source) => Ok(self.sm.set_file(display, source)),
+            Err(err) => Err(match err.kind() {
+                io::ErrorKind::NotFound => LoadError::FileNotFound {
+                    name: name.to_string(),
+                    path: display,
+                    span,
+                    file_id,
+                },
+                _ => LoadError::Unreadable {
+                    path: display,
+                    message: err.to_string(),
+                    span,
+                    file_id,
+                },
+            }),
+        }
+    }
+}
+
+/// Lexes and parses `file_id`, with the errors found. A file that fails to
+/// lex yields an empty program so that its module still exists in the tree.
+fn parse(sm: &SourceManager, file_id: FileId) -> (Program, Vec<LoadError>) {
+    let (name, source) = sm
+        .get_file(file_id)
+        .map(|file| (file.name.as_str(), file.source.as_str()))
+        .unwrap_or_default();
+    let _span = info_span!("file", %name).entered();
+    let tokens = match Lexer::new(file_id, source).lex_all() {
+        Ok(tokens) => tokens,
+        Err(errs) => {
+            let program = Program {
+                file_id,
+                items: Vec::new(),
+            };
+            return (program, errs.into_iter().map(LoadError::Lexer).collect());
+        }
+    };
+    let mut parser = Parser::new(file_id, tokens);
+    let program = parser.parse_program();
+    let errors = parser.errors().iter().cloned().map(LoadError::Parser);
+    (program, errors.collect())
+}
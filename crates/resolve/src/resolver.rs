@@ -0,0 +1,1076 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use ginto_diag::{
+    Diagnostic, DiagnosticConvertible, FileId, Label, Span, Spanned, Symbol, did_you_mean,
+    find_similar, sym,
+};
+use ginto_syntax::{
+    Expr, ExprKind, Func, ImplDecl, Item, ItemKind, Param, Pattern, TraitDecl, Type, UseDecl,
+    join_path,
+    visit::{self, Visitor},
+};
+
+use crate::{BUILTIN_FILE, Def, DefId, DefKind, ModuleId, ModuleTree, Resolutions};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveError {
+    UnresolvedName {
+        name: String,
+        /// A name in scope that `name` looks like a misspelling of.
+        similar: Option<String>,
+        span: Span,
+        file_id: FileId,
+    },
+    UnresolvedType {
+        name: String,
+        span: Span,
+        file_id: FileId,
+    },
+    ExpectedTrait {
+        name: String,
+        kind: DefKind,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A `self` parameter of a function that is not a method.
+    SelfOutsideMethod { span: Span, file_id: FileId },
+    DuplicateDefinition {
+        name: String,
+        kind: DefKind,
+        span: Span,
+        previous: Span,
+        file_id: FileId,
+    },
+    UnresolvedImport {
+        /// The whole path as written, e.g. `math::sub`.
+        path: String,
+        name: String,
+        /// The module that was searched, or `None` for the importing one.
+        module: Option<String>,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A segment of a path expression, like `math::sub`, that names
+    /// nothing.
+    UnresolvedPath {
+        name: String,
+        /// The module that was searched, or `None` for the one the path is
+        /// in.
+        module: Option<String>,
+        span: Span,
+        file_id: FileId,
+    },
+    PrivateItem {
+        name: String,
+        kind: DefKind,
+        span: Span,
+        def_span: Span,
+        def_file: FileId,
+        file_id: FileId,
+    },
+    NotAModule {
+        name: String,
+        kind: DefKind,
+        span: Span,
+        file_id: FileId,
+    },
+    ExpectedValue {
+        name: String,
+        span: Span,
+        file_id: FileId,
+    },
+    /// Modules that import from each other in a cycle, one edge per `use`.
+    ImportCycle { cycle: Vec<ImportEdge> },
+    /// A name bound by some alternatives of an or-pattern but not others.
+    BindingNotInAllPatterns {
+        name: String,
+        bound: Vec<Span>,
+        missing: Vec<Span>,
+        file_id: FileId,
+    },
+}
+
+/// A `use` in module `from` that imports an item of module `to`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportEdge {
+    pub from: String,
+    pub to: String,
+    pub span: Span,
+    pub file_id: FileId,
+}
+
+impl DiagnosticConvertible for ResolveError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            ResolveError::UnresolvedName {
+                name,
+                similar,
+                span,
+                file_id,
+            } => {
+                let diagnostic = Diagnostic::error(format!("cannot find `{}` in this scope", name))
+                    .with_label(
+                        Label::primary(file_id, span).with_message("not found in this scope"),
+                    );
+                match similar {
+                    Some(similar) => {
+                        diagnostic.with_suggestion(did_you_mean(file_id, span, &similar))
+                    }
+                    None => diagnostic,
+                }
+            }
+
+            ResolveError::UnresolvedType {
+                name,
+                span,
+                file_id,
+            } => {
+                let diagnostic =
+                    Diagnostic::error(format!("cannot find type `{}` in this scope", name))
+                        .with_label(
+                            Label::primary(file_id, span).with_message("not found in this scope"),
+                        );
+                if name == "Self" {
+                    diagnostic.with_note("`Self` is only available in traits and `impl` blocks")
+                } else {
+                    diagnostic.with_help(format!(
+                        "declare it as a type parameter of the function: `<{}>`",
+                        name
+                    ))
+                }
+            }
+
+            ResolveError::ExpectedTrait {
+                name,
+                kind,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("expected trait, found {} `{}`", kind.descr(), name))
+                .with_label(Label::primary(file_id, span).with_message("not a trait")),
+
+            ResolveError::SelfOutsideMethod { span, file_id } => {
+                Diagnostic::error("`self` parameter is only allowed in methods")
+                    .with_label(Label::primary(file_id, span).with_message("not a method"))
+                    .with_note("methods are declared in traits and `impl` blocks")
+            }
+
+            ResolveError::DuplicateDefinition {
+                name,
+                kind,
+                span,
+                previous,
+                file_id,
+            } => {
+                let scope = match kind {
+                    DefKind::Param => "parameter list",
+                    DefKind::TypeParam => "type parameter list",
+                    DefKind::Method => "trait or `impl` block",
+                    _ => "module",
+                };
+                Diagnostic::error(format!("the name `{}` is defined multiple times", name))
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message(format!("`{}` redefined here", name)),
+                    )
+                    .with_label(Label::secondary(file_id, previous).with_message(format!(
+                        "previous definition of the {} `{}` here",
+                        kind.descr(),
+                        name
+                    )))
+                    .with_note(format!(
+                        "`{}` must be defined only once in this {}",
+                        name, scope
+                    ))
+            }
+
+            ResolveError::UnresolvedImport {
+                path,
+                name,
+                module,
+                span,
+                file_id,
+            } => {
+                let label = match module {
+                    Some(module) => format!("no `{}` in `{}`", name, module),
+                    None => format!("no `{}` in the current module", name),
+                };
+                Diagnostic::error(format!("unresolved import `{}`", path))
+                    .with_label(Label::primary(file_id, span).with_message(label))
+            }
+
+            ResolveError::UnresolvedPath {
+                name,
+                module,
+                span,
+                file_id,
+            } => {
+                let scope = match module {
+                    Some(module) => format!("`{}`", module),
+                    None => "this scope".to_string(),
+                };
+                Diagnostic::error(format!("cannot find `{}` in {}", name, scope)).with_label(
+                    Label::primary(file_id, span).with_message(format!("not found in {}", scope)),
+                )
+            }
+
+            ResolveError::PrivateItem {
+                name,
+                kind,
+                span,
+                def_span,
+                def_file,
+                file_id,
+            } => Diagnostic::error(format!("{} `{}` is private", kind.descr(), name))
+                .with_label(
+                    Label::primary(file_id, span).with_message(format!("private {}", kind.descr())),
+                )
+                .with_label(Label::secondary(def_file, def_span).with_message(format!(
+                    "the {} `{}` is defined here",
+                    kind.descr(),
+                    name
+                )))
+                .with_help(format!(
+                    "consider marking `{}` as `pub` in its module",
+                    name
+                )),
+
+            ResolveError::NotAModule {
+                name,
+                kind,
+                span,
+                file_id,
+            } => Diagnostic::error(format!(
+                "expected module, found {} `{}`",
+                kind.descr(),
+                name
+            ))
+            .with_label(Label::primary(file_id, span).with_message("not a module")),
+
+            ResolveError::ExpectedValue {
+                name,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("expected value, found module `{}`", name))
+                .with_label(Label::primary(file_id, span).with_message("not a value")),
+
+            ResolveError::ImportCycle { cycle } => {
+                let path = cycle
+                    .iter()
+                    .map(|edge| format!("`{}`", edge.from))
+                    .chain(cycle.first().map(|edge| format!("`{}`", edge.from)))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                let labels = cycle
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, edge)| {
+                        let label = if i == 0 {
+                            Label::primary(edge.file_id, edge.span)
+                        } else {
+                            Label::secondary(edge.file_id, edge.span)
+                        };
+                        label.with_message(format!(
+                            "`{}` imports from `{}` here",
+                            edge.from, edge.to
+                        ))
+                    })
+                    .collect();
+                Diagnostic::error(format!("import cycle between modules {}", path))
+                    .with_labels(labels)
+                    .with_note("modules cannot depend on each other in a cycle")
+                    .with_help("move the shared items into a module that all of them import")
+            }
+
+            ResolveError::BindingNotInAllPatterns {
+                name,
+                bound,
+                missing,
+                file_id,
+            } => {
+                let missing = missing.into_iter().map(|span| {
+                    Label::primary(file_id, span)
+                        .with_message(format!("pattern doesn't bind `{}`", name))
+                });
+                let bound = bound.into_iter().map(|span| {
+                    Label::secondary(file_id, span).with_message("variable not in all patterns")
+                });
+                Diagnostic::error(format!("variable `{}` is not bound in all patterns", name))
+                    .with_labels(missing.chain(bound).collect())
+                    .with_note("every alternative of an or-pattern must bind the same names")
+            }
+        }
+    }
+}
+
+/// A name declared at module level, either by an item or by a `use`.
+#[derive(Debug, Clone, Copy)]
+struct Binding {
+    def: DefId,
+    /// Where the name is bound in the module's own file.
+    span: Span,
+    is_pub: bool,
+}
+
+enum ImportResult {
+    /// The definition each resolved segment refers to, and the module the
+    /// imported item was found in.
+    Resolved(Vec<(Span, DefId)>, ModuleId),
+    /// The path goes through a name that a pending import may still bind.
+    Blocked,
+    Failed(ResolveError),
+}
+
+pub struct Resolver {
+    file_id: FileId,
+    scopes: Vec<HashMap<Symbol, DefId>>,
+    /// The type parameters of the function being resolved.
+    generics: Vec<DefId>,
+    /// Whether `self` and `Self` are available, inside a trait or an
+    /// `impl` block.
+    in_trait_or_impl: bool,
+    /// The module-level names of each module, indexed by `ModuleId`.
+    namespaces: Vec<HashMap<Symbol, Binding>>,
+    module_defs: HashMap<DefId, ModuleId>,
+    /// For each module, the modules it imports from and the `use` paths
+    /// doing so.
+    imports: BTreeMap<ModuleId, Vec<(ModuleId, Span)>>,
+    /// The builtin functions, looked up after every scope.
+    builtins: HashMap<Symbol, DefId>,
+    /// The closures being resolved, innermost last, with the index of the
+    /// first scope inside each. Variables of outer scopes they use are
+    /// their captures.
+    closures: Vec<(DefId, usize)>,
+    resolutions: Resolutions,
+    errors: Vec<ResolveError>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self {
+            file_id: FileId(0),
+            scopes: Vec::new(),
+            generics: Vec::new(),
+            in_trait_or_impl: false,
+            namespaces: Vec::new(),
+            module_defs: HashMap::new(),
+            imports: BTreeMap::new(),
+            builtins: HashMap::new(),
+            closures: Vec::new(),
+            resolutions: Resolutions::default(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn errors(&self) -> &[ResolveError] {
+        &self.errors
+    }
+
+    /// Declares a function provided by the host, with type parameters
+    /// `generics`. Builtins must be declared before resolving.
+    pub fn declare_builtin(&mut self, name: &str, generics: &[&str]) -> DefId {
+        let builtin = |name: &str, kind| Def {
+            name: Symbol::intern(name),
+            kind,
+            span: Span::default(),
+            file_id: BUILTIN_FILE,
+        };
+        let def = self
+            .resolutions
+            .add_builtin_def(builtin(name, DefKind::Builtin));
+        let generics = generics
+            .iter()
+            .map(|generic| {
+                self.resolutions
+                    .add_builtin_def(builtin(generic, DefKind::TypeParam))
+            })
+            .collect();
+        self.resolutions.set_builtin_generics(def, generics);
+        self.builtins.insert(Symbol::intern(name), def);
+        def
+    }
+
+    /// Resolves every module of `tree`. Items are visible throughout their
+    /// module, and `use` declarations may refer to each other in any order.
+    pub fn resolve_modules(&mut self, tree: &ModuleTree) -> Resolutions {
+        self.namespaces = tree.modules().map(|_| HashMap::new()).collect();
+        for (id, _) in tree.modules() {
+            self.collect_items(tree, id);
+        }
+        self.resolve_imports(tree);
+        self.check_import_cycles(tree);
+        for (id, module) in tree.modules() {
+            self.file_id = module.program.file_id;
+            self.resolve_paths(tree, id);
+            let scope = self.namespaces[id.0]
+                .iter()
+                .map(|(&name, binding)| (name, binding.def))
+                .collect();
+            self.scopes.push(scope);
+            self.visit_program(&module.program);
+            self.scopes.pop();
+        }
+        std::mem::take(&mut self.resolutions)
+    }
+
+    fn collect_items(&mut self, tree: &ModuleTree, id: ModuleId) {
+        let module = tree.module(id);
+        self.file_id = module.program.file_id;
+        for item in &module.program.items {
+            match &item.inner {
+                ItemKind::Func(func) => {
+                    let def = self.add_def(&func.name, DefKind::Func);
+                    self.bind(id, &func.name, def, func.is_pub);
+                }
+                ItemKind::Mod(decl) => {
+                    let def = self.add_def(&decl.name, DefKind::Mod);
+                    if let Some(&child) = module.children.get(&decl.name.inner) {
+                        self.module_defs.insert(def, child);
+                    }
+                    self.bind(id, &decl.name, def, decl.is_pub);
+                }
+                ItemKind::Trait(decl) => {
+                    let def = self.add_def(&decl.name, DefKind::Trait);
+                    self.bind(id, &decl.name, def, decl.is_pub);
+                }
+                ItemKind::Use(_) | ItemKind::Impl(_) | ItemKind::StaticAssert(_) => {}
+            }
+        }
+    }
+
+    fn add_def(&mut self, name: &Spanned<Symbol>, kind: DefKind) -> DefId {
+        self.resolutions.add_def(Def {
+            name: name.inner,
+            kind,
+            span: name.span,
+            file_id: self.file_id,
+        })
+    }
+
+    /// Binds `name` at the top level of `module`, reporting a conflict with
+    /// an earlier binding. The earlier one stays visible.
+    fn bind(&mut self, module: ModuleId, name: &Spanned<Symbol>, def: DefId, is_pub: bool) {
+        if let Some(previous) = self.namespaces[module.0].get(&name.inner) {
+            self.errors.push(ResolveError::DuplicateDefinition {
+                name: name.inner.to_string(),
+                kind: self.resolutions.def(def).kind,
+                span: name.span,
+                previous: previous.span,
+                file_id: self.file_id,
+            });
+            return;
+        }
+        self.namespaces[module.0].insert(
+            name.inner,
+            Binding {
+                def,
+                span: name.span,
+                is_pub,
+            },
+        );
+    }
+
+    /// Resolves `use` declarations until none makes progress. Whatever is
+    /// left cannot be resolved, including imports that depend on each other.
+    fn resolve_imports(&mut self, tree: &ModuleTree) {
+        let mut pending: Vec<(ModuleId, &UseDecl)> = tree
+            .modules()
+            .flat_map(|(id, module)| {
+                module
+                    .program
+                    .items
+                    .iter()
+                    .filter_map(move |item| match &item.inner {
+                        ItemKind::Use(decl) => Some((id, decl)),
+                        _ => None,
+                    })
+            })
+            .collect();
+
+        let mut stuck = false;
+        while !pending.is_empty() {
+            let blocking: HashSet<(ModuleId, Symbol)> = if stuck {
+                HashSet::new()
+            } else {
+                pending
+                    .iter()
+                    .map(|(id, decl)| (*id, decl.name().inner))
+                    .collect()
+            };
+            let before = pending.len();
+            let mut remaining = Vec::new();
+            for (id, decl) in pending {
+                self.file_id = tree.module(id).program.file_id;
+                match self.resolve_path(tree, id, &decl.path, &blocking) {
+                    ImportResult::Resolved(segments, source) => {
+                        for &(span, def) in &segments {
+                            self.resolutions.add_use(self.file_id, span, def);
+                        }
+                        if source != id {
+                            let span = decl.path[0].span.merge(decl.name().span);
+                            self.imports.entry(id).or_default().push((source, span));
+                        }
+                        let (_, def) = segments[segments.len() - 1];
+                        self.bind(id, decl.name(), def, decl.is_pub);
+                    }
+                    ImportResult::Blocked => remaining.push((id, decl)),
+                    ImportResult::Failed(err) => self.errors.push(err),
+                }
+            }
+            stuck = remaining.len() == before;
+            pending = remaining;
+        }
+    }
+
+    /// Resolves `path`, of a `use` or an expression in module `importer`,
+    /// segment by segment from the names of `importer`.
+    fn resolve_path(
+        &self,
+        tree: &ModuleTree,
+        importer: ModuleId,
+        path: &[Spanned<Symbol>],
+        blocking: &HashSet<(ModuleId, Symbol)>,
+    ) -> ImportResult {
+        let mut module = importer;
+        let mut searched = None;
+        let mut segments: Vec<(Span, DefId)> = Vec::new();
+        for (i, segment) in path.iter().enumerate() {
+            if i == 0 && segment.inner == sym::CRATE && path.len() > 1 {
+                module = tree.root();
+                searched = Some(tree.path(module));
+                continue;
+            }
+            if let Some(&(prev_span, prev)) = segments.last() {
+                let Some(&next) = self.module_defs.get(&prev) else {
+                    let def = self.resolutions.def(prev);
+                    return ImportResult::Failed(ResolveError::NotAModule {
+                        name: def.name.to_string(),
+                        kind: def.kind,
+                        span: prev_span,
+                        file_id: self.file_id,
+                    });
+                };
+                module = next;
+                searched = Some(tree.path(module));
+            }
+            let Some(binding) = self.namespaces[module.0].get(&segment.inner) else {
+                if blocking.contains(&(module, segment.inner)) {
+                    return ImportResult::Blocked;
+                }
+                return ImportResult::Failed(ResolveError::UnresolvedImport {
+                    path: join_path(path),
+                    name: segment.inner.to_string(),
+                    module: searched,
+                    span: segment.span,
+                    file_id: self.file_id,
+                });
+            };
+            // private names are visible in their module and its descendants
+            if !binding.is_pub && !tree.is_ancestor(module, importer) {
+                let def = self.resolutions.def(binding.def);
+                return ImportResult::Failed(ResolveError::PrivateItem {
+                    name: def.name.to_string(),
+                    kind: def.kind,
+                    span: segment.span,
+                    def_span: def.span,
+                    def_file: def.file_id,
+                    file_id: self.file_id,
+                });
+            }
+            segments.push((segment.span, binding.def));
+        }
+        ImportResult::Resolved(segments, module)
+    }
+
+    /// Resolves the path expressions of module `id`, like `math::sub`.
+    /// Locals never shadow them, since they name items of modules.
+    fn resolve_paths(&mut self, tree: &ModuleTree, id: ModuleId) {
+        let mut finder = PathFinder::default();
+        finder.visit_program(&tree.module(id).program);
+        for (path, span) in finder.paths {
+            match self.resolve_path(tree, id, &path, &HashSet::new()) {
+                ImportResult::Resolved(segments, _) => {
+                    for &(span, def) in &segments {
+                        self.resolutions.add_use(self.file_id, span, def);
+                    }
+                    let (_, def) = segments[segments.len() - 1];
+                    self.resolutions.add_path(self.file_id, span, def);
+                    if self.resolutions.def(def).kind == DefKind::Mod {
+                        self.errors.push(ResolveError::ExpectedValue {
+                            name: join_path(&path),
+                            span,
+                            file_id: self.file_id,
+                        });
+                    }
+                }
+                ImportResult::Failed(ResolveError::UnresolvedImport {
+                    name,
+                    module,
+                    span,
+                    file_id,
+                    ..
+                }) => self.errors.push(ResolveError::UnresolvedPath {
+                    name,
+                    module,
+                    span,
+                    file_id,
+                }),
+                ImportResult::Failed(err) => self.errors.push(err),
+                ImportResult::Blocked => unreachable!("imports are resolved"),
+            }
+        }
+    }
+
+    /// Reports every cycle in the graph of imports between modules.
+    fn check_import_cycles(&mut self, tree: &ModuleTree) {
+        let mut visited = HashSet::new();
+        let mut stack = Vec::new();
+        for (id, _) in tree.modules() {
+            self.find_cycles(tree, id, &mut visited, &mut stack);
+        }
+    }
+
+    /// Depth-first search from `module`. `stack` holds the modules on the
+    /// current path, each with the `use` that leads to the next one.
+    fn find_cycles(
+        &mut self,
+        tree: &ModuleTree,
+        module: ModuleId,
+        visited: &mut HashSet<ModuleId>,
+        stack: &mut Vec<(ModuleId, Span)>,
+    ) {
+        if !visited.insert(module) {
+            return;
+        }
+        let edges = self.imports.get(&module).cloned().unwrap_or_default();
+        for (target, span) in edges {
+            stack.push((module, span));
+            if let Some(start) = stack.iter().position(|(m, _)| *m == target) {
+                let cycle = stack[start..]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(from, span))| {
+                        let to = stack.get(start + i + 1).map_or(target, |(to, _)| *to);
+                        ImportEdge {
+                            from: tree.path(from),
+                            to: tree.path(to),
+                            span,
+                            file_id: tree.module(from).program.file_id,
+                        }
+                    })
+                    .collect();
+                self.errors.push(ResolveError::ImportCycle { cycle });
+            } else {
+                self.find_cycles(tree, target, visited, stack);
+            }
+            stack.pop();
+        }
+    }
+
+    fn define(&mut self, name: &Spanned<Symbol>, kind: DefKind) -> DefId {
+        let id = self.resolutions.add_def(Def {
+            name: name.inner,
+            kind,
+            span: name.span,
+            file_id: self.file_id,
+        });
+        self.scopes
+            .last_mut()
+            .expect("resolver scope stack is empty")
+            .insert(name.inner, id);
+        id
+    }
+
+    /// Defines `name` in the innermost scope, reporting a conflict with an
+    /// earlier definition in that same scope. The earlier one stays visible.
+    fn define_unique(&mut self, name: &Spanned<Symbol>, kind: DefKind) -> DefId {
+        let scope = self.scopes.last().expect("resolver scope stack is empty");
+        let Some(&previous) = scope.get(&name.inner) else {
+            return self.define(name, kind);
+        };
+        self.errors.push(ResolveError::DuplicateDefinition {
+            name: name.inner.to_string(),
+            kind,
+            span: name.span,
+            previous: self.resolutions.def(previous).span,
+            file_id: self.file_id,
+        });
+        self.resolutions.add_def(Def {
+            name: name.inner,
+            kind,
+            span: name.span,
+            file_id: self.file_id,
+        })
+    }
+
+    /// Defines the names bound by `pattern`. A name bound by several
+    /// alternatives of an or-pattern is one definition, declared at each of
+    /// them.
+    fn define_pattern(&mut self, pattern: &Pattern) {
+        let alternatives = visit::pattern_alternatives(pattern);
+        let bindings: Vec<Vec<Spanned<Symbol>>> =
+            alternatives.iter().map(visit::pattern_bindings).collect();
+        let mut defs: Vec<(Symbol, DefId)> = Vec::new();
+        for name in bindings.iter().flatten() {
+            match defs.iter().find(|(defined, _)| *defined == name.inner) {
+                Some(&(_, id)) => self.resolutions.add_def_span(self.file_id, name.span, id),
+                None => defs.push((name.inner, self.define(name, DefKind::Local))),
+            }
+        }
+        for (name, _) in defs {
+            let (bound, missing): (Vec<_>, Vec<_>) = alternatives
+                .iter()
+                .zip(&bindings)
+                .partition(|(_, names)| names.iter().any(|n| n.inner == name));
+            if missing.is_empty() {
+                continue;
+            }
+            self.errors.push(ResolveError::BindingNotInAllPatterns {
+                bound: bound
+                    .iter()
+                    .flat_map(|(_, names)| names.iter().filter(|n| n.inner == name))
+                    .map(|n| n.span)
+                    .collect(),
+                missing: missing.iter().map(|(alt, _)| alt.span).collect(),
+                name: name.to_string(),
+                file_id: self.file_id,
+            });
+        }
+    }
+
+    fn lookup(&mut self, name: Symbol, span: Span) {
+        let in_scope = self
+            .scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(depth, scope)| scope.get(&name).map(|&id| (depth, id)));
+        if let Some((depth, id)) = in_scope {
+            self.capture(depth, id);
+        }
+        let found = in_scope
+            .map(|(_, id)| id)
+            .or_else(|| self.builtins.get(&name).copied());
+        match found {
+            Some(id) if self.resolutions.def(id).kind == DefKind::Mod => {
+                self.resolutions.add_use(self.file_id, span, id);
+                self.errors.push(ResolveError::ExpectedValue {
+                    name: name.to_string(),
+                    span,
+                    file_id: self.file_id,
+                });
+            }
+            Some(id) => self.resolutions.add_use(self.file_id, span, id),
+            None => self.errors.push(ResolveError::UnresolvedName {
+                name: name.to_string(),
+                similar: self.similar_name(name, |kind| kind != DefKind::Mod),
+                span,
+                file_id: self.file_id,
+            }),
+        }
+    }
+
+    /// The name in scope of a definition of a kind `kinds` accepts that
+    /// `name` looks like a misspelling of.
+    fn similar_name(&self, name: Symbol, kinds: impl Fn(DefKind) -> bool) -> Option<String> {
+        let names: Vec<String> = self
+            .scopes
+            .iter()
+            .flatten()
+            .chain(&self.builtins)
+            .filter(|&(_, &id)| kinds(self.resolutions.def(id).kind))
+            .map(|(name, _)| name.to_string())
+            .collect();
+        find_similar(&name.to_string(), names.iter().map(String::as_str)).map(str::to_string)
+    }
+
+    /// Records the variable `id`, found in the scope at `depth`, as a
+    /// capture of every closure being resolved that it is defined outside
+    /// of.
+    fn capture(&mut self, depth: usize, id: DefId) {
+        if !matches!(
+            self.resolutions.def(id).kind,
+            DefKind::Local | DefKind::Param
+        ) {
+            return;
+        }
+        for &(closure, base) in &self.closures {
+            if depth < base {
+                self.resolutions.add_capture(closure, id);
+            }
+        }
+    }
+
+    /// Defines a type parameter of the current function. Type parameters live
+    /// apart from the value scopes, so `fn f<T>(T: T)` is fine.
+    fn define_generic(&mut self, name: &Spanned<Symbol>) {
+        let kind = DefKind::TypeParam;
+        if let Some(&previous) = self
+            .generics
+            .iter()
+            .find(|&&id| self.resolutions.def(id).name == name.inner)
+        {
+            self.errors.push(ResolveError::DuplicateDefinition {
+                name: name.inner.to_string(),
+                kind,
+                span: name.span,
+                previous: self.resolutions.def(previous).span,
+                file_id: self.file_id,
+            });
+        }
+        let id = self.resolutions.add_def(Def {
+            name: name.inner,
+            kind,
+            span: name.span,
+            file_id: self.file_id,
+        });
+        self.generics.push(id);
+    }
+
+    fn resolve_type(&mut self, ty: &Spanned<Type>) {
+        if ty.inner == Type::SelfType && !self.in_trait_or_impl {
+            self.errors.push(ResolveError::UnresolvedType {
+                name: ty.inner.to_string(),
+                span: ty.span,
+                file_id: self.file_id,
+            });
+        }
+        if let Type::Fn { params, ret } = &ty.inner {
+            for param in params {
+                self.resolve_type(param);
+            }
+            self.resolve_type(ret);
+            return;
+        }
+        let Type::Param(name) = &ty.inner else {
+            return;
+        };
+        match self
+            .generics
+            .iter()
+            .find(|&&id| self.resolutions.def(id).name == *name)
+        {
+            Some(&id) => self.resolutions.add_use(self.file_id, ty.span, id),
+            None => self.errors.push(ResolveError::UnresolvedType {
+                name: name.to_string(),
+                span: ty.span,
+                file_id: self.file_id,
+            }),
+        }
+    }
+
+    fn with_scope(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    /// Resolves the types of `params` and defines them in the innermost
+    /// scope.
+    fn define_params(&mut self, params: &[Spanned<Param>]) {
+        for param in params {
+            match &param.inner {
+                Param::SelfParam => {
+                    if !self.in_trait_or_impl {
+                        self.errors.push(ResolveError::SelfOutsideMethod {
+                            span: param.span,
+                            file_id: self.file_id,
+                        });
+                    }
+                    let name = Spanned::new(sym::SELF, param.span);
+                    self.define_unique(&name, DefKind::Param);
+                }
+                Param::Named { name, ty, .. } => {
+                    if let Some(ty) = ty {
+                        self.resolve_type(ty);
+                    }
+                    self.define_unique(name, DefKind::Param);
+                }
+            }
+        }
+    }
+
+    fn resolve_trait(&mut self, decl: &TraitDecl) {
+        let mut methods: HashMap<Symbol, Span> = HashMap::new();
+        for method in &decl.methods {
+            let name = &method.inner.name;
+            match methods.get(&name.inner) {
+                Some(&previous) => self.errors.push(ResolveError::DuplicateDefinition {
+                    name: name.inner.to_string(),
+                    kind: DefKind::Method,
+                    span: name.span,
+                    previous,
+                    file_id: self.file_id,
+                }),
+                None => {
+                    methods.insert(name.inner, name.span);
+                }
+            }
+            self.add_def(name, DefKind::Method);
+            self.with_scope(|this| {
+                this.define_params(&method.inner.params);
+                if let Some(ty) = &method.inner.ty {
+                    this.resolve_type(ty);
+                }
+            });
+        }
+    }
+
+    fn resolve_impl(&mut self, decl: &ImplDecl) {
+        let name = &decl.trait_name;
+        let found = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.inner).copied());
+        match found {
+            Some(id) if self.resolutions.def(id).kind == DefKind::Trait => {
+                self.resolutions.add_use(self.file_id, name.span, id);
+            }
+            Some(id) => self.errors.push(ResolveError::ExpectedTrait {
+                name: name.inner.to_string(),
+                kind: self.resolutions.def(id).kind,
+                span: name.span,
+                file_id: self.file_id,
+            }),
+            None => self.errors.push(ResolveError::UnresolvedName {
+                name: name.inner.to_string(),
+                similar: self.similar_name(name.inner, |kind| kind == DefKind::Trait),
+                span: name.span,
+                file_id: self.file_id,
+            }),
+        }
+        self.resolve_type(&decl.self_ty);
+        let mut methods: HashMap<Symbol, Span> = HashMap::new();
+        for method in &decl.methods {
+            let name = &method.name;
+            match methods.get(&name.inner) {
+                Some(&previous) => self.errors.push(ResolveError::DuplicateDefinition {
+                    name: name.inner.to_string(),
+                    kind: DefKind::Method,
+                    span: name.span,
+                    previous,
+                    file_id: self.file_id,
+                }),
+                None => {
+                    methods.insert(name.inner, name.span);
+                }
+            }
+            self.add_def(name, DefKind::Method);
+            self.in_trait_or_impl = true;
+            self.visit_func(method);
+        }
+    }
+}
+
+/// Collects the path expressions of a program, with their spans.
+#[derive(Default)]
+struct PathFinder {
+    paths: Vec<(Vec<Spanned<Symbol>>, Span)>,
+}
+
+impl Visitor for PathFinder {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Path(path) = &expr.inner {
+            self.paths.push((path.clone(), expr.span));
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+impl Visitor for Resolver {
+    fn visit_item(&mut self, item: &Item) {
+        match &item.inner {
+            ItemKind::Trait(decl) => {
+                self.in_trait_or_impl = true;
+                self.resolve_trait(decl);
+            }
+            ItemKind::Impl(decl) => {
+                // `Self` in the implemented type has nothing to refer to
+                self.in_trait_or_impl = false;
+                self.resolve_impl(decl);
+            }
+            _ => {
+                self.in_trait_or_impl = false;
+                visit::walk_item(self, item);
+            }
+        }
+        self.in_trait_or_impl = false;
+    }
+
+    fn visit_func(&mut self, func: &Func) {
+        for name in &func.generics {
+            self.define_generic(name);
+        }
+        self.with_scope(|this| {
+            this.define_params(&func.params);
+            if let Some(ty) = &func.ty {
+                this.resolve_type(ty);
+            }
+            visit::walk_func(this, func);
+        });
+        self.generics.clear();
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.inner {
+            ExprKind::Var(name) => self.lookup(*name, expr.span),
+            ExprKind::Let {
+                name, ty, value, ..
+            } => {
+                if let Some(ty) = ty {
+                    self.resolve_type(ty);
+                }
+                if let Some(value) = value {
+                    self.visit_expr(value);
+                }
+                self.define(name, DefKind::Local);
+            }
+            ExprKind::Cast { expr, ty } => {
+                self.visit_expr(expr);
+                self.resolve_type(ty);
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                self.visit_expr(cond);
+                self.with_scope(|this| this.visit_expr(then_branch));
+                if let Some(else_branch) = else_branch {
+                    self.with_scope(|this| this.visit_expr(else_branch));
+                }
+            }
+            ExprKind::Assign { name, value } => {
+                self.visit_expr(value);
+                self.lookup(name.inner, name.span);
+            }
+            ExprKind::Block { .. } => self.with_scope(|this| visit::walk_expr(this, expr)),
+            ExprKind::Closure { params, body } => {
+                let name = Spanned::new(sym::CLOSURE, expr.span);
+                let closure = self.add_def(&name, DefKind::Closure);
+                self.closures.push((closure, self.scopes.len()));
+                self.with_scope(|this| {
+                    this.define_params(params);
+                    this.visit_expr(body);
+                });
+                self.closures.pop();
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.visit_expr(scrutinee);
+                for arm in arms {
+                    self.with_scope(|this| {
+                        this.define_pattern(&arm.pattern);
+                        this.visit_expr(&arm.body);
+                    });
+                }
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! What the tests of name resolution share: loading programs and resolving
+//! their names.
+
+// each test uses some of the helpers
+#![allow(dead_code)]
+
+use ginto_diag::{FileId, SourceManager, Span};
+use ginto_resolve::{
+    LoadError, ModuleTree, Resolutions, ResolveError, Resolver, load_modules_with,
+};
+use ginto_test_support::{reader, text_in};
+
+pub struct Resolved {
+    pub sm: SourceManager,
+    pub tree: ModuleTree,
+    pub resolutions: Resolutions,
+    pub load_errors: Vec<LoadError>,
+    pub errors: Vec<ResolveError>,
+}
+
+/// The program whose root file `main.ginto` holds `source`, with its names
+/// resolved.
+pub fn resolve(source: &str) -> Resolved {
+    resolve_files(source, &[])
+}
+
+/// The program whose root file `main.ginto` holds `root`, with the modules
+/// of `files`, named after their path, and its names resolved.
+pub fn resolve_files(root: &str, files: &[(&str, &str)]) -> Resolved {
+    let mut sm = SourceManager::new();
+    let root = sm.add_file("main.ginto".to_string(), root.to_string());
+    let (tree, load_errors) = load_modules_with(&mut sm, root, reader(files));
+    let mut resolver = Resolver::new();
+    let resolutions = resolver.resolve_modules(&tree);
+    let errors = resolver.errors().to_vec();
+    Resolved {
+        sm,
+        tree,
+        resolutions,
+        load_errors,
+        errors,
+    }
+}
+
+impl Resolved {
+    /// The text at `span` of the file of the root module.
+    pub fn text(&self, span: Span) -> &str {
+        let file = self.tree.module(self.tree.root()).program.file_id;
+        text_in(&self.sm, file, span)
+    }
+
+    /// The last occurrence of `needle` in the file named `file`.
+    pub fn find(&self, file: &str, needle: &str) -> (FileId, Span) {
+        let id = self.sm.file_id(file).unwrap();
+        let start = self.sm.get_file(id).unwrap().source.rfind(needle).unwrap();
+        (id, Span::from_range(start..start + needle.len()))
+    }
+
+    /// The file and text of the definition the last occurrence of `needle`
+    /// in `file` refers to.
+    pub fn definition_of(&self, file: &str, needle: &str) -> (&str, &str) {
+        let (id, span) = self.find(file, needle);
+        let def = self.resolutions.resolve(id, span).unwrap();
+        let def = self.resolutions.def(def);
+        let name = &self.sm.get_file(def.file_id).unwrap().name;
+        (name, text_in(&self.sm, def.file_id, def.span))
+    }
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use common::resolve_files;
+use ginto_diag::DiagnosticConvertible;
+use ginto_resolve::ResolveError;
+use ginto_test_support::labels_in;
+
+/// The modules of each import cycle of the program.
+fn cycles(root: &str, files: &[(&str, &str)]) -> Vec<Vec<(String, String)>> {
+    resolve_files(root, files)
+        .errors
+        .into_iter()
+        .filter_map(|error| match error {
+            ResolveError::ImportCycle { cycle } => {
+                Some(cycle.into_iter().map(|edge| (edge.from, edge.to)).collect())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn edge(from: &str, to: &str) -> (String, String) {
+    (from.to_string(), to.to_string())
+}
+
+#[test]
+fn modules_importing_from_each_other() {
+    let files = [
+        ("a.ginto", "use crate::b::g\n\npub fn f() -> u64:\n    1\n"),
+        ("b.ginto", "use crate::a::f\n\npub fn g() -> u64:\n    2\n"),
+    ];
+    assert_eq!(
+        cycles("mod a\nmod b\n", &files),
+        [[edge("crate::a", "crate::b"), edge("crate::b", "crate::a")]]
+    );
+}
+
+#[test]
+fn every_edge_of_a_longer_cycle_is_reported() {
+    let files = [
+        ("a.ginto", "use crate::b::g\n\npub fn f() -> u64:\n    1\n"),
+        ("b.ginto", "use crate::c::h\n\npub fn g() -> u64:\n    2\n"),
+        ("c.ginto", "use crate::a::f\n\npub fn h() -> u64:\n    3\n"),
+    ];
+    assert_eq!(
+        cycles("mod a\nmod b\nmod c\n", &files),
+        [[
+            edge("crate::a", "crate::b"),
+            edge("crate::b", "crate::c"),
+            edge("crate::c", "crate::a"),
+        ]]
+    );
+}
+
+#[test]
+fn imports_in_one_direction_are_fine() {
+    let files = [
+        (
+            "a.ginto",
+            "use crate::b::g\n\npub fn f() -> u64:\n    g()\n",
+        ),
+        (
+            "b.ginto",
+            "use crate::c::h\n\npub fn g() -> u64:\n    h()\n",
+        ),
+        ("c.ginto", "pub fn h() -> u64:\n    3\n"),
+    ];
+    assert!(cycles("mod a\nmod b\nmod c\n", &files).is_empty());
+    // a diamond is not a cycle either
+    let files = [
+        (
+            "a.ginto",
+            "use crate::c::h\n\npub fn f() -> u64:\n    h()\n",
+        ),
+        (
+            "b.ginto",
+            "use crate::c::h\n\npub fn g() -> u64:\n    h()\n",
+        ),
+        ("c.ginto", "pub fn h() -> u64:\n    3\n"),
+    ];
+    assert!(cycles("mod a\nmod b\nmod c\nuse a::f\nuse b::g\n", &files).is_empty());
+}
+
+#[test]
+fn a_module_and_its_parent_can_form_a_cycle() {
+    let files = [("a.ginto", "use crate::g\n\npub fn f() -> u64:\n    1\n")];
+    assert_eq!(
+        cycles("mod a\nuse a::f\n\npub fn g() -> u64:\n    f()\n", &files),
+        [[edge("crate", "crate::a"), edge("crate::a", "crate")]]
+    );
+}
+
+#[test]
+fn cycles_label_each_import() {
+    let files = [
+        ("a.ginto", "use crate::b::g\n\npub fn f() -> u64:\n    1\n"),
+        ("b.ginto", "use crate::a::f\n\npub fn g() -> u64:\n    2\n"),
+    ];
+    let resolved = resolve_files("mod a\nmod b\n", &files);
+    let diagnostic = resolved.errors[0].clone().into_diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "import cycle between modules `crate::a` -> `crate::b` -> `crate::a`"
+    );
+    assert_eq!(
+        labels_in(&resolved.sm, &diagnostic),
+        [
+            (
+                "crate::b::g",
+                "`crate::a` imports from `crate::b` here".to_string()
+            ),
+            (
+                "crate::a::f",
+                "`crate::b` imports from `crate::a` here".to_string()
+            ),
+        ]
+    );
+    assert!(diagnostic.labels[0].is_primary);
+    assert!(!diagnostic.labels[1].is_primary);
+    assert_eq!(
+        diagnostic.notes,
+        ["modules cannot depend on each other in a cycle"]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn module_files_that_include_themselves_stop_loading() {
+    use std::fs;
+
+    use ginto_diag::SourceManager;
+    use ginto_resolve::{LoadError, load_modules};
+
+    let dir = std::env::temp_dir().join(format!("ginto-resolve-cycles-{}", std::process::id()));
+    fs::create_dir_all(dir.join("a")).unwrap();
+    fs::write(dir.join("main.ginto"), "mod a\n").unwrap();
+    fs::write(dir.join("a.ginto"), "mod b\n").unwrap();
+    let link = dir.join("a/b.ginto");
+    let _ = fs::remove_file(&link);
+    std::os::unix::fs::symlink(dir.join("main.ginto"), &link).unwrap();
+
+    let mut sm = SourceManager::new();
+    let root = sm.add_file_from_path(dir.join("main.ginto")).unwrap();
+    let (tree, errors) = load_modules(&mut sm, root);
+    assert_eq!(tree.modules().count(), 2);
+    let [LoadError::RecursiveModule { path, chain }] = &errors[..] else {
+        panic!("expected a recursive module: {errors:#?}");
+    };
+    assert!(path.ends_with("b.ginto"), "{path}");
+    let names: Vec<_> = chain.iter().map(|(name, _, _)| name.as_str()).collect();
+    assert_eq!(names, ["a", "b"]);
+
+    let diagnostic = errors[0].clone().into_diagnostic();
+    assert!(diagnostic.message.ends_with("b.ginto` includes itself"));
+    let labels: Vec<_> = diagnostic
+        .labels
+        .iter()
+        .map(|label| label.message.clone().unwrap())
+        .collect();
+    assert_eq!(labels, ["declares `mod a` here", "declares `mod b` here"]);
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use common::resolve;
+use ginto_diag::{DiagnosticConvertible, Severity};
+use ginto_resolve::{DefKind, ResolveError};
+use ginto_test_support::labels_in;
+
+/// The names `source` defines more than once, with their kind, the text of
+/// the second definition and of the first.
+fn duplicates(source: &str) -> Vec<(String, DefKind, usize, usize)> {
+    let resolved = resolve(source);
+    resolved
+        .errors
+        .iter()
+        .map(|error| match error {
+            ResolveError::DuplicateDefinition {
+                name,
+                kind,
+                span,
+                previous,
+                ..
+            } => {
+                assert_eq!(resolved.text(*span), name);
+                assert_eq!(resolved.text(*previous), name);
+                assert!(previous.start < span.start);
+                (name.clone(), *kind, previous.start.0, span.start.0)
+            }
+            error => panic!("expected a duplicate definition: {error:?}"),
+        })
+        .collect()
+}
+
+fn names(source: &str) -> Vec<(String, DefKind)> {
+    duplicates(source)
+        .into_iter()
+        .map(|(name, kind, _, _)| (name, kind))
+        .collect()
+}
+
+#[test]
+fn functions_of_a_module_have_distinct_names() {
+    let source = "fn f():\n    ()\n\nfn f():\n    ()\n";
+    assert_eq!(
+        duplicates(source),
+        [("f".to_string(), DefKind::Func, 3, 19)]
+    );
+}
+
+#[test]
+fn items_of_different_kinds_share_a_namespace() {
+    let source = "trait f:\n    fn g(self) -> u64\n\nfn f():\n    ()\n";
+    assert_eq!(names(source), [("f".to_string(), DefKind::Func)]);
+}
+
+#[test]
+fn every_later_definition_is_reported() {
+    let source = "fn f():\n    ()\n\nfn f():\n    ()\n\nfn f():\n    ()\n";
+    let duplicates = duplicates(source);
+    assert_eq!(duplicates.len(), 2);
+    // each points at the first definition
+    assert!(duplicates.iter().all(|(_, _, previous, _)| *previous == 3));
+}
+
+#[test]
+fn parameters_have_distinct_names() {
+    let source = "fn f(x: u64, x: u64) -> u64:\n    x\n";
+    assert_eq!(names(source), [("x".to_string(), DefKind::Param)]);
+}
+
+#[test]
+fn type_parameters_have_distinct_names() {
+    let source = "fn f<T, T>(x: T) -> T:\n    x\n";
+    assert_eq!(names(source), [("T".to_string(), DefKind::TypeParam)]);
+}
+
+#[test]
+fn methods_of_a_trait_have_distinct_names() {
+    let source = "trait Shape:\n    fn area(self) -> u64\n    fn area(self) -> u64\n";
+    assert_eq!(names(source), [("area".to_string(), DefKind::Method)]);
+}
+
+#[test]
+fn locals_may_shadow_each_other() {
+    let source = "fn f() -> u64:\n    let x = 1\n    let x = x + 1\n    x\n";
+    assert!(duplicates(source).is_empty());
+}
+
+#[test]
+fn the_same_name_in_different_scopes_is_fine() {
+    let source = "fn f(x: u64) -> u64:\n    x\n\nfn g(x: u64) -> u64:\n    x\n";
+    assert!(duplicates(source).is_empty());
+}
+
+#[test]
+fn duplicates_label_both_definitions() {
+    let source = "fn f():\n    ()\n\nfn f():\n    ()\n";
+    let resolved = resolve(source);
+    let diagnostic = resolved.errors[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.message, "the name `f` is defined multiple times");
+    let labels = labels_in(&resolved.sm, &diagnostic);
+    assert_eq!(
+        labels,
+        [
+            ("f", "`f` redefined here".to_string()),
+            (
+                "f",
+                "previous definition of the function `f` here".to_string()
+            ),
+        ]
+    );
+    assert!(diagnostic.labels[0].is_primary);
+    assert!(!diagnostic.labels[1].is_primary);
+    assert_eq!(diagnostic.labels[0].span.start.0, 19);
+    assert_eq!(diagnostic.labels[1].span.start.0, 3);
+    assert_eq!(
+        diagnostic.notes,
+        ["`f` must be defined only once in this module"]
+    );
+}
+
+#[test]
+fn the_note_names_the_scope_of_the_duplicate() {
+    let note = |source: &str| resolve(source).errors[0].clone().into_diagnostic().notes;
+    assert_eq!(
+        note("fn f(x: u64, x: u64):\n    ()\n"),
+        ["`x` must be defined only once in this parameter list"]
+    );
+    assert_eq!(
+        note("fn f<T, T>():\n    ()\n"),
+        ["`T` must be defined only once in this type parameter list"]
+    );
+    assert_eq!(
+        note("trait Shape:\n    fn area(self) -> u64\n    fn area(self) -> u64\n"),
+        ["`area` must be defined only once in this trait or `impl` block"]
+    );
+}
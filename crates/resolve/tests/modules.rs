@@ -0,0 +1,224 @@
+mod common;
+
+use common::{resolve, resolve_files};
+use ginto_diag::{DiagnosticConvertible, Symbol};
+use ginto_resolve::{LoadError, ResolveError};
+use ginto_test_support::labels_in;
+
+const MATH: &str = "pub fn add(a: u64, b: u64) -> u64:\n    a + b\n\nfn secret() -> u64:\n    1\n";
+
+#[test]
+fn mod_declarations_load_the_file_named_after_the_module() {
+    let resolved = resolve_files("mod math\n", &[("math.ginto", MATH)]);
+    assert!(
+        resolved.load_errors.is_empty(),
+        "{:#?}",
+        resolved.load_errors
+    );
+    let root = resolved.tree.module(resolved.tree.root());
+    let &math = root.children.get(&Symbol::intern("math")).unwrap();
+    let module = resolved.tree.module(math);
+    assert_eq!(module.parent, Some(resolved.tree.root()));
+    assert_eq!(resolved.text(module.decl.unwrap()), "math");
+    assert_eq!(module.program.items.len(), 2);
+    assert_eq!(resolved.tree.path(math), "crate::math");
+}
+
+#[test]
+fn submodules_live_in_the_directory_of_their_module() {
+    let resolved = resolve_files(
+        "mod math\n",
+        &[
+            ("math.ginto", "pub mod vec\n"),
+            ("math/vec.ginto", "pub fn dot() -> u64:\n    0\n"),
+        ],
+    );
+    assert!(
+        resolved.load_errors.is_empty(),
+        "{:#?}",
+        resolved.load_errors
+    );
+    let paths: Vec<_> = resolved
+        .tree
+        .modules()
+        .map(|(id, _)| resolved.tree.path(id))
+        .collect();
+    assert_eq!(paths, ["crate", "crate::math", "crate::math::vec"]);
+}
+
+#[test]
+fn use_imports_items_of_other_files() {
+    let resolved = resolve_files(
+        "mod math\nuse math::add\n\nfn main() -> u64:\n    add(1, 2)\n",
+        &[("math.ginto", MATH)],
+    );
+    assert!(resolved.errors.is_empty(), "{:#?}", resolved.errors);
+    assert_eq!(
+        resolved.definition_of("main.ginto", "add"),
+        ("math.ginto", "add")
+    );
+}
+
+#[test]
+fn paths_name_items_of_other_files() {
+    let resolved = resolve_files(
+        "mod math\n\nfn main() -> u64:\n    math::add(1, 2)\n",
+        &[("math.ginto", MATH)],
+    );
+    assert!(resolved.errors.is_empty(), "{:#?}", resolved.errors);
+    assert_eq!(
+        resolved.definition_of("main.ginto", "add"),
+        ("math.ginto", "add")
+    );
+}
+
+#[test]
+fn modules_import_from_their_siblings_through_crate() {
+    let resolved = resolve_files(
+        "mod math\nmod geometry\n",
+        &[
+            ("math.ginto", MATH),
+            (
+                "geometry.ginto",
+                "use crate::math::add\n\npub fn perimeter(a: u64) -> u64:\n    add(a, a)\n",
+            ),
+        ],
+    );
+    assert!(resolved.errors.is_empty(), "{:#?}", resolved.errors);
+    assert_eq!(
+        resolved.definition_of("geometry.ginto", "add"),
+        ("math.ginto", "add")
+    );
+}
+
+#[test]
+fn private_items_are_not_visible_to_other_modules() {
+    let resolved = resolve_files("mod math\nuse math::secret\n", &[("math.ginto", MATH)]);
+    let diagnostic = resolved.errors[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.message, "function `secret` is private");
+    assert_eq!(
+        labels_in(&resolved.sm, &diagnostic),
+        [
+            ("secret", "private function".to_string()),
+            (
+                "secret",
+                "the function `secret` is defined here".to_string()
+            ),
+        ]
+    );
+    // the second label is in the module's file
+    let math = resolved.sm.file_id("math.ginto").unwrap();
+    assert_eq!(diagnostic.labels[1].file_id, math);
+}
+
+#[test]
+fn modules_see_the_private_items_of_their_ancestors() {
+    let resolved = resolve_files(
+        "mod math\n\nfn secret() -> u64:\n    1\n",
+        &[("math.ginto", "use crate::secret\n")],
+    );
+    assert!(resolved.errors.is_empty(), "{:#?}", resolved.errors);
+}
+
+#[test]
+fn imports_of_missing_items_name_the_module_searched() {
+    let resolved = resolve_files("mod math\nuse math::sub\n", &[("math.ginto", MATH)]);
+    let diagnostic = resolved.errors[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.message, "unresolved import `math::sub`");
+    assert_eq!(
+        labels_in(&resolved.sm, &diagnostic),
+        [("sub", "no `sub` in `crate::math`".to_string())]
+    );
+}
+
+#[test]
+fn paths_through_items_that_are_not_modules() {
+    let resolved = resolve_files("mod math\nuse math::add::x\n", &[("math.ginto", MATH)]);
+    assert!(matches!(
+        &resolved.errors[..],
+        [ResolveError::NotAModule { name, .. }] if name == "add"
+    ));
+}
+
+#[test]
+fn modules_are_not_values() {
+    let resolved = resolve_files(
+        "mod math\n\nfn main() -> u64:\n    let m = math::add\n    crate::math\n",
+        &[("math.ginto", MATH)],
+    );
+    assert!(matches!(
+        &resolved.errors[..],
+        [ResolveError::ExpectedValue { name, .. }] if name == "crate::math"
+    ));
+}
+
+#[test]
+fn missing_module_files_label_the_declaration() {
+    let resolved = resolve("fn main():\n    ()\n\nmod math\n");
+    let [
+        LoadError::FileNotFound {
+            name, path, span, ..
+        },
+    ] = &resolved.load_errors[..]
+    else {
+        panic!("expected a missing file: {:#?}", resolved.load_errors);
+    };
+    assert_eq!((name.as_str(), path.as_str()), ("math", "math.ginto"));
+    assert_eq!(resolved.text(*span), "math");
+    let diagnostic = resolved.load_errors[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.message, "file not found for module `math`");
+    assert_eq!(
+        labels_in(&resolved.sm, &diagnostic),
+        [("math", "declared here".to_string())]
+    );
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("to create the module `math`, create file \"math.ginto\"")
+    );
+}
+
+#[test]
+fn the_rest_of_the_program_loads_around_a_missing_module() {
+    let resolved = resolve_files("mod gone\nmod math\n", &[("math.ginto", MATH)]);
+    assert_eq!(resolved.load_errors.len(), 1);
+    assert_eq!(resolved.tree.modules().count(), 2);
+}
+
+#[test]
+fn errors_in_module_files_point_into_them() {
+    let resolved = resolve_files(
+        "mod math\n",
+        &[("math.ginto", "fn f() -> u64:\n    missing\n")],
+    );
+    let diagnostic = resolved.errors[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.message, "cannot find `missing` in this scope");
+    let math = resolved.sm.file_id("math.ginto").unwrap();
+    assert_eq!(diagnostic.labels[0].file_id, math);
+}
+
+#[test]
+fn modules_and_their_errors_come_in_the_order_they_are_declared() {
+    let files = [
+        ("a.ginto", "mod c\nfn f(:\n"),
+        ("a/c.ginto", "fn g(:\n"),
+        ("b.ginto", "fn h(:\n"),
+    ];
+    for _ in 0..10 {
+        let resolved = resolve_files("mod a\nmod gone\nmod b\n", &files);
+        let paths: Vec<_> = resolved
+            .tree
+            .modules()
+            .map(|(id, _)| resolved.tree.path(id))
+            .collect();
+        assert_eq!(paths, ["crate", "crate::a", "crate::a::c", "crate::b"]);
+        let files: Vec<_> = resolved
+            .load_errors
+            .iter()
+            .map(|err| {
+                let file = err.clone().into_diagnostic().labels[0].file_id;
+                resolved.sm.get_file(file).unwrap().name.clone()
+            })
+            .collect();
+        assert_eq!(files, ["a.ginto", "a/c.ginto", "main.ginto", "b.ginto"]);
+    }
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::resolve;
+use ginto_diag::{DiagnosticConvertible, Span};
+use ginto_resolve::ResolveError;
+use ginto_test_support::labels_in;
+
+fn arm(pattern: &str) -> String {
+    format!("fn f(n: u64) -> u64:\n    match n:\n        {pattern} -> 0\n        _ -> 1\n")
+}
+
+/// The names bound by only some alternatives, with the text of the
+/// alternatives missing them.
+fn not_in_all(source: &str) -> Vec<(String, Vec<String>)> {
+    let resolved = resolve(source);
+    resolved
+        .errors
+        .iter()
+        .map(|error| match error {
+            ResolveError::BindingNotInAllPatterns { name, missing, .. } => (
+                name.clone(),
+                missing
+                    .iter()
+                    .map(|&span| resolved.text(span).to_string())
+                    .collect(),
+            ),
+            error => panic!("expected a binding not in all patterns: {error:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn alternatives_binding_the_same_names_are_fine() {
+    assert!(not_in_all(&arm("x | x")).is_empty());
+    assert!(not_in_all(&arm("0 | 1 | 2")).is_empty());
+    assert!(not_in_all(&arm("_ | 1")).is_empty());
+}
+
+#[test]
+fn alternatives_that_do_not_bind_a_name() {
+    assert_eq!(
+        not_in_all(&arm("x | 1")),
+        [("x".to_string(), vec!["1".to_string()])]
+    );
+    assert_eq!(
+        not_in_all(&arm("0 | x | _")),
+        [("x".to_string(), vec!["0".to_string(), "_".to_string()])]
+    );
+}
+
+#[test]
+fn alternatives_binding_different_names() {
+    assert_eq!(
+        not_in_all(&arm("a | b")),
+        [
+            ("a".to_string(), vec!["b".to_string()]),
+            ("b".to_string(), vec!["a".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn bindings_not_in_all_patterns_label_every_alternative() {
+    let source = arm("x | 1 | x");
+    let resolved = resolve(&source);
+    let diagnostic = resolved.errors[0].clone().into_diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "variable `x` is not bound in all patterns"
+    );
+    assert_eq!(
+        labels_in(&resolved.sm, &diagnostic),
+        [
+            ("1", "pattern doesn't bind `x`".to_string()),
+            ("x", "variable not in all patterns".to_string()),
+            ("x", "variable not in all patterns".to_string()),
+        ]
+    );
+    assert!(diagnostic.labels[0].is_primary);
+    assert!(diagnostic.labels[1..].iter().all(|label| !label.is_primary));
+    // the two bindings are distinct occurrences
+    assert_ne!(diagnostic.labels[1].span, diagnostic.labels[2].span);
+    assert_eq!(
+        diagnostic.notes,
+        ["every alternative of an or-pattern must bind the same names"]
+    );
+}
+
+#[test]
+fn a_name_bound_by_every_alternative_is_one_variable() {
+    let source = "fn f(n: u64) -> u64:\n    match n:\n        x | x -> x\n";
+    let resolved = resolve(source);
+    assert!(resolved.errors.is_empty(), "{:#?}", resolved.errors);
+    let file = resolved.tree.module(resolved.tree.root()).program.file_id;
+    let start = source.rfind('x').unwrap();
+    let def = resolved
+        .resolutions
+        .resolve(file, Span::from_range(start..start + 1))
+        .unwrap();
+    let declared: Vec<_> = resolved
+        .resolutions
+        .def_spans_of(def)
+        .map(|(_, span)| span.start.0)
+        .collect();
+    assert_eq!(declared.len(), 2);
+    assert!(
+        declared
+            .iter()
+            .all(|&start| source[start..].starts_with('x'))
+    );
+}
+
+#[test]
+fn bindings_are_scoped_to_their_arm() {
+    let resolved = resolve("fn f(n: u64) -> u64:\n    match n:\n        x -> x\n        _ -> x\n");
+    assert!(matches!(
+        &resolved.errors[..],
+        [ResolveError::UnresolvedName { name, .. }] if name == "x"
+    ));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::resolve;
+use ginto_diag::DiagnosticConvertible;
+use ginto_resolve::{DefKind, ResolveError};
+use ginto_test_support::labels_in;
+
+#[test]
+fn impls_name_a_trait() {
+    let source =
+        "fn Shape():\n    ()\n\nimpl Shape for u64:\n    fn area(self) -> u64:\n        self\n";
+    let resolved = resolve(source);
+    let [ResolveError::ExpectedTrait { kind, span, .. }] = &resolved.errors[..] else {
+        panic!("expected a trait: {:#?}", resolved.errors);
+    };
+    assert_eq!(*kind, DefKind::Func);
+    assert_eq!(resolved.text(*span), "Shape");
+    assert_eq!(
+        resolved.errors[0].clone().into_diagnostic().message,
+        "expected trait, found function `Shape`"
+    );
+}
+
+#[test]
+fn self_parameters_are_for_methods() {
+    let resolved = resolve("fn area(self) -> u64:\n    1\n");
+    let diagnostic = resolved.errors[0].clone().into_diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "`self` parameter is only allowed in methods"
+    );
+    assert_eq!(
+        labels_in(&resolved.sm, &diagnostic),
+        [("self", "not a method".to_string())]
+    );
+}
+
+#[test]
+fn self_type_is_for_traits_and_impls() {
+    let resolved = resolve("fn f(x: Self) -> u64:\n    1\n");
+    let diagnostic = resolved.errors[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.message, "cannot find type `Self` in this scope");
+    assert_eq!(
+        diagnostic.notes,
+        ["`Self` is only available in traits and `impl` blocks"]
+    );
+}
+
+#[test]
+fn methods_see_self_and_self_type() {
+    let source = "trait Twice:\n    fn twice(self) -> Self\n\nimpl Twice for u64:\n    fn twice(self) -> Self:\n        self + self\n";
+    let resolved = resolve(source);
+    assert!(resolved.errors.is_empty(), "{:#?}", resolved.errors);
+}
@@ -1,14 +1,52 @@
-use ginto_diag::Spanned;
+use core::fmt;
 
-#[derive(Clone, Debug)]
+use ginto_diag::{FileId, Spanned, Symbol};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Type {
     Unit,
     Bool,
     U64,
     I64,
+    F64,
+    Str,
+    /// A type parameter of the enclosing function, like `T` in
+    /// `fn id<T>(x: T) -> T`.
+    Param(Symbol),
+    /// `Self`, the type a trait is implemented for.
+    SelfType,
+    /// `fn(params) -> ret`, the type of functions and closures taking
+    /// `params`. Without `-> ret` the function returns `()`.
+    Fn {
+        params: Vec<Spanned<Type>>,
+        ret: Box<Spanned<Type>>,
+    },
 }
 
-#[derive(Clone, Debug)]
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Unit => write!(f, "()"),
+            Type::Bool => write!(f, "bool"),
+            Type::U64 => write!(f, "u64"),
+            Type::Str => write!(f, "str"),
+            Type::I64 => write!(f, "i64"),
+            Type::F64 => write!(f, "f64"),
+            Type::Param(name) => write!(f, "{}", name),
+            Type::SelfType => write!(f, "Self"),
+            Type::Fn { params, ret } => {
+                let params = params
+                    .iter()
+                    .map(|param| param.inner.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "fn({}) -> {}", params, ret.inner)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BinOp {
     Add,
     Sub,
@@ -36,19 +74,56 @@ impl BinOp {
             BinOp::Mul | BinOp::Div | BinOp::Mod => (11, 12),
         }
     }
+
+    /// The left binding power of `as`, which binds tighter than every
+    /// binary operator but looser than prefix operators.
+    pub const CAST_BINDING_POWER: u8 = 13;
 }
 
-#[derive(Clone, Debug)]
+impl fmt::Display for BinOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Mod => "mod",
+            BinOp::Equal => "=",
+            BinOp::NotEq => "<>",
+            BinOp::Less => "<",
+            BinOp::Le => "<=",
+            BinOp::Greater => ">",
+            BinOp::Ge => ">=",
+            BinOp::Or => "||",
+            BinOp::And => "&&",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum UnaryOp {
     Neg,
     Not,
 }
 
+impl fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnaryOp::Neg => write!(f, "-"),
+            UnaryOp::Not => write!(f, "not "),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ExprKind {
     Int(u64),
+    Float(f64),
     Bool(bool),
     Unit,
+    /// A string literal, with the variables interpolated into it.
+    Str(Vec<StrPart>),
 
     Unary {
         op: Spanned<UnaryOp>,
@@ -61,39 +136,235 @@ pub enum ExprKind {
         rhs: Box<Expr>,
     },
 
+    /// `expr as ty`, the only way to convert between integer types.
+    Cast {
+        expr: Box<Expr>,
+        ty: Spanned<Type>,
+    },
+
     Let {
-        name: Spanned<String>,
+        name: Spanned<Symbol>,
+        mutable: bool,
         ty: Option<Spanned<Type>>,
-        value: Box<Expr>,
+        /// `None` for a declaration that is assigned later.
+        value: Option<Box<Expr>>,
     },
 
     Assign {
-        name: Spanned<String>,
+        name: Spanned<Symbol>,
         value: Box<Expr>,
     },
 
-    Ident {
+    Block {
         exprs: Vec<Expr>,
-        tail: Box<Expr>,
+        tail: Option<Box<Expr>>,
+    },
+
+    Call {
+        callee: Box<Expr>,
+        args: Vec<Expr>,
+    },
+    /// `receiver.method(args)`, calling a method of a trait implemented for
+    /// the receiver's type.
+    MethodCall {
+        receiver: Box<Expr>,
+        method: Spanned<Symbol>,
+        args: Vec<Expr>,
+    },
+
+    Match {
+        scrutinee: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
+
+    Return(Option<Box<Expr>>),
+
+    If {
+        cond: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Option<Box<Expr>>,
+    },
+
+    /// `while cond: body`, which runs `body` for as long as `cond` holds
+    /// and evaluates to `()`.
+    While {
+        cond: Box<Expr>,
+        body: Box<Expr>,
     },
 
-    Var(String),
+    Var(Symbol),
+    /// `a::b::f`, naming an item of another module.
+    Path(Vec<Spanned<Symbol>>),
+
+    /// Where the parser expected an expression and reported that none was
+    /// there. Programs with syntax errors are never checked further, so
+    /// only tools see it.
+    Error,
+
+    /// `|params| body`, a function value that captures the variables of
+    /// the enclosing function it uses, by value.
+    Closure {
+        params: Vec<Spanned<Param>>,
+        body: Box<Expr>,
+    },
 }
 
 pub type Expr = Spanned<ExprKind>;
 
+/// A piece of a string literal.
+#[derive(Clone, Debug)]
+pub enum StrPart {
+    /// Text, with its escapes resolved.
+    Lit(String),
+    /// An interpolation, like `{name}` in `"hello {name}"`. Only variables
+    /// can be interpolated.
+    Expr(Expr),
+}
+
+#[derive(Clone, Debug)]
+pub enum PatternKind {
+    Wildcard,
+    Bind(Symbol),
+    Int(i128),
+    Bool(bool),
+    Unit,
+    /// `p | q`, matching when any alternative matches. Every alternative
+    /// must bind the same names.
+    Or(Vec<Pattern>),
+}
+
+pub type Pattern = Spanned<PatternKind>;
+
+#[derive(Clone, Debug)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Expr,
+}
+
 #[derive(Clone, Debug)]
 pub enum Param {
+    /// The `self` receiver of a method.
+    SelfParam,
     Named {
-        name: Spanned<String>,
+        name: Spanned<Symbol>,
+        mutable: bool,
         ty: Option<Spanned<Type>>,
     },
 }
 
 #[derive(Clone, Debug)]
 pub struct Func {
-    pub name: Spanned<String>,
+    pub is_pub: bool,
+    pub name: Spanned<Symbol>,
+    pub generics: Vec<Spanned<Symbol>>,
     pub params: Vec<Spanned<Param>>,
     pub ty: Option<Spanned<Type>>,
     pub body: Expr,
 }
+
+/// `trait Name:` followed by the signatures of its methods.
+#[derive(Clone, Debug)]
+pub struct TraitDecl {
+    pub is_pub: bool,
+    pub name: Spanned<Symbol>,
+    pub methods: Vec<Spanned<MethodSig>>,
+}
+
+/// A method declared by a trait, without a body.
+#[derive(Clone, Debug)]
+pub struct MethodSig {
+    pub name: Spanned<Symbol>,
+    pub params: Vec<Spanned<Param>>,
+    pub ty: Option<Spanned<Type>>,
+}
+
+/// `impl Trait for Type:` followed by the trait's methods.
+#[derive(Clone, Debug)]
+pub struct ImplDecl {
+    pub trait_name: Spanned<Symbol>,
+    pub self_ty: Spanned<Type>,
+    pub methods: Vec<Func>,
+}
+
+/// `static_assert(cond, "message")`, which fails compilation when `cond`
+/// evaluates to `false`.
+#[derive(Clone, Debug)]
+pub struct StaticAssert {
+    pub cond: Expr,
+    pub message: Option<Spanned<String>>,
+}
+
+/// `mod name`, whose items live in `name.ginto` next to the declaring file.
+#[derive(Clone, Debug)]
+pub struct ModDecl {
+    pub is_pub: bool,
+    pub name: Spanned<Symbol>,
+}
+
+/// `use a::b::c`, which brings `c` into scope.
+#[derive(Clone, Debug)]
+pub struct UseDecl {
+    pub is_pub: bool,
+    pub path: Vec<Spanned<Symbol>>,
+}
+
+impl UseDecl {
+    /// The segment naming the imported item.
+    pub fn name(&self) -> &Spanned<Symbol> {
+        self.path.last().expect("use path is empty")
+    }
+}
+
+/// The segments of a path joined as written, like `math::sub`.
+pub fn join_path(path: &[Spanned<Symbol>]) -> String {
+    path.iter()
+        .map(|segment| segment.inner.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+#[derive(Clone, Debug)]
+pub enum ItemKind {
+    Func(Func),
+    Mod(ModDecl),
+    Use(UseDecl),
+    Trait(TraitDecl),
+    Impl(ImplDecl),
+    StaticAssert(StaticAssert),
+}
+
+pub type Item = Spanned<ItemKind>;
+
+#[derive(Clone, Debug)]
+pub struct Program {
+    pub file_id: FileId,
+    pub items: Vec<Item>,
+}
+
+impl Program {
+    /// Every function with a body: free functions and the methods of `impl`
+    /// blocks.
+    pub fn funcs(&self) -> impl Iterator<Item = &Func> {
+        self.items.iter().flat_map(|item| match &item.inner {
+            ItemKind::Func(func) => std::slice::from_ref(func),
+            ItemKind::Impl(decl) => decl.methods.as_slice(),
+            ItemKind::Mod(_)
+            | ItemKind::Use(_)
+            | ItemKind::Trait(_)
+            | ItemKind::StaticAssert(_) => &[],
+        })
+    }
+
+    pub fn funcs_mut(&mut self) -> impl Iterator<Item = &mut Func> {
+        self.items
+            .iter_mut()
+            .flat_map(|item| match &mut item.inner {
+                ItemKind::Func(func) => std::slice::from_mut(func),
+                ItemKind::Impl(decl) => decl.methods.as_mut_slice(),
+                ItemKind::Mod(_)
+                | ItemKind::Use(_)
+                | ItemKind::Trait(_)
+                | ItemKind::StaticAssert(_) => &mut [],
+            })
+    }
+}
@@ -0,0 +1,140 @@
+//! A lossless view of a file for tools that rewrite source, which the AST
+//! can't give back as written: it drops parentheses, and the layout of
+//! lines and spaces.
+
+use ginto_diag::{BytePos, FileId, Span};
+
+use crate::{Lexer, LexerError, Parser, ParserError, Program, Token, TokenKind};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CstKind {
+    Token(TokenKind),
+    /// The spaces between two tokens on a line. Line breaks and the
+    /// indentation after them are part of `newline` tokens, up to the first
+    /// comment among them.
+    Whitespace,
+    /// A comment, from its `#` to the end of its line.
+    Comment,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstToken {
+    pub kind: CstKind,
+    pub span: Span,
+}
+
+/// A file as the tokens it lexes to and the whitespace between them, in
+/// order, which together cover every byte of it. Indents, dedents and the
+/// end of the file are tokens without text where the lexer puts them.
+#[derive(Debug, Clone)]
+pub struct Cst {
+    file_id: FileId,
+    source: String,
+    tokens: Vec<CstToken>,
+}
+
+impl Cst {
+    pub fn new(file_id: FileId, source: &str) -> Result<Self, Vec<LexerError>> {
+        let mut lexer = Lexer::new(file_id, source);
+        let mut tokens = Vec::new();
+        let mut pos = 0;
+        for token in lexer.lex_all()? {
+            if token.span.start.0 > pos {
+                tokens.push(CstToken {
+                    kind: CstKind::Whitespace,
+                    span: Span::from_range(pos..token.span.start.0),
+                });
+            }
+            pos = token.span.end.0;
+            tokens.push(CstToken {
+                kind: CstKind::Token(token.inner),
+                span: token.span,
+            });
+        }
+        Ok(Self {
+            file_id,
+            source: source.to_string(),
+            tokens: with_comments(tokens, lexer.comments()),
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn tokens(&self) -> &[CstToken] {
+        &self.tokens
+    }
+
+    /// The source of `token`.
+    pub fn text(&self, token: &CstToken) -> &str {
+        &self.source[token.span.start.0..token.span.end.0]
+    }
+
+    /// The token with text that `pos` is in, or that ends at `pos` if none
+    /// starts there.
+    pub fn token_at(&self, pos: BytePos) -> Option<&CstToken> {
+        let with_text = || self.tokens.iter().filter(|token| !token.span.is_empty());
+        with_text()
+            .find(|token| token.span.start <= pos && pos < token.span.end)
+            .or_else(|| with_text().find(|token| token.span.end == pos))
+    }
+
+    /// Parses the tokens into the AST, as the parser would the source.
+    pub fn to_ast(&self) -> (Program, Vec<ParserError>) {
+        let tokens: Vec<_> = self
+            .tokens
+            .iter()
+            .filter_map(|token| match &token.kind {
+                CstKind::Token(kind) => Some(Token::new(kind.clone(), token.span)),
+                CstKind::Whitespace | CstKind::Comment => None,
+            })
+            .collect();
+        let mut parser = Parser::new(self.file_id, tokens);
+        let program = parser.parse_program();
+        (program, parser.errors().to_vec())
+    }
+}
+
+/// `tokens` with `comments`, which are in their whitespace or `newline`
+/// tokens, split out of them. What follows a comment in the same token is
+/// whitespace.
+fn with_comments(tokens: Vec<CstToken>, comments: &[Span]) -> Vec<CstToken> {
+    let mut comments = comments.iter().peekable();
+    let mut split = Vec::new();
+    for mut token in tokens {
+        while let Some(&&comment) = comments.peek()
+            && comment.start < token.span.end
+        {
+            comments.next();
+            if comment.start > token.span.start {
+                split.push(CstToken {
+                    kind: token.kind.clone(),
+                    span: Span::new(token.span.start, comment.start),
+                });
+            }
+            split.push(CstToken {
+                kind: CstKind::Comment,
+                span: comment,
+            });
+            token = CstToken {
+                kind: CstKind::Whitespace,
+                span: Span::new(comment.end, token.span.end),
+            };
+        }
+        if !(token.kind == CstKind::Whitespace && token.span.is_empty()) {
+            split.push(token);
+        }
+    }
+    split
+}
+
+impl std::fmt::Display for Cst {
+    /// Writes the source back from the tokens, as it was read.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for token in &self.tokens {
+            f.write_str(self.text(token))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use ginto_diag::BytePos;
+
+/// The doc comment of the item whose first line holds `pos` in `source`:
+/// the comments on lines of their own right above that line, with no blank
+/// line between, joined by line breaks with the `#` of each and a space
+/// after it dropped. `None` if the item has no such comments.
+pub fn doc_comment(source: &str, pos: BytePos) -> Option<String> {
+    let line_start = source[..pos.0].rfind('\n').map_or(0, |i| i + 1);
+    let mut lines: Vec<_> = source[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .take_while(|line| line.starts_with('#'))
+        .map(|line| {
+            let text = &line[1..];
+            text.strip_prefix(' ').unwrap_or(text)
+        })
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    lines.reverse();
+    Some(lines.join("\n"))
+}
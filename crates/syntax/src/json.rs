@@ -0,0 +1,414 @@
+use core::fmt::{self, Write};
+
+use ginto_diag::{Span, Spanned, Symbol};
+
+use crate::{
+    Expr, ExprKind, Func, Item, ItemKind, MethodSig, Param, Pattern, PatternKind, Program, StrPart,
+    Type,
+};
+
+/// Writes `program` as JSON, one object per node with its `kind` and
+/// `span`, the byte offsets of its start and end.
+pub fn ast_to_json(program: &Program) -> String {
+    let json = Json::Object(vec![
+        ("file", Json::Int(program.file_id.0 as i128)),
+        (
+            "items",
+            Json::Array(program.items.iter().map(item).collect()),
+        ),
+    ]);
+    let mut out = String::new();
+    json.write(&mut out, 0)
+        .expect("writing to a string can't fail");
+    out.push('\n');
+    out
+}
+
+enum Json {
+    Null,
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    /// Writes the value, with the lines of nested values indented past
+    /// `indent` levels.
+    fn write(&self, out: &mut String, indent: usize) -> fmt::Result {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(v) => write!(out, "{v}")?,
+            Json::Int(v) => write!(out, "{v}")?,
+            // always with a fraction or an exponent, as literals are written
+            Json::Float(v) => write!(out, "{v:?}")?,
+            Json::Str(s) => write_str(out, s)?,
+            Json::Array(values) if values.is_empty() => out.push_str("[]"),
+            // arrays of numbers, like spans, fit on a line
+            Json::Array(values) if values.iter().all(|value| matches!(value, Json::Int(_))) => {
+                out.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    value.write(out, indent)?;
+                }
+                out.push(']');
+            }
+            Json::Array(values) => {
+                out.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, indent + 1);
+                    value.write(out, indent + 1)?;
+                }
+                newline(out, indent);
+                out.push(']');
+            }
+            Json::Object(fields) if fields.is_empty() => out.push_str("{}"),
+            Json::Object(fields) => {
+                out.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, indent + 1);
+                    write_str(out, key)?;
+                    out.push_str(": ");
+                    value.write(out, indent + 1)?;
+                }
+                newline(out, indent);
+                out.push('}');
+            }
+        }
+        Ok(())
+    }
+}
+
+fn newline(out: &mut String, indent: usize) {
+    out.push('\n');
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_str(out: &mut String, s: &str) -> fmt::Result {
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            ch if ch.is_control() => write!(out, "\\u{:04x}", ch as u32)?,
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    Ok(())
+}
+
+fn node(kind: &str, span: Span, mut fields: Vec<(&'static str, Json)>) -> Json {
+    fields.insert(0, ("kind", Json::Str(kind.to_string())));
+    fields.insert(1, ("span", span_json(span)));
+    Json::Object(fields)
+}
+
+fn span_json(span: Span) -> Json {
+    Json::Array(vec![
+        Json::Int(span.start.0 as i128),
+        Json::Int(span.end.0 as i128),
+    ])
+}
+
+fn name(name: &Spanned<Symbol>) -> Json {
+    Json::Object(vec![
+        ("name", Json::Str(name.inner.to_string())),
+        ("span", span_json(name.span)),
+    ])
+}
+
+fn ty(ty: &Spanned<Type>) -> Json {
+    Json::Object(vec![
+        ("type", Json::Str(ty.inner.to_string())),
+        ("span", span_json(ty.span)),
+    ])
+}
+
+fn opt<T>(value: Option<T>, f: impl FnOnce(T) -> Json) -> Json {
+    value.map_or(Json::Null, f)
+}
+
+fn item(item: &Item) -> Json {
+    match &item.inner {
+        ItemKind::Func(f) => node("fn", item.span, func_fields(f)),
+        ItemKind::Mod(decl) => node(
+            "mod",
+            item.span,
+            vec![("pub", Json::Bool(decl.is_pub)), ("name", name(&decl.name))],
+        ),
+        ItemKind::Use(decl) => node(
+            "use",
+            item.span,
+            vec![
+                ("pub", Json::Bool(decl.is_pub)),
+                ("path", Json::Array(decl.path.iter().map(name).collect())),
+            ],
+        ),
+        ItemKind::Trait(decl) => node(
+            "trait",
+            item.span,
+            vec![
+                ("pub", Json::Bool(decl.is_pub)),
+                ("name", name(&decl.name)),
+                (
+                    "methods",
+                    Json::Array(
+                        decl.methods
+                            .iter()
+                            .map(|sig| node("method_sig", sig.span, method_sig_fields(&sig.inner)))
+                            .collect(),
+                    ),
+                ),
+            ],
+        ),
+        ItemKind::Impl(decl) => node(
+            "impl",
+            item.span,
+            vec![
+                ("trait", name(&decl.trait_name)),
+                ("self_type", ty(&decl.self_ty)),
+                (
+                    "methods",
+                    Json::Array(
+                        decl.methods
+                            .iter()
+                            .map(|method| {
+                                let span = method.name.span.merge(method.body.span);
+                                node("fn", span, func_fields(method))
+                            })
+                            .collect(),
+                    ),
+                ),
+            ],
+        ),
+        ItemKind::StaticAssert(assert) => node(
+            "static_assert",
+            item.span,
+            vec![
+                ("cond", expr(&assert.cond)),
+                (
+                    "message",
+                    opt(assert.message.as_ref(), |message| {
+                        Json::Str(message.inner.clone())
+                    }),
+                ),
+            ],
+        ),
+    }
+}
+
+fn func_fields(func: &Func) -> Vec<(&'static str, Json)> {
+    vec![
+        ("pub", Json::Bool(func.is_pub)),
+        ("name", name(&func.name)),
+        (
+            "generics",
+            Json::Array(func.generics.iter().map(name).collect()),
+        ),
+        ("params", params(&func.params)),
+        ("ret", opt(func.ty.as_ref(), ty)),
+        ("body", expr(&func.body)),
+    ]
+}
+
+fn method_sig_fields(sig: &MethodSig) -> Vec<(&'static str, Json)> {
+    vec![
+        ("name", name(&sig.name)),
+        ("params", params(&sig.params)),
+        ("ret", opt(sig.ty.as_ref(), ty)),
+    ]
+}
+
+fn params(params: &[Spanned<Param>]) -> Json {
+    Json::Array(
+        params
+            .iter()
+            .map(|param| match &param.inner {
+                Param::SelfParam => node("self", param.span, Vec::new()),
+                Param::Named {
+                    name: param_name,
+                    mutable,
+                    ty: param_ty,
+                } => node(
+                    "param",
+                    param.span,
+                    vec![
+                        ("name", name(param_name)),
+                        ("mut", Json::Bool(*mutable)),
+                        ("type", opt(param_ty.as_ref(), ty)),
+                    ],
+                ),
+            })
+            .collect(),
+    )
+}
+
+fn exprs(exprs: &[Expr]) -> Json {
+    Json::Array(exprs.iter().map(expr).collect())
+}
+
+fn expr(e: &Expr) -> Json {
+    let (kind, fields) = match &e.inner {
+        ExprKind::Int(v) => ("int", vec![("value", Json::Int(*v as i128))]),
+        ExprKind::Float(v) => ("float", vec![("value", Json::Float(*v))]),
+        ExprKind::Bool(v) => ("bool", vec![("value", Json::Bool(*v))]),
+        ExprKind::Unit => ("unit", Vec::new()),
+        ExprKind::Str(parts) => (
+            "str",
+            vec![(
+                "parts",
+                Json::Array(
+                    parts
+                        .iter()
+                        .map(|part| match part {
+                            StrPart::Lit(text) => Json::Str(text.clone()),
+                            StrPart::Expr(e) => expr(e),
+                        })
+                        .collect(),
+                ),
+            )],
+        ),
+        ExprKind::Unary { op, expr: operand } => (
+            "unary",
+            vec![
+                ("op", Json::Str(op.inner.to_string().trim().to_string())),
+                ("expr", expr(operand)),
+            ],
+        ),
+        ExprKind::Binary { op, lhs, rhs } => (
+            "binary",
+            vec![
+                ("op", Json::Str(op.inner.to_string())),
+                ("lhs", expr(lhs)),
+                ("rhs", expr(rhs)),
+            ],
+        ),
+        ExprKind::Cast {
+            expr: operand,
+            ty: to,
+        } => ("cast", vec![("expr", expr(operand)), ("type", ty(to))]),
+        ExprKind::Let {
+            name: let_name,
+            mutable,
+            ty: let_ty,
+            value,
+        } => (
+            "let",
+            vec![
+                ("name", name(let_name)),
+                ("mut", Json::Bool(*mutable)),
+                ("type", opt(let_ty.as_ref(), ty)),
+                ("value", opt(value.as_deref(), expr)),
+            ],
+        ),
+        ExprKind::Assign { name: var, value } => {
+            ("assign", vec![("name", name(var)), ("value", expr(value))])
+        }
+        ExprKind::Block { exprs: stmts, tail } => (
+            "block",
+            vec![
+                ("exprs", exprs(stmts)),
+                ("tail", opt(tail.as_deref(), expr)),
+            ],
+        ),
+        ExprKind::Call { callee, args } => (
+            "call",
+            vec![("callee", expr(callee)), ("args", exprs(args))],
+        ),
+        ExprKind::MethodCall {
+            receiver,
+            method,
+            args,
+        } => (
+            "method_call",
+            vec![
+                ("receiver", expr(receiver)),
+                ("method", name(method)),
+                ("args", exprs(args)),
+            ],
+        ),
+        ExprKind::Match { scrutinee, arms } => (
+            "match",
+            vec![
+                ("scrutinee", expr(scrutinee)),
+                (
+                    "arms",
+                    Json::Array(
+                        arms.iter()
+                            .map(|arm| {
+                                Json::Object(vec![
+                                    ("pattern", pattern(&arm.pattern)),
+                                    ("body", expr(&arm.body)),
+                                ])
+                            })
+                            .collect(),
+                    ),
+                ),
+            ],
+        ),
+        ExprKind::Return(value) => ("return", vec![("value", opt(value.as_deref(), expr))]),
+        ExprKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => (
+            "if",
+            vec![
+                ("cond", expr(cond)),
+                ("then", expr(then_branch)),
+                ("else", opt(else_branch.as_deref(), expr)),
+            ],
+        ),
+        ExprKind::While { cond, body } => {
+            ("while", vec![("cond", expr(cond)), ("body", expr(body))])
+        }
+        ExprKind::Var(var) => ("var", vec![("name", Json::Str(var.to_string()))]),
+        ExprKind::Path(path) => (
+            "path",
+            vec![("path", Json::Array(path.iter().map(name).collect()))],
+        ),
+        ExprKind::Error => ("error", Vec::new()),
+        ExprKind::Closure {
+            params: closure_params,
+            body,
+        } => (
+            "closure",
+            vec![("params", params(closure_params)), ("body", expr(body))],
+        ),
+    };
+    node(kind, e.span, fields)
+}
+
+fn pattern(p: &Pattern) -> Json {
+    let (kind, fields) = match &p.inner {
+        PatternKind::Wildcard => ("wildcard", Vec::new()),
+        PatternKind::Bind(var) => ("bind", vec![("name", Json::Str(var.to_string()))]),
+        PatternKind::Int(v) => ("int", vec![("value", Json::Int(*v))]),
+        PatternKind::Bool(v) => ("bool", vec![("value", Json::Bool(*v))]),
+        PatternKind::Unit => ("unit", Vec::new()),
+        PatternKind::Or(alternatives) => (
+            "or",
+            vec![(
+                "alternatives",
+                Json::Array(alternatives.iter().map(pattern).collect()),
+            )],
+        ),
+    };
+    node(kind, p.span, fields)
+}
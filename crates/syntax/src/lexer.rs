@@ -1,19 +1,50 @@
-use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Severity, Span};
+use core::ops::Range;
+use std::borrow::Cow;
+
+use ginto_diag::{
+    Applicability, BytePos, Diagnostic, DiagnosticConvertible, Edit, FileId, Label, Span, Spanned,
+    Suggestion, Symbol,
+};
+use tracing::{debug, debug_span};
 
 use crate::{Token, TokenKind};
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum LexerError {
+    /// A character no token starts with. The span also covers the
+    /// characters `replacement` replaces, like the `=` of `!=`.
     UnexpectedCharacter {
         ch: char,
         span: Span,
         file_id: FileId,
+        /// The ginto spelling of an operator from other languages, like
+        /// `<>` for `!=`.
+        replacement: Option<String>,
     },
+    /// A literal with a digit its radix does not have, like `0b102`, or
+    /// with no digits after its prefix.
     InvalidNumber {
+        text: String,
+        radix: u32,
+        /// The first invalid digit, `None` if the literal has no digits.
+        digit: Option<Spanned<char>>,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A literal whose exponent has no digits, like `1.5e`.
+    MissingExponent {
         text: String,
         span: Span,
         file_id: FileId,
     },
+    /// A literal starting with `0` and a letter that isn't a radix prefix,
+    /// like `0XFF`.
+    UnknownRadixPrefix {
+        text: String,
+        prefix: Span,
+        span: Span,
+        file_id: FileId,
+    },
     NumberTooLarge {
         span: Span,
         file_id: FileId,
@@ -22,121 +53,460 @@ pub enum LexerError {
         span: Span,
         file_id: FileId,
     },
+    UnterminatedString {
+        span: Span,
+        file_id: FileId,
+    },
+    UnknownEscape {
+        ch: char,
+        span: Span,
+        file_id: FileId,
+    },
 }
 
 impl DiagnosticConvertible for LexerError {
     fn into_diagnostic(self) -> Diagnostic {
         match self {
-            LexerError::UnexpectedCharacter { ch, span, file_id } => {
-                Diagnostic::new(Severity::Error)
-                    .with_message(format!("unexpected character `{}`", ch))
+            LexerError::UnexpectedCharacter {
+                ch,
+                span,
+                file_id,
+                replacement,
+            } => {
+                let diagnostic = Diagnostic::error(format!("unexpected character `{}`", ch))
+                    .with_code("E0001")
                     .with_label(
                         Label::primary(file_id, span)
                             .with_message("this character is not valid here"),
-                    )
-                    .with_help("remove this character or replace it with a valid token")
+                    );
+                match replacement {
+                    Some(replacement) => diagnostic.with_suggestion(Suggestion::new(
+                        format!("use `{}` instead", replacement.trim()),
+                        vec![Edit::new(file_id, span, replacement)],
+                        Applicability::MachineApplicable,
+                    )),
+                    None => diagnostic
+                        .with_help("remove this character or replace it with a valid token"),
+                }
             }
 
             LexerError::InvalidNumber {
                 text,
+                radix,
+                digit,
                 span,
                 file_id,
-            } => Diagnostic::new(Severity::Error)
-                .with_message("invalid number literal")
-                .with_label(
-                    Label::primary(file_id, span)
-                        .with_message(format!("`{}` is not a valid number", text)),
-                )
-                .with_help(
-                    "check that the number uses valid digits and does not contain extra symbols",
-                ),
+            } => {
+                let diagnostic = Diagnostic::error("invalid number literal").with_code("E0002");
+                match digit {
+                    Some(digit) => diagnostic
+                        .with_label(Label::primary(file_id, digit.span).with_message(format!(
+                            "`{}` is not {} {} digit",
+                            digit.inner,
+                            if radix == 8 { "an" } else { "a" },
+                            radix_name(radix)
+                        )))
+                        .with_label(
+                            Label::secondary(file_id, span)
+                                .with_message(format!("in the literal `{}`", text)),
+                        )
+                        .with_help(format!(
+                            "{} literals use the digits {}",
+                            radix_name(radix),
+                            radix_digits(radix)
+                        )),
+                    None => diagnostic
+                        .with_label(
+                            Label::primary(file_id, span)
+                                .with_message(format!("`{}` has no digits", text)),
+                        )
+                        .with_help(format!(
+                            "write {} digits after the prefix, like `{}1`",
+                            radix_name(radix),
+                            text.trim_end_matches('_')
+                        )),
+                }
+            }
 
-            LexerError::NumberTooLarge { span, file_id } => Diagnostic::new(Severity::Error)
-                .with_message("number literal is too large")
+            LexerError::MissingExponent {
+                text,
+                span,
+                file_id,
+            } => Diagnostic::error("invalid number literal")
+                .with_code("E0002")
                 .with_label(
-                    Label::primary(file_id, span)
-                        .with_message("this value does not fit in the target integer type"),
+                    Label::primary(file_id, span).with_message("the exponent has no digits"),
                 )
-                .with_help("try using a smaller value or a wider integer type if available"),
+                .with_help(format!(
+                    "write decimal digits after the exponent, like `{}1`",
+                    text
+                )),
 
-            LexerError::InvalidIndentation { span, file_id } => Diagnostic::new(Severity::Error)
-                .with_message("invalid indentation")
-                .with_label(
-                    Label::primary(file_id, span)
-                        .with_message("this indentation does not match any previous block level"),
-                )
-                .with_note("indentation must match the indentation of a previous block exactly")
-                .with_help("align this line with a previous block or fix inconsistent spaces/tabs"),
+            LexerError::UnknownRadixPrefix {
+                text,
+                prefix,
+                span,
+                file_id,
+            } => {
+                // the second character may be any letter, not just ASCII
+                let lowercase = text
+                    .chars()
+                    .take(2)
+                    .collect::<String>()
+                    .to_ascii_lowercase();
+                let diagnostic = Diagnostic::error("invalid number literal")
+                    .with_code("E0002")
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message(format!("`{}` has an unknown prefix", text)),
+                    );
+                if matches!(lowercase.as_str(), "0x" | "0o" | "0b") {
+                    diagnostic.with_suggestion(Suggestion::new(
+                        format!("radix prefixes are lowercase, write `{}`", lowercase),
+                        vec![Edit::new(file_id, prefix, lowercase.clone())],
+                        Applicability::MachineApplicable,
+                    ))
+                } else {
+                    diagnostic.with_help("the radix prefixes are `0x`, `0o` and `0b`")
+                }
+            }
+
+            LexerError::NumberTooLarge { span, file_id } => {
+                Diagnostic::error("number literal is too large")
+                    .with_code("E0003")
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message("this value does not fit in the target type"),
+                    )
+                    .with_help("try using a smaller value or a wider integer type if available")
+            }
+
+            LexerError::InvalidIndentation { span, file_id } => {
+                Diagnostic::error("invalid indentation")
+                    .with_code("E0004")
+                    .with_label(
+                        Label::primary(file_id, span).with_message(
+                            "this indentation does not match any previous block level",
+                        ),
+                    )
+                    .with_note("indentation must match the indentation of a previous block exactly")
+                    .with_help(
+                        "align this line with a previous block or fix inconsistent spaces/tabs",
+                    )
+            }
+
+            LexerError::UnterminatedString { span, file_id } => {
+                Diagnostic::error("unterminated string literal")
+                    .with_code("E0005")
+                    .with_label(
+                        Label::primary(file_id, span).with_message("this string is never closed"),
+                    )
+                    .with_help("add a closing `\"` before the end of the line")
+            }
+
+            LexerError::UnknownEscape { ch, span, file_id } => {
+                Diagnostic::error(format!("unknown character escape `\\{}`", ch))
+                    .with_code("E0006")
+                    .with_label(Label::primary(file_id, span).with_message("unknown escape"))
+                    .with_note("the supported escapes are `\\n`, `\\t`, `\\\\` and `\\\"`")
+            }
         }
     }
 }
 
+fn radix_name(radix: u32) -> &'static str {
+    match radix {
+        2 => "binary",
+        8 => "octal",
+        16 => "hexadecimal",
+        _ => "decimal",
+    }
+}
+
+fn radix_digits(radix: u32) -> &'static str {
+    match radix {
+        2 => "`0` and `1`",
+        8 => "`0` to `7`",
+        16 => "`0` to `9` and `a` to `f`",
+        _ => "`0` to `9`",
+    }
+}
+
+/// Lexes the borrowed input in place: `pos` is a byte offset into it, and
+/// the text of tokens is sliced out of it.
 #[derive(Clone)]
-pub struct Lexer {
+pub struct Lexer<'a> {
     pos: usize,
     file_id: FileId,
-    input: Vec<char>,
+    input: &'a str,
     indent_stack: Vec<usize>,
     pending: Vec<TokenKind>,
+    comments: Vec<Span>,
 }
 
-impl Lexer {
-    pub fn new(file_id: FileId, input: &str) -> Self {
+impl<'a> Lexer<'a> {
+    pub fn new(file_id: FileId, input: &'a str) -> Self {
         Self {
             pos: 0,
             file_id,
-            input: input.chars().collect(),
+            input,
             indent_stack: vec![0],
             pending: Vec::new(),
+            comments: Vec::new(),
         }
     }
 
+    /// The comments lexed so far, from a `#` to the end of its line, in
+    /// order. They aren't tokens, so a line with only a comment is blank.
+    pub fn comments(&self) -> &[Span] {
+        &self.comments
+    }
+
+    /// The span of the bytes at `range`, which may run past the end of the
+    /// input.
+    fn span(&self, range: Range<usize>) -> Span {
+        let offset = |pos: usize| BytePos(pos.min(self.input.len()));
+        Span::new(offset(range.start), offset(range.end))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    /// The current character, `\0` at the end of the input.
     fn current(&self) -> char {
-        self.input.get(self.pos).copied().unwrap_or('\0')
+        match self.input.as_bytes().get(self.pos) {
+            Some(&byte) if byte.is_ascii() => byte as char,
+            Some(_) => self.rest().chars().next().unwrap(),
+            None => '\0',
+        }
     }
 
     fn peek(&self) -> char {
-        self.input.get(self.pos + 1).copied().unwrap_or('\0')
+        self.rest().chars().nth(1).unwrap_or('\0')
     }
 
+    /// Moves past the current character, if the input isn't at its end.
     fn advance(&mut self) {
-        self.pos += 1
+        if self.pos < self.input.len() {
+            self.pos += self.current().len_utf8();
+        }
     }
 
+    /// The ginto spelling of the operator starting with `ch`, the character
+    /// before the current one, consuming the rest of the operator. A word
+    /// is spaced from the characters around it.
+    fn replacement(&mut self, ch: char) -> Option<String> {
+        let space = |c: char| if c.is_whitespace() { "" } else { " " };
+        match ch {
+            '!' if self.current() == '=' => {
+                self.advance();
+                Some("<>".to_string())
+            }
+            '!' => Some(format!("not{}", space(self.current()))),
+            '%' => {
+                let before = self.input[..self.pos - 1]
+                    .chars()
+                    .next_back()
+                    .unwrap_or(' ');
+                Some(format!("{}mod{}", space(before), space(self.current())))
+            }
+            _ => None,
+        }
+    }
+
+    /// Skips the spaces before the next token or line break, and the
+    /// comment ending the line.
     fn skip_whitespace(&mut self) {
         while matches!(self.current(), ' ' | '\t' | '\r') {
             self.advance();
         }
+        if self.current() == '#' {
+            let start = self.pos;
+            while !matches!(self.current(), '\n' | '\0') {
+                self.advance();
+            }
+            let end = self.input[start..self.pos].trim_end().len();
+            self.comments.push(self.span(start..start + end));
+        }
     }
 
-    fn lex_ident(&mut self) -> Result<String, LexerError> {
+    fn lex_ident(&mut self) -> &'a str {
         let start = self.pos;
         while self.current().is_alphanumeric() || self.current() == '_' {
             self.advance();
         }
-        Ok(self.input[start..self.pos].iter().collect())
+        &self.input[start..self.pos]
     }
 
-    fn lex_num(&mut self) -> Result<u64, LexerError> {
+    /// Lexes an integer literal, or a float literal if the digits are
+    /// followed by a fraction like `1.5` or an exponent like `1e9`. A `.`
+    /// not followed by a digit is left alone, as in `1.foo`. Integers can
+    /// be written in hexadecimal, octal and binary after `0x`, `0o` and
+    /// `0b`, and digits can be separated by `_`, as in `1_000` or `0xFF_FF`.
+    fn lex_num(&mut self) -> Result<TokenKind, LexerError> {
         let start = self.pos;
-        while self.current().is_ascii_digit() {
+        let radix = match (self.current(), self.peek()) {
+            ('0', 'x') => 16,
+            ('0', 'o') => 8,
+            ('0', 'b') => 2,
+            ('0', 'e' | 'E') => 10,
+            ('0', c) if c.is_alphabetic() => return Err(self.unknown_radix_prefix()),
+            _ => 10,
+        };
+        if radix != 10 {
+            return self.lex_radix_int(radix);
+        }
+        self.skip_digits();
+        let mut is_float = false;
+        if self.current() == '.' && self.peek().is_ascii_digit() {
             self.advance();
+            self.skip_digits();
+            is_float = true;
         }
-        let text: String = self.input[start..self.pos].iter().collect();
-        text.parse::<u64>().map_err(|_| {
-            if text.chars().all(|c| c.is_ascii_digit()) {
-                LexerError::NumberTooLarge {
+        if matches!(self.current(), 'e' | 'E') {
+            let sign = usize::from(matches!(self.peek(), '+' | '-'));
+            let digit = self.input.as_bytes().get(self.pos + 1 + sign);
+            self.pos += 1 + sign;
+            if !digit.is_some_and(u8::is_ascii_digit) {
+                return Err(LexerError::MissingExponent {
+                    text: self.input[start..self.pos].to_string(),
+                    span: self.span(start..self.pos),
                     file_id: self.file_id,
-                    span: Span::from_range(start..self.pos),
+                });
+            }
+            self.skip_digits();
+            is_float = true;
+        }
+        let digits = without_separators(&self.input[start..self.pos]);
+        let too_large = LexerError::NumberTooLarge {
+            file_id: self.file_id,
+            span: self.span(start..self.pos),
+        };
+        if is_float {
+            // the digits always parse, but may not fit
+            return match digits.parse::<f64>() {
+                Ok(v) if v.is_finite() => Ok(TokenKind::FloatLiteral(v)),
+                _ => Err(too_large),
+            };
+        }
+        // only digits were taken, so the literal can only be too large
+        digits
+            .parse::<u64>()
+            .map(TokenKind::IntLiteral)
+            .map_err(|_| too_large)
+    }
+
+    /// Lexes an integer literal after its prefix for `radix`. The letters
+    /// and digits right after the prefix all belong to the literal, so that
+    /// a digit out of the radix, like the `2` of `0b102`, is reported.
+    fn lex_radix_int(&mut self, radix: u32) -> Result<TokenKind, LexerError> {
+        let start = self.pos;
+        self.pos += 2;
+        let mut has_digits = false;
+        let mut invalid = None;
+        while self.current().is_alphanumeric() || self.current() == '_' {
+            let c = self.current();
+            if c.is_digit(radix) {
+                has_digits = true;
+            } else if c != '_' && invalid.is_none() {
+                invalid = Some(Spanned::new(
+                    c,
+                    self.span(self.pos..self.pos + c.len_utf8()),
+                ));
+            }
+            self.advance();
+        }
+        let span = self.span(start..self.pos);
+        if invalid.is_some() || !has_digits {
+            return Err(LexerError::InvalidNumber {
+                text: self.input[start..self.pos].to_string(),
+                radix,
+                digit: invalid,
+                span,
+                file_id: self.file_id,
+            });
+        }
+        u64::from_str_radix(&without_separators(&self.input[start + 2..self.pos]), radix)
+            .map(TokenKind::IntLiteral)
+            .map_err(|_| LexerError::NumberTooLarge {
+                span,
+                file_id: self.file_id,
+            })
+    }
+
+    /// The error of a literal starting with `0` and a letter that isn't a
+    /// radix prefix, taking the letters and digits after it.
+    fn unknown_radix_prefix(&mut self) -> LexerError {
+        let start = self.pos;
+        self.advance();
+        let prefix = self.span(start..self.pos + self.current().len_utf8());
+        while self.current().is_alphanumeric() || self.current() == '_' {
+            self.advance();
+        }
+        LexerError::UnknownRadixPrefix {
+            text: self.input[start..self.pos].to_string(),
+            prefix,
+            span: self.span(start..self.pos),
+            file_id: self.file_id,
+        }
+    }
+
+    /// Skips decimal digits and the `_`s separating them.
+    fn skip_digits(&mut self) {
+        while self.current().is_ascii_digit() || self.current() == '_' {
+            self.advance();
+        }
+    }
+
+    /// Lexes a string literal on a single line, starting at the opening
+    /// quote, to its text between the quotes with escapes as written, so
+    /// positions in the text map to the source; see `unescape`. An unknown
+    /// escape is reported after the rest of the literal is consumed.
+    fn lex_str(&mut self) -> Result<String, LexerError> {
+        let start = self.pos;
+        self.advance();
+        let mut error = None;
+        loop {
+            match self.current() {
+                '"' => {
+                    self.advance();
+                    break;
                 }
-            } else {
-                LexerError::InvalidNumber {
-                    file_id: self.file_id,
-                    text,
-                    span: Span::from_range(start..self.pos),
+                '\n' | '\0' => {
+                    return Err(LexerError::UnterminatedString {
+                        span: self.span(start..self.pos),
+                        file_id: self.file_id,
+                    });
+                }
+                '\\' => {
+                    let ch = self.peek();
+                    match ch {
+                        'n' | 't' | '\\' | '"' => {}
+                        // let the next iteration report the unclosed literal
+                        '\n' | '\0' => {
+                            self.advance();
+                            continue;
+                        }
+                        _ => {
+                            error.get_or_insert(LexerError::UnknownEscape {
+                                ch,
+                                span: self.span(self.pos..self.pos + 1 + ch.len_utf8()),
+                                file_id: self.file_id,
+                            });
+                        }
+                    }
+                    self.advance();
+                    self.advance();
                 }
+                _ => self.advance(),
             }
-        })
+        }
+        match error {
+            Some(error) => Err(error),
+            // the text between the quotes
+            None => Ok(self.input[start + 1..self.pos - 1].to_string()),
+        }
     }
 
     fn count_indent(&mut self) -> usize {
@@ -149,9 +519,21 @@ impl Lexer {
     }
 
     fn handle_newline(&mut self) -> Result<TokenKind, LexerError> {
-        self.advance();
+        let mut indent;
+        loop {
+            self.advance();
+            indent = self.count_indent();
+            self.skip_whitespace();
+            if self.current() != '\n' {
+                break;
+            }
+        }
+
+        // trailing blank lines close blocks at end of file, see `lex_all`
+        if self.current() == '\0' {
+            return Ok(TokenKind::Newline);
+        }
 
-        let indent = self.count_indent();
         let current = *self.indent_stack.last().unwrap();
 
         if indent > current {
@@ -168,8 +550,11 @@ impl Lexer {
             }
 
             if *self.indent_stack.last().unwrap() != indent {
+                // treat the line as opening a new level so lexing can continue
+                self.indent_stack.push(indent);
+                self.pending.push(TokenKind::Indent);
                 return Err(LexerError::InvalidIndentation {
-                    span: Span::from_range(self.pos..self.pos),
+                    span: self.span(self.pos..self.pos),
                     file_id: self.file_id,
                 });
             }
@@ -179,6 +564,14 @@ impl Lexer {
     }
 
     fn next_token(&mut self) -> Result<Token, LexerError> {
+        if !self.pending.is_empty() {
+            let kind = self.pending.remove(0);
+            return Ok(Token {
+                inner: kind,
+                span: self.span(self.pos..self.pos),
+            });
+        }
+
         self.skip_whitespace();
         let pos = self.pos;
         let ch = self.current();
@@ -207,7 +600,12 @@ impl Lexer {
             }
             ':' => {
                 self.advance();
-                TokenKind::Colon
+                if self.current() == ':' {
+                    self.advance();
+                    TokenKind::ColonColon
+                } else {
+                    TokenKind::Colon
+                }
             }
             '+' => {
                 self.advance();
@@ -215,7 +613,7 @@ impl Lexer {
             }
             '-' => {
                 self.advance();
-                if self.peek() == '>' {
+                if self.current() == '>' {
                     self.advance();
                     TokenKind::Arrow
                 } else {
@@ -236,10 +634,10 @@ impl Lexer {
             }
             '<' => {
                 self.advance();
-                if self.peek() == '>' {
+                if self.current() == '>' {
                     self.advance();
                     TokenKind::NotEqual
-                } else if self.peek() == '=' {
+                } else if self.current() == '=' {
                     self.advance();
                     TokenKind::LessEqual
                 } else {
@@ -248,7 +646,7 @@ impl Lexer {
             }
             '>' => {
                 self.advance();
-                if self.peek() == '=' {
+                if self.current() == '=' {
                     self.advance();
                     TokenKind::GreaterEqual
                 } else {
@@ -260,42 +658,65 @@ impl Lexer {
                 self.advance();
                 TokenKind::And
             }
-            '|' if self.peek() == '|' => {
+            '|' => {
                 self.advance();
-                self.advance();
-                TokenKind::Or
-            }
-            'a'..='z' | 'A'..='Z' | '_' => {
-                let ident = self.lex_ident()?;
-                match ident.as_str() {
-                    "let" => TokenKind::Let,
-                    "mod" => TokenKind::Mod,
-                    "fn" => TokenKind::Fn,
-                    "not" => TokenKind::Not,
-                    "u64" => TokenKind::U64,
-                    "i64" => TokenKind::I64,
-                    "true" => TokenKind::BoolLiteral(true),
-                    "false" => TokenKind::BoolLiteral(false),
-                    _ => TokenKind::Ident(ident),
+                if self.current() == '|' {
+                    self.advance();
+                    TokenKind::Or
+                } else {
+                    TokenKind::Pipe
                 }
             }
-            '0'..='9' => self.lex_num().map(TokenKind::IntLiteral)?,
+            'a'..='z' | 'A'..='Z' | '_' => match self.lex_ident() {
+                "let" => TokenKind::Let,
+                "mod" => TokenKind::Mod,
+                "mut" => TokenKind::Mut,
+                "use" => TokenKind::Use,
+                "fn" => TokenKind::Fn,
+                "pub" => TokenKind::Pub,
+                "match" => TokenKind::Match,
+                "return" => TokenKind::Return,
+                "if" => TokenKind::If,
+                "else" => TokenKind::Else,
+                "as" => TokenKind::As,
+                "static_assert" => TokenKind::StaticAssert,
+                "trait" => TokenKind::Trait,
+                "impl" => TokenKind::Impl,
+                "for" => TokenKind::For,
+                "while" => TokenKind::While,
+                "_" => TokenKind::Underscore,
+                "bool" => TokenKind::Bool,
+                "not" => TokenKind::Not,
+                "u64" => TokenKind::U64,
+                "i64" => TokenKind::I64,
+                "f64" => TokenKind::F64,
+                "str" => TokenKind::Str,
+                "true" => TokenKind::BoolLiteral(true),
+                "false" => TokenKind::BoolLiteral(false),
+                ident => TokenKind::Ident(Symbol::intern(ident)),
+            },
+            '0'..='9' => self.lex_num()?,
+            '"' => self.lex_str().map(TokenKind::StrLiteral)?,
             _ => {
+                self.advance();
+                let replacement = self.replacement(ch);
                 return Err(LexerError::UnexpectedCharacter {
                     ch,
                     file_id: self.file_id,
-                    span: Span::from_range(pos..pos),
+                    span: self.span(pos..self.pos),
+                    replacement,
                 });
             }
         };
 
         Ok(Token {
             inner: kind,
-            span: Span::from_range(pos..self.pos),
+            span: self.span(pos..self.pos),
         })
     }
 
     pub fn lex_all(&mut self) -> Result<Vec<Token>, Vec<LexerError>> {
+        let _span = debug_span!("lex", file = self.file_id.0).entered();
         let mut tokens = Vec::new();
         let mut errors = Vec::new();
 
@@ -316,13 +737,11 @@ impl Lexer {
                     }
                     tokens.push(tok);
                 }
-                Err(err) => {
-                    errors.push(err);
-                    self.advance();
-                }
+                Err(err) => errors.push(err),
             }
         }
 
+        debug!(tokens = tokens.len(), errors = errors.len(), "lexed");
         if errors.is_empty() {
             Ok(tokens)
         } else {
@@ -330,3 +749,31 @@ impl Lexer {
         }
     }
 }
+
+/// The digits of the number literal `text`, without the `_`s separating
+/// them.
+fn without_separators(text: &str) -> Cow<'_, str> {
+    if text.contains('_') {
+        Cow::Owned(text.replace('_', ""))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Resolves the escapes in the text of a string literal.
+pub fn unescape(text: &str) -> String {
+    let mut value = String::new();
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(ch) => value.push(ch),
+                None => {}
+            },
+            ch => value.push(ch),
+        }
+    }
+    value
+}
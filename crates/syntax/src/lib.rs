@@ -1,9 +1,18 @@
 mod ast;
+mod cst;
+mod doc_comment;
+mod json;
 mod lexer;
 mod parser;
+mod pretty;
 mod token;
+pub mod visit;
 
 pub use ast::*;
+pub use cst::*;
+pub use doc_comment::*;
+pub use json::*;
 pub use lexer::*;
 pub use parser::*;
+pub use pretty::*;
 pub use token::*;
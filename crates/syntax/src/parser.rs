@@ -1,5 +1,13 @@
-use crate::{BinOp, Expr, ExprKind, Token, TokenKind, UnaryOp};
-use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Severity, Span, Spanned};
+use crate::{
+    BinOp, Expr, ExprKind, Func, ImplDecl, Item, ItemKind, MatchArm, MethodSig, ModDecl, Param,
+    Pattern, PatternKind, Program, StaticAssert, StrPart, Token, TokenKind, TraitDecl, Type,
+    UnaryOp, UseDecl, unescape,
+};
+use ginto_diag::{
+    Diagnostic, DiagnosticConvertible, FileId, Label, Span, Spanned, Symbol, did_you_mean,
+    find_similar, sym,
+};
+use tracing::{debug, debug_span};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParserError {
@@ -18,13 +26,38 @@ pub enum ParserError {
         span: Span,
         file_id: FileId,
     },
+    /// A token other than `)` where the `(` opened at `open` should be
+    /// closed.
+    UnclosedParen {
+        open: Span,
+        found: TokenKind,
+        span: Span,
+        file_id: FileId,
+    },
     InvalidSyntax {
         message: String,
         span: Span,
         file_id: FileId,
     },
+    /// A statement that is a name followed by more on its line, where the
+    /// name looks like a misspelling of the keyword starting a statement.
+    MisspelledKeyword {
+        name: String,
+        keyword: &'static str,
+        span: Span,
+        file_id: FileId,
+    },
 }
 
+/// The keywords that start a statement.
+const STMT_KEYWORDS: &[TokenKind] = &[
+    TokenKind::Let,
+    TokenKind::If,
+    TokenKind::Match,
+    TokenKind::While,
+    TokenKind::Return,
+];
+
 impl DiagnosticConvertible for ParserError {
     fn into_diagnostic(self) -> Diagnostic {
         match self {
@@ -37,26 +70,32 @@ impl DiagnosticConvertible for ParserError {
                 let expected_str = if expected.is_empty() {
                     "something else".to_string()
                 } else if expected.len() == 1 {
-                    format!("`{:?}`", expected[0])
+                    expected[0].to_string()
                 } else {
                     format!(
                         "one of {}",
                         expected
                             .iter()
-                            .map(|k| format!("`{:?}`", k))
+                            .map(ToString::to_string)
                             .collect::<Vec<_>>()
                             .join(", ")
                     )
                 };
 
-                Diagnostic::new(Severity::Error)
-                    .with_message(format!("unexpected token `{:?}`", found))
-                    .with_label(
-                        Label::primary(file_id, span).with_message(format!(
-                            "expected {}, found `{:?}`",
-                            expected_str, found
-                        )),
-                    )
+                let diagnostic = Diagnostic::error(format!("unexpected {}", found)).with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected {}, found {}", expected_str, found)),
+                );
+                let TokenKind::Ident(name) = found else {
+                    return diagnostic;
+                };
+                let keywords = expected.iter().filter_map(TokenKind::keyword);
+                match name.with(|name| find_similar(name, keywords)) {
+                    Some(keyword) => {
+                        diagnostic.with_suggestion(did_you_mean(file_id, span, keyword))
+                    }
+                    None => diagnostic,
+                }
             }
 
             ParserError::UnexpectedEof {
@@ -75,30 +114,51 @@ impl DiagnosticConvertible for ParserError {
                         .to_string()
                 };
 
-                Diagnostic::new(Severity::Error)
-                    .with_message("unexpected end of file")
-                    .with_label(
-                        Label::primary(file_id, span)
-                            .with_message(format!("expected {}", expected_str)),
-                    )
+                Diagnostic::error("unexpected end of file").with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected {}", expected_str)),
+                )
             }
 
-            ParserError::MissingExpression { span, file_id } => Diagnostic::new(Severity::Error)
-                .with_message("expected expression")
-                .with_label(Label::primary(file_id, span).with_message("expression expected here")),
+            ParserError::MissingExpression { span, file_id } => Diagnostic::error(
+                "expected expression",
+            )
+            .with_label(Label::primary(file_id, span).with_message("expression expected here")),
+
+            ParserError::UnclosedParen {
+                open,
+                found,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("unexpected {}", found))
+                .with_label(Label::primary(file_id, span).with_message(format!(
+                    "expected {}, found {}",
+                    TokenKind::RParen,
+                    found
+                )))
+                .with_label(Label::secondary(file_id, open).with_message("unclosed `(`")),
 
             ParserError::InvalidSyntax {
                 message,
                 span,
                 file_id,
-            } => Diagnostic::new(Severity::Error)
-                .with_message("invalid syntax")
+            } => Diagnostic::error("invalid syntax")
                 .with_label(Label::primary(file_id, span).with_message(message)),
+
+            ParserError::MisspelledKeyword {
+                name,
+                keyword,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("`{name}` is not a keyword"))
+                .with_label(Label::primary(file_id, span).with_message("not a keyword"))
+                .with_suggestion(did_you_mean(file_id, span, keyword)),
         }
     }
 }
 
 pub struct Parser {
+    /// Never empty, as it ends with an `Eof` token.
     tokens: Vec<Token>,
     pos: usize,
     file_id: FileId,
@@ -106,7 +166,18 @@ pub struct Parser {
 }
 
 impl Parser {
-    pub fn new(file_id: FileId, tokens: Vec<Token>) -> Self {
+    /// A parser of `tokens`, which the lexer ends with an `Eof` token. If
+    /// they don't end with one, one is added after the last token.
+    pub fn new(file_id: FileId, mut tokens: Vec<Token>) -> Self {
+        if tokens
+            .last()
+            .is_none_or(|token| token.inner != TokenKind::Eof)
+        {
+            let end = tokens.last().map_or(Span::default(), |token| {
+                Span::new(token.span.end, token.span.end)
+            });
+            tokens.push(Token::new(TokenKind::Eof, end));
+        }
         Self {
             tokens,
             pos: 0,
@@ -163,15 +234,6 @@ impl Parser {
         }
     }
 
-    fn match_any(&mut self, kinds: &[TokenKind]) -> bool {
-        for kind in kinds {
-            if self.match_token(kind.clone()) {
-                return true;
-            }
-        }
-        false
-    }
-
     fn expect(&mut self, kind: TokenKind) -> Result<Token, ParserError> {
         if self.check(&kind) {
             Ok(self.advance().clone())
@@ -186,28 +248,60 @@ impl Parser {
         }
     }
 
-    fn expect_with_recovery(
-        &mut self,
-        kind: TokenKind,
-        sync_tokens: &[TokenKind],
-    ) -> Option<Token> {
+    fn expect_or_report(&mut self, kind: TokenKind) -> Option<Token> {
         match self.expect(kind) {
             Ok(tok) => Some(tok),
             Err(err) => {
-                self.errors.push(err);
-                self.synchronize(sync_tokens);
+                self.report_error(err);
                 None
             }
         }
     }
 
     fn synchronize(&mut self, sync_tokens: &[TokenKind]) {
+        let start = self.pos;
+        while !self.is_at_end() && !sync_tokens.iter().any(|k| self.check(k)) {
+            self.advance();
+        }
+        self.recovered(start);
+    }
+
+    /// Skips to the next `fn` or `pub` that is not nested inside an indented
+    /// block opened after the error.
+    fn synchronize_to_item(&mut self) {
+        let start = self.pos;
+        let mut depth = 0i32;
         while !self.is_at_end() {
-            if sync_tokens.iter().any(|k| self.check(k)) {
-                return;
+            match self.current_kind() {
+                TokenKind::Indent => depth += 1,
+                TokenKind::Dedent => depth -= 1,
+                TokenKind::Fn
+                | TokenKind::Pub
+                | TokenKind::Trait
+                | TokenKind::Impl
+                | TokenKind::StaticAssert
+                    if depth <= 0 =>
+                {
+                    break;
+                }
+                // `mod` is also an operator, so only a line start counts
+                TokenKind::Mod | TokenKind::Use if depth <= 0 && self.at_line_start() => break,
+                _ => {}
             }
             self.advance();
         }
+        self.recovered(start);
+    }
+
+    /// Logs where recovery from an error that stopped at token `start`
+    /// resumed parsing.
+    fn recovered(&self, start: usize) {
+        debug!(
+            skipped = self.pos - start,
+            at = %self.current_kind(),
+            offset = self.current().span.start.0,
+            "recovered from a syntax error"
+        );
     }
 
     fn synchronize_to_statement(&mut self) {
@@ -228,30 +322,22 @@ impl Parser {
         self.errors.push(error);
     }
 
-    fn error_and_recover<T>(&mut self, error: ParserError, sync_tokens: &[TokenKind]) -> Option<T> {
-        self.report_error(error);
-        self.synchronize(sync_tokens);
-        None
-    }
-
     fn skip_newlines(&mut self) {
         while self.match_token(TokenKind::Newline) {}
     }
 
-    fn expect_newline_or_eof(&mut self) -> Result<(), ParserError> {
-        if self.is_at_end() || self.check(&TokenKind::Newline) {
-            if !self.is_at_end() {
-                self.advance();
-            }
-            Ok(())
-        } else {
-            Err(ParserError::UnexpectedToken {
-                expected: vec![TokenKind::Newline, TokenKind::Eof],
-                found: self.current().inner.clone(),
-                span: self.current().span,
-                file_id: self.file_id,
-            })
-        }
+    /// Whether the last consumed token closed an indented block, in which
+    /// case the statement that owned the block needs no trailing newline.
+    fn ended_block(&self) -> bool {
+        self.pos > 0 && self.tokens[self.pos - 1].inner == TokenKind::Dedent
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.pos == 0
+            || matches!(
+                self.tokens[self.pos - 1].inner,
+                TokenKind::Newline | TokenKind::Indent | TokenKind::Dedent
+            )
     }
 
     fn current_kind(&self) -> &TokenKind {
@@ -262,6 +348,617 @@ impl Parser {
         &self.errors
     }
 
+    pub fn parse_program(&mut self) -> Program {
+        let _span = debug_span!("parse", file = self.file_id.0).entered();
+        let mut items = Vec::new();
+        self.skip_newlines();
+        while !self.is_at_end() {
+            match self.parse_item() {
+                Some(item) => items.push(item),
+                None => self.synchronize_to_item(),
+            }
+            self.skip_newlines();
+        }
+        debug!(items = items.len(), errors = self.errors.len(), "parsed");
+        Program {
+            file_id: self.file_id,
+            items,
+        }
+    }
+
+    fn parse_item(&mut self) -> Option<Item> {
+        let start = self.current().span;
+        let is_pub = self.match_token(TokenKind::Pub);
+        match self.current_kind() {
+            TokenKind::Fn => {
+                let func = self.parse_func(is_pub)?;
+                let span = start.merge(func.body.span);
+                Some(Item::new(ItemKind::Func(func), span))
+            }
+            TokenKind::Mod => {
+                self.advance();
+                let name = self.parse_ident()?;
+                let span = start.merge(name.span);
+                self.expect_item_end()?;
+                Some(Item::new(ItemKind::Mod(ModDecl { is_pub, name }), span))
+            }
+            TokenKind::Use => {
+                self.advance();
+                let mut path = vec![self.parse_ident()?];
+                while self.match_token(TokenKind::ColonColon) {
+                    path.push(self.parse_ident()?);
+                }
+                let span = start.merge(path[path.len() - 1].span);
+                self.expect_item_end()?;
+                Some(Item::new(ItemKind::Use(UseDecl { is_pub, path }), span))
+            }
+            TokenKind::Trait => {
+                let (decl, end) = self.parse_trait(is_pub)?;
+                Some(Item::new(ItemKind::Trait(decl), start.merge(end)))
+            }
+            TokenKind::Impl => {
+                let (decl, end) = self.parse_impl()?;
+                Some(Item::new(ItemKind::Impl(decl), start.merge(end)))
+            }
+            TokenKind::StaticAssert => {
+                if is_pub {
+                    self.report_error(ParserError::InvalidSyntax {
+                        message: "static assertions cannot be `pub`".to_string(),
+                        span: start,
+                        file_id: self.file_id,
+                    });
+                }
+                self.advance();
+                self.expect_or_report(TokenKind::LParen)?;
+                let cond = self.parse_expr()?;
+                let message = if self.match_token(TokenKind::Comma) {
+                    Some(self.parse_str()?)
+                } else {
+                    None
+                };
+                let end = self.expect_or_report(TokenKind::RParen)?.span;
+                self.expect_item_end()?;
+                let assert = StaticAssert { cond, message };
+                Some(Item::new(ItemKind::StaticAssert(assert), start.merge(end)))
+            }
+            TokenKind::Let => {
+                let error = ParserError::InvalidSyntax {
+                    message: "`let` is only allowed inside functions".to_string(),
+                    span: self.current().span,
+                    file_id: self.file_id,
+                };
+                // `parse_program` skips to the next item of any kind
+                self.report_error(error);
+                None
+            }
+            _ => {
+                let current = self.current();
+                let error = ParserError::UnexpectedToken {
+                    expected: vec![
+                        TokenKind::Fn,
+                        TokenKind::Mod,
+                        TokenKind::Use,
+                        TokenKind::Trait,
+                        TokenKind::Impl,
+                        TokenKind::StaticAssert,
+                    ],
+                    found: current.inner.clone(),
+                    span: current.span,
+                    file_id: self.file_id,
+                };
+                // `parse_program` skips to the next item of any kind
+                self.report_error(error);
+                None
+            }
+        }
+    }
+
+    /// Expects the newline that ends a single-line item.
+    fn expect_item_end(&mut self) -> Option<()> {
+        if self.is_at_end() {
+            return Some(());
+        }
+        self.expect_or_report(TokenKind::Newline).map(|_| ())
+    }
+
+    fn parse_func(&mut self, is_pub: bool) -> Option<Func> {
+        self.expect_or_report(TokenKind::Fn)?;
+        let name = self.parse_ident()?;
+        let mut generics = Vec::new();
+        if self.match_token(TokenKind::Less) {
+            while !self.check(&TokenKind::Greater) && !self.is_at_end() {
+                generics.push(self.parse_ident()?);
+                if !self.match_token(TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.expect_or_report(TokenKind::Greater)?;
+        }
+        let params = self.parse_params()?;
+        let ty = self.parse_return_type()?;
+        self.expect_or_report(TokenKind::Colon)?;
+        let body = self.parse_block()?;
+        Some(Func {
+            is_pub,
+            name,
+            generics,
+            params,
+            ty,
+            body,
+        })
+    }
+
+    /// Parses a parenthesized parameter list. A leading `self` is the
+    /// receiver of a method.
+    fn parse_params(&mut self) -> Option<Vec<Spanned<Param>>> {
+        self.expect_or_report(TokenKind::LParen)?;
+        let mut params = Vec::new();
+        while !self.check(&TokenKind::RParen) && !self.is_at_end() {
+            if params.is_empty()
+                && let TokenKind::Ident(name) = self.current_kind()
+                && *name == sym::SELF
+            {
+                let span = self.advance().span;
+                params.push(Spanned::new(Param::SelfParam, span));
+            } else {
+                params.push(self.parse_named_param()?);
+            }
+            if !self.match_token(TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect_or_report(TokenKind::RParen)?;
+        Some(params)
+    }
+
+    /// Parses `mut? name (: type)?`, a parameter of a function or closure.
+    fn parse_named_param(&mut self) -> Option<Spanned<Param>> {
+        let start = self.current().span;
+        let mutable = self.match_token(TokenKind::Mut);
+        let name = self.parse_ident()?;
+        let ty = if self.match_token(TokenKind::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        let end = ty.as_ref().map_or(name.span, |ty| ty.span);
+        Some(Spanned::new(
+            Param::Named { name, mutable, ty },
+            start.merge(end),
+        ))
+    }
+
+    fn parse_return_type(&mut self) -> Option<Option<Spanned<Type>>> {
+        if self.match_token(TokenKind::Arrow) {
+            Some(Some(self.parse_type()?))
+        } else {
+            Some(None)
+        }
+    }
+
+    /// Parses `trait Name:` and the indented method signatures after it,
+    /// returning the declaration and where it ends.
+    fn parse_trait(&mut self, is_pub: bool) -> Option<(TraitDecl, Span)> {
+        self.expect_or_report(TokenKind::Trait)?;
+        let name = self.parse_ident()?;
+        let mut end = name.span;
+        self.expect_or_report(TokenKind::Colon)?;
+        self.expect_or_report(TokenKind::Newline)?;
+        self.expect_or_report(TokenKind::Indent)?;
+        let mut methods = Vec::new();
+        loop {
+            self.skip_newlines();
+            if self.check(&TokenKind::Dedent) || self.is_at_end() {
+                break;
+            }
+            let start = self.current().span;
+            let Some(method) = self.parse_method_sig() else {
+                self.synchronize_to_newline();
+                continue;
+            };
+            end = method
+                .ty
+                .as_ref()
+                .map_or(self.tokens[self.pos - 1].span, |ty| ty.span);
+            methods.push(Spanned::new(method, start.merge(end)));
+            if !self.check(&TokenKind::Dedent) {
+                self.expect_item_end()?;
+            }
+        }
+        self.match_token(TokenKind::Dedent);
+        Some((
+            TraitDecl {
+                is_pub,
+                name,
+                methods,
+            },
+            end,
+        ))
+    }
+
+    fn parse_method_sig(&mut self) -> Option<MethodSig> {
+        self.expect_or_report(TokenKind::Fn)?;
+        let name = self.parse_ident()?;
+        let params = self.parse_params()?;
+        let ty = self.parse_return_type()?;
+        Some(MethodSig { name, params, ty })
+    }
+
+    /// Parses `impl Trait for Type:` and the indented methods after it,
+    /// returning the declaration and where it ends.
+    fn parse_impl(&mut self) -> Option<(ImplDecl, Span)> {
+        self.expect_or_report(TokenKind::Impl)?;
+        let trait_name = self.parse_ident()?;
+        self.expect_or_report(TokenKind::For)?;
+        let self_ty = self.parse_type()?;
+        let mut end = self_ty.span;
+        self.expect_or_report(TokenKind::Colon)?;
+        self.expect_or_report(TokenKind::Newline)?;
+        self.expect_or_report(TokenKind::Indent)?;
+        let mut methods = Vec::new();
+        loop {
+            self.skip_newlines();
+            if self.check(&TokenKind::Dedent) || self.is_at_end() {
+                break;
+            }
+            match self.parse_func(false) {
+                Some(method) => {
+                    end = method.body.span;
+                    methods.push(method);
+                }
+                None => self.synchronize_to_newline(),
+            }
+        }
+        self.match_token(TokenKind::Dedent);
+        Some((
+            ImplDecl {
+                trait_name,
+                self_ty,
+                methods,
+            },
+            end,
+        ))
+    }
+
+    fn parse_ident(&mut self) -> Option<Spanned<Symbol>> {
+        match self.current_kind().clone() {
+            TokenKind::Ident(name) => {
+                let span = self.advance().span;
+                Some(Spanned::new(name, span))
+            }
+            found => {
+                let span = self.current().span;
+                self.report_error(ParserError::UnexpectedToken {
+                    expected: vec![TokenKind::Ident(Symbol::intern(""))],
+                    found,
+                    span,
+                    file_id: self.file_id,
+                });
+                None
+            }
+        }
+    }
+
+    fn parse_str(&mut self) -> Option<Spanned<String>> {
+        match self.current_kind().clone() {
+            TokenKind::StrLiteral(text) => {
+                let span = self.advance().span;
+                Some(Spanned::new(unescape(&text), span))
+            }
+            found => {
+                let span = self.current().span;
+                self.report_error(ParserError::UnexpectedToken {
+                    expected: vec![TokenKind::StrLiteral(String::new())],
+                    found,
+                    span,
+                    file_id: self.file_id,
+                });
+                None
+            }
+        }
+    }
+
+    /// Splits the text of the string literal at `span` into literal text
+    /// and `{name}` interpolations. `{{` and `}}` stand for braces.
+    fn parse_str_parts(&mut self, text: &str, span: Span) -> Vec<StrPart> {
+        let chars: Vec<char> = text.chars().collect();
+        // the text starts after the opening quote
+        let offsets: Vec<usize> = text
+            .char_indices()
+            .map(|(offset, _)| span.start.0 + 1 + offset)
+            .chain([span.start.0 + 1 + text.len()])
+            .collect();
+        let mut parts = Vec::new();
+        let mut lit = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' => {
+                    lit.extend(&chars[i..i + 2]);
+                    i += 2;
+                }
+                '{' | '}' if chars.get(i + 1) == Some(&chars[i]) => {
+                    lit.push(chars[i]);
+                    i += 2;
+                }
+                '{' => {
+                    let Some(end) = chars[i..].iter().position(|&ch| ch == '}') else {
+                        self.report_error(ParserError::InvalidSyntax {
+                            message: "unclosed `{` in string; write `{{` for a brace".to_string(),
+                            span: Span::from_range(offsets[i]..offsets[i + 1]),
+                            file_id: self.file_id,
+                        });
+                        break;
+                    };
+                    let end = i + end;
+                    let name: String = chars[i + 1..end].iter().collect();
+                    let name_span = Span::from_range(offsets[i + 1]..offsets[end]);
+                    let is_ident = name
+                        .chars()
+                        .next()
+                        .is_some_and(|ch| ch.is_alphabetic() || ch == '_')
+                        && name.chars().all(|ch| ch.is_alphanumeric() || ch == '_');
+                    if is_ident {
+                        if !lit.is_empty() {
+                            parts.push(StrPart::Lit(unescape(&std::mem::take(&mut lit))));
+                        }
+                        parts.push(StrPart::Expr(Expr::new(
+                            ExprKind::Var(Symbol::intern(&name)),
+                            name_span,
+                        )));
+                    } else {
+                        self.report_error(ParserError::InvalidSyntax {
+                            message: "only variables can be interpolated".to_string(),
+                            span: Span::from_range(offsets[i]..offsets[end + 1]),
+                            file_id: self.file_id,
+                        });
+                    }
+                    i = end + 1;
+                }
+                '}' => {
+                    self.report_error(ParserError::InvalidSyntax {
+                        message: "unmatched `}` in string; write `}}` for a brace".to_string(),
+                        span: Span::from_range(offsets[i]..offsets[i + 1]),
+                        file_id: self.file_id,
+                    });
+                    i += 1;
+                }
+                ch => {
+                    lit.push(ch);
+                    i += 1;
+                }
+            }
+        }
+        if !lit.is_empty() {
+            parts.push(StrPart::Lit(unescape(&lit)));
+        }
+        parts
+    }
+
+    fn parse_type(&mut self) -> Option<Spanned<Type>> {
+        let ty = match self.current_kind() {
+            TokenKind::U64 => Type::U64,
+            TokenKind::I64 => Type::I64,
+            TokenKind::F64 => Type::F64,
+            TokenKind::Bool => Type::Bool,
+            TokenKind::Str => Type::Str,
+            TokenKind::Ident(sym::SELF_TYPE) => Type::SelfType,
+            TokenKind::Ident(name) => Type::Param(*name),
+            TokenKind::LParen => {
+                let l_span = self.advance().span;
+                let r_span = self.expect_or_report(TokenKind::RParen)?.span;
+                return Some(Spanned::new(Type::Unit, l_span.merge(r_span)));
+            }
+            TokenKind::Fn => return self.parse_fn_type(),
+            found => {
+                let error = ParserError::UnexpectedToken {
+                    expected: vec![
+                        TokenKind::U64,
+                        TokenKind::I64,
+                        TokenKind::F64,
+                        TokenKind::Bool,
+                        TokenKind::Str,
+                        TokenKind::Ident(Symbol::intern("")),
+                    ],
+                    found: found.clone(),
+                    span: self.current().span,
+                    file_id: self.file_id,
+                };
+                self.report_error(error);
+                return None;
+            }
+        };
+        let span = self.advance().span;
+        Some(Spanned::new(ty, span))
+    }
+
+    /// Parses `fn(params) -> ret`, where `-> ret` defaults to `()`.
+    fn parse_fn_type(&mut self) -> Option<Spanned<Type>> {
+        let start = self.expect_or_report(TokenKind::Fn)?.span;
+        self.expect_or_report(TokenKind::LParen)?;
+        let mut params = Vec::new();
+        while !self.check(&TokenKind::RParen) && !self.is_at_end() {
+            params.push(self.parse_type()?);
+            if !self.match_token(TokenKind::Comma) {
+                break;
+            }
+        }
+        let r_span = self.expect_or_report(TokenKind::RParen)?.span;
+        let ret = if self.match_token(TokenKind::Arrow) {
+            self.parse_type()?
+        } else {
+            Spanned::new(Type::Unit, r_span)
+        };
+        let span = start.merge(ret.span);
+        Some(Spanned::new(
+            Type::Fn {
+                params,
+                ret: Box::new(ret),
+            },
+            span,
+        ))
+    }
+
+    /// Parses the body following a `:`, either an indented block or a single
+    /// statement on the same line.
+    fn parse_block(&mut self) -> Option<Expr> {
+        if !self.check(&TokenKind::Newline) {
+            return self.parse_stmt();
+        }
+        let start = self.advance().span;
+        self.expect_or_report(TokenKind::Indent)?;
+
+        let mut exprs = self.parse_stmt_list();
+        let end = self.current().span;
+        self.match_token(TokenKind::Dedent);
+
+        let tail = match exprs.last() {
+            Some(expr) if !matches!(expr.inner, ExprKind::Let { .. } | ExprKind::Assign { .. }) => {
+                exprs.pop().map(Box::new)
+            }
+            _ => None,
+        };
+        let span = exprs
+            .first()
+            .or(tail.as_deref())
+            .map_or(start, |first| first.span.merge(end));
+        Some(Expr::new(ExprKind::Block { exprs, tail }, span))
+    }
+
+    /// Parses statements on consecutive lines, up to the end of the block or
+    /// of the input, like the input of an interactive session.
+    pub fn parse_stmts(&mut self) -> Vec<Expr> {
+        self.skip_newlines();
+        self.parse_stmt_list()
+    }
+
+    fn parse_stmt_list(&mut self) -> Vec<Expr> {
+        let mut exprs = Vec::new();
+        loop {
+            self.skip_newlines();
+            if self.check(&TokenKind::Dedent) || self.is_at_end() {
+                break;
+            }
+            let start = self.pos;
+            let errors = self.errors.len();
+            match self.parse_stmt() {
+                Some(expr) => exprs.push(expr),
+                None => {
+                    self.synchronize_to_statement();
+                    // a statement can't start with the token recovery
+                    // stopped at, such as a nested `fn`
+                    if self.pos == start {
+                        self.advance();
+                    }
+                    continue;
+                }
+            }
+            // an error node for a missing expression consumes nothing
+            let stuck = self.pos == start;
+            if stuck
+                || !self.check_any(&[TokenKind::Newline, TokenKind::Dedent])
+                    && !self.is_at_end()
+                    && !self.ended_block()
+            {
+                // the statement stopped at an error it reported
+                if stuck || self.errors.len() > errors {
+                    self.synchronize_to_statement();
+                    if self.pos == start {
+                        self.advance();
+                    }
+                    continue;
+                }
+                if let Some(error) = exprs.last().and_then(|expr| self.misspelled_keyword(expr)) {
+                    self.report_error(error);
+                    self.synchronize_to_newline();
+                    continue;
+                }
+                let current = self.current();
+                let error = ParserError::UnexpectedToken {
+                    expected: vec![TokenKind::Newline],
+                    found: current.inner.clone(),
+                    span: current.span,
+                    file_id: self.file_id,
+                };
+                self.report_error(error);
+                self.synchronize_to_newline();
+            }
+        }
+        exprs
+    }
+
+    /// The error for a statement `stmt` that is only a name followed by an
+    /// identifier, if the name looks like a misspelled keyword, as in
+    /// `lte x = 1`.
+    fn misspelled_keyword(&self, stmt: &Expr) -> Option<ParserError> {
+        let ExprKind::Var(name) = stmt.inner else {
+            return None;
+        };
+        if !matches!(self.current_kind(), TokenKind::Ident(_)) {
+            return None;
+        }
+        let keywords = STMT_KEYWORDS.iter().filter_map(TokenKind::keyword);
+        let keyword = name.with(|name| find_similar(name, keywords))?;
+        Some(ParserError::MisspelledKeyword {
+            name: name.to_string(),
+            keyword,
+            span: stmt.span,
+            file_id: self.file_id,
+        })
+    }
+
+    fn parse_stmt(&mut self) -> Option<Expr> {
+        match self.current_kind() {
+            TokenKind::Let => self.parse_let(),
+            TokenKind::Ident(_) if self.peek_is(1, TokenKind::Equal) => self.parse_assign(),
+            _ => self.parse_expr(),
+        }
+    }
+
+    fn parse_let(&mut self) -> Option<Expr> {
+        let start = self.expect_or_report(TokenKind::Let)?.span;
+        let mutable = self.match_token(TokenKind::Mut);
+        let name = self.parse_ident()?;
+        let ty = if self.match_token(TokenKind::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        let value = if self.match_token(TokenKind::Equal) {
+            Some(Box::new(self.parse_expr()?))
+        } else {
+            None
+        };
+        let end = value
+            .as_ref()
+            .map(|value| value.span)
+            .or(ty.as_ref().map(|ty| ty.span))
+            .unwrap_or(name.span);
+        Some(Expr::new(
+            ExprKind::Let {
+                name,
+                mutable,
+                ty,
+                value,
+            },
+            start.merge(end),
+        ))
+    }
+
+    fn parse_assign(&mut self) -> Option<Expr> {
+        let name = self.parse_ident()?;
+        self.expect_or_report(TokenKind::Equal)?;
+        let value = self.parse_expr()?;
+        let span = name.span.merge(value.span);
+        Some(Expr::new(
+            ExprKind::Assign {
+                name,
+                value: Box::new(value),
+            },
+            span,
+        ))
+    }
+
     pub fn parse_expr(&mut self) -> Option<Expr> {
         self.parse_binary_expr(0)
     }
@@ -272,36 +969,242 @@ impl Parser {
                 let span = self.advance().span;
                 Some(Expr::new(ExprKind::Int(x), span))
             }
+            TokenKind::FloatLiteral(x) => {
+                let span = self.advance().span;
+                Some(Expr::new(ExprKind::Float(x), span))
+            }
             TokenKind::BoolLiteral(v) => {
                 let span = self.advance().span;
                 Some(Expr::new(ExprKind::Bool(v), span))
             }
+            TokenKind::StrLiteral(text) => {
+                let span = self.advance().span;
+                let parts = self.parse_str_parts(&text, span);
+                Some(Expr::new(ExprKind::Str(parts), span))
+            }
             TokenKind::Ident(v) => {
                 let span = self.advance().span;
-                Some(Expr::new(ExprKind::Var(v), span))
+                if !self.check(&TokenKind::ColonColon) {
+                    return Some(Expr::new(ExprKind::Var(v), span));
+                }
+                let mut path = vec![Spanned::new(v, span)];
+                while self.match_token(TokenKind::ColonColon) {
+                    path.push(self.parse_ident()?);
+                }
+                let span = span.merge(path[path.len() - 1].span);
+                Some(Expr::new(ExprKind::Path(path), span))
+            }
+            TokenKind::Match => self.parse_match(),
+            TokenKind::If => self.parse_if(),
+            TokenKind::While => self.parse_while(),
+            TokenKind::Pipe | TokenKind::Or => self.parse_closure(),
+            TokenKind::Return => {
+                let start = self.advance().span;
+                if self.check_any(&[
+                    TokenKind::Newline,
+                    TokenKind::Dedent,
+                    TokenKind::RParen,
+                    TokenKind::Comma,
+                ]) || self.is_at_end()
+                {
+                    return Some(Expr::new(ExprKind::Return(None), start));
+                }
+                let value = self.parse_expr()?;
+                let span = start.merge(value.span);
+                Some(Expr::new(ExprKind::Return(Some(Box::new(value))), span))
             }
             TokenKind::LParen => {
                 let l_span = self.advance().span;
-                if self.peek_is(1, TokenKind::RParen) {
+                if self.peek_is(0, TokenKind::RParen) {
                     let r_span = self.advance().span;
                     let span = l_span.merge(r_span);
                     Some(Expr::new(ExprKind::Unit, span))
                 } else {
                     let Spanned { inner, span } = self.parse_expr()?;
-                    let r_span = self.advance().span;
-                    let span = l_span.merge(span).merge(r_span);
+                    let span = match self.expect_closing_paren(l_span) {
+                        Some(r_span) => l_span.merge(span).merge(r_span),
+                        None => l_span.merge(span),
+                    };
                     Some(Expr::new(inner, span))
                 }
             }
+            // an error node keeps the rest of the tree, leaving the token
+            // for the caller to recover from
             _ => {
-                let current = self.current();
+                let span = self.current().span;
                 self.report_error(ParserError::MissingExpression {
-                    span: current.span,
+                    span,
                     file_id: self.file_id,
                 });
-                None
+                Some(Expr::new(ExprKind::Error, span))
+            }
+        }
+    }
+
+    /// Parses `|params| body`, where `||` is a closure without parameters
+    /// and the body is an expression or an indented block.
+    fn parse_closure(&mut self) -> Option<Expr> {
+        let start = self.current().span;
+        let mut params = Vec::new();
+        if !self.match_token(TokenKind::Or) {
+            self.expect_or_report(TokenKind::Pipe)?;
+            while !self.check(&TokenKind::Pipe) && !self.is_at_end() {
+                params.push(self.parse_named_param()?);
+                if !self.match_token(TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.expect_or_report(TokenKind::Pipe)?;
+        }
+        let body = self.parse_block()?;
+        let span = start.merge(body.span);
+        Some(Expr::new(
+            ExprKind::Closure {
+                params,
+                body: Box::new(body),
+            },
+            span,
+        ))
+    }
+
+    fn parse_if(&mut self) -> Option<Expr> {
+        let start = self.expect_or_report(TokenKind::If)?.span;
+        let cond = self.parse_expr()?;
+        self.expect_or_report(TokenKind::Colon)?;
+        let then_branch = self.parse_block()?;
+
+        // `if c: a` followed by `else: b` on the next line
+        if self.check(&TokenKind::Newline) && self.peek_is(1, TokenKind::Else) {
+            self.advance();
+        }
+        let else_branch = if self.match_token(TokenKind::Else) {
+            if self.check(&TokenKind::If) {
+                Some(self.parse_if()?)
+            } else {
+                self.expect_or_report(TokenKind::Colon)?;
+                Some(self.parse_block()?)
+            }
+        } else {
+            None
+        };
+
+        let end = else_branch.as_ref().unwrap_or(&then_branch).span;
+        Some(Expr::new(
+            ExprKind::If {
+                cond: Box::new(cond),
+                then_branch: Box::new(then_branch),
+                else_branch: else_branch.map(Box::new),
+            },
+            start.merge(end),
+        ))
+    }
+
+    fn parse_while(&mut self) -> Option<Expr> {
+        let start = self.expect_or_report(TokenKind::While)?.span;
+        let cond = self.parse_expr()?;
+        self.expect_or_report(TokenKind::Colon)?;
+        let body = self.parse_block()?;
+        let span = start.merge(body.span);
+        Some(Expr::new(
+            ExprKind::While {
+                cond: Box::new(cond),
+                body: Box::new(body),
+            },
+            span,
+        ))
+    }
+
+    fn parse_match(&mut self) -> Option<Expr> {
+        let start = self.expect_or_report(TokenKind::Match)?.span;
+        let scrutinee = self.parse_expr()?;
+        self.expect_or_report(TokenKind::Colon)?;
+        self.expect_or_report(TokenKind::Newline)?;
+        self.expect_or_report(TokenKind::Indent)?;
+
+        let mut arms = Vec::new();
+        loop {
+            self.skip_newlines();
+            if self.check(&TokenKind::Dedent) || self.is_at_end() {
+                break;
+            }
+            let Some(pattern) = self.parse_pattern() else {
+                self.synchronize_to_newline();
+                continue;
+            };
+            if self.expect_or_report(TokenKind::Arrow).is_none() {
+                self.synchronize_to_newline();
+                continue;
+            }
+            match self.parse_block() {
+                Some(body) => arms.push(MatchArm { pattern, body }),
+                None => self.synchronize_to_newline(),
             }
         }
+        self.match_token(TokenKind::Dedent);
+
+        let end = arms.last().map_or(scrutinee.span, |arm| arm.body.span);
+        let span = start.merge(end);
+        Some(Expr::new(
+            ExprKind::Match {
+                scrutinee: Box::new(scrutinee),
+                arms,
+            },
+            span,
+        ))
+    }
+
+    fn parse_pattern(&mut self) -> Option<Pattern> {
+        let first = self.parse_primary_pattern()?;
+        if !self.check(&TokenKind::Pipe) {
+            return Some(first);
+        }
+        let mut alternatives = vec![first];
+        while self.match_token(TokenKind::Pipe) {
+            alternatives.push(self.parse_primary_pattern()?);
+        }
+        let span = alternatives[0]
+            .span
+            .merge(alternatives[alternatives.len() - 1].span);
+        Some(Pattern::new(PatternKind::Or(alternatives), span))
+    }
+
+    fn parse_primary_pattern(&mut self) -> Option<Pattern> {
+        let kind = match self.current_kind().clone() {
+            TokenKind::Underscore => PatternKind::Wildcard,
+            TokenKind::Ident(name) => PatternKind::Bind(name),
+            TokenKind::IntLiteral(v) => PatternKind::Int(v as i128),
+            TokenKind::BoolLiteral(v) => PatternKind::Bool(v),
+            TokenKind::Minus if matches!(self.peek(1).inner, TokenKind::IntLiteral(_)) => {
+                let start = self.advance().span;
+                let TokenKind::IntLiteral(v) = self.current_kind().clone() else {
+                    unreachable!()
+                };
+                let span = start.merge(self.advance().span);
+                return Some(Pattern::new(PatternKind::Int(-(v as i128)), span));
+            }
+            TokenKind::LParen if self.peek_is(1, TokenKind::RParen) => {
+                let start = self.advance().span;
+                let span = start.merge(self.advance().span);
+                return Some(Pattern::new(PatternKind::Unit, span));
+            }
+            found => {
+                let span = self.current().span;
+                self.report_error(ParserError::UnexpectedToken {
+                    expected: vec![
+                        TokenKind::Underscore,
+                        TokenKind::Ident(Symbol::intern("")),
+                        TokenKind::IntLiteral(0),
+                        TokenKind::BoolLiteral(true),
+                    ],
+                    found,
+                    span,
+                    file_id: self.file_id,
+                });
+                return None;
+            }
+        };
+        let span = self.advance().span;
+        Some(Pattern::new(kind, span))
     }
 
     fn parse_unary_expr(&mut self) -> Option<Expr> {
@@ -330,13 +1233,103 @@ impl Parser {
                     span,
                 ))
             }
-            _ => self.parse_primary(),
+            _ => self.parse_postfix_expr(),
+        }
+    }
+
+    fn parse_postfix_expr(&mut self) -> Option<Expr> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            // a line after a block starts a new statement
+            if self.ended_block() {
+                return Some(expr);
+            }
+            if self.match_token(TokenKind::Dot) {
+                let method = self.parse_ident()?;
+                let l_span = self.expect_or_report(TokenKind::LParen)?.span;
+                let (args, r_span) = self.parse_args(l_span)?;
+                let span = expr.span.merge(r_span);
+                expr = Expr::new(
+                    ExprKind::MethodCall {
+                        receiver: Box::new(expr),
+                        method,
+                        args,
+                    },
+                    span,
+                );
+            } else if self.check(&TokenKind::LParen) {
+                let l_span = self.advance().span;
+                let (args, r_span) = self.parse_args(l_span)?;
+                let span = expr.span.merge(r_span);
+                expr = Expr::new(
+                    ExprKind::Call {
+                        callee: Box::new(expr),
+                        args,
+                    },
+                    span,
+                );
+            } else {
+                return Some(expr);
+            }
         }
     }
 
+    /// Parses call arguments after the `(` at `l_span`, returning them and
+    /// the span of the closing `)`.
+    fn parse_args(&mut self, l_span: Span) -> Option<(Vec<Expr>, Span)> {
+        let mut args = Vec::new();
+        while !self.check(&TokenKind::RParen) && !self.is_at_end() {
+            args.push(self.parse_expr()?);
+            if !self.match_token(TokenKind::Comma) {
+                break;
+            }
+        }
+        let Some(r_span) = self.expect_closing_paren(l_span) else {
+            self.synchronize_to_newline();
+            return None;
+        };
+        Some((args, r_span))
+    }
+
+    /// Consumes the `)` closing the `(` at `l_span`, or reports that it is
+    /// missing without consuming anything.
+    fn expect_closing_paren(&mut self, l_span: Span) -> Option<Span> {
+        if self.check(&TokenKind::RParen) {
+            return Some(self.advance().span);
+        }
+        let current = self.current();
+        let error = ParserError::UnclosedParen {
+            open: l_span,
+            found: current.inner.clone(),
+            span: current.span,
+            file_id: self.file_id,
+        };
+        self.report_error(error);
+        None
+    }
+
     fn parse_binary_expr(&mut self, min_bp: u8) -> Option<Expr> {
         let mut lhs = self.parse_unary_expr()?;
         loop {
+            if self.ended_block() {
+                break;
+            }
+            if self.check(&TokenKind::As) {
+                if BinOp::CAST_BINDING_POWER < min_bp {
+                    break;
+                }
+                self.advance();
+                let ty = self.parse_type()?;
+                let span = lhs.span.merge(ty.span);
+                lhs = Expr::new(
+                    ExprKind::Cast {
+                        expr: Box::new(lhs),
+                        ty,
+                    },
+                    span,
+                );
+                continue;
+            }
             let op = match self.current_kind() {
                 TokenKind::Plus => BinOp::Add,
                 TokenKind::Minus => BinOp::Sub,
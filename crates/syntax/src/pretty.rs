@@ -0,0 +1,680 @@
+use ginto_diag::{BytePos, FileId, Span, Spanned, Symbol};
+
+use crate::{
+    BinOp, Cst, CstKind, Expr, ExprKind, Func, Item, ItemKind, Lexer, MethodSig, Param, Pattern,
+    PatternKind, Program, StrPart, TokenKind, join_path,
+};
+
+/// Prints `program` back as source, with every block indented by four
+/// spaces and parentheses only where they are needed.
+pub fn pretty_print(program: &Program) -> String {
+    Printer::default().program(program)
+}
+
+/// Prints `program` as `pretty_print` does, keeping a blank line between
+/// the statements, match arms, method signatures and `mod` and `use`
+/// declarations that `cst`, the file it was parsed from, has blank lines
+/// between, and the comments of `cst`: a comment after code at the end of
+/// its line, and one on a line of its own before the line of code after
+/// it, or at the end of the block it ends if it is indented as the block
+/// is.
+pub fn pretty_print_with_source(program: &Program, cst: &Cst) -> String {
+    pretty_print_with_indent(program, cst, DEFAULT_INDENT_WIDTH)
+}
+
+/// How many spaces `pretty_print` indents each block by.
+pub const DEFAULT_INDENT_WIDTH: usize = 4;
+
+/// Prints `program` as `pretty_print_with_source` does, indenting each
+/// block by `indent_width` spaces.
+pub fn pretty_print_with_indent(program: &Program, cst: &Cst, indent_width: usize) -> String {
+    let printer = Printer {
+        source: cst.source().to_string(),
+        comments: comments(cst),
+        indent_width,
+        ..Printer::default()
+    };
+    printer.program(program)
+}
+
+/// The signature `func` starts with, like `pub fn max<T>(a: T, b: T) -> T`.
+pub fn func_signature(func: &Func) -> String {
+    let mut printer = Printer::default();
+    printer.func_sig(func);
+    printer.out
+}
+
+/// The signature of a method declared by a trait, like `fn show(self) -> str`.
+pub fn method_signature(sig: &MethodSig) -> String {
+    let mut printer = Printer::default();
+    printer.method_sig(sig);
+    printer.out
+}
+
+/// Whether `text` is just the literal `token`.
+fn is_literal(text: &str, token: &TokenKind) -> bool {
+    match Lexer::new(FileId(0), text).lex_all().as_deref() {
+        Ok([literal, eof]) => literal.inner == *token && eof.inner == TokenKind::Eof,
+        _ => false,
+    }
+}
+
+fn is_decl(item: &Item) -> bool {
+    matches!(item.inner, ItemKind::Mod(_) | ItemKind::Use(_))
+}
+
+/// A comment of the source.
+#[derive(Debug, Clone, Copy)]
+struct Comment {
+    span: Span,
+    /// Whether code comes before the comment on its line.
+    trailing: bool,
+}
+
+/// The comments of `cst`, in order.
+fn comments(cst: &Cst) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    let mut trailing = false;
+    for token in cst.tokens() {
+        match token.kind {
+            CstKind::Comment => comments.push(Comment {
+                span: token.span,
+                trailing,
+            }),
+            _ if cst.text(token).contains('\n') => trailing = false,
+            // indents, dedents and the end of the file have no text
+            CstKind::Token(_) if !token.span.is_empty() => trailing = true,
+            CstKind::Token(_) | CstKind::Whitespace => {}
+        }
+    }
+    comments
+}
+
+struct Printer {
+    out: String,
+    indent: usize,
+    indent_width: usize,
+    /// The source the program was parsed from, if any.
+    source: String,
+    /// The comments of the source, of which those from `next_comment` on
+    /// are yet to be printed.
+    comments: Vec<Comment>,
+    next_comment: usize,
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self {
+            out: String::new(),
+            indent: 0,
+            indent_width: DEFAULT_INDENT_WIDTH,
+            source: String::new(),
+            comments: Vec::new(),
+            next_comment: 0,
+        }
+    }
+}
+
+impl Printer {
+    fn program(mut self, program: &Program) -> String {
+        let mut prev: Option<&Item> = None;
+        for item in &program.items {
+            // consecutive `mod` and `use` declarations are kept together
+            let grouped = prev.is_some_and(|prev| is_decl(prev) && is_decl(item));
+            let commented = self.comments_before(item.span.start, prev.is_none(), !grouped);
+            if prev.is_some() || commented {
+                if (!grouped && !commented) || self.blank_before(item.span.start) {
+                    self.out.push('\n');
+                }
+                self.newline();
+            }
+            self.item(item);
+            prev = Some(item);
+        }
+        self.comments_before(BytePos(self.source.len()), prev.is_none(), false);
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out
+    }
+
+    /// Prints the comments before `pos` that haven't been printed, as
+    /// `comment` does. Returns whether one was printed on a line of its
+    /// own.
+    fn comments_before(&mut self, pos: BytePos, mut first: bool, blank: bool) -> bool {
+        let mut own_line = false;
+        while let Some(&comment) = self.comments.get(self.next_comment)
+            && comment.span.start < pos
+        {
+            own_line |= self.comment(comment, first, blank && !own_line);
+            first &= !own_line;
+        }
+        own_line
+    }
+
+    /// Prints the comments that end the block of statements `stmts`: those
+    /// after it with only blank lines before them, indented as deep as the
+    /// block at least.
+    fn comments_ending_block(&mut self, stmts: Span) {
+        let mut end = stmts.end;
+        while let Some(&comment) = self.comments.get(self.next_comment)
+            // a statement ending in a block may span the blank lines after it
+            && (end >= comment.span.start
+                || self.source[end.0..comment.span.start.0].trim().is_empty())
+            && (comment.trailing || self.column(comment.span.start) >= self.column(stmts.start))
+        {
+            self.comment(comment, false, false);
+            end = comment.span.end;
+        }
+    }
+
+    /// Prints the next comment, `comment`: after the code before it on its
+    /// line at the end of the last line, or else on a line of its own. The
+    /// line is after a blank line if the source has one before it, or if
+    /// `blank`, unless it is the `first` line of a block. Returns whether
+    /// the comment was printed on a line of its own.
+    fn comment(&mut self, comment: Comment, first: bool, blank: bool) -> bool {
+        self.next_comment += 1;
+        let span = comment.span;
+        let text = self.source[span.start.0..span.end.0].to_string();
+        if comment.trailing {
+            self.out.push_str("  ");
+            self.out.push_str(&text);
+            return false;
+        }
+        if !self.out.is_empty() {
+            if !first && (blank || self.blank_before(span.start)) {
+                self.out.push('\n');
+            }
+            self.newline();
+        }
+        self.out.push_str(&text);
+        true
+    }
+
+    /// The column of `pos`, in bytes.
+    fn column(&self, pos: BytePos) -> usize {
+        pos.0 - self.line_start(pos).0
+    }
+
+    /// The start of the line of `pos`.
+    fn line_start(&self, pos: BytePos) -> BytePos {
+        let before = self.source.get(..pos.0).unwrap_or_default();
+        BytePos(before.rfind('\n').map_or(0, |i| i + 1))
+    }
+
+    /// Whether the source has a blank line right before `pos`.
+    fn blank_before(&self, pos: BytePos) -> bool {
+        let before = self.source.get(..pos.0).unwrap_or_default();
+        before
+            .chars()
+            .rev()
+            .take_while(|ch| ch.is_whitespace())
+            .filter(|&ch| ch == '\n')
+            .count()
+            > 1
+    }
+
+    /// The literal at `span` as the source writes it, like `0xFF` or
+    /// `1_000`, if the source has the literal `token` there.
+    fn written(&self, span: Span, token: &TokenKind) -> Option<String> {
+        let text = self.source.get(span.start.0..span.end.0)?;
+        is_literal(text, token).then(|| text.to_string())
+    }
+
+    /// Starts a line of a block, after the comments before it and a blank
+    /// line if the source has one before `pos`, where the line's code
+    /// starts.
+    fn line(&mut self, pos: BytePos, first: bool) {
+        let commented = self.comments_before(pos, first, false);
+        if (!first || commented) && self.blank_before(pos) {
+            self.out.push('\n');
+        }
+        self.newline();
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.indent * self.indent_width {
+            self.out.push(' ');
+        }
+    }
+
+    fn item(&mut self, item: &Item) {
+        match &item.inner {
+            ItemKind::Func(func) => self.func(func),
+            ItemKind::Mod(decl) => {
+                self.visibility(decl.is_pub);
+                self.out.push_str("mod ");
+                self.symbol(decl.name.inner);
+            }
+            ItemKind::Use(decl) => {
+                self.visibility(decl.is_pub);
+                self.out.push_str("use ");
+                self.out.push_str(&join_path(&decl.path));
+            }
+            ItemKind::Trait(decl) => {
+                self.visibility(decl.is_pub);
+                self.out.push_str("trait ");
+                self.symbol(decl.name.inner);
+                self.out.push(':');
+                self.indent += 1;
+                for (i, method) in decl.methods.iter().enumerate() {
+                    self.line(method.span.start, i == 0);
+                    self.method_sig(&method.inner);
+                }
+                self.indent -= 1;
+            }
+            ItemKind::Impl(decl) => {
+                self.out.push_str("impl ");
+                self.symbol(decl.trait_name.inner);
+                self.out.push_str(" for ");
+                self.out.push_str(&decl.self_ty.inner.to_string());
+                self.out.push(':');
+                self.indent += 1;
+                for (i, method) in decl.methods.iter().enumerate() {
+                    // the name is the only part of a method with a span
+                    let start = self.line_start(method.name.span.start);
+                    let commented = self.comments_before(start, i == 0, true);
+                    if (i > 0 && !commented) || (commented && self.blank_before(start)) {
+                        self.out.push('\n');
+                    }
+                    self.newline();
+                    self.func(method);
+                }
+                self.indent -= 1;
+            }
+            ItemKind::StaticAssert(assert) => {
+                self.out.push_str("static_assert(");
+                self.expr(&assert.cond);
+                if let Some(message) = &assert.message {
+                    self.out.push_str(", ");
+                    self.str_lit(&message.inner, false);
+                }
+                self.out.push(')');
+            }
+        }
+    }
+
+    fn visibility(&mut self, is_pub: bool) {
+        if is_pub {
+            self.out.push_str("pub ");
+        }
+    }
+
+    fn symbol(&mut self, name: Symbol) {
+        name.with(|name| self.out.push_str(name));
+    }
+
+    fn func(&mut self, func: &Func) {
+        self.func_sig(func);
+        self.out.push(':');
+        self.body(&func.body);
+    }
+
+    fn func_sig(&mut self, func: &Func) {
+        self.visibility(func.is_pub);
+        self.out.push_str("fn ");
+        self.symbol(func.name.inner);
+        if !func.generics.is_empty() {
+            let generics: Vec<_> = func.generics.iter().map(|g| g.inner.to_string()).collect();
+            self.out.push('<');
+            self.out.push_str(&generics.join(", "));
+            self.out.push('>');
+        }
+        self.out.push('(');
+        self.params(&func.params);
+        self.out.push(')');
+        if let Some(ty) = &func.ty {
+            self.out.push_str(" -> ");
+            self.out.push_str(&ty.inner.to_string());
+        }
+    }
+
+    fn method_sig(&mut self, sig: &MethodSig) {
+        self.out.push_str("fn ");
+        self.symbol(sig.name.inner);
+        self.out.push('(');
+        self.params(&sig.params);
+        self.out.push(')');
+        if let Some(ty) = &sig.ty {
+            self.out.push_str(" -> ");
+            self.out.push_str(&ty.inner.to_string());
+        }
+    }
+
+    fn params(&mut self, params: &[Spanned<Param>]) {
+        for (i, param) in params.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            match &param.inner {
+                Param::SelfParam => self.out.push_str("self"),
+                Param::Named { name, mutable, ty } => {
+                    if *mutable {
+                        self.out.push_str("mut ");
+                    }
+                    self.symbol(name.inner);
+                    if let Some(ty) = ty {
+                        self.out.push_str(": ");
+                        self.out.push_str(&ty.inner.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Prints the body following a `:`, as an indented block or a single
+    /// statement on the same line.
+    fn body(&mut self, body: &Expr) {
+        let ExprKind::Block { exprs, tail } = &body.inner else {
+            self.out.push(' ');
+            self.stmt(body);
+            return;
+        };
+        if exprs.is_empty() && tail.is_none() {
+            self.out.push_str(" ()");
+            return;
+        }
+        self.indent += 1;
+        let mut stmts: Option<Span> = None;
+        for (i, expr) in exprs.iter().chain(tail.as_deref()).enumerate() {
+            self.line(expr.span.start, i == 0);
+            self.stmt(expr);
+            stmts = Some(stmts.map_or(expr.span, |stmts| stmts.merge(expr.span)));
+        }
+        if let Some(stmts) = stmts {
+            self.comments_ending_block(stmts);
+        }
+        self.indent -= 1;
+    }
+
+    /// Prints a statement, in parentheses if it is a comparison that would
+    /// read as an assignment, like `a = b`.
+    fn stmt(&mut self, stmt: &Expr) {
+        self.operand(stmt, reads_as_assignment(stmt));
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.inner {
+            ExprKind::Int(v) => {
+                let text = self
+                    .written(expr.span, &TokenKind::IntLiteral(*v))
+                    .unwrap_or_else(|| v.to_string());
+                self.out.push_str(&text);
+            }
+            ExprKind::Float(v) => {
+                // `{:?}` keeps the `.0` of whole numbers, so the literal stays a float
+                let text = self
+                    .written(expr.span, &TokenKind::FloatLiteral(*v))
+                    .unwrap_or_else(|| format!("{:?}", v));
+                self.out.push_str(&text);
+            }
+            ExprKind::Bool(v) => self.out.push_str(&v.to_string()),
+            ExprKind::Unit => self.out.push_str("()"),
+            ExprKind::Str(parts) => {
+                self.out.push('"');
+                for part in parts {
+                    match part {
+                        StrPart::Lit(text) => self.escaped(text, true),
+                        StrPart::Expr(expr) => {
+                            self.out.push('{');
+                            self.expr(expr);
+                            self.out.push('}');
+                        }
+                    }
+                }
+                self.out.push('"');
+            }
+            ExprKind::Unary { op, expr } => {
+                self.out.push_str(&op.inner.to_string());
+                let parens = !is_postfix(expr) && !matches!(expr.inner, ExprKind::Unary { .. });
+                self.operand(expr, parens);
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let (left_bp, right_bp) = op.inner.binding_power();
+                // the lhs binds as tightly as the operator, the rhs tighter
+                let lhs_parens = binding_power(lhs).is_some_and(|bp| bp < left_bp);
+                let rhs_parens = binding_power(rhs).is_some_and(|bp| bp < right_bp);
+                self.operand(lhs, lhs_parens);
+                self.out.push(' ');
+                self.out.push_str(&op.inner.to_string());
+                self.out.push(' ');
+                self.operand(rhs, rhs_parens);
+            }
+            ExprKind::Cast { expr, ty } => {
+                let parens = binding_power(expr).is_some_and(|bp| bp < BinOp::CAST_BINDING_POWER);
+                self.operand(expr, parens);
+                self.out.push_str(" as ");
+                self.out.push_str(&ty.inner.to_string());
+            }
+            ExprKind::Let {
+                name,
+                mutable,
+                ty,
+                value,
+            } => {
+                self.out.push_str("let ");
+                if *mutable {
+                    self.out.push_str("mut ");
+                }
+                self.symbol(name.inner);
+                if let Some(ty) = ty {
+                    self.out.push_str(": ");
+                    self.out.push_str(&ty.inner.to_string());
+                }
+                if let Some(value) = value {
+                    self.out.push_str(" = ");
+                    self.expr(value);
+                }
+            }
+            ExprKind::Assign { name, value } => {
+                self.symbol(name.inner);
+                self.out.push_str(" = ");
+                self.expr(value);
+            }
+            ExprKind::Block { .. } => {
+                // blocks only appear as bodies, so one on its own is printed
+                // as the body of an `if` that always runs it
+                self.out.push_str("if true:");
+                self.body(expr);
+            }
+            ExprKind::Call { callee, args } => {
+                self.operand(callee, !is_postfix(callee));
+                self.args(args);
+            }
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => {
+                self.operand(receiver, !is_postfix(receiver));
+                self.out.push('.');
+                self.symbol(method.inner);
+                self.args(args);
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.out.push_str("match ");
+                self.expr(scrutinee);
+                self.out.push(':');
+                self.indent += 1;
+                for (i, arm) in arms.iter().enumerate() {
+                    self.line(arm.pattern.span.start, i == 0);
+                    self.pattern(&arm.pattern);
+                    self.out.push_str(" ->");
+                    self.body(&arm.body);
+                }
+                self.indent -= 1;
+            }
+            ExprKind::Return(value) => {
+                self.out.push_str("return");
+                if let Some(value) = value {
+                    self.out.push(' ');
+                    self.expr(value);
+                }
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                self.out.push_str("if ");
+                self.expr(cond);
+                self.out.push(':');
+                self.body(then_branch);
+                if let Some(else_branch) = else_branch {
+                    if matches!(then_branch.inner, ExprKind::Block { .. }) {
+                        self.newline();
+                    } else {
+                        self.out.push(' ');
+                    }
+                    self.out.push_str("else");
+                    if matches!(else_branch.inner, ExprKind::If { .. }) {
+                        self.out.push(' ');
+                        self.expr(else_branch);
+                    } else {
+                        self.out.push(':');
+                        self.body(else_branch);
+                    }
+                }
+            }
+            ExprKind::While { cond, body } => {
+                self.out.push_str("while ");
+                self.expr(cond);
+                self.out.push(':');
+                self.body(body);
+            }
+            ExprKind::Var(name) => self.symbol(*name),
+            ExprKind::Path(path) => self.out.push_str(&join_path(path)),
+            // not ginto, but programs with syntax errors aren't formatted
+            ExprKind::Error => self.out.push_str("<error>"),
+            ExprKind::Closure { params, body } => {
+                self.out.push('|');
+                self.params(params);
+                self.out.push('|');
+                self.body(body);
+            }
+        }
+    }
+
+    /// Prints an operand of an operator, in parentheses if `parens`.
+    fn operand(&mut self, expr: &Expr, parens: bool) {
+        if parens {
+            self.out.push('(');
+            self.expr(expr);
+            self.out.push(')');
+        } else {
+            self.expr(expr);
+        }
+    }
+
+    fn args(&mut self, args: &[Expr]) {
+        self.out.push('(');
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            self.expr(arg);
+        }
+        self.out.push(')');
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match &pattern.inner {
+            PatternKind::Wildcard => self.out.push('_'),
+            PatternKind::Bind(name) => self.symbol(*name),
+            PatternKind::Int(v) => {
+                let span = pattern.span;
+                let written = self.source.get(span.start.0..span.end.0).and_then(|text| {
+                    // a negative literal is `-` before the literal
+                    let (sign, literal) = match text.strip_prefix('-') {
+                        Some(literal) => ("-", literal.trim_start()),
+                        None => ("", text),
+                    };
+                    let token = TokenKind::IntLiteral(u64::try_from(v.unsigned_abs()).ok()?);
+                    is_literal(literal, &token).then(|| format!("{}{}", sign, literal))
+                });
+                self.out.push_str(&written.unwrap_or_else(|| v.to_string()));
+            }
+            PatternKind::Bool(v) => self.out.push_str(&v.to_string()),
+            PatternKind::Unit => self.out.push_str("()"),
+            PatternKind::Or(alternatives) => {
+                for (i, alternative) in alternatives.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(" | ");
+                    }
+                    self.pattern(alternative);
+                }
+            }
+        }
+    }
+
+    /// Prints `text` as a string literal, doubling braces if it is one that
+    /// interpolates.
+    fn str_lit(&mut self, text: &str, braces: bool) {
+        self.out.push('"');
+        self.escaped(text, braces);
+        self.out.push('"');
+    }
+
+    fn escaped(&mut self, text: &str, braces: bool) {
+        for ch in text.chars() {
+            match ch {
+                '\n' => self.out.push_str("\\n"),
+                '\t' => self.out.push_str("\\t"),
+                '\\' => self.out.push_str("\\\\"),
+                '"' => self.out.push_str("\\\""),
+                '{' | '}' if braces => {
+                    self.out.push(ch);
+                    self.out.push(ch);
+                }
+                ch => self.out.push(ch),
+            }
+        }
+    }
+}
+
+/// How tightly `expr` binds as an operand: the left binding power of its
+/// operator, or `None` for expressions that bind tighter than any binary
+/// operator or cast.
+fn binding_power(expr: &Expr) -> Option<u8> {
+    match &expr.inner {
+        ExprKind::Binary { op, .. } => Some(op.inner.binding_power().0),
+        ExprKind::Cast { .. } => Some(BinOp::CAST_BINDING_POWER),
+        ExprKind::Unary { .. } => None,
+        _ if is_postfix(expr) => None,
+        // anything else would take the rest of the line with it
+        _ => Some(0),
+    }
+}
+
+/// Whether `expr` printed as a statement starts with a variable followed by
+/// `=`, which the parser takes for an assignment.
+fn reads_as_assignment(expr: &Expr) -> bool {
+    let ExprKind::Binary { op, lhs, .. } = &expr.inner else {
+        return false;
+    };
+    if op.inner == BinOp::Equal && matches!(lhs.inner, ExprKind::Var(_)) {
+        return true;
+    }
+    // the lhs only comes first if it isn't in parentheses
+    let left_bp = op.inner.binding_power().0;
+    binding_power(lhs).is_some_and(|bp| bp >= left_bp) && reads_as_assignment(lhs)
+}
+
+/// Whether `expr` can be the operand of a postfix or prefix operator
+/// without parentheses.
+fn is_postfix(expr: &Expr) -> bool {
+    matches!(
+        expr.inner,
+        ExprKind::Int(_)
+            | ExprKind::Bool(_)
+            | ExprKind::Unit
+            | ExprKind::Str(_)
+            | ExprKind::Var(_)
+            | ExprKind::Path(_)
+            | ExprKind::Error
+            | ExprKind::Call { .. }
+            | ExprKind::MethodCall { .. }
+    )
+}
@@ -1,23 +1,41 @@
 use core::fmt;
 
-use ginto_diag::Spanned;
+use ginto_diag::{Spanned, Symbol};
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum TokenKind {
     // Literals
     IntLiteral(u64),
+    FloatLiteral(f64),
     BoolLiteral(bool),
+    StrLiteral(String),
 
     // Identifiers
-    Ident(String),
+    Ident(Symbol),
 
     // Keywords
     Let,
+    Mut,
     Mod,
+    Use,
     Not,
     Fn,
+    Pub,
+    Match,
+    Return,
+    If,
+    Else,
+    As,
+    StaticAssert,
+    Trait,
+    Impl,
+    For,
+    While,
     U64,
     I64,
+    F64,
+    Bool,
+    Str,
 
     // Operators
     Plus,  // +
@@ -32,16 +50,19 @@ pub enum TokenKind {
     Greater,      // >
     GreaterEqual, // >=
 
-    And, // &&
-    Or,  // ||
+    And,  // &&
+    Or,   // ||
+    Pipe, // |
 
     // Delimiters
     LParen, // (
     RParen, // )
 
-    Comma, // ,
-    Dot,   // .
-    Colon, // :
+    Comma,      // ,
+    Dot,        // .
+    Colon,      // :
+    ColonColon, // ::
+    Underscore, // _
 
     // end of file
     Eof,
@@ -53,41 +74,83 @@ pub enum TokenKind {
     Newline,
 }
 
+impl TokenKind {
+    /// How the keyword is spelled, if this is one.
+    pub fn keyword(&self) -> Option<&'static str> {
+        Some(match self {
+            TokenKind::Let => "let",
+            TokenKind::Mut => "mut",
+            TokenKind::Mod => "mod",
+            TokenKind::Use => "use",
+            TokenKind::Not => "not",
+            TokenKind::Fn => "fn",
+            TokenKind::Pub => "pub",
+            TokenKind::Match => "match",
+            TokenKind::Return => "return",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::As => "as",
+            TokenKind::StaticAssert => "static_assert",
+            TokenKind::Trait => "trait",
+            TokenKind::Impl => "impl",
+            TokenKind::For => "for",
+            TokenKind::While => "while",
+            TokenKind::U64 => "u64",
+            TokenKind::I64 => "i64",
+            TokenKind::F64 => "f64",
+            TokenKind::Bool => "bool",
+            TokenKind::Str => "str",
+            _ => return None,
+        })
+    }
+}
+
+/// Describes the token as diagnostics mention it: keywords and
+/// punctuation as written, in backticks, and the rest by what they are,
+/// like `newline` or identifier `foo`.
 impl fmt::Display for TokenKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            TokenKind::IntLiteral(_) => write!(f, "integer literal"),
-            TokenKind::BoolLiteral(_) => write!(f, "boolean literal"),
-            TokenKind::Ident(_) => write!(f, "identifier"),
-            TokenKind::Let => write!(f, "`let`"),
-            TokenKind::Mod => write!(f, "`mod`"),
-            TokenKind::Not => write!(f, "`not`"),
-            TokenKind::Plus => write!(f, "`+`"),
-            TokenKind::Minus => write!(f, "`-`"),
-            TokenKind::Star => write!(f, "`*`"),
-            TokenKind::Slash => write!(f, "`/`"),
-            TokenKind::Equal => write!(f, "`=`"),
-            TokenKind::NotEqual => write!(f, "`<>`"),
-            TokenKind::Less => write!(f, "`<`"),
-            TokenKind::LessEqual => write!(f, "`<=`"),
-            TokenKind::Greater => write!(f, "`>`"),
-            TokenKind::GreaterEqual => write!(f, "`>=`"),
-            TokenKind::And => write!(f, "`&&`"),
-            TokenKind::Or => write!(f, "`||`"),
-            TokenKind::LParen => write!(f, "`(`"),
-            TokenKind::RParen => write!(f, "`)`"),
-            TokenKind::Comma => write!(f, "`,`"),
-            TokenKind::Dot => write!(f, "`.`"),
-            TokenKind::Colon => write!(f, "`:`"),
-            TokenKind::Newline => write!(f, "newline"),
-            TokenKind::Eof => write!(f, "end of file"),
-            TokenKind::Fn => write!(f, "`fn`"),
-            TokenKind::Indent => write!(f, "`indent`"),
-            TokenKind::Dedent => write!(f, "dedent"),
-            TokenKind::Arrow => write!(f, "`->`"),
-            TokenKind::U64 => write!(f, "u64"),
-            TokenKind::I64 => write!(f, "i64"),
+        if let Some(keyword) = self.keyword() {
+            return write!(f, "`{}`", keyword);
         }
+        let punct = match self {
+            TokenKind::IntLiteral(_) => return write!(f, "integer literal"),
+            TokenKind::FloatLiteral(_) => return write!(f, "float literal"),
+            TokenKind::BoolLiteral(v) => return write!(f, "`{}`", v),
+            TokenKind::StrLiteral(_) => return write!(f, "string literal"),
+            // parsers expecting any identifier name an empty one
+            TokenKind::Ident(name) if name.with(str::is_empty) => {
+                return write!(f, "identifier");
+            }
+            TokenKind::Ident(name) => return write!(f, "identifier `{}`", name),
+            TokenKind::Indent => return write!(f, "indent"),
+            TokenKind::Dedent => return write!(f, "dedent"),
+            TokenKind::Newline => return write!(f, "newline"),
+            TokenKind::Eof => return write!(f, "end of file"),
+            TokenKind::Plus => "+",
+            TokenKind::Minus => "-",
+            TokenKind::Star => "*",
+            TokenKind::Slash => "/",
+            TokenKind::Equal => "=",
+            TokenKind::NotEqual => "<>",
+            TokenKind::Less => "<",
+            TokenKind::LessEqual => "<=",
+            TokenKind::Greater => ">",
+            TokenKind::GreaterEqual => ">=",
+            TokenKind::And => "&&",
+            TokenKind::Or => "||",
+            TokenKind::Pipe => "|",
+            TokenKind::LParen => "(",
+            TokenKind::RParen => ")",
+            TokenKind::Comma => ",",
+            TokenKind::Dot => ".",
+            TokenKind::Colon => ":",
+            TokenKind::ColonColon => "::",
+            TokenKind::Underscore => "_",
+            TokenKind::Arrow => "->",
+            _ => unreachable!("keywords are written above"),
+        };
+        write!(f, "`{}`", punct)
     }
 }
 
@@ -0,0 +1,141 @@
+use ginto_diag::{Spanned, Symbol};
+
+use crate::{Expr, ExprKind, Func, Item, ItemKind, Pattern, PatternKind, Program, StrPart};
+
+pub trait Visitor: Sized {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_item(&mut self, item: &Item) {
+        walk_item(self, item);
+    }
+
+    fn visit_func(&mut self, func: &Func) {
+        walk_func(self, func);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+}
+
+pub fn walk_program<V: Visitor>(visitor: &mut V, program: &Program) {
+    for item in &program.items {
+        visitor.visit_item(item);
+    }
+}
+
+pub fn walk_item<V: Visitor>(visitor: &mut V, item: &Item) {
+    match &item.inner {
+        ItemKind::Func(func) => visitor.visit_func(func),
+        ItemKind::Impl(decl) => {
+            for method in &decl.methods {
+                visitor.visit_func(method);
+            }
+        }
+        ItemKind::StaticAssert(assert) => visitor.visit_expr(&assert.cond),
+        ItemKind::Mod(_) | ItemKind::Use(_) | ItemKind::Trait(_) => {}
+    }
+}
+
+pub fn walk_func<V: Visitor>(visitor: &mut V, func: &Func) {
+    visitor.visit_expr(&func.body);
+}
+
+pub fn walk_expr<V: Visitor>(visitor: &mut V, expr: &Expr) {
+    match &expr.inner {
+        ExprKind::Int(_)
+        | ExprKind::Float(_)
+        | ExprKind::Bool(_)
+        | ExprKind::Unit
+        | ExprKind::Var(_)
+        | ExprKind::Path(_)
+        | ExprKind::Error => {}
+        ExprKind::Str(parts) => {
+            for part in parts {
+                if let StrPart::Expr(expr) = part {
+                    visitor.visit_expr(expr);
+                }
+            }
+        }
+        ExprKind::Unary { expr, .. } | ExprKind::Cast { expr, .. } => visitor.visit_expr(expr),
+        ExprKind::Binary { lhs, rhs, .. } => {
+            visitor.visit_expr(lhs);
+            visitor.visit_expr(rhs);
+        }
+        ExprKind::Let { value, .. } => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+        }
+        ExprKind::Assign { value, .. } => visitor.visit_expr(value),
+        ExprKind::Block { exprs, tail } => {
+            for expr in exprs {
+                visitor.visit_expr(expr);
+            }
+            if let Some(tail) = tail {
+                visitor.visit_expr(tail);
+            }
+        }
+        ExprKind::Closure { body, .. } => visitor.visit_expr(body),
+        ExprKind::Call { callee, args } => {
+            visitor.visit_expr(callee);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        ExprKind::MethodCall { receiver, args, .. } => {
+            visitor.visit_expr(receiver);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        ExprKind::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => {
+            visitor.visit_expr(cond);
+            visitor.visit_expr(then_branch);
+            if let Some(else_branch) = else_branch {
+                visitor.visit_expr(else_branch);
+            }
+        }
+        ExprKind::While { cond, body } => {
+            visitor.visit_expr(cond);
+            visitor.visit_expr(body);
+        }
+        ExprKind::Return(value) => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr(scrutinee);
+            for arm in arms {
+                visitor.visit_expr(&arm.body);
+            }
+        }
+    }
+}
+
+/// The alternatives of an or-pattern, or `pattern` itself.
+pub fn pattern_alternatives(pattern: &Pattern) -> &[Pattern] {
+    match &pattern.inner {
+        PatternKind::Or(alternatives) => alternatives,
+        _ => std::slice::from_ref(pattern),
+    }
+}
+
+/// Every name bound in `pattern`, in source order. A name bound by several
+/// alternatives of an or-pattern appears once per alternative.
+pub fn pattern_bindings(pattern: &Pattern) -> Vec<Spanned<Symbol>> {
+    match &pattern.inner {
+        PatternKind::Bind(name) => vec![Spanned::new(*name, pattern.span)],
+        PatternKind::Or(alternatives) => alternatives.iter().flat_map(pattern_bindings).collect(),
+        PatternKind::Wildcard | PatternKind::Int(_) | PatternKind::Bool(_) | PatternKind::Unit => {
+            Vec::new()
+        }
+    }
+}
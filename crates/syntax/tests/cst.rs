@@ -0,0 +1,60 @@
+//! Checks that the concrete syntax tree of the programs of the other
+//! crates' tests holds their source and parses as the source does.
+
+use std::{fs, path::Path};
+
+use ginto_diag::{BytePos, FileId};
+use ginto_syntax::{Cst, CstKind, Lexer, Parser, TokenKind, ast_to_json};
+
+fn check(source: &str) {
+    let cst = Cst::new(FileId(0), source).unwrap_or_else(|errors| panic!("{errors:?}"));
+    assert_eq!(cst.to_string(), source);
+    let tokens = Lexer::new(FileId(0), source).lex_all().unwrap();
+    let mut parser = Parser::new(FileId(0), tokens);
+    let (program, errors) = cst.to_ast();
+    assert_eq!(ast_to_json(&program), ast_to_json(&parser.parse_program()));
+    assert_eq!(errors, parser.errors());
+}
+
+#[test]
+fn holds_test_programs() {
+    let dirs = [
+        "../vm/tests/corpus",
+        "../wasm/tests/programs",
+        "../c/tests/programs",
+    ];
+    for dir in dirs {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(dir);
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "ginto") {
+                check(&fs::read_to_string(&path).unwrap());
+            }
+        }
+    }
+}
+
+#[test]
+fn keeps_spaces_and_parentheses() {
+    let source = "fn f(a: u64) -> u64:  \n    ( a  +  1 )   \n\n\n    a  ";
+    check(source);
+    let cst = Cst::new(FileId(0), source).unwrap();
+    let paren = cst.token_at(BytePos(27)).unwrap();
+    assert_eq!(paren.kind, CstKind::Token(TokenKind::LParen));
+    let space = cst.token_at(BytePos(28)).unwrap();
+    assert_eq!((&space.kind, cst.text(space)), (&CstKind::Whitespace, " "));
+}
+
+#[test]
+fn keeps_comments() {
+    let source = "# a note\nfn f(a: u64) -> u64:  # trailing  \n    a\n";
+    check(source);
+    let cst = Cst::new(FileId(0), source).unwrap();
+    let comment = cst.token_at(BytePos(31)).unwrap();
+    assert_eq!(
+        (&comment.kind, cst.text(comment)),
+        (&CstKind::Comment, "# trailing")
+    );
+    let space = cst.token_at(BytePos(41)).unwrap();
+    assert_eq!((&space.kind, cst.text(space)), (&CstKind::Whitespace, "  "));
+}
@@ -0,0 +1,90 @@
+//! Pretty prints the programs of the other crates' tests and checks that
+//! what comes out parses to a program that prints the same again.
+
+use std::{fs, path::Path};
+
+use ginto_diag::FileId;
+use ginto_syntax::{
+    Cst, ExprKind, ItemKind, Lexer, Parser, Program, pretty_print, pretty_print_with_source,
+};
+
+fn parse(source: &str) -> Program {
+    let tokens = Lexer::new(FileId(0), source)
+        .lex_all()
+        .unwrap_or_else(|errors| panic!("{errors:?} in:\n{source}"));
+    let mut parser = Parser::new(FileId(0), tokens);
+    let program = parser.parse_program();
+    assert!(
+        parser.errors().is_empty(),
+        "{:?} in:\n{source}",
+        parser.errors()
+    );
+    program
+}
+
+fn round_trip(source: &str) -> String {
+    let printed = pretty_print(&parse(source));
+    assert_eq!(pretty_print(&parse(&printed)), printed);
+    printed
+}
+
+#[test]
+fn round_trips_test_programs() {
+    let dirs = [
+        "../vm/tests/corpus",
+        "../wasm/tests/programs",
+        "../c/tests/programs",
+    ];
+    for dir in dirs {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(dir);
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "ginto") {
+                round_trip(&fs::read_to_string(&path).unwrap());
+            }
+        }
+    }
+}
+
+#[test]
+fn keeps_parentheses_that_matter() {
+    let source = "fn f(a: u64, b: u64) -> i64:\n    \
+                  -((a + b) * (a - (b - 1)) as i64)\n";
+    assert_eq!(round_trip(source), source);
+}
+
+#[test]
+fn drops_parentheses_that_dont() {
+    let printed = round_trip("fn f(a: u64) -> u64:\n    ((a * 2) + (f(a)))\n");
+    assert_eq!(printed, "fn f(a: u64) -> u64:\n    a * 2 + f(a)\n");
+}
+
+#[test]
+fn escapes_strings() {
+    let source = "fn f(n: u64) -> str:\n    \"{{{n}}}\\t\\\"\\\\\"\n";
+    assert_eq!(round_trip(source), source);
+}
+
+#[test]
+fn ends_expressions_at_blocks() {
+    let source = "fn f(a: bool) -> u64:\n    if a:\n        1\n    (2)\n    - 3\n";
+    let Some(ItemKind::Func(func)) = parse(source).items.first().map(|item| item.inner.clone())
+    else {
+        panic!("expected a function");
+    };
+    let ExprKind::Block { exprs, tail } = func.body.inner else {
+        panic!("expected a block");
+    };
+    assert_eq!(exprs.len(), 2);
+    assert!(matches!(tail.unwrap().inner, ExprKind::Unary { .. }));
+}
+
+#[test]
+fn places_the_comments_of_the_cst() {
+    let source =
+        "# top\nfn f() -> str:  # returns\n    # the text\n    \"# not a comment\"  # trailing\n";
+    let cst = Cst::new(FileId(0), source).unwrap();
+    let (program, errors) = cst.to_ast();
+    assert!(errors.is_empty());
+    assert_eq!(pretty_print_with_source(&program, &cst), source);
+}
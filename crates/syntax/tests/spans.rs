@@ -0,0 +1,272 @@
+use ginto_diag::{DiagnosticConvertible, FileId, Span, Symbol};
+use ginto_syntax::{
+    ExprKind, ItemKind, Lexer, LexerError, Parser, ParserError, StrPart, TokenKind,
+};
+
+#[test]
+fn spans_are_byte_offsets() {
+    let source = "fn main():\n    \"ü {x}\" + y\n";
+    let tokens = Lexer::new(FileId(0), source).lex_all().unwrap();
+    let mut parser = Parser::new(FileId(0), tokens);
+    let program = parser.parse_program();
+    assert!(parser.errors().is_empty());
+    let ItemKind::Func(func) = &program.items[0].inner else {
+        panic!("expected a function");
+    };
+    let ExprKind::Block {
+        tail: Some(tail), ..
+    } = &func.body.inner
+    else {
+        panic!("expected a block with a tail");
+    };
+    let ExprKind::Binary { lhs, rhs, .. } = &tail.inner else {
+        panic!("expected a binary expression");
+    };
+    assert_eq!(&source[rhs.span.start.0..rhs.span.end.0], "y");
+    assert_eq!(rhs.span, Span::from_range(26..27));
+    let ExprKind::Str(parts) = &lhs.inner else {
+        panic!("expected a string");
+    };
+    let StrPart::Expr(var) = &parts[1] else {
+        panic!("expected an interpolation");
+    };
+    assert_eq!(&source[var.span.start.0..var.span.end.0], "x");
+}
+
+#[test]
+fn float_literals_are_single_tokens() {
+    let source = "1.5 2e9 2.5E-3 1.foo 1";
+    let tokens = Lexer::new(FileId(0), source).lex_all().unwrap();
+    let kinds: Vec<_> = tokens.iter().map(|token| token.inner.clone()).collect();
+    assert_eq!(
+        kinds[..7],
+        [
+            TokenKind::FloatLiteral(1.5),
+            TokenKind::FloatLiteral(2e9),
+            TokenKind::FloatLiteral(2.5e-3),
+            TokenKind::IntLiteral(1),
+            TokenKind::Dot,
+            TokenKind::Ident(Symbol::intern("foo")),
+            TokenKind::IntLiteral(1),
+        ]
+    );
+    assert_eq!(tokens[2].span, Span::from_range(8..14));
+    assert!(Lexer::new(FileId(0), "1e999").lex_all().is_err());
+}
+
+#[test]
+fn exponents_without_digits_are_errors() {
+    let source = "1.5e + 2E- + 3e";
+    let errors = Lexer::new(FileId(0), source).lex_all().unwrap_err();
+    let spans: Vec<_> = errors
+        .iter()
+        .map(|error| match error {
+            LexerError::MissingExponent { span, .. } => &source[span.start.0..span.end.0],
+            error => panic!("expected a missing exponent: {error:?}"),
+        })
+        .collect();
+    assert_eq!(spans, ["1.5e", "2E-", "3e"]);
+}
+
+#[test]
+fn unknown_radix_prefixes_are_errors() {
+    let source = "0XFF + 0d12 + 0e1";
+    let errors = Lexer::new(FileId(0), source).lex_all().unwrap_err();
+    let spans: Vec<_> = errors
+        .iter()
+        .map(|error| match error {
+            LexerError::UnknownRadixPrefix { span, prefix, .. } => (
+                &source[span.start.0..span.end.0],
+                &source[prefix.start.0..prefix.end.0],
+            ),
+            error => panic!("expected an unknown radix prefix: {error:?}"),
+        })
+        .collect();
+    assert_eq!(spans, [("0XFF", "0X"), ("0d12", "0d")]);
+    let suggestion = &errors[0].clone().into_diagnostic().suggestions[0];
+    assert_eq!(suggestion.edits[0].text, "0x");
+}
+
+#[test]
+fn non_ascii_radix_prefixes_are_errors() {
+    let source = "0é1";
+    let errors = Lexer::new(FileId(0), source).lex_all().unwrap_err();
+    let diagnostic = errors[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.labels[0].span, Span::from_range(0..source.len()));
+    assert!(diagnostic.suggestions.is_empty());
+}
+
+#[test]
+fn integer_literals_have_radixes_and_separators() {
+    let source = "0xFF_ff 0o17 0b1010 1_000_000 1_0.5 0x_1";
+    let tokens = Lexer::new(FileId(0), source).lex_all().unwrap();
+    let kinds: Vec<_> = tokens.iter().map(|token| token.inner.clone()).collect();
+    assert_eq!(
+        kinds[..6],
+        [
+            TokenKind::IntLiteral(0xffff),
+            TokenKind::IntLiteral(0o17),
+            TokenKind::IntLiteral(0b1010),
+            TokenKind::IntLiteral(1_000_000),
+            TokenKind::FloatLiteral(10.5),
+            TokenKind::IntLiteral(1),
+        ]
+    );
+    assert_eq!(tokens[0].span, Span::from_range(0..7));
+    assert!(
+        Lexer::new(FileId(0), "0x1_0000_0000_0000_0000")
+            .lex_all()
+            .is_err()
+    );
+}
+
+#[test]
+fn comments_run_to_the_end_of_their_line() {
+    let source = "# top\nfn f(): # note  \n      # deeper\n  # shallower\n    \"#1\" # one\n# end";
+    let mut lexer = Lexer::new(FileId(0), source);
+    let kinds: Vec<_> = lexer
+        .lex_all()
+        .unwrap()
+        .into_iter()
+        .map(|token| token.inner)
+        .collect();
+    let plain = "\nfn f():\n    \"#1\"\n";
+    let plain: Vec<_> = Lexer::new(FileId(0), plain)
+        .lex_all()
+        .unwrap()
+        .into_iter()
+        .map(|token| token.inner)
+        .collect();
+    // lines with only a comment are blank, whatever their indentation
+    assert_eq!(kinds, plain);
+    let comments: Vec<_> = lexer
+        .comments()
+        .iter()
+        .map(|span| &source[span.start.0..span.end.0])
+        .collect();
+    assert_eq!(
+        comments,
+        [
+            "# top",
+            "# note",
+            "# deeper",
+            "# shallower",
+            "# one",
+            "# end"
+        ]
+    );
+}
+
+#[test]
+fn missing_expressions_become_error_nodes() {
+    let source = "fn f() -> u64:\n    let x = 1 +\n    g(, 2)\n";
+    let tokens = Lexer::new(FileId(0), source).lex_all().unwrap();
+    let mut parser = Parser::new(FileId(0), tokens);
+    let program = parser.parse_program();
+    assert_eq!(parser.errors().len(), 2);
+    let ItemKind::Func(func) = &program.items[0].inner else {
+        panic!("expected a function");
+    };
+    let ExprKind::Block {
+        exprs,
+        tail: Some(tail),
+    } = &func.body.inner
+    else {
+        panic!("expected a block with a tail");
+    };
+    let ExprKind::Let {
+        value: Some(value), ..
+    } = &exprs[0].inner
+    else {
+        panic!("expected a let");
+    };
+    let ExprKind::Binary { rhs, .. } = &value.inner else {
+        panic!("expected a binary expression");
+    };
+    assert!(matches!(rhs.inner, ExprKind::Error));
+    let ExprKind::Call { args, .. } = &tail.inner else {
+        panic!("expected a call");
+    };
+    assert!(matches!(args[0].inner, ExprKind::Error));
+    assert_eq!(&source[args[0].span.start.0..args[0].span.end.0], ",");
+    assert!(matches!(args[1].inner, ExprKind::Int(2)));
+}
+
+#[test]
+fn recovers_from_blocks_missing_their_body() {
+    let source = "fn f():\n    if n = 0:\nfn g():\n    1\n";
+    let tokens = Lexer::new(FileId(0), source).lex_all().unwrap();
+    let mut parser = Parser::new(FileId(0), tokens);
+    let program = parser.parse_program();
+    assert!(!parser.errors().is_empty());
+    assert!(!program.items.is_empty());
+}
+
+#[test]
+fn recovers_from_top_level_lets_at_the_next_item() {
+    let source = "let limit = 10\nmod m\nuse m\nstatic_assert(true)\nfn f():\n    1\n";
+    let tokens = Lexer::new(FileId(0), source).lex_all().unwrap();
+    let mut parser = Parser::new(FileId(0), tokens);
+    let program = parser.parse_program();
+    assert_eq!(parser.errors().len(), 1);
+    let kinds: Vec<_> = program.items.iter().map(|item| &item.inner).collect();
+    assert!(matches!(
+        kinds.as_slice(),
+        [
+            ItemKind::Mod(_),
+            ItemKind::Use(_),
+            ItemKind::StaticAssert(_),
+            ItemKind::Func(_)
+        ]
+    ));
+}
+
+#[test]
+fn parses_token_streams_without_eof() {
+    let program = Parser::new(FileId(0), Vec::new()).parse_program();
+    assert!(program.items.is_empty());
+
+    let mut tokens = Lexer::new(FileId(0), "fn f() -> u64:\n    (1")
+        .lex_all()
+        .unwrap();
+    tokens.truncate(tokens.len() - 3);
+    let mut parser = Parser::new(FileId(0), tokens);
+    parser.parse_program();
+    assert!(!parser.errors().is_empty());
+}
+
+#[test]
+fn unclosed_parens_point_at_the_open_paren() {
+    let source = "fn f() -> u64:\n    (1 + 2\n";
+    let tokens = Lexer::new(FileId(0), source).lex_all().unwrap();
+    let mut parser = Parser::new(FileId(0), tokens);
+    parser.parse_program();
+    let [ParserError::UnclosedParen { open, found, .. }] = parser.errors() else {
+        panic!("expected an unclosed paren, found {:?}", parser.errors());
+    };
+    assert_eq!(*open, Span::from_range(19..20));
+    assert_eq!(*found, TokenKind::Newline);
+    let diagnostic = parser.errors()[0].clone().into_diagnostic();
+    assert_eq!(diagnostic.message, "unexpected newline");
+    assert_eq!(
+        diagnostic.labels[0].message.as_deref(),
+        Some("expected `)`, found newline")
+    );
+}
+
+#[test]
+fn parse_errors_name_tokens_as_written() {
+    let kinds = [
+        (TokenKind::Slash, "`/`"),
+        (TokenKind::Arrow, "`->`"),
+        (TokenKind::Fn, "`fn`"),
+        (TokenKind::BoolLiteral(true), "`true`"),
+        (TokenKind::Ident(Symbol::intern("foo")), "identifier `foo`"),
+        (TokenKind::Ident(Symbol::intern("")), "identifier"),
+        (TokenKind::IntLiteral(1), "integer literal"),
+        (TokenKind::Newline, "newline"),
+    ];
+    for (kind, text) in kinds {
+        assert_eq!(kind.to_string(), text);
+    }
+}
@@ -0,0 +1,139 @@
+//! What the tests of every crate share: looking up the text of spans and
+//! labels, reading modules from memory, checking programs through the
+//! driver, comparing what backends compile programs to with golden files,
+//! and running the interpreter on a stack it fits in.
+
+use std::{collections::HashMap, fs, io, path::Path};
+
+use ginto_diag::{Diagnostic, FileId, Interner, SourceManager, Span};
+use ginto_driver::{Compilation, Driver};
+use ginto_eval::INTERPRETER_STACK_SIZE;
+
+/// The text of `source` at `span`.
+pub fn text(source: &str, span: Span) -> &str {
+    &source[span.start.0..span.end.0]
+}
+
+/// The labels of `diagnostic`, primary first, as the text of `source` at
+/// them and their message.
+pub fn labels<'a>(source: &'a str, diagnostic: &Diagnostic) -> Vec<(&'a str, String)> {
+    diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            (
+                text(source, label.span),
+                label.message.clone().unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+/// The text of the file `file` of `sm` at `span`.
+pub fn text_in(sm: &SourceManager, file: FileId, span: Span) -> &str {
+    text(&sm.get_file(file).unwrap().source, span)
+}
+
+/// The labels of `diagnostic`, primary first, as the text of the file of
+/// `sm` each is in at them and their message.
+pub fn labels_in<'a>(sm: &'a SourceManager, diagnostic: &Diagnostic) -> Vec<(&'a str, String)> {
+    diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            (
+                text_in(sm, label.file_id, label.span),
+                label.message.clone().unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+/// A reader of the modules of `files`, named after their path, that finds
+/// no other file.
+pub fn reader<'a>(
+    files: &[(&'a str, &'a str)],
+) -> impl FnMut(&SourceManager, &Path) -> io::Result<String> + 'a {
+    let files: HashMap<_, _> = files.iter().copied().collect();
+    move |_, path| {
+        let path = path.to_str().unwrap();
+        files
+            .get(path)
+            .map(|source| source.to_string())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.to_string()))
+    }
+}
+
+/// The program whose root file `main.ginto` holds `source`, checked as
+/// `ginto` does, and the files it was loaded from. Panics with the
+/// diagnostics of a program with errors.
+pub fn check(source: &str) -> (Compilation, SourceManager) {
+    check_files(source, &[])
+}
+
+/// The program whose root file `main.ginto` holds `root`, with the modules
+/// of `files`, named after their path, checked as `check` does.
+pub fn check_files(root: &str, files: &[(&str, &str)]) -> (Compilation, SourceManager) {
+    check_with("main.ginto", root, files, |driver| driver)
+}
+
+/// The program whose root file `name` holds `root`, with the modules of
+/// `files`, checked as `check_files` does by the driver `configure` makes
+/// of the default one.
+pub fn check_with(
+    name: &str,
+    root: &str,
+    files: &[(&str, &str)],
+    configure: impl for<'d> FnOnce(Driver<'d>) -> Driver<'d>,
+) -> (Compilation, SourceManager) {
+    let mut read = reader(files);
+    let mut sm = SourceManager::new();
+    let root = sm.add_file(name.to_string(), root.to_string());
+    let (compilation, diagnostics) =
+        configure(Driver::new().with_reader(&mut read)).check(&mut sm, root);
+    let compilation = compilation.unwrap_or_else(|| panic!("{name}: {diagnostics:#?}"));
+    (compilation, sm)
+}
+
+/// Compiles every program of `tests/programs` of the crate at `manifest_dir`
+/// with `compile` and compares the output to the file next to it with the
+/// extension `extension`. Run with `BLESS=1` to rewrite those files.
+pub fn golden(manifest_dir: &str, extension: &str, compile: impl Fn(&Compilation) -> String) {
+    let programs = Path::new(manifest_dir).join("tests/programs");
+    let mut paths: Vec<_> = fs::read_dir(programs)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ginto"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let source = fs::read_to_string(&path).unwrap();
+        let (program, _) = check_with(&path.display().to_string(), &source, &[], |driver| driver);
+        let actual = compile(&program);
+        let golden = path.with_extension(extension);
+        if std::env::var_os("BLESS").is_some() {
+            fs::write(&golden, &actual).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&golden).unwrap_or_default();
+        assert_eq!(expected, actual, "{}", golden.display());
+    }
+}
+
+/// Runs `run` on a thread with the stack the interpreter needs, in the
+/// session of this one.
+pub fn on_interpreter_stack<R: Send>(run: impl FnOnce() -> R + Send) -> R {
+    let interner = Interner::current();
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(INTERPRETER_STACK_SIZE)
+            .spawn_scoped(scope, || {
+                let _session = interner.enter();
+                run()
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+    })
+}
@@ -0,0 +1,1909 @@
+use std::collections::HashMap;
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span, Spanned, Symbol, sym};
+use ginto_resolve::{DefId, DefKind, ModuleTree, Resolutions};
+use ginto_syntax::{
+    BinOp, Expr, ExprKind, Func, ImplDecl, ItemKind, Param, Program, StrPart, TraitDecl, Type,
+    UnaryOp, join_path, visit,
+};
+
+use crate::{FnSig, Ty, TyParam, TypeckResults};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeError {
+    ArgCountMismatch {
+        expected: usize,
+        found: usize,
+        span: Span,
+        /// The name of the called function at its definition.
+        callee: Span,
+        file_id: FileId,
+    },
+    ArgTypeMismatch {
+        expected: Ty,
+        found: Ty,
+        span: Span,
+        param: Span,
+        file_id: FileId,
+    },
+    NotCallable {
+        ty: Ty,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A binary operation between numbers of different types, like a `u64`
+    /// and an `i64`. Numbers are never converted implicitly.
+    MismatchedNumbers {
+        op: BinOp,
+        lhs: Spanned<Ty>,
+        rhs: Spanned<Ty>,
+        file_id: FileId,
+    },
+    /// A string operand of an operator strings do not support, or mixed
+    /// with another type, like `"a" + 1`.
+    InvalidStrOperands {
+        op: BinOp,
+        lhs: Spanned<Ty>,
+        rhs: Spanned<Ty>,
+        file_id: FileId,
+    },
+    /// Operands of an operator that doesn't apply to their types, like
+    /// `1 + true`.
+    InvalidOperands {
+        op: Spanned<BinOp>,
+        lhs: Spanned<Ty>,
+        rhs: Spanned<Ty>,
+        file_id: FileId,
+    },
+    /// The operand of `-` or `not` when it isn't a number or a `bool`.
+    InvalidUnaryOperand {
+        op: Spanned<UnaryOp>,
+        operand: Spanned<Ty>,
+        file_id: FileId,
+    },
+    /// A `let` whose value doesn't match its type annotation.
+    LetTypeMismatch {
+        expected: Spanned<Ty>,
+        found: Ty,
+        span: Span,
+        file_id: FileId,
+    },
+    /// An assignment whose value doesn't match the type of the variable.
+    AssignTypeMismatch {
+        expected: Ty,
+        found: Ty,
+        span: Span,
+        /// The name of the variable at its definition.
+        def: Span,
+        def_file: FileId,
+        file_id: FileId,
+    },
+    InvalidCast {
+        from: Ty,
+        to: Ty,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A value returned from a function, explicitly or as the trailing
+    /// expression of a path through its body, that doesn't match the
+    /// declared return type.
+    ReturnMismatch {
+        expected: Spanned<Ty>,
+        found: Ty,
+        span: Span,
+        file_id: FileId,
+    },
+    /// An operator applied to a value whose type is a type parameter, which
+    /// has no bounds that would provide the operator.
+    OperatorOnTypeParam {
+        op: BinOp,
+        operand: Spanned<Ty>,
+        file_id: FileId,
+    },
+    GenericFnAsValue {
+        name: String,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A type parameter of a called function that neither the arguments
+    /// nor the context determine.
+    CannotInferTypeParam {
+        param: String,
+        span: Span,
+        /// The name of the called function at its definition.
+        callee: Span,
+        file_id: FileId,
+    },
+    MethodNotFound {
+        method: String,
+        ty: Ty,
+        /// Traits that declare a method of that name.
+        candidates: Vec<String>,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A method call that matches methods of several traits implemented for
+    /// the receiver's type.
+    AmbiguousMethod {
+        method: String,
+        ty: Ty,
+        traits: Vec<String>,
+        span: Span,
+        file_id: FileId,
+    },
+    MethodNotInTrait {
+        method: String,
+        trait_name: String,
+        span: Span,
+        file_id: FileId,
+    },
+    MissingTraitMethods {
+        trait_name: String,
+        /// The signatures in the trait of the methods the `impl` lacks.
+        missing: Vec<Spanned<String>>,
+        trait_file: FileId,
+        span: Span,
+        file_id: FileId,
+    },
+    MethodSigMismatch {
+        method: String,
+        trait_name: String,
+        expected: String,
+        found: String,
+        span: Span,
+        trait_span: Span,
+        trait_file: FileId,
+        file_id: FileId,
+    },
+    ConflictingImpls {
+        trait_name: String,
+        ty: Ty,
+        span: Span,
+        previous: Span,
+        previous_file: FileId,
+        file_id: FileId,
+    },
+    StaticAssertNotBool {
+        found: Ty,
+        span: Span,
+        file_id: FileId,
+    },
+    WhileConditionNotBool {
+        found: Ty,
+        span: Span,
+        file_id: FileId,
+    },
+    IfConditionNotBool {
+        found: Ty,
+        span: Span,
+        file_id: FileId,
+    },
+    /// A branch of an `if`, or an arm of a `match`, whose type doesn't
+    /// match that of the first branch that produces a value.
+    BranchTypeMismatch {
+        kind: BranchKind,
+        expected: Spanned<Ty>,
+        found: Spanned<Ty>,
+        file_id: FileId,
+    },
+    /// An `if` without `else` in return position, which produces no value
+    /// when its condition is false.
+    MissingElse {
+        expected: Spanned<Ty>,
+        span: Span,
+        then_branch: Span,
+        file_id: FileId,
+    },
+}
+
+/// What has the branches of a `BranchTypeMismatch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchKind {
+    If,
+    Match,
+}
+
+/// Whether `ty` is known well enough to report an operator on it.
+fn is_known(ty: &Ty) -> bool {
+    !ty.is_unknown() && *ty != Ty::Never
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        format!("{} {}", n, word)
+    } else {
+        format!("{} {}s", n, word)
+    }
+}
+
+impl DiagnosticConvertible for TypeError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            TypeError::ArgCountMismatch {
+                expected,
+                found,
+                span,
+                callee,
+                file_id,
+            } => {
+                let was = if found == 1 { "was" } else { "were" };
+                Diagnostic::error(format!(
+                        "this function takes {} but {} {} supplied",
+                        plural(expected, "argument"),
+                        plural(found, "argument"),
+                        was
+                    ))
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message(format!("expected {}", plural(expected, "argument"))),
+                    )
+                    .with_label(
+                        Label::secondary(file_id, callee).with_message("function defined here"),
+                    )
+            }
+
+            TypeError::ArgTypeMismatch {
+                expected,
+                found,
+                span,
+                param,
+                file_id,
+            } => Diagnostic::error("mismatched types")
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected `{}`, found `{}`", expected, found)),
+                )
+                .with_label(
+                    Label::secondary(file_id, param).with_message("parameter declared here"),
+                ),
+
+            TypeError::NotCallable { ty, span, file_id } => Diagnostic::error(format!("expected function, found `{}`", ty))
+                .with_label(
+                    Label::primary(file_id, span).with_message("call expression requires function"),
+                ),
+
+            TypeError::MismatchedNumbers {
+                op,
+                lhs,
+                rhs,
+                file_id,
+            } => Diagnostic::error(format!(
+                    "mismatched numeric types: cannot apply `{}` to `{}` and `{}`",
+                    op, lhs.inner, rhs.inner
+                ))
+                .with_label(
+                    Label::primary(file_id, rhs.span)
+                        .with_message(format!("expected `{}`, found `{}`", lhs.inner, rhs.inner)),
+                )
+                .with_label(
+                    Label::secondary(file_id, lhs.span)
+                        .with_message(format!("this is of type `{}`", lhs.inner)),
+                )
+                .with_note("numbers are never converted implicitly")
+                .with_help(format!(
+                    "convert the right operand with `as {}`, or the left one with `as {}`",
+                    lhs.inner, rhs.inner
+                )),
+
+            TypeError::InvalidStrOperands {
+                op,
+                lhs,
+                rhs,
+                file_id,
+            } => {
+                // point at the operand that is not a string, if any
+                let (other, string) = if lhs.inner == Ty::Str {
+                    (&rhs, &lhs)
+                } else {
+                    (&lhs, &rhs)
+                };
+                let diagnostic = Diagnostic::error(format!(
+                        "cannot apply `{}` to `{}` and `{}`",
+                        op, lhs.inner, rhs.inner
+                    ))
+                    .with_label(
+                        Label::primary(file_id, other.span)
+                            .with_message(format!("this is of type `{}`", other.inner)),
+                    )
+                    .with_label(
+                        Label::secondary(file_id, string.span)
+                            .with_message(format!("this is of type `{}`", string.inner)),
+                    );
+                match op {
+                    BinOp::Add if other.inner != Ty::Str => diagnostic
+                        .with_note("values are never converted to strings implicitly")
+                        .with_help("interpolate the value into a string instead, like `\"{x}\"`"),
+                    BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod => {
+                        diagnostic.with_note("strings can only be joined with `+`")
+                    }
+                    _ => diagnostic.with_note("strings can only be compared with strings"),
+                }
+            }
+
+            TypeError::InvalidCast {
+                from,
+                to,
+                span,
+                file_id,
+            } => {
+                let diagnostic = Diagnostic::error(format!("cannot cast `{}` as `{}`", from, to))
+                    .with_label(Label::primary(file_id, span).with_message("invalid cast"))
+                    .with_note(
+                        "`as` only converts between number types and from `bool` to integers",
+                    );
+                if to == Ty::Bool && from.is_integer() {
+                    diagnostic.with_help("compare with zero instead: `<> 0`")
+                } else {
+                    diagnostic
+                }
+            }
+
+            TypeError::OperatorOnTypeParam {
+                op,
+                operand,
+                file_id,
+            } => Diagnostic::error(format!(
+                    "binary operation `{}` cannot be applied to type `{}`",
+                    op, operand.inner
+                ))
+                .with_label(
+                    Label::primary(file_id, operand.span)
+                        .with_message(format!("this is of type `{}`", operand.inner)),
+                )
+                .with_note(format!(
+                    "`{}` can be any type, so values of it can only be passed around",
+                    operand.inner
+                )),
+
+            TypeError::GenericFnAsValue {
+                name,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("cannot use generic function `{}` as a value", name))
+                .with_label(
+                    Label::primary(file_id, span).with_message("generic function used as a value"),
+                )
+                .with_note("generic functions are instantiated at each call, so they can only be called directly"),
+
+            TypeError::CannotInferTypeParam {
+                param,
+                span,
+                callee,
+                file_id,
+            } => Diagnostic::error("type annotations needed")
+                .with_label(Label::primary(file_id, span).with_message(format!(
+                    "cannot infer type of the type parameter `{}`",
+                    param
+                )))
+                .with_label(
+                    Label::secondary(file_id, callee).with_message("function defined here"),
+                )
+                .with_help("use the call where its type is known, like `let x: u64 = f()`"),
+
+            TypeError::MethodNotFound {
+                method,
+                ty,
+                candidates,
+                span,
+                file_id,
+            } => {
+                let diagnostic = Diagnostic::error(format!(
+                        "no method named `{}` found for type `{}` in the current scope",
+                        method, ty
+                    ))
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message(format!("method not found in `{}`", ty)),
+                    );
+                if candidates.is_empty() {
+                    return diagnostic;
+                }
+                let traits = candidates
+                    .iter()
+                    .map(|name| format!("`{}`", name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let (these, define) = if candidates.len() == 1 {
+                    ("trait", "defines")
+                } else {
+                    ("traits", "define")
+                };
+                diagnostic.with_help(format!(
+                    "the following {} {} a method `{}`, but {} not implemented for `{}`: {}",
+                    these,
+                    define,
+                    method,
+                    if candidates.len() == 1 { "it is" } else { "they are" },
+                    ty,
+                    traits
+                ))
+            }
+
+            TypeError::AmbiguousMethod {
+                method,
+                ty,
+                traits,
+                span,
+                file_id,
+            } => {
+                let traits = traits
+                    .iter()
+                    .map(|name| format!("`{}`", name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Diagnostic::error(format!("multiple applicable methods named `{}` found", method))
+                    .with_label(Label::primary(file_id, span).with_message(format!(
+                        "`{}` implements traits {} which all define it",
+                        ty, traits
+                    )))
+                    .with_note("rename the method in all but one of the traits")
+            }
+
+            TypeError::MethodNotInTrait {
+                method,
+                trait_name,
+                span,
+                file_id,
+            } => Diagnostic::error(format!(
+                    "method `{}` is not a member of trait `{}`",
+                    method, trait_name
+                ))
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("not a member of trait `{}`", trait_name)),
+                ),
+
+            TypeError::MissingTraitMethods {
+                trait_name,
+                missing,
+                trait_file,
+                span,
+                file_id,
+            } => {
+                let names = missing
+                    .iter()
+                    .map(|method| format!("`{}`", method.inner))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let labels = missing
+                    .iter()
+                    .map(|method| {
+                        Label::secondary(trait_file, method.span)
+                            .with_message(format!("`{}` from trait", method.inner))
+                    })
+                    .collect();
+                Diagnostic::error(format!("not all trait methods implemented, missing: {}", names))
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message(format!("missing {} in implementation", names)),
+                    )
+                    .with_labels(labels)
+                    .with_help(format!(
+                        "implement the missing methods of `{}` in this `impl` block",
+                        trait_name
+                    ))
+            }
+
+            TypeError::MethodSigMismatch {
+                method,
+                trait_name,
+                expected,
+                found,
+                span,
+                trait_span,
+                trait_file,
+                file_id,
+            } => Diagnostic::error(format!(
+                    "method `{}` has an incompatible type for trait `{}`",
+                    method, trait_name
+                ))
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected `{}`, found `{}`", expected, found)),
+                )
+                .with_label(
+                    Label::secondary(trait_file, trait_span)
+                        .with_message("type in trait declared here"),
+                ),
+
+            TypeError::ConflictingImpls {
+                trait_name,
+                ty,
+                span,
+                previous,
+                previous_file,
+                file_id,
+            } => Diagnostic::error(format!(
+                    "conflicting implementations of trait `{}` for type `{}`",
+                    trait_name, ty
+                ))
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("conflicting implementation for `{}`", ty)),
+                )
+                .with_label(
+                    Label::secondary(previous_file, previous)
+                        .with_message("first implementation here"),
+                ),
+
+            TypeError::StaticAssertNotBool {
+                found,
+                span,
+                file_id,
+            } => Diagnostic::error("static assertion condition must be a `bool`")
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected `bool`, found `{}`", found)),
+                ),
+
+            TypeError::WhileConditionNotBool {
+                found,
+                span,
+                file_id,
+            } => Diagnostic::error("`while` condition must be a `bool`")
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected `bool`, found `{}`", found)),
+                ),
+
+            TypeError::IfConditionNotBool {
+                found,
+                span,
+                file_id,
+            } => Diagnostic::error("`if` condition must be a `bool`")
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected `bool`, found `{}`", found)),
+                ),
+
+            TypeError::BranchTypeMismatch {
+                kind,
+                expected,
+                found,
+                file_id,
+            } => {
+                let (message, first) = match kind {
+                    BranchKind::If => ("`if` and `else` have incompatible types", "branch"),
+                    BranchKind::Match => ("`match` arms have incompatible types", "arm"),
+                };
+                Diagnostic::error(message)
+                    .with_label(Label::primary(file_id, found.span).with_message(format!(
+                        "expected `{}`, found `{}`",
+                        expected.inner, found.inner
+                    )))
+                    .with_label(Label::secondary(file_id, expected.span).with_message(
+                        format!("expected `{}` because of this {}", expected.inner, first),
+                    ))
+            }
+
+            TypeError::InvalidOperands {
+                op,
+                lhs,
+                rhs,
+                file_id,
+            } => {
+                let message = match op.inner {
+                    BinOp::Add => format!("cannot add `{}` to `{}`", rhs.inner, lhs.inner),
+                    BinOp::Sub => format!("cannot subtract `{}` from `{}`", rhs.inner, lhs.inner),
+                    BinOp::Mul => format!("cannot multiply `{}` by `{}`", lhs.inner, rhs.inner),
+                    BinOp::Div => format!("cannot divide `{}` by `{}`", lhs.inner, rhs.inner),
+                    BinOp::Mod => format!(
+                        "cannot take the remainder of `{}` divided by `{}`",
+                        lhs.inner, rhs.inner
+                    ),
+                    BinOp::Equal
+                    | BinOp::NotEq
+                    | BinOp::Less
+                    | BinOp::Le
+                    | BinOp::Greater
+                    | BinOp::Ge => format!("cannot compare `{}` with `{}`", lhs.inner, rhs.inner),
+                    BinOp::And | BinOp::Or => format!(
+                        "cannot apply `{}` to `{}` and `{}`",
+                        op.inner, lhs.inner, rhs.inner
+                    ),
+                };
+                let needs = match op.inner {
+                    BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod => {
+                        "two numbers of the same type"
+                    }
+                    BinOp::Equal | BinOp::NotEq => "two values of the same type",
+                    BinOp::Less | BinOp::Le | BinOp::Greater | BinOp::Ge => {
+                        "two numbers, `bool`s or strings of the same type"
+                    }
+                    BinOp::And | BinOp::Or => "two `bool`s",
+                };
+                Diagnostic::error(message)
+                    .with_label(
+                        Label::primary(file_id, op.span)
+                            .with_message(format!("`{}` takes {}", op.inner, needs)),
+                    )
+                    .with_label(
+                        Label::secondary(file_id, lhs.span)
+                            .with_message(format!("this is of type `{}`", lhs.inner)),
+                    )
+                    .with_label(
+                        Label::secondary(file_id, rhs.span)
+                            .with_message(format!("this is of type `{}`", rhs.inner)),
+                    )
+            }
+
+            TypeError::InvalidUnaryOperand {
+                op,
+                operand,
+                file_id,
+            } => {
+                // `not` displays with the space that separates it from its operand
+                let name = op.inner.to_string();
+                let name = name.trim_end();
+                let expected = match op.inner {
+                    UnaryOp::Neg => "a number",
+                    UnaryOp::Not => "a `bool`",
+                };
+                Diagnostic::error(format!(
+                        "cannot apply `{}` to `{}`",
+                        name, operand.inner
+                    ))
+                    .with_label(
+                        Label::primary(file_id, op.span)
+                            .with_message(format!("`{}` takes {}", name, expected)),
+                    )
+                    .with_label(
+                        Label::secondary(file_id, operand.span)
+                            .with_message(format!("this is of type `{}`", operand.inner)),
+                    )
+            }
+
+            TypeError::LetTypeMismatch {
+                expected,
+                found,
+                span,
+                file_id,
+            } => Diagnostic::error("mismatched types")
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected `{}`, found `{}`", expected.inner, found)),
+                )
+                .with_label(
+                    Label::secondary(file_id, expected.span).with_message(format!(
+                        "expected `{}` because of this annotation",
+                        expected.inner
+                    )),
+                ),
+
+            TypeError::AssignTypeMismatch {
+                expected,
+                found,
+                span,
+                def,
+                def_file,
+                file_id,
+            } => Diagnostic::error("mismatched types")
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected `{}`, found `{}`", expected, found)),
+                )
+                .with_label(
+                    Label::secondary(def_file, def)
+                        .with_message(format!("this variable is of type `{}`", expected)),
+                ),
+
+            TypeError::ReturnMismatch {
+                expected,
+                found,
+                span,
+                file_id,
+            } => Diagnostic::error("mismatched types")
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected `{}`, found `{}`", expected.inner, found)),
+                )
+                .with_label(
+                    Label::secondary(file_id, expected.span).with_message(format!(
+                        "expected `{}` because of return type",
+                        expected.inner
+                    )),
+                ),
+
+            TypeError::MissingElse {
+                expected,
+                span,
+                then_branch,
+                file_id,
+            } => Diagnostic::error("`if` may be missing an `else` clause")
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message(format!("expected `{}`, found `()`", expected.inner)),
+                )
+                .with_label(
+                    Label::secondary(file_id, then_branch)
+                        .with_message("only this branch produces a value"),
+                )
+                .with_label(
+                    Label::secondary(file_id, expected.span).with_message(format!(
+                        "expected `{}` because of return type",
+                        expected.inner
+                    )),
+                )
+                .with_note("`if` expressions without `else` evaluate to `()`")
+                .with_help(format!(
+                    "consider adding an `else` branch that evaluates to `{}`",
+                    expected.inner
+                )),
+        }
+    }
+}
+
+/// Where a function's signature was written, for labels pointing at it.
+struct SigSpans {
+    name: Span,
+    params: Vec<Span>,
+}
+
+/// A method of a trait or an `impl` block. Its signature leaves out the
+/// `self` receiver.
+#[derive(Clone)]
+struct MethodInfo {
+    name: Spanned<Symbol>,
+    def: DefId,
+    sig: FnSig,
+    has_self: bool,
+}
+
+/// A trait's methods, in whose signatures `Self` is a type parameter of
+/// the trait.
+struct TraitInfo {
+    name: Symbol,
+    self_param: TyParam,
+    methods: Vec<MethodInfo>,
+    file_id: FileId,
+}
+
+struct ImplInfo {
+    trait_def: Option<DefId>,
+    trait_name: Spanned<Symbol>,
+    self_ty: Ty,
+    methods: Vec<MethodInfo>,
+    file_id: FileId,
+}
+
+pub struct TypeChecker<'a> {
+    file_id: FileId,
+    resolutions: &'a Resolutions,
+    results: TypeckResults,
+    sig_spans: HashMap<DefId, SigSpans>,
+    /// The declared return type of the function being checked.
+    ret: Option<Spanned<Ty>>,
+    /// What `Self` stands for in the trait or `impl` block being checked.
+    self_ty: Option<Ty>,
+    traits: HashMap<DefId, TraitInfo>,
+    impls: Vec<ImplInfo>,
+    errors: Vec<TypeError>,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(resolutions: &'a Resolutions) -> Self {
+        Self {
+            file_id: FileId(0),
+            resolutions,
+            results: TypeckResults::default(),
+            sig_spans: HashMap::new(),
+            ret: None,
+            self_ty: None,
+            traits: HashMap::new(),
+            impls: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn errors(&self) -> &[TypeError] {
+        &self.errors
+    }
+
+    /// Declares the signature of the builtin function `def`, whose type
+    /// parameters are named in `params` and `ret` like `Type::Param("T")`.
+    /// Builtins must be declared before checking.
+    pub fn declare_builtin(&mut self, def: DefId, params: &[Type], ret: &Type) {
+        let generics: Vec<TyParam> = self
+            .resolutions
+            .builtin_generics(def)
+            .iter()
+            .map(|&param| TyParam {
+                def: param,
+                name: self.resolutions.def(param).name,
+            })
+            .collect();
+        fn lower(ty: &Type, generics: &[TyParam]) -> Ty {
+            match ty {
+                Type::Unit => Ty::Unit,
+                Type::Bool => Ty::Bool,
+                Type::U64 => Ty::U64,
+                Type::I64 => Ty::I64,
+                Type::F64 => Ty::F64,
+                Type::Str => Ty::Str,
+                Type::Param(name) => generics
+                    .iter()
+                    .find(|param| param.name == *name)
+                    .map_or(Ty::Error, |param| Ty::Param(param.clone())),
+                Type::SelfType => Ty::Error,
+                Type::Fn { params, ret } => Ty::Fn(FnSig {
+                    generics: Vec::new(),
+                    params: params
+                        .iter()
+                        .map(|param| lower(&param.inner, generics))
+                        .collect(),
+                    ret: Box::new(lower(&ret.inner, generics)),
+                }),
+            }
+        }
+        let sig = FnSig {
+            params: params.iter().map(|ty| lower(ty, &generics)).collect(),
+            ret: Box::new(lower(ret, &generics)),
+            generics,
+        };
+        self.results.def_types.insert(def, Ty::Fn(sig));
+    }
+
+    pub fn check_program(&mut self, program: &Program) -> TypeckResults {
+        self.collect_sigs(program);
+        self.check_impls();
+        self.check_bodies(program);
+        std::mem::take(&mut self.results)
+    }
+
+    /// Checks every module of `tree`. All signatures are collected first, so
+    /// functions can call functions imported from any module.
+    pub fn check_modules(&mut self, tree: &ModuleTree) -> TypeckResults {
+        for (_, module) in tree.modules() {
+            self.collect_sigs(&module.program);
+        }
+        self.check_impls();
+        for (_, module) in tree.modules() {
+            self.check_bodies(&module.program);
+        }
+        std::mem::take(&mut self.results)
+    }
+
+    fn collect_sigs(&mut self, program: &Program) {
+        self.file_id = program.file_id;
+        for item in &program.items {
+            match &item.inner {
+                ItemKind::Func(func) => {
+                    self.collect_sig(func);
+                }
+                ItemKind::Trait(decl) => self.collect_trait(decl),
+                ItemKind::Impl(decl) => self.collect_impl(decl),
+                ItemKind::Mod(_) | ItemKind::Use(_) | ItemKind::StaticAssert(_) => {}
+            }
+        }
+    }
+
+    fn check_bodies(&mut self, program: &Program) {
+        self.file_id = program.file_id;
+        for item in &program.items {
+            match &item.inner {
+                ItemKind::Func(func) => self.check_func(func),
+                ItemKind::Impl(decl) => {
+                    self.self_ty = Some(self.lower_ty(&decl.self_ty));
+                    for method in &decl.methods {
+                        self.check_func(method);
+                    }
+                    self.self_ty = None;
+                }
+                ItemKind::StaticAssert(assert) => {
+                    let found = self.check_expr(&assert.cond, Some(&Ty::Bool));
+                    if !found.is_compatible(&Ty::Bool) {
+                        self.errors.push(TypeError::StaticAssertNotBool {
+                            found,
+                            span: assert.cond.span,
+                            file_id: self.file_id,
+                        });
+                    }
+                }
+                ItemKind::Mod(_) | ItemKind::Use(_) | ItemKind::Trait(_) => {}
+            }
+        }
+    }
+
+    fn collect_sig(&mut self, func: &Func) -> Option<MethodInfo> {
+        let def = self.resolutions.def_at(self.file_id, func.name.span)?;
+        let (sig, has_self) =
+            self.lower_sig(def, &func.name, &func.generics, &func.params, &func.ty);
+        Some(MethodInfo {
+            name: func.name.clone(),
+            def,
+            sig,
+            has_self,
+        })
+    }
+
+    /// Lowers a function or method signature and records the types of its
+    /// parameters. The `self` receiver, which has the type `Self`, is left
+    /// out of the signature.
+    fn lower_sig(
+        &mut self,
+        def: DefId,
+        name: &Spanned<Symbol>,
+        generics: &[Spanned<Symbol>],
+        params: &[Spanned<Param>],
+        ret: &Option<Spanned<Type>>,
+    ) -> (FnSig, bool) {
+        let mut has_self = false;
+        let mut param_tys = Vec::new();
+        let mut param_spans = Vec::new();
+        for param in params {
+            let (name_span, ty) = match &param.inner {
+                Param::SelfParam => {
+                    has_self = true;
+                    (param.span, self.self_ty.clone().unwrap_or(Ty::Error))
+                }
+                Param::Named { name, ty, .. } => {
+                    let ty = ty.as_ref().map_or(Ty::Unknown, |ty| self.lower_ty(ty));
+                    param_tys.push(ty.clone());
+                    param_spans.push(param.span);
+                    (name.span, ty)
+                }
+            };
+            if let Some(param_def) = self.resolutions.def_at(self.file_id, name_span) {
+                self.results.def_types.insert(param_def, ty);
+            }
+        }
+        let ret = ret.as_ref().map_or(Ty::Unit, |ty| self.lower_ty(ty));
+        let generics = generics
+            .iter()
+            .filter_map(|name| {
+                let def = self.resolutions.def_at(self.file_id, name.span)?;
+                Some(TyParam {
+                    def,
+                    name: name.inner,
+                })
+            })
+            .collect();
+        let sig = FnSig {
+            generics,
+            params: param_tys,
+            ret: Box::new(ret),
+        };
+        self.results.def_types.insert(def, Ty::Fn(sig.clone()));
+        self.sig_spans.insert(
+            def,
+            SigSpans {
+                name: name.span,
+                params: param_spans,
+            },
+        );
+        (sig, has_self)
+    }
+
+    fn collect_trait(&mut self, decl: &TraitDecl) {
+        let Some(def) = self.resolutions.def_at(self.file_id, decl.name.span) else {
+            return;
+        };
+        let self_param = TyParam {
+            def,
+            name: sym::SELF_TYPE,
+        };
+        self.self_ty = Some(Ty::Param(self_param.clone()));
+        let mut methods = Vec::new();
+        for method in &decl.methods {
+            let method = &method.inner;
+            let Some(method_def) = self.resolutions.def_at(self.file_id, method.name.span) else {
+                continue;
+            };
+            let (sig, has_self) =
+                self.lower_sig(method_def, &method.name, &[], &method.params, &method.ty);
+            methods.push(MethodInfo {
+                name: method.name.clone(),
+                def: method_def,
+                sig,
+                has_self,
+            });
+        }
+        self.self_ty = None;
+        self.traits.insert(
+            def,
+            TraitInfo {
+                name: decl.name.inner,
+                self_param,
+                methods,
+                file_id: self.file_id,
+            },
+        );
+    }
+
+    fn collect_impl(&mut self, decl: &ImplDecl) {
+        let self_ty = self.lower_ty(&decl.self_ty);
+        self.self_ty = Some(self_ty.clone());
+        let methods = decl
+            .methods
+            .iter()
+            .filter_map(|method| self.collect_sig(method))
+            .collect();
+        self.self_ty = None;
+        self.impls.push(ImplInfo {
+            trait_def: self.resolutions.resolve(self.file_id, decl.trait_name.span),
+            trait_name: decl.trait_name.clone(),
+            self_ty,
+            methods,
+            file_id: self.file_id,
+        });
+    }
+
+    /// Checks that every `impl` block implements exactly the methods of its
+    /// trait, with the trait's signatures, and that no type implements a
+    /// trait twice.
+    fn check_impls(&mut self) {
+        let mut errors = Vec::new();
+        for (i, imp) in self.impls.iter().enumerate() {
+            let Some(trait_info) = imp.trait_def.and_then(|def| self.traits.get(&def)) else {
+                continue;
+            };
+            if imp.self_ty.is_unknown() {
+                continue;
+            }
+            if let Some(previous) = self.impls[..i]
+                .iter()
+                .find(|other| other.trait_def == imp.trait_def && other.self_ty == imp.self_ty)
+            {
+                errors.push(TypeError::ConflictingImpls {
+                    trait_name: trait_info.name.to_string(),
+                    ty: imp.self_ty.clone(),
+                    span: imp.trait_name.span,
+                    previous: previous.trait_name.span,
+                    previous_file: previous.file_id,
+                    file_id: imp.file_id,
+                });
+                continue;
+            }
+
+            for method in &imp.methods {
+                let Some(declared) = trait_info
+                    .methods
+                    .iter()
+                    .find(|declared| declared.name.inner == method.name.inner)
+                else {
+                    errors.push(TypeError::MethodNotInTrait {
+                        method: method.name.inner.to_string(),
+                        trait_name: trait_info.name.to_string(),
+                        span: method.name.span,
+                        file_id: imp.file_id,
+                    });
+                    continue;
+                };
+                let Ty::Fn(expected) = Ty::Fn(declared.sig.clone()).subst(
+                    std::slice::from_ref(&trait_info.self_param),
+                    std::slice::from_ref(&imp.self_ty),
+                ) else {
+                    continue;
+                };
+                if expected != method.sig || declared.has_self != method.has_self {
+                    errors.push(TypeError::MethodSigMismatch {
+                        method: method.name.inner.to_string(),
+                        trait_name: trait_info.name.to_string(),
+                        expected: method_sig_string(&expected, declared.has_self),
+                        found: method_sig_string(&method.sig, method.has_self),
+                        span: method.name.span,
+                        trait_span: declared.name.span,
+                        trait_file: trait_info.file_id,
+                        file_id: imp.file_id,
+                    });
+                }
+            }
+
+            let missing: Vec<Spanned<String>> = trait_info
+                .methods
+                .iter()
+                .filter(|declared| {
+                    !imp.methods
+                        .iter()
+                        .any(|method| method.name.inner == declared.name.inner)
+                })
+                .map(|declared| declared.name.clone().map(|name| name.to_string()))
+                .collect();
+            if !missing.is_empty() {
+                errors.push(TypeError::MissingTraitMethods {
+                    trait_name: trait_info.name.to_string(),
+                    missing,
+                    trait_file: trait_info.file_id,
+                    span: imp.trait_name.span,
+                    file_id: imp.file_id,
+                });
+            }
+        }
+        self.errors.extend(errors);
+    }
+
+    fn lower_ty(&self, ty: &Spanned<Type>) -> Ty {
+        match (&ty.inner, &self.self_ty) {
+            (Type::SelfType, Some(self_ty)) => self_ty.clone(),
+            _ => Ty::from_ast(ty, self.file_id, self.resolutions),
+        }
+    }
+
+    fn check_func(&mut self, func: &Func) {
+        self.ret = func
+            .ty
+            .as_ref()
+            .map(|ty| Spanned::new(self.lower_ty(ty), ty.span));
+        let ret = self.ret.clone();
+        self.check_expr(&func.body, ret.as_ref().map(|ret| &ret.inner));
+        if let Some(ret) = &ret {
+            self.check_tail(&func.body, ret);
+        }
+        self.ret = None;
+    }
+
+    /// Checks that every path through `expr`, which is in return position,
+    /// ends in a value of type `ret` or leaves the function early.
+    fn check_tail(&mut self, expr: &Expr, ret: &Spanned<Ty>) {
+        let ty = self.results.expr_types[&(self.file_id, expr.span)].clone();
+        if ty == Ty::Never {
+            return;
+        }
+        match &expr.inner {
+            ExprKind::Block { exprs, tail } => match tail {
+                Some(tail) => self.check_tail(tail, ret),
+                None => self.errors.push(TypeError::ReturnMismatch {
+                    expected: ret.clone(),
+                    found: Ty::Unit,
+                    span: exprs.last().map_or(expr.span, |last| last.span),
+                    file_id: self.file_id,
+                }),
+            },
+            ExprKind::If {
+                then_branch,
+                else_branch,
+                ..
+            } => match else_branch {
+                Some(else_branch) => {
+                    self.check_tail(then_branch, ret);
+                    self.check_tail(else_branch, ret);
+                }
+                None if ret.inner.is_compatible(&Ty::Unit) => {}
+                None => self.errors.push(TypeError::MissingElse {
+                    expected: ret.clone(),
+                    span: expr.span,
+                    then_branch: then_branch.span,
+                    file_id: self.file_id,
+                }),
+            },
+            ExprKind::Match { arms, .. } => {
+                for arm in arms {
+                    self.check_tail(&arm.body, ret);
+                }
+            }
+            _ => self.check_return_value(ty, expr.span, ret),
+        }
+    }
+
+    fn check_return_value(&mut self, found: Ty, span: Span, ret: &Spanned<Ty>) {
+        if !found.is_compatible(&ret.inner) {
+            self.errors.push(TypeError::ReturnMismatch {
+                expected: ret.clone(),
+                found,
+                span,
+                file_id: self.file_id,
+            });
+        }
+    }
+
+    /// Computes the type of `expr`. `expected` is the type the context
+    /// wants, which decides the type of integer literals.
+    fn check_expr(&mut self, expr: &Expr, expected: Option<&Ty>) -> Ty {
+        let ty = match &expr.inner {
+            ExprKind::Int(_) => match expected {
+                Some(Ty::I64) => Ty::I64,
+                _ => Ty::U64,
+            },
+            ExprKind::Float(_) => Ty::F64,
+            ExprKind::Bool(_) => Ty::Bool,
+            ExprKind::Unit => Ty::Unit,
+            ExprKind::Error => Ty::Error,
+            ExprKind::Str(parts) => {
+                for part in parts {
+                    if let StrPart::Expr(expr) = part {
+                        self.check_expr(expr, None);
+                    }
+                }
+                Ty::Str
+            }
+            ExprKind::Var(_) | ExprKind::Path(_) => {
+                let ty = self.var_ty(expr.span);
+                if let Ty::Fn(sig) = &ty
+                    && !sig.generics.is_empty()
+                {
+                    let name = match &expr.inner {
+                        ExprKind::Var(name) => name.to_string(),
+                        ExprKind::Path(path) => join_path(path),
+                        _ => unreachable!(),
+                    };
+                    self.errors.push(TypeError::GenericFnAsValue {
+                        name,
+                        span: expr.span,
+                        file_id: self.file_id,
+                    });
+                    Ty::Error
+                } else {
+                    ty
+                }
+            }
+            ExprKind::Unary { op, expr: operand } => {
+                let (operand_ty, valid) = match op.inner {
+                    UnaryOp::Neg => {
+                        let ty = self.check_expr(operand, Some(expected.unwrap_or(&Ty::I64)));
+                        let valid = ty.is_numeric();
+                        (ty, valid)
+                    }
+                    UnaryOp::Not => {
+                        let ty = self.check_expr(operand, Some(&Ty::Bool));
+                        let valid = ty == Ty::Bool;
+                        (ty, valid)
+                    }
+                };
+                if valid || !is_known(&operand_ty) {
+                    match op.inner {
+                        UnaryOp::Neg => operand_ty,
+                        UnaryOp::Not => Ty::Bool,
+                    }
+                } else {
+                    self.errors.push(TypeError::InvalidUnaryOperand {
+                        op: op.clone(),
+                        operand: Spanned::new(operand_ty, operand.span),
+                        file_id: self.file_id,
+                    });
+                    Ty::Error
+                }
+            }
+            ExprKind::Binary { op, lhs, rhs } => self.check_binary(op, lhs, rhs, expected),
+            ExprKind::Cast { expr: operand, ty } => {
+                let from = self.check_expr(operand, None);
+                let to = self.lower_ty(ty);
+                if !from.can_cast_to(&to) {
+                    self.errors.push(TypeError::InvalidCast {
+                        from,
+                        to: to.clone(),
+                        span: expr.span,
+                        file_id: self.file_id,
+                    });
+                }
+                to
+            }
+            ExprKind::Let {
+                name, ty, value, ..
+            } => {
+                let annotated = ty.as_ref().map(|ty| self.lower_ty(ty));
+                let value_ty = value
+                    .as_ref()
+                    .map(|value| self.check_expr(value, annotated.as_ref()));
+                if let (Some(ty), Some(annotated), Some(value), Some(value_ty)) =
+                    (ty, &annotated, value, &value_ty)
+                    && !value_ty.is_compatible(annotated)
+                {
+                    self.errors.push(TypeError::LetTypeMismatch {
+                        expected: Spanned::new(annotated.clone(), ty.span),
+                        found: value_ty.clone(),
+                        span: value.span,
+                        file_id: self.file_id,
+                    });
+                }
+                let ty = annotated.or(value_ty).unwrap_or(Ty::Unknown);
+                if let Some(def) = self.resolutions.def_at(self.file_id, name.span) {
+                    self.results.def_types.insert(def, ty);
+                }
+                Ty::Unit
+            }
+            ExprKind::Assign { name, value } => {
+                // assignments to anything but a variable are reported by the
+                // mutability check
+                let def = self
+                    .resolutions
+                    .resolve(self.file_id, name.span)
+                    .filter(|def| {
+                        matches!(
+                            self.resolutions.def(*def).kind,
+                            DefKind::Local | DefKind::Param
+                        )
+                    });
+                let target = def.and_then(|def| self.results.def_types.get(&def).cloned());
+                let found = self.check_expr(value, target.as_ref());
+                if let (Some(def), Some(target)) = (def, target)
+                    && !found.is_compatible(&target)
+                {
+                    let def = self.resolutions.def(def);
+                    self.errors.push(TypeError::AssignTypeMismatch {
+                        expected: target,
+                        found,
+                        span: value.span,
+                        def: def.span,
+                        def_file: def.file_id,
+                        file_id: self.file_id,
+                    });
+                }
+                Ty::Unit
+            }
+            ExprKind::Block { exprs, tail } => {
+                let mut diverges = false;
+                for expr in exprs {
+                    diverges |= self.check_expr(expr, None) == Ty::Never;
+                }
+                let ty = tail
+                    .as_ref()
+                    .map_or(Ty::Unit, |tail| self.check_expr(tail, expected));
+                if diverges { Ty::Never } else { ty }
+            }
+            ExprKind::Call { callee, args } => self.check_call(expr.span, callee, args, expected),
+            ExprKind::Closure { params, body } => {
+                self.check_closure(expr.span, params, body, expected)
+            }
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => self.check_method_call(expr.span, receiver, method, args, expected),
+            ExprKind::Match { scrutinee, arms } => {
+                let scrutinee_ty = self.check_expr(scrutinee, None);
+                for arm in arms {
+                    for name in visit::pattern_bindings(&arm.pattern) {
+                        if let Some(def) = self.resolutions.def_at(self.file_id, name.span) {
+                            self.results.def_types.insert(def, scrutinee_ty.clone());
+                        }
+                    }
+                }
+                let bodies: Vec<_> = arms.iter().map(|arm| &arm.body).collect();
+                self.check_branches(BranchKind::Match, &bodies, expected)
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                let found = self.check_expr(cond, Some(&Ty::Bool));
+                if !found.is_compatible(&Ty::Bool) {
+                    self.errors.push(TypeError::IfConditionNotBool {
+                        found,
+                        span: cond.span,
+                        file_id: self.file_id,
+                    });
+                }
+                match else_branch {
+                    Some(else_branch) => {
+                        self.check_branches(BranchKind::If, &[then_branch, else_branch], expected)
+                    }
+                    None => {
+                        self.check_expr(then_branch, expected);
+                        Ty::Unit
+                    }
+                }
+            }
+            ExprKind::While { cond, body } => {
+                let found = self.check_expr(cond, Some(&Ty::Bool));
+                if !found.is_compatible(&Ty::Bool) {
+                    self.errors.push(TypeError::WhileConditionNotBool {
+                        found,
+                        span: cond.span,
+                        file_id: self.file_id,
+                    });
+                }
+                self.check_expr(body, None);
+                Ty::Unit
+            }
+            ExprKind::Return(value) => {
+                let ret = self.ret.clone();
+                let found = match value {
+                    Some(value) => self.check_expr(value, ret.as_ref().map(|ret| &ret.inner)),
+                    None => Ty::Unit,
+                };
+                if let Some(ret) = &ret {
+                    let span = value.as_ref().map_or(expr.span, |value| value.span);
+                    self.check_return_value(found, span, ret);
+                }
+                Ty::Never
+            }
+        };
+        self.results
+            .expr_types
+            .insert((self.file_id, expr.span), ty.clone());
+        ty
+    }
+
+    /// Checks `branches`, which produce the value of an `if` or a `match`,
+    /// and returns the type of the first that doesn't diverge, or `!` if
+    /// all do. Integer literals take the type of the other branches, so
+    /// they are checked last. If the context expects a type, the type of
+    /// the first branch that doesn't match it is returned instead, for the
+    /// context to report; otherwise a branch of another type than the
+    /// first is reported here.
+    fn check_branches(
+        &mut self,
+        kind: BranchKind,
+        branches: &[&Expr],
+        expected: Option<&Ty>,
+    ) -> Ty {
+        let mut hint = expected.cloned();
+        let mut tys = vec![Ty::Never; branches.len()];
+        let (literals, others): (Vec<_>, Vec<_>) =
+            (0..branches.len()).partition(|&i| is_int_literal(branches[i]));
+        for i in others.into_iter().chain(literals) {
+            tys[i] = self.check_expr(branches[i], hint.as_ref());
+            if hint.is_none() && is_known(&tys[i]) {
+                hint = Some(tys[i].clone());
+            }
+        }
+        if let Some(expected) = expected {
+            let mismatch = tys.iter().find(|ty| !ty.is_compatible(expected));
+            return mismatch
+                .or_else(|| tys.iter().find(|ty| **ty != Ty::Never))
+                .cloned()
+                .unwrap_or(Ty::Never);
+        }
+        let Some(first) = tys.iter().position(|ty| *ty != Ty::Never) else {
+            return Ty::Never;
+        };
+        let mut ty = tys[first].clone();
+        for (branch, found) in branches.iter().zip(&tys).skip(first + 1) {
+            if !found.is_compatible(&tys[first]) {
+                self.errors.push(TypeError::BranchTypeMismatch {
+                    kind,
+                    expected: Spanned::new(tys[first].clone(), value_span(branches[first])),
+                    found: Spanned::new(found.clone(), value_span(branch)),
+                    file_id: self.file_id,
+                });
+                ty = Ty::Error;
+            }
+        }
+        ty
+    }
+
+    fn check_binary(
+        &mut self,
+        op: &Spanned<BinOp>,
+        lhs: &Expr,
+        rhs: &Expr,
+        expected: Option<&Ty>,
+    ) -> Ty {
+        match op.inner {
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod => {
+                let (lhs_ty, rhs_ty) = self.check_operands(op.inner, lhs, rhs, expected);
+                if lhs_ty == rhs_ty
+                    && (lhs_ty.is_numeric() || op.inner == BinOp::Add && lhs_ty == Ty::Str)
+                {
+                    lhs_ty
+                } else {
+                    self.check_invalid_operands(op, lhs, lhs_ty, rhs, rhs_ty);
+                    Ty::Error
+                }
+            }
+            BinOp::Equal | BinOp::NotEq => {
+                let (lhs_ty, rhs_ty) = self.check_operands(op.inner, lhs, rhs, None);
+                if !lhs_ty.is_compatible(&rhs_ty) && !rhs_ty.is_compatible(&lhs_ty) {
+                    self.check_invalid_operands(op, lhs, lhs_ty, rhs, rhs_ty);
+                }
+                Ty::Bool
+            }
+            BinOp::Less | BinOp::Le | BinOp::Greater | BinOp::Ge => {
+                let (lhs_ty, rhs_ty) = self.check_operands(op.inner, lhs, rhs, None);
+                let ordered = |ty: &Ty| ty.is_numeric() || matches!(ty, Ty::Bool | Ty::Str);
+                if lhs_ty != rhs_ty || !ordered(&lhs_ty) {
+                    self.check_invalid_operands(op, lhs, lhs_ty, rhs, rhs_ty);
+                }
+                Ty::Bool
+            }
+            BinOp::And | BinOp::Or => {
+                let lhs_ty = self.check_expr(lhs, Some(&Ty::Bool));
+                let rhs_ty = self.check_expr(rhs, Some(&Ty::Bool));
+                if (lhs_ty != Ty::Bool || rhs_ty != Ty::Bool)
+                    && is_known(&lhs_ty)
+                    && is_known(&rhs_ty)
+                {
+                    self.errors.push(TypeError::InvalidOperands {
+                        op: op.clone(),
+                        lhs: Spanned::new(lhs_ty, lhs.span),
+                        rhs: Spanned::new(rhs_ty, rhs.span),
+                        file_id: self.file_id,
+                    });
+                }
+                Ty::Bool
+            }
+        }
+    }
+
+    /// Checks the operands of an arithmetic or comparison operator, which
+    /// must have the same type. A literal on the left takes its type from the
+    /// right operand, so `1 + x` works for an `i64` `x`.
+    fn check_operands(
+        &mut self,
+        op: BinOp,
+        lhs: &Expr,
+        rhs: &Expr,
+        expected: Option<&Ty>,
+    ) -> (Ty, Ty) {
+        let mut lhs_ty = self.check_expr(lhs, expected);
+        let rhs_ty = self.check_expr(rhs, Some(&lhs_ty));
+        if lhs_ty != rhs_ty && rhs_ty.is_integer() && is_int_literal(lhs) {
+            lhs_ty = self.check_expr(lhs, Some(&rhs_ty));
+        }
+        if let Some((ty, span)) = [(&lhs_ty, lhs.span), (&rhs_ty, rhs.span)]
+            .into_iter()
+            .find(|(ty, _)| matches!(ty, Ty::Param(_)))
+        {
+            self.errors.push(TypeError::OperatorOnTypeParam {
+                op,
+                operand: Spanned::new(ty.clone(), span),
+                file_id: self.file_id,
+            });
+            return (Ty::Error, Ty::Error);
+        }
+        if lhs_ty != rhs_ty && lhs_ty.is_numeric() && rhs_ty.is_numeric() {
+            self.errors.push(TypeError::MismatchedNumbers {
+                op,
+                lhs: Spanned::new(lhs_ty.clone(), lhs.span),
+                rhs: Spanned::new(rhs_ty.clone(), rhs.span),
+                file_id: self.file_id,
+            });
+        }
+        (lhs_ty, rhs_ty)
+    }
+
+    /// Reports the operands of `op` as invalid, unless either type is
+    /// unknown or `check_operands` already reported them as mismatched
+    /// numbers.
+    fn check_invalid_operands(
+        &mut self,
+        op: &Spanned<BinOp>,
+        lhs: &Expr,
+        lhs_ty: Ty,
+        rhs: &Expr,
+        rhs_ty: Ty,
+    ) {
+        let mismatched_numbers = lhs_ty != rhs_ty && lhs_ty.is_numeric() && rhs_ty.is_numeric();
+        if !is_known(&lhs_ty) || !is_known(&rhs_ty) || mismatched_numbers {
+            return;
+        }
+        let lhs = Spanned::new(lhs_ty, lhs.span);
+        let rhs = Spanned::new(rhs_ty, rhs.span);
+        let error = if lhs.inner == Ty::Str || rhs.inner == Ty::Str {
+            TypeError::InvalidStrOperands {
+                op: op.inner,
+                lhs,
+                rhs,
+                file_id: self.file_id,
+            }
+        } else {
+            TypeError::InvalidOperands {
+                op: op.clone(),
+                lhs,
+                rhs,
+                file_id: self.file_id,
+            }
+        };
+        self.errors.push(error);
+    }
+
+    fn var_ty(&self, span: Span) -> Ty {
+        self.resolutions
+            .resolve(self.file_id, span)
+            .and_then(|def| self.results.def_types.get(&def).cloned())
+            .unwrap_or(Ty::Error)
+    }
+
+    fn check_call(
+        &mut self,
+        span: Span,
+        callee: &Expr,
+        args: &[Expr],
+        expected: Option<&Ty>,
+    ) -> Ty {
+        // a generic function can be named here without being a value
+        let callee_ty = match &callee.inner {
+            ExprKind::Var(_) | ExprKind::Path(_) => {
+                let ty = self.var_ty(callee.span);
+                self.results
+                    .expr_types
+                    .insert((self.file_id, callee.span), ty.clone());
+                ty
+            }
+            _ => self.check_expr(callee, None),
+        };
+        let sig = match callee_ty {
+            Ty::Fn(sig) => sig,
+            ty if ty.is_unknown() => {
+                for arg in args {
+                    self.check_expr(arg, None);
+                }
+                return Ty::Error;
+            }
+            ty => {
+                for arg in args {
+                    self.check_expr(arg, None);
+                }
+                self.errors.push(TypeError::NotCallable {
+                    ty,
+                    span: callee.span,
+                    file_id: self.file_id,
+                });
+                return Ty::Error;
+            }
+        };
+
+        let def = self.resolutions.resolve(self.file_id, callee.span);
+        self.check_args(span, callee.span, def, sig, args, expected)
+    }
+
+    /// Checks a closure. Parameters without annotation take their types
+    /// from the function type the context expects, and the body is checked
+    /// against its return type.
+    fn check_closure(
+        &mut self,
+        span: Span,
+        params: &[Spanned<Param>],
+        body: &Expr,
+        expected: Option<&Ty>,
+    ) -> Ty {
+        let expected = match expected {
+            Some(Ty::Fn(sig)) if sig.generics.is_empty() && sig.params.len() == params.len() => {
+                Some(sig.clone())
+            }
+            _ => None,
+        };
+        let mut param_tys = Vec::new();
+        for (i, param) in params.iter().enumerate() {
+            // the parser gives closures no `self`
+            let Param::Named { name, ty, .. } = &param.inner else {
+                continue;
+            };
+            let ty = match ty {
+                Some(ty) => self.lower_ty(ty),
+                None => expected
+                    .as_ref()
+                    .map_or(Ty::Unknown, |sig| sig.params[i].clone()),
+            };
+            if let Some(def) = self.resolutions.def_at(self.file_id, name.span) {
+                self.results.def_types.insert(def, ty.clone());
+            }
+            param_tys.push(ty);
+        }
+
+        // `return` in the body leaves the closure, not the function
+        let outer_ret = self.ret.take();
+        self.ret = expected
+            .as_ref()
+            .map(|sig| Spanned::new((*sig.ret).clone(), span));
+        let ret = self.ret.clone();
+        let body_ty = self.check_expr(body, ret.as_ref().map(|ret| &ret.inner));
+        let ret = match ret {
+            Some(ret) => {
+                self.check_tail(body, &ret);
+                ret.inner
+            }
+            None => body_ty,
+        };
+        self.ret = outer_ret;
+
+        let ty = Ty::Fn(FnSig {
+            generics: Vec::new(),
+            params: param_tys,
+            ret: Box::new(ret),
+        });
+        if let Some(def) = self.resolutions.def_at(self.file_id, span) {
+            self.results.def_types.insert(def, ty.clone());
+        }
+        ty
+    }
+
+    fn check_method_call(
+        &mut self,
+        span: Span,
+        receiver: &Expr,
+        method: &Spanned<Symbol>,
+        args: &[Expr],
+        expected: Option<&Ty>,
+    ) -> Ty {
+        let ty = self.check_expr(receiver, None);
+        let check_args_alone = |this: &mut Self| {
+            for arg in args {
+                this.check_expr(arg, None);
+            }
+            Ty::Error
+        };
+        if ty.is_unknown() || ty == Ty::Never {
+            return check_args_alone(self);
+        }
+
+        let targets: Vec<(Option<DefId>, &MethodInfo)> = self
+            .impls
+            .iter()
+            .filter(|imp| imp.self_ty == ty)
+            .filter_map(|imp| {
+                let target = imp
+                    .methods
+                    .iter()
+                    .find(|m| m.name.inner == method.inner && m.has_self)?;
+                Some((imp.trait_def, target))
+            })
+            .collect();
+        match targets.as_slice() {
+            [] => {
+                let declaring = self.traits.iter().filter_map(|(&def, info)| {
+                    info.methods
+                        .iter()
+                        .any(|m| m.name.inner == method.inner && m.has_self)
+                        .then_some(def)
+                });
+                let candidates = self.trait_names(declaring);
+                self.errors.push(TypeError::MethodNotFound {
+                    method: method.inner.to_string(),
+                    ty,
+                    candidates,
+                    span: method.span,
+                    file_id: self.file_id,
+                });
+                check_args_alone(self)
+            }
+            [(_, target)] => {
+                let (def, sig) = (target.def, target.sig.clone());
+                self.results
+                    .method_calls
+                    .insert((self.file_id, method.span), def);
+                self.check_args(span, method.span, Some(def), sig, args, expected)
+            }
+            _ => {
+                let traits = self.trait_names(targets.iter().filter_map(|(def, _)| *def));
+                self.errors.push(TypeError::AmbiguousMethod {
+                    method: method.inner.to_string(),
+                    ty,
+                    traits,
+                    span: method.span,
+                    file_id: self.file_id,
+                });
+                check_args_alone(self)
+            }
+        }
+    }
+
+    /// The sorted names of the traits `defs`.
+    fn trait_names(&self, defs: impl Iterator<Item = DefId>) -> Vec<String> {
+        let mut names: Vec<String> = defs
+            .filter_map(|def| self.traits.get(&def))
+            .map(|info| info.name.to_string())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Checks the arguments of a call to a function of signature `sig` and
+    /// returns the type of the call, inferring the function's type
+    /// arguments. `callee` is the span the instantiation is recorded at.
+    fn check_args(
+        &mut self,
+        span: Span,
+        callee: Span,
+        def: Option<DefId>,
+        sig: FnSig,
+        args: &[Expr],
+        expected: Option<&Ty>,
+    ) -> Ty {
+        let sig_spans = def.and_then(|def| self.sig_spans.get(&def));
+        let callee_span = sig_spans.map_or(callee, |spans| spans.name);
+        let param_spans = sig_spans.map(|spans| spans.params.clone());
+
+        if args.len() != sig.params.len() {
+            self.errors.push(TypeError::ArgCountMismatch {
+                expected: sig.params.len(),
+                found: args.len(),
+                span,
+                callee: callee_span,
+                file_id: self.file_id,
+            });
+        }
+        // type arguments are inferred from the first argument passed for
+        // each type parameter, or else from the type the call should have
+        let mut inferred: Vec<Option<Ty>> = vec![None; sig.generics.len()];
+        let infer = |inferred: &mut Vec<Option<Ty>>, param: &Ty, found: &Ty| {
+            if let Ty::Param(param) = param
+                && let Some(i) = sig.generics.iter().position(|g| g.def == param.def)
+                && inferred[i].is_none()
+                && !found.is_unknown()
+                && *found != Ty::Never
+            {
+                inferred[i] = Some(found.clone());
+            }
+        };
+        for (i, arg) in args.iter().enumerate() {
+            let Some(param) = sig.params.get(i) else {
+                self.check_expr(arg, None);
+                continue;
+            };
+            let known = subst_inferred(param, &sig.generics, &inferred);
+            let hint = (!matches!(known, Ty::Param(_))).then_some(&known);
+            let found = self.check_expr(arg, hint);
+            infer(&mut inferred, &known, &found);
+            let expected = subst_inferred(param, &sig.generics, &inferred);
+            if !found.is_compatible(&expected) {
+                self.errors.push(TypeError::ArgTypeMismatch {
+                    expected,
+                    found,
+                    span: arg.span,
+                    param: param_spans
+                        .as_ref()
+                        .and_then(|spans| spans.get(i).copied())
+                        .unwrap_or(callee_span),
+                    file_id: self.file_id,
+                });
+            }
+        }
+        if sig.generics.is_empty() {
+            return *sig.ret;
+        }
+
+        if let Some(expected) = expected {
+            infer(&mut inferred, &sig.ret, expected);
+        }
+        let args: Vec<Ty> = sig
+            .generics
+            .iter()
+            .zip(inferred)
+            .map(|(param, ty)| {
+                ty.unwrap_or_else(|| {
+                    self.errors.push(TypeError::CannotInferTypeParam {
+                        param: param.name.to_string(),
+                        span: callee,
+                        callee: callee_span,
+                        file_id: self.file_id,
+                    });
+                    Ty::Error
+                })
+            })
+            .collect();
+        let ret = sig.ret.subst(&sig.generics, &args);
+        self.results
+            .instantiations
+            .insert((self.file_id, callee), args);
+        ret
+    }
+}
+
+/// A method signature as it would be written, like `fn(self, u64) -> bool`.
+fn method_sig_string(sig: &FnSig, has_self: bool) -> String {
+    let params = has_self
+        .then(|| "self".to_string())
+        .into_iter()
+        .chain(sig.params.iter().map(ToString::to_string))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("fn({}) -> {}", params, sig.ret)
+}
+
+/// Substitutes the type arguments inferred so far into `ty`.
+fn subst_inferred(ty: &Ty, generics: &[TyParam], inferred: &[Option<Ty>]) -> Ty {
+    let (generics, args): (Vec<TyParam>, Vec<Ty>) = generics
+        .iter()
+        .zip(inferred)
+        .filter_map(|(param, ty)| Some((param.clone(), ty.clone()?)))
+        .unzip();
+    ty.subst(&generics, &args)
+}
+
+/// Whether `expr` is an integer literal, possibly negated or alone in a
+/// block, whose type is decided by its context.
+fn is_int_literal(expr: &Expr) -> bool {
+    match &expr.inner {
+        ExprKind::Int(_) => true,
+        ExprKind::Unary { op, expr } => op.inner == UnaryOp::Neg && is_int_literal(expr),
+        ExprKind::Block {
+            exprs,
+            tail: Some(tail),
+        } => exprs.is_empty() && is_int_literal(tail),
+        _ => false,
+    }
+}
+
+/// The span of the expression that produces the value of `expr`: the
+/// innermost tail of the blocks it is in.
+fn value_span(expr: &Expr) -> Span {
+    match &expr.inner {
+        ExprKind::Block {
+            tail: Some(tail), ..
+        } => value_span(tail),
+        _ => expr.span,
+    }
+}
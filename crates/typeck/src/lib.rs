@@ -0,0 +1,7 @@
+mod checker;
+mod mono;
+mod ty;
+
+pub use checker::*;
+pub use mono::*;
+pub use ty::*;
@@ -0,0 +1,210 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span};
+use ginto_resolve::{DefId, DefKind, ModuleTree, Resolutions};
+use ginto_syntax::{
+    Expr, ExprKind, Func,
+    visit::{self, Visitor},
+};
+
+use crate::{FnSig, Ty, TypeckResults};
+
+/// How many generic calls deep an instantiation chain may get before the
+/// collector gives up on it.
+pub const INSTANTIATION_DEPTH_LIMIT: usize = 64;
+
+/// How many instances one program may need in total.
+pub const INSTANCE_LIMIT: usize = 10_000;
+
+/// A function together with the concrete types of its type parameters.
+/// Functions without type parameters have a single instance with no
+/// arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Instance {
+    pub def: DefId,
+    pub args: Vec<Ty>,
+}
+
+impl Instance {
+    /// The signature of the function with the type arguments substituted.
+    pub fn sig(&self, types: &TypeckResults) -> Option<FnSig> {
+        let Some(Ty::Fn(sig)) = types.def_ty(self.def) else {
+            return None;
+        };
+        let Ty::Fn(sig) = Ty::Fn(sig.clone()).subst(&sig.generics, &self.args) else {
+            return None;
+        };
+        Some(FnSig {
+            generics: Vec::new(),
+            ..sig
+        })
+    }
+
+    /// The instance as it would be written, like `id::<u64>`.
+    pub fn display(&self, resolutions: &Resolutions) -> String {
+        let name = resolutions.def(self.def).name;
+        if self.args.is_empty() {
+            return name.to_string();
+        }
+        let args = self
+            .args
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{}::<{}>", name, args)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MonoError {
+    /// A chain of generic calls deeper than `INSTANTIATION_DEPTH_LIMIT`.
+    RecursionLimit {
+        instance: String,
+        span: Span,
+        file_id: FileId,
+    },
+    TooManyInstances {
+        span: Span,
+        file_id: FileId,
+    },
+}
+
+impl DiagnosticConvertible for MonoError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            MonoError::RecursionLimit {
+                instance,
+                span,
+                file_id,
+            } => Diagnostic::error(format!(
+                "reached the recursion limit while instantiating `{}`",
+                instance
+            ))
+            .with_label(Label::primary(file_id, span).with_message("instantiated here"))
+            .with_note(format!(
+                "generic calls can only be nested {} instantiations deep",
+                INSTANTIATION_DEPTH_LIMIT
+            )),
+
+            MonoError::TooManyInstances { span, file_id } => {
+                Diagnostic::error("too many generic instantiations")
+                    .with_label(
+                        Label::primary(file_id, span)
+                            .with_message("the limit was reached instantiating this"),
+                    )
+                    .with_note(format!(
+                        "a program can need at most {} function instances",
+                        INSTANCE_LIMIT
+                    ))
+            }
+        }
+    }
+}
+
+/// Collects every function instance reachable from `entry`, starting with
+/// `entry` itself, so that each generic function can be compiled once per
+/// set of concrete type arguments it is used with.
+///
+/// Calls whose type arguments failed to infer are skipped; they are already
+/// reported by the type checker.
+pub fn collect_instances(
+    tree: &ModuleTree,
+    resolutions: &Resolutions,
+    types: &TypeckResults,
+    entry: DefId,
+) -> (Vec<Instance>, Vec<MonoError>) {
+    let mut funcs: HashMap<DefId, (&Func, FileId)> = HashMap::new();
+    for (_, module) in tree.modules() {
+        let file_id = module.program.file_id;
+        for func in module.program.funcs() {
+            if let Some(def) = resolutions.def_at(file_id, func.name.span) {
+                funcs.insert(def, (func, file_id));
+            }
+        }
+    }
+
+    let entry = Instance {
+        def: entry,
+        args: Vec::new(),
+    };
+    let mut instances = vec![entry.clone()];
+    let mut seen: HashSet<Instance> = HashSet::from([entry.clone()]);
+    let mut queue = VecDeque::from([(entry, 0)]);
+    let mut errors = Vec::new();
+
+    while let Some((instance, depth)) = queue.pop_front() {
+        let Some(&(func, file_id)) = funcs.get(&instance.def) else {
+            continue;
+        };
+        let generics = match types.def_ty(instance.def) {
+            Some(Ty::Fn(sig)) => sig.generics.clone(),
+            _ => Vec::new(),
+        };
+        let mut collector = FnRefCollector {
+            file_id,
+            resolutions,
+            types,
+            refs: Vec::new(),
+        };
+        collector.visit_func(func);
+
+        for (def, span) in collector.refs {
+            let args: Vec<Ty> = types
+                .instantiation(file_id, span)
+                .unwrap_or_default()
+                .iter()
+                .map(|ty| ty.subst(&generics, &instance.args))
+                .collect();
+            if args.iter().any(Ty::is_unknown) {
+                continue;
+            }
+            let callee = Instance { def, args };
+            if seen.contains(&callee) {
+                continue;
+            }
+            if depth + 1 > INSTANTIATION_DEPTH_LIMIT {
+                errors.push(MonoError::RecursionLimit {
+                    instance: callee.display(resolutions),
+                    span,
+                    file_id,
+                });
+                continue;
+            }
+            if instances.len() >= INSTANCE_LIMIT {
+                errors.push(MonoError::TooManyInstances { span, file_id });
+                return (instances, errors);
+            }
+            seen.insert(callee.clone());
+            instances.push(callee.clone());
+            queue.push_back((callee, depth + 1));
+        }
+    }
+    (instances, errors)
+}
+
+/// Every mention of a function in a body, called or not, and every method
+/// called, in source order.
+struct FnRefCollector<'a> {
+    file_id: FileId,
+    resolutions: &'a Resolutions,
+    types: &'a TypeckResults,
+    refs: Vec<(DefId, Span)>,
+}
+
+impl Visitor for FnRefCollector<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Var(_) | ExprKind::Path(_) = expr.inner
+            && let Some(def) = self.resolutions.resolve(self.file_id, expr.span)
+            && self.resolutions.def(def).kind == DefKind::Func
+        {
+            self.refs.push((def, expr.span));
+        }
+        if let ExprKind::MethodCall { method, .. } = &expr.inner
+            && let Some(def) = self.types.method_target(self.file_id, method.span)
+        {
+            self.refs.push((def, method.span));
+        }
+        visit::walk_expr(self, expr);
+    }
+}
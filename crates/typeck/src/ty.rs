@@ -0,0 +1,213 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use ginto_diag::{BytePos, FileId, Span, Spanned, Symbol};
+use ginto_resolve::{DefId, Resolutions};
+use ginto_syntax::Type;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Ty {
+    Unit,
+    Bool,
+    U64,
+    I64,
+    F64,
+    Str,
+    Fn(FnSig),
+    /// A type parameter of the function being checked. Nothing is known
+    /// about it, so it is only compatible with itself.
+    Param(TyParam),
+    /// The type of expressions that never produce a value, like `return`.
+    Never,
+    /// A parameter without annotation; compatible with every type.
+    Unknown,
+    /// The type of an expression that already failed to check.
+    Error,
+}
+
+impl Ty {
+    /// Lowers a type written in `file_id`, looking type parameters up in
+    /// `resolutions` by the span they are named at.
+    pub fn from_ast(ty: &Spanned<Type>, file_id: FileId, resolutions: &Resolutions) -> Self {
+        match &ty.inner {
+            Type::Unit => Ty::Unit,
+            Type::Bool => Ty::Bool,
+            Type::U64 => Ty::U64,
+            Type::I64 => Ty::I64,
+            Type::F64 => Ty::F64,
+            Type::Str => Ty::Str,
+            // only the checker knows what `Self` stands for
+            Type::SelfType => Ty::Error,
+            Type::Param(name) => match resolutions.resolve(file_id, ty.span) {
+                Some(def) => Ty::Param(TyParam { def, name: *name }),
+                None => Ty::Error,
+            },
+            Type::Fn { params, ret } => Ty::Fn(FnSig {
+                generics: Vec::new(),
+                params: params
+                    .iter()
+                    .map(|param| Ty::from_ast(param, file_id, resolutions))
+                    .collect(),
+                ret: Box::new(Ty::from_ast(ret, file_id, resolutions)),
+            }),
+        }
+    }
+
+    /// Replaces each of the type parameters `generics` with the type at the
+    /// same position in `args`.
+    pub fn subst(&self, generics: &[TyParam], args: &[Ty]) -> Ty {
+        match self {
+            Ty::Param(param) => generics
+                .iter()
+                .position(|generic| generic.def == param.def)
+                .and_then(|i| args.get(i).cloned())
+                .unwrap_or_else(|| self.clone()),
+            Ty::Fn(sig) => Ty::Fn(FnSig {
+                generics: sig.generics.clone(),
+                params: sig
+                    .params
+                    .iter()
+                    .map(|ty| ty.subst(generics, args))
+                    .collect(),
+                ret: Box::new(sig.ret.subst(generics, args)),
+            }),
+            _ => self.clone(),
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Ty::U64 | Ty::I64)
+    }
+
+    /// Whether arithmetic applies to values of the type.
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || *self == Ty::F64
+    }
+
+    /// Whether errors involving this type should be suppressed because
+    /// something else is already wrong or unknown about it.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Ty::Unknown | Ty::Error)
+    }
+
+    /// Whether `self as target` is a valid conversion. Integers convert to
+    /// each other by reinterpreting their two's complement bits, so
+    /// `-1 as u64` is `u64::MAX`; `bool` converts to `0` or `1`. Integers
+    /// convert to the nearest `f64`, and an `f64` to an integer by rounding
+    /// toward zero to the nearest value the integer type holds, `NaN` to `0`.
+    pub fn can_cast_to(&self, target: &Ty) -> bool {
+        self == target
+            || self.is_unknown()
+            || *self == Ty::Never
+            || (target.is_numeric() && self.is_numeric())
+            || (target.is_integer() && *self == Ty::Bool)
+    }
+
+    /// Whether a value of type `self` can be used where `expected` is needed.
+    pub fn is_compatible(&self, expected: &Ty) -> bool {
+        if let (Ty::Fn(sig), Ty::Fn(expected)) = (self, expected)
+            && sig.generics.is_empty()
+            && expected.generics.is_empty()
+            && sig.params.len() == expected.params.len()
+        {
+            // a closure with unannotated parameters fits any arguments
+            return sig
+                .params
+                .iter()
+                .zip(&expected.params)
+                .all(|(param, expected)| expected.is_compatible(param))
+                && sig.ret.is_compatible(&expected.ret);
+        }
+        self == expected || self.is_unknown() || expected.is_unknown() || *self == Ty::Never
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Unit => write!(f, "()"),
+            Ty::Bool => write!(f, "bool"),
+            Ty::U64 => write!(f, "u64"),
+            Ty::I64 => write!(f, "i64"),
+            Ty::F64 => write!(f, "f64"),
+            Ty::Str => write!(f, "str"),
+            Ty::Fn(sig) => {
+                if !sig.generics.is_empty() {
+                    let generics = sig
+                        .generics
+                        .iter()
+                        .map(|param| param.name.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    write!(f, "fn<{}>", generics)?;
+                } else {
+                    write!(f, "fn")?;
+                }
+                let params = sig
+                    .params
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "({}) -> {}", params, sig.ret)
+            }
+            Ty::Param(param) => write!(f, "{}", param.name),
+            Ty::Never => write!(f, "!"),
+            Ty::Unknown | Ty::Error => write!(f, "_"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FnSig {
+    pub generics: Vec<TyParam>,
+    pub params: Vec<Ty>,
+    pub ret: Box<Ty>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TyParam {
+    pub def: DefId,
+    pub name: Symbol,
+}
+
+/// Types computed by the checker, keyed like `Resolutions`.
+#[derive(Debug, Default, Clone)]
+pub struct TypeckResults {
+    pub(crate) expr_types: HashMap<(FileId, Span), Ty>,
+    pub(crate) def_types: HashMap<DefId, Ty>,
+    /// The type arguments inferred for each call of a generic function,
+    /// keyed by the span of the callee.
+    pub(crate) instantiations: HashMap<(FileId, Span), Vec<Ty>>,
+    /// The `impl` method each method call resolved to, keyed by the span of
+    /// the method name.
+    pub(crate) method_calls: HashMap<(FileId, Span), DefId>,
+}
+
+impl TypeckResults {
+    pub fn expr_ty(&self, file_id: FileId, span: Span) -> Option<&Ty> {
+        self.expr_types.get(&(file_id, span))
+    }
+
+    /// Returns the innermost expression in `file_id` whose span contains
+    /// `pos`, with its type.
+    pub fn expr_at(&self, file_id: FileId, pos: BytePos) -> Option<(Span, &Ty)> {
+        self.expr_types
+            .iter()
+            .filter(|((file, span), _)| *file == file_id && span.start <= pos && pos < span.end)
+            .map(|((_, span), ty)| (*span, ty))
+            .min_by_key(|(span, _)| (span.len(), span.start))
+    }
+
+    pub fn def_ty(&self, def: DefId) -> Option<&Ty> {
+        self.def_types.get(&def)
+    }
+
+    pub fn instantiation(&self, file_id: FileId, span: Span) -> Option<&[Ty]> {
+        self.instantiations.get(&(file_id, span)).map(Vec::as_slice)
+    }
+
+    pub fn method_target(&self, file_id: FileId, span: Span) -> Option<DefId> {
+        self.method_calls.get(&(file_id, span)).copied()
+    }
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::typecheck;
+use ginto_test_support::labels_in;
+use ginto_typeck::{Ty, TypeError};
+
+const ADD: &str = "fn add(a: u64, b: u64) -> u64:\n    a + b\n\n";
+
+#[test]
+fn calls_with_the_arguments_of_the_signature_check() {
+    let typed = typecheck(&format!("{ADD}fn main() -> u64:\n    add(1, 2)\n"));
+    assert!(typed.errors.is_empty(), "{:#?}", typed.errors);
+}
+
+#[test]
+fn too_few_arguments() {
+    let typed = typecheck(&format!("{ADD}fn main() -> u64:\n    add(1)\n"));
+    let diagnostic = typed.diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "this function takes 2 arguments but 1 argument was supplied"
+    );
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [
+            ("add(1)", "expected 2 arguments".to_string()),
+            ("add", "function defined here".to_string()),
+        ]
+    );
+    // the second label is on the signature, not the call
+    assert!(diagnostic.labels[1].span.start < diagnostic.labels[0].span.start);
+}
+
+#[test]
+fn too_many_arguments() {
+    let typed = typecheck(&format!("{ADD}fn main() -> u64:\n    add(1, 2, 3)\n"));
+    assert!(matches!(
+        typed.errors[..],
+        [TypeError::ArgCountMismatch {
+            expected: 2,
+            found: 3,
+            ..
+        }]
+    ));
+    assert_eq!(
+        typed.diagnostic().message,
+        "this function takes 2 arguments but 3 arguments were supplied"
+    );
+}
+
+#[test]
+fn extra_arguments_are_still_checked() {
+    let typed = typecheck(&format!(
+        "{ADD}fn main() -> u64:\n    add(1, 2, true + 1)\n"
+    ));
+    assert!(matches!(
+        typed.errors[..],
+        [
+            TypeError::ArgCountMismatch { .. },
+            TypeError::InvalidOperands { .. }
+        ]
+    ));
+}
+
+#[test]
+fn mismatched_arguments_point_at_the_argument() {
+    let typed = typecheck(&format!("{ADD}fn main() -> u64:\n    add(1, true)\n"));
+    let TypeError::ArgTypeMismatch {
+        expected, found, ..
+    } = &typed.errors[0]
+    else {
+        panic!("expected an argument mismatch: {:#?}", typed.errors);
+    };
+    assert_eq!((expected, found), (&Ty::U64, &Ty::Bool));
+    let diagnostic = typed.diagnostic();
+    assert_eq!(diagnostic.message, "mismatched types");
+    let labels = labels_in(&typed.sm, &diagnostic);
+    assert_eq!(
+        labels[0],
+        ("true", "expected `u64`, found `bool`".to_string())
+    );
+    assert_eq!(labels[1].1, "parameter declared here");
+    assert!(labels[1].0.starts_with('b'), "{labels:?}");
+}
+
+#[test]
+fn every_mismatched_argument_is_reported() {
+    let typed = typecheck(&format!("{ADD}fn main() -> u64:\n    add(false, true)\n"));
+    let spans: Vec<_> = typed
+        .errors
+        .iter()
+        .map(|error| match error {
+            TypeError::ArgTypeMismatch { span, .. } => typed.text(*span),
+            error => panic!("expected an argument mismatch: {error:?}"),
+        })
+        .collect();
+    assert_eq!(spans, ["false", "true"]);
+}
+
+#[test]
+fn arguments_of_the_wrong_count_and_type_report_both() {
+    let typed = typecheck(&format!("{ADD}fn main() -> u64:\n    add(true)\n"));
+    assert!(matches!(
+        typed.errors[..],
+        [
+            TypeError::ArgCountMismatch { .. },
+            TypeError::ArgTypeMismatch { .. }
+        ]
+    ));
+}
+
+#[test]
+fn calling_a_value_that_is_not_a_function() {
+    let typed = typecheck("fn main() -> u64:\n    let x = 1\n    x(2)\n");
+    let diagnostic = typed.diagnostic();
+    assert_eq!(diagnostic.message, "expected function, found `u64`");
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [("x", "call expression requires function".to_string())]
+    );
+}
+
+#[test]
+fn calls_through_function_values_check_their_arguments() {
+    let typed = typecheck(&format!(
+        "{ADD}fn main() -> u64:\n    let f = add\n    f(1)\n"
+    ));
+    assert!(matches!(
+        typed.errors[..],
+        [TypeError::ArgCountMismatch {
+            expected: 2,
+            found: 1,
+            ..
+        }]
+    ));
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{diagnostics, typecheck};
+use ginto_diag::{DiagnosticConvertible, Span};
+use ginto_test_support::labels_in;
+use ginto_typeck::{Ty, TypeError};
+
+/// The type of the trailing expression of `main`, whose body is `body`.
+fn type_of(body: &str) -> Ty {
+    let source = format!("fn main():\n    {body}\n");
+    let typed = typecheck(&source);
+    assert!(typed.errors.is_empty(), "{:#?}", typed.errors);
+    let start = source.find(body).unwrap();
+    let span = Span::from_range(start..start + body.len());
+    let file = typed.tree.module(typed.tree.root()).program.file_id;
+    typed.types.expr_ty(file, span).unwrap().clone()
+}
+
+#[test]
+fn casts_have_the_type_they_cast_to() {
+    assert_eq!(type_of("1 as i64"), Ty::I64);
+    assert_eq!(type_of("-1 as u64"), Ty::U64);
+    assert_eq!(type_of("true as u64"), Ty::U64);
+    assert_eq!(type_of("2 as f64"), Ty::F64);
+    assert_eq!(type_of("2.5 as i64"), Ty::I64);
+    assert_eq!(type_of("1 as u64"), Ty::U64);
+}
+
+#[test]
+fn casts_bind_tighter_than_arithmetic() {
+    let typed = typecheck("fn f(a: u64, b: i64) -> i64:\n    a as i64 + b\n");
+    assert!(typed.errors.is_empty(), "{:#?}", typed.errors);
+    let typed = typecheck("fn f(a: u64, b: i64) -> i64:\n    b + a as i64 * 2\n");
+    assert!(typed.errors.is_empty(), "{:#?}", typed.errors);
+}
+
+#[test]
+fn casts_chain() {
+    assert_eq!(type_of("true as u64 as i64"), Ty::I64);
+}
+
+#[test]
+fn only_numbers_and_bools_cast() {
+    let typed = typecheck("fn main():\n    let b = 1 as bool\n    ()\n");
+    let TypeError::InvalidCast { from, to, span, .. } = &typed.errors[0] else {
+        panic!("expected an invalid cast: {:#?}", typed.errors);
+    };
+    assert_eq!((from, to), (&Ty::U64, &Ty::Bool));
+    assert_eq!(typed.text(*span), "1 as bool");
+    let diagnostic = typed.diagnostic();
+    assert_eq!(diagnostic.message, "cannot cast `u64` as `bool`");
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("compare with zero instead: `<> 0`")
+    );
+
+    let diagnostic = &diagnostics("fn main():\n    let u = () as u64\n    ()\n")[0];
+    assert_eq!(diagnostic.message, "cannot cast `()` as `u64`");
+    assert_eq!(diagnostic.help, None);
+    assert_eq!(
+        diagnostic.notes,
+        ["`as` only converts between number types and from `bool` to integers"]
+    );
+}
+
+#[test]
+fn unsigned_and_signed_numbers_never_mix() {
+    let typed = typecheck("fn f(a: u64, b: i64) -> i64:\n    a + b\n");
+    let TypeError::MismatchedNumbers { lhs, rhs, .. } = &typed.errors[0] else {
+        panic!("expected mismatched numbers: {:#?}", typed.errors);
+    };
+    assert_eq!((&lhs.inner, &rhs.inner), (&Ty::U64, &Ty::I64));
+    let diagnostic = typed.errors[0].clone().into_diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "mismatched numeric types: cannot apply `+` to `u64` and `i64`"
+    );
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [
+            ("b", "expected `u64`, found `i64`".to_string()),
+            ("a", "this is of type `u64`".to_string()),
+        ]
+    );
+    assert_eq!(diagnostic.notes, ["numbers are never converted implicitly"]);
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("convert the right operand with `as u64`, or the left one with `as i64`")
+    );
+}
+
+#[test]
+fn comparisons_do_not_mix_numbers_either() {
+    let typed = typecheck("fn f(a: u64, b: i64) -> bool:\n    a < b\n");
+    assert!(matches!(
+        typed.errors[..],
+        [TypeError::MismatchedNumbers { .. }]
+    ));
+}
+
+#[test]
+fn literals_take_the_type_of_the_other_operand() {
+    let typed = typecheck("fn f(b: i64) -> i64:\n    b + 1\n");
+    assert!(typed.errors.is_empty(), "{:#?}", typed.errors);
+    let typed = typecheck("fn f(b: i64) -> i64:\n    1 - b\n");
+    assert!(typed.errors.is_empty(), "{:#?}", typed.errors);
+}
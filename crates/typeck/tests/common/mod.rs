@@ -0,0 +1,70 @@
+//! What the tests of type checking share: checking the types of programs
+//! whose names resolve.
+
+// each test uses some of the helpers
+#![allow(dead_code)]
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible, SourceManager, Span};
+use ginto_resolve::{ModuleTree, Resolutions, Resolver, load_modules_with};
+use ginto_test_support::{reader, text_in};
+use ginto_typeck::{TypeChecker, TypeError, TypeckResults};
+
+pub struct Typed {
+    pub sm: SourceManager,
+    pub tree: ModuleTree,
+    pub resolutions: Resolutions,
+    pub types: TypeckResults,
+    pub errors: Vec<TypeError>,
+}
+
+/// The program whose root file `main.ginto` holds `source`, with its types
+/// checked. Panics if it doesn't load or its names don't resolve.
+pub fn typecheck(source: &str) -> Typed {
+    typecheck_files(source, &[])
+}
+
+/// The program whose root file `main.ginto` holds `root`, with the modules
+/// of `files`, named after their path, and its types checked.
+pub fn typecheck_files(root: &str, files: &[(&str, &str)]) -> Typed {
+    let mut sm = SourceManager::new();
+    let root = sm.add_file("main.ginto".to_string(), root.to_string());
+    let (tree, load_errors) = load_modules_with(&mut sm, root, reader(files));
+    assert!(load_errors.is_empty(), "{load_errors:#?}");
+    let mut resolver = Resolver::new();
+    let resolutions = resolver.resolve_modules(&tree);
+    assert!(resolver.errors().is_empty(), "{:#?}", resolver.errors());
+    let mut checker = TypeChecker::new(&resolutions);
+    let types = checker.check_modules(&tree);
+    let errors = checker.errors().to_vec();
+    drop(checker);
+    Typed {
+        sm,
+        tree,
+        resolutions,
+        types,
+        errors,
+    }
+}
+
+/// The diagnostics of the type errors of `source`.
+pub fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    typecheck(source)
+        .errors
+        .into_iter()
+        .map(DiagnosticConvertible::into_diagnostic)
+        .collect()
+}
+
+impl Typed {
+    /// The text at `span` of the file of the root module.
+    pub fn text(&self, span: Span) -> &str {
+        let file = self.tree.module(self.tree.root()).program.file_id;
+        text_in(&self.sm, file, span)
+    }
+
+    /// The diagnostic of the only type error of the program.
+    pub fn diagnostic(&self) -> Diagnostic {
+        assert_eq!(self.errors.len(), 1, "{:#?}", self.errors);
+        self.errors[0].clone().into_diagnostic()
+    }
+}
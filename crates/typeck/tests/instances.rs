@@ -0,0 +1,114 @@
+mod common;
+
+use common::{Typed, typecheck};
+use ginto_diag::DiagnosticConvertible;
+use ginto_typeck::{INSTANTIATION_DEPTH_LIMIT, Instance, MonoError, Ty, collect_instances};
+
+/// The instances of the program needed from `main`, as written.
+fn instances(source: &str) -> (Typed, Vec<Instance>, Vec<MonoError>) {
+    let typed = typecheck(source);
+    assert!(typed.errors.is_empty(), "{:#?}", typed.errors);
+    let main = typed
+        .resolutions
+        .defs()
+        .find(|(_, def)| def.name == "main")
+        .map(|(id, _)| id)
+        .unwrap();
+    let (instances, errors) =
+        collect_instances(&typed.tree, &typed.resolutions, &typed.types, main);
+    (typed, instances, errors)
+}
+
+fn names(source: &str) -> Vec<String> {
+    let (typed, instances, errors) = instances(source);
+    assert!(errors.is_empty(), "{errors:#?}");
+    instances
+        .iter()
+        .map(|instance| instance.display(&typed.resolutions))
+        .collect()
+}
+
+const ID: &str = "fn id<T>(x: T) -> T:\n    x\n\n";
+
+#[test]
+fn functions_reachable_from_main_have_one_instance() {
+    let source = "fn main() -> u64:\n    helper() + helper()\n\nfn helper() -> u64:\n    1\n\nfn unused() -> u64:\n    2\n";
+    assert_eq!(names(source), ["main", "helper"]);
+}
+
+#[test]
+fn generic_functions_have_an_instance_per_type_arguments() {
+    let source = format!("{ID}fn main() -> u64:\n    let b = id(true)\n    id(1) + id(2)\n");
+    assert_eq!(names(&source), ["main", "id::<bool>", "id::<u64>"]);
+}
+
+#[test]
+fn type_arguments_pass_through_generic_callers() {
+    let source = format!(
+        "{ID}fn twice<T>(x: T) -> T:\n    id(id(x))\n\nfn main() -> i64:\n    let s = twice(\"a\")\n    twice(-1)\n"
+    );
+    assert_eq!(
+        names(&source),
+        [
+            "main",
+            "twice::<str>",
+            "twice::<i64>",
+            "id::<str>",
+            "id::<i64>"
+        ]
+    );
+}
+
+#[test]
+fn functions_passed_as_values_are_instances_too() {
+    let source = "fn apply(f: fn(u64) -> u64) -> u64:\n    f(1)\n\nfn inc(n: u64) -> u64:\n    n + 1\n\nfn main() -> u64:\n    apply(inc)\n";
+    assert_eq!(names(source), ["main", "apply", "inc"]);
+}
+
+#[test]
+fn instances_have_concrete_signatures() {
+    let source = format!("{ID}fn main() -> u64:\n    id(1)\n");
+    let (typed, instances, _) = instances(&source);
+    let sig = instances[1].sig(&typed.types).unwrap();
+    assert!(sig.generics.is_empty());
+    assert_eq!(sig.params, [Ty::U64]);
+    assert_eq!(*sig.ret, Ty::U64);
+}
+
+#[test]
+fn instantiations_that_never_end_stop_at_the_limit() {
+    // each call wraps `x` in another closure, so `T` grows without end
+    let source = "fn nest<T>(x: T, n: u64) -> u64:\n    if n = 0: 0 else: nest(|| x, n - 1)\n\nfn main() -> u64:\n    nest(1, 3)\n";
+    let (_, instances, errors) = instances(source);
+    assert_eq!(instances.len(), INSTANTIATION_DEPTH_LIMIT + 1);
+    let [MonoError::RecursionLimit { instance, .. }] = &errors[..] else {
+        panic!("expected the recursion limit: {errors:#?}");
+    };
+    assert!(
+        instance.starts_with("nest::<fn() -> fn() -> "),
+        "{instance}"
+    );
+
+    let diagnostic = errors[0].clone().into_diagnostic();
+    assert!(
+        diagnostic
+            .message
+            .starts_with("reached the recursion limit while instantiating `nest::<")
+    );
+    assert_eq!(
+        diagnostic.labels[0].message.as_deref(),
+        Some("instantiated here")
+    );
+    assert_eq!(
+        diagnostic.notes,
+        [format!(
+            "generic calls can only be nested {INSTANTIATION_DEPTH_LIMIT} instantiations deep"
+        )]
+    );
+}
+
+#[test]
+fn recursion_with_the_same_type_arguments_ends() {
+    let source = "fn count<T>(x: T, n: u64) -> u64:\n    if n = 0: 0 else: count(x, n - 1)\n\nfn main() -> u64:\n    count(true, 3)\n";
+    assert_eq!(names(source), ["main", "count::<bool>"]);
+}
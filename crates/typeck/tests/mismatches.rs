@@ -0,0 +1,129 @@
+mod common;
+
+use common::typecheck;
+use ginto_test_support::labels_in;
+use ginto_typeck::{BranchKind, Ty, TypeError};
+
+#[test]
+fn assignments_match_the_variable() {
+    let typed = typecheck("fn main() -> u64:\n    let mut x = 1\n    x = true\n    x\n");
+    let [
+        TypeError::AssignTypeMismatch {
+            expected, found, ..
+        },
+    ] = &typed.errors[..]
+    else {
+        panic!("expected a mismatched assignment: {:#?}", typed.errors);
+    };
+    assert_eq!((expected, found), (&Ty::U64, &Ty::Bool));
+    let diagnostic = typed.diagnostic();
+    assert_eq!(diagnostic.message, "mismatched types");
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [
+            ("true", "expected `u64`, found `bool`".to_string()),
+            ("x", "this variable is of type `u64`".to_string()),
+        ]
+    );
+    // the label is on the definition, not the assignment
+    assert!(diagnostic.labels[1].span.start < diagnostic.labels[0].span.start);
+}
+
+#[test]
+fn assignments_decide_the_type_of_literals() {
+    let sources = [
+        "fn main() -> i64:\n    let mut x: i64 = 1\n    x = -2\n    x\n",
+        "fn main() -> u64:\n    let x: u64\n    x = 1\n    x\n",
+        "fn f(a: i64) -> i64:\n    a = 3\n    a\n",
+    ];
+    for source in sources {
+        let typed = typecheck(source);
+        assert!(typed.errors.is_empty(), "{source}: {:#?}", typed.errors);
+    }
+}
+
+#[test]
+fn if_conditions_are_bools() {
+    let typed = typecheck("fn main() -> u64:\n    let n = 0\n    if n: 1 else: 2\n");
+    let [TypeError::IfConditionNotBool { found, span, .. }] = &typed.errors[..] else {
+        panic!("expected a non-bool condition: {:#?}", typed.errors);
+    };
+    assert_eq!((found, typed.text(*span)), (&Ty::U64, "n"));
+    let diagnostic = typed.diagnostic();
+    assert_eq!(diagnostic.message, "`if` condition must be a `bool`");
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [("n", "expected `bool`, found `u64`".to_string())]
+    );
+}
+
+#[test]
+fn if_and_else_have_the_same_type() {
+    let typed = typecheck("fn main(c: bool):\n    let x = if c: 1 else: true\n");
+    let [
+        TypeError::BranchTypeMismatch {
+            kind: BranchKind::If,
+            ..
+        },
+    ] = &typed.errors[..]
+    else {
+        panic!("expected mismatched branches: {:#?}", typed.errors);
+    };
+    let diagnostic = typed.diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "`if` and `else` have incompatible types"
+    );
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [
+            ("true", "expected `u64`, found `bool`".to_string()),
+            ("1", "expected `u64` because of this branch".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn match_arms_have_the_same_type() {
+    let source = "fn main(n: u64):\n    match n:\n        0 -> ()\n        1 -> return\n        _ -> \"many\"\n";
+    let typed = typecheck(source);
+    let diagnostic = typed.diagnostic();
+    assert_eq!(diagnostic.message, "`match` arms have incompatible types");
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [
+            ("\"many\"", "expected `()`, found `str`".to_string()),
+            ("()", "expected `()` because of this arm".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn branches_of_the_wrong_type_for_their_context() {
+    let typed = typecheck("fn main(c: bool):\n    let x: u64 = if c: 1 else: true\n");
+    assert!(
+        matches!(
+            &typed.errors[..],
+            [TypeError::LetTypeMismatch {
+                found: Ty::Bool,
+                ..
+            }]
+        ),
+        "{:#?}",
+        typed.errors
+    );
+}
+
+#[test]
+fn integer_literal_branches_take_the_type_of_the_others() {
+    let sources = [
+        "fn f(c: bool, a: i64):\n    let x = if c: 1 else: a\n",
+        "fn f(c: bool, a: i64):\n    let x = if c: a else: -1\n",
+        "fn f(n: u64, a: i64):\n    let x = match n:\n        0 -> 1\n        1 -> a\n        _ -> return\n",
+        "fn f(c: bool) -> i64:\n    if c: 1 else: 2\n",
+    ];
+    for source in sources {
+        let typed = typecheck(source);
+        assert!(typed.errors.is_empty(), "{source}: {:#?}", typed.errors);
+    }
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::typecheck;
+use ginto_test_support::labels_in;
+use ginto_typeck::{Ty, TypeError};
+
+#[test]
+fn every_path_yields_a_value() {
+    let sources = [
+        "fn f(c: bool) -> u64:\n    if c: 1 else: 2\n",
+        "fn f(c: bool) -> u64:\n    if c:\n        return 1\n    2\n",
+        "fn f(n: u64) -> u64:\n    match n:\n        0 -> 1\n        _ -> n\n",
+        "fn f(c: bool) -> u64:\n    if c:\n        return 1\n    else:\n        return 2\n",
+        "fn f(c: bool) -> u64:\n    if c: 1 else if not c: 2 else: 3\n",
+        // a path that never ends yields no value
+        "fn f() -> u64:\n    while true:\n        ()\n    return 1\n",
+    ];
+    for source in sources {
+        let typed = typecheck(source);
+        assert!(typed.errors.is_empty(), "{source}: {:#?}", typed.errors);
+    }
+}
+
+#[test]
+fn if_without_else_in_return_position() {
+    let source = "fn f(c: bool) -> u64:\n    if c: 1\n";
+    let typed = typecheck(source);
+    let [TypeError::MissingElse { expected, .. }] = &typed.errors[..] else {
+        panic!("expected a missing `else`: {:#?}", typed.errors);
+    };
+    assert_eq!(expected.inner, Ty::U64);
+    let diagnostic = typed.diagnostic();
+    assert_eq!(diagnostic.message, "`if` may be missing an `else` clause");
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [
+            ("if c: 1", "expected `u64`, found `()`".to_string()),
+            ("1", "only this branch produces a value".to_string()),
+            ("u64", "expected `u64` because of return type".to_string()),
+        ]
+    );
+    assert_eq!(
+        diagnostic.notes,
+        ["`if` expressions without `else` evaluate to `()`"]
+    );
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("consider adding an `else` branch that evaluates to `u64`")
+    );
+}
+
+#[test]
+fn nested_branches_are_checked_too() {
+    let typed = typecheck(
+        "fn f(a: bool, b: bool) -> u64:\n    if a:\n        if b: 1\n    else:\n        2\n",
+    );
+    assert!(matches!(
+        &typed.errors[..],
+        [TypeError::MissingElse { span, .. }] if typed.text(*span) == "if b: 1"
+    ));
+    let typed = typecheck(
+        "fn f(n: u64, c: bool) -> u64:\n    match n:\n        0 -> 1\n        _ -> if c: 2\n",
+    );
+    assert!(matches!(&typed.errors[..], [TypeError::MissingElse { .. }]));
+}
+
+#[test]
+fn if_without_else_is_fine_in_functions_without_a_value() {
+    let typed = typecheck("fn f(c: bool):\n    if c: ()\n");
+    assert!(typed.errors.is_empty(), "{:#?}", typed.errors);
+    let typed = typecheck("fn f(c: bool) -> ():\n    if c: ()\n");
+    assert!(typed.errors.is_empty(), "{:#?}", typed.errors);
+}
+
+#[test]
+fn bodies_ending_in_a_statement_yield_nothing() {
+    let source = "fn f() -> u64:\n    let x = 1\n";
+    let typed = typecheck(source);
+    let diagnostic = typed.diagnostic();
+    assert_eq!(diagnostic.message, "mismatched types");
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [
+            ("let x = 1", "expected `u64`, found `()`".to_string()),
+            ("u64", "expected `u64` because of return type".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn branches_of_the_wrong_type_point_at_the_branch() {
+    let typed = typecheck("fn f(c: bool) -> u64:\n    if c: 1 else: true\n");
+    let [TypeError::ReturnMismatch { found, span, .. }] = &typed.errors[..] else {
+        panic!("expected a mismatched return: {:#?}", typed.errors);
+    };
+    assert_eq!(found, &Ty::Bool);
+    assert_eq!(typed.text(*span), "true");
+}
+
+#[test]
+fn returned_values_match_the_return_type() {
+    let typed = typecheck("fn f(c: bool) -> u64:\n    if c:\n        return false\n    1\n");
+    let [TypeError::ReturnMismatch { found, span, .. }] = &typed.errors[..] else {
+        panic!("expected a mismatched return: {:#?}", typed.errors);
+    };
+    assert_eq!(found, &Ty::Bool);
+    assert_eq!(typed.text(*span), "false");
+}
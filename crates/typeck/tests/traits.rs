@@ -0,0 +1,197 @@
+mod common;
+
+use common::typecheck;
+use ginto_diag::Span;
+use ginto_test_support::labels_in;
+use ginto_typeck::{Ty, TypeError};
+
+const SHAPE: &str = "\
+trait Shape:
+    fn area(self) -> u64
+    fn scale(self, by: u64) -> u64
+
+";
+
+const IMPL_U64: &str = "\
+impl Shape for u64:
+    fn area(self) -> u64:
+        self * self
+    fn scale(self, by: u64) -> u64:
+        self * by
+
+";
+
+#[test]
+fn methods_resolve_through_the_impl_for_the_receiver() {
+    let source = format!(
+        "{SHAPE}{IMPL_U64}fn main() -> u64:\n    let side: u64 = 3\n    side.area() + side.scale(2)\n"
+    );
+    let typed = typecheck(&source);
+    assert!(typed.errors.is_empty(), "{:#?}", typed.errors);
+    let file = typed.tree.module(typed.tree.root()).program.file_id;
+    let call = source.rfind("area").unwrap();
+    let target = typed
+        .types
+        .method_target(file, Span::from_range(call..call + 4))
+        .unwrap();
+    // the method of the `impl`, not the declaration in the trait
+    let def = typed.resolutions.def(target);
+    assert_eq!(
+        def.span.start.0,
+        source.find("fn area(self) -> u64:").unwrap() + 3
+    );
+    let call_ty = typed
+        .types
+        .expr_ty(file, Span::from_range(call - 5..call + 6));
+    assert_eq!(call_ty, Some(&Ty::U64));
+}
+
+#[test]
+fn method_arguments_are_checked_against_the_signature() {
+    let source = format!(
+        "{SHAPE}{IMPL_U64}fn main() -> u64:\n    let side: u64 = 3\n    side.scale(true)\n"
+    );
+    let typed = typecheck(&source);
+    assert!(matches!(
+        &typed.errors[..],
+        [TypeError::ArgTypeMismatch {
+            found: Ty::Bool,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn methods_not_implemented_for_the_receiver_name_the_traits_defining_them() {
+    let source =
+        format!("{SHAPE}{IMPL_U64}fn main() -> u64:\n    let yes = true\n    yes.area()\n");
+    let typed = typecheck(&source);
+    let diagnostic = typed.diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "no method named `area` found for type `bool` in the current scope"
+    );
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [("area", "method not found in `bool`".to_string())]
+    );
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some(
+            "the following trait defines a method `area`, but it is not implemented for `bool`: `Shape`"
+        )
+    );
+}
+
+#[test]
+fn methods_no_trait_defines() {
+    let typed = typecheck("fn main() -> u64:\n    let n: u64 = 1\n    n.area()\n");
+    let diagnostic = typed.diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "no method named `area` found for type `u64` in the current scope"
+    );
+    assert_eq!(diagnostic.help, None);
+}
+
+#[test]
+fn methods_of_several_implemented_traits_are_ambiguous() {
+    let source = "trait A:\n    fn get(self) -> u64\n\ntrait B:\n    fn get(self) -> u64\n\nimpl A for u64:\n    fn get(self) -> u64:\n        1\n\nimpl B for u64:\n    fn get(self) -> u64:\n        2\n\nfn main() -> u64:\n    let n: u64 = 0\n    n.get()\n";
+    let typed = typecheck(source);
+    let [TypeError::AmbiguousMethod { traits, .. }] = &typed.errors[..] else {
+        panic!("expected an ambiguous method: {:#?}", typed.errors);
+    };
+    assert_eq!(traits, &["A", "B"]);
+    assert_eq!(
+        typed.diagnostic().message,
+        "multiple applicable methods named `get` found"
+    );
+}
+
+#[test]
+fn impls_implement_every_method_of_their_trait() {
+    let source = format!("{SHAPE}impl Shape for u64:\n    fn area(self) -> u64:\n        self\n");
+    let typed = typecheck(&source);
+    let diagnostic = typed.diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "not all trait methods implemented, missing: `scale`"
+    );
+    let labels = labels_in(&typed.sm, &diagnostic);
+    assert_eq!(labels[0].1, "missing `scale` in implementation");
+    assert_eq!(labels[1], ("scale", "`scale` from trait".to_string()));
+}
+
+#[test]
+fn impls_implement_only_methods_of_their_trait() {
+    let source = format!(
+        "{SHAPE}{}\n    fn perimeter(self) -> u64:\n        4\n",
+        IMPL_U64.trim_end()
+    );
+    let typed = typecheck(&format!("{source}\n"));
+    let diagnostic = typed.diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "method `perimeter` is not a member of trait `Shape`"
+    );
+    assert_eq!(
+        labels_in(&typed.sm, &diagnostic),
+        [("perimeter", "not a member of trait `Shape`".to_string())]
+    );
+}
+
+#[test]
+fn impl_methods_match_the_signature_in_the_trait() {
+    let source = format!(
+        "{SHAPE}impl Shape for u64:\n    fn area(self) -> bool:\n        true\n    fn scale(self, by: u64) -> u64:\n        by\n"
+    );
+    let typed = typecheck(&source);
+    let [
+        TypeError::MethodSigMismatch {
+            expected, found, ..
+        },
+    ] = &typed.errors[..]
+    else {
+        panic!("expected a mismatched signature: {:#?}", typed.errors);
+    };
+    assert_eq!(
+        (expected.as_str(), found.as_str()),
+        ("fn(self) -> u64", "fn(self) -> bool")
+    );
+    let diagnostic = typed.diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "method `area` has an incompatible type for trait `Shape`"
+    );
+    assert_eq!(
+        diagnostic.labels[1].message.as_deref(),
+        Some("type in trait declared here")
+    );
+}
+
+#[test]
+fn a_trait_is_implemented_once_per_type() {
+    let source = format!("{SHAPE}{IMPL_U64}{IMPL_U64}");
+    let typed = typecheck(&source);
+    let diagnostic = typed.diagnostic();
+    assert_eq!(
+        diagnostic.message,
+        "conflicting implementations of trait `Shape` for type `u64`"
+    );
+    let labels = labels_in(&typed.sm, &diagnostic);
+    assert_eq!(labels[0].1, "conflicting implementation for `u64`");
+    assert_eq!(labels[1].1, "first implementation here");
+    assert!(diagnostic.labels[1].span.start < diagnostic.labels[0].span.start);
+
+    // but once for each type
+    let source = format!(
+        "{SHAPE}{IMPL_U64}impl Shape for bool:\n    fn area(self) -> u64:\n        1\n    fn scale(self, by: u64) -> u64:\n        by\n"
+    );
+    let typed = typecheck(&source);
+    assert!(
+        !typed
+            .errors
+            .iter()
+            .any(|error| matches!(error, TypeError::ConflictingImpls { .. }))
+    );
+}
@@ -0,0 +1,648 @@
+use std::collections::HashMap;
+
+use ginto_codegen::IntLiteral;
+use ginto_diag::{FileId, Span, Spanned, Symbol, sym};
+use ginto_eval::{RuntimeErrorKind, Value};
+use ginto_resolve::{DefId, DefKind, ModuleTree, Resolutions};
+use ginto_syntax::{
+    BinOp, Expr, ExprKind, Func, ItemKind, MatchArm, Param, Pattern, PatternKind, Program, StrPart,
+    Type,
+};
+use ginto_typeck::{Ty, TypeckResults};
+use tracing::{debug, info_span};
+
+use crate::{BuiltinRef, Bytecode, Function, Op, Statement};
+
+/// Compiles type-checked programs to bytecode.
+///
+/// Every local variable gets a slot in its function's frame, and integer
+/// literals take the type the type checker inferred for them, as in the
+/// interpreter.
+pub struct Compiler<'a> {
+    resolutions: &'a Resolutions,
+    types: &'a TypeckResults,
+    bytecode: Bytecode,
+    file_id: FileId,
+    code: Vec<Op>,
+    spans: Vec<Span>,
+    statements: Vec<Statement>,
+    /// The slot of each local of the function being compiled.
+    slots: HashMap<DefId, u32>,
+}
+
+impl<'a> Compiler<'a> {
+    pub fn new(resolutions: &'a Resolutions, types: &'a TypeckResults) -> Self {
+        Self {
+            resolutions,
+            types,
+            bytecode: Bytecode::default(),
+            file_id: FileId(0),
+            code: Vec::new(),
+            spans: Vec::new(),
+            statements: Vec::new(),
+            slots: HashMap::new(),
+        }
+    }
+
+    pub fn compile_program(mut self, program: &Program) -> Bytecode {
+        self.declare(program);
+        self.compile_funcs(program);
+        self.bytecode.root = Some(program.file_id);
+        self.bytecode.main = self.find_main(program);
+        self.bytecode
+    }
+
+    /// Compiles every module of `tree`; `main` is looked up in the root.
+    pub fn compile_modules(mut self, tree: &ModuleTree) -> Bytecode {
+        let _span = info_span!("codegen").entered();
+        for (_, module) in tree.modules() {
+            self.declare(&module.program);
+        }
+        for (_, module) in tree.modules() {
+            self.compile_funcs(&module.program);
+        }
+        let root = &tree.module(tree.root()).program;
+        self.bytecode.root = Some(root.file_id);
+        self.bytecode.main = self.find_main(root);
+        debug!(instructions = self.bytecode.instructions(), "compiled");
+        self.bytecode
+    }
+
+    /// Numbers the functions of `program`, so calls can refer to functions
+    /// compiled later.
+    fn declare(&mut self, program: &Program) {
+        for func in program.funcs() {
+            if let Some(def) = self.resolutions.def_at(program.file_id, func.name.span) {
+                let id = self.bytecode.funcs.len() as u32;
+                self.bytecode.func_ids.insert(def, id);
+                self.bytecode.funcs.push(Function {
+                    name: func.name.inner.to_string(),
+                    def,
+                    arity: func.params.len() as u32,
+                    captures: 0,
+                    locals: 0,
+                    local_names: Vec::new(),
+                    code: Vec::new(),
+                    spans: Vec::new(),
+                    statements: Vec::new(),
+                    file_id: program.file_id,
+                });
+            }
+        }
+    }
+
+    /// The index in `builtins` of `def`, if it is a builtin, adding it on
+    /// its first use.
+    fn builtin_id(&mut self, def: DefId) -> Option<u32> {
+        let info = self.resolutions.def(def);
+        if info.kind != DefKind::Builtin {
+            return None;
+        }
+        if let Some(&id) = self.bytecode.builtin_ids.get(&def) {
+            return Some(id);
+        }
+        let arity = match self.types.def_ty(def) {
+            Some(Ty::Fn(sig)) => sig.params.len() as u32,
+            _ => unreachable!("builtin `{}` has no signature", info.name),
+        };
+        let id = self.bytecode.builtins.len() as u32;
+        self.bytecode.builtins.push(BuiltinRef {
+            name: info.name.to_string(),
+            def,
+            arity,
+        });
+        self.bytecode.builtin_ids.insert(def, id);
+        Some(id)
+    }
+
+    fn find_main(&self, program: &Program) -> Option<u32> {
+        program.items.iter().find_map(|item| match &item.inner {
+            ItemKind::Func(func) if func.name.inner == sym::MAIN => {
+                let def = self.resolutions.def_at(program.file_id, func.name.span)?;
+                self.bytecode.func_ids.get(&def).copied()
+            }
+            _ => None,
+        })
+    }
+
+    fn compile_funcs(&mut self, program: &Program) {
+        self.file_id = program.file_id;
+        for func in program.funcs() {
+            self.compile_func(func);
+        }
+    }
+
+    fn compile_func(&mut self, func: &Func) {
+        let Some(def) = self.resolutions.def_at(self.file_id, func.name.span) else {
+            return;
+        };
+        for param in &func.params {
+            let name_span = match &param.inner {
+                Param::SelfParam => param.span,
+                Param::Named { name, .. } => name.span,
+            };
+            self.declare_local(name_span);
+        }
+        self.compile_tail(&func.body);
+
+        let id = self.bytecode.func_ids[&def] as usize;
+        let local_names = self.local_names();
+        let compiled = &mut self.bytecode.funcs[id];
+        compiled.locals = self.slots.len() as u32;
+        compiled.local_names = local_names;
+        compiled.code = std::mem::take(&mut self.code);
+        compiled.spans = std::mem::take(&mut self.spans);
+        compiled.statements = std::mem::take(&mut self.statements);
+        self.slots.clear();
+    }
+
+    /// Compiles the closure defined at `span` to a function of its own,
+    /// whose first slots hold its captures, and code building the closure
+    /// from their current values.
+    fn compile_closure(&mut self, span: Span, params: &[Spanned<Param>], body: &Expr) {
+        let def = self
+            .resolutions
+            .def_at(self.file_id, span)
+            .expect("closure was not resolved");
+        let captures = self.resolutions.captures(def);
+        for capture in captures {
+            let slot = self.slots[capture];
+            self.emit(Op::Load(slot), span);
+        }
+
+        let outer_code = std::mem::take(&mut self.code);
+        let outer_spans = std::mem::take(&mut self.spans);
+        let outer_statements = std::mem::take(&mut self.statements);
+        let outer_slots = std::mem::take(&mut self.slots);
+        for (slot, &capture) in captures.iter().enumerate() {
+            self.slots.insert(capture, slot as u32);
+        }
+        for param in params {
+            if let Param::Named { name, .. } = &param.inner {
+                self.declare_local(name.span);
+            }
+        }
+        self.compile_tail(body);
+        let id = self.bytecode.funcs.len() as u32;
+        self.bytecode.func_ids.insert(def, id);
+        self.bytecode.funcs.push(Function {
+            name: "{closure}".to_string(),
+            def,
+            arity: params.len() as u32,
+            captures: captures.len() as u32,
+            locals: self.slots.len() as u32,
+            local_names: self.local_names(),
+            code: std::mem::replace(&mut self.code, outer_code),
+            spans: std::mem::replace(&mut self.spans, outer_spans),
+            statements: std::mem::replace(&mut self.statements, outer_statements),
+            file_id: self.file_id,
+        });
+        self.slots = outer_slots;
+
+        self.emit(Op::MakeClosure(id), span);
+    }
+
+    /// Gives the local declared at `span` a slot, the same one for every
+    /// alternative of an or-pattern binding it.
+    fn declare_local(&mut self, span: Span) -> u32 {
+        let def = self
+            .resolutions
+            .def_at(self.file_id, span)
+            .expect("local was not resolved");
+        let slot = self.slots.len() as u32;
+        *self.slots.entry(def).or_insert(slot)
+    }
+
+    /// The name of the local in each slot of the function being compiled.
+    fn local_names(&self) -> Vec<String> {
+        let mut names = vec![String::new(); self.slots.len()];
+        for (&def, &slot) in &self.slots {
+            names[slot as usize] = self.resolutions.def(def).name.to_string();
+        }
+        names
+    }
+
+    /// Records that the code of `stmt` starts at the next instruction.
+    fn start_statement(&mut self, stmt: &Expr) {
+        self.statements.push(Statement {
+            at: self.code.len() as u32,
+            span: stmt.span,
+            locals: self.slots.len() as u32,
+        });
+    }
+
+    fn emit(&mut self, op: Op, span: Span) -> usize {
+        self.code.push(op);
+        self.spans.push(span);
+        self.code.len() - 1
+    }
+
+    fn emit_const(&mut self, value: Value, span: Span) {
+        let index = match self
+            .bytecode
+            .constants
+            .iter()
+            .position(|c| c.is_identical(&value))
+        {
+            Some(index) => index,
+            None => {
+                self.bytecode.constants.push(value);
+                self.bytecode.constants.len() - 1
+            }
+        };
+        self.emit(Op::Const(index as u32), span);
+    }
+
+    /// Points the jump at `at` to the next instruction.
+    fn patch(&mut self, at: usize) {
+        let target = self.code.len() as u32;
+        match &mut self.code[at] {
+            Op::Jump(t) | Op::JumpIfFalse(t) | Op::JumpIfTrue(t) => *t = target,
+            op => unreachable!("`{}` is not a jump", op),
+        }
+    }
+
+    /// Compiles `expr` to code leaving its value on the stack.
+    fn compile_expr(&mut self, expr: &Expr) {
+        let span = expr.span;
+        match &expr.inner {
+            ExprKind::Int(v) => {
+                let ty = self.types.expr_ty(self.file_id, span);
+                self.compile_int(IntLiteral::new(*v as i128, ty), span)
+            }
+            ExprKind::Float(v) => self.emit_const(Value::F64(*v), span),
+            ExprKind::Bool(v) => self.emit_const(Value::Bool(*v), span),
+            ExprKind::Unit => self.emit_const(Value::Unit, span),
+            ExprKind::Error => unreachable!("program with syntax errors was compiled"),
+            ExprKind::Str(parts) => match parts.as_slice() {
+                [] => self.emit_const(Value::from(""), span),
+                [StrPart::Lit(text)] => self.emit_const(Value::from(text.as_str()), span),
+                _ => {
+                    for part in parts {
+                        match part {
+                            StrPart::Lit(text) => self.emit_const(Value::from(text.as_str()), span),
+                            StrPart::Expr(expr) => self.compile_expr(expr),
+                        }
+                    }
+                    self.emit(Op::Interpolate(parts.len() as u32), span);
+                }
+            },
+            ExprKind::Unary { op, expr: operand } => {
+                if let Some(lit) = IntLiteral::of(expr, self.types, self.file_id) {
+                    return self.compile_int(lit, span);
+                }
+                self.compile_expr(operand);
+                self.emit(Op::Unary(op.inner), span);
+            }
+            ExprKind::Binary { op, lhs, rhs } => match op.inner {
+                BinOp::And | BinOp::Or => {
+                    self.compile_expr(lhs);
+                    self.emit(Op::Dup, span);
+                    let jump = match op.inner {
+                        BinOp::And => Op::JumpIfFalse(0),
+                        _ => Op::JumpIfTrue(0),
+                    };
+                    let short_circuit = self.emit(jump, span);
+                    self.emit(Op::Pop, span);
+                    self.compile_expr(rhs);
+                    self.patch(short_circuit);
+                }
+                op => {
+                    self.compile_expr(lhs);
+                    self.compile_expr(rhs);
+                    self.emit(Op::Binary(op), span);
+                }
+            },
+            ExprKind::Cast { expr: operand, ty } => {
+                self.compile_expr(operand);
+                match ty.inner {
+                    Type::U64 => {
+                        self.emit(Op::CastU64, span);
+                    }
+                    Type::I64 => {
+                        self.emit(Op::CastI64, span);
+                    }
+                    Type::F64 => {
+                        self.emit(Op::CastF64, span);
+                    }
+                    _ => {}
+                }
+            }
+            ExprKind::Let { .. } | ExprKind::Assign { .. } => {
+                self.compile_stmt(expr);
+                self.emit_const(Value::Unit, span);
+            }
+            ExprKind::Block { exprs, tail } => {
+                for expr in exprs {
+                    self.start_statement(expr);
+                    self.compile_stmt(expr);
+                }
+                match tail {
+                    Some(tail) => {
+                        self.start_statement(tail);
+                        self.compile_expr(tail)
+                    }
+                    None => self.emit_const(Value::Unit, span),
+                }
+            }
+            ExprKind::Call { callee, args } => self.compile_call(callee, args, span, false),
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => self.compile_method_call(receiver, method, args, span, false),
+            ExprKind::Closure { params, body } => self.compile_closure(span, params, body),
+            ExprKind::Match { scrutinee, arms } => self.compile_match(scrutinee, arms, false),
+            ExprKind::Return(value) => match value {
+                Some(value) => self.compile_tail(value),
+                None => {
+                    self.emit_const(Value::Unit, span);
+                    self.emit(Op::Return, span);
+                }
+            },
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                self.compile_expr(cond);
+                let to_else = self.emit(Op::JumpIfFalse(0), span);
+                self.compile_expr(then_branch);
+                let to_end = self.emit(Op::Jump(0), span);
+                self.patch(to_else);
+                match else_branch {
+                    Some(else_branch) => self.compile_expr(else_branch),
+                    None => self.emit_const(Value::Unit, span),
+                }
+                self.patch(to_end);
+            }
+            ExprKind::While { cond, body } => {
+                let start = self.code.len() as u32;
+                self.compile_expr(cond);
+                let to_end = self.emit(Op::JumpIfFalse(0), span);
+                self.compile_stmt(body);
+                self.emit(Op::Jump(start), span);
+                self.patch(to_end);
+                self.emit_const(Value::Unit, span);
+            }
+            ExprKind::Var(name) => {
+                let def = self
+                    .resolutions
+                    .resolve(self.file_id, span)
+                    .expect("variable was not resolved");
+                match self.slots.get(&def).copied() {
+                    Some(slot) => {
+                        self.emit(Op::Load(slot), span);
+                    }
+                    None if self.bytecode.func_ids.contains_key(&def)
+                        || self.builtin_id(def).is_some() =>
+                    {
+                        self.emit_const(Value::Fn(def), span)
+                    }
+                    None => unreachable!("`{}` is neither a local nor a function", name),
+                }
+            }
+            // paths only name functions of other modules
+            ExprKind::Path(_) => {
+                let def = self
+                    .resolutions
+                    .resolve(self.file_id, span)
+                    .expect("path was not resolved");
+                self.emit_const(Value::Fn(def), span)
+            }
+        }
+    }
+
+    /// Compiles `expr`, whose value the function returns, to code returning
+    /// it. Calls in tail position reuse the frame of the function, so tail
+    /// recursion runs in constant stack space.
+    fn compile_tail(&mut self, expr: &Expr) {
+        let span = expr.span;
+        match &expr.inner {
+            ExprKind::Block {
+                exprs,
+                tail: Some(tail),
+            } => {
+                for expr in exprs {
+                    self.start_statement(expr);
+                    self.compile_stmt(expr);
+                }
+                self.start_statement(tail);
+                self.compile_tail(tail);
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch: Some(else_branch),
+            } => {
+                self.compile_expr(cond);
+                let to_else = self.emit(Op::JumpIfFalse(0), span);
+                self.compile_tail(then_branch);
+                self.patch(to_else);
+                self.compile_tail(else_branch);
+            }
+            ExprKind::Match { scrutinee, arms } => self.compile_match(scrutinee, arms, true),
+            ExprKind::Call { callee, args } => self.compile_call(callee, args, span, true),
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => self.compile_method_call(receiver, method, args, span, true),
+            ExprKind::Return(_) => self.compile_expr(expr),
+            _ => {
+                self.compile_expr(expr);
+                self.emit(Op::Return, span);
+            }
+        }
+    }
+
+    /// Compiles a call, which returns from the function if it is a `tail`
+    /// call.
+    fn compile_call(&mut self, callee: &Expr, args: &[Expr], span: Span, tail: bool) {
+        let def = match &callee.inner {
+            ExprKind::Var(_) | ExprKind::Path(_) => {
+                self.resolutions.resolve(self.file_id, callee.span)
+            }
+            _ => None,
+        };
+        let direct = def.and_then(|def| match self.bytecode.func_ids.get(&def) {
+            Some(&id) if tail => Some(Op::TailCall(id)),
+            Some(&id) => Some(Op::Call(id)),
+            None => self.builtin_id(def).map(Op::CallBuiltin),
+        });
+        match direct {
+            // calling a function by name, the common case, needs no
+            // function value
+            Some(call) => {
+                for arg in args {
+                    self.compile_expr(arg);
+                }
+                self.emit(call, span);
+                // builtins have no frame to reuse
+                if tail && matches!(call, Op::CallBuiltin(_)) {
+                    self.emit(Op::Return, span);
+                }
+            }
+            _ => {
+                self.compile_expr(callee);
+                for arg in args {
+                    self.compile_expr(arg);
+                }
+                let argc = args.len() as u32;
+                if tail {
+                    self.emit(Op::TailCallValue(argc), span);
+                } else {
+                    self.emit(Op::CallValue(argc), span);
+                }
+            }
+        }
+    }
+
+    fn compile_method_call(
+        &mut self,
+        receiver: &Expr,
+        method: &Spanned<Symbol>,
+        args: &[Expr],
+        span: Span,
+        tail: bool,
+    ) {
+        let def = self
+            .types
+            .method_target(self.file_id, method.span)
+            .expect("method call was not resolved");
+        self.compile_expr(receiver);
+        for arg in args {
+            self.compile_expr(arg);
+        }
+        let id = self.bytecode.func_ids[&def];
+        if tail {
+            self.emit(Op::TailCall(id), span);
+        } else {
+            self.emit(Op::Call(id), span);
+        }
+    }
+
+    /// Compiles `expr` to code leaving nothing on the stack.
+    fn compile_stmt(&mut self, expr: &Expr) {
+        match &expr.inner {
+            ExprKind::Let { name, value, .. } => {
+                let slot = self.declare_local(name.span);
+                if let Some(value) = value {
+                    self.compile_expr(value);
+                    self.emit(Op::Store(slot), expr.span);
+                }
+            }
+            ExprKind::Assign { name, value } => {
+                self.compile_expr(value);
+                let slot = self
+                    .resolutions
+                    .resolve(self.file_id, name.span)
+                    .and_then(|def| self.slots.get(&def).copied())
+                    .expect("assigned variable has no slot");
+                self.emit(Op::Store(slot), expr.span);
+            }
+            _ => {
+                self.compile_expr(expr);
+                self.emit(Op::Pop, expr.span);
+            }
+        }
+    }
+
+    /// Pushes the integer literal `lit`, or fails if it is out of range.
+    fn compile_int(&mut self, lit: IntLiteral, span: Span) {
+        match lit.bits() {
+            Some(bits) if lit.signed => self.emit_const(Value::I64(bits), span),
+            Some(bits) => self.emit_const(Value::U64(bits as u64), span),
+            None => {
+                self.bytecode.errors.push(RuntimeErrorKind::Overflow {
+                    expr: lit.value.to_string(),
+                    ty: lit.ty_name(),
+                    span,
+                    file_id: self.file_id,
+                });
+                let error = self.bytecode.errors.len() as u32 - 1;
+                self.emit(Op::Fail(error), span);
+            }
+        }
+    }
+
+    fn int_ty(&self, span: Span) -> Type {
+        match self.types.expr_ty(self.file_id, span) {
+            Some(Ty::I64) => Type::I64,
+            _ => Type::U64,
+        }
+    }
+
+    /// Compiles a `match`: each arm tests the scrutinee, kept on the stack,
+    /// and falls through to the next arm if it does not match. The arms of
+    /// a `tail` match return from the function.
+    fn compile_match(&mut self, scrutinee: &Expr, arms: &[MatchArm], tail: bool) {
+        self.compile_expr(scrutinee);
+        let ty = self.int_ty(scrutinee.span);
+        let mut to_end = Vec::new();
+        for arm in arms {
+            let mut to_next = Vec::new();
+            self.compile_pattern(&arm.pattern, &ty, &mut to_next);
+            self.emit(Op::Pop, arm.pattern.span);
+            if tail {
+                self.compile_tail(&arm.body);
+            } else {
+                self.compile_expr(&arm.body);
+                to_end.push(self.emit(Op::Jump(0), arm.body.span));
+            }
+            for jump in to_next {
+                self.patch(jump);
+            }
+        }
+        self.emit(Op::Unreachable, scrutinee.span);
+        for jump in to_end {
+            self.patch(jump);
+        }
+    }
+
+    /// Compiles a test of the value on top of the stack against `pattern`,
+    /// binding its names if it matches and jumping by one of `to_fail`
+    /// otherwise. The value stays on the stack either way.
+    fn compile_pattern(&mut self, pattern: &Pattern, ty: &Type, to_fail: &mut Vec<usize>) {
+        let span = pattern.span;
+        match &pattern.inner {
+            PatternKind::Wildcard | PatternKind::Unit => {}
+            PatternKind::Bind(_) => {
+                let slot = self.declare_local(span);
+                self.emit(Op::Dup, span);
+                self.emit(Op::Store(slot), span);
+            }
+            PatternKind::Int(n) => match Value::int(*n, ty) {
+                Some(value) => self.compile_test(value, span, to_fail),
+                // a literal the scrutinee's type cannot hold never matches
+                None => to_fail.push(self.emit(Op::Jump(0), span)),
+            },
+            PatternKind::Bool(b) => self.compile_test(Value::Bool(*b), span, to_fail),
+            PatternKind::Or(alternatives) => {
+                let Some((last, rest)) = alternatives.split_last() else {
+                    return;
+                };
+                let mut to_matched = Vec::new();
+                for alternative in rest {
+                    let mut to_next = Vec::new();
+                    self.compile_pattern(alternative, ty, &mut to_next);
+                    to_matched.push(self.emit(Op::Jump(0), span));
+                    for jump in to_next {
+                        self.patch(jump);
+                    }
+                }
+                self.compile_pattern(last, ty, to_fail);
+                for jump in to_matched {
+                    self.patch(jump);
+                }
+            }
+        }
+    }
+
+    fn compile_test(&mut self, value: Value, span: Span, to_fail: &mut Vec<usize>) {
+        self.emit(Op::Dup, span);
+        self.emit_const(value, span);
+        self.emit(Op::Binary(BinOp::Equal), span);
+        to_fail.push(self.emit(Op::JumpIfFalse(0), span));
+    }
+}
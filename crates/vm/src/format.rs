@@ -0,0 +1,704 @@
+use std::collections::HashMap;
+
+use ginto_diag::{
+    BytePos, Diagnostic, DiagnosticConvertible, FileId, Severity, SourceManager, Span,
+};
+use ginto_eval::{RuntimeErrorKind, Value};
+use ginto_resolve::DefId;
+use ginto_syntax::{BinOp, UnaryOp};
+
+use crate::{BuiltinRef, Bytecode, Function, Op, Statement};
+
+/// The bytes a compiled program file starts with.
+pub const BYTECODE_MAGIC: &[u8; 4] = b"GBC\0";
+
+/// The version of the format `Bytecode::save` writes, raised whenever the
+/// format changes.
+pub const BYTECODE_VERSION: u16 = 1;
+
+/// Why `Bytecode::load` rejected a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytecodeError {
+    /// The file does not start with `BYTECODE_MAGIC`.
+    NotBytecode,
+    UnsupportedVersion {
+        found: u16,
+    },
+    /// The file ends in the middle of the program.
+    Truncated,
+    /// The file is well formed but describes a program the VM cannot run,
+    /// like one calling a function it does not have.
+    Invalid {
+        reason: String,
+    },
+}
+
+impl DiagnosticConvertible for BytecodeError {
+    fn into_diagnostic(self) -> Diagnostic {
+        let diagnostic = Diagnostic::new(Severity::Error);
+        match self {
+            BytecodeError::NotBytecode => diagnostic
+                .with_message("not a compiled ginto program")
+                .with_note(format!(
+                    "compiled programs start with `{}`",
+                    BYTECODE_MAGIC.escape_ascii()
+                )),
+            BytecodeError::UnsupportedVersion { found } => diagnostic
+                .with_message(format!(
+                    "compiled program has format version {}, expected {}",
+                    found, BYTECODE_VERSION
+                ))
+                .with_note("compile the program again with this version of ginto"),
+            BytecodeError::Truncated => diagnostic.with_message("compiled program is truncated"),
+            BytecodeError::Invalid { reason } => {
+                diagnostic.with_message(format!("compiled program is invalid: {}", reason))
+            }
+        }
+    }
+}
+
+impl Bytecode {
+    /// Writes the program in the versioned format `load` reads, with the
+    /// files of `sm` so errors can still be shown with their source.
+    ///
+    /// The layout is the magic and version, then the files, constants,
+    /// errors, builtins and functions, each a count followed by its items,
+    /// and last the definition tables and entry point. Each function holds
+    /// its code with the span of every instruction, its line table.
+    pub fn save(&self, sm: &SourceManager) -> Vec<u8> {
+        let mut w = Writer::default();
+        w.bytes.extend_from_slice(BYTECODE_MAGIC);
+        w.bytes.extend_from_slice(&BYTECODE_VERSION.to_le_bytes());
+
+        let files: Vec<_> = (0..).map_while(|id| sm.get_file(FileId(id))).collect();
+        w.len(files.len());
+        for file in files {
+            w.str(&file.name);
+            w.str(&file.source);
+        }
+
+        w.len(self.constants.len());
+        for constant in &self.constants {
+            match constant {
+                Value::Unit => w.u8(0),
+                Value::Bool(b) => {
+                    w.u8(1);
+                    w.u8(*b as u8);
+                }
+                Value::U64(v) => {
+                    w.u8(2);
+                    w.u64(*v);
+                }
+                Value::I64(v) => {
+                    w.u8(3);
+                    w.u64(*v as u64);
+                }
+                Value::Str(s) => {
+                    w.u8(4);
+                    w.str(s);
+                }
+                Value::Fn(def) => {
+                    w.u8(5);
+                    w.u64(def.0 as u64);
+                }
+                Value::F64(v) => {
+                    w.u8(6);
+                    w.u64(v.to_bits());
+                }
+                Value::Closure { .. } => unreachable!("closures are never constants"),
+            }
+        }
+
+        w.len(self.errors.len());
+        for error in &self.errors {
+            match error {
+                RuntimeErrorKind::Overflow {
+                    expr,
+                    ty,
+                    span,
+                    file_id,
+                } => {
+                    w.u8(0);
+                    w.str(expr);
+                    w.u8((*ty == "i64") as u8);
+                    w.span(*span);
+                    w.u64(file_id.0 as u64);
+                }
+                RuntimeErrorKind::DivisionByZero {
+                    expr,
+                    span,
+                    file_id,
+                } => {
+                    w.u8(1);
+                    w.str(expr);
+                    w.span(*span);
+                    w.u64(file_id.0 as u64);
+                }
+                _ => unreachable!("only errors known when compiling are stored"),
+            }
+        }
+
+        w.len(self.builtins.len());
+        for builtin in &self.builtins {
+            w.str(&builtin.name);
+            w.u64(builtin.def.0 as u64);
+            w.u32(builtin.arity);
+        }
+
+        w.len(self.funcs.len());
+        for func in &self.funcs {
+            w.str(&func.name);
+            w.u64(func.def.0 as u64);
+            w.u32(func.arity);
+            w.u32(func.captures);
+            w.u32(func.locals);
+            w.len(func.local_names.len());
+            for name in &func.local_names {
+                w.str(name);
+            }
+            w.u64(func.file_id.0 as u64);
+            w.len(func.code.len());
+            for (op, span) in func.code.iter().zip(&func.spans) {
+                w.op(*op);
+                w.span(*span);
+            }
+            w.len(func.statements.len());
+            for statement in &func.statements {
+                w.u32(statement.at);
+                w.span(statement.span);
+                w.u32(statement.locals);
+            }
+        }
+
+        // sorted so the same program is always saved to the same bytes
+        for ids in [&self.func_ids, &self.builtin_ids] {
+            let mut ids: Vec<_> = ids.iter().collect();
+            ids.sort();
+            w.len(ids.len());
+            for (def, id) in ids {
+                w.u64(def.0 as u64);
+                w.u32(*id);
+            }
+        }
+        w.option(self.main.map(u64::from));
+        w.option(self.root.map(|file_id| file_id.0 as u64));
+        w.bytes
+    }
+
+    /// Reads a program written by `save`, with the files it was compiled
+    /// from, checking that the VM can run it.
+    pub fn load(bytes: &[u8]) -> Result<(Bytecode, SourceManager), BytecodeError> {
+        if !bytes.starts_with(BYTECODE_MAGIC) {
+            return Err(BytecodeError::NotBytecode);
+        }
+        let mut r = Reader {
+            bytes,
+            pos: BYTECODE_MAGIC.len(),
+        };
+        let version = u16::from_le_bytes([r.u8()?, r.u8()?]);
+        if version != BYTECODE_VERSION {
+            return Err(BytecodeError::UnsupportedVersion { found: version });
+        }
+
+        let mut sm = SourceManager::new();
+        for _ in 0..r.len()? {
+            let name = r.string()?;
+            let source = r.string()?;
+            sm.add_file(name, source);
+        }
+
+        let mut bytecode = Bytecode::default();
+        for _ in 0..r.len()? {
+            let constant = match r.u8()? {
+                0 => Value::Unit,
+                1 => Value::Bool(r.u8()? != 0),
+                2 => Value::U64(r.u64()?),
+                3 => Value::I64(r.u64()? as i64),
+                4 => Value::Str(r.string()?.into()),
+                5 => Value::Fn(r.def()?),
+                6 => Value::F64(f64::from_bits(r.u64()?)),
+                tag => return Err(invalid(format!("unknown constant kind {}", tag))),
+            };
+            bytecode.constants.push(constant);
+        }
+
+        for _ in 0..r.len()? {
+            let error = match r.u8()? {
+                0 => RuntimeErrorKind::Overflow {
+                    expr: r.string()?,
+                    ty: if r.u8()? != 0 { "i64" } else { "u64" },
+                    span: r.span()?,
+                    file_id: r.file_id()?,
+                },
+                1 => RuntimeErrorKind::DivisionByZero {
+                    expr: r.string()?,
+                    span: r.span()?,
+                    file_id: r.file_id()?,
+                },
+                tag => return Err(invalid(format!("unknown error kind {}", tag))),
+            };
+            bytecode.errors.push(error);
+        }
+
+        for _ in 0..r.len()? {
+            bytecode.builtins.push(BuiltinRef {
+                name: r.string()?,
+                def: r.def()?,
+                arity: r.u32()?,
+            });
+        }
+
+        for _ in 0..r.len()? {
+            let name = r.string()?;
+            let def = r.def()?;
+            let arity = r.u32()?;
+            let captures = r.u32()?;
+            let locals = r.u32()?;
+            let local_names = (0..r.len()?)
+                .map(|_| r.string())
+                .collect::<Result<_, _>>()?;
+            let file_id = r.file_id()?;
+            let mut code = Vec::new();
+            let mut spans = Vec::new();
+            for _ in 0..r.len()? {
+                code.push(r.op()?);
+                spans.push(r.span()?);
+            }
+            let statements = (0..r.len()?)
+                .map(|_| {
+                    Ok(Statement {
+                        at: r.u32()?,
+                        span: r.span()?,
+                        locals: r.u32()?,
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            bytecode.funcs.push(Function {
+                name,
+                def,
+                arity,
+                captures,
+                locals,
+                local_names,
+                code,
+                spans,
+                statements,
+                file_id,
+            });
+        }
+
+        bytecode.func_ids = r.ids()?;
+        bytecode.builtin_ids = r.ids()?;
+        bytecode.main = r.option()?.map(|main| main as u32);
+        bytecode.root = r.option()?.map(|root| FileId(root as usize));
+        if r.pos != bytes.len() {
+            return Err(invalid("unexpected bytes after the program"));
+        }
+        validate(&bytecode, &sm)?;
+        Ok((bytecode, sm))
+    }
+}
+
+/// Checks every index in `bytecode` refers to something it has, every span
+/// to text of its file and every function to values it has pushed, so the
+/// VM cannot go out of bounds running it.
+fn validate(bytecode: &Bytecode, sm: &SourceManager) -> Result<(), BytecodeError> {
+    let check = |ok: bool, reason: &dyn Fn() -> String| match ok {
+        true => Ok(()),
+        false => Err(invalid(reason())),
+    };
+    let file = |file_id: FileId| {
+        check(sm.get_file(file_id).is_some(), &|| {
+            format!("file {} does not exist", file_id.0)
+        })
+    };
+    let span = |span: Span, file_id: FileId| {
+        file(file_id)?;
+        let source = &sm.get_file(file_id).expect("file exists").source;
+        check(
+            span.end.0 <= source.len()
+                && source.is_char_boundary(span.start.0)
+                && source.is_char_boundary(span.end.0),
+            &|| {
+                format!(
+                    "span {}..{} is not in file {}",
+                    span.start.0, span.end.0, file_id.0
+                )
+            },
+        )
+    };
+    let funcs = bytecode.funcs.len();
+    for (def, &id) in &bytecode.func_ids {
+        check((id as usize) < funcs, &|| {
+            format!(
+                "definition {} is function {}, which does not exist",
+                def.0, id
+            )
+        })?;
+    }
+    for (def, &id) in &bytecode.builtin_ids {
+        check((id as usize) < bytecode.builtins.len(), &|| {
+            format!(
+                "definition {} is builtin {}, which does not exist",
+                def.0, id
+            )
+        })?;
+    }
+    if let Some(main) = bytecode.main {
+        check((main as usize) < funcs, &|| {
+            format!("main function {} does not exist", main)
+        })?;
+        let main = &bytecode.funcs[main as usize];
+        check(main.arity == 0 && main.captures == 0, &|| {
+            format!("main function `{}` takes arguments", main.name)
+        })?;
+    }
+    if let Some(root) = bytecode.root {
+        file(root)?;
+    }
+    for constant in &bytecode.constants {
+        if let Value::Fn(def) = constant {
+            check(
+                bytecode.func_ids.contains_key(def) || bytecode.builtin_ids.contains_key(def),
+                &|| {
+                    format!(
+                        "constant refers to definition {}, which is not compiled",
+                        def.0
+                    )
+                },
+            )?;
+        }
+    }
+    for error in &bytecode.errors {
+        if let RuntimeErrorKind::Overflow {
+            span: at, file_id, ..
+        }
+        | RuntimeErrorKind::DivisionByZero {
+            span: at, file_id, ..
+        } = error
+        {
+            span(*at, *file_id)?;
+        }
+    }
+
+    for func in &bytecode.funcs {
+        file(func.file_id)?;
+        check(
+            func.captures + func.arity <= func.locals
+                && func.local_names.len() == func.locals as usize,
+            &|| format!("function `{}` has inconsistent locals", func.name),
+        )?;
+        check(bytecode.func_ids.contains_key(&func.def), &|| {
+            format!("function `{}` has no definition", func.name)
+        })?;
+        let len = func.code.len();
+        for (ip, op) in func.code.iter().enumerate() {
+            let (index, bound, what) = match *op {
+                Op::Const(i) => (i, bytecode.constants.len(), "constant"),
+                Op::Load(i) | Op::Store(i) => (i, func.locals as usize, "local"),
+                Op::Jump(target) | Op::JumpIfFalse(target) | Op::JumpIfTrue(target) => {
+                    (target, len, "instruction")
+                }
+                Op::Call(i) | Op::TailCall(i) | Op::MakeClosure(i) => (i, funcs, "function"),
+                Op::CallBuiltin(i) => (i, bytecode.builtins.len(), "builtin"),
+                Op::Fail(i) => (i, bytecode.errors.len(), "error"),
+                _ => continue,
+            };
+            check((index as usize) < bound, &|| {
+                format!(
+                    "instruction {} of `{}` refers to {} {}, which does not exist",
+                    ip, func.name, what, index
+                )
+            })?;
+        }
+        for statement in &func.statements {
+            check(
+                statement.at as usize <= len && statement.locals <= func.locals,
+                &|| format!("function `{}` has an invalid statement", func.name),
+            )?;
+            span(statement.span, func.file_id)?;
+        }
+        for &at in &func.spans {
+            span(at, func.file_id)?;
+        }
+        check_stack(bytecode, func)?;
+    }
+    Ok(())
+}
+
+/// Checks that every instruction of `func` the VM can reach pops only values
+/// the function pushed, with as many on the stack whichever way it is
+/// reached, and that no instruction runs past the end of the code. Indices
+/// must already be checked.
+fn check_stack(bytecode: &Bytecode, func: &Function) -> Result<(), BytecodeError> {
+    let frame = |i: u32| {
+        let callee = &bytecode.funcs[i as usize];
+        callee.captures + callee.arity
+    };
+    let mut heights = vec![None; func.code.len()];
+    let mut pending = vec![(0, 0u64)];
+    while let Some((ip, height)) = pending.pop() {
+        let Some(&op) = func.code.get(ip) else {
+            return Err(invalid(format!(
+                "function `{}` runs past its end",
+                func.name
+            )));
+        };
+        match heights[ip] {
+            Some(seen) if seen == height => continue,
+            Some(_) => {
+                return Err(invalid(format!(
+                    "instruction {} of `{}` is reached with different stack heights",
+                    ip, func.name
+                )));
+            }
+            None => heights[ip] = Some(height),
+        }
+        // the values the instruction pops and pushes, and whether the next
+        // one runs after it
+        let (pops, pushes, next): (u32, u64, bool) = match op {
+            Op::Const(_) | Op::Load(_) => (0, 1, true),
+            Op::Pop | Op::Store(_) => (1, 0, true),
+            Op::Dup => (1, 2, true),
+            Op::Unary(_) | Op::CastU64 | Op::CastI64 | Op::CastF64 => (1, 1, true),
+            Op::Binary(_) => (2, 1, true),
+            Op::Interpolate(n) => (n, 1, true),
+            Op::Jump(_) => (0, 0, false),
+            Op::JumpIfFalse(_) | Op::JumpIfTrue(_) => (1, 0, true),
+            Op::Call(i) => (frame(i), 1, true),
+            Op::CallBuiltin(i) => (bytecode.builtins[i as usize].arity, 1, true),
+            Op::CallValue(n) => (n.saturating_add(1), 1, true),
+            Op::TailCall(i) => (frame(i), 0, false),
+            Op::TailCallValue(n) => (n.saturating_add(1), 0, false),
+            Op::MakeClosure(i) => (bytecode.funcs[i as usize].captures, 1, true),
+            Op::Return => (1, 0, false),
+            Op::Fail(_) | Op::Unreachable => (0, 0, false),
+        };
+        let Some(height) = height.checked_sub(pops.into()) else {
+            return Err(invalid(format!(
+                "instruction {} of `{}` pops {} values but only {} were pushed",
+                ip, func.name, pops, height
+            )));
+        };
+        let height = height + pushes;
+        if next {
+            pending.push((ip + 1, height));
+        }
+        if let Op::Jump(target) | Op::JumpIfFalse(target) | Op::JumpIfTrue(target) = op {
+            pending.push((target as usize, height));
+        }
+    }
+    Ok(())
+}
+
+fn invalid(reason: impl Into<String>) -> BytecodeError {
+    BytecodeError::Invalid {
+        reason: reason.into(),
+    }
+}
+
+const BIN_OPS: [BinOp; 13] = [
+    BinOp::Add,
+    BinOp::Sub,
+    BinOp::Mul,
+    BinOp::Div,
+    BinOp::Mod,
+    BinOp::Equal,
+    BinOp::NotEq,
+    BinOp::Less,
+    BinOp::Le,
+    BinOp::Greater,
+    BinOp::Ge,
+    BinOp::Or,
+    BinOp::And,
+];
+
+const UNARY_OPS: [UnaryOp; 2] = [UnaryOp::Neg, UnaryOp::Not];
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, v: u8) {
+        self.bytes.push(v);
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.u32(len as u32);
+    }
+
+    fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.bytes.extend_from_slice(s.as_bytes());
+    }
+
+    fn span(&mut self, span: Span) {
+        self.u64(span.start.0 as u64);
+        self.u64(span.end.0 as u64);
+    }
+
+    fn option(&mut self, v: Option<u64>) {
+        match v {
+            Some(v) => {
+                self.u8(1);
+                self.u64(v);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn op(&mut self, op: Op) {
+        let (tag, operand) = match op {
+            Op::Const(i) => (0, i),
+            Op::Pop => (1, 0),
+            Op::Dup => (2, 0),
+            Op::Load(i) => (3, i),
+            Op::Store(i) => (4, i),
+            Op::Unary(op) => (5, UNARY_OPS.iter().position(|o| *o == op).unwrap() as u32),
+            Op::Binary(op) => (6, BIN_OPS.iter().position(|o| *o == op).unwrap() as u32),
+            Op::Interpolate(n) => (7, n),
+            Op::CastU64 => (8, 0),
+            Op::CastI64 => (9, 0),
+            Op::Jump(target) => (10, target),
+            Op::JumpIfFalse(target) => (11, target),
+            Op::JumpIfTrue(target) => (12, target),
+            Op::Call(i) => (13, i),
+            Op::CallBuiltin(i) => (14, i),
+            Op::CallValue(n) => (15, n),
+            Op::TailCall(i) => (16, i),
+            Op::TailCallValue(n) => (17, n),
+            Op::MakeClosure(i) => (18, i),
+            Op::Return => (19, 0),
+            Op::Fail(i) => (20, i),
+            Op::Unreachable => (21, 0),
+            Op::CastF64 => (22, 0),
+        };
+        self.u8(tag);
+        self.u32(operand);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], BytecodeError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + N)
+            .ok_or(BytecodeError::Truncated)?;
+        self.pos += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, BytecodeError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, BytecodeError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, BytecodeError> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+
+    fn len(&mut self) -> Result<usize, BytecodeError> {
+        Ok(self.u32()? as usize)
+    }
+
+    fn string(&mut self) -> Result<String, BytecodeError> {
+        let len = self.len()?;
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or(BytecodeError::Truncated)?;
+        self.pos += len;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("a string is not UTF-8"))
+    }
+
+    fn span(&mut self) -> Result<Span, BytecodeError> {
+        let start = self.u64()? as usize;
+        let end = self.u64()? as usize;
+        if start > end {
+            return Err(invalid(format!(
+                "span {}..{} ends before it starts",
+                start, end
+            )));
+        }
+        Ok(Span::new(BytePos(start), BytePos(end)))
+    }
+
+    fn def(&mut self) -> Result<DefId, BytecodeError> {
+        Ok(DefId(self.u64()? as usize))
+    }
+
+    fn file_id(&mut self) -> Result<FileId, BytecodeError> {
+        Ok(FileId(self.u64()? as usize))
+    }
+
+    fn option(&mut self) -> Result<Option<u64>, BytecodeError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.u64()?)),
+            tag => Err(invalid(format!("unknown option tag {}", tag))),
+        }
+    }
+
+    fn ids(&mut self) -> Result<HashMap<DefId, u32>, BytecodeError> {
+        (0..self.len()?)
+            .map(|_| Ok((self.def()?, self.u32()?)))
+            .collect()
+    }
+
+    fn op(&mut self) -> Result<Op, BytecodeError> {
+        let tag = self.u8()?;
+        let i = self.u32()?;
+        Ok(match tag {
+            0 => Op::Const(i),
+            1 => Op::Pop,
+            2 => Op::Dup,
+            3 => Op::Load(i),
+            4 => Op::Store(i),
+            5 => Op::Unary(
+                *UNARY_OPS
+                    .get(i as usize)
+                    .ok_or_else(|| invalid(format!("unknown unary operator {}", i)))?,
+            ),
+            6 => Op::Binary(
+                *BIN_OPS
+                    .get(i as usize)
+                    .ok_or_else(|| invalid(format!("unknown binary operator {}", i)))?,
+            ),
+            7 => Op::Interpolate(i),
+            8 => Op::CastU64,
+            9 => Op::CastI64,
+            10 => Op::Jump(i),
+            11 => Op::JumpIfFalse(i),
+            12 => Op::JumpIfTrue(i),
+            13 => Op::Call(i),
+            14 => Op::CallBuiltin(i),
+            15 => Op::CallValue(i),
+            16 => Op::TailCall(i),
+            17 => Op::TailCallValue(i),
+            18 => Op::MakeClosure(i),
+            19 => Op::Return,
+            20 => Op::Fail(i),
+            21 => Op::Unreachable,
+            22 => Op::CastF64,
+            tag => return Err(invalid(format!("unknown instruction {}", tag))),
+        })
+    }
+}
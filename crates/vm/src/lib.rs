@@ -0,0 +1,13 @@
+mod compiler;
+mod format;
+mod op;
+mod optimize;
+mod profile;
+mod vm;
+
+pub use compiler::*;
+pub use format::*;
+pub use op::*;
+pub use optimize::*;
+pub use profile::*;
+pub use vm::*;
@@ -0,0 +1,182 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use ginto_diag::{FileId, Span};
+use ginto_eval::{RuntimeErrorKind, Value};
+use ginto_resolve::DefId;
+use ginto_syntax::{BinOp, UnaryOp};
+
+/// An instruction of the stack machine. Operands are popped from and results
+/// pushed to the value stack; jump targets are instruction indices in the
+/// same function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// Pushes `constants[i]`.
+    Const(u32),
+    Pop,
+    Dup,
+    /// Pushes the local in slot `i` of the current frame.
+    Load(u32),
+    /// Pops a value into the local in slot `i`.
+    Store(u32),
+    Unary(UnaryOp),
+    Binary(BinOp),
+    /// Pops `n` values and pushes the string of them written one after
+    /// another, as in an interpolated string literal.
+    Interpolate(u32),
+    CastU64,
+    CastI64,
+    CastF64,
+    Jump(u32),
+    JumpIfFalse(u32),
+    JumpIfTrue(u32),
+    /// Calls `funcs[i]` with as many arguments as it takes, on top of the
+    /// stack.
+    Call(u32),
+    /// Calls `builtins[i]` with as many arguments as it takes, on top of
+    /// the stack.
+    CallBuiltin(u32),
+    /// Calls the function value below the `n` arguments on top of the stack.
+    CallValue(u32),
+    /// Returns by calling `funcs[i]` in the current frame, in place of the
+    /// running function, with the arguments on top of the stack.
+    TailCall(u32),
+    /// Returns by calling the function value below the `n` arguments on
+    /// top of the stack in the current frame.
+    TailCallValue(u32),
+    /// Pops the captures of the closure `funcs[i]` and pushes the closure
+    /// holding them.
+    MakeClosure(u32),
+    Return,
+    /// Stops with `errors[i]`, an error known when compiling, like an
+    /// integer literal out of range.
+    Fail(u32),
+    /// Code that a checked program cannot reach, like the end of an
+    /// exhaustive `match`.
+    Unreachable,
+}
+
+/// A compiled function.
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub def: DefId,
+    pub arity: u32,
+    /// The number of captured values a closure gets in the slots before
+    /// its parameters; zero for functions.
+    pub captures: u32,
+    /// The number of local slots, the captures and parameters included.
+    pub locals: u32,
+    /// The name of the local in each slot.
+    pub local_names: Vec<String>,
+    pub code: Vec<Op>,
+    /// The span of the expression each instruction was compiled from.
+    pub spans: Vec<Span>,
+    /// The statements of the function, in the order of their code.
+    pub statements: Vec<Statement>,
+    pub file_id: FileId,
+}
+
+/// Where the code of a statement starts, for a debugger to stop at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Statement {
+    /// The index of the statement's first instruction. Nested statements
+    /// may start at the same one.
+    pub at: u32,
+    pub span: Span,
+    /// How many slots hold locals declared before the statement.
+    pub locals: u32,
+}
+
+/// A builtin function called by compiled code, run by the host.
+#[derive(Debug, Clone)]
+pub struct BuiltinRef {
+    pub name: String,
+    pub def: DefId,
+    pub arity: u32,
+}
+
+/// The compiled form of a program.
+#[derive(Debug, Clone, Default)]
+pub struct Bytecode {
+    pub funcs: Vec<Function>,
+    pub constants: Vec<Value>,
+    pub errors: Vec<RuntimeErrorKind>,
+    /// The index in `funcs` of each function definition.
+    pub func_ids: HashMap<DefId, u32>,
+    pub builtins: Vec<BuiltinRef>,
+    /// The index in `builtins` of each builtin definition.
+    pub builtin_ids: HashMap<DefId, u32>,
+    /// The `main` function of the root file, if it has one.
+    pub main: Option<u32>,
+    pub root: Option<FileId>,
+}
+
+impl Bytecode {
+    /// How many instructions the functions have in all.
+    pub fn instructions(&self) -> usize {
+        self.funcs.iter().map(|func| func.code.len()).sum()
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Op::Const(i) => write!(f, "const {}", i),
+            Op::Pop => write!(f, "pop"),
+            Op::Dup => write!(f, "dup"),
+            Op::Load(i) => write!(f, "load {}", i),
+            Op::Store(i) => write!(f, "store {}", i),
+            Op::Unary(op) => write!(f, "unary {}", op.to_string().trim()),
+            Op::Binary(op) => write!(f, "binary {}", op),
+            Op::Interpolate(n) => write!(f, "interpolate {}", n),
+            Op::CastU64 => write!(f, "cast u64"),
+            Op::CastI64 => write!(f, "cast i64"),
+            Op::CastF64 => write!(f, "cast f64"),
+            Op::Jump(target) => write!(f, "jump {}", target),
+            Op::JumpIfFalse(target) => write!(f, "jump_if_false {}", target),
+            Op::JumpIfTrue(target) => write!(f, "jump_if_true {}", target),
+            Op::Call(i) => write!(f, "call {}", i),
+            Op::CallBuiltin(i) => write!(f, "call_builtin {}", i),
+            Op::CallValue(n) => write!(f, "call_value {}", n),
+            Op::TailCall(i) => write!(f, "tail_call {}", i),
+            Op::TailCallValue(n) => write!(f, "tail_call_value {}", n),
+            Op::MakeClosure(i) => write!(f, "make_closure {}", i),
+            Op::Return => write!(f, "return"),
+            Op::Fail(i) => write!(f, "fail {}", i),
+            Op::Unreachable => write!(f, "unreachable"),
+        }
+    }
+}
+
+impl fmt::Display for Bytecode {
+    /// Disassembles every function, with constants inline.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, func) in self.funcs.iter().enumerate() {
+            writeln!(
+                f,
+                "fn {} `{}` (arity {}, locals {}):",
+                i, func.name, func.arity, func.locals
+            )?;
+            for (ip, op) in func.code.iter().enumerate() {
+                match op {
+                    Op::Const(c) => {
+                        writeln!(f, "  {:4} {}  ; {}", ip, op, self.constants[*c as usize])?
+                    }
+                    Op::Call(callee) | Op::TailCall(callee) | Op::MakeClosure(callee) => writeln!(
+                        f,
+                        "  {:4} {}  ; {}",
+                        ip, op, self.funcs[*callee as usize].name
+                    )?,
+                    Op::CallBuiltin(callee) => writeln!(
+                        f,
+                        "  {:4} {}  ; {}",
+                        ip, op, self.builtins[*callee as usize].name
+                    )?,
+                    _ => writeln!(f, "  {:4} {}", ip, op)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
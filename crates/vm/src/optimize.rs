@@ -0,0 +1,314 @@
+use std::collections::{HashMap, HashSet};
+
+use ginto_eval::Value;
+use ginto_syntax::{BinOp, Type};
+use tracing::{debug, info_span};
+
+use crate::{Bytecode, Function, Op};
+
+/// Optimizes every function of `bytecode` in place, until nothing changes:
+///
+/// - locals stored once with a constant are replaced by the constant,
+/// - operations on constants are folded, and operations leaving their
+///   operand unchanged, like `x * 1` and `x + 0`, are removed,
+/// - jumps to jumps go straight to the final target, and code no jump
+///   reaches is removed.
+///
+/// Only operations that succeed are folded, so an optimized program fails
+/// with the same errors, at the same spans, as the unoptimized one.
+pub fn optimize(bytecode: &mut Bytecode) {
+    let _span = info_span!("optimize").entered();
+    let constants = &mut bytecode.constants;
+    for func in &mut bytecode.funcs {
+        loop {
+            let propagated = propagate(func);
+            let folded = fold(func, constants);
+            let threaded = thread_jumps(func);
+            if !(propagated || folded || threaded) {
+                break;
+            }
+        }
+    }
+    debug!(instructions = bytecode.instructions(), "optimized");
+}
+
+/// Replaces loads of locals that are stored exactly once, with a constant,
+/// by the constant, and removes the store once nothing loads the local.
+///
+/// Checked programs assign locals before reading them, and a store in a
+/// loop stores the same constant each time round, so every load after the
+/// only store reads the stored constant.
+fn propagate(func: &mut Function) -> bool {
+    let targets = jump_targets(&func.code);
+    let mut stores: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (i, op) in func.code.iter().enumerate() {
+        if let Op::Store(slot) = op {
+            stores.entry(*slot).or_default().push(i);
+        }
+    }
+    // captures and parameters hold values from the caller
+    let params = func.captures + func.arity;
+    let mut dead = vec![false; func.code.len()];
+    let mut changed = false;
+    for (slot, at) in stores {
+        let &[at] = at.as_slice() else {
+            continue;
+        };
+        if slot < params || at == 0 || targets.contains(&at) {
+            continue;
+        }
+        let Op::Const(c) = func.code[at - 1] else {
+            continue;
+        };
+        for op in &mut func.code[at + 1..] {
+            if *op == Op::Load(slot) {
+                *op = Op::Const(c);
+                changed = true;
+            }
+        }
+        if !func.code[..at].contains(&Op::Load(slot)) {
+            dead[at - 1] = true;
+            dead[at] = true;
+            changed = true;
+        }
+    }
+    remove(func, &dead);
+    changed
+}
+
+/// Folds operations on constants and removes operations that leave their
+/// operand unchanged, looking at a few instructions at a time.
+fn fold(func: &mut Function, constants: &mut Vec<Value>) -> bool {
+    let targets = jump_targets(&func.code);
+    let mut dead = vec![false; func.code.len()];
+    let mut changed = false;
+    let mut i = 0;
+    while i < func.code.len() {
+        // a window is only rewritten if nothing jumps into its middle
+        let window = |len: usize| {
+            let ops = func.code.get(i..i + len)?;
+            (1..len)
+                .all(|k| !targets.contains(&(i + k)))
+                .then(|| ops.to_vec())
+        };
+
+        let mut fold_to = None;
+        let mut remove_len = 0;
+        if let Some(&[a, b, Op::Binary(op)]) = window(3).as_deref() {
+            match (constant(constants, a), constant(constants, b)) {
+                (Some(lhs), Some(rhs)) => {
+                    if let Ok(value) = lhs.binary(op, &rhs) {
+                        fold_to = Some((Op::Const(intern(constants, value)), 3));
+                    }
+                }
+                // `0 + x` and `1 * x` are `x`
+                (Some(lhs), None) if matches!(b, Op::Load(_)) && is_left_identity(op, &lhs) => {
+                    fold_to = Some((b, 3));
+                }
+                _ => {}
+            }
+        }
+        if fold_to.is_none()
+            && let Some(&[a, b]) = window(2).as_deref()
+        {
+            match (constant(constants, a), b) {
+                (Some(value), Op::Unary(op)) => {
+                    if let Ok(value) = value.unary(op) {
+                        fold_to = Some((Op::Const(intern(constants, value)), 2));
+                    }
+                }
+                (Some(value), Op::CastU64) => {
+                    fold_to = Some((Op::Const(intern(constants, value.cast(&Type::U64))), 2));
+                }
+                (Some(value), Op::CastI64) => {
+                    fold_to = Some((Op::Const(intern(constants, value.cast(&Type::I64))), 2));
+                }
+                (Some(value), Op::CastF64) => {
+                    fold_to = Some((Op::Const(intern(constants, value.cast(&Type::F64))), 2));
+                }
+                // `x + 0`, `x - 0`, `x * 1` and `x / 1` are `x`
+                (Some(value), Op::Binary(op)) if is_right_identity(op, &value) => remove_len = 2,
+                (Some(value), Op::JumpIfFalse(target) | Op::JumpIfTrue(target)) => {
+                    if value.as_bool() == Some(matches!(b, Op::JumpIfTrue(_))) {
+                        fold_to = Some((Op::Jump(target), 2));
+                    } else {
+                        remove_len = 2;
+                    }
+                }
+                (Some(_), Op::Dup) => {
+                    // lets the copy fold too, as in `true && x`
+                    func.code[i + 1] = a;
+                    changed = true;
+                }
+                (_, Op::Pop) if matches!(a, Op::Const(_) | Op::Load(_) | Op::Dup) => remove_len = 2,
+                _ => {}
+            }
+        }
+
+        if let Some((op, len)) = fold_to {
+            // the folded value stands for the whole operation
+            func.code[i] = op;
+            func.spans[i] = func.spans[i + len - 1];
+            dead[i + 1..i + len].fill(true);
+            changed = true;
+            i += len;
+        } else if remove_len > 0 {
+            dead[i..i + remove_len].fill(true);
+            changed = true;
+            i += remove_len;
+        } else {
+            i += 1;
+        }
+    }
+    remove(func, &dead);
+    changed
+}
+
+/// Points jumps to jumps at the final target, turns jumps to a `return`
+/// into one, and removes jumps to the next instruction and code after an
+/// unconditional jump that nothing jumps to.
+fn thread_jumps(func: &mut Function) -> bool {
+    let len = func.code.len();
+    let mut changed = false;
+    for i in 0..len {
+        let (Op::Jump(target) | Op::JumpIfFalse(target) | Op::JumpIfTrue(target)) = func.code[i]
+        else {
+            continue;
+        };
+        // only forward jumps are followed, so following them ends
+        let mut last = target;
+        while let Some(&Op::Jump(next)) = func.code.get(last as usize)
+            && next > last
+        {
+            last = next;
+        }
+        let op = match func.code[i] {
+            Op::Jump(_) if func.code.get(last as usize) == Some(&Op::Return) => Op::Return,
+            Op::Jump(_) => Op::Jump(last),
+            Op::JumpIfFalse(_) => Op::JumpIfFalse(last),
+            _ => Op::JumpIfTrue(last),
+        };
+        if op != func.code[i] {
+            func.code[i] = op;
+            changed = true;
+        }
+    }
+
+    let targets = jump_targets(&func.code);
+    let mut dead = vec![false; len];
+    let mut unreachable = vec![false; len];
+    let mut reachable = true;
+    for (i, is_dead) in dead.iter_mut().enumerate() {
+        reachable |= targets.contains(&i);
+        if !reachable {
+            *is_dead = true;
+            unreachable[i] = true;
+            changed = true;
+            continue;
+        }
+        match func.code[i] {
+            Op::Jump(target) if target as usize == i + 1 => {
+                *is_dead = true;
+                changed = true;
+            }
+            Op::JumpIfFalse(target) | Op::JumpIfTrue(target) if target as usize == i + 1 => {
+                func.code[i] = Op::Pop;
+                changed = true;
+            }
+            Op::Jump(_)
+            | Op::Return
+            | Op::TailCall(_)
+            | Op::TailCallValue(_)
+            | Op::Fail(_)
+            | Op::Unreachable => reachable = false,
+            _ => {}
+        }
+    }
+    // a debugger never stops at statements that do not run
+    func.statements
+        .retain(|stmt| !unreachable.get(stmt.at as usize).copied().unwrap_or(false));
+    remove(func, &dead);
+    changed
+}
+
+/// Whether `value op x` is `x` for every `x`.
+fn is_left_identity(op: BinOp, value: &Value) -> bool {
+    match op {
+        BinOp::Add => matches!(value, Value::U64(0) | Value::I64(0)),
+        BinOp::Mul => matches!(value, Value::U64(1) | Value::I64(1)),
+        _ => false,
+    }
+}
+
+/// Whether `x op value` is `x` for every `x`.
+fn is_right_identity(op: BinOp, value: &Value) -> bool {
+    match op {
+        BinOp::Add | BinOp::Sub => matches!(value, Value::U64(0) | Value::I64(0)),
+        BinOp::Mul | BinOp::Div => matches!(value, Value::U64(1) | Value::I64(1)),
+        _ => false,
+    }
+}
+
+/// The value `op` pushes, if it pushes a constant.
+fn constant(constants: &[Value], op: Op) -> Option<Value> {
+    match op {
+        Op::Const(c) => Some(constants[c as usize].clone()),
+        _ => None,
+    }
+}
+
+/// The index of `value` in `constants`, adding it if it is new.
+fn intern(constants: &mut Vec<Value>, value: Value) -> u32 {
+    match constants.iter().position(|c| c.is_identical(&value)) {
+        Some(index) => index as u32,
+        None => {
+            constants.push(value);
+            constants.len() as u32 - 1
+        }
+    }
+}
+
+fn jump_targets(code: &[Op]) -> HashSet<usize> {
+    code.iter()
+        .filter_map(|op| match op {
+            Op::Jump(target) | Op::JumpIfFalse(target) | Op::JumpIfTrue(target) => {
+                Some(*target as usize)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Removes the instructions marked `dead`, pointing jumps and statements
+/// starting at a removed instruction at the next one kept.
+fn remove(func: &mut Function, dead: &[bool]) {
+    if !dead.contains(&true) {
+        return;
+    }
+    let mut new_index = Vec::with_capacity(dead.len() + 1);
+    let mut kept = 0;
+    for &is_dead in dead {
+        new_index.push(kept as u32);
+        kept += usize::from(!is_dead);
+    }
+    new_index.push(kept as u32);
+
+    let mut i = 0;
+    func.code.retain(|_| {
+        i += 1;
+        !dead[i - 1]
+    });
+    let mut i = 0;
+    func.spans.retain(|_| {
+        i += 1;
+        !dead[i - 1]
+    });
+    for op in &mut func.code {
+        if let Op::Jump(target) | Op::JumpIfFalse(target) | Op::JumpIfTrue(target) = op {
+            *target = new_index[*target as usize];
+        }
+    }
+    for stmt in &mut func.statements {
+        stmt.at = new_index[stmt.at as usize];
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use ginto_diag::{FileId, SourceManager};
+
+use crate::Bytecode;
+
+/// How many of the lines running the most instructions a report shows.
+const HOT_LINES_SHOWN: usize = 10;
+
+/// What the functions of a program did while the VM ran it, counted
+/// instruction by instruction.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    funcs: Vec<FuncProfile>,
+    /// How often each instruction of each function ran.
+    counts: Vec<Vec<u64>>,
+    total: u64,
+    /// The calls running, innermost last, with the instruction count and
+    /// time they started at.
+    active: Vec<(u32, u64, Instant)>,
+    /// How many calls of each function are running, so recursive calls
+    /// are only counted once in the inclusive counts.
+    depths: Vec<u32>,
+}
+
+/// What the calls to a function did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuncProfile {
+    pub calls: u64,
+    /// The instructions run by the function itself.
+    pub exclusive: u64,
+    /// The instructions run by the function and the functions it called.
+    pub inclusive: u64,
+    /// The time spent in the function and the functions it called.
+    pub time: Duration,
+}
+
+impl Profile {
+    pub fn new(bytecode: &Bytecode) -> Self {
+        Self {
+            funcs: vec![FuncProfile::default(); bytecode.funcs.len()],
+            counts: bytecode
+                .funcs
+                .iter()
+                .map(|func| vec![0; func.code.len()])
+                .collect(),
+            total: 0,
+            active: Vec::new(),
+            depths: vec![0; bytecode.funcs.len()],
+        }
+    }
+
+    /// What the calls to `bytecode.funcs[func]` did.
+    pub fn func(&self, func: u32) -> &FuncProfile {
+        &self.funcs[func as usize]
+    }
+
+    /// How many instructions ran in total.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub(crate) fn instruction(&mut self, func: u32, ip: usize) {
+        self.counts[func as usize][ip] += 1;
+        self.funcs[func as usize].exclusive += 1;
+        self.total += 1;
+    }
+
+    pub(crate) fn enter(&mut self, func: u32) {
+        self.funcs[func as usize].calls += 1;
+        self.depths[func as usize] += 1;
+        self.active.push((func, self.total, Instant::now()));
+    }
+
+    pub(crate) fn leave(&mut self) {
+        let Some((func, start, started)) = self.active.pop() else {
+            return;
+        };
+        self.depths[func as usize] -= 1;
+        if self.depths[func as usize] == 0 {
+            let profile = &mut self.funcs[func as usize];
+            profile.inclusive += self.total - start;
+            profile.time += started.elapsed();
+        }
+    }
+
+    /// Ends the calls still running, as when an error stopped the program.
+    pub(crate) fn leave_all(&mut self) {
+        while !self.active.is_empty() {
+            self.leave();
+        }
+    }
+
+    /// A table of the functions that ran, the busiest first, followed by
+    /// the source lines that ran the most instructions.
+    pub fn report(&self, bytecode: &Bytecode, sm: &SourceManager) -> String {
+        let mut out = String::new();
+        let mut funcs: Vec<_> = (0..self.funcs.len())
+            .filter(|&id| self.funcs[id].calls > 0)
+            .collect();
+        funcs.sort_by_key(|&id| std::cmp::Reverse(self.funcs[id].exclusive));
+        let _ = writeln!(
+            out,
+            "{:<20} {:>10} {:>12} {:>12} {:>12}",
+            "function", "calls", "exclusive", "inclusive", "time"
+        );
+        for id in funcs {
+            let profile = &self.funcs[id];
+            let _ = writeln!(
+                out,
+                "{:<20} {:>10} {:>12} {:>12} {:>12}",
+                bytecode.funcs[id].name,
+                profile.calls,
+                profile.exclusive,
+                profile.inclusive,
+                format!("{:.2?}", profile.time)
+            );
+        }
+
+        let mut lines: HashMap<(FileId, usize), u64> = HashMap::new();
+        for (func, counts) in bytecode.funcs.iter().zip(&self.counts) {
+            let Some(file) = sm.get_file(func.file_id) else {
+                continue;
+            };
+            for (span, &count) in func.spans.iter().zip(counts) {
+                if count > 0 {
+                    let line = file.line_col(span.start).line;
+                    *lines.entry((func.file_id, line)).or_default() += count;
+                }
+            }
+        }
+        let mut lines: Vec<_> = lines.into_iter().collect();
+        lines.sort_by_key(|&((file_id, line), count)| (std::cmp::Reverse(count), file_id, line));
+        let _ = writeln!(out, "\nhottest lines:");
+        for ((file_id, line), count) in lines.into_iter().take(HOT_LINES_SHOWN) {
+            let file = sm.get_file(file_id).expect("profiled file is not loaded");
+            let text = file.source.lines().nth(line - 1).unwrap_or_default();
+            let percent = count as f64 * 100.0 / self.total.max(1) as f64;
+            let _ = writeln!(
+                out,
+                "{:>12} {:>5.1}%  {}:{}  {}",
+                count,
+                percent,
+                file.name,
+                line,
+                text.trim()
+            );
+        }
+        out
+    }
+}
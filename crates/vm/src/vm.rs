@@ -0,0 +1,436 @@
+use core::fmt;
+
+use ginto_diag::FileId;
+use ginto_eval::{
+    Builtins, CALL_DEPTH_LIMIT, DebugHandler, Debugger, Limit, Limits, Meter, NO_BUILTINS,
+    RuntimeError, RuntimeErrorKind, StackFrame, Value, ValueError,
+};
+use ginto_resolve::DefId;
+use ginto_syntax::Type;
+
+use crate::{Bytecode, Function, Op, Profile};
+
+struct Frame {
+    func: u32,
+    /// The function the frame was called for, which tail calls replace as
+    /// `func`. Errors name the call by it, as the interpreter does.
+    called: u32,
+    /// The next instruction to run.
+    ip: usize,
+    /// Where the frame's locals start on the value stack.
+    base: usize,
+}
+
+/// Runs bytecode on a value stack, with an explicit call stack instead of
+/// native recursion.
+///
+/// Programs behave as in the interpreter, errors and their call stacks
+/// included.
+pub struct Vm<'a> {
+    bytecode: &'a Bytecode,
+    builtins: &'a Builtins,
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+    meter: Meter,
+    debugger: Option<Debugger<'a>>,
+    profile: Option<&'a mut Profile>,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(bytecode: &'a Bytecode) -> Self {
+        Self {
+            bytecode,
+            builtins: &NO_BUILTINS,
+            stack: Vec::new(),
+            frames: Vec::new(),
+            meter: Meter::default(),
+            debugger: None,
+            profile: None,
+        }
+    }
+
+    /// Runs calls to builtin functions with `builtins`, which the program
+    /// was resolved and checked with.
+    pub fn with_builtins(mut self, builtins: &'a Builtins) -> Self {
+        self.builtins = builtins;
+        self
+    }
+
+    /// Stops programs exceeding `limits`.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.meter = Meter::new(limits);
+        self
+    }
+
+    /// Lets `handler` stop the program before each statement.
+    pub fn with_debugger(mut self, handler: &'a mut dyn DebugHandler) -> Self {
+        self.debugger = Some(Debugger::new(handler));
+        self
+    }
+
+    /// Counts what each function and instruction of the program does in
+    /// `profile`, made for the same bytecode.
+    pub fn with_profile(mut self, profile: &'a mut Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Runs the `main` function of the root file.
+    pub fn run(&mut self) -> Result<Value, RuntimeError> {
+        let main = self.bytecode.main.ok_or(RuntimeErrorKind::NoMain {
+            file_id: self.bytecode.root.unwrap_or(FileId(0)),
+        })?;
+        self.call(self.bytecode.funcs[main as usize].def, Vec::new())
+    }
+
+    /// Calls the function `def` with `args`. The call itself is not part of
+    /// the stack of an error it returns.
+    pub fn call(&mut self, def: DefId, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let id = self.bytecode.func_ids[&def];
+        let func = &self.bytecode.funcs[id as usize];
+        if args.len() != func.arity as usize {
+            return Err(RuntimeErrorKind::ArityMismatch {
+                name: func.name.clone(),
+                expected: func.arity as usize,
+                found: args.len(),
+            }
+            .into());
+        }
+        self.stack.clear();
+        self.frames.clear();
+        self.stack.extend(args);
+        self.meter.start();
+        self.push_frame(id);
+        let result = self.execute();
+        if let Some(profile) = &mut self.profile {
+            profile.leave_all();
+        }
+        self.stack.clear();
+        self.frames.clear();
+        result
+    }
+
+    fn push_frame(&mut self, func: u32) {
+        let func_info = &self.bytecode.funcs[func as usize];
+        let base = self.stack.len() - (func_info.captures + func_info.arity) as usize;
+        self.stack
+            .resize(base + func_info.locals as usize, Value::Unit);
+        self.frames.push(Frame {
+            func,
+            called: func,
+            ip: 0,
+            base,
+        });
+        if let Some(profile) = &mut self.profile {
+            profile.enter(func);
+        }
+    }
+
+    /// Replaces the function running in the current frame by `func`, whose
+    /// captures and arguments are on top of the stack.
+    fn reuse_frame(&mut self, func: u32) {
+        let func_info = &self.bytecode.funcs[func as usize];
+        let frame = self.frames.last_mut().expect("no frame to reuse");
+        let args = self.stack.len() - (func_info.captures + func_info.arity) as usize;
+        self.stack.drain(frame.base..args);
+        self.stack
+            .resize(frame.base + func_info.locals as usize, Value::Unit);
+        frame.func = func;
+        frame.ip = 0;
+        if let Some(profile) = &mut self.profile {
+            profile.leave();
+            profile.enter(func);
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("value stack underflow")
+    }
+
+    fn execute(&mut self) -> Result<Value, RuntimeError> {
+        let bytecode = self.bytecode;
+        loop {
+            let frame = self.frames.last_mut().expect("no frame to run");
+            if let Some(profile) = &mut self.profile {
+                profile.instruction(frame.func, frame.ip);
+            }
+            let func = &bytecode.funcs[frame.func as usize];
+            let ip = frame.ip;
+            let base = frame.base;
+            frame.ip += 1;
+            if self.debugger.is_some() {
+                self.before_statements(func, ip, base);
+            }
+            if let Err(limit) = self.meter.step() {
+                return Err(self.limit_exceeded(limit, ip));
+            }
+            match func.code[ip] {
+                Op::Const(i) => self.stack.push(bytecode.constants[i as usize].clone()),
+                Op::Pop => {
+                    self.pop();
+                }
+                Op::Dup => {
+                    let top = self.stack.last().expect("value stack underflow").clone();
+                    self.stack.push(top);
+                }
+                Op::Load(slot) => self.stack.push(self.stack[base + slot as usize].clone()),
+                Op::Store(slot) => {
+                    let value = self.pop();
+                    self.stack[base + slot as usize] = value;
+                }
+                Op::Unary(op) => {
+                    let value = self.pop();
+                    let result = value.unary(op).map_err(|error| {
+                        let expr = format!("{}({})", op, value);
+                        self.value_error(error, expr, op, std::slice::from_ref(&value), ip)
+                    })?;
+                    self.stack.push(result);
+                }
+                Op::Binary(op) => {
+                    let rhs = self.pop();
+                    let lhs = self.pop();
+                    let result = lhs.binary(op, &rhs).map_err(|error| {
+                        let expr = format!("{} {} {}", lhs, op, rhs);
+                        self.value_error(error, expr, op, &[lhs.clone(), rhs.clone()], ip)
+                    })?;
+                    self.stack.push(result);
+                }
+                Op::Interpolate(n) => {
+                    if let Err(limit) = self.meter.allocate() {
+                        return Err(self.limit_exceeded(limit, ip));
+                    }
+                    let parts = self.stack.split_off(self.stack.len() - n as usize);
+                    let value: String = parts.iter().map(Value::to_string).collect();
+                    self.stack.push(Value::Str(value.into()));
+                }
+                Op::CastU64 => {
+                    let value = self.pop();
+                    self.stack.push(value.cast(&Type::U64));
+                }
+                Op::CastI64 => {
+                    let value = self.pop();
+                    self.stack.push(value.cast(&Type::I64));
+                }
+                Op::CastF64 => {
+                    let value = self.pop();
+                    self.stack.push(value.cast(&Type::F64));
+                }
+                Op::Jump(target) => self.jump(target),
+                Op::JumpIfFalse(target) => {
+                    if self.pop() == Value::Bool(false) {
+                        self.jump(target);
+                    }
+                }
+                Op::JumpIfTrue(target) => {
+                    if self.pop() == Value::Bool(true) {
+                        self.jump(target);
+                    }
+                }
+                Op::Call(callee) => self.call_func(callee, ip)?,
+                Op::CallBuiltin(callee) => self.call_builtin(callee, ip)?,
+                Op::CallValue(argc) => match self.pop_callee(argc) {
+                    Ok(id) => self.call_func(id, ip)?,
+                    Err(builtin) => self.call_builtin(builtin, ip)?,
+                },
+                Op::TailCall(callee) => self.reuse_frame(callee),
+                Op::TailCallValue(argc) => match self.pop_callee(argc) {
+                    Ok(id) => self.reuse_frame(id),
+                    Err(builtin) => {
+                        // builtins have no frame to reuse
+                        self.call_builtin(builtin, ip)?;
+                        if let Some(value) = self.return_value(base) {
+                            return Ok(value);
+                        }
+                    }
+                },
+                Op::MakeClosure(id) => {
+                    if let Err(limit) = self.meter.allocate() {
+                        return Err(self.limit_exceeded(limit, ip));
+                    }
+                    let closure = &bytecode.funcs[id as usize];
+                    let env = self
+                        .stack
+                        .split_off(self.stack.len() - closure.captures as usize);
+                    self.stack.push(Value::Closure {
+                        func: closure.def,
+                        env: env.into(),
+                    });
+                }
+                Op::Return => {
+                    if let Some(value) = self.return_value(base) {
+                        return Ok(value);
+                    }
+                }
+                Op::Fail(error) => {
+                    let kind = bytecode.errors[error as usize].clone();
+                    return Err(self.error(kind));
+                }
+                Op::Unreachable => unreachable!("reached unreachable code in `{}`", func.name),
+            }
+        }
+    }
+
+    /// Lets the debugger stop before the statements of `func` starting at
+    /// `ip`, whose locals start at `base`.
+    fn before_statements(&mut self, func: &Function, ip: usize, base: usize) {
+        let Some(debugger) = &mut self.debugger else {
+            return;
+        };
+        let first = func
+            .statements
+            .partition_point(|stmt| (stmt.at as usize) < ip);
+        for stmt in &func.statements[first..] {
+            if stmt.at as usize != ip {
+                break;
+            }
+            let stack = &self.stack;
+            debugger.before_statement(func.file_id, stmt.span, self.frames.len(), || {
+                let slots = &stack[base..base + stmt.locals as usize];
+                func.local_names
+                    .iter()
+                    .cloned()
+                    .zip(slots.iter().cloned())
+                    .collect()
+            });
+        }
+    }
+
+    /// Pops the current frame, whose locals start at `base`, passing the
+    /// value on top of the stack to the caller. Returns the value if the
+    /// frame was the first.
+    fn return_value(&mut self, base: usize) -> Option<Value> {
+        let value = self.pop();
+        self.stack.truncate(base);
+        self.frames.pop();
+        if let Some(profile) = &mut self.profile {
+            profile.leave();
+        }
+        if self.frames.is_empty() {
+            return Some(value);
+        }
+        self.stack.push(value);
+        None
+    }
+
+    /// Pops the function value below the `argc` arguments on top of the
+    /// stack, putting a closure's captures before the arguments. Returns the
+    /// index of the function to call, or of the builtin.
+    fn pop_callee(&mut self, argc: u32) -> Result<u32, u32> {
+        let callee = self.stack.remove(self.stack.len() - 1 - argc as usize);
+        match callee {
+            Value::Fn(def) => match self.bytecode.func_ids.get(&def) {
+                Some(&id) => Ok(id),
+                None => Err(self.bytecode.builtin_ids[&def]),
+            },
+            Value::Closure { func, env } => {
+                let at = self.stack.len() - argc as usize;
+                self.stack.splice(at..at, env.iter().cloned());
+                Ok(self.bytecode.func_ids[&func])
+            }
+            _ => unreachable!("callee is not a function"),
+        }
+    }
+
+    fn jump(&mut self, target: u32) {
+        self.frames.last_mut().expect("no frame to run").ip = target as usize;
+    }
+
+    /// Calls `callee` from the instruction at `ip` of the current frame.
+    fn call_func(&mut self, callee: u32, ip: usize) -> Result<(), RuntimeError> {
+        if self.frames.len() >= CALL_DEPTH_LIMIT {
+            let caller = &self.bytecode.funcs[self.frames.last().expect("no frame").func as usize];
+            let kind = RuntimeErrorKind::StackOverflow {
+                name: self.bytecode.funcs[callee as usize].name.clone(),
+                span: caller.spans[ip],
+                file_id: caller.file_id,
+            };
+            return Err(self.error(kind));
+        }
+        if let Err(limit) = self.meter.call(self.frames.len()) {
+            return Err(self.limit_exceeded(limit, ip));
+        }
+        self.push_frame(callee);
+        Ok(())
+    }
+
+    /// Runs the builtin `callee` on the arguments on top of the stack, from
+    /// the instruction at `ip` of the current frame.
+    fn call_builtin(&mut self, callee: u32, ip: usize) -> Result<(), RuntimeError> {
+        let builtin_ref = &self.bytecode.builtins[callee as usize];
+        let builtin = self
+            .builtins
+            .get(&builtin_ref.name)
+            .unwrap_or_else(|| panic!("builtin `{}` was not registered", builtin_ref.name));
+        let args = self.stack.len() - builtin_ref.arity as usize;
+        match builtin.call(&self.stack[args..]) {
+            Ok(value) => {
+                self.stack.truncate(args);
+                self.stack.push(value);
+                Ok(())
+            }
+            Err(message) => {
+                let caller =
+                    &self.bytecode.funcs[self.frames.last().expect("no frame").func as usize];
+                let kind = RuntimeErrorKind::BuiltinFailed {
+                    name: builtin.name.clone(),
+                    message,
+                    span: caller.spans[ip],
+                    file_id: caller.file_id,
+                };
+                Err(self.error(kind))
+            }
+        }
+    }
+
+    /// The error for exceeding `limit` at the instruction `ip` of the
+    /// current frame.
+    fn limit_exceeded(&self, limit: Limit, ip: usize) -> RuntimeError {
+        let func = &self.bytecode.funcs[self.frames.last().expect("no frame").func as usize];
+        let kind = RuntimeErrorKind::LimitExceeded {
+            limit,
+            span: func.spans[ip],
+            file_id: func.file_id,
+        };
+        self.error(kind)
+    }
+
+    fn value_error(
+        &self,
+        error: ValueError,
+        expr: String,
+        op: impl fmt::Display,
+        operands: &[Value],
+        ip: usize,
+    ) -> RuntimeError {
+        let func = &self.bytecode.funcs[self.frames.last().expect("no frame").func as usize];
+        let kind = RuntimeErrorKind::from_value_error(
+            error,
+            expr,
+            op,
+            operands,
+            func.spans[ip],
+            func.file_id,
+        );
+        self.error(kind)
+    }
+
+    /// `kind` with the calls on the stack, innermost first. The first frame
+    /// is the call made from outside, which is left out.
+    fn error(&self, kind: RuntimeErrorKind) -> RuntimeError {
+        let stack = self
+            .frames
+            .windows(2)
+            .rev()
+            .map(|pair| {
+                let caller = &self.bytecode.funcs[pair[0].func as usize];
+                StackFrame {
+                    name: self.bytecode.funcs[pair[1].called as usize].name.clone(),
+                    // the caller has moved past its call instruction
+                    span: caller.spans[pair[0].ip - 1],
+                    file_id: caller.file_id,
+                }
+            })
+            .collect();
+        RuntimeError { kind, stack }
+    }
+}
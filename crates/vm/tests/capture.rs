@@ -0,0 +1,61 @@
+//! Programs run with captured output report what they printed along with
+//! their value or error, the same in the interpreter and in the VM.
+
+use ginto_driver::Compilation;
+use ginto_eval::{Builtins, CapturedOutput, Interpreter, RunResult, RuntimeErrorKind, Value};
+use ginto_test_support::check_with;
+use ginto_vm::{Compiler, Vm};
+
+/// The results of running `source` in the interpreter and in the VM.
+fn run(source: &str) -> [RunResult; 2] {
+    let output = CapturedOutput::new();
+    let builtins = Builtins::with_captured_prelude(&output);
+    let (
+        Compilation {
+            tree,
+            resolutions,
+            types,
+            builtins,
+            ..
+        },
+        _,
+    ) = check_with("main.ginto", source, &[], |driver| {
+        driver.with_builtins(builtins)
+    });
+    let interpreted = Interpreter::new(&resolutions, &types)
+        .with_builtins(&builtins)
+        .run_modules(&tree);
+    let interpreted = RunResult::capture(&output, interpreted);
+    let bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+    let executed = Vm::new(&bytecode).with_builtins(&builtins).run();
+    [interpreted, RunResult::capture(&output, executed)]
+}
+
+#[test]
+fn captures_output_and_value() {
+    let [interpreted, executed] = run("fn main() -> u64:
+    let name = \"ginto\"
+    print(\"hello, {name}\")
+    print(len(name))
+    7
+");
+    assert_eq!(interpreted.output, "hello, ginto\n5\n");
+    assert_eq!(interpreted.result, Ok(Value::U64(7)));
+    assert_eq!(interpreted, executed);
+}
+
+#[test]
+fn captures_output_before_an_error() {
+    let [interpreted, executed] = run("fn main() -> u64:
+    print(1)
+    let max = 18446744073709551615
+    print(2)
+    max + 1
+");
+    assert_eq!(interpreted.output, "1\n2\n");
+    assert!(matches!(
+        interpreted.result,
+        Err(ref error) if matches!(error.kind, RuntimeErrorKind::Overflow { .. })
+    ));
+    assert_eq!(interpreted, executed);
+}
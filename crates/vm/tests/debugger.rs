@@ -0,0 +1,136 @@
+//! A debugger stops the interpreter and the VM at the same statements, with
+//! the same locals, as it steps through a program.
+
+use ginto_diag::{FileId, Span};
+use ginto_driver::Compilation;
+use ginto_eval::{DebugAction, DebugHandler, DebugStop, Interpreter, Value};
+use ginto_test_support::check;
+use ginto_vm::{Compiler, Vm};
+
+const SOURCE: &str = "fn main() -> u64:
+    let a = 2
+    let b = double(a)
+    b + 1
+
+fn double(n: u64) -> u64:
+    let m = n * 2
+    m
+";
+
+/// Stops at `breakpoint` and then goes on with `actions`, one per stop,
+/// recording each stop as the statement's source, depth and locals.
+struct Recorder {
+    breakpoint: &'static str,
+    actions: Vec<DebugAction>,
+    stops: Vec<String>,
+}
+
+impl Recorder {
+    fn new(breakpoint: &'static str, actions: &[DebugAction]) -> Self {
+        Self {
+            breakpoint,
+            actions: actions.to_vec(),
+            stops: Vec::new(),
+        }
+    }
+}
+
+fn text(span: Span) -> String {
+    SOURCE
+        .chars()
+        .skip(span.start.0)
+        .take(span.end.0 - span.start.0)
+        .collect()
+}
+
+impl DebugHandler for Recorder {
+    fn is_breakpoint(&mut self, _: FileId, span: Span) -> bool {
+        text(span) == self.breakpoint
+    }
+
+    fn on_stop(&mut self, stop: &DebugStop) -> DebugAction {
+        let locals: Vec<_> = stop
+            .locals()
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        self.stops.push(format!(
+            "{} @{} [{}]",
+            text(stop.span()),
+            stop.depth(),
+            locals.join(", ")
+        ));
+        match self.actions.len() {
+            0 => DebugAction::Continue,
+            _ => self.actions.remove(0),
+        }
+    }
+}
+
+/// The stops `recorder` records in the interpreter and in the VM, which
+/// must agree.
+fn record(breakpoint: &'static str, actions: &[DebugAction]) -> Vec<String> {
+    let (
+        Compilation {
+            tree,
+            resolutions,
+            types,
+            ..
+        },
+        _,
+    ) = check(SOURCE);
+
+    let mut interpreted = Recorder::new(breakpoint, actions);
+    let result = Interpreter::new(&resolutions, &types)
+        .with_debugger(&mut interpreted)
+        .run_modules(&tree);
+    assert_eq!(result, Ok(Value::U64(5)));
+
+    let bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+    let mut compiled = Recorder::new(breakpoint, actions);
+    let result = Vm::new(&bytecode).with_debugger(&mut compiled).run();
+    assert_eq!(result, Ok(Value::U64(5)));
+
+    assert_eq!(interpreted.stops, compiled.stops);
+    interpreted.stops
+}
+
+#[test]
+fn steps_into_calls() {
+    let stops = record("let a = 2", &[DebugAction::StepIn; 5]);
+    assert_eq!(
+        stops,
+        [
+            "let a = 2 @1 []",
+            "let b = double(a) @1 [a=2]",
+            "let m = n * 2 @2 [n=2]",
+            "m @2 [n=2, m=4]",
+            "b + 1 @1 [a=2, b=4]",
+        ]
+    );
+}
+
+#[test]
+fn steps_over_calls() {
+    let stops = record("let a = 2", &[DebugAction::StepOver; 3]);
+    assert_eq!(
+        stops,
+        [
+            "let a = 2 @1 []",
+            "let b = double(a) @1 [a=2]",
+            "b + 1 @1 [a=2, b=4]",
+        ]
+    );
+}
+
+#[test]
+fn steps_out_of_calls() {
+    let stops = record("let m = n * 2", &[DebugAction::StepOut]);
+    assert_eq!(stops, ["let m = n * 2 @2 [n=2]", "b + 1 @1 [a=2, b=4]"]);
+}
+
+#[test]
+fn continues_to_breakpoints() {
+    let stops = record("m", &[]);
+    assert_eq!(stops, ["m @2 [n=2, m=4]"]);
+}
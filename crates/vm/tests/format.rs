@@ -0,0 +1,143 @@
+//! Compiled programs saved to bytes load back to the same program, and
+//! bytes that are not a program the VM can run are rejected.
+
+use ginto_diag::{BytePos, SourceManager, Span};
+use ginto_driver::Compilation;
+use ginto_eval::Value;
+use ginto_test_support::check;
+use ginto_vm::{BYTECODE_VERSION, Bytecode, BytecodeError, Compiler, Op, Vm};
+
+const SOURCE: &str = "fn main() -> u64:
+    let double = |n: u64| n * 2
+    double(add(20, 1))
+
+fn add(a: u64, b: u64) -> u64:
+    a + b
+";
+
+fn compile() -> (Bytecode, SourceManager) {
+    let (
+        Compilation {
+            tree,
+            resolutions,
+            types,
+            ..
+        },
+        sm,
+    ) = check(SOURCE);
+    let bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+    (bytecode, sm)
+}
+
+#[test]
+fn loads_saved_programs() {
+    let (bytecode, sm) = compile();
+    let bytes = bytecode.save(&sm);
+    let (loaded, loaded_sm) = Bytecode::load(&bytes).unwrap();
+    assert_eq!(loaded.to_string(), bytecode.to_string());
+    assert_eq!(loaded_sm, sm);
+    assert_eq!(loaded.save(&loaded_sm), bytes);
+    assert_eq!(Vm::new(&loaded).run(), Ok(Value::U64(42)));
+}
+
+#[test]
+fn rejects_invalid_programs() {
+    let (mut bytecode, sm) = compile();
+    let bytes = bytecode.save(&sm);
+    assert_eq!(
+        Bytecode::load(b"fn main() -> u64").unwrap_err(),
+        BytecodeError::NotBytecode
+    );
+    let mut newer = bytes.clone();
+    newer[4..6].copy_from_slice(&(BYTECODE_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        Bytecode::load(&newer).unwrap_err(),
+        BytecodeError::UnsupportedVersion {
+            found: BYTECODE_VERSION + 1
+        }
+    );
+    assert_eq!(
+        Bytecode::load(&bytes[..bytes.len() - 1]).unwrap_err(),
+        BytecodeError::Truncated
+    );
+
+    bytecode.funcs[0].code[0] = Op::Call(99);
+    let error = Bytecode::load(&bytecode.save(&sm)).unwrap_err();
+    assert!(
+        matches!(&error, BytecodeError::Invalid { reason } if reason.contains("function 99")),
+        "{:?}",
+        error
+    );
+}
+
+/// The reason `load` gives for rejecting the saved `bytecode`.
+fn rejection(bytecode: &Bytecode, sm: &SourceManager) -> String {
+    match Bytecode::load(&bytecode.save(sm)) {
+        Err(BytecodeError::Invalid { reason }) => reason,
+        other => panic!(
+            "expected an invalid program, got {:?}",
+            other.map(|(b, _)| b)
+        ),
+    }
+}
+
+#[test]
+fn rejects_spans_outside_their_file() {
+    let (mut bytecode, sm) = compile();
+    let len = SOURCE.len();
+    bytecode.funcs[0].spans[0] = Span::from_range(len..len + 1);
+    assert!(rejection(&bytecode, &sm).contains("is not in file 0"));
+
+    // `Span::new` only checks the order of its ends in debug builds
+    let (mut bytecode, sm) = compile();
+    bytecode.funcs[0].spans[0] = Span {
+        start: BytePos(5),
+        end: BytePos(2),
+    };
+    assert_eq!(rejection(&bytecode, &sm), "span 5..2 ends before it starts");
+
+    let (mut bytecode, sm) = compile();
+    bytecode.funcs[0].statements[0].span = Span::from_range(0..len * 2);
+    assert!(rejection(&bytecode, &sm).contains("is not in file 0"));
+}
+
+#[test]
+fn rejects_code_popping_values_it_did_not_push() {
+    let (mut bytecode, sm) = compile();
+    bytecode.funcs[0].code[0] = Op::Interpolate(99);
+    assert!(
+        rejection(&bytecode, &sm).contains("pops 99 values but only 0 were pushed"),
+        "{}",
+        bytecode
+    );
+
+    let (mut bytecode, sm) = compile();
+    let last = bytecode.funcs[0].code.len() - 1;
+    bytecode.funcs[0].code[last] = Op::Dup;
+    assert_eq!(
+        rejection(&bytecode, &sm),
+        format!("function `{}` runs past its end", bytecode.funcs[0].name)
+    );
+
+    // a loop pushing a value every time around
+    let (mut bytecode, sm) = compile();
+    bytecode.funcs[0]
+        .code
+        .splice(0..0, [Op::Const(0), Op::Jump(0)]);
+    bytecode.funcs[0].spans.splice(0..0, [Span::default(); 2]);
+    assert!(rejection(&bytecode, &sm).contains("different stack heights"));
+}
+
+#[test]
+fn loads_flipped_bytes_without_panicking() {
+    let (bytecode, sm) = compile();
+    let bytes = bytecode.save(&sm);
+    for i in 0..bytes.len() {
+        for flip in [0x01, 0x80, 0xff] {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= flip;
+            // loading must not panic, whatever it makes of the bytes
+            let _ = Bytecode::load(&corrupted);
+        }
+    }
+}
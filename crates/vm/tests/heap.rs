@@ -0,0 +1,67 @@
+//! Strings and closures are shared rather than copied, and freeing them
+//! takes no more stack however deeply closures capture closures.
+
+use ginto_driver::Compilation;
+use ginto_eval::{Interpreter, Value};
+use ginto_test_support::check;
+use ginto_vm::{Compiler, Vm};
+
+/// The values of running `source` in the interpreter and in the VM.
+fn run(source: &str) -> [Value; 2] {
+    let (
+        Compilation {
+            tree,
+            resolutions,
+            types,
+            ..
+        },
+        _,
+    ) = check(source);
+
+    let interpreted = ginto_test_support::on_interpreter_stack(|| {
+        Interpreter::new(&resolutions, &types).run_modules(&tree)
+    })
+    .unwrap();
+    let bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+    let compiled = Vm::new(&bytecode).run().unwrap();
+    [interpreted, compiled]
+}
+
+#[test]
+fn frees_deeply_nested_closures() {
+    // each closure captures the one before it; the chain is freed on this
+    // thread, whose stack is far too small to free it recursively
+    let source = "fn wrap(f: fn() -> u64, n: u64) -> fn() -> u64:
+    if n = 0:
+        f
+    else:
+        wrap(|| f() + 1, n - 1)
+
+fn main() -> fn() -> u64:
+    wrap(|| 0, 200000)
+";
+    for value in run(source) {
+        assert!(matches!(value, Value::Closure { ref env, .. } if env.len() == 1));
+        drop(value);
+    }
+}
+
+#[test]
+fn shares_long_lived_closures() {
+    // the closure and its string are copied into every call without
+    // copying the string
+    let source = "fn repeat(f: fn(u64) -> str, n: u64, last: str) -> str:
+    if n = 0:
+        last
+    else:
+        repeat(f, n - 1, f(n))
+
+fn main() -> str:
+    let big = \"0123456789abcdef\"
+    let f = |n: u64| \"{big}{big}{big}{big}\"
+    repeat(f, 100000, \"\")
+";
+    for value in run(source) {
+        assert_eq!(value, Value::Str("0123456789abcdef".repeat(4).into()));
+    }
+}
@@ -0,0 +1,128 @@
+//! Programs exceeding the limits they are run with stop with
+//! `LimitExceeded`, in the interpreter and in the VM.
+
+use std::time::Duration;
+
+use ginto_driver::Compilation;
+use ginto_eval::{Interpreter, Limit, Limits, RuntimeError, RuntimeErrorKind, Value};
+use ginto_test_support::check;
+use ginto_vm::{Compiler, Vm};
+
+/// The result of running `source` with `limits` in the interpreter and in
+/// the VM, keeping only the limit of a `LimitExceeded` error.
+fn run(source: &str, limits: Limits) -> [Result<Value, Option<Limit>>; 2] {
+    let (
+        Compilation {
+            tree,
+            resolutions,
+            types,
+            ..
+        },
+        _,
+    ) = check(source);
+
+    let limit = |error: RuntimeError| match error.kind {
+        RuntimeErrorKind::LimitExceeded { limit, .. } => Some(limit),
+        _ => None,
+    };
+    let interpreted = ginto_test_support::on_interpreter_stack(|| {
+        Interpreter::new(&resolutions, &types)
+            .with_limits(limits)
+            .run_modules(&tree)
+    })
+    .map_err(limit);
+    let bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+    let compiled = Vm::new(&bytecode).with_limits(limits).run().map_err(limit);
+    [interpreted, compiled]
+}
+
+const COUNT: &str = "fn count(n: u64) -> u64:
+    if n = 0:
+        0
+    else:
+        count(n - 1)
+
+fn main() -> u64:
+    count(1000000000000)
+";
+
+#[test]
+fn limits_steps() {
+    let limits = Limits {
+        max_steps: Some(1000),
+        ..Limits::default()
+    };
+    let [interpreted, compiled] = run(COUNT, limits);
+    assert_eq!(interpreted, Err(Some(Limit::Steps(1000))));
+    assert_eq!(compiled, Err(Some(Limit::Steps(1000))));
+}
+
+#[test]
+fn limits_running_time() {
+    let timeout = Duration::from_millis(50);
+    let limits = Limits {
+        timeout: Some(timeout),
+        ..Limits::default()
+    };
+    let [interpreted, compiled] = run(COUNT, limits);
+    assert_eq!(interpreted, Err(Some(Limit::Timeout(timeout))));
+    assert_eq!(compiled, Err(Some(Limit::Timeout(timeout))));
+}
+
+#[test]
+fn limits_call_depth() {
+    let source = "fn depth(n: u64) -> u64:
+    if n = 0:
+        0
+    else:
+        depth(n - 1) + 1
+
+fn main() -> u64:
+    depth(10) + depth(100)
+";
+    let limits = Limits {
+        max_call_depth: Some(50),
+        ..Limits::default()
+    };
+    let [interpreted, compiled] = run(source, limits);
+    assert_eq!(interpreted, Err(Some(Limit::CallDepth(50))));
+    assert_eq!(compiled, Err(Some(Limit::CallDepth(50))));
+}
+
+#[test]
+fn limits_heap_values() {
+    let source = "fn build(n: u64) -> u64:
+    let text = \"step {n}\"
+    if n = 0:
+        0
+    else:
+        build(n - 1)
+
+fn main() -> u64:
+    build(100)
+";
+    let limits = Limits {
+        max_heap_values: Some(10),
+        ..Limits::default()
+    };
+    let [interpreted, compiled] = run(source, limits);
+    assert_eq!(interpreted, Err(Some(Limit::HeapValues(10))));
+    assert_eq!(compiled, Err(Some(Limit::HeapValues(10))));
+}
+
+#[test]
+fn programs_within_limits_run() {
+    let limits = Limits {
+        max_steps: Some(1_000_000),
+        max_call_depth: Some(50),
+        max_heap_values: Some(10),
+        timeout: Some(Duration::from_secs(60)),
+    };
+    let source = "fn main() -> u64:
+    let add = |a: u64, b: u64| a + b
+    add(1, 2)
+";
+    let [interpreted, compiled] = run(source, limits);
+    assert_eq!(interpreted, Ok(Value::U64(3)));
+    assert_eq!(compiled, Ok(Value::U64(3)));
+}
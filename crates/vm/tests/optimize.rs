@@ -0,0 +1,173 @@
+//! Compares the disassembly of programs before and after optimizing their
+//! bytecode. That optimized programs still behave the same is checked by
+//! the parity test.
+
+use ginto_driver::Compilation;
+use ginto_test_support::check;
+use ginto_vm::{Compiler, optimize};
+
+/// The disassembly of `source` before and after optimizing it.
+fn disassemble(source: &str) -> (String, String) {
+    let (
+        Compilation {
+            tree,
+            resolutions,
+            types,
+            ..
+        },
+        _,
+    ) = check(source);
+    let mut bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+    let before = bytecode.to_string();
+    optimize(&mut bytecode);
+    (before, bytecode.to_string())
+}
+
+#[test]
+fn propagates_and_folds_constants() {
+    let (before, after) = disassemble(
+        "fn main() -> u64:
+    let one = 1
+    let x = 5
+    let y = if true && x > 2: x * one else: 0 + x
+    y + 0
+",
+    );
+    assert_eq!(
+        before,
+        "fn 0 `main` (arity 0, locals 3):
+     0 const 0  ; 1
+     1 store 0
+     2 const 1  ; 5
+     3 store 1
+     4 const 2  ; true
+     5 dup
+     6 jump_if_false 11
+     7 pop
+     8 load 1
+     9 const 3  ; 2
+    10 binary >
+    11 jump_if_false 16
+    12 load 1
+    13 load 0
+    14 binary *
+    15 jump 19
+    16 const 4  ; 0
+    17 load 1
+    18 binary +
+    19 store 2
+    20 load 2
+    21 const 4  ; 0
+    22 binary +
+    23 return
+"
+    );
+    assert_eq!(
+        after,
+        "fn 0 `main` (arity 0, locals 3):
+     0 const 1  ; 5
+     1 return
+"
+    );
+}
+
+#[test]
+fn removes_identity_operations() {
+    let (before, after) = disassemble(
+        "fn main() -> u64:
+    identity(7)
+
+fn identity(x: u64) -> u64:
+    (0 + x) * 1 - 0
+",
+    );
+    assert_eq!(
+        before,
+        "fn 0 `main` (arity 0, locals 0):
+     0 const 0  ; 7
+     1 tail_call 1  ; identity
+fn 1 `identity` (arity 1, locals 1):
+     0 const 1  ; 0
+     1 load 0
+     2 binary +
+     3 const 2  ; 1
+     4 binary *
+     5 const 1  ; 0
+     6 binary -
+     7 return
+"
+    );
+    assert_eq!(
+        after,
+        "fn 0 `main` (arity 0, locals 0):
+     0 const 0  ; 7
+     1 tail_call 1  ; identity
+fn 1 `identity` (arity 1, locals 1):
+     0 load 0
+     1 return
+"
+    );
+}
+
+#[test]
+fn threads_jumps() {
+    let (before, after) = disassemble(
+        "fn main(a: bool, b: bool) -> u64:
+    let r = if a: (if b: 1 else: 2) else: 3
+    r
+",
+    );
+    assert_eq!(
+        before,
+        "fn 0 `main` (arity 2, locals 3):
+     0 load 0
+     1 jump_if_false 8
+     2 load 1
+     3 jump_if_false 6
+     4 const 0  ; 1
+     5 jump 7
+     6 const 1  ; 2
+     7 jump 9
+     8 const 2  ; 3
+     9 store 2
+    10 load 2
+    11 return
+"
+    );
+    assert_eq!(
+        after,
+        "fn 0 `main` (arity 2, locals 3):
+     0 load 0
+     1 jump_if_false 8
+     2 load 1
+     3 jump_if_false 6
+     4 const 0  ; 1
+     5 jump 9
+     6 const 1  ; 2
+     7 jump 9
+     8 const 2  ; 3
+     9 store 2
+    10 load 2
+    11 return
+"
+    );
+}
+
+#[test]
+fn keeps_operations_that_fail() {
+    let (_, after) = disassemble(
+        "fn main() -> u64:
+    let max = 18446744073709551615
+    max + 1
+",
+    );
+    assert_eq!(
+        after,
+        "fn 0 `main` (arity 0, locals 1):
+     0 const 0  ; 18446744073709551615
+     1 const 1  ; 1
+     2 binary +
+     3 return
+"
+    );
+}
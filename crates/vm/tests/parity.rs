@@ -0,0 +1,102 @@
+//! Runs every program of `tests/corpus` with both the interpreter and the
+//! VM, before and after optimizing the bytecode and after saving and
+//! loading it again, which must all agree on
+//! what the program prints and its value or error. Both run with the
+//! prelude and a few host builtins.
+
+use std::{fs, path::Path};
+
+use ginto_diag::Symbol;
+use ginto_driver::Compilation;
+use ginto_eval::{Builtins, CapturedOutput, Interpreter, RunResult, Value};
+use ginto_syntax::Type;
+use ginto_test_support::check_with;
+use ginto_vm::{Bytecode, Compiler, Vm, optimize};
+
+fn host_builtins(output: &CapturedOutput) -> Builtins {
+    let mut builtins = Builtins::with_captured_prelude(output);
+    builtins
+        .register(
+            "host_add",
+            &[Type::U64, Type::U64],
+            Type::U64,
+            |args| match args {
+                [Value::U64(a), Value::U64(b)] => a
+                    .checked_add(*b)
+                    .map(Value::U64)
+                    .ok_or_else(|| format!("`host_add({}, {})` overflowed", a, b)),
+                _ => unreachable!(),
+            },
+        )
+        .register_generic(
+            "host_id",
+            &["T"],
+            &[Type::Param(Symbol::intern("T"))],
+            Type::Param(Symbol::intern("T")),
+            |args| Ok(args[0].clone()),
+        );
+    builtins
+}
+
+fn check_parity(path: &Path) {
+    let output = CapturedOutput::new();
+    let builtins = host_builtins(&output);
+    let source = fs::read_to_string(path).unwrap();
+    let (
+        Compilation {
+            tree,
+            resolutions,
+            types,
+            builtins,
+            ..
+        },
+        sm,
+    ) = check_with(&path.display().to_string(), &source, &[], |driver| {
+        driver.with_builtins(builtins)
+    });
+
+    let interpreted = ginto_test_support::on_interpreter_stack(|| {
+        Interpreter::new(&resolutions, &types)
+            .with_builtins(&builtins)
+            .run_modules(&tree)
+    });
+    let interpreted = RunResult::capture(&output, interpreted);
+    let mut bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+    let executed = RunResult::capture(&output, Vm::new(&bytecode).with_builtins(&builtins).run());
+    assert_eq!(interpreted, executed, "{}:\n{}", path.display(), bytecode);
+
+    optimize(&mut bytecode);
+    let optimized = RunResult::capture(&output, Vm::new(&bytecode).with_builtins(&builtins).run());
+    assert_eq!(
+        interpreted,
+        optimized,
+        "{} (optimized):\n{}",
+        path.display(),
+        bytecode
+    );
+
+    let (loaded, _) = Bytecode::load(&bytecode.save(&sm)).unwrap();
+    let reloaded = RunResult::capture(&output, Vm::new(&loaded).with_builtins(&builtins).run());
+    assert_eq!(
+        interpreted,
+        reloaded,
+        "{} (loaded):\n{}",
+        path.display(),
+        loaded
+    );
+}
+
+#[test]
+fn vm_matches_interpreter() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut paths: Vec<_> = fs::read_dir(corpus)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ginto"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        check_parity(&path);
+    }
+}
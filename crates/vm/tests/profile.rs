@@ -0,0 +1,65 @@
+//! Profiling counts the calls and instructions of each function, counting
+//! recursive calls once in the inclusive counts.
+
+use ginto_driver::Compilation;
+use ginto_eval::Value;
+use ginto_test_support::check;
+use ginto_vm::{Compiler, Profile, Vm};
+
+const SOURCE: &str = "fn fib(n: u64) -> u64:
+    if n < 2:
+        n
+    else:
+        fib(n - 1) + fib(n - 2)
+
+fn count(n: u64) -> u64:
+    if n = 0:
+        0
+    else:
+        count(n - 1)
+
+fn main() -> u64:
+    fib(10) + count(100)
+";
+
+#[test]
+fn profiles_calls_and_instructions() {
+    let (
+        Compilation {
+            tree,
+            resolutions,
+            types,
+            ..
+        },
+        sm,
+    ) = check(SOURCE);
+    let bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+
+    let mut profile = Profile::new(&bytecode);
+    let result = Vm::new(&bytecode).with_profile(&mut profile).run();
+    assert_eq!(result, Ok(Value::U64(55)));
+
+    let [fib, count, main] = ["fib", "count", "main"].map(|name| {
+        let id = bytecode.funcs.iter().position(|func| func.name == name);
+        profile.func(id.unwrap() as u32)
+    });
+    assert_eq!(fib.calls, 177);
+    // tail calls are calls too, made in the caller's frame
+    assert_eq!(count.calls, 101);
+    assert_eq!(main.calls, 1);
+    assert_eq!(fib.inclusive, fib.exclusive);
+    assert_eq!(count.inclusive, count.exclusive);
+    assert_eq!(main.inclusive, profile.total());
+    assert_eq!(
+        main.exclusive + fib.exclusive + count.exclusive,
+        profile.total()
+    );
+
+    let report = profile.report(&bytecode, &sm);
+    let hottest = report.lines().skip_while(|line| *line != "hottest lines:");
+    let first = hottest.clone().nth(1).unwrap();
+    assert!(
+        first.ends_with("main.ginto:5  fib(n - 1) + fib(n - 2)"),
+        "{report}"
+    );
+}
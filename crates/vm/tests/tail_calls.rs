@@ -0,0 +1,99 @@
+//! Tail calls reuse the caller's frame, so tail recursion far deeper than
+//! `CALL_DEPTH_LIMIT` runs on the VM.
+
+use ginto_driver::Compilation;
+use ginto_eval::{RuntimeErrorKind, Value};
+use ginto_test_support::check;
+use ginto_vm::{Compiler, Vm};
+
+const DEPTH: u64 = 1_000_000;
+
+fn run(source: &str) -> Result<Value, RuntimeErrorKind> {
+    let (
+        Compilation {
+            tree,
+            resolutions,
+            types,
+            builtins,
+            ..
+        },
+        _,
+    ) = check(source);
+    let bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+    Vm::new(&bytecode)
+        .with_builtins(&builtins)
+        .run()
+        .map_err(|error| error.kind)
+}
+
+#[test]
+fn deep_tail_recursion() {
+    let source = format!(
+        "fn count(n: u64, acc: u64) -> u64:
+    if n = 0:
+        acc
+    else:
+        count(n - 1, acc + 1)
+
+fn main() -> u64:
+    count({DEPTH}, 0)
+"
+    );
+    assert_eq!(run(&source), Ok(Value::U64(DEPTH)));
+}
+
+#[test]
+fn deep_mutual_tail_recursion() {
+    let source = format!(
+        "fn is_even(n: u64) -> bool:
+    match n:
+        0 -> true
+        _ -> is_odd(n - 1)
+
+fn is_odd(n: u64) -> bool:
+    if n = 0:
+        return false
+    return is_even(n - 1)
+
+fn main() -> bool:
+    is_even({DEPTH})
+"
+    );
+    assert_eq!(run(&source), Ok(Value::Bool(true)));
+}
+
+#[test]
+fn deep_tail_recursion_through_closures() {
+    let source = format!(
+        "fn go(step: fn(u64) -> u64, n: u64) -> u64:
+    if n = 0:
+        return 0
+    let next = step(n)
+    go(step, next)
+
+fn main() -> u64:
+    let by = 1
+    go(|n| n - by, {DEPTH})
+"
+    );
+    assert_eq!(run(&source), Ok(Value::U64(0)));
+}
+
+#[test]
+fn non_tail_recursion_still_overflows() {
+    let source = format!(
+        "fn count(n: u64) -> u64:
+    if n = 0:
+        0
+    else:
+        count(n - 1) + 1
+
+fn main() -> u64:
+    count({DEPTH})
+"
+    );
+    assert!(matches!(
+        run(&source),
+        Err(RuntimeErrorKind::StackOverflow { .. })
+    ));
+}
@@ -0,0 +1,87 @@
+//! A tracer sees every expression the interpreter evaluates, with the
+//! values of its subexpressions and what it came to.
+
+use ginto_diag::Span;
+use ginto_driver::Compilation;
+use ginto_eval::{Interpreter, Outcome, TraceEvent, Tracer, Value};
+use ginto_test_support::check;
+
+const SOURCE: &str = "fn main() -> u64:
+    let a = 2
+    double(a) + 1
+
+fn double(n: u64) -> u64:
+    n * 2
+";
+
+/// Records each event as the expression's source, function, inputs and
+/// outcome.
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+}
+
+fn text(span: Span) -> String {
+    SOURCE
+        .chars()
+        .skip(span.start.0)
+        .take(span.end.0 - span.start.0)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+impl Tracer for Recorder {
+    fn on_eval(&mut self, event: &TraceEvent) {
+        let inputs: Vec<_> = event.inputs().iter().map(Value::to_string).collect();
+        let outcome = match event.outcome() {
+            Outcome::Value(value) => value.to_string(),
+            Outcome::Returned(value) => format!("return {value}"),
+            Outcome::TailCall => "tail call".to_string(),
+            Outcome::Failed(_) => "failed".to_string(),
+        };
+        self.events.push(format!(
+            "{} in {} [{}] {}",
+            text(event.span()),
+            event.func(),
+            inputs.join(", "),
+            outcome
+        ));
+    }
+}
+
+#[test]
+fn traces_every_expression() {
+    let (
+        Compilation {
+            tree,
+            resolutions,
+            types,
+            ..
+        },
+        _,
+    ) = check(SOURCE);
+
+    let mut recorder = Recorder::default();
+    let result = Interpreter::new(&resolutions, &types)
+        .with_tracer(&mut recorder)
+        .run_modules(&tree);
+    assert_eq!(result, Ok(Value::U64(5)));
+    assert_eq!(
+        recorder.events,
+        [
+            "2 in main [] 2",
+            "let a = 2 in main [2] ()",
+            "double in main [] <fn>",
+            "a in main [] 2",
+            "n in double [] 2",
+            "2 in double [] 2",
+            "n * 2 in double [2, 2] 4",
+            "n * 2 in double [4] 4",
+            "double(a) in main [<fn>, 2] 4",
+            "1 in main [] 1",
+            "double(a) + 1 in main [4, 1] 5",
+            "let a = 2\n    double(a) + 1 in main [(), 5] 5",
+        ]
+    );
+}
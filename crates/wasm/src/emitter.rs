@@ -0,0 +1,675 @@
+use std::collections::HashMap;
+
+use ginto_codegen::{
+    IntLiteral, Lowering, Repr, Unsupported, compare_units, func_instances, param_name_span,
+};
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Label, Span};
+use ginto_resolve::{DefId, DefKind, ModuleTree, Resolutions};
+use ginto_syntax::{
+    BinOp, Expr, ExprKind, Func, MatchArm, Pattern, PatternKind, Type, UnaryOp, visit,
+};
+use ginto_typeck::{Instance, Ty, TypeckResults};
+use wasm_encoder::{
+    BlockType, CodeSection, ExportKind, ExportSection, Function, FunctionSection, Instruction,
+    Module, TypeSection, ValType,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WasmError {
+    /// A construct the WebAssembly backend cannot compile yet.
+    Unsupported {
+        what: &'static str,
+        span: Span,
+        file_id: FileId,
+    },
+}
+
+impl From<Unsupported> for WasmError {
+    fn from(err: Unsupported) -> Self {
+        WasmError::Unsupported {
+            what: err.what,
+            span: err.span,
+            file_id: err.file_id,
+        }
+    }
+}
+
+impl DiagnosticConvertible for WasmError {
+    fn into_diagnostic(self) -> Diagnostic {
+        match self {
+            WasmError::Unsupported {
+                what,
+                span,
+                file_id,
+            } => Diagnostic::error(format!("{} cannot be compiled to WebAssembly", what))
+                .with_label(
+                    Label::primary(file_id, span)
+                        .with_message("not supported by the WebAssembly backend"),
+                ),
+        }
+    }
+}
+
+/// The functions every module starts with, doing the arithmetic that traps
+/// where the interpreter reports an overflow. Division by zero traps in
+/// WebAssembly itself.
+#[derive(Debug, Clone, Copy)]
+enum Helper {
+    AddU64,
+    SubU64,
+    MulU64,
+    NegU64,
+    AddI64,
+    SubI64,
+    MulI64,
+    RemI64,
+    NegI64,
+}
+
+const HELPERS: [Helper; 9] = [
+    Helper::AddU64,
+    Helper::SubU64,
+    Helper::MulU64,
+    Helper::NegU64,
+    Helper::AddI64,
+    Helper::SubI64,
+    Helper::MulI64,
+    Helper::RemI64,
+    Helper::NegI64,
+];
+
+impl Helper {
+    fn arity(self) -> usize {
+        match self {
+            Helper::NegU64 | Helper::NegI64 => 1,
+            _ => 2,
+        }
+    }
+
+    /// The body, with the operands in locals 0 and 1 and a scratch local 2.
+    fn body(self) -> Vec<Instruction<'static>> {
+        use Instruction::*;
+        let trap_if = [If(BlockType::Empty), Unreachable, End];
+        let mut body = match self {
+            Helper::AddU64 => vec![
+                LocalGet(0),
+                LocalGet(1),
+                I64Add,
+                LocalTee(2),
+                LocalGet(0),
+                I64LtU,
+            ],
+            Helper::SubU64 => vec![LocalGet(0), LocalGet(1), I64LtU],
+            Helper::MulU64 => vec![
+                LocalGet(0),
+                LocalGet(1),
+                I64Mul,
+                LocalSet(2),
+                LocalGet(0),
+                I64Eqz,
+                If(BlockType::Result(ValType::I32)),
+                I32Const(0),
+                Else,
+                LocalGet(2),
+                LocalGet(0),
+                I64DivU,
+                LocalGet(1),
+                I64Ne,
+                End,
+            ],
+            Helper::NegU64 => vec![LocalGet(0), I64Eqz, I32Eqz],
+            Helper::AddI64 => vec![
+                LocalGet(0),
+                LocalGet(1),
+                I64Add,
+                LocalSet(2),
+                LocalGet(0),
+                LocalGet(2),
+                I64Xor,
+                LocalGet(1),
+                LocalGet(2),
+                I64Xor,
+                I64And,
+                I64Const(0),
+                I64LtS,
+            ],
+            Helper::SubI64 => vec![
+                LocalGet(0),
+                LocalGet(1),
+                I64Sub,
+                LocalSet(2),
+                LocalGet(0),
+                LocalGet(1),
+                I64Xor,
+                LocalGet(0),
+                LocalGet(2),
+                I64Xor,
+                I64And,
+                I64Const(0),
+                I64LtS,
+            ],
+            // `r / a` traps by itself for `i64::MIN / -1`
+            Helper::MulI64 => vec![
+                LocalGet(0),
+                LocalGet(1),
+                I64Mul,
+                LocalSet(2),
+                LocalGet(0),
+                I64Eqz,
+                If(BlockType::Result(ValType::I32)),
+                I32Const(0),
+                Else,
+                LocalGet(2),
+                LocalGet(0),
+                I64DivS,
+                LocalGet(1),
+                I64Ne,
+                End,
+            ],
+            Helper::RemI64 => vec![
+                LocalGet(0),
+                I64Const(i64::MIN),
+                I64Eq,
+                LocalGet(1),
+                I64Const(-1),
+                I64Eq,
+                I32And,
+            ],
+            Helper::NegI64 => vec![LocalGet(0), I64Const(i64::MIN), I64Eq],
+        };
+        body.extend(trap_if);
+        body.extend(match self {
+            Helper::AddU64 | Helper::AddI64 | Helper::MulU64 | Helper::MulI64 => {
+                vec![LocalGet(2)]
+            }
+            Helper::SubU64 => vec![LocalGet(0), LocalGet(1), I64Sub],
+            Helper::SubI64 => vec![LocalGet(2)],
+            Helper::NegU64 => vec![I64Const(0)],
+            Helper::RemI64 => vec![LocalGet(0), LocalGet(1), I64RemS],
+            Helper::NegI64 => vec![I64Const(0), LocalGet(0), I64Sub],
+        });
+        body.push(End);
+        body
+    }
+}
+
+/// Compiles a type-checked program to a WebAssembly module exporting its
+/// functions, `main` included. Functions of modules other than the root are
+/// exported under their path, like `math::add`, and a generic function
+/// once for each of its `instances`, like `id::<u64>`.
+///
+/// `u64` and `i64` are represented as `i64` and `bool` as `i32`; `()` has
+/// no representation. Function values are not supported yet.
+pub fn emit_wasm(
+    tree: &ModuleTree,
+    resolutions: &Resolutions,
+    types: &TypeckResults,
+    instances: &[Instance],
+) -> Result<Vec<u8>, Vec<WasmError>> {
+    let mut emitter = Emitter {
+        lower: Lowering::new(resolutions, types),
+        func_ids: HashMap::new(),
+        locals: HashMap::new(),
+        local_tys: Vec::new(),
+        params: 0,
+        code: Vec::new(),
+    };
+    let mut type_ids: HashMap<(Vec<ValType>, Vec<ValType>), u32> = HashMap::new();
+    let mut type_section = TypeSection::new();
+    let mut type_of = |params: Vec<ValType>, results: Vec<ValType>| {
+        let next = type_ids.len() as u32;
+        *type_ids
+            .entry((params.clone(), results.clone()))
+            .or_insert_with(|| {
+                type_section.ty().function(params, results);
+                next
+            })
+    };
+
+    let mut functions = FunctionSection::new();
+    let mut codes = CodeSection::new();
+    let mut exports = ExportSection::new();
+    for helper in HELPERS {
+        functions.function(type_of(
+            vec![ValType::I64; helper.arity()],
+            vec![ValType::I64],
+        ));
+        let mut function = Function::new([(1, ValType::I64)]);
+        for instruction in helper.body() {
+            function.instruction(&instruction);
+        }
+        codes.function(&function);
+    }
+
+    // number every function first, so calls can refer to later ones
+    let mut compiled = Vec::new();
+    for (func, instance) in func_instances(tree, resolutions, types, instances) {
+        emitter.lower.enter(func.file_id, &instance);
+        let Some(sig) = emitter.lower.signature(func.func, func.def) else {
+            continue;
+        };
+        let index = (HELPERS.len() + compiled.len()) as u32;
+        emitter.func_ids.insert(instance.clone(), index);
+        let params = sig.params.into_iter().flatten().map(val_ty).collect();
+        let results = sig.ret.map(val_ty).into_iter().collect();
+        functions.function(type_of(params, results));
+        if func.impl_decl.is_none() {
+            let name = match func.module == tree.root() {
+                true => instance.display(resolutions),
+                false => format!(
+                    "{}::{}",
+                    &tree.path(func.module)["crate::".len()..],
+                    instance.display(resolutions)
+                ),
+            };
+            exports.export(&name, ExportKind::Func, index);
+        }
+        compiled.push((func, instance));
+    }
+    for (func, instance) in compiled {
+        emitter.lower.enter(func.file_id, &instance);
+        codes.function(&emitter.compile_func(func.func));
+    }
+    if !emitter.lower.errors.is_empty() {
+        return Err(emitter
+            .lower
+            .errors
+            .into_iter()
+            .map(WasmError::from)
+            .collect());
+    }
+
+    let mut module = Module::new();
+    module
+        .section(&type_section)
+        .section(&functions)
+        .section(&exports)
+        .section(&codes);
+    Ok(module.finish())
+}
+
+/// The WebAssembly type of the values of `repr`.
+fn val_ty(repr: Repr) -> ValType {
+    match repr {
+        Repr::U64 | Repr::I64 => ValType::I64,
+        Repr::Bool => ValType::I32,
+    }
+}
+
+struct Emitter<'a> {
+    lower: Lowering<'a>,
+    /// The index of each function instance.
+    func_ids: HashMap<Instance, u32>,
+    /// The local of each variable of the function being compiled that has
+    /// a representation.
+    locals: HashMap<DefId, u32>,
+    /// The types of the locals that are not parameters.
+    local_tys: Vec<ValType>,
+    params: u32,
+    code: Vec<Instruction<'static>>,
+}
+
+impl Emitter<'_> {
+    fn expr_val_ty(&mut self, expr: &Expr) -> Option<ValType> {
+        self.lower.expr_repr(expr).map(val_ty)
+    }
+
+    fn def_val_ty(&mut self, def: DefId, span: Span) -> Option<ValType> {
+        self.lower.def_repr(def, span).map(val_ty)
+    }
+
+    fn compile_func(&mut self, func: &Func) -> Function {
+        self.locals.clear();
+        self.local_tys.clear();
+        self.params = 0;
+        for param in &func.params {
+            let span = param_name_span(param);
+            if let Some(def) = self.lower.def_at(span)
+                && self.def_val_ty(def, param.span).is_some()
+            {
+                self.locals.insert(def, self.params);
+                self.params += 1;
+            }
+        }
+        self.compile_expr(&func.body);
+        self.code.push(Instruction::End);
+
+        let mut function = Function::new(self.local_tys.iter().map(|ty| (1, *ty)));
+        for instruction in self.code.drain(..) {
+            function.instruction(&instruction);
+        }
+        function
+    }
+
+    /// The index of the local for the variable declared at `span`, if its
+    /// type has a representation.
+    fn declare_local(&mut self, span: Span) -> Option<u32> {
+        let def = self.lower.def_at(span)?;
+        if let Some(&local) = self.locals.get(&def) {
+            return Some(local);
+        }
+        let ty = self.def_val_ty(def, span)?;
+        let local = self.params + self.local_tys.len() as u32;
+        self.local_tys.push(ty);
+        self.locals.insert(def, local);
+        Some(local)
+    }
+
+    /// A scratch local of type `ty`.
+    fn temp(&mut self, ty: ValType) -> u32 {
+        self.local_tys.push(ty);
+        self.params + self.local_tys.len() as u32 - 1
+    }
+
+    fn emit(&mut self, instruction: Instruction<'static>) {
+        self.code.push(instruction);
+    }
+
+    fn block_ty(&mut self, expr: &Expr) -> BlockType {
+        match self.expr_val_ty(expr) {
+            Some(ty) => BlockType::Result(ty),
+            None => BlockType::Empty,
+        }
+    }
+
+    /// Compiles `expr` to code leaving its value, if it has a
+    /// representation, on the stack.
+    fn compile_expr(&mut self, expr: &Expr) {
+        self.compile_expr_kind(expr);
+        // code after an expression that never finishes is unreachable, which
+        // lets it expect any value on the stack
+        if self.lower.expr_ty(expr) == Some(Ty::Never) {
+            self.emit(Instruction::Unreachable);
+        }
+    }
+
+    fn compile_expr_kind(&mut self, expr: &Expr) {
+        let span = expr.span;
+        match &expr.inner {
+            ExprKind::Int(v) => self.compile_int(IntLiteral::new(
+                *v as i128,
+                self.lower.expr_ty(expr).as_ref(),
+            )),
+            ExprKind::Bool(v) => self.emit(Instruction::I32Const(*v as i32)),
+            ExprKind::Unit => {}
+            ExprKind::Error => unreachable!("program with syntax errors was compiled"),
+            ExprKind::Float(_) => self.lower.unsupported("floats", span),
+            ExprKind::Str(_) => self.lower.unsupported("strings", span),
+            ExprKind::Closure { .. } => self.lower.unsupported("closures", span),
+            ExprKind::Unary { op, expr: operand } => match op.inner {
+                UnaryOp::Neg => {
+                    if let Some(lit) = self.lower.int_literal(expr) {
+                        return self.compile_int(lit);
+                    }
+                    self.compile_expr(operand);
+                    let helper = match self.lower.expr_ty(operand) {
+                        Some(Ty::I64) => Helper::NegI64,
+                        _ => Helper::NegU64,
+                    };
+                    self.emit(Instruction::Call(helper as u32));
+                }
+                UnaryOp::Not => {
+                    self.compile_expr(operand);
+                    self.emit(Instruction::I32Eqz);
+                }
+            },
+            ExprKind::Binary { op, lhs, rhs } => self.compile_binary(op.inner, lhs, rhs),
+            ExprKind::Cast { expr: operand, ty } => {
+                self.compile_expr(operand);
+                let from = self.lower.expr_ty(operand);
+                if from == Some(Ty::Bool) && matches!(ty.inner, Type::U64 | Type::I64) {
+                    self.emit(Instruction::I64ExtendI32U);
+                }
+            }
+            ExprKind::Let { name, value, .. } => {
+                let local = self.declare_local(name.span);
+                if let Some(value) = value {
+                    self.compile_expr(value);
+                    if let Some(local) = local {
+                        self.emit(Instruction::LocalSet(local));
+                    }
+                }
+            }
+            ExprKind::Assign { name, value } => {
+                self.compile_expr(value);
+                let local = self
+                    .lower
+                    .resolve(name.span)
+                    .and_then(|def| self.locals.get(&def).copied());
+                if let Some(local) = local {
+                    self.emit(Instruction::LocalSet(local));
+                }
+            }
+            ExprKind::Block { exprs, tail } => {
+                for expr in exprs {
+                    self.compile_expr(expr);
+                    if self.expr_val_ty(expr).is_some() {
+                        self.emit(Instruction::Drop);
+                    }
+                }
+                if let Some(tail) = tail {
+                    self.compile_expr(tail);
+                }
+            }
+            ExprKind::Call { callee, args } => {
+                let instance = match &callee.inner {
+                    ExprKind::Var(_) | ExprKind::Path(_) => self
+                        .lower
+                        .resolve(callee.span)
+                        .map(|def| self.lower.instance(def, callee.span)),
+                    _ => None,
+                };
+                match instance
+                    .as_ref()
+                    .and_then(|instance| self.func_ids.get(instance))
+                {
+                    Some(&index) => {
+                        for arg in args {
+                            self.compile_expr(arg);
+                        }
+                        self.emit(Instruction::Call(index));
+                    }
+                    None => self.lower.unsupported_call(instance.as_ref(), span),
+                }
+            }
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => {
+                let index = self
+                    .lower
+                    .types
+                    .method_target(self.lower.file_id, method.span)
+                    .and_then(|def| self.func_ids.get(&self.lower.instance(def, method.span)))
+                    .copied();
+                let Some(index) = index else {
+                    return self.lower.unsupported("calls to this method", span);
+                };
+                self.compile_expr(receiver);
+                for arg in args {
+                    self.compile_expr(arg);
+                }
+                self.emit(Instruction::Call(index));
+            }
+            ExprKind::Match { scrutinee, arms } => self.compile_match(expr, scrutinee, arms),
+            ExprKind::Return(value) => {
+                if let Some(value) = value {
+                    self.compile_expr(value);
+                }
+                self.emit(Instruction::Return);
+            }
+            ExprKind::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                self.compile_expr(cond);
+                let ty = self.block_ty(expr);
+                self.emit(Instruction::If(ty));
+                self.compile_expr(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.emit(Instruction::Else);
+                    self.compile_expr(else_branch);
+                }
+                self.emit(Instruction::End);
+            }
+            ExprKind::While { cond, body } => {
+                // `br_if 1` leaves the loop, `br 0` starts it over
+                self.emit(Instruction::Block(BlockType::Empty));
+                self.emit(Instruction::Loop(BlockType::Empty));
+                self.compile_expr(cond);
+                self.emit(Instruction::I32Eqz);
+                self.emit(Instruction::BrIf(1));
+                self.compile_expr(body);
+                if self.expr_val_ty(body).is_some() {
+                    self.emit(Instruction::Drop);
+                }
+                self.emit(Instruction::Br(0));
+                self.emit(Instruction::End);
+                self.emit(Instruction::End);
+            }
+            ExprKind::Var(_) | ExprKind::Path(_) => {
+                let Some(def) = self.lower.resolve(span) else {
+                    return;
+                };
+                match self.locals.get(&def) {
+                    Some(&local) => self.emit(Instruction::LocalGet(local)),
+                    None if self.lower.resolutions.def(def).kind == DefKind::Func => {
+                        self.lower.unsupported("function values", span)
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
+    /// A literal out of range for its type traps, like it fails in the
+    /// interpreter.
+    fn compile_int(&mut self, lit: IntLiteral) {
+        match lit.bits() {
+            Some(bits) => self.emit(Instruction::I64Const(bits)),
+            None => self.emit(Instruction::Unreachable),
+        }
+    }
+
+    fn compile_binary(&mut self, op: BinOp, lhs: &Expr, rhs: &Expr) {
+        use Instruction::*;
+        match op {
+            BinOp::And => {
+                self.compile_expr(lhs);
+                self.emit(If(BlockType::Result(ValType::I32)));
+                self.compile_expr(rhs);
+                self.emit(Else);
+                self.emit(I32Const(0));
+                self.emit(End);
+                return;
+            }
+            BinOp::Or => {
+                self.compile_expr(lhs);
+                self.emit(If(BlockType::Result(ValType::I32)));
+                self.emit(I32Const(1));
+                self.emit(Else);
+                self.compile_expr(rhs);
+                self.emit(End);
+                return;
+            }
+            _ => {}
+        }
+        self.compile_expr(lhs);
+        self.compile_expr(rhs);
+        let ty = self.lower.expr_ty(lhs);
+        let instruction = match (ty, op) {
+            (Some(Ty::I64), BinOp::Add) => Call(Helper::AddI64 as u32),
+            (Some(Ty::I64), BinOp::Sub) => Call(Helper::SubI64 as u32),
+            (Some(Ty::I64), BinOp::Mul) => Call(Helper::MulI64 as u32),
+            (Some(Ty::I64), BinOp::Div) => I64DivS,
+            (Some(Ty::I64), BinOp::Mod) => Call(Helper::RemI64 as u32),
+            (Some(Ty::I64), BinOp::Less) => I64LtS,
+            (Some(Ty::I64), BinOp::Le) => I64LeS,
+            (Some(Ty::I64), BinOp::Greater) => I64GtS,
+            (Some(Ty::I64), BinOp::Ge) => I64GeS,
+            (Some(Ty::I64 | Ty::U64), BinOp::Equal) => I64Eq,
+            (Some(Ty::I64 | Ty::U64), BinOp::NotEq) => I64Ne,
+            (Some(Ty::Bool), BinOp::Equal) => I32Eq,
+            (Some(Ty::Bool), BinOp::NotEq) => I32Ne,
+            (Some(Ty::Bool), BinOp::Less) => I32LtU,
+            (Some(Ty::Bool), BinOp::Le) => I32LeU,
+            (Some(Ty::Bool), BinOp::Greater) => I32GtU,
+            (Some(Ty::Bool), BinOp::Ge) => I32GeU,
+            (Some(Ty::Unit), _) => I32Const(compare_units(op) as i32),
+            (_, BinOp::Add) => Call(Helper::AddU64 as u32),
+            (_, BinOp::Sub) => Call(Helper::SubU64 as u32),
+            (_, BinOp::Mul) => Call(Helper::MulU64 as u32),
+            (_, BinOp::Div) => I64DivU,
+            (_, BinOp::Mod) => I64RemU,
+            (_, BinOp::Less) => I64LtU,
+            (_, BinOp::Le) => I64LeU,
+            (_, BinOp::Greater) => I64GtU,
+            (_, BinOp::Ge) => I64GeU,
+            (_, BinOp::Equal) => I64Eq,
+            (_, BinOp::NotEq | BinOp::And | BinOp::Or) => I64Ne,
+        };
+        self.emit(instruction);
+    }
+
+    /// Compiles a `match` to a chain of `if`s testing the scrutinee, kept in
+    /// a local.
+    fn compile_match(&mut self, expr: &Expr, scrutinee: &Expr, arms: &[MatchArm]) {
+        self.compile_expr(scrutinee);
+        let scrutinee_ty = self.lower.expr_ty(scrutinee);
+        let local = self.expr_val_ty(scrutinee).map(|ty| {
+            let local = self.temp(ty);
+            self.emit(Instruction::LocalSet(local));
+            local
+        });
+        let ty = self.block_ty(expr);
+        for arm in arms {
+            self.compile_test(&arm.pattern, local, scrutinee_ty.as_ref());
+            self.emit(Instruction::If(ty));
+            for name in visit::pattern_bindings(&arm.pattern) {
+                if let (Some(binding), Some(local)) = (self.declare_local(name.span), local) {
+                    self.emit(Instruction::LocalGet(local));
+                    self.emit(Instruction::LocalSet(binding));
+                }
+            }
+            self.compile_expr(&arm.body);
+            self.emit(Instruction::Else);
+        }
+        // no arm matched, which the type checker rules out
+        self.emit(Instruction::Unreachable);
+        for _ in arms {
+            self.emit(Instruction::End);
+        }
+    }
+
+    /// Pushes whether the scrutinee in `local` matches `pattern`.
+    fn compile_test(&mut self, pattern: &Pattern, local: Option<u32>, ty: Option<&Ty>) {
+        use Instruction::*;
+        match (&pattern.inner, local) {
+            (PatternKind::Int(n), Some(local)) => match IntLiteral::new(*n, ty).bits() {
+                Some(bits) => {
+                    self.emit(LocalGet(local));
+                    self.emit(I64Const(bits));
+                    self.emit(I64Eq);
+                }
+                None => self.emit(I32Const(0)),
+            },
+            (PatternKind::Bool(b), Some(local)) => {
+                self.emit(LocalGet(local));
+                if !b {
+                    self.emit(I32Eqz);
+                }
+            }
+            (PatternKind::Or(alternatives), _) => {
+                for (i, alternative) in alternatives.iter().enumerate() {
+                    self.compile_test(alternative, local, ty);
+                    if i > 0 {
+                        self.emit(I32Or);
+                    }
+                }
+            }
+            _ => self.emit(I32Const(1)),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Dead code elimination keeps emitted modules down to the functions the
+//! program can run.
+
+use ginto_analysis::{DeadCodeStats, eliminate_dead_code};
+use ginto_driver::Compilation;
+use ginto_test_support::check;
+use ginto_wasm::emit_wasm;
+use wasmparser::{Parser, Payload, Validator};
+
+const SOURCE: &str = "fn main() -> u64:
+    let a = pick(3)
+    let b = if false: unused(1) else: 2
+    a + b + early(4)
+
+pub fn pick(n: u64) -> u64:
+    if true:
+        n * 2
+    else:
+        unused(n)
+
+fn early(n: u64) -> u64:
+    return n
+    unused(n)
+
+fn unused(n: u64) -> u64:
+    helper(n) + 1
+
+fn helper(n: u64) -> u64:
+    n
+";
+
+/// The names `module` exports, after validating it.
+fn exports(module: &[u8]) -> Vec<String> {
+    Validator::new().validate_all(module).unwrap();
+    let mut names = Vec::new();
+    for payload in Parser::new(0).parse_all(module) {
+        if let Payload::ExportSection(reader) = payload.unwrap() {
+            for export in reader {
+                names.push(export.unwrap().name.to_string());
+            }
+        }
+    }
+    names
+}
+
+#[test]
+fn strips_unreachable_functions_and_blocks() {
+    // the driver folds the constant conditions
+    let Compilation {
+        mut tree,
+        resolutions,
+        types,
+        instances,
+        ..
+    } = check(SOURCE).0;
+
+    let before = emit_wasm(&tree, &resolutions, &types, &instances).unwrap();
+    assert_eq!(
+        exports(&before),
+        ["main", "pick", "early", "unused", "helper"]
+    );
+    let stats = eliminate_dead_code(&mut tree, &resolutions);
+    assert_eq!(
+        stats,
+        DeadCodeStats {
+            funcs: 2,
+            blocks: 3
+        }
+    );
+    assert_eq!(
+        stats.to_string(),
+        "removed 2 unreachable functions and 3 unreachable blocks"
+    );
+    let after = emit_wasm(&tree, &resolutions, &types, &instances).unwrap();
+    assert_eq!(exports(&after), ["main", "pick", "early"]);
+    assert!(after.len() < before.len());
+}
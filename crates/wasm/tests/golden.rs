@@ -0,0 +1,72 @@
+//! Compiles every program of `tests/programs` to WebAssembly, validates the
+//! module and compares its exports, or the errors, to the `.golden` file
+//! next to it. Run with `BLESS=1` to rewrite the golden files.
+
+use std::fmt::Write;
+
+use ginto_diag::DiagnosticConvertible;
+use ginto_test_support::golden;
+use ginto_wasm::emit_wasm;
+use wasmparser::{Parser, Payload, Validator};
+
+/// The exports of `module` with their signatures, one per line.
+fn summarize(module: &[u8]) -> String {
+    let mut types = Vec::new();
+    let mut funcs = Vec::new();
+    let mut summary = String::new();
+    for payload in Parser::new(0).parse_all(module) {
+        match payload.unwrap() {
+            Payload::TypeSection(reader) => {
+                for ty in reader.into_iter_err_on_gc_types() {
+                    types.push(ty.unwrap());
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                for ty in reader {
+                    funcs.push(ty.unwrap());
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export.unwrap();
+                    let ty = &types[funcs[export.index as usize] as usize];
+                    writeln!(
+                        summary,
+                        "{}: {:?} -> {:?}",
+                        export.name,
+                        ty.params(),
+                        ty.results()
+                    )
+                    .unwrap();
+                }
+            }
+            _ => {}
+        }
+    }
+    summary
+}
+
+#[test]
+fn wasm_golden() {
+    golden(
+        env!("CARGO_MANIFEST_DIR"),
+        "golden",
+        |program| match emit_wasm(
+            &program.tree,
+            &program.resolutions,
+            &program.types,
+            &program.instances,
+        ) {
+            Ok(module) => {
+                Validator::new()
+                    .validate_all(&module)
+                    .unwrap_or_else(|err| panic!("invalid module: {}", err));
+                summarize(&module)
+            }
+            Err(errors) => errors
+                .into_iter()
+                .map(|err| format!("error: {}\n", err.into_diagnostic().message))
+                .collect(),
+        },
+    );
+}
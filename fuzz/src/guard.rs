@@ -0,0 +1,54 @@
+use std::{any::Any, fmt, panic, sync::mpsc, time::Duration};
+
+use ginto_eval::INTERPRETER_STACK_SIZE;
+
+use crate::{Outcome, run_pipeline};
+
+/// An input the pipeline failed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// A stage panicked, with the panic's message.
+    Panic(String),
+    /// The pipeline didn't finish in time.
+    Hang(Duration),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Panic(message) => write!(f, "panicked: {message}"),
+            Failure::Hang(timeout) => write!(f, "didn't finish in {timeout:?}"),
+        }
+    }
+}
+
+/// Runs `input` through the pipeline on a thread of its own, with the
+/// interpreter's stack, catching panics. A hung pipeline is left running
+/// once `timeout` has passed.
+pub fn run_guarded(input: &[u8], timeout: Duration) -> Result<Outcome, Failure> {
+    let (sender, receiver) = mpsc::channel();
+    let input = input.to_vec();
+    std::thread::Builder::new()
+        .stack_size(INTERPRETER_STACK_SIZE)
+        .spawn(move || {
+            let result = panic::catch_unwind(|| run_pipeline(&input));
+            // the receiver is gone if the input took too long
+            let _ = sender.send(result.map_err(|payload| panic_message(&*payload)));
+        })
+        .expect("failed to spawn the pipeline thread");
+    match receiver.recv_timeout(timeout) {
+        Ok(Ok(outcome)) => Ok(outcome),
+        Ok(Err(message)) => Err(Failure::Panic(message)),
+        Err(_) => Err(Failure::Hang(timeout)),
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "a panic without a message".to_string()
+    }
+}
@@ -0,0 +1,9 @@
+mod guard;
+mod minimize;
+mod mutate;
+mod pipeline;
+
+pub use guard::*;
+pub use minimize::*;
+pub use mutate::*;
+pub use pipeline::*;
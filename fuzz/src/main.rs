@@ -0,0 +1,213 @@
+use std::{
+    collections::{BTreeSet, hash_map::DefaultHasher},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand};
+use ginto_fuzz::{Failure, Rng, load_corpus, minimize_corpus, minimize_input, mutate, run_guarded};
+
+/// Fuzzes the ginto pipeline, from lexing source to running it.
+#[derive(Parser)]
+#[command(name = "ginto-fuzz")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// How long an input may take before it counts as a hang, in
+    /// milliseconds
+    #[arg(long, global = true, default_value_t = 10_000)]
+    timeout: u64,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Mutates the inputs of a corpus and runs them, stopping at the first
+    /// that panics or hangs and writing it to the artifacts directory
+    Run {
+        /// The corpus to start from
+        #[arg(default_value_os_t = default_corpus())]
+        corpus: PathBuf,
+        #[arg(long, default_value = "fuzz/artifacts")]
+        artifacts: PathBuf,
+        /// How many mutated inputs to run
+        #[arg(long, default_value_t = 100_000)]
+        iterations: u64,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Runs inputs once each, reporting those that fail
+    Check {
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+    },
+    /// Copies the inputs of a corpus that exercise something none of the
+    /// smaller ones do to another directory
+    Cmin { corpus: PathBuf, output: PathBuf },
+    /// Shrinks an input that fails to the smallest that still fails the
+    /// same way
+    Tmin {
+        input: PathBuf,
+        /// The file to write, by default the input's with the extension
+        /// `min`
+        #[arg(short)]
+        output: Option<PathBuf>,
+    },
+}
+
+fn default_corpus() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus")
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let timeout = Duration::from_millis(cli.timeout);
+    // failures are reported once, not as each panic happens
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = match cli.command {
+        Command::Run {
+            corpus,
+            artifacts,
+            iterations,
+            seed,
+        } => run(&corpus, &artifacts, iterations, seed, timeout),
+        Command::Check { inputs } => check(&inputs, timeout),
+        Command::Cmin { corpus, output } => cmin(&corpus, &output, timeout),
+        Command::Tmin { input, output } => {
+            let output = output.unwrap_or_else(|| input.with_extension("min"));
+            tmin(&input, &output, timeout)
+        }
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn run(
+    corpus: &Path,
+    artifacts: &Path,
+    iterations: u64,
+    seed: u64,
+    timeout: Duration,
+) -> Result<bool> {
+    let mut inputs: Vec<Vec<u8>> = load_corpus(corpus)?
+        .into_iter()
+        .map(|(_, input)| input)
+        .collect();
+    if inputs.is_empty() {
+        inputs.push(Vec::new());
+    }
+    let mut covered = BTreeSet::new();
+    for input in &inputs {
+        match run_guarded(input, timeout) {
+            Ok(outcome) => covered.extend(outcome.features),
+            Err(failure) => return save_failure(artifacts, input, &failure),
+        }
+    }
+    println!(
+        "{} inputs with {} features, seed {seed}",
+        inputs.len(),
+        covered.len()
+    );
+
+    let mut rng = Rng::new(seed);
+    for iteration in 1..=iterations {
+        let input = mutate(&inputs[rng.below(inputs.len())], &inputs, &mut rng);
+        match run_guarded(&input, timeout) {
+            // inputs exercising something new are mutated further
+            Ok(outcome) if !outcome.features.is_subset(&covered) => {
+                covered.extend(outcome.features);
+                inputs.push(input);
+            }
+            Ok(_) => {}
+            Err(failure) => return save_failure(artifacts, &input, &failure),
+        }
+        if iteration % 10_000 == 0 {
+            println!(
+                "{iteration} runs, {} inputs with {} features",
+                inputs.len(),
+                covered.len()
+            );
+        }
+    }
+    Ok(true)
+}
+
+fn save_failure(artifacts: &Path, input: &[u8], failure: &Failure) -> Result<bool> {
+    let mut hasher = DefaultHasher::new();
+    input.hash(&mut hasher);
+    let kind = match failure {
+        Failure::Panic(_) => "crash",
+        Failure::Hang(_) => "hang",
+    };
+    fs::create_dir_all(artifacts)?;
+    let path = artifacts.join(format!("{kind}-{:016x}", hasher.finish()));
+    fs::write(&path, input)?;
+    println!("input {failure}, written to {}", path.display());
+    Ok(false)
+}
+
+fn check(inputs: &[PathBuf], timeout: Duration) -> Result<bool> {
+    let mut ok = true;
+    for path in inputs {
+        let input = fs::read(path)?;
+        match run_guarded(&input, timeout) {
+            Ok(outcome) => println!("{}: stopped at {}", path.display(), outcome.stage),
+            Err(failure) => {
+                println!("{}: {failure}", path.display());
+                ok = false;
+            }
+        }
+    }
+    Ok(ok)
+}
+
+fn cmin(corpus: &Path, output: &Path, timeout: Duration) -> Result<bool> {
+    let mut entries = Vec::new();
+    let mut inputs = Vec::new();
+    for (path, input) in load_corpus(corpus)? {
+        match run_guarded(&input, timeout) {
+            Ok(outcome) => {
+                entries.push(path);
+                inputs.push((input, outcome.features));
+            }
+            Err(failure) => println!("{}: {failure}, left out", path.display()),
+        }
+    }
+    let kept = minimize_corpus(&inputs);
+    fs::create_dir_all(output)?;
+    for &i in &kept {
+        let name = entries[i].file_name().expect("corpus entries are files");
+        fs::write(output.join(name), &inputs[i].0)?;
+    }
+    println!("kept {} of {} inputs", kept.len(), inputs.len());
+    Ok(true)
+}
+
+fn tmin(input: &Path, output: &Path, timeout: Duration) -> Result<bool> {
+    let input = fs::read(input)?;
+    let Err(failure) = run_guarded(&input, timeout) else {
+        println!("the input doesn't fail");
+        return Ok(false);
+    };
+    let minimized = minimize_input(&input, |candidate| {
+        run_guarded(candidate, timeout).err().as_ref() == Some(&failure)
+    });
+    fs::write(output, &minimized)?;
+    println!(
+        "{failure}: shrunk from {} to {} bytes, written to {}",
+        input.len(),
+        minimized.len(),
+        output.display()
+    );
+    Ok(true)
+}
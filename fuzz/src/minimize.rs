@@ -0,0 +1,66 @@
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Shrinks `input` while `fails` holds for it, removing chunks of half its
+/// length, then of ever smaller ones down to single bytes.
+pub fn minimize_input(input: &[u8], mut fails: impl FnMut(&[u8]) -> bool) -> Vec<u8> {
+    let mut best = input.to_vec();
+    let mut chunk = best.len() / 2;
+    while chunk > 0 {
+        let mut removed = false;
+        let mut start = 0;
+        while start < best.len() {
+            let end = (start + chunk).min(best.len());
+            let mut candidate = best[..start].to_vec();
+            candidate.extend_from_slice(&best[end..]);
+            if fails(&candidate) {
+                best = candidate;
+                removed = true;
+            } else {
+                start += chunk;
+            }
+        }
+        if !removed {
+            chunk /= 2;
+        }
+    }
+    best
+}
+
+/// The inputs of a corpus to keep so that every feature any of them has is
+/// still had: each input, smallest first, is kept if it has a feature the
+/// ones kept before it don't. Returns their indices in `corpus`.
+pub fn minimize_corpus(corpus: &[(Vec<u8>, BTreeSet<String>)]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..corpus.len()).collect();
+    order.sort_by_key(|&i| corpus[i].0.len());
+    let mut covered = BTreeSet::new();
+    let mut kept = Vec::new();
+    for i in order {
+        let features = &corpus[i].1;
+        if !features.is_subset(&covered) {
+            covered.extend(features.iter().cloned());
+            kept.push(i);
+        }
+    }
+    kept.sort_unstable();
+    kept
+}
+
+/// The files of the corpus in `dir` with their contents, in order.
+pub fn load_corpus(dir: &Path) -> io::Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| fs::read(&path).map(|input| (path, input)))
+        .collect()
+}
@@ -0,0 +1,138 @@
+/// The longest input a mutation makes.
+pub const MAX_INPUT_LEN: usize = 4096;
+
+/// Pieces of ginto that mutations insert, so that inputs keep looking like
+/// programs.
+const DICTIONARY: &[&str] = &[
+    "fn ",
+    "let ",
+    "mut ",
+    "mod ",
+    "use ",
+    "pub ",
+    "match ",
+    "return",
+    "if ",
+    "else",
+    " as ",
+    "static_assert(",
+    "trait ",
+    "impl ",
+    " for ",
+    "u64",
+    "i64",
+    "bool",
+    "str",
+    "true",
+    "false",
+    "not ",
+    "main",
+    "self",
+    "Self",
+    "crate::",
+    "print",
+    "(",
+    ")",
+    ",",
+    ".",
+    ":",
+    "::",
+    "_",
+    "->",
+    "|",
+    "||",
+    "&&",
+    "+",
+    "-",
+    "*",
+    "/",
+    " mod ",
+    "=",
+    "<>",
+    "<",
+    "<=",
+    ">",
+    ">=",
+    "\n",
+    "\n    ",
+    "\n        ",
+    "\"",
+    "{",
+    "}",
+    "\\",
+    "0",
+    "1",
+    "18446744073709551615",
+    "9223372036854775808",
+    "x",
+    "é",
+];
+
+/// A xorshift generator, so that a run is reproduced from its seed.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // the state must not be zero
+        Self(seed ^ 0x9e37_79b9_7f4a_7c15 | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number below `n`, or 0 if `n` is.
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            (self.next_u64() % n as u64) as usize
+        }
+    }
+}
+
+/// Changes `input` a few times over: inserting pieces of ginto, deleting,
+/// duplicating or overwriting bytes, splicing in part of another input of
+/// `corpus` or swapping two lines.
+pub fn mutate(input: &[u8], corpus: &[Vec<u8>], rng: &mut Rng) -> Vec<u8> {
+    let mut out = input.to_vec();
+    for _ in 0..1 + rng.below(4) {
+        let pos = rng.below(out.len() + 1);
+        let len = 1 + rng.below(8);
+        match rng.below(6) {
+            0 => {
+                let word = DICTIONARY[rng.below(DICTIONARY.len())];
+                out.splice(pos..pos, word.bytes());
+            }
+            1 => {
+                let end = (pos + len).min(out.len());
+                out.drain(pos..end);
+            }
+            2 => {
+                let end = (pos + len).min(out.len());
+                let piece = out[pos..end].to_vec();
+                let at = rng.below(out.len() + 1);
+                out.splice(at..at, piece);
+            }
+            3 if pos < out.len() => out[pos] = rng.next_u64() as u8,
+            4 if !corpus.is_empty() => {
+                let other = &corpus[rng.below(corpus.len())];
+                let start = rng.below(other.len() + 1);
+                let end = (start + len * 8).min(other.len());
+                out.splice(pos..pos, other[start..end].iter().copied());
+            }
+            _ => {
+                let mut lines: Vec<&[u8]> = out.split(|&byte| byte == b'\n').collect();
+                let (a, b) = (rng.below(lines.len()), rng.below(lines.len()));
+                lines.swap(a, b);
+                out = lines.join(&b'\n');
+            }
+        }
+    }
+    out.truncate(MAX_INPUT_LEN);
+    out
+}
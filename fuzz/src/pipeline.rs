@@ -0,0 +1,178 @@
+use std::{collections::BTreeSet, fmt, io, path::Path, sync::Arc, time::Duration};
+
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, Interner, SourceManager, Span};
+use ginto_driver::{self as driver, Driver};
+use ginto_eval::{Builtins, CapturedOutput, Interpreter, Limits};
+use ginto_lint::{LintLevels, LintStore};
+use ginto_syntax::{ItemKind, Lexer, Parser};
+
+/// What a fuzzed program may use when it runs, so that every input
+/// finishes.
+pub const LIMITS: Limits = Limits {
+    max_steps: Some(100_000),
+    max_call_depth: Some(256),
+    max_heap_values: Some(10_000),
+    timeout: Some(Duration::from_secs(2)),
+};
+
+/// The stages of the pipeline, in the order an input goes through them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    Lex,
+    Parse,
+    Resolve,
+    Typecheck,
+    Analysis,
+    Run,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stage::Lex => "lex",
+            Stage::Parse => "parse",
+            Stage::Resolve => "resolve",
+            Stage::Typecheck => "typecheck",
+            Stage::Analysis => "analysis",
+            Stage::Run => "run",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How far an input got through the pipeline and what it exercised on the
+/// way: the stage it stopped at, the kinds of tokens it has and the codes
+/// and messages of the diagnostics it got. Inputs with the same features
+/// are alike to the fuzzer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub stage: Stage,
+    pub features: BTreeSet<String>,
+}
+
+/// Runs `input` through the lexer, the parser, the checks of the driver
+/// and, if it gets that far, the interpreter, with the `LIMITS`. Panics if a stage panics or breaks an invariant:
+///
+/// - every token and every diagnostic span lies within the file, on
+///   character boundaries
+pub fn run_pipeline(input: &[u8]) -> Outcome {
+    // the names of an input are freed with it
+    let _session = Arc::new(Interner::new()).enter();
+    let source = String::from_utf8_lossy(input).into_owned();
+    let mut sm = SourceManager::new();
+    let file = sm.add_file("fuzz.ginto".to_string(), source);
+    let mut features = BTreeSet::new();
+    let (stage, diagnostics) = run_stages(&mut sm, file, &mut features);
+    let source = &sm.get_file(file).expect("the input is added").source;
+    for diagnostic in &diagnostics {
+        let labels = diagnostic
+            .labels
+            .iter()
+            .filter(|label| label.file_id == file);
+        for label in labels {
+            check_span(
+                source,
+                label.span,
+                &format!("diagnostic `{}`", diagnostic.message),
+            );
+        }
+        features.insert(match &diagnostic.code {
+            Some(code) => format!("{stage}:{code}"),
+            None => format!("{stage}:{}", diagnostic.message),
+        });
+    }
+    features.insert(format!("stage:{stage}"));
+    Outcome { stage, features }
+}
+
+/// Runs the stages of the pipeline on `file` until one reports errors,
+/// returning it with them. The kinds of tokens of the file are added to
+/// `features`.
+fn run_stages(
+    sm: &mut SourceManager,
+    file: FileId,
+    features: &mut BTreeSet<String>,
+) -> (Stage, Vec<Diagnostic>) {
+    let source = sm
+        .get_file(file)
+        .expect("the input is added")
+        .source
+        .clone();
+    let tokens = match Lexer::new(file, &source).lex_all() {
+        Ok(tokens) => tokens,
+        Err(errors) => return (Stage::Lex, diagnostics(errors)),
+    };
+    for token in &tokens {
+        check_span(&source, token.span, &format!("token {:?}", token.inner));
+        features.insert(format!("token:{}", token.inner));
+    }
+    let mut parser = Parser::new(file, tokens);
+    parser.parse_program();
+    if !parser.errors().is_empty() {
+        return (Stage::Parse, diagnostics(parser.errors().to_vec()));
+    }
+
+    // modules declared by the input are never found
+    let mut read = |_: &SourceManager, _: &Path| Err(io::Error::from(io::ErrorKind::NotFound));
+    let output = CapturedOutput::new();
+    let mut lints = LintStore::with_builtin_lints();
+    let levels = LintLevels::new();
+    let checked = Driver::new()
+        .with_builtins(Builtins::with_captured_prelude(&output))
+        .with_lints(&mut lints, &levels)
+        .with_reader(&mut read)
+        .load(sm, file);
+    let stage = match checked.failed {
+        // the input itself lexed and parsed, so only its modules are missing
+        Some(driver::Stage::Load | driver::Stage::Resolve) => Stage::Resolve,
+        Some(driver::Stage::Typecheck) => Stage::Typecheck,
+        Some(_) => Stage::Analysis,
+        None => Stage::Run,
+    };
+    let (compilation, diagnostics) = checked.into_compilation();
+    let Some(compilation) = compilation else {
+        return (stage, diagnostics);
+    };
+    let has_main = compilation
+        .tree
+        .module(compilation.tree.root())
+        .program
+        .items
+        .iter()
+        .any(|item| matches!(&item.inner, ItemKind::Func(func) if func.name.inner == "main"));
+    if !has_main {
+        return (Stage::Analysis, diagnostics);
+    }
+
+    let result = Interpreter::new(&compilation.resolutions, &compilation.types)
+        .with_builtins(&compilation.builtins)
+        .with_limits(LIMITS)
+        .run_modules(&compilation.tree);
+    (
+        Stage::Run,
+        diagnostics
+            .into_iter()
+            .chain(result.err().map(|err| err.into_diagnostic()))
+            .collect(),
+    )
+}
+
+fn diagnostics<E: DiagnosticConvertible>(errors: Vec<E>) -> Vec<Diagnostic> {
+    errors
+        .into_iter()
+        .map(DiagnosticConvertible::into_diagnostic)
+        .collect()
+}
+
+fn check_span(source: &str, span: Span, what: &str) {
+    let (start, end) = (span.start.0, span.end.0);
+    assert!(
+        start <= end && end <= source.len(),
+        "{what} has span {start}..{end} outside the {} bytes of the file",
+        source.len()
+    );
+    assert!(
+        source.is_char_boundary(start) && source.is_char_boundary(end),
+        "{what} has span {start}..{end} splitting a character"
+    );
+}
@@ -0,0 +1,73 @@
+//! Runs the seed corpus, and mutations of it from a fixed seed, through
+//! the pipeline the way `ginto-fuzz run` does, so that `cargo test` finds
+//! what a fuzzing run would find first.
+
+use std::{collections::BTreeSet, path::Path, time::Duration};
+
+use ginto_fuzz::{
+    Rng, Stage, load_corpus, minimize_corpus, minimize_input, mutate, run_guarded, run_pipeline,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+const MUTATIONS: usize = 1000;
+
+fn corpus() -> Vec<Vec<u8>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus");
+    let corpus = load_corpus(&dir).unwrap();
+    assert!(!corpus.is_empty());
+    corpus.into_iter().map(|(_, input)| input).collect()
+}
+
+#[test]
+fn runs_the_seed_corpus() {
+    for input in corpus() {
+        if let Err(failure) = run_guarded(&input, TIMEOUT) {
+            panic!("{failure} on\n{}", String::from_utf8_lossy(&input));
+        }
+    }
+}
+
+#[test]
+fn runs_mutations_of_the_seed_corpus() {
+    let corpus = corpus();
+    let mut rng = Rng::new(0);
+    for _ in 0..MUTATIONS {
+        let input = mutate(&corpus[rng.below(corpus.len())], &corpus, &mut rng);
+        if let Err(failure) = run_guarded(&input, TIMEOUT) {
+            panic!("{failure} on\n{}", String::from_utf8_lossy(&input));
+        }
+    }
+}
+
+#[test]
+fn stops_programs_running_forever() {
+    let outcome = run_pipeline(b"fn main() -> u64:\n    main()\n");
+    assert_eq!(outcome.stage, Stage::Run);
+    assert!(
+        outcome
+            .features
+            .iter()
+            .any(|feature| feature.starts_with("run:"))
+    );
+}
+
+#[test]
+fn minimizes_inputs_and_corpora() {
+    let minimized = minimize_input(b"fn main():\n    print(1 / 0)\n", |input| {
+        input.windows(3).any(|window| window == b"1 /")
+    });
+    assert_eq!(minimized, b"1 /");
+
+    let features = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<BTreeSet<_>>()
+    };
+    let corpus = [
+        (b"abc".to_vec(), features(&["a", "b"])),
+        (b"a".to_vec(), features(&["a"])),
+        (b"bc".to_vec(), features(&["b", "c"])),
+    ];
+    assert_eq!(minimize_corpus(&corpus), [1, 2]);
+}
@@ -0,0 +1,211 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use ginto_diag::{
+    Diagnostic, DiagnosticConvertible, DiagnosticRenderer, Entered, FileId, Interner,
+    PlainDiagnosticRenderer, SourceManager, Span,
+};
+use ginto_driver::{Compilation, Driver};
+use ginto_lint::{LintLevels, LintStore};
+use ginto_resolve::{ModuleTree, Resolutions};
+use ginto_syntax::{Lexer, Token};
+use ginto_typeck::{Ty, TypeckResults};
+
+use crate::{Value, run};
+
+/// Compiles programs for tools built on ginto, keeping the files of every
+/// program it compiles so their diagnostics can be rendered. The names of
+/// its programs are interned in a session of its own.
+///
+/// ```
+/// let mut compiler = ginto::Compiler::new();
+/// let result = compiler.compile_source("main.ginto", "fn main() -> u64:\n    6 * 7\n");
+/// assert!(result.diagnostics.is_empty());
+/// let program = result.program.unwrap();
+/// assert_eq!(program.run(), Ok(ginto::Value::U64(42)));
+/// ```
+pub struct Compiler {
+    sm: SourceManager,
+    lints: LintStore,
+    levels: LintLevels,
+    interner: Arc<Interner>,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compiler {
+    /// A compiler checking programs against the prelude, with the builtin
+    /// lints at their default levels.
+    pub fn new() -> Self {
+        Self {
+            sm: SourceManager::new(),
+            lints: LintStore::with_builtin_lints(),
+            levels: LintLevels::new(),
+            interner: Arc::new(Interner::new()),
+        }
+    }
+
+    /// Runs the lints at `levels` instead.
+    pub fn with_lint_levels(mut self, levels: LintLevels) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Compiles the program `text`, naming its file `name`. The modules it
+    /// declares are read relative to `name`.
+    pub fn compile_source(&mut self, name: &str, text: &str) -> CompileResult {
+        let root = self.sm.add_file(name.to_string(), text.to_string());
+        self.compile(root)
+    }
+
+    /// Compiles the program whose root file is at `root`, with the files of
+    /// the modules it declares.
+    pub fn compile_files(&mut self, root: impl AsRef<Path>) -> CompileResult {
+        match self.sm.set_file_from_path(root.as_ref()) {
+            Ok(root) => self.compile(root),
+            Err(err) => CompileResult {
+                program: None,
+                diagnostics: vec![err.into_diagnostic()],
+            },
+        }
+    }
+
+    fn compile(&mut self, root: FileId) -> CompileResult {
+        let _session = self.interner.clone().enter();
+        let (compilation, diagnostics) = Driver::new()
+            .with_lints(&mut self.lints, &self.levels)
+            .check(&mut self.sm, root);
+        let program = compilation.map(|compilation| {
+            let tokens = compilation
+                .tree
+                .modules()
+                .filter_map(|(_, module)| {
+                    let file_id = module.program.file_id;
+                    let source = &self.sm.get_file(file_id)?.source;
+                    Some((file_id, Lexer::new(file_id, source).lex_all().ok()?))
+                })
+                .collect();
+            Program {
+                compilation,
+                root,
+                tokens,
+                interner: self.interner.clone(),
+            }
+        });
+        CompileResult {
+            program,
+            diagnostics,
+        }
+    }
+
+    /// The files of the programs compiled so far, which the labels of their
+    /// diagnostics point into.
+    pub fn source_manager(&self) -> &SourceManager {
+        &self.sm
+    }
+
+    /// `diagnostic` as the command line prints it, with the source it
+    /// points at.
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        PlainDiagnosticRenderer.render(&self.sm, diagnostic.clone())
+    }
+}
+
+/// What compiling a program came to.
+#[derive(Debug)]
+pub struct CompileResult {
+    /// The program, if it has no errors.
+    pub program: Option<Program>,
+    /// The errors of the program, or the warnings of one without errors.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A program that passed every check, with its files' tokens, syntax trees
+/// and types.
+pub struct Program {
+    compilation: Compilation,
+    root: FileId,
+    tokens: HashMap<FileId, Vec<Token>>,
+    interner: Arc<Interner>,
+}
+
+impl std::fmt::Debug for Program {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Program")
+            .field("root", &self.root)
+            .field("files", &self.tokens.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Program {
+    /// The file the program was compiled from.
+    pub fn root(&self) -> FileId {
+        self.root
+    }
+
+    /// The root file and the files of the modules the program declares.
+    pub fn files(&self) -> impl Iterator<Item = FileId> + '_ {
+        self.compilation
+            .tree
+            .modules()
+            .map(|(_, module)| module.program.file_id)
+    }
+
+    /// Enters the session the program's names were interned in, which the
+    /// names of its tokens and syntax trees are read in until the returned
+    /// guard is dropped.
+    pub fn enter(&self) -> Entered {
+        self.interner.clone().enter()
+    }
+
+    /// The tokens of `file`.
+    pub fn tokens(&self, file: FileId) -> Option<&[Token]> {
+        self.tokens.get(&file).map(Vec::as_slice)
+    }
+
+    /// The syntax tree of `file`, with its constants folded.
+    pub fn ast(&self, file: FileId) -> Option<&ginto_syntax::Program> {
+        self.compilation
+            .tree
+            .modules()
+            .map(|(_, module)| &module.program)
+            .find(|program| program.file_id == file)
+    }
+
+    /// The type of the expression at `span` of `file`.
+    pub fn type_at(&self, file: FileId, span: Span) -> Option<&Ty> {
+        self.compilation.types.expr_ty(file, span)
+    }
+
+    pub fn module_tree(&self) -> &ModuleTree {
+        &self.compilation.tree
+    }
+
+    pub fn resolutions(&self) -> &Resolutions {
+        &self.compilation.resolutions
+    }
+
+    pub fn types(&self) -> &TypeckResults {
+        &self.compilation.types
+    }
+
+    /// Runs the program's `main` function in the interpreter, returning its
+    /// value.
+    pub fn run(&self) -> Result<Value, Vec<Diagnostic>> {
+        let Compilation {
+            tree,
+            resolutions,
+            types,
+            builtins,
+            ..
+        } = &self.compilation;
+        let _session = self.enter();
+        run(builtins, resolutions, types, |interpreter| {
+            interpreter.run_modules(tree)
+        })
+    }
+}
@@ -0,0 +1,228 @@
+use std::{io::Write, path::Path};
+
+use clap::ValueEnum;
+use ginto_analysis::eliminate_dead_code;
+use ginto_diag::{Diagnostic, DiagnosticConvertible, FileId, SourceFile, SourceManager};
+use ginto_driver::Compilation;
+use ginto_lint::{LintLevels, LintStore};
+use ginto_syntax::{Lexer, Parser, Token, ast_to_json, pretty_print};
+use ginto_vm::{Compiler, optimize};
+
+use crate::{Options, STDIN_PATH, check, output_path, session::Session};
+
+/// A stage of the pipeline whose output `--emit` writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EmitKind {
+    /// The tokens of the root file, one per line
+    Tokens,
+    /// The syntax tree of the root file
+    Ast,
+    /// The syntax tree of the root file as JSON
+    AstJson,
+    /// The root file printed back from its syntax tree
+    Pretty,
+    /// The program's bytecode, disassembled
+    Bytecode,
+    /// A WebAssembly module
+    Wasm,
+    /// A C translation unit
+    C,
+    /// An LLVM IR module
+    LlvmIr,
+}
+
+impl EmitKind {
+    fn extension(self) -> &'static str {
+        match self {
+            EmitKind::Tokens => "tokens",
+            EmitKind::Ast => "ast",
+            EmitKind::AstJson => "ast.json",
+            EmitKind::Pretty => "pretty.ginto",
+            EmitKind::Bytecode => "bytecode",
+            EmitKind::Wasm => "wasm",
+            EmitKind::C => "c",
+            EmitKind::LlvmIr => "ll",
+        }
+    }
+
+    /// Whether the stage only parses the root file, so its output is
+    /// written even for programs that don't check.
+    fn is_syntax(self) -> bool {
+        matches!(
+            self,
+            EmitKind::Tokens | EmitKind::Ast | EmitKind::AstJson | EmitKind::Pretty
+        )
+    }
+}
+
+/// Writes the output of each stage in `options.emit` for the program whose
+/// root file is `file`, read from `path`. Returns whether every stage
+/// succeeded.
+pub fn emit(
+    session: &mut Session,
+    sm: &mut SourceManager,
+    file: FileId,
+    path: &Path,
+    options: &Options,
+    lints: &mut LintStore,
+    levels: &LintLevels,
+) -> bool {
+    let mut kinds = Vec::new();
+    for &kind in &options.emit {
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    if kinds.iter().any(|kind| kind.is_syntax()) {
+        let Some(source_file) = sm.get_file(file) else {
+            return false;
+        };
+        let tokens = match Lexer::new(file, &source_file.source).lex_all() {
+            Ok(tokens) => tokens,
+            Err(errors) => {
+                session.report_compilation(sm, errors.into_iter().map(|err| err.into_diagnostic()));
+                return false;
+            }
+        };
+        if kinds.contains(&EmitKind::Tokens)
+            && !write_output(
+                path,
+                EmitKind::Tokens,
+                options,
+                dump_tokens(source_file, &tokens),
+            )
+        {
+            return false;
+        }
+        let mut parser = Parser::new(file, tokens);
+        let program = parser.parse_program();
+        let parsed = kinds
+            .iter()
+            .any(|kind| kind.is_syntax() && *kind != EmitKind::Tokens);
+        if parsed && !parser.errors().is_empty() {
+            session.report_compilation(
+                sm,
+                parser
+                    .errors()
+                    .iter()
+                    .map(|err| err.clone().into_diagnostic()),
+            );
+            return false;
+        }
+        for &kind in &kinds {
+            let output = match kind {
+                EmitKind::Ast => format!("{program:#?}\n"),
+                EmitKind::AstJson => ast_to_json(&program),
+                EmitKind::Pretty => pretty_print(&program),
+                _ => continue,
+            };
+            if !write_output(path, kind, options, output.into_bytes()) {
+                return false;
+            }
+        }
+    }
+    if kinds.iter().all(|kind| kind.is_syntax()) {
+        return true;
+    }
+
+    let Some(Compilation {
+        mut tree,
+        resolutions,
+        types,
+        instances,
+        ..
+    }) = check(session, sm, file, lints, levels)
+    else {
+        return false;
+    };
+    let stats = eliminate_dead_code(&mut tree, &resolutions);
+    if options.verbose > 0 {
+        eprintln!("note: {stats}");
+    }
+    for &kind in &kinds {
+        let output = match kind {
+            EmitKind::Bytecode => {
+                let mut bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+                if options.optimize {
+                    optimize(&mut bytecode);
+                }
+                Ok(bytecode.to_string().into_bytes())
+            }
+            EmitKind::Wasm => ginto_wasm::emit_wasm(&tree, &resolutions, &types, &instances)
+                .map_err(into_diagnostics),
+            EmitKind::C => ginto_c::emit_c(&tree, &resolutions, &types, &instances)
+                .map(String::into_bytes)
+                .map_err(into_diagnostics),
+            EmitKind::LlvmIr => ginto_llvm::emit_llvm_ir(&tree, &resolutions, &types, &instances)
+                .map(String::into_bytes)
+                .map_err(into_diagnostics),
+            _ => continue,
+        };
+        match output {
+            Ok(output) => {
+                if !write_output(path, kind, options, output) {
+                    return false;
+                }
+            }
+            Err(errors) => {
+                session.report(sm, errors);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Lists the tokens of `file` one per line, after the line and column they
+/// start at.
+fn dump_tokens(file: &SourceFile, tokens: &[Token]) -> Vec<u8> {
+    let mut out = String::new();
+    for token in tokens {
+        let line_col = file.line_col(token.span.start);
+        out.push_str(&format!(
+            "{}:{} {:?}\n",
+            line_col.line, line_col.col, token.inner
+        ));
+    }
+    out.into_bytes()
+}
+
+/// Writes the output of the stage `kind` for the program at `path` where
+/// `options` say. Returns whether it succeeded.
+fn write_output(path: &Path, kind: EmitKind, options: &Options, output: Vec<u8>) -> bool {
+    let output_path = match (&options.output, &options.out_dir) {
+        (Some(output_path), _) if output_path == Path::new(STDIN_PATH) => {
+            return match std::io::stdout().write_all(&output) {
+                Ok(()) => true,
+                Err(err) => {
+                    eprintln!("error: cannot write to standard output: {err}");
+                    false
+                }
+            };
+        }
+        (Some(output_path), _) => output_path.clone(),
+        (None, Some(dir)) => {
+            if let Err(err) = std::fs::create_dir_all(dir) {
+                eprintln!("error: cannot create `{}`: {err}", dir.display());
+                return false;
+            }
+            let name = output_path(path, kind.extension());
+            dir.join(name.file_name().unwrap_or(name.as_os_str()))
+        }
+        (None, None) => output_path(path, kind.extension()),
+    };
+    match std::fs::write(&output_path, output) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("error: cannot write `{}`: {err}", output_path.display());
+            false
+        }
+    }
+}
+
+fn into_diagnostics<E: DiagnosticConvertible>(errors: Vec<E>) -> Vec<Diagnostic> {
+    errors
+        .into_iter()
+        .map(DiagnosticConvertible::into_diagnostic)
+        .collect()
+}
@@ -0,0 +1,76 @@
+use std::io::IsTerminal;
+
+use ginto_diag::{ColorChoice, ERROR_CODES, find_error_code};
+
+/// Prints the explanation of the error or warning `code`, or every code
+/// with its summary for `list`. Returns whether the code exists.
+pub fn explain(code: &str) -> bool {
+    if code == "list" {
+        for error_code in ERROR_CODES {
+            println!("{}  {}", error_code.code, error_code.summary());
+        }
+        return true;
+    }
+    match find_error_code(code) {
+        Some(error_code) => {
+            // `--explain` takes no other options, `--color` included
+            let styled = ColorChoice::Auto.use_color(std::io::stdout().is_terminal());
+            print!("{}", render_markdown(error_code.explanation, styled));
+            true
+        }
+        None => {
+            eprintln!("error: `{code}` is not a known code, `--explain list` lists them");
+            false
+        }
+    }
+}
+
+const BOLD: &str = "\x1b[1m";
+const CODE: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders the markdown of an explanation for the terminal: code blocks are
+/// indented and their fences dropped, and with `styled`, headings are bold
+/// and code is colored instead of quoted in backticks.
+pub fn render_markdown(markdown: &str, styled: bool) -> String {
+    let mut out = String::new();
+    let mut in_code_block = false;
+    for line in markdown.lines() {
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            if styled {
+                out.push_str(&format!("    {CODE}{line}{RESET}"));
+            } else {
+                out.push_str(&format!("    {line}"));
+            }
+        } else if let Some(heading) = line.strip_prefix('#') {
+            let heading = heading.trim_start_matches('#').trim();
+            if styled {
+                out.push_str(&format!("{BOLD}{heading}{RESET}"));
+            } else {
+                out.push_str(heading);
+            }
+        } else if styled {
+            out.push_str(&style_inline_code(line));
+        } else {
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Colors the text between backticks, dropping the backticks.
+fn style_inline_code(line: &str) -> String {
+    let mut out = String::new();
+    for (i, part) in line.split('`').enumerate() {
+        match i % 2 {
+            0 => out.push_str(part),
+            _ => out.push_str(&format!("{CODE}{part}{RESET}")),
+        }
+    }
+    out
+}
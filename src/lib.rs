@@ -0,0 +1,276 @@
+//! Embeds ginto in Rust applications.
+//!
+//! ```
+//! let value = ginto::eval_str("let x = 6\nx * 7").unwrap();
+//! assert_eq!(value, ginto::Value::U64(42));
+//! ```
+//!
+//! Tools reach the tokens, syntax trees and types of programs through a
+//! [`Compiler`]. The items of this crate follow semantic versioning; the
+//! `ginto_*` crates re-exported for the types they hold don't, so their
+//! details may change in any release.
+
+use std::sync::Arc;
+
+pub use ginto_diag::{self as diag, Diagnostic, FileId, SourceManager, Span};
+use ginto_diag::{DiagnosticConvertible, Interner, Severity, Spanned, Symbol};
+use ginto_driver::{Compilation, Driver, Stage};
+pub use ginto_eval::Value;
+use ginto_eval::{Builtins, INTERPRETER_STACK_SIZE, Interpreter, RuntimeError};
+use ginto_lint::{LintLevels, LintStore};
+use ginto_resolve::{ModuleTree, Resolutions, load_modules};
+pub use ginto_syntax::{self as syntax, Token, TokenKind};
+use ginto_syntax::{Expr, ExprKind, Func, Item, ItemKind, Lexer, Parser};
+use ginto_typeck::TypeckResults;
+pub use ginto_typeck::{self as typeck, Ty};
+
+mod compiler;
+pub use compiler::*;
+
+/// Checks and runs `source` with the prelude, returning its value.
+///
+/// A source starting with an item is a program, whose `main` function is
+/// run. Any other source is a sequence of statements, whose value is the
+/// value of the last one.
+///
+/// The labels of the diagnostics point into `source` as the first file of
+/// a `SourceManager`, so they can be rendered by adding it to a new one.
+pub fn eval_str(source: &str) -> Result<Value, Vec<Diagnostic>> {
+    // the names of `source` are freed with its session
+    let _session = Arc::new(Interner::new()).enter();
+    let mut sm = SourceManager::new();
+    let file_id = sm.add_file("<eval>".to_string(), source.to_string());
+    let builtins = Builtins::with_prelude();
+    let tokens = Lexer::new(file_id, source)
+        .lex_all()
+        .map_err(into_diagnostics)?;
+    let is_program = tokens
+        .iter()
+        .find(|token| token.inner != TokenKind::Newline)
+        .is_some_and(|token| {
+            matches!(
+                token.inner,
+                TokenKind::Fn
+                    | TokenKind::Pub
+                    | TokenKind::Mod
+                    | TokenKind::Use
+                    | TokenKind::Trait
+                    | TokenKind::Impl
+                    | TokenKind::StaticAssert
+            )
+        });
+
+    if is_program {
+        let (tree, load_errors) = load_modules(&mut sm, file_id);
+        if !load_errors.is_empty() {
+            return Err(into_diagnostics(load_errors));
+        }
+        let Compilation {
+            tree,
+            resolutions,
+            types,
+            builtins,
+            ..
+        } = check(tree, builtins, "main")?;
+        return run(&builtins, &resolutions, &types, |interpreter| {
+            interpreter.run_modules(&tree)
+        });
+    }
+
+    let mut parser = Parser::new(file_id, tokens);
+    let stmts = parser.parse_stmts();
+    if !parser.errors().is_empty() {
+        return Err(into_diagnostics(parser.errors().to_vec()));
+    }
+    // the function of the statements has a name no statement can refer to
+    let end = Span::from_range(source.len()..source.len());
+    let body = Expr::new(
+        ExprKind::Block {
+            exprs: stmts,
+            tail: None,
+        },
+        end,
+    );
+    let func = Func {
+        is_pub: false,
+        name: Spanned::new(Symbol::intern("<eval>"), end),
+        generics: Vec::new(),
+        params: Vec::new(),
+        ty: None,
+        body,
+    };
+    let tree = ModuleTree::from_program(syntax::Program {
+        file_id,
+        items: vec![Item::new(ItemKind::Func(func), end)],
+    });
+    let Compilation {
+        tree,
+        resolutions,
+        types,
+        builtins,
+        ..
+    } = check(tree, builtins, "<eval>")?;
+    let program = &tree.module(tree.root()).program;
+    let Some(ItemKind::Func(func)) = program.items.first().map(|item| &item.inner) else {
+        unreachable!("the statements' function is the only item");
+    };
+    let ExprKind::Block { exprs: stmts, .. } = &func.body.inner else {
+        unreachable!("the statements' function has a block body");
+    };
+    run(&builtins, &resolutions, &types, |interpreter| {
+        interpreter.load(program);
+        let mut value = Value::Unit;
+        for stmt in stmts {
+            value = interpreter.eval_expr(file_id, stmt)?;
+        }
+        Ok(value)
+    })
+}
+
+/// Checks and runs `source` as `eval_str` does, converting its value to
+/// `T`.
+pub fn eval_str_as<T: FromValue>(source: &str) -> Result<T, Vec<Diagnostic>> {
+    let value = eval_str(source)?;
+    let found = value.type_name();
+    T::from_value(value).ok_or_else(|| {
+        vec![Diagnostic::error(format!(
+            "expected a value of type `{}`, found `{}`",
+            T::TYPE_NAME,
+            found
+        ))]
+    })
+}
+
+/// Rust types a ginto value converts to.
+pub trait FromValue: Sized {
+    /// The name of the ginto type converting to `Self`.
+    const TYPE_NAME: &'static str;
+
+    fn from_value(value: Value) -> Option<Self>;
+}
+
+impl FromValue for () {
+    const TYPE_NAME: &'static str = "()";
+
+    fn from_value(value: Value) -> Option<Self> {
+        matches!(value, Value::Unit).then_some(())
+    }
+}
+
+impl FromValue for bool {
+    const TYPE_NAME: &'static str = "bool";
+
+    fn from_value(value: Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl FromValue for u64 {
+    const TYPE_NAME: &'static str = "u64";
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::U64(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for i64 {
+    const TYPE_NAME: &'static str = "i64";
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::I64(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for f64 {
+    const TYPE_NAME: &'static str = "f64";
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::F64(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Arc<str> {
+    const TYPE_NAME: &'static str = "str";
+
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Str(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for String {
+    const TYPE_NAME: &'static str = "str";
+
+    fn from_value(value: Value) -> Option<Self> {
+        Arc::<str>::from_value(value).map(|v| v.to_string())
+    }
+}
+
+/// Checks `tree`, which runs from its function named `entry`, returning
+/// the program if it has no errors.
+fn check(
+    tree: ModuleTree,
+    builtins: Builtins,
+    entry: &str,
+) -> Result<Compilation, Vec<Diagnostic>> {
+    // warnings are the embedder's to ask for, so only denied lints are
+    // reported
+    let mut lints = LintStore::with_builtin_lints();
+    let levels = LintLevels::new();
+    let checked = Driver::new()
+        .with_builtins(builtins)
+        .with_lints(&mut lints, &levels)
+        .with_entry(entry)
+        .check_tree(tree);
+    let only_denied = checked.failed == Some(Stage::Lint);
+    match checked.into_compilation() {
+        (Some(compilation), _) => Ok(compilation),
+        (None, diagnostics) if only_denied => Err(diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .collect()),
+        (None, diagnostics) => Err(diagnostics),
+    }
+}
+
+/// Runs `run` with an interpreter, on a thread with the stack it needs, in
+/// the session of the calling thread.
+fn run<'a>(
+    builtins: &'a Builtins,
+    resolutions: &'a Resolutions,
+    types: &'a TypeckResults,
+    run: impl FnOnce(&mut Interpreter<'a>) -> Result<Value, RuntimeError> + Send,
+) -> Result<Value, Vec<Diagnostic>> {
+    let interner = Interner::current();
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(INTERPRETER_STACK_SIZE)
+            .spawn_scoped(scope, || {
+                let _session = interner.enter();
+                let mut interpreter = Interpreter::new(resolutions, types).with_builtins(builtins);
+                run(&mut interpreter)
+            })
+            .expect("failed to spawn the interpreter thread")
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+    .map_err(|err| vec![err.into_diagnostic()])
+}
+
+fn into_diagnostics<E: DiagnosticConvertible>(errors: Vec<E>) -> Vec<Diagnostic> {
+    errors
+        .into_iter()
+        .map(DiagnosticConvertible::into_diagnostic)
+        .collect()
+}
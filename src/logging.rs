@@ -0,0 +1,54 @@
+use tracing_subscriber::{
+    EnvFilter, Layer, filter::LevelFilter, fmt::format::FmtSpan, layer::SubscriberExt,
+    util::SubscriberInitExt,
+};
+
+use crate::timings::TimingsLayer;
+
+/// The variable holding a filter for what to log, such as `debug` or
+/// `ginto_syntax=trace,info`.
+pub const LOG_ENV: &str = "GINTO_LOG";
+
+/// Logs what the compiler does to stderr: the events `GINTO_LOG` lets
+/// through or, if it isn't set, those of the phases for a `verbosity` of 1,
+/// their recovery decisions too for 2 and everything for more. Each phase
+/// is logged with how long it took when it ends, in color if `color`.
+/// `timings`, if given, records every phase whatever is logged.
+pub fn init(verbosity: u8, color: bool, timings: Option<TimingsLayer>) {
+    let filter = log_filter(verbosity);
+    if filter.is_none() && timings.is_none() {
+        return;
+    }
+    let log = filter.map(|filter| {
+        tracing_subscriber::fmt::layer()
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(std::io::stderr)
+            .with_ansi(color)
+            .with_filter(filter)
+    });
+    tracing_subscriber::registry()
+        .with(log)
+        .with(timings)
+        .init();
+}
+
+fn log_filter(verbosity: u8) -> Option<EnvFilter> {
+    match std::env::var(LOG_ENV) {
+        Ok(filter) => match EnvFilter::try_new(&filter) {
+            Ok(filter) => Some(filter),
+            Err(err) => {
+                eprintln!("warning: ignoring `{LOG_ENV}={filter}`: {err}");
+                None
+            }
+        },
+        Err(_) => {
+            let level = match verbosity {
+                0 => return None,
+                1 => LevelFilter::INFO,
+                2 => LevelFilter::DEBUG,
+                _ => LevelFilter::TRACE,
+            };
+            Some(EnvFilter::default().add_directive(level.into()))
+        }
+    }
+}
@@ -1,35 +1,1211 @@
-use std::process::exit;
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::IsTerminal,
+    num::NonZeroUsize,
+    panic::AssertUnwindSafe,
+    path::{Path, PathBuf},
+    sync::{Arc, mpsc},
+    time::Duration,
+};
 
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum, error::ErrorKind};
+use emit::EmitKind;
+use ginto_analysis::eliminate_dead_code;
+use ginto_config::{CONFIG_FILE, Config, find_config, parse_config};
 use ginto_diag::{
-    DiagnosticConvertible, DiagnosticRenderer, PlainDiagnosticRenderer, SourceFile, SourceManager,
+    Applicability, ColorChoice, Diagnostic, DiagnosticConvertible, FileId, FileReadError, Interner,
+    RendererOptions, Severity, SourceManager, Suggestion, apply_edits, machine_applicable_edits,
 };
-use ginto_syntax::{Lexer, Parser};
+use ginto_driver::{Compilation, Driver};
+use ginto_eval::{Builtins, INTERPRETER_STACK_SIZE, Interpreter, RuntimeError, Tracer, Value};
+use ginto_fmt::FormatOptions;
+use ginto_lint::{LintLevels, LintStore};
+use ginto_resolve::{ModuleTree, Resolutions};
+use ginto_typeck::TypeckResults;
+use ginto_vm::{Bytecode, Compiler, Profile, Vm, optimize};
+use notify::{EventKind, RecursiveMode, Watcher};
+use rayon::prelude::*;
+use session::Session;
+use timings::TimingsFormat;
+use trace::EvalTrace;
+
+mod emit;
+mod explain;
+mod logging;
+mod repl;
+mod session;
+mod timings;
+mod trace;
+
+/// Compiles and runs ginto programs.
+#[derive(Parser)]
+#[command(
+    name = "ginto",
+    version,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    after_help = "Lint levels are set with `-A`, `-W` and `-D`, each followed by the name of a \
+                  lint or `warnings`."
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// The root files of the programs to run, each with the modules it
+    /// declares. `-` reads a program from standard input, as does giving no
+    /// files when it is not a terminal; otherwise giving none runs the entry
+    /// of the project's `ginto.toml`
+    files: Vec<PathBuf>,
+    #[command(flatten)]
+    options: Options,
+    /// Prints the explanation of an error or warning code such as `E0001`,
+    /// or every code for `list`
+    #[arg(long, value_name = "CODE", exclusive = true)]
+    explain: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Reads inputs from the terminal and runs them
+    Repl,
+    /// Compiles a program to a file of bytecode
+    Build {
+        /// The program's root file, by default the entry of the project's
+        /// `ginto.toml`
+        file: Option<PathBuf>,
+        /// The file to write, by default the program's file with the
+        /// extension `gbc`
+        #[arg(short)]
+        output: Option<PathBuf>,
+        /// Optimizes the bytecode
+        #[arg(short = 'O')]
+        optimize: bool,
+    },
+    /// Runs a program compiled to bytecode
+    Run { file: PathBuf },
+    /// Checks programs without running them, exiting with the number of
+    /// errors they have, at most 100, and 101 if any couldn't be checked
+    Check {
+        /// The programs' root files, by default the entry of the project's
+        /// `ginto.toml`
+        files: Vec<PathBuf>,
+        /// How to print diagnostics
+        #[arg(long, alias = "error-format", value_enum, default_value_t = MessageFormat::Human)]
+        message_format: MessageFormat,
+        /// Checks the programs again whenever one of their files changes
+        #[arg(long, conflicts_with = "fix")]
+        watch: bool,
+        #[command(flatten)]
+        fix: Fix,
+        #[command(flatten)]
+        jobs: Jobs,
+    },
+    /// Reports what the lints find in programs, at the levels set with
+    /// `-A`, `-W` and `-D`, checking only the names and types they need,
+    /// and exits like `check`
+    Lint {
+        /// The programs' root files, by default the entry of the project's
+        /// `ginto.toml`
+        files: Vec<PathBuf>,
+        /// How to print diagnostics
+        #[arg(long, alias = "error-format", value_enum, default_value_t = MessageFormat::Human)]
+        message_format: MessageFormat,
+        /// Lists the lints and the level each is at instead
+        #[arg(long, conflicts_with_all = ["files", "fix"])]
+        list: bool,
+        #[command(flatten)]
+        fix: Fix,
+        #[command(flatten)]
+        jobs: Jobs,
+    },
+    /// Formats source files in place, or prints standard input formatted
+    /// for `-`
+    Fmt {
+        /// The files to format, by default those in the source directories
+        /// of the project's `ginto.toml`
+        files: Vec<PathBuf>,
+        /// Rewrites nothing, listing the files that aren't formatted and
+        /// exiting with 1 if there are any
+        #[arg(long)]
+        check: bool,
+        /// Prints the changes formatting would make as a unified diff
+        /// instead of rewriting the files
+        #[arg(long)]
+        diff: bool,
+    },
+    /// Documents a program's modules and the signatures of their items, with
+    /// the comments above them
+    Doc {
+        /// The program's root file, by default the entry of the project's
+        /// `ginto.toml`
+        file: Option<PathBuf>,
+        /// The directory to write the pages to, by default `doc` in the
+        /// project's output directory or next to the program's file
+        #[arg(short)]
+        output: Option<PathBuf>,
+        /// What to write
+        #[arg(long, value_enum, default_value_t = DocFormat::Html)]
+        format: DocFormat,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DocFormat {
+    /// A page per module, linking to each other
+    Html,
+    /// The documentation as JSON on standard output, for other renderers
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MessageFormat {
+    /// Diagnostics with the source they point at, then a summary
+    Human,
+    /// A JSON object per diagnostic, one per line
+    Json,
+}
+
+#[derive(Args)]
+struct Fix {
+    /// Applies the fixes the diagnostics suggest for certain to the files,
+    /// then checks the programs again
+    #[arg(long)]
+    fix: bool,
+    /// Prints the changes `--fix` would make as a unified diff instead of
+    /// rewriting the files
+    #[arg(long, requires = "fix")]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+struct Jobs {
+    /// How many threads to check programs on, by default as many as there
+    /// are processors. The files of a program are lexed and parsed at once,
+    /// and its names and types are checked on one thread
+    #[arg(short = 'j', long = "jobs", value_name = "N")]
+    count: Option<NonZeroUsize>,
+}
+
+#[derive(Args)]
+struct Options {
+    /// Writes the output of the given stages instead of running the
+    /// program, each next to its file unless `-o` or `--out-dir` is given
+    #[arg(long, value_name = "KIND", value_enum, value_delimiter = ',')]
+    emit: Vec<EmitKind>,
+    /// The file to write the output of the only `--emit` stage to, or `-`
+    /// for standard output
+    #[arg(short, long, value_name = "FILE", requires = "emit")]
+    output: Option<PathBuf>,
+    /// The directory to write the output of the `--emit` stages to
+    #[arg(long, value_name = "DIR", requires = "emit", conflicts_with = "output")]
+    out_dir: Option<PathBuf>,
+    /// Optimizes the program and runs it as bytecode on the VM
+    #[arg(short = 'O')]
+    optimize: bool,
+    /// Runs the program on the VM and reports where it spent its time
+    #[arg(long)]
+    profile: bool,
+    /// Runs the program in the interpreter, tracing every expression it
+    /// evaluates or only those of the function FUNC
+    #[arg(
+        long,
+        value_name = "FUNC",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ""
+    )]
+    trace_eval: Option<String>,
+    /// Reports the code removed as unreachable and logs each phase of
+    /// compiling to stderr, with how the parser recovered from errors when
+    /// given twice. `GINTO_LOG` sets what to log instead, as a filter such as
+    /// `debug` or `ginto_syntax=trace`
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Reports the time, allocations and output of each phase of compiling
+    /// to stderr when done, as a table or JSON
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_enum,
+        global = true,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "table"
+    )]
+    timings: Option<TimingsFormat>,
+    /// When to color output: `auto` for terminals, unless `NO_COLOR` is
+    /// set or `CLICOLOR_FORCE` asks for color anyway, `always` or `never`
+    #[arg(long, value_name = "WHEN", global = true, default_value = "auto")]
+    color: ColorChoice,
+}
+
+/// The path standing for standard input, or standard output for `-o`.
+const STDIN_PATH: &str = "-";
 
 fn main() {
-    let mut sm = SourceManager::new();
-    let input = "23+1@".to_string();
-    let file = sm.add_file("main.ginto".to_string(), input.to_string());
-    let mut lexer = Lexer::new(file, &input);
-    let error_renderer = PlainDiagnosticRenderer;
-    let tokens = match lexer.lex_all() {
-        Ok(t) => t,
-        Err(errs) => {
-            for err in errs {
-                let r = error_renderer.render(&sm, err.into_diagnostic());
-                println!("{r}")
+    // lint levels are parsed first, as they may be attached to their flag,
+    // over those of the project
+    let mut lints = LintStore::with_builtin_lints();
+    let mut config_sm = SourceManager::new();
+    let (project, config_errors) = load_project(&mut config_sm, &lints);
+    let mut levels = project
+        .as_ref()
+        .map_or_else(LintLevels::new, |project| project.lint_levels.clone());
+    let (rest, level_errors) = levels.parse_args(&lints, std::env::args().skip(1));
+    let mut session = Session::new();
+    session.report(
+        &SourceManager::new(),
+        level_errors
+            .into_iter()
+            .map(DiagnosticConvertible::into_diagnostic),
+    );
+    let mut cli = Cli::parse_from(std::env::args().take(1).chain(rest));
+    if let Some(code) = &cli.explain {
+        exit(if explain::explain(code) { 0 } else { 1 })
+    }
+    let color = cli.options.color;
+    session.options = RendererOptions {
+        color: color.use_color(std::io::stderr().is_terminal()),
+    };
+    logging::init(
+        cli.options.verbose,
+        color.use_color(std::io::stderr().is_terminal()),
+        cli.options.timings.map(timings::layer),
+    );
+    if !config_errors.is_empty() {
+        session.report(&config_sm, config_errors);
+        exit(2)
+    }
+    let project = project.as_ref();
+    if let Some(project) = project
+        && cli.options.output.is_none()
+        && cli.options.out_dir.is_none()
+    {
+        cli.options.out_dir = project.out_dir.clone();
+    }
+    let mut emit = cli.options.emit.clone();
+    emit.sort_by_key(|kind| *kind as u8);
+    emit.dedup();
+    if cli.options.output.is_some() && (emit.len() > 1 || cli.files.len() > 1) {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "`-o` writes a single output, use `--out-dir` to emit several stages or files",
+            )
+            .exit();
+    }
+
+    let ok = match cli.command {
+        Some(Command::Repl) => {
+            // every input runs on the thread reading it
+            let options = session.options;
+            std::thread::Builder::new()
+                .stack_size(INTERPRETER_STACK_SIZE)
+                .spawn(move || repl::run(options))
+                .expect("failed to spawn the REPL thread")
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            true
+        }
+        Some(Command::Build {
+            file,
+            output,
+            optimize,
+        }) => {
+            let file = match file {
+                Some(file) => file,
+                None => project_or_exit(project).entry.clone(),
+            };
+            let output = output.unwrap_or_else(|| match project.and_then(|p| p.out_dir.as_ref()) {
+                Some(dir) => dir.join(output_path(&file, "gbc").file_name().unwrap_or_default()),
+                None => output_path(&file, "gbc"),
+            });
+            build(
+                &mut session,
+                &file,
+                &output,
+                optimize,
+                cli.options.verbose > 0,
+                &mut lints,
+                &levels,
+            )
+        }
+        Some(Command::Run { file }) => run_bytecode(&mut session, &file),
+        Some(Command::Doc {
+            file,
+            output,
+            format,
+        }) => {
+            let file = match file {
+                Some(file) => file,
+                None => project_or_exit(project).entry.clone(),
+            };
+            let output = output.unwrap_or_else(|| match project.and_then(|p| p.out_dir.as_ref()) {
+                Some(dir) => dir.join("doc"),
+                None => file.with_file_name("doc"),
+            });
+            doc(&mut session, &file, &output, format, &mut lints, &levels)
+        }
+        Some(Command::Check {
+            files,
+            message_format,
+            watch: true,
+            jobs,
+            ..
+        }) => {
+            session.json = message_format == MessageFormat::Json;
+            let files = files_or_entry(files, project);
+            exit(watch_files(&mut session, &files, jobs.count, &levels))
+        }
+        Some(Command::Check {
+            files,
+            message_format,
+            fix: Fix { fix: true, dry_run },
+            jobs,
+            ..
+        }) => {
+            session.json = message_format == MessageFormat::Json;
+            let files = files_or_entry(files, project);
+            exit(fix_files(
+                &mut session,
+                &files,
+                jobs.count,
+                &levels,
+                false,
+                dry_run,
+            ))
+        }
+        Some(Command::Check {
+            files,
+            message_format,
+            jobs,
+            ..
+        }) => {
+            session.json = message_format == MessageFormat::Json;
+            let files = files_or_entry(files, project);
+            exit(check_files(
+                &mut session,
+                &mut new_source_managers(&files),
+                &files,
+                jobs.count,
+                &levels,
+                false,
+            ))
+        }
+        Some(Command::Lint { list: true, .. }) => {
+            list_lints(&lints, &levels);
+            true
+        }
+        Some(Command::Lint {
+            files,
+            message_format,
+            fix: Fix { fix: true, dry_run },
+            jobs,
+            ..
+        }) => {
+            session.json = message_format == MessageFormat::Json;
+            let files = files_or_entry(files, project);
+            exit(fix_files(
+                &mut session,
+                &files,
+                jobs.count,
+                &levels,
+                true,
+                dry_run,
+            ))
+        }
+        Some(Command::Lint {
+            files,
+            message_format,
+            jobs,
+            ..
+        }) => {
+            session.json = message_format == MessageFormat::Json;
+            let files = files_or_entry(files, project);
+            exit(check_files(
+                &mut session,
+                &mut new_source_managers(&files),
+                &files,
+                jobs.count,
+                &levels,
+                true,
+            ))
+        }
+        Some(Command::Fmt { files, check, diff }) => {
+            let files = match files.is_empty() {
+                true => project_or_exit(project).source_files(),
+                false => files,
+            };
+            let options = project.map(|project| project.fmt).unwrap_or_default();
+            files
+                .iter()
+                .filter(|file| {
+                    !fmt_file(
+                        &mut session,
+                        &mut SourceManager::new(),
+                        file,
+                        &options,
+                        check,
+                        diff,
+                    )
+                })
+                .count()
+                == 0
+        }
+        None if cli.files.is_empty() && std::io::stdin().is_terminal() => match project {
+            Some(project) => run_file(
+                &mut session,
+                &project.entry,
+                &cli.options,
+                &mut lints,
+                &levels,
+            ),
+            None => Cli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "no files were given to run, standard input is a terminal and there is no \
+                     `ginto.toml`",
+                )
+                .exit(),
+        },
+        None if cli.files.is_empty() => run_file(
+            &mut session,
+            Path::new(STDIN_PATH),
+            &cli.options,
+            &mut lints,
+            &levels,
+        ),
+        // every file is a program of its own, run even if one before failed
+        None => {
+            cli.files
+                .iter()
+                .filter(|file| !run_file(&mut session, file, &cli.options, &mut lints, &levels))
+                .count()
+                == 0
+        }
+    };
+    exit(if ok { 0 } else { 1 })
+}
+
+/// Prints the `--timings` report, if it was asked for, and exits with
+/// `code`.
+fn exit(code: i32) -> ! {
+    timings::report();
+    std::process::exit(code)
+}
+
+/// Loads the `ginto.toml` of the project the current directory is in into
+/// `sm`, if there is one, with the problems found in it.
+fn load_project(sm: &mut SourceManager, lints: &LintStore) -> (Option<Config>, Vec<Diagnostic>) {
+    let Ok(cwd) = std::env::current_dir() else {
+        return (None, Vec::new());
+    };
+    let Some(path) = find_config(&cwd) else {
+        return (None, Vec::new());
+    };
+    // a project in the current directory has relative paths
+    let path = path.strip_prefix(&cwd).unwrap_or(&path);
+    let file = match sm.set_file_from_path(path) {
+        Ok(file) => file,
+        Err(err) => return (None, vec![err.into_diagnostic()]),
+    };
+    let (config, errors) = parse_config(sm, file, lints);
+    let errors = errors
+        .into_iter()
+        .map(DiagnosticConvertible::into_diagnostic)
+        .collect();
+    (Some(config), errors)
+}
+
+/// The project, or if there is none, exits saying the command needs files.
+fn project_or_exit(project: Option<&Config>) -> &Config {
+    project.unwrap_or_else(|| {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                format!("no files were given and there is no `{CONFIG_FILE}`"),
+            )
+            .exit()
+    })
+}
+
+/// `files`, or the entry of the project if there are none.
+fn files_or_entry(files: Vec<PathBuf>, project: Option<&Config>) -> Vec<PathBuf> {
+    if files.is_empty() {
+        vec![project_or_exit(project).entry.clone()]
+    } else {
+        files
+    }
+}
+
+/// Prints every lint in `lints` with the level `levels` put it at.
+fn list_lints(lints: &LintStore, levels: &LintLevels) {
+    let width = lints
+        .lints()
+        .iter()
+        .map(|lint| lint.name.len())
+        .max()
+        .unwrap_or(0);
+    for lint in lints.lints() {
+        let (level, _) = levels.level(lint);
+        println!(
+            "{:width$}  {}  {:5}  {}",
+            lint.name,
+            lint.code,
+            level.to_string(),
+            lint.description
+        );
+    }
+}
+
+/// Adds the root file of a program at `path` to `sm`, reporting it if it
+/// can't be read.
+fn add_root(session: &mut Session, sm: &mut SourceManager, path: &Path) -> Option<FileId> {
+    read_root(sm, path)
+        .map_err(|err| session.report(sm, [err.into_diagnostic()]))
+        .ok()
+}
+
+/// Adds the root file of a program at `path` to `sm`.
+fn read_root(sm: &mut SourceManager, path: &Path) -> Result<FileId, FileReadError> {
+    if path == Path::new(STDIN_PATH) {
+        sm.add_stdin()
+    } else {
+        sm.set_file_from_path(path)
+    }
+}
+
+/// Loads the program whose root file is `file` into `sm` and checks it,
+/// reporting its errors and warnings.
+fn check(
+    session: &mut Session,
+    sm: &mut SourceManager,
+    file: FileId,
+    lints: &mut LintStore,
+    levels: &LintLevels,
+) -> Option<Compilation> {
+    let (compilation, diagnostics) = Driver::new().with_lints(lints, levels).check(sm, file);
+    session.report_compilation(sm, diagnostics);
+    compilation
+}
+
+/// The path of the file with `extension` next to the program at `path`,
+/// which is `stdin` for a program read from standard input.
+fn output_path(path: &Path, extension: &str) -> PathBuf {
+    if path == Path::new(STDIN_PATH) {
+        Path::new("stdin").with_extension(extension)
+    } else {
+        path.with_extension(extension)
+    }
+}
+
+/// The stack of each thread checking programs, as much as the main thread
+/// usually has.
+const CHECK_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// The exit code of checking programs with this many errors or more.
+const MAX_ERRORS_EXIT_CODE: usize = 100;
+
+/// The exit code of checking programs when one couldn't be checked, as its
+/// root file couldn't be read or it crashed the compiler.
+const NOT_CHECKED_EXIT_CODE: i32 = 101;
+
+/// A source manager for each of the programs whose root files are at
+/// `paths`, for `check_files` to load them into.
+fn new_source_managers(paths: &[PathBuf]) -> Vec<SourceManager> {
+    paths.iter().map(|_| SourceManager::new()).collect()
+}
+
+/// Checks the programs whose root files are at `paths`, each loaded into
+/// the source manager at the same index of `sms`, on `jobs` threads. Their
+/// diagnostics are printed once all are checked, in the order of `paths`,
+/// followed by how many errors and warnings they have. With `lints_only`,
+/// only names and types are checked before the lints run. Returns the exit
+/// code: the number of errors, up to `MAX_ERRORS_EXIT_CODE`, or
+/// `NOT_CHECKED_EXIT_CODE` if a program couldn't be checked.
+fn check_files(
+    session: &mut Session,
+    sms: &mut [SourceManager],
+    paths: &[PathBuf],
+    jobs: Option<NonZeroUsize>,
+    levels: &LintLevels,
+    lints_only: bool,
+) -> i32 {
+    match check_programs(sms, paths, jobs, levels, lints_only) {
+        Ok(checked) => report(session, sms, checked),
+        Err(code) => code,
+    }
+}
+
+/// What checking a program found.
+enum Outcome {
+    Checked(Vec<Diagnostic>),
+    /// Its root file couldn't be read, as the error says.
+    Unreadable(Diagnostic),
+    /// It crashed the compiler, whose panic is printed already.
+    Crashed,
+}
+
+/// What checking each program found.
+type Checked = Vec<Outcome>;
+
+/// Checks the programs whose root files are at `paths` on `jobs` threads,
+/// each into its own of `sms`, without reporting anything. The files of a
+/// program are lexed and parsed on any of the threads, the rest of it is
+/// checked on one. Returns the exit code if the threads can't be started.
+fn check_programs(
+    sms: &mut [SourceManager],
+    paths: &[PathBuf],
+    jobs: Option<NonZeroUsize>,
+    levels: &LintLevels,
+    lints_only: bool,
+) -> Result<Checked, i32> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.map_or(0, NonZeroUsize::get))
+        .stack_size(CHECK_STACK_SIZE)
+        .build();
+    let pool = match pool {
+        Ok(pool) => pool,
+        Err(err) => {
+            eprintln!("error: cannot start threads to check programs on: {err}");
+            return Err(NOT_CHECKED_EXIT_CODE);
+        }
+    };
+    Ok(pool.install(|| {
+        sms.par_iter_mut()
+            .zip(paths)
+            .map(|(sm, path)| {
+                let file = match read_root(sm, path) {
+                    Ok(file) => file,
+                    Err(err) => return Outcome::Unreadable(err.into_diagnostic()),
+                };
+                // the panic has been printed by the hook
+                std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    // the threads outlive the program, whose names are
+                    // freed with its session
+                    let _session = Arc::new(Interner::new()).enter();
+                    let mut lints = LintStore::with_builtin_lints();
+                    let mut driver = Driver::new().with_lints(&mut lints, levels);
+                    if lints_only {
+                        driver = driver.lints_only();
+                    }
+                    let (_, diagnostics) = driver.check(sm, file);
+                    Outcome::Checked(diagnostics)
+                }))
+                .unwrap_or(Outcome::Crashed)
+            })
+            .collect()
+    }))
+}
+
+/// Prints the diagnostics of the checked programs and a summary, returning
+/// the exit code.
+fn report(session: &mut Session, sms: &[SourceManager], checked: Checked) -> i32 {
+    let mut failed = false;
+    for (sm, outcome) in sms.iter().zip(checked) {
+        match outcome {
+            Outcome::Checked(diagnostics) => session.report(sm, diagnostics),
+            Outcome::Unreadable(err) => {
+                failed = true;
+                session.report(sm, [err]);
             }
-            exit(1)
+            Outcome::Crashed => failed = true,
         }
+    }
+    let (summary, errors) = session.summary();
+    // JSON output is only diagnostics
+    if !session.json {
+        eprintln!("{summary}");
+    }
+    if failed {
+        NOT_CHECKED_EXIT_CODE
+    } else {
+        errors.min(MAX_ERRORS_EXIT_CODE) as i32
+    }
+}
+
+/// Checks the programs whose root files are at `paths` and applies the
+/// machine-applicable suggestions of their diagnostics, then checks them
+/// again to report what is left. With `dry_run`, the changes are printed
+/// as a diff and the diagnostics reported instead.
+fn fix_files(
+    session: &mut Session,
+    paths: &[PathBuf],
+    jobs: Option<NonZeroUsize>,
+    levels: &LintLevels,
+    lints_only: bool,
+    dry_run: bool,
+) -> i32 {
+    if !dry_run && paths.iter().any(|path| path == Path::new(STDIN_PATH)) {
+        eprintln!("error: standard input can't be fixed in place, try `--dry-run`");
+        return NOT_CHECKED_EXIT_CODE;
+    }
+    let mut sms = new_source_managers(paths);
+    let checked = match check_programs(&mut sms, paths, jobs, levels, lints_only) {
+        Ok(checked) => checked,
+        Err(code) => return code,
     };
-    let mut p = Parser::new(file, tokens);
-    let errs = p.errors();
-    if errs.is_empty() {
-        let expr = p.parse_expr().unwrap();
-        println!("{:#?}", expr);
+    // a module shared by several programs is fixed once
+    let mut fixed: BTreeMap<String, (String, String)> = BTreeMap::new();
+    for (sm, outcome) in sms.iter().zip(&checked) {
+        let Outcome::Checked(diagnostics) = outcome else {
+            continue;
+        };
+        let sources = apply_fixes(sm, diagnostics, &fixed);
+        fixed.extend(sources);
+    }
+    if dry_run {
+        for (name, (source, fixed)) in &fixed {
+            let diff = ginto_fmt::unified_diff(name, source, fixed);
+            if session.options.color {
+                print!("{}", color_diff(&diff));
+            } else {
+                print!("{diff}");
+            }
+        }
+        return report(session, &sms, checked);
+    }
+    // the files are written once every fix applied
+    if let Err(code) = write_sources(fixed.iter().map(|(name, (_, source))| (name, source))) {
+        return code;
+    }
+    let mut fixed_sms = new_source_managers(paths);
+    let rechecked = match check_programs(&mut fixed_sms, paths, jobs, levels, lints_only) {
+        Ok(rechecked) => rechecked,
+        Err(code) => return code,
+    };
+    if errors(&rechecked) > errors(&checked) {
+        eprintln!("error: the fixes would introduce errors, so the files were left as they were");
+        if let Err(code) = write_sources(fixed.iter().map(|(name, (source, _))| (name, source))) {
+            return code;
+        }
+        return report(session, &sms, checked);
+    }
+    report(session, &fixed_sms, rechecked)
+}
+
+/// The files of `sm` that the machine-applicable suggestions of
+/// `diagnostics` change, by name, with their source before and after. A
+/// suggestion is applied whole or not at all: one overlapping an earlier
+/// one is left for the next run, and one that changes a file of `done` or
+/// whose edits don't apply to one of its files is left out.
+fn apply_fixes(
+    sm: &SourceManager,
+    diagnostics: &[Diagnostic],
+    done: &BTreeMap<String, (String, String)>,
+) -> BTreeMap<String, (String, String)> {
+    let mut taken: Vec<&Suggestion> = Vec::new();
+    let mut sources = BTreeMap::new();
+    let suggestions = diagnostics
+        .iter()
+        .flat_map(|diagnostic| &diagnostic.suggestions)
+        .filter(|suggestion| suggestion.applicability == Applicability::MachineApplicable);
+    for suggestion in suggestions {
+        let with: Vec<_> = taken.iter().copied().chain([suggestion]).collect();
+        let files = machine_applicable_edits(with.iter().copied());
+        let edits = with.iter().map(|suggestion| suggestion.edits.len()).sum();
+        if files.values().map(Vec::len).sum::<usize>() != edits {
+            continue;
+        }
+        let applied: Result<BTreeMap<_, _>, Option<String>> = files
+            .iter()
+            .map(|(&file_id, edits)| {
+                let file = sm.get_file(file_id).ok_or(None)?;
+                if done.contains_key(&file.name) {
+                    return Err(None);
+                }
+                let source = apply_edits(&file.source, edits)
+                    .map_err(|err| Some(format!("cannot fix `{}`: {err}", file.name)))?;
+                Ok((file.name.clone(), (file.source.clone(), source)))
+            })
+            .collect();
+        match applied {
+            Ok(applied) => {
+                taken.push(suggestion);
+                sources = applied;
+            }
+            Err(Some(err)) => eprintln!("error: {err}"),
+            Err(None) => {}
+        }
+    }
+    sources
+}
+
+/// Writes each of `files`, a name and a source, returning the exit code if
+/// one can't be written.
+fn write_sources<'a>(files: impl IntoIterator<Item = (&'a String, &'a String)>) -> Result<(), i32> {
+    for (name, source) in files {
+        if let Err(err) = std::fs::write(name, source) {
+            eprintln!("error: cannot write `{name}`: {err}");
+            return Err(NOT_CHECKED_EXIT_CODE);
+        }
+    }
+    Ok(())
+}
+
+/// How many errors checking the programs found, counting each that
+/// couldn't be checked as one.
+fn errors(checked: &Checked) -> usize {
+    checked
+        .iter()
+        .map(|outcome| match outcome {
+            Outcome::Checked(diagnostics) => diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.severity == Severity::Error)
+                .count(),
+            Outcome::Unreadable(_) | Outcome::Crashed => 1,
+        })
+        .sum()
+}
+
+/// Checks the programs whose root files are at `paths` again whenever a
+/// file in the directories of their files changes, until interrupted.
+/// Returns the exit code if the files can't be watched.
+fn watch_files(
+    session: &mut Session,
+    paths: &[PathBuf],
+    jobs: Option<NonZeroUsize>,
+    levels: &LintLevels,
+) -> i32 {
+    if paths.iter().any(|path| path == Path::new(STDIN_PATH)) {
+        eprintln!("error: standard input can't be watched");
+        return NOT_CHECKED_EXIT_CODE;
+    }
+    let (sender, events) = mpsc::channel();
+    let mut watcher = match notify::recommended_watcher(sender) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            eprintln!("error: cannot watch files: {err}");
+            return NOT_CHECKED_EXIT_CODE;
+        }
+    };
+    // the files are kept between checks and updated in place
+    let mut sms = new_source_managers(paths);
+    let mut watched = HashSet::new();
+    loop {
+        // clears the screen and moves the cursor to its top left
+        print!("\x1b[2J\x1b[H");
+        session.clear();
+        check_files(session, &mut sms, paths, jobs, levels, false);
+
+        // directories are watched rather than files, so that a module
+        // declared before its file is written is loaded once it is
+        let files = paths.iter().cloned().chain(
+            sms.iter()
+                .flat_map(SourceManager::files)
+                .map(|(_, file)| PathBuf::from(&file.name)),
+        );
+        for file in files {
+            let dir = match file.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            if dir.is_dir()
+                && watched.insert(dir.clone())
+                && let Err(err) = watcher.watch(&dir, RecursiveMode::NonRecursive)
+            {
+                eprintln!("error: cannot watch `{}`: {err}", dir.display());
+            }
+        }
+
+        loop {
+            match events.recv() {
+                Ok(Ok(event)) if is_source_change(&event) => break,
+                Ok(Ok(_)) => {}
+                Ok(Err(err)) => eprintln!("error: {err}"),
+                Err(_) => return NOT_CHECKED_EXIT_CODE,
+            }
+        }
+        // editors often save a file in several steps, so the rest of a
+        // burst of events is dropped
+        while events.recv_timeout(Duration::from_millis(50)).is_ok() {}
+    }
+}
+
+/// Whether `event` changed a source file.
+fn is_source_change(event: &notify::Event) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event
+            .paths
+            .iter()
+            .any(|path| path.extension().is_some_and(|ext| ext == "ginto"))
+}
+
+/// Formats the file at `path`, rewriting it if it changed, or prints
+/// standard input formatted. With `check`, a file that changes is listed
+/// and fails; with `diff`, the changes are printed instead. Returns whether
+/// it succeeded.
+fn fmt_file(
+    session: &mut Session,
+    sm: &mut SourceManager,
+    path: &Path,
+    options: &FormatOptions,
+    check: bool,
+    diff: bool,
+) -> bool {
+    let Some(file) = add_root(session, sm, path) else {
+        return false;
+    };
+    let source_file = sm.get_file(file).expect("the file was just added");
+    let source = &source_file.source;
+    let formatted = match ginto_fmt::format_source_with(file, source, options) {
+        Ok(formatted) => formatted,
+        Err(errors) => {
+            session.report(sm, errors);
+            return false;
+        }
+    };
+    if check || diff {
+        if formatted == *source {
+            return true;
+        }
+        if diff {
+            let diff = ginto_fmt::unified_diff(&source_file.name, source, &formatted);
+            if session.options.color {
+                print!("{}", color_diff(&diff));
+            } else {
+                print!("{diff}");
+            }
+        } else {
+            println!("{}", path.display());
+        }
+        return !check;
+    }
+    if path == Path::new(STDIN_PATH) {
+        print!("{formatted}");
+        return true;
+    }
+    if formatted == *source {
+        return true;
+    }
+    match std::fs::write(path, formatted) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("error: cannot write `{}`: {err}", path.display());
+            false
+        }
+    }
+}
+
+/// Checks the program at `path` and runs it, or writes it out with
+/// `--emit`. Returns whether it succeeded.
+fn run_file(
+    session: &mut Session,
+    path: &Path,
+    options: &Options,
+    lints: &mut LintStore,
+    levels: &LintLevels,
+) -> bool {
+    let mut sm = SourceManager::new();
+    let Some(file) = add_root(session, &mut sm, path) else {
+        return false;
+    };
+    if !options.emit.is_empty() {
+        return emit::emit(session, &mut sm, file, path, options, lints, levels);
+    }
+    let Some(Compilation {
+        mut tree,
+        resolutions,
+        types,
+        builtins,
+        ..
+    }) = check(session, &mut sm, file, lints, levels)
+    else {
+        return false;
+    };
+    // optimized and profiled programs run as bytecode on the VM, unless
+    // traced
+    let trace = options
+        .trace_eval
+        .as_deref()
+        .map(|func| (!func.is_empty()).then_some(func));
+    if options.optimize {
+        let stats = eliminate_dead_code(&mut tree, &resolutions);
+        if options.verbose > 0 {
+            eprintln!("note: {stats}");
+        }
+    }
+    let result = if trace.is_none() && (options.optimize || options.profile) {
+        let mut bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+        if options.optimize {
+            optimize(&mut bytecode);
+        }
+        let mut profile = Profile::new(&bytecode);
+        let mut vm = Vm::new(&bytecode).with_builtins(&builtins);
+        if options.profile {
+            vm = vm.with_profile(&mut profile);
+        }
+        let result = vm.run();
+        if options.profile {
+            eprint!("{}", profile.report(&bytecode, &sm));
+        }
+        result
     } else {
-        for err in errs {
-            let r = error_renderer.render(&sm, err.clone().into_diagnostic());
-            println!("{r}");
+        let mut tracer = trace.map(|func| EvalTrace::new(&sm, func));
+        run_interpreter(
+            &tree,
+            &resolutions,
+            &types,
+            &builtins,
+            tracer.as_mut().map(|tracer| tracer as _),
+        )
+    };
+    match result {
+        Ok(value) => {
+            println!("{value}");
+            true
+        }
+        Err(err) => {
+            session.report(&sm, [err.into_diagnostic()]);
+            false
+        }
+    }
+}
+
+fn run_interpreter(
+    tree: &ModuleTree,
+    resolutions: &Resolutions,
+    types: &TypeckResults,
+    builtins: &Builtins,
+    tracer: Option<&mut (dyn Tracer + Send)>,
+) -> Result<Value, RuntimeError> {
+    // every call in the program is several nested calls in the interpreter
+    let interner = Interner::current();
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(INTERPRETER_STACK_SIZE)
+            .spawn_scoped(scope, || {
+                let _session = interner.enter();
+                let mut interpreter = Interpreter::new(resolutions, types).with_builtins(builtins);
+                if let Some(tracer) = tracer {
+                    interpreter = interpreter.with_tracer(tracer);
+                }
+                interpreter.run_modules(tree)
+            })
+            .expect("failed to spawn the interpreter thread")
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// `diff` with removed lines in red, added lines in green and the headers
+/// of files and hunks in bold and cyan.
+fn color_diff(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let color = match line.as_bytes() {
+                [b'-', b'-', b'-', ..] | [b'+', b'+', b'+', ..] => "\x1b[1m",
+                [b'@', ..] => "\x1b[36m",
+                [b'-', ..] => "\x1b[31m",
+                [b'+', ..] => "\x1b[32m",
+                _ => return format!("{line}\n"),
+            };
+            format!("{color}{line}\x1b[0m\n")
+        })
+        .collect()
+}
+
+/// Checks the program at `path` and compiles it to bytecode in the file at
+/// `output`. Returns whether it succeeded.
+fn build(
+    session: &mut Session,
+    path: &Path,
+    output: &Path,
+    optimized: bool,
+    verbose: bool,
+    lints: &mut LintStore,
+    levels: &LintLevels,
+) -> bool {
+    let mut sm = SourceManager::new();
+    let Some(Compilation {
+        mut tree,
+        resolutions,
+        types,
+        ..
+    }) = add_root(session, &mut sm, path)
+        .and_then(|file| check(session, &mut sm, file, lints, levels))
+    else {
+        return false;
+    };
+    let stats = eliminate_dead_code(&mut tree, &resolutions);
+    if verbose {
+        eprintln!("note: {stats}");
+    }
+    let mut bytecode = Compiler::new(&resolutions, &types).compile_modules(&tree);
+    if optimized {
+        optimize(&mut bytecode);
+    }
+    match std::fs::write(output, bytecode.save(&sm)) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("error: cannot write `{}`: {err}", output.display());
+            false
+        }
+    }
+}
+
+/// Documents the program at `path`, writing its pages to the directory
+/// `output` or printing it as JSON. Returns whether it succeeded.
+fn doc(
+    session: &mut Session,
+    path: &Path,
+    output: &Path,
+    format: DocFormat,
+    lints: &mut LintStore,
+    levels: &LintLevels,
+) -> bool {
+    let mut sm = SourceManager::new();
+    let Some(Compilation {
+        tree, resolutions, ..
+    }) = add_root(session, &mut sm, path)
+        .and_then(|file| check(session, &mut sm, file, lints, levels))
+    else {
+        return false;
+    };
+    let docs = ginto_doc::document(&sm, &tree, &resolutions);
+    if format == DocFormat::Json {
+        print!("{}", ginto_doc::render_json(&docs));
+        return true;
+    }
+    if let Err(err) = std::fs::create_dir_all(output) {
+        eprintln!("error: cannot create `{}`: {err}", output.display());
+        return false;
+    }
+    for (name, page) in ginto_doc::render_html(&docs) {
+        let path = output.join(name);
+        if let Err(err) = std::fs::write(&path, page) {
+            eprintln!("error: cannot write `{}`: {err}", path.display());
+            return false;
+        }
+    }
+    true
+}
+
+/// Runs the program compiled to bytecode in the file at `path`. Returns
+/// whether it succeeded.
+fn run_bytecode(session: &mut Session, path: &Path) -> bool {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            eprintln!("error: cannot read `{}`: {err}", path.display());
+            return false;
+        }
+    };
+    let (bytecode, sm) = match Bytecode::load(&bytes) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            session.report(&SourceManager::new(), [err.into_diagnostic()]);
+            return false;
+        }
+    };
+    let builtins = Builtins::with_prelude();
+    match Vm::new(&bytecode).with_builtins(&builtins).run() {
+        Ok(value) => {
+            println!("{value}");
+            true
+        }
+        Err(err) => {
+            session.report(&sm, [err.into_diagnostic()]);
+            false
         }
     }
 }
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use ginto_diag::{
+    BytePos, Diagnostic, DiagnosticConvertible, FileId, RendererOptions, Severity, SourceManager,
+    Span, Spanned, Symbol,
+};
+use ginto_driver::{Compilation, Driver, Stage};
+use ginto_eval::{Builtins, Interpreter, Value};
+use ginto_lint::{LintLevels, LintStore};
+use ginto_resolve::ModuleTree;
+use ginto_syntax::{Expr, ExprKind, Func, Item, ItemKind, Lexer, Parser, Program, TokenKind};
+use rustyline::{DefaultEditor, error::ReadlineError};
+
+/// Reads inputs from the terminal and runs them until end of input,
+/// styling diagnostics with `options`.
+pub fn run(options: RendererOptions) {
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(err) => {
+            eprintln!("error: cannot start the REPL: {err}");
+            return;
+        }
+    };
+    let mut repl = Repl::new(options);
+    let mut input = String::new();
+    loop {
+        let prompt = if input.is_empty() { ">> " } else { ".. " };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => {
+                input.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                eprintln!("error: {err}");
+                break;
+            }
+        };
+        if input.is_empty() && line.trim().is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line.as_str());
+        input.push_str(&line);
+        input.push('\n');
+        // an input that opens an indented block goes on until an empty line
+        let opens_block = input
+            .lines()
+            .next()
+            .is_some_and(|first| first.trim_end().ends_with(':'));
+        if opens_block && !line.trim().is_empty() {
+            continue;
+        }
+        repl.eval(std::mem::take(&mut input));
+    }
+}
+
+/// An input the session accepted.
+struct Entry {
+    /// The `<repl-N>` file holding the input.
+    file_id: FileId,
+    /// Where the input starts in the session.
+    offset: usize,
+    len: usize,
+    parsed: Parsed,
+}
+
+#[derive(Clone)]
+enum Parsed {
+    Items(Vec<Item>),
+    Stmts(Vec<Expr>),
+}
+
+/// An interactive session.
+///
+/// Every input is kept in a `<repl-N>` file for rendering, and placed one
+/// after another in the session, which is checked as a single program: the
+/// items of all inputs, and a function whose body is the statements of all
+/// inputs. Each input is only run once, with the variables bound by earlier
+/// inputs set to the values they had afterwards.
+struct Repl {
+    sm: SourceManager,
+    /// The file the session's spans refer to. It has no source of its own;
+    /// diagnostics are rendered against the `<repl-N>` files.
+    session: FileId,
+    entries: Vec<Entry>,
+    /// The values of the variables bound at the top level of accepted
+    /// inputs, by the span of their name.
+    values: HashMap<Span, Value>,
+    /// How many inputs were read, accepted or not.
+    inputs: usize,
+    builtins: Builtins,
+    lints: LintStore,
+    levels: LintLevels,
+    options: RendererOptions,
+}
+
+impl Repl {
+    fn new(options: RendererOptions) -> Self {
+        let mut sm = SourceManager::new();
+        let session = sm.add_file("<repl>".to_string(), String::new());
+        Self {
+            sm,
+            session,
+            entries: Vec::new(),
+            values: HashMap::new(),
+            inputs: 0,
+            builtins: Builtins::with_prelude(),
+            lints: LintStore::with_builtin_lints(),
+            levels: LintLevels::new(),
+            options,
+        }
+    }
+
+    /// Runs `source`, printing its value or its errors, and keeps it in the
+    /// session if it succeeds.
+    fn eval(&mut self, source: String) {
+        self.inputs += 1;
+        let file_id = self
+            .sm
+            .add_file(format!("<repl-{}>", self.inputs), source.clone());
+        let offset = self
+            .entries
+            .last()
+            .map_or(0, |entry| entry.offset + entry.len + 1);
+        let mut entry = Entry {
+            file_id,
+            offset,
+            len: source.chars().count(),
+            parsed: Parsed::Stmts(Vec::new()),
+        };
+        match self.parse(file_id, &source, offset) {
+            Ok(parsed) => entry.parsed = parsed,
+            Err(errors) => return self.report(errors, &entry),
+        }
+        match self.check_and_run(&entry) {
+            Ok(value) => {
+                if let Some(value) = value.filter(|value| *value != Value::Unit) {
+                    println!("{value}");
+                }
+                self.entries.push(entry);
+            }
+            Err(errors) => self.report(errors, &entry),
+        }
+    }
+
+    /// Parses the input in `file_id`, which starts at `offset` in the
+    /// session, as items if it starts with one and as statements otherwise.
+    fn parse(
+        &self,
+        file_id: FileId,
+        source: &str,
+        offset: usize,
+    ) -> Result<Parsed, Vec<Diagnostic>> {
+        let mut tokens = Lexer::new(file_id, source)
+            .lex_all()
+            .map_err(into_diagnostics)?;
+        for token in &mut tokens {
+            token.span = shift(token.span, offset);
+        }
+        let is_items = tokens
+            .iter()
+            .find(|token| token.inner != TokenKind::Newline)
+            .is_some_and(|token| {
+                matches!(
+                    token.inner,
+                    TokenKind::Fn
+                        | TokenKind::Pub
+                        | TokenKind::Mod
+                        | TokenKind::Use
+                        | TokenKind::Trait
+                        | TokenKind::Impl
+                        | TokenKind::StaticAssert
+                )
+            });
+        let mut parser = Parser::new(self.session, tokens);
+        let parsed = if is_items {
+            Parsed::Items(parser.parse_program().items)
+        } else {
+            Parsed::Stmts(parser.parse_stmts())
+        };
+        if !parser.errors().is_empty() {
+            return Err(into_diagnostics(parser.errors().to_vec()));
+        }
+        Ok(parsed)
+    }
+
+    /// Checks the session with `entry` added, then runs `entry`. Returns the
+    /// value of its last statement, if it has statements.
+    fn check_and_run(&mut self, entry: &Entry) -> Result<Option<Value>, Vec<Diagnostic>> {
+        let mut items = Vec::new();
+        let mut stmts = Vec::new();
+        for parsed in self
+            .entries
+            .iter()
+            .chain([entry])
+            .map(|entry| &entry.parsed)
+        {
+            match parsed {
+                Parsed::Items(parsed) => items.extend(parsed.iter().cloned()),
+                Parsed::Stmts(parsed) => stmts.extend(parsed.iter().cloned()),
+            }
+        }
+        let new_stmts = match &entry.parsed {
+            Parsed::Items(_) => 0,
+            Parsed::Stmts(stmts) => stmts.len(),
+        };
+        // the function of the statements has a name no input can refer to
+        let end = Span::from_range(entry.offset + entry.len..entry.offset + entry.len);
+        let body = Expr::new(
+            ExprKind::Block {
+                exprs: stmts,
+                tail: None,
+            },
+            end,
+        );
+        items.push(Item::new(
+            ItemKind::Func(Func {
+                is_pub: false,
+                name: Spanned::new(Symbol::intern("<repl>"), end),
+                generics: Vec::new(),
+                params: Vec::new(),
+                ty: None,
+                body,
+            }),
+            end,
+        ));
+        let tree = ModuleTree::from_program(Program {
+            file_id: self.session,
+            items,
+        });
+
+        let mut checked = Driver::new()
+            .with_builtins(std::mem::take(&mut self.builtins))
+            .with_lints(&mut self.lints, &self.levels)
+            .with_entry("<repl>")
+            .check_tree(tree);
+        if let Some(stage) = checked.failed {
+            self.builtins = std::mem::take(&mut checked.builtins);
+            // warnings like unused variables are noise in a session, so
+            // only denied lints are reported
+            return Err(checked
+                .diagnostics
+                .into_iter()
+                .filter(|diagnostic| stage != Stage::Lint || diagnostic.severity == Severity::Error)
+                .collect());
+        }
+        let (Some(compilation), _) = checked.into_compilation() else {
+            unreachable!("the session's program has no errors");
+        };
+        let Compilation {
+            tree,
+            resolutions,
+            types,
+            builtins,
+            ..
+        } = compilation;
+        self.builtins = builtins;
+        let program = &tree.module(tree.root()).program;
+        let Some(ItemKind::Func(func)) = program.items.last().map(|item| &item.inner) else {
+            unreachable!("the statements' function is the last item");
+        };
+        let ExprKind::Block { exprs: stmts, .. } = &func.body.inner else {
+            unreachable!("the statements' function has a block body");
+        };
+        let mut interpreter = Interpreter::new(&resolutions, &types).with_builtins(&self.builtins);
+        interpreter.load(program);
+        for (&span, value) in &self.values {
+            if let Some(def) = resolutions.def_at(self.session, span) {
+                interpreter.set_local(def, value.clone());
+            }
+        }
+        let mut value = None;
+        for stmt in &stmts[stmts.len() - new_stmts..] {
+            match interpreter.eval_expr(self.session, stmt) {
+                Ok(v) => value = Some(v),
+                Err(err) => return Err(vec![err.into_diagnostic()]),
+            }
+        }
+        for stmt in stmts {
+            if let ExprKind::Let { name, .. } = &stmt.inner
+                && let Some(value) = resolutions
+                    .def_at(self.session, name.span)
+                    .and_then(|def| interpreter.local(def))
+            {
+                self.values.insert(name.span, value);
+            }
+        }
+        // a `let` or an assignment has no value to show
+        match stmts.last().map(|stmt| &stmt.inner) {
+            Some(ExprKind::Let { .. } | ExprKind::Assign { .. }) => Ok(None),
+            _ => Ok(value),
+        }
+    }
+
+    /// Renders `errors` against the `<repl-N>` files of the inputs they
+    /// point into.
+    fn report(&self, errors: Vec<Diagnostic>, pending: &Entry) {
+        let renderer = self.options.renderer();
+        for mut diagnostic in errors {
+            diagnostic.labels.retain_mut(|label| {
+                if label.file_id != self.session {
+                    return true;
+                }
+                let start = label.span.start.0;
+                let Some(entry) = self
+                    .entries
+                    .iter()
+                    .chain([pending])
+                    .find(|entry| (entry.offset..=entry.offset + entry.len).contains(&start))
+                else {
+                    return false;
+                };
+                label.file_id = entry.file_id;
+                label.span = Span::new(
+                    BytePos(start - entry.offset),
+                    BytePos(label.span.end.0.min(entry.offset + entry.len) - entry.offset),
+                );
+                true
+            });
+            println!("{}", renderer.render(&self.sm, diagnostic));
+        }
+    }
+}
+
+fn shift(span: Span, offset: usize) -> Span {
+    Span::from_range(span.start.0 + offset..span.end.0 + offset)
+}
+
+fn into_diagnostics<E: DiagnosticConvertible>(errors: Vec<E>) -> Vec<Diagnostic> {
+    errors
+        .into_iter()
+        .map(DiagnosticConvertible::into_diagnostic)
+        .collect()
+}
@@ -0,0 +1,64 @@
+use ginto_diag::{
+    Diagnostic, DiagnosticRenderer, Emitter, JsonDiagnosticRenderer, RendererOptions, SourceManager,
+};
+
+/// How this run of `ginto` reports diagnostics, and what it has reported.
+/// Diagnostics are printed to stderr.
+#[derive(Debug, Default)]
+pub struct Session {
+    emitter: Emitter,
+    /// How diagnostics are styled, for `--color`.
+    pub options: RendererOptions,
+    /// Whether diagnostics are printed as JSON, for `--message-format json`.
+    pub json: bool,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn renderer(&self) -> &'static dyn DiagnosticRenderer {
+        if self.json {
+            &JsonDiagnosticRenderer
+        } else {
+            self.options.renderer()
+        }
+    }
+
+    /// Prints `diagnostics`, which point into `sm`, sorted and each once.
+    pub fn report(
+        &mut self,
+        sm: &SourceManager,
+        diagnostics: impl IntoIterator<Item = Diagnostic>,
+    ) {
+        let renderer = self.renderer();
+        self.emitter.extend(diagnostics);
+        eprint!("{}", self.emitter.flush(sm, renderer));
+    }
+
+    /// Prints the diagnostics of compiling the program in `sm` as `report`
+    /// does, ending with how many errors it has if it has any.
+    pub fn report_compilation(
+        &mut self,
+        sm: &SourceManager,
+        diagnostics: impl IntoIterator<Item = Diagnostic>,
+    ) {
+        self.emitter.clear();
+        self.report(sm, diagnostics);
+        if let Some(aborting) = self.emitter.aborting() {
+            eprintln!("{}", self.renderer().render(sm, aborting));
+        }
+    }
+
+    /// The line ending the report of checking programs, like `1 error, 2
+    /// warnings`, and how many errors they have.
+    pub fn summary(&self) -> (String, usize) {
+        (self.emitter.summary(), self.emitter.errors())
+    }
+
+    /// Forgets what was reported, for checking programs again.
+    pub fn clear(&mut self) {
+        self.emitter.clear();
+    }
+}
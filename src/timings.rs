@@ -0,0 +1,286 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fmt::{self, Write},
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+    span,
+};
+use tracing_subscriber::{Layer, layer::Context, registry::LookupSpan};
+
+/// The phases `--timings` reports on, by the name of their span, in the
+/// order a program goes through them.
+const PHASES: [&str; 9] = [
+    "lex",
+    "parse",
+    "resolve",
+    "typecheck",
+    "instances",
+    "analysis",
+    "lint",
+    "codegen",
+    "optimize",
+];
+
+/// The fields of the events of a phase that count what it produced.
+const OUTPUTS: [&str; 3] = ["tokens", "items", "instructions"];
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TimingsFormat {
+    /// A table with a row per phase
+    Table,
+    /// A JSON object with an entry per phase
+    Json,
+}
+
+/// What a phase took, summed over every time it ran.
+#[derive(Debug, Clone, Default)]
+struct Phase {
+    runs: u64,
+    time: Duration,
+    allocations: u64,
+    allocated: u64,
+    /// What the phase produced and how much of it, such as `tokens`.
+    output: Option<(&'static str, u64)>,
+}
+
+type Phases = Arc<Mutex<[Phase; PHASES.len()]>>;
+
+/// The phases recorded and how to report them, once `--timings` is given.
+static REPORT: OnceLock<(TimingsFormat, Phases)> = OnceLock::new();
+
+/// Records the phases of compiling from their spans, for the report
+/// `report` prints.
+pub struct TimingsLayer {
+    phases: Phases,
+}
+
+/// A layer recording phases for a report in `format`, counting
+/// allocations from now on.
+pub fn layer(format: TimingsFormat) -> TimingsLayer {
+    let phases = Phases::default();
+    COUNTING.store(true, Ordering::Relaxed);
+    REPORT
+        .set((format, phases.clone()))
+        .unwrap_or_else(|_| panic!("the timings layer is made once"));
+    TimingsLayer { phases }
+}
+
+/// When a phase's span was entered, and the allocations of its thread by
+/// then.
+struct Entered {
+    at: Instant,
+    allocations: (u64, u64),
+}
+
+impl<S> Layer<S> for TimingsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && phase_index(span.name()).is_some()
+        {
+            span.extensions_mut().insert(Entered {
+                at: Instant::now(),
+                allocations: thread_allocations(),
+            });
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let Some(index) = phase_index(span.name()) else {
+            return;
+        };
+        let Some(entered) = span.extensions_mut().remove::<Entered>() else {
+            return;
+        };
+        let (allocations, allocated) = thread_allocations();
+        let mut phases = self.phases.lock().unwrap_or_else(|err| err.into_inner());
+        let phase = &mut phases[index];
+        phase.runs += 1;
+        phase.time += entered.at.elapsed();
+        phase.allocations += allocations - entered.allocations.0;
+        phase.allocated += allocated - entered.allocations.1;
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(index) = ctx
+            .event_span(event)
+            .and_then(|span| phase_index(span.name()))
+        else {
+            return;
+        };
+        let mut output = OutputVisitor(None);
+        event.record(&mut output);
+        if let Some((unit, count)) = output.0 {
+            let mut phases = self.phases.lock().unwrap_or_else(|err| err.into_inner());
+            let total = phases[index].output.map_or(0, |(_, total)| total);
+            phases[index].output = Some((unit, total + count));
+        }
+    }
+}
+
+fn phase_index(name: &str) -> Option<usize> {
+    PHASES.iter().position(|phase| *phase == name)
+}
+
+struct OutputVisitor(Option<(&'static str, u64)>);
+
+impl Visit for OutputVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if OUTPUTS.contains(&field.name()) {
+            self.0 = Some((field.name(), value));
+        }
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn fmt::Debug) {}
+}
+
+/// Prints what each phase that ran took to stderr, if `--timings` was
+/// given. Phases that ran for several files or programs are summed, so on
+/// several threads their time can add up to more than the time it took.
+pub fn report() {
+    let Some((format, phases)) = REPORT.get() else {
+        return;
+    };
+    let phases = phases.lock().unwrap_or_else(|err| err.into_inner());
+    let ran = PHASES
+        .iter()
+        .zip(phases.iter())
+        .filter(|(_, phase)| phase.runs > 0);
+    let report = match format {
+        TimingsFormat::Table => table(ran),
+        TimingsFormat::Json => json(ran),
+    };
+    eprint!("{report}");
+}
+
+fn table<'a>(phases: impl Iterator<Item = (&'a &'a str, &'a Phase)>) -> String {
+    let mut out = format!(
+        "{:<10} {:>10} {:>12} {:>11}  output\n",
+        "phase", "time", "allocations", "allocated"
+    );
+    let mut total = Phase::default();
+    for (name, phase) in phases {
+        let output = phase
+            .output
+            .map(|(unit, count)| format!("{count} {unit}"))
+            .unwrap_or_default();
+        row(&mut out, name, phase, &output);
+        total.time += phase.time;
+        total.allocations += phase.allocations;
+        total.allocated += phase.allocated;
+    }
+    row(&mut out, "total", &total, "");
+    out
+}
+
+fn row(out: &mut String, name: &str, phase: &Phase, output: &str) {
+    let row = format!(
+        "{name:<10} {:>8.3}ms {:>12} {:>11}  {output}",
+        phase.time.as_secs_f64() * 1000.0,
+        phase.allocations,
+        bytes(phase.allocated),
+    );
+    out.push_str(row.trim_end());
+    out.push('\n');
+}
+
+/// `count` bytes in the largest unit that keeps them at least one.
+fn bytes(count: u64) -> String {
+    match count {
+        0..1024 => format!("{count} B"),
+        1024..1_048_576 => format!("{:.1} KiB", count as f64 / 1024.0),
+        _ => format!("{:.1} MiB", count as f64 / 1_048_576.0),
+    }
+}
+
+fn json<'a>(phases: impl Iterator<Item = (&'a &'a str, &'a Phase)>) -> String {
+    let mut out = String::from("{\"phases\":[");
+    for (i, (name, phase)) in phases.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write!(
+            out,
+            "{{\"phase\":\"{name}\",\"runs\":{},\"time_ms\":{:.3},\"allocations\":{},\
+             \"allocated_bytes\":{},\"output\":",
+            phase.runs,
+            phase.time.as_secs_f64() * 1000.0,
+            phase.allocations,
+            phase.allocated,
+        )
+        .expect("writing to a string can't fail");
+        match phase.output {
+            Some((unit, count)) => write!(out, "{{\"{unit}\":{count}}}}}"),
+            None => write!(out, "null}}"),
+        }
+        .expect("writing to a string can't fail");
+    }
+    out.push_str("]}\n");
+    out
+}
+
+/// Whether allocations are counted, which only `--timings` needs.
+static COUNTING: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// How many allocations this thread made and how many bytes they took,
+    /// since counting began.
+    static ALLOCATIONS: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+}
+
+fn thread_allocations() -> (u64, u64) {
+    ALLOCATIONS.try_with(Cell::get).unwrap_or_default()
+}
+
+fn count_allocation(size: usize) {
+    if COUNTING.load(Ordering::Relaxed) {
+        // threads being torn down no longer count
+        let _ = ALLOCATIONS.try_with(|allocations| {
+            let (count, bytes) = allocations.get();
+            allocations.set((count + 1, bytes + size as u64));
+        });
+    }
+}
+
+/// The system allocator, counting the allocations of each thread for
+/// `--timings`.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation(layout.size());
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation(new_size);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
@@ -0,0 +1,63 @@
+use ginto_diag::SourceManager;
+use ginto_eval::{Outcome, TraceEvent, Tracer, Value};
+
+/// Prints each expression the interpreter evaluates to stderr, indented by
+/// how deeply it is nested, with its inputs and what it came to.
+pub struct EvalTrace<'a> {
+    sm: &'a SourceManager,
+    /// The function to trace the expressions of, or every function.
+    func: Option<&'a str>,
+}
+
+impl<'a> EvalTrace<'a> {
+    pub fn new(sm: &'a SourceManager, func: Option<&'a str>) -> Self {
+        Self { sm, func }
+    }
+}
+
+impl Tracer for EvalTrace<'_> {
+    fn on_eval(&mut self, event: &TraceEvent) {
+        if self.func.is_some_and(|func| func != event.func()) {
+            return;
+        }
+        let Some(file) = self.sm.get_file(event.file_id()) else {
+            return;
+        };
+        let line_col = file.line_col(event.span().start);
+        // only the first line of expressions spanning several is shown
+        let span = event.span();
+        let source = file
+            .source
+            .get(span.start.0..span.end.0.min(file.source.len()))
+            .unwrap_or_default();
+        let mut lines = source.lines();
+        let mut text = lines.next().unwrap_or_default().trim().to_string();
+        if lines.next().is_some() {
+            text.push_str(" ...");
+        }
+        let inputs = match event.inputs() {
+            [] => String::new(),
+            inputs => {
+                let inputs: Vec<_> = inputs.iter().map(Value::to_string).collect();
+                format!(" <- {}", inputs.join(", "))
+            }
+        };
+        let outcome = match event.outcome() {
+            Outcome::Value(value) => format!("= {value}"),
+            Outcome::Returned(value) => format!("returns {value}"),
+            Outcome::TailCall => "calls in tail position".to_string(),
+            Outcome::Failed(_) => "fails".to_string(),
+        };
+        eprintln!(
+            "{:indent$}{}:{}:{} `{}`{} {}",
+            "",
+            file.name,
+            line_col.line,
+            line_col.col,
+            text,
+            inputs,
+            outcome,
+            indent = event.depth() * 2
+        );
+    }
+}
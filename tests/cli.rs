@@ -0,0 +1,569 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn ginto(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ginto"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn runs_programs_read_from_stdin() {
+    let source = "fn main() -> u64:\n    6 * 7\n";
+    for args in [&["-"][..], &[]] {
+        let output = ginto(args, source);
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "42\n");
+    }
+}
+
+#[test]
+fn names_stdin_in_diagnostics() {
+    let output = ginto(&["-"], "fn main() -> u64:\n    missing\n");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--> <stdin>:2:5"));
+}
+
+#[test]
+fn aborts_programs_with_errors_after_reporting_them() {
+    let output = ginto(&["-"], "fn main() -> u64:\n    missing + other\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("cannot find").count(), 2, "{stderr}");
+    assert!(
+        stderr.contains("error : aborting due to 2 previous errors\n"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn runs_programs_calling_through_module_paths() {
+    let dir = std::env::temp_dir().join(format!("ginto-paths-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("math")).unwrap();
+    std::fs::write(
+        dir.join("main.ginto"),
+        "mod math\n\nfn main() -> u64:\n    math::double(math::util::add(1, 2)) + crate::seven()\n\nfn seven() -> u64:\n    7\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("math.ginto"),
+        "pub mod util\n\npub fn double(n: u64) -> u64:\n    util::add(n, n)\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("math/util.ginto"),
+        "pub fn add(a: u64, b: u64) -> u64:\n    a + b\n",
+    )
+    .unwrap();
+    let main = dir.join("main.ginto");
+    let bytecode = dir.join("main.gbc");
+    let output = ginto(&[main.to_str().unwrap()], "");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "13\n");
+    let output = ginto(
+        &[
+            "build",
+            main.to_str().unwrap(),
+            "-o",
+            bytecode.to_str().unwrap(),
+        ],
+        "",
+    );
+    assert!(output.status.success());
+    let output = ginto(&["run", bytecode.to_str().unwrap()], "");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "13\n");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn emits_stages_to_an_out_dir() {
+    let dir = std::env::temp_dir().join(format!("ginto-emit-{}", std::process::id()));
+    let out_dir = dir.to_str().unwrap();
+    let source = "fn main() -> u64:\n    (6 * 7)\n";
+    let output = ginto(
+        &[
+            "--emit=tokens,pretty",
+            "--emit=bytecode",
+            "--out-dir",
+            out_dir,
+        ],
+        source,
+    );
+    assert!(output.status.success());
+    let pretty = std::fs::read_to_string(dir.join("stdin.pretty.ginto")).unwrap();
+    assert_eq!(pretty, "fn main() -> u64:\n    6 * 7\n");
+    let tokens = std::fs::read_to_string(dir.join("stdin.tokens")).unwrap();
+    assert!(tokens.starts_with("1:1 Fn\n1:4 Ident(\"main\")\n"));
+    assert!(dir.join("stdin.bytecode").exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn emits_a_single_stage_to_stdout() {
+    let output = ginto(&["--emit=ast-json", "-o", "-"], "fn main():\n    ()\n");
+    assert!(output.status.success());
+    let json = String::from_utf8_lossy(&output.stdout);
+    assert!(json.contains("\"kind\": \"fn\""));
+    assert!(json.contains("\"kind\": \"unit\""));
+}
+
+#[test]
+fn check_exits_with_what_it_found() {
+    let output = ginto(&["check", "-"], "fn main():\n    let unused = 1\n");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).ends_with("0 errors, 1 warning\n"));
+
+    let output = ginto(&["check", "-"], "fn main() -> u64:\n    2 +\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).ends_with("1 error, 0 warnings\n"));
+
+    let output = ginto(&["check", "-"], "fn main() -> u64:\n    a + b\n");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).ends_with("2 errors, 0 warnings\n"));
+
+    // a program that can't be read isn't checked
+    let output = ginto(&["check", "does/not/exist.ginto"], "");
+    assert_eq!(output.status.code(), Some(101));
+    let output = ginto(
+        &["check", "-", "does/not/exist.ginto"],
+        "fn main() -> u64:\n    a + b\n",
+    );
+    assert_eq!(output.status.code(), Some(101));
+    assert!(String::from_utf8_lossy(&output.stderr).ends_with("3 errors, 0 warnings\n"));
+
+    let source: String = (0..120)
+        .map(|i| format!("fn g{i}() -> u64:\n    true\n"))
+        .collect();
+    let output = ginto(&["check", "-"], &source);
+    assert_eq!(output.status.code(), Some(100));
+    assert!(String::from_utf8_lossy(&output.stderr).ends_with("120 errors, 0 warnings\n"));
+}
+
+#[test]
+fn check_prints_programs_checked_at_once_in_order() {
+    let dir = std::env::temp_dir().join(format!("ginto-jobs-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let paths: Vec<_> = (0..8)
+        .map(|i| {
+            let path = dir.join(format!("p{i}.ginto"));
+            std::fs::write(&path, format!("fn main() -> u64:\n    missing{i}\n")).unwrap();
+            path.to_str().unwrap().to_string()
+        })
+        .collect();
+    let mut args = vec!["check", "-j", "4"];
+    args.extend(paths.iter().map(String::as_str));
+    let output = ginto(&args, "");
+    assert_eq!(output.status.code(), Some(8));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let found: Vec<_> = (0..8)
+        .map(|i| stderr.find(&format!("`missing{i}`")).unwrap())
+        .collect();
+    assert!(found.is_sorted(), "{stderr}");
+    assert!(stderr.ends_with("8 errors, 0 warnings\n"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn fmt_rewrites_files_in_place() {
+    let path = std::env::temp_dir().join(format!("ginto-fmt-{}.ginto", std::process::id()));
+    std::fs::write(&path, "fn main()->u64:\n  6*(7)\n").unwrap();
+    let output = ginto(&["fmt", path.to_str().unwrap()], "");
+    assert!(output.status.success());
+    let formatted = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(formatted, "fn main() -> u64:\n    6 * 7\n");
+    std::fs::remove_file(path).unwrap();
+
+    let output = ginto(&["fmt", "-"], "fn main():\n  ()\n");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "fn main():\n    ()\n"
+    );
+}
+
+#[test]
+fn fmt_checks_and_diffs_without_rewriting() {
+    let output = ginto(&["fmt", "--check", "-"], "fn main():\n    ()\n");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let output = ginto(&["fmt", "--check", "-"], "fn main():\n  ()\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "-\n");
+
+    let output = ginto(&["fmt", "--diff", "-"], "fn main():\n  ()\n");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "--- <stdin>\n+++ <stdin>\n@@ -1,2 +1,2 @@\n fn main():\n-  ()\n+    ()\n"
+    );
+}
+
+#[test]
+fn fixes_what_the_diagnostics_suggest() {
+    let path = std::env::temp_dir().join(format!("ginto-fix-{}.ginto", std::process::id()));
+    let source = "pub fn f(unused: u64, y: bool) -> bool:\n    (y || false) && true\n";
+    std::fs::write(&path, source).unwrap();
+    let path_arg = path.to_str().unwrap();
+
+    let output = ginto(&["check", "--fix", "--dry-run", path_arg], "");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("+pub fn f(_unused: u64, y: bool) -> bool:\n+    (y)\n"),
+        "{stdout}"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.ends_with("0 errors, 3 warnings\n"), "{stderr}");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), source);
+
+    let output = ginto(&["lint", "--fix", path_arg], "");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "0 errors, 0 warnings\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "pub fn f(_unused: u64, y: bool) -> bool:\n    (y)\n"
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn fixes_every_file_a_suggestion_changes() {
+    let dir = std::env::temp_dir().join(format!("ginto-fix-files-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let main = dir.join("main.ginto");
+    let util = dir.join("util.ginto");
+    std::fs::write(
+        &main,
+        "mod util\n\nfn main() -> u64:\n    util::helperFn(2) + crate::util::helperFn(3)\n",
+    )
+    .unwrap();
+    std::fs::write(&util, "pub fn helperFn(x: u64) -> u64:\n    x\n").unwrap();
+
+    let output = ginto(&["lint", "--fix", main.to_str().unwrap()], "");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert_eq!(stderr, "0 errors, 0 warnings\n");
+    assert_eq!(
+        std::fs::read_to_string(&main).unwrap(),
+        "mod util\n\nfn main() -> u64:\n    util::helper_fn(2) + crate::util::helper_fn(3)\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&util).unwrap(),
+        "pub fn helper_fn(x: u64) -> u64:\n    x\n"
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn documents_programs_as_html_or_json() {
+    let dir = std::env::temp_dir().join(format!("ginto-doc-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("main.ginto");
+    std::fs::write(
+        &path,
+        "fn main() -> u64:\n    one()\n\nfn one() -> u64:\n    1\n",
+    )
+    .unwrap();
+    let path_arg = path.to_str().unwrap();
+
+    let output = ginto(&["doc", path_arg], "");
+    assert!(output.status.success());
+    let index = std::fs::read_to_string(dir.join("doc/index.html")).unwrap();
+    assert!(index.contains("<a href=\"index.html#fn.one\">"), "{index}");
+
+    let output = ginto(&["doc", "--format", "json", path_arg], "");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"signature\":\"fn one() -> u64\""));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn lint_prints_json_at_the_levels_given() {
+    let source = "fn main():\n    let unused = 1\n";
+    let output = ginto(&["lint", "--message-format", "json", "-"], source);
+    assert_eq!(output.status.code(), Some(0));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.lines().count(), 1);
+    assert!(stderr.starts_with("{\"$message_type\":\"diagnostic\","));
+    assert!(stderr.contains("\"code\":{\"code\":\"W0004\","));
+    assert!(stderr.contains("\"level\":\"warning\""));
+    assert!(stderr.contains("\"line_start\":2,\"line_end\":2,\"column_start\":9,"));
+
+    let output = ginto(&["-D", "unused-variables", "lint", "-"], source);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).ends_with("1 error, 0 warnings\n"));
+
+    let output = ginto(&["-A", "unused_variables", "lint", "--list"], "");
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("unused_variables         W0004  allow")
+    );
+}
+
+#[test]
+fn lint_only_runs_the_lints() {
+    let source = "fn main() -> u64:\n    let x: u64\n    let unused = 1\n    x\n";
+    let output = ginto(&["check", "-"], source);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("`x` used here but it isn't initialized")
+    );
+
+    let output = ginto(&["lint", "-"], source);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).ends_with("0 errors, 1 warning\n"));
+}
+
+#[test]
+fn check_prints_json_with_the_rustc_flag() {
+    let output = ginto(
+        &["check", "--error-format=json", "-"],
+        "fn main() -> u64:\n    true\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.lines().count(), 1);
+    assert!(
+        stderr.starts_with("{\"$message_type\":\"diagnostic\",\"message\":\"mismatched types\",")
+    );
+    assert!(stderr.contains("\"level\":\"error\""));
+}
+
+#[test]
+fn explains_error_codes() {
+    let output = ginto(&["--explain", "E0005"], "");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("A string literal isn't closed before the end of its line.\n"));
+    // code blocks are indented, without their fences
+    assert!(stdout.contains("\n    fn main():\n        print(\"hello)\n"));
+    assert!(!stdout.contains("```"));
+
+    let output = ginto(&["--explain", "list"], "");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("E0001  A character that can't start any token was found.\n"));
+    assert!(stdout.contains("\nW0004  A parameter or local variable is never used.\n"));
+
+    let output = ginto(&["--explain", "E9999"], "");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn uses_the_ginto_toml_of_the_current_project() {
+    let dir = std::env::temp_dir().join(format!("ginto-project-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(
+        dir.join("ginto.toml"),
+        "[project]\nentry = \"src/app.ginto\"\nsource-dirs = [\"src\"]\n\n\
+         [lints]\nunused_variables = \"deny\"\n\n[fmt]\nindent-width = 2\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("src/app.ginto"),
+        "fn main():\n    let unused = 1\n",
+    )
+    .unwrap();
+    let ginto_in = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_ginto"))
+            .args(args)
+            .current_dir(dir.join("src"))
+            .output()
+            .unwrap()
+    };
+
+    let output = ginto_in(&["check"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("set to `deny` in `[lints]` of `ginto.toml`"),
+        "{stderr}"
+    );
+    // the command line overrides the project
+    let output = ginto_in(&["-A", "unused_variables", "check"]);
+    assert!(output.status.success());
+
+    let output = ginto_in(&["fmt", "--diff"]);
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .ends_with("-    let unused = 1\n+  let unused = 1\n")
+    );
+
+    std::fs::write(dir.join("ginto.toml"), "[fmt]\nindent-width = \"2\"\n").unwrap();
+    let output = ginto_in(&["check"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("invalid value for `indent-width`"),
+        "{stderr}"
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn logs_phases_and_recovery_when_asked() {
+    let source = "fn main() -> u64:\n    1 + )\nfn f() -> u64:\n    1\n";
+    let output = ginto(&["check", "-"], source);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("check{root=<stdin>}"), "{stderr}");
+
+    let output = ginto(&["check", "-v", "-"], source);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("check{root=<stdin>}:load:"), "{stderr}");
+    assert!(!stderr.contains("recovered"), "{stderr}");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ginto"))
+        .args(["check", "-"])
+        .env("GINTO_LOG", "ginto_syntax=debug")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&child.wait_with_output().unwrap().stderr).into_owned();
+    assert!(
+        stderr.contains("recovered from a syntax error skipped=1 at=newline"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("INFO"), "{stderr}");
+}
+
+#[test]
+fn reports_the_timings_of_each_phase() {
+    let source = "fn main() -> u64:\n    6 * 7\n";
+    let output = ginto(&["check", "--timings", "-"], source);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let (summary, timings) = stderr.split_once('\n').unwrap();
+    assert_eq!(summary, "0 errors, 0 warnings");
+    let phases: Vec<_> = timings
+        .lines()
+        .map(|line| line.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(
+        phases,
+        [
+            "phase",
+            "lex",
+            "parse",
+            "resolve",
+            "typecheck",
+            "instances",
+            "analysis",
+            "lint",
+            "total"
+        ],
+        "{stderr}"
+    );
+    assert!(timings.lines().nth(1).unwrap().ends_with(" 15 tokens"));
+
+    let output = ginto(&["-O", "--timings=json", "-"], source);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "42\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("{\"phases\":[{\"phase\":\"lex\",\"runs\":1,"));
+    assert!(stderr.contains("\"output\":{\"tokens\":15}}"));
+    assert!(stderr.contains("{\"phase\":\"codegen\",\"runs\":1,"));
+    assert!(stderr.ends_with("\"output\":{\"instructions\":2}}]}\n"));
+}
+
+#[test]
+fn colors_output_when_asked_or_forced() {
+    let source = "fn main() -> u64:\n    missing\n";
+    let output = ginto(&["check", "-"], source);
+    assert!(!output.stderr.contains(&b'\x1b'));
+    let output = ginto(&["check", "--color=always", "-"], source);
+    assert!(output.stderr.starts_with(b"\x1b[1;31merror"));
+    let output = ginto(
+        &["fmt", "--diff", "--color=always", "-"],
+        "fn main():\n  ()\n",
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\x1b[32m+    ()\x1b[0m\n"));
+
+    let check_with = |vars: &[(&str, &str)], args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ginto"))
+            .args(args)
+            .envs(vars.iter().copied())
+            .output()
+            .unwrap();
+        output.stderr.contains(&b'\x1b')
+    };
+    let path = std::env::temp_dir().join(format!("ginto-color-{}.ginto", std::process::id()));
+    std::fs::write(&path, source).unwrap();
+    let path = path.to_str().unwrap();
+    assert!(check_with(&[("CLICOLOR_FORCE", "1")], &["check", path]));
+    assert!(!check_with(&[("CLICOLOR_FORCE", "0")], &["check", path]));
+    assert!(!check_with(
+        &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")],
+        &["check", path]
+    ));
+    assert!(!check_with(
+        &[("CLICOLOR_FORCE", "1")],
+        &["check", "--color=never", path]
+    ));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn repl_keeps_bindings_and_items_across_inputs() {
+    let input = "let x = 6
+x * 7
+let mut n = 1
+n = n + x
+n
+fn double(n: u64) -> u64:
+    n * 2
+
+double(x)
+if x > 1:
+    \"big\"
+else:
+    \"small\"
+
+";
+    let output = ginto(&["repl"], input);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "42\n7\n12\nbig\n");
+}
+
+#[test]
+fn repl_reports_errors_against_each_input() {
+    let input = "let d = 0
+let z = 1 / d
+z
+let y = missing
+y
+d + 1
+";
+    let output = ginto(&["repl"], input);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(" --> <repl-2>:1:9\n   1 | let z = 1 / d\n     |         ^^^^^ attempt to compute `1 / 0`\n"),
+        "{stdout}"
+    );
+    assert!(stdout.contains(" --> <repl-4>:1:9\n"), "{stdout}");
+    // inputs with errors bind nothing
+    assert!(stdout.contains(" --> <repl-3>:1:1\n"), "{stdout}");
+    assert!(stdout.contains(" --> <repl-5>:1:1\n"), "{stdout}");
+    assert_eq!(stdout.matches("cannot find").count(), 3, "{stdout}");
+    assert!(stdout.ends_with("\n1\n"), "{stdout}");
+}
@@ -0,0 +1,85 @@
+//! Tools compile programs through `Compiler` and inspect what it found.
+
+use std::fs;
+
+use ginto::{Compiler, TokenKind, Ty, Value, syntax::ItemKind};
+
+#[test]
+fn compiles_and_runs_sources() {
+    let mut compiler = Compiler::new();
+    let result = compiler.compile_source(
+        "main.ginto",
+        "fn main() -> u64:\n    let unused = 1\n    double(21)\n\nfn double(n: u64) -> u64:\n    n * 2\n",
+    );
+    // warnings come with the program
+    assert_eq!(result.diagnostics.len(), 1);
+    assert!(
+        compiler
+            .render(&result.diagnostics[0])
+            .contains("--> main.ginto:2:9")
+    );
+    let program = result.program.unwrap();
+    assert_eq!(program.run(), Ok(Value::U64(42)));
+
+    let root = program.root();
+    let tokens = program.tokens(root).unwrap();
+    assert_eq!(tokens[0].inner, TokenKind::Fn);
+    let ast = program.ast(root).unwrap();
+    let _session = program.enter();
+    let names: Vec<_> = ast
+        .items
+        .iter()
+        .map(|item| match &item.inner {
+            ItemKind::Func(func) => func.name.inner.to_string(),
+            _ => String::new(),
+        })
+        .collect();
+    assert_eq!(names, ["main", "double"]);
+    let ItemKind::Func(double) = &ast.items[1].inner else {
+        unreachable!()
+    };
+    assert_eq!(program.type_at(root, double.body.span), Some(&Ty::U64));
+}
+
+#[test]
+fn reports_the_errors_of_programs() {
+    let mut compiler = Compiler::new();
+    let result = compiler.compile_source("bad.ginto", "fn main() -> u64:\n    missing\n");
+    assert!(result.program.is_none());
+    assert_eq!(result.diagnostics.len(), 1);
+    assert!(
+        compiler
+            .render(&result.diagnostics[0])
+            .contains("--> bad.ginto:2:5")
+    );
+
+    let result = compiler.compile_files("no/such/file.ginto");
+    assert!(result.program.is_none());
+    assert_eq!(result.diagnostics.len(), 1);
+}
+
+#[test]
+fn compiles_programs_across_files() {
+    let dir = std::env::temp_dir().join(format!("ginto-compiler-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("main.ginto"),
+        "mod math\nuse math::double\n\nfn main() -> u64:\n    double(21)\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("math.ginto"),
+        "pub fn double(n: u64) -> u64:\n    n * 2\n",
+    )
+    .unwrap();
+    let mut compiler = Compiler::new();
+    let result = compiler.compile_files(dir.join("main.ginto"));
+    let program = result.program.unwrap();
+    assert_eq!(program.files().count(), 2);
+    for file in program.files() {
+        assert!(program.tokens(file).is_some());
+        assert!(program.ast(file).is_some());
+    }
+    assert_eq!(program.run(), Ok(Value::U64(42)));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,53 @@
+//! Source strings evaluate to values through the whole pipeline.
+
+use ginto::{Value, eval_str, eval_str_as};
+
+#[test]
+fn evaluates_statements() {
+    assert_eq!(eval_str("let x = 6\nx * 7"), Ok(Value::U64(42)));
+    assert_eq!(eval_str_as::<bool>("1 < 2"), Ok(true));
+    assert_eq!(eval_str_as::<()>("let x = 1"), Ok(()));
+    assert_eq!(eval_str_as::<f64>("7 as f64 / 2.0"), Ok(3.5));
+}
+
+#[test]
+fn evaluates_programs() {
+    let source = "fn main() -> str:
+    let n = double(21)
+    \"{n}\"
+
+fn double(n: u64) -> u64:
+    n * 2
+";
+    assert_eq!(eval_str_as::<String>(source), Ok("42".to_string()));
+}
+
+#[test]
+fn reports_errors_as_diagnostics() {
+    let errors = eval_str("missing + 1").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    let errors = eval_str("1 / 0").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    let errors = eval_str_as::<i64>("true").unwrap_err();
+    assert_eq!(
+        errors[0].message,
+        "expected a value of type `i64`, found `bool`"
+    );
+}
+
+#[test]
+fn evaluates_sources_with_multibyte_characters() {
+    let source = "let s = \"héllo → wörld\"\ns";
+    assert_eq!(
+        eval_str_as::<String>(source),
+        Ok("héllo → wörld".to_string())
+    );
+
+    let source = "let s = \"日本語\"\ns + 1";
+    let errors = eval_str(source).unwrap_err();
+    for label in errors.iter().flat_map(|error| &error.labels) {
+        let (start, end) = (label.span.start.0, label.span.end.0);
+        assert!(end <= source.len(), "{start}..{end}");
+        assert!(source.is_char_boundary(start) && source.is_char_boundary(end));
+    }
+}
@@ -0,0 +1,112 @@
+//! Runs `ginto` on every `.ginto` file under `tests/ui` and compares what
+//! it prints to the `.expected` file next to it. The directory a file is in
+//! says what is done with it:
+//!
+//! - `run`: the program is run
+//! - `check`: the program is checked, for its diagnostics
+//! - `ast`: its syntax tree is emitted with `--emit=ast`
+//!
+//! Run with `cargo test --test ui -- --bless`, or with `BLESS=1`, to
+//! rewrite the `.expected` files. Other arguments filter the files by path.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode},
+};
+
+use ginto_fmt::unified_diff;
+
+const MODES: &[(&str, &[&str])] = &[
+    ("run", &[]),
+    ("check", &["check"]),
+    ("ast", &["--emit=ast", "-o", "-"]),
+];
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let bless = args.iter().any(|arg| arg == "--bless") || std::env::var_os("BLESS").is_some();
+    let filters: Vec<&String> = args.iter().filter(|arg| !arg.starts_with('-')).collect();
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/ui");
+
+    let mut failed = Vec::new();
+    let mut passed = 0;
+    for &(mode, args) in MODES {
+        for path in fixtures(&root.join(mode)) {
+            let name = path.strip_prefix(&root).unwrap().display().to_string();
+            if !filters.is_empty() && !filters.iter().any(|filter| name.contains(filter.as_str())) {
+                continue;
+            }
+            let actual = ginto(&root, args, &name);
+            let expected_path = path.with_extension("expected");
+            if bless {
+                fs::write(&expected_path, &actual).unwrap();
+                println!("test {name} ... blessed");
+                passed += 1;
+                continue;
+            }
+            let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+            if expected == actual {
+                println!("test {name} ... ok");
+                passed += 1;
+            } else {
+                println!("test {name} ... FAILED");
+                let expected_name = expected_path.strip_prefix(&root).unwrap().display();
+                failed.push((
+                    name,
+                    unified_diff(&expected_name.to_string(), &expected, &actual),
+                ));
+            }
+        }
+    }
+
+    for (name, diff) in &failed {
+        println!("\n---- {name} ----\n{diff}");
+    }
+    println!(
+        "\ntest result: {}. {passed} passed; {} failed",
+        if failed.is_empty() { "ok" } else { "FAILED" },
+        failed.len()
+    );
+    if failed.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        println!("rerun with `cargo test --test ui -- --bless` to accept the output");
+        ExitCode::FAILURE
+    }
+}
+
+/// The `.ginto` files in `dir`, in order.
+fn fixtures(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ginto"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// What `ginto` prints for the file `name`, run from `root` with `args`:
+/// its standard output, then its standard error, then its exit status
+/// unless it succeeded.
+fn ginto(root: &Path, args: &[&str], name: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ginto"))
+        .args(args)
+        .arg(name)
+        .current_dir(root)
+        .output()
+        .unwrap();
+    let mut out = String::from_utf8_lossy(&output.stdout).into_owned();
+    out.push_str(&String::from_utf8_lossy(&output.stderr));
+    if !output.status.success() {
+        let code = output
+            .status
+            .code()
+            .map_or("none".to_string(), |code| code.to_string());
+        out.push_str(&format!("exit status: {code}\n"));
+    }
+    out
+}